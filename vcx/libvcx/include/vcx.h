@@ -9,6 +9,7 @@ typedef unsigned int vcx_error_t;
 typedef unsigned int vcx_schema_handle_t;
 typedef unsigned int vcx_credentialdef_handle_t;
 typedef unsigned int vcx_issuer_credential_handle_t;
+typedef unsigned int vcx_issuer_credential_batch_handle_t;
 typedef unsigned int vcx_disclosed_proof_handle_t;
 typedef unsigned int vcx_connection_handle_t;
 typedef unsigned int vcx_credential_handle_t;
@@ -16,6 +17,7 @@ typedef unsigned int vcx_proof_handle_t;
 typedef unsigned int vcx_command_handle_t;
 typedef unsigned int vcx_payment_handle_t;
 typedef unsigned int vcx_wallet_search_handle_t;
+typedef unsigned int vcx_context_handle_t;
 typedef unsigned int vcx_bool_t;
 typedef unsigned int count_t;
 typedef unsigned long vcx_price_t;
@@ -37,8 +39,32 @@ typedef enum
   undefined = 0,
   validated = 1,
   invalid = 2,
+  tampered = 3,
 } vcx_proof_state_t;
 
+typedef enum
+{
+  built = 0,
+  published = 1,
+} vcx_public_entity_state_t;
+
+typedef enum
+{
+  invite_json = 0,
+  invite_abbreviated = 1,
+  invite_url = 2,
+  invite_qr_code = 3,
+} vcx_invite_format_t;
+
+typedef enum
+{
+  object_connection = 0,
+  object_issuer_credential = 1,
+  object_credential = 2,
+  object_proof = 3,
+  object_disclosed_proof = 4,
+} vcx_object_type_t;
+
 
 // Provision an agent in the agency, populate configuration and wallet for this agent.
 // NOTE: for synchronous call use vcx_provision_agent
@@ -82,6 +108,12 @@ vcx_error_t vcx_agent_update_info(vcx_command_handle_t command_handle,
 //
 // # Examples connection_options -> "{"connection_type":"SMS","phone":"123"}" OR: "{"connection_type":"QR","phone":""}"
 //
+// Add "envelope_format":"didcomm" to exchange credentials and proofs in DIDComm envelopes (Aries RFC 0019)
+// instead of the default "bundled" format, e.g. "{"connection_type":"QR","phone":"","envelope_format":"didcomm"}"
+// Connections made from an invitation then post their messages to the inviter's service endpoint,
+// wrapped in forward messages for its routing keys. Connections created by releases without DIDComm
+// support have keys only libindy can use and fail with error 1106 (Key Was Not Created For Packing DIDComm Messages).
+//
 // cb: Callback that provides error status of request
 //
 // #Returns
@@ -113,7 +145,8 @@ vcx_error_t vcx_connection_create(vcx_command_handle_t command_handle,
 //
 // source_id: institution's personal identification for the user
 //
-// invite_details: Provided via the other end of the connection calling "vcx_connection_connect" or "vcx_connection_invite_details"
+// invite_details: Provided via the other end of the connection calling "vcx_connection_connect" or "vcx_connection_invite_details".
+//     Invitation urls and connection invitation json are accepted as well
 //
 // cb: Callback that provides connection handle and error status of request
 //
@@ -175,15 +208,17 @@ vcx_error_t vcx_connection_get_state(vcx_command_handle_t command_handle,
 //
 // connection_handle: was provided during creation. Used to identify connection object
 //
-// abbreviated: abbreviated connection details for QR codes or not
+// format: how to render the details, see vcx_invite_format_t
+//     invite_url and invite_qr_code carry a VCX only "~legacy" decorator with the agency details other
+//     VCX instances need to accept them; agents following the connections protocol ignore it.
 //
-// cb: Callback that provides the json string of details
+// cb: Callback that provides the details in the requested format
 //
 // #Returns
 // Error code as a u32
 vcx_error_t vcx_connection_invite_details(vcx_command_handle_t command_handle,
                                        vcx_connection_handle_t connection_handle,
-                                       vcx_bool_t abbreviated,
+                                       vcx_invite_format_t format,
                                        void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
 // Releases the connection object by de-allocating memory
@@ -225,6 +260,341 @@ vcx_error_t vcx_connection_update_state(vcx_command_handle_t command_handle,
                                      vcx_connection_handle_t connection_handle,
                                      void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_state_t));
 
+// Creates a context with its own settings, wallet, pool and objects, so that several
+// institutions can be served by one process. Every context needs its own wallet_name
+// and pool_name.
+//
+// Calls on an object run in the context the object was created in. Other calls take the context
+// explicitly in their _in_context variant, or target the default context, configured by vcx_init,
+// until vcx_context_set_current selects another one on the calling thread.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// config: configuration of the context, same as for vcx_init_with_config
+//
+// cb: Callback that provides error status of initialization and the handle of the context
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_context_create_with_config(vcx_command_handle_t command_handle,
+                                           const char *config,
+                                           void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_context_handle_t));
+
+// Closes the wallet and pool of the context and releases its objects and settings
+//
+// #Params
+// context_handle: handle of the context to release
+//
+// delete: specify whether wallet/pool should be deleted
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_context_release(vcx_context_handle_t context_handle, vcx_bool_t delete);
+
+// Selects the context targeted by the following calls made on this thread
+//
+// #Params
+// context_handle: handle of a context created with vcx_context_create_with_config, 0 for the default context
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_context_set_current(vcx_context_handle_t context_handle);
+
+// Every function below works like the one without the _in_context suffix, in the context given by
+// context_handle instead of the context selected on the calling thread. Calls on an object, e.g. a
+// connection handle, always run in the context the object was created in.
+
+vcx_error_t vcx_connection_create_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             const char *source_id,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_connection_handle_t));
+
+vcx_error_t vcx_connection_create_with_invite_in_context(vcx_context_handle_t context_handle,
+                                                         vcx_command_handle_t command_handle,
+                                                         const char *source_id,
+                                                         const char *invite_details,
+                                                         void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_connection_handle_t));
+
+vcx_error_t vcx_connection_deserialize_in_context(vcx_context_handle_t context_handle,
+                                                  vcx_command_handle_t command_handle,
+                                                  const char *connection_data,
+                                                  void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_connection_handle_t));
+
+vcx_error_t vcx_credential_create_with_offer_in_context(vcx_context_handle_t context_handle,
+                                                        vcx_command_handle_t command_handle,
+                                                        const char *source_id,
+                                                        const char *offer,
+                                                        void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
+vcx_error_t vcx_credential_deserialize_in_context(vcx_context_handle_t context_handle,
+                                                  vcx_command_handle_t command_handle,
+                                                  const char *credential_data,
+                                                  void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
+vcx_error_t vcx_credentialdef_create_in_context(vcx_context_handle_t context_handle,
+                                                vcx_command_handle_t command_handle,
+                                                const char *source_id,
+                                                const char *credentialdef_name,
+                                                const char *schema_id,
+                                                const char *issuer_did,
+                                                const char *tag,
+                                                const char *config,
+                                                vcx_payment_handle_t payment_handle,
+                                                void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
+vcx_error_t vcx_credentialdef_prepare_from_ledger_in_context(vcx_context_handle_t context_handle,
+                                                             vcx_command_handle_t command_handle,
+                                                             const char *source_id,
+                                                             const char *cred_def_id,
+                                                             void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
+vcx_error_t vcx_credentialdef_prepare_for_endorser_in_context(vcx_context_handle_t context_handle,
+                                                              vcx_command_handle_t command_handle,
+                                                              const char *source_id,
+                                                              const char *credentialdef_name,
+                                                              const char *schema_id,
+                                                              const char *issuer_did,
+                                                              const char *tag,
+                                                              const char *endorser,
+                                                              void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t, const char*));
+
+vcx_error_t vcx_credentialdef_deserialize_in_context(vcx_context_handle_t context_handle,
+                                                     vcx_command_handle_t command_handle,
+                                                     const char *credentialdef_data,
+                                                     void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
+vcx_error_t vcx_credentialdef_list_in_context(vcx_context_handle_t context_handle,
+                                              vcx_command_handle_t command_handle,
+                                              void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_disclosed_proof_create_with_request_in_context(vcx_context_handle_t context_handle,
+                                                               vcx_command_handle_t command_handle,
+                                                               const char *source_id,
+                                                               const char *proof_req,
+                                                               void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_disclosed_proof_handle_t));
+
+vcx_error_t vcx_disclosed_proof_deserialize_in_context(vcx_context_handle_t context_handle,
+                                                       vcx_command_handle_t command_handle,
+                                                       const char *proof_data,
+                                                       void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_disclosed_proof_handle_t));
+
+vcx_error_t vcx_issuer_create_credential_in_context(vcx_context_handle_t context_handle,
+                                                    vcx_command_handle_t command_handle,
+                                                    const char *source_id,
+                                                    const char *cred_def_id,
+                                                    const char *issuer_did,
+                                                    const char *credential_data,
+                                                    const char *credential_name,
+                                                    vcx_payment_handle_t price,
+                                                    void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_handle_t));
+
+vcx_error_t vcx_issuer_credential_deserialize_in_context(vcx_context_handle_t context_handle,
+                                                         vcx_command_handle_t command_handle,
+                                                         const char *credential_data,
+                                                         void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_handle_t));
+
+vcx_error_t vcx_issuer_credential_batch_create_in_context(vcx_context_handle_t context_handle,
+                                                          vcx_command_handle_t command_handle,
+                                                          const char *source_id,
+                                                          const char *cred_def_id,
+                                                          const char *issuer_did,
+                                                          const char *credential_data,
+                                                          const char *connections,
+                                                          const char *credential_name,
+                                                          const char *price,
+                                                          void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_batch_handle_t));
+
+vcx_error_t vcx_proof_create_in_context(vcx_context_handle_t context_handle,
+                                        vcx_command_handle_t command_handle,
+                                        const char *source_id,
+                                        const char *requested_attrs,
+                                        const char *requested_predicates,
+                                        const char *revocation_interval,
+                                        const char *name,
+                                        void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_proof_handle_t));
+
+vcx_error_t vcx_proof_template_create_in_context(vcx_context_handle_t context_handle,
+                                                 vcx_command_handle_t command_handle,
+                                                 const char *name,
+                                                 const char *requested_attrs,
+                                                 const char *requested_predicates,
+                                                 const char *revocation_interval,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_proof_template_get_in_context(vcx_context_handle_t context_handle,
+                                              vcx_command_handle_t command_handle,
+                                              const char *name,
+                                              void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_proof_template_list_in_context(vcx_context_handle_t context_handle,
+                                               vcx_command_handle_t command_handle,
+                                               void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_proof_template_delete_in_context(vcx_context_handle_t context_handle,
+                                                 vcx_command_handle_t command_handle,
+                                                 const char *name,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_proof_create_from_template_in_context(vcx_context_handle_t context_handle,
+                                                      vcx_command_handle_t command_handle,
+                                                      const char *source_id,
+                                                      const char *template_name,
+                                                      void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_proof_handle_t));
+
+vcx_error_t vcx_proof_deserialize_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             const char *proof_data,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_proof_handle_t));
+
+vcx_error_t vcx_schema_create_in_context(vcx_context_handle_t context_handle,
+                                         vcx_command_handle_t command_handle,
+                                         const char *source_id,
+                                         const char *schema_name,
+                                         const char *version,
+                                         const char *schema_data,
+                                         vcx_payment_handle_t payment_handle,
+                                         void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_schema_handle_t));
+
+vcx_error_t vcx_schema_prepare_for_endorser_in_context(vcx_context_handle_t context_handle,
+                                                       vcx_command_handle_t command_handle,
+                                                       const char *source_id,
+                                                       const char *schema_name,
+                                                       const char *version,
+                                                       const char *schema_data,
+                                                       const char *endorser,
+                                                       void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_schema_handle_t, const char*));
+
+vcx_error_t vcx_schema_deserialize_in_context(vcx_context_handle_t context_handle,
+                                              vcx_command_handle_t command_handle,
+                                              const char *schema_data,
+                                              void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_schema_handle_t));
+
+vcx_error_t vcx_schema_get_attributes_in_context(vcx_context_handle_t context_handle,
+                                                 vcx_command_handle_t command_handle,
+                                                 const char *source_id,
+                                                 const char *schema_id,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_schema_handle_t, const char*));
+
+vcx_error_t vcx_schema_list_in_context(vcx_context_handle_t context_handle,
+                                       vcx_command_handle_t command_handle,
+                                       void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_agent_provision_async_in_context(vcx_context_handle_t context_handle,
+                                                 vcx_command_handle_t command_handle,
+                                                 const char *json,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_agent_update_info_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             const char *json,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_ledger_get_fees_in_context(vcx_context_handle_t context_handle,
+                                           vcx_command_handle_t command_handle, void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_ledger_cache_invalidate_in_context(vcx_context_handle_t context_handle,
+                                                   const char *ledger_id);
+
+vcx_error_t vcx_endorse_transaction_in_context(vcx_context_handle_t context_handle,
+                                               vcx_command_handle_t command_handle,
+                                               const char *transaction,
+                                               void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_messages_download_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             const char *message_status,
+                                             const char *uids,
+                                             const char *pw_dids,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_messages_update_status_in_context(vcx_context_handle_t context_handle,
+                                                  vcx_command_handle_t command_handle,
+                                                  const char *message_status,
+                                                  const char *msg_json,
+                                                  void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_messages_dispatch_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_messages_start_dispatcher_in_context(vcx_context_handle_t context_handle,
+                                                     unsigned int interval_ms);
+
+vcx_error_t vcx_messages_stop_dispatcher_in_context(vcx_context_handle_t context_handle);
+
+vcx_error_t vcx_wallet_get_token_info_in_context(vcx_context_handle_t context_handle,
+                                                 vcx_command_handle_t command_handle,
+                                                 vcx_payment_handle_t payment_handle,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_wallet_create_payment_address_in_context(vcx_context_handle_t context_handle,
+                                                         vcx_command_handle_t command_handle,
+                                                         const char *seed,
+                                                         void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_wallet_add_record_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             const char *type_,
+                                             const char *id,
+                                             const char *value,
+                                             const char *tags_json,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_wallet_update_record_value_in_context(vcx_context_handle_t context_handle,
+                                                      vcx_command_handle_t command_handle,
+                                                      const char *type_,
+                                                      const char *id,
+                                                      const char *value,
+                                                      void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_wallet_update_record_tags_in_context(vcx_context_handle_t context_handle,
+                                                     vcx_command_handle_t command_handle,
+                                                     const char *type_,
+                                                     const char *id,
+                                                     const char *tags,
+                                                     void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_wallet_add_record_tags_in_context(vcx_context_handle_t context_handle,
+                                                  vcx_command_handle_t command_handle,
+                                                  const char *type_,
+                                                  const char *id,
+                                                  const char *tags,
+                                                  void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_wallet_delete_record_tags_in_context(vcx_context_handle_t context_handle,
+                                                     vcx_command_handle_t command_handle,
+                                                     const char *type_,
+                                                     const char *id,
+                                                     const char *tags,
+                                                     void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_wallet_get_record_in_context(vcx_context_handle_t context_handle,
+                                             vcx_command_handle_t command_handle,
+                                             const char *type_,
+                                             const char *id,
+                                             const char *options_json,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_wallet_delete_record_in_context(vcx_context_handle_t context_handle,
+                                                vcx_command_handle_t command_handle,
+                                                const char *type_,
+                                                const char *id,
+                                                void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+vcx_error_t vcx_wallet_send_tokens_in_context(vcx_context_handle_t context_handle,
+                                              vcx_command_handle_t command_handle,
+                                              vcx_payment_handle_t payment_handle,
+                                              const char *tokens,
+                                              const char *recipient,
+                                              void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+vcx_error_t vcx_wallet_export_in_context(vcx_context_handle_t context_handle,
+                                         vcx_command_handle_t command_handle,
+                                         const char *path,
+                                         const char *backup_key,
+                                         void (*cb)(vcx_command_handle_t, vcx_error_t));
+
 // Create a Credential object that requests and receives a credential for an institution
 //
 // #Params
@@ -423,6 +793,8 @@ vcx_error_t vcx_credential_update_state(vcx_command_handle_t command_handle,
 // tag: way to create a unique credential def with the same schema and issuer did.
 //
 // config: revocation info
+//     {"support_revocation": true, "tails_file": "/tmp/tails", "max_creds": 5}
+//     tails_file is the directory the tails file is written to. Use "{}" for no revocation support.
 //
 // cb: Callback that provides CredentialDef handle and error status of request.
 //
@@ -490,6 +862,84 @@ vcx_error_t vcx_credentialdef_get_payment_txn(vcx_command_handle_t command_handl
                                            vcx_credential_handle_t handle,
                                            void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Get the state of the credentialdef: 0 - built for an endorser, 1 - written to the ledger
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credentialdef_handle: Credentialdef handle that was provided during creation. Used to access credentialdef object
+//
+// cb: Callback that provides the state and error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credentialdef_get_state(vcx_command_handle_t command_handle,
+                                        vcx_credential_handle_t credentialdef_handle,
+                                        void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_public_entity_state_t));
+
+// List the credential definitions created with this wallet
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// cb: Callback that provides the credential definitions and error status of request
+//
+// #Returns
+// Error code as a u32
+//
+// # Example cred_defs -> [{"source_id":"1","cred_def_id":"2hoqvcwupRTUNkXn6ArYzs:3:CL:1697","name":"name","schema_id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4","tag":"tag"}]
+vcx_error_t vcx_credentialdef_list(vcx_command_handle_t command_handle,
+                                   void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+// Create a new CredentialDef object and the transaction writing it to the ledger, for an endorser
+// to sign and write instead of the issuer. Used when issuer_did has no permission to write to the ledger.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Enterprise's personal identification for the user.
+//
+// credentialdef_name: Name of credential definition
+//
+// schema_id: The schema id given during the creation of the schema
+//
+// issuer_did: did corresponding to entity issuing a credential, NULL to use the institution_did
+//
+// tag: way to create a unique credential def with the same schema and issuer did.
+//
+// endorser: DID of the endorser that is going to write the credential definition to the ledger
+//
+// cb: Callback that provides CredentialDef handle, the transaction to be endorsed and error status of request.
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credentialdef_prepare_for_endorser(vcx_command_handle_t command_handle,
+                                                   const char *source_id,
+                                                   const char *credentialdef_name,
+                                                   const char *schema_id,
+                                                   const char *issuer_did,
+                                                   const char *tag,
+                                                   const char *endorser,
+                                                   void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t, const char*));
+
+// Create a new CredentialDef object from a credential definition already written to the ledger
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Enterprise's personal identification for the user.
+//
+// cred_def_id: id of the credential definition on the ledger
+//
+// cb: Callback that provides CredentialDef handle and error status of request.
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credentialdef_prepare_from_ledger(vcx_command_handle_t command_handle,
+                                                  const char *source_id,
+                                                  const char *cred_def_id,
+                                                  void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
 // Releases the credentialdef object by de-allocating memory
 //
 // #Params
@@ -514,6 +964,24 @@ vcx_error_t vcx_credentialdef_serialize(vcx_command_handle_t command_handle,
                                      vcx_credential_handle_t credentialdef_handle,
                                      void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Write the credential definition transaction, signed by the endorser, to the ledger
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credentialdef_handle: handle of the credentialdef returned by vcx_credentialdef_prepare_for_endorser
+//
+// endorsed_txn: the transaction from vcx_credentialdef_prepare_for_endorser signed by the endorser
+//
+// cb: Callback that provides error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credentialdef_submit_endorsed(vcx_command_handle_t command_handle,
+                                              vcx_credential_handle_t credentialdef_handle,
+                                              const char *endorsed_txn,
+                                              void (*cb)(vcx_command_handle_t, vcx_error_t));
+
 // Create a proof for fulfilling a corresponding proof request
 //
 // #Params
@@ -578,12 +1046,16 @@ vcx_error_t vcx_disclosed_proof_deserialize(vcx_command_handle_t command_handle,
 // handle: Proof handle that was provided during creation. Used to identify the disclosed proof object
 //
 // selected_credentials: a json string with a credential for each proof request attribute.
-// List of possible credentials for each attribute is returned from vcx_disclosed_proof_retrieve_credentials
+// List of possible credentials for each attribute is returned from vcx_disclosed_proof_retrieve_credentials,
+// vcx_disclosed_proof_select_credentials picks one of them for each attribute
 // # Examples selected_credential -> "{"req_attr_0":cred_info}" Where cred_info is returned from retrieve credentials
 //
 // self_attested_attrs: a json string with attributes self attested by user
 // # Examples self_attested_attrs -> "{"self_attested_attr_0":"attested_val"}"
 //
+// Revocable credentials need the tails file of their revocation registry, it is looked up by its tailsHash
+// in the "tails_dir" directory of the configuration
+//
 // cb: Callback that returns error status
 //
 // #Returns
@@ -648,6 +1120,33 @@ vcx_error_t vcx_disclosed_proof_retrieve_credentials(vcx_command_handle_t comman
                                                   vcx_disclosed_proof_handle_t proof_handle,
                                                   void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Takes the disclosed proof object and picks a credential from wallet for each attribute and predicate of the proof request
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// handle: Proof handle that was provided during creation. Used to identify the disclosed proof object
+//
+// selection_options: a json string with the strategy used to pick among the matching credentials, all fields are optional
+//     strategy: "newest" (default) takes the credential stored last by vcx, credentials stored otherwise count as the oldest,
+//               "preferred_issuer" takes a credential of the first issuer in preferred_issuers that has one,
+//               "least_revealing" uses as few credentials as possible, then the ones holding the fewest attributes
+//     preferred_issuers: issuer DIDs, most preferred first
+//     overrides: credential id (referent of its cred_info) to use for a proof request referent, whatever the strategy picks,
+//                under "attrs" or "predicates" like in selected_credentials
+// # Examples selection_options -> "{"strategy":"preferred_issuer","preferred_issuers":["V4SGRU86Z58d6TV7PBUe6f"],"overrides":{"attrs":{"height_1":"92556f60-d290-4b58-9a43-05c25aac214e"}}}"
+//
+// cb: Callback that provides json string of the selection, selected_credentials can be passed to vcx_disclosed_proof_generate_proof
+//     and unsatisfied lists the referents no credential in wallet can be used for
+// # Example selection -> "{"selected_credentials":{"attrs":{"height_1":{"cred_info":{...},"interval":null}},"predicates":{}},"unsatisfied":["zip_2"]}"
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_disclosed_proof_select_credentials(vcx_command_handle_t command_handle,
+                                                vcx_disclosed_proof_handle_t proof_handle,
+                                                const char *selection_options,
+                                                void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 // Send a proof to the connection, called after having received a proof request
 //
 // #params
@@ -696,8 +1195,40 @@ vcx_error_t vcx_disclosed_proof_update_state(vcx_command_handle_t command_handle
                                           vcx_disclosed_proof_handle_t proof_handle,
                                           void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_state_t));
 
+// Endorse a schema or credential definition transaction prepared by another issuer, the institution_did
+// must be the endorser named in the transaction. The issuer writes the endorsed transaction to the ledger.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// transaction: transaction returned by vcx_schema_prepare_for_endorser or vcx_credentialdef_prepare_for_endorser
+//
+// cb: Callback that provides the endorsed transaction and error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_endorse_transaction(vcx_command_handle_t command_handle,
+                                    const char *transaction,
+                                    void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 const char *vcx_error_c_message(vcx_error_t error_code);
 
+// Get details for the last error returned on the calling thread, either by a function or to a callback.
+// Every API call clears the details of the previous error.
+//
+// #Params
+// error_json_p: Reference that will contain the error details (if any) or NULL otherwise.
+//     The string is valid until the next API call on the same thread.
+//     {
+//         "error": int - error code,
+//         "message": str - human-readable description of the error,
+//         "causes": [{"error": int, "message": str}] - errors that caused it, outermost first
+//     }
+//
+// #Returns
+// Success
+vcx_error_t vcx_get_current_error(const char **error_json_p);
+
 // Retrieve information about a stored credential in user's wallet, including credential id and the credential itself.
 //
 // #Params
@@ -731,6 +1262,25 @@ vcx_error_t vcx_get_proof(vcx_command_handle_t command_handle,
                        vcx_connection_handle_t connection_handle,
                        void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_proof_state_t, const char*));
 
+// Provide the agency response for a message handed out by the callback registered with vcx_set_http_transport
+//
+// #Params
+// command_handle: command handle received by the transport callback
+//
+// err: 0 if the message was delivered, 1093 (agency unreachable) if it could not be delivered
+//      and may be retried, any other value if the agency rejected it
+//
+// response: body of the agency response
+//
+// response_len: length of the response
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_http_transport_response(vcx_command_handle_t command_handle,
+                                        vcx_error_t err,
+                                        const unsigned char *response,
+                                        unsigned int response_len);
+
 // Initializes VCX with config file
 //
 // An example file is at libvcx/sample_config/config.json
@@ -775,7 +1325,8 @@ vcx_error_t vcx_init_with_config(vcx_command_handle_t command_handle,
 //
 // issuer_did: did corresponding to entity issuing a credential. Needs to have Trust Anchor permissions on ledger
 //
-// credential_data: data attributes offered to person in the credential
+// credential_data: data attributes offered to person in the credential, they must be the attributes of the schema
+// of the credential definition. On a mismatch the error details from vcx_get_current_error list the attribute names.
 //
 // credential_name: Name of the credential - ex. Drivers Licence
 //
@@ -796,6 +1347,101 @@ vcx_error_t vcx_issuer_create_credential(vcx_command_handle_t command_handle,
                                       vcx_payment_handle_t price,
                                       void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_handle_t));
 
+// Create a batch of issuer credentials from one credential definition, one issuer credential per connection.
+// Connections that cannot be used are reported as failed entries in the batch status.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Enterprise's personal identification for the batch, each credential gets "<source_id>-<connection_handle>".
+//
+// cred_def_id: id of credential definition given during creation of the credential definition
+//
+// issuer_did: did corresponding to entity issuing a credential. Uses the institution did from the config if null
+//
+// credential_data: attribute template shared by all credentials in the batch
+//
+// connections: json list of connection handles, an entry may override attributes of the template
+// example: [123, {"connection_handle": 456, "attributes": {"name": ["Bob"]}}]
+//
+// credential_name: Name of the credential - ex. Drivers Licence
+//
+// price: price of each credential
+//
+// cb: Callback that provides batch handle and error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_credential_batch_create(vcx_command_handle_t command_handle,
+                                            const char *source_id,
+                                            const char *cred_def_id,
+                                            const char *issuer_did,
+                                            const char *credential_data,
+                                            const char *connections,
+                                            const char *credential_name,
+                                            const char *price,
+                                            void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_batch_handle_t));
+
+// Get the progress of the batch without contacting the agency
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// batch_handle: Batch handle that was provided during creation.
+//
+// cb: Callback that provides the batch status
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_credential_batch_get_status(vcx_command_handle_t command_handle,
+                                                vcx_issuer_credential_batch_handle_t batch_handle,
+                                                void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+// Releases the batch and the issuer credentials created for it
+//
+// #Params
+// batch_handle: Batch handle that was provided during creation.
+//
+// #Returns
+// Success
+vcx_error_t vcx_issuer_credential_batch_release(vcx_issuer_credential_batch_handle_t batch_handle);
+
+// Sends the credential offer to every connection of the batch concurrently.
+// Offers that failed are sent again when this is called again.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// batch_handle: Batch handle that was provided during creation.
+//
+// cb: Callback that provides the batch status once every offer has been attempted
+//
+// #Returns
+// Error code as a u32
+//
+// # Example status -> {"source_id":"diplomas","total":2,"offers_sent":1,"requests_received":0,"issued":0,"failed":1,
+//     "entries":[{"connection_handle":123,"credential_handle":789,"state":2},
+//                {"connection_handle":456,"credential_handle":790,"state":1,"error_code":1093,"error_message":"Agency could not be reached"}]}
+vcx_error_t vcx_issuer_credential_batch_send_offers(vcx_command_handle_t command_handle,
+                                                 vcx_issuer_credential_batch_handle_t batch_handle,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+// Checks every outstanding offer of the batch concurrently and sends the credential to each
+// connection whose credential request has arrived.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// batch_handle: Batch handle that was provided during creation.
+//
+// cb: Callback that provides the batch status once every connection has been checked
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_credential_batch_update_state(vcx_command_handle_t command_handle,
+                                                  vcx_issuer_credential_batch_handle_t batch_handle,
+                                                  void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 // Takes a json string representing an issuer credential object and recreates an object matching the json
 //
 // #Params
@@ -887,6 +1533,22 @@ vcx_error_t vcx_issuer_credential_update_state(vcx_command_handle_t command_hand
                                             vcx_issuer_credential_handle_t credential_handle,
                                             void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_state_t));
 
+// Revoke Credential that was issued with a revocable credential definition. Updates the
+// revocation registry on the ledger.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+//
+// cb: Callback that provides error status of revoking the credential
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_revoke_credential(vcx_command_handle_t command_handle,
+                                      vcx_issuer_credential_handle_t credential_handle,
+                                      void (*cb)(vcx_command_handle_t, vcx_error_t));
+
 // Send Credential that was requested by user
 //
 // #Params
@@ -923,6 +1585,15 @@ vcx_error_t vcx_issuer_send_credential_offer(vcx_command_handle_t command_handle
                                           vcx_connection_handle_t connection_handle,
                                           void (*cb)(vcx_command_handle_t, vcx_error_t));
 
+// Remove schemas and credential definitions from the ledger cache, the next lookup fetches them from the ledger
+//
+// #Params
+// ledger_id: id of the schema or credential definition to remove, NULL to clear the whole cache
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_ledger_cache_invalidate(const char *ledger_id);
+
 // Get ledger fees from the sovrin network
 //
 // #Params
@@ -934,6 +1605,24 @@ vcx_error_t vcx_issuer_send_credential_offer(vcx_command_handle_t command_handle
 // Error code as a u32
 vcx_error_t vcx_ledger_get_fees(vcx_command_handle_t command_handle, void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Download the messages of all connections with a single request and update the state of every
+// connection, credential and proof waiting for an answer, instead of calling update_state on each of them.
+// A disclosed proof is created for every new proof request received on an accepted connection and
+// reported in request_received state, its source id is the uid of the request message.
+//
+// #params
+//
+// command_handle: command handle to map callback to user context.
+//
+// cb: Callback that provides the objects whose state changed
+//
+// #Returns
+// Error code as a u32
+//
+// # Example changes -> [{"object_type":1,"handle":2875418925,"state":3}]
+vcx_error_t vcx_messages_dispatch(vcx_command_handle_t command_handle,
+                               void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 // Retrieve messages from the specified connection
 //
 // #params
@@ -954,6 +1643,22 @@ vcx_error_t vcx_messages_download(vcx_command_handle_t command_handle,
                                const char *pw_dids,
                                void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Dispatch messages in the background every interval_ms milliseconds.
+// State changes are reported through the callback registered with vcx_set_state_change_callback.
+//
+// #Params
+// interval_ms: time between two downloads, must be greater than 0
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_messages_start_dispatcher(unsigned int interval_ms);
+
+// Stop the background dispatcher started with vcx_messages_start_dispatcher
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_messages_stop_dispatcher();
+
 // Update the status of messages from the specified connection
 //
 // #params
@@ -984,10 +1689,27 @@ vcx_error_t vcx_messages_update_status(vcx_command_handle_t command_handle,
 //
 // # Example requested_attrs -> "[{"name":"attrName","restrictions":["issuer_did":"did","schema_id":"id","schema_issuer_did":"did","schema_name":"name","schema_version":"1.1.1","cred_def_id":"id"}]]"
 //
+// An entry may ask for "names":["attrName1","attrName2"] instead of a name, all of them have to come from the same credential
+// This is a VCX extension, only VCX provers answer such entries. It has to be enabled with "attribute_names":"true"
+// in the configuration, otherwise vcx_proof_send_request fails with error 1103.
+//
 // requested_predicates: predicate specifications prover must provide claim for
 //
-// # Example requested_predicates -> "[{"name":"attrName","p_type":"GE","p_value":9,"restrictions":["issuer_did":"did","schema_id":"id","schema_issuer_did":"did","schema_name":"name","schema_version":"1.1.1","cred_def_id":"id"}]]"
+// # Example requested_predicates -> "[{"name":"attrName","p_type":">=","p_value":9,"restrictions":["issuer_did":"did","schema_id":"id","schema_issuer_did":"did","schema_name":"name","schema_version":"1.1.1","cred_def_id":"id"}]]"
+//
+// Any one of the restrictions of an entry has to be met. A restriction may group others with {"$and":[...]} or
+// {"$or":[...]} and may require a value of the credential with "attr::<attrName>::value":"<value>"
+//
+// p_type is one of "<", "<=", ">", ">=" (or "LT", "LE", "GT", "GE"), any other value fails with error 1101
+//
+// Both requested_attrs and requested_predicates entries may contain a "non_revoked" interval that overrides
+// revocation_interval for that entry, e.g. "non_revoked":{"from":1540498474,"to":1540498474}
+//
+// revocation_interval: interval applied to the whole proof in which the prover must show the credentials were not revoked
 //
+// # Example revocation_interval -> "{"from":1540498474,"to":1540498474}", "{}" if no interval is required
+//
+// name: Name of the proof request
 //
 // cb: Callback that provides proof handle and error status of request.
 //
@@ -997,9 +1719,28 @@ vcx_error_t vcx_proof_create(vcx_command_handle_t command_handle,
                           const char *source_id,
                           const char *requested_attrs,
                           const char *requested_predicates,
+                          const char *revocation_interval,
                           const char *name,
                           void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_proof_handle_t));
 
+// Create a new Proof object requesting what a stored proof template asks for, with a fresh nonce
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Enterprise's personal identification for the user.
+//
+// template_name: Name of a template stored with vcx_proof_template_create
+//
+// cb: Callback that provides proof handle and error status of request.
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_proof_create_from_template(vcx_command_handle_t command_handle,
+                                        const char *source_id,
+                                        const char *template_name,
+                                        void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_proof_handle_t));
+
 // Takes a json string representing a proof object and recreates an object matching the json
 //
 // #Params
@@ -1072,6 +1813,74 @@ vcx_error_t vcx_proof_serialize(vcx_command_handle_t command_handle,
                              vcx_proof_handle_t proof_handle,
                              void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Stores a named proof template in the wallet, a template stored before under the name is replaced
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// name: Name of the template, proofs created from it have this name
+//
+// requested_attrs: attributes/claims prover must provide in proof, as for vcx_proof_create
+//
+// requested_predicates: predicate specifications prover must provide claim for, as for vcx_proof_create
+//
+// revocation_interval: interval applied to the whole proof, as for vcx_proof_create
+//
+// cb: Callback that provides error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_proof_template_create(vcx_command_handle_t command_handle,
+                                   const char *name,
+                                   const char *requested_attrs,
+                                   const char *requested_predicates,
+                                   const char *revocation_interval,
+                                   void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+// Removes a proof template from the wallet
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// name: Name of the template
+//
+// cb: Callback that provides error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_proof_template_delete(vcx_command_handle_t command_handle,
+                                   const char *name,
+                                   void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+// Retrieves a proof template stored in the wallet
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// name: Name of the template
+//
+// cb: Callback that provides the template and error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_proof_template_get(vcx_command_handle_t command_handle,
+                                const char *name,
+                                void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+// Lists the proof templates stored in the wallet
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// cb: Callback that provides the templates, ordered by name, and error status of request
+//
+// # Example templates -> "[{"name":"kyc","requested_attrs":[{"name":"attrName"}],"requested_predicates":[],"revocation_interval":{}}]"
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_proof_template_list(vcx_command_handle_t command_handle,
+                                 void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 // Checks for any state change and updates the proof state attribute
 //
 // #Params
@@ -1194,6 +2003,63 @@ vcx_error_t vcx_schema_get_schema_id(vcx_command_handle_t command_handle,
                                   vcx_schema_handle_t schema_handle,
                                   void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Get the state of the schema: 0 - built for an endorser, 1 - written to the ledger
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// schema_handle: Schema handle that was provided during creation. Used to access schema object
+//
+// cb: Callback that provides the state and error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_schema_get_state(vcx_command_handle_t command_handle,
+                                 vcx_schema_handle_t schema_handle,
+                                 void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_public_entity_state_t));
+
+// List the schemas created with this wallet
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// cb: Callback that provides the schemas and error status of request
+//
+// #Returns
+// Error code as a u32
+//
+// # Example schemas -> [{"source_id":"1","schema_id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4","name":"test-licence","version":"4.4.4"}]
+vcx_error_t vcx_schema_list(vcx_command_handle_t command_handle,
+                            void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+// Create a new Schema object and the transaction writing it to the ledger, for an endorser to sign
+// and write instead of the institution. Used when institution_did has no permission to write to the ledger.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Enterprise's personal identification for the user.
+//
+// schema_name: Name of schema
+//
+// version: version of schema
+//
+// schema_data: list of attributes that will make up the schema
+//
+// endorser: DID of the endorser that is going to write the schema to the ledger
+//
+// cb: Callback that provides Schema handle, the transaction to be endorsed and error status of request.
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_schema_prepare_for_endorser(vcx_command_handle_t command_handle,
+                                            const char *source_id,
+                                            const char *schema_name,
+                                            const char *version,
+                                            const char *schema_data,
+                                            const char *endorser,
+                                            void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_schema_handle_t, const char*));
+
 // Releases the schema object by de-allocating memory
 //
 // #Params
@@ -1218,6 +2084,90 @@ vcx_error_t vcx_schema_serialize(vcx_command_handle_t command_handle,
                               vcx_schema_handle_t schema_handle,
                               void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Write the schema transaction, signed by the endorser, to the ledger
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// schema_handle: handle of the schema returned by vcx_schema_prepare_for_endorser
+//
+// endorsed_txn: the transaction from vcx_schema_prepare_for_endorser signed by the endorser
+//
+// cb: Callback that provides error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_schema_submit_endorsed(vcx_command_handle_t command_handle,
+                                       vcx_schema_handle_t schema_handle,
+                                       const char *endorsed_txn,
+                                       void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+// Route all agency traffic through the host instead of the built in http client.
+// For every message libvcx calls send_cb and waits for vcx_http_transport_response to be called
+// with the same command handle.
+//
+// #Params
+// send_cb: Callback that receives the url and the encrypted bundle to post, NULL restores the default client
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_set_http_transport(void (*send_cb)(vcx_command_handle_t command_handle,
+                                                   const char *url,
+                                                   const unsigned char *body,
+                                                   unsigned int body_len));
+
+// Register a callback fired whenever the message dispatcher changes the state of an object of a context
+//
+// #Params
+// context_handle: context whose objects are reported, 0 for the default context
+//
+// state_cb: Callback that receives the context, the object type, the handle of the object and its new state.
+//           NULL removes the callback of the context
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_set_state_change_callback(vcx_context_handle_t context_handle,
+                                          void (*state_cb)(vcx_context_handle_t context_handle,
+                                                           vcx_object_type_t object_type,
+                                                           unsigned int handle,
+                                                           vcx_state_t state));
+
+// Change the most verbose level logged by libvcx at runtime
+//
+// #Params
+// max_lvl: 0 - Off, 1 - Error, 2 - Warn, 3 - Info, 4 - Debug, 5 - Trace
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_set_log_max_lvl(unsigned int max_lvl);
+
+// Set a custom logger receiving the logs of libvcx and libindy, instead of the built-in ones
+// configured with RUST_LOG and log_config. Must be called before vcx_init, it can only be set once.
+//
+// #Params
+// context: pointer to some logger context that will be passed to logger callbacks
+// enabled: (optional) tells whether a record with given level and target should be logged.
+//          Can be used to change the levels at runtime
+// log: receives level, target, message, module path, file and line of every record
+// flush: (optional) flushes buffered records
+// max_lvl: most verbose level to log: 0 - Off, 1 - Error, 2 - Warn, 3 - Info, 4 - Debug, 5 - Trace
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_set_logger(const void* context,
+                           vcx_bool_t (*enabled)(const void* context,
+                                                 unsigned int level,
+                                                 const char* target),
+                           void (*log)(const void* context,
+                                       unsigned int level,
+                                       const char* target,
+                                       const char* message,
+                                       const char* module_path,
+                                       const char* file,
+                                       unsigned int line),
+                           void (*flush)(const void* context),
+                           unsigned int max_lvl);
+
 // Reset libvcx to a pre-configured state, releasing/deleting any handles and freeing memory
 //
 // libvcx will be inoperable and must be initialized again with vcx_init_with_config
@@ -5,10 +5,10 @@ use utils::cstring::CStringUtils;
 use utils::error;
 use utils::error::error_string;
 use utils::threadpool::spawn;
+use context;
 use std::ptr;
-use error::ToErrorCode;
-use error::connection::ConnectionError;
-use connection::{get_source_id, build_connection, build_connection_with_invite, connect, to_string, get_state, release, is_valid_handle, update_state, from_string, get_invite_details, delete_connection};
+use connection::{get_source_id, get_context, build_connection, build_connection_with_invite, connect, to_string, get_state, release, is_valid_handle, update_state, from_string, get_invite_details, get_invite_details_with_format, delete_connection};
+use messages::invitation::InviteFormat;
 
 /// Delete a Connection object and release its handle
 ///
@@ -28,9 +28,11 @@ pub extern fn vcx_connection_delete_connection(command_handle: u32,
                                                cb: Option<extern fn(
                                                    xcommand_handle: u32,
                                                    err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     if !is_valid_handle(connection_handle) {
-        return ConnectionError::InvalidHandle().to_error_code()
+        return error::INVALID_CONNECTION_HANDLE.code_num
     }
     info!("vcx_connection_delete_connection(command_handle: {}, connection_handle: {})", command_handle, connection_handle);
     spawn(move|| {
@@ -41,7 +43,7 @@ pub extern fn vcx_connection_delete_connection(command_handle: u32,
             },
             Err(e) => {
                 info!("vcx_connection_delete_connection_cb(command_handle: {}, rc: {})", command_handle, e);
-                cb(command_handle, e.to_error_code());
+                cb(command_handle, e.ffi_code());
             },
         }
 
@@ -67,6 +69,7 @@ pub extern fn vcx_connection_delete_connection(command_handle: u32,
 pub extern fn vcx_connection_create(command_handle: u32,
                                     source_id: *const c_char,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, connection_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
     info!("vcx_connection_create(command_handle: {}, source_id: {})", command_handle, source_id);
@@ -80,7 +83,7 @@ pub extern fn vcx_connection_create(command_handle: u32,
             Err(x) => {
                 warn!("vcx_connection_create_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
                       command_handle, x.to_string(), 0, source_id);
-                cb(command_handle, x.to_error_code(), 0);
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -97,7 +100,8 @@ pub extern fn vcx_connection_create(command_handle: u32,
 ///
 /// source_id: institution's personal identification for the user
 ///
-/// invite_details: Provided via the other end of the connection calling "vcx_connection_connect" or "vcx_connection_invite_details"
+/// invite_details: Provided via the other end of the connection calling "vcx_connection_connect" or "vcx_connection_invite_details".
+///     Invitation urls and connection invitation json are accepted as well
 ///
 /// cb: Callback that provides connection handle and error status of request
 ///
@@ -108,6 +112,7 @@ pub extern fn vcx_connection_create_with_invite(command_handle: u32,
                                                 source_id: *const c_char,
                                                 invite_details: *const c_char,
                                                 cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
@@ -123,7 +128,7 @@ pub extern fn vcx_connection_create_with_invite(command_handle: u32,
             Err(x) => {
                 warn!("vcx_connection_create_with_invite_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
                       command_handle, x.to_string(), 0, source_id);
-                cb(command_handle, x.to_error_code(), 0);
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -144,6 +149,12 @@ pub extern fn vcx_connection_create_with_invite(command_handle: u32,
 ///
 /// # Examples connection_options -> "{"connection_type":"SMS","phone":"123"}" OR: "{"connection_type":"QR","phone":""}"
 ///
+/// Add "envelope_format":"didcomm" to exchange credentials and proofs in DIDComm envelopes (Aries RFC 0019)
+/// instead of the default "bundled" format, e.g. "{"connection_type":"QR","phone":"","envelope_format":"didcomm"}"
+/// Connections made from an invitation then post their messages to the inviter's service endpoint,
+/// wrapped in forward messages for its routing keys. Connections created by releases without DIDComm
+/// support have keys only libindy can use and fail with error 1106 (Key Was Not Created For Packing DIDComm Messages).
+///
 /// cb: Callback that provides error status of request
 ///
 /// #Returns
@@ -153,6 +164,8 @@ pub extern fn vcx_connection_connect(command_handle:u32,
                                      connection_handle: u32,
                                      connection_options: *const c_char,
                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, invite_details: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -193,7 +206,7 @@ pub extern fn vcx_connection_connect(command_handle:u32,
             Err(x) => {
                 warn!("vcx_connection_connect_cb(command_handle: {}, connection_handle: {}, rc: {}, details: {}, source_id: {})",
                       command_handle, connection_handle, x.to_string(), "null", source_id);
-                cb(command_handle,x.to_error_code(), ptr::null_mut());
+                cb(command_handle,x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -218,6 +231,8 @@ pub extern fn vcx_connection_connect(command_handle:u32,
 pub extern fn vcx_connection_serialize(command_handle: u32,
                                        connection_handle: u32,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, serialized_data: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -240,8 +255,8 @@ pub extern fn vcx_connection_serialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_connection_serialize_cb(command_handle: {}, connection_handle: {}, rc: {}, state: {}), source_id: {:?}",
-                      command_handle, connection_handle, error_string(x), "null", source_id);
-                cb(command_handle, x, ptr::null_mut());
+                      command_handle, connection_handle, x, "null", source_id);
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -266,6 +281,7 @@ pub extern fn vcx_connection_serialize(command_handle: u32,
 pub extern fn vcx_connection_deserialize(command_handle: u32,
                                       connection_data: *const c_char,
                                       cb: Option<extern fn(xcommand_handle: u32, err: u32, connection_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(connection_data, error::INVALID_OPTION.code_num);
@@ -282,8 +298,8 @@ pub extern fn vcx_connection_deserialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_connection_deserialize_cb(command_handle: {}, rc: {}, handle: {} )",
-                      command_handle, error_string(x.to_error_code()), 0);
-                (x.to_error_code(), 0)
+                      command_handle, x, 0);
+                (x.ffi_code(), 0)
             },
         };
 
@@ -311,6 +327,8 @@ pub extern fn vcx_connection_deserialize(command_handle: u32,
 pub extern fn vcx_connection_update_state(command_handle: u32,
                                           connection_handle: u32,
                                           cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -333,8 +351,8 @@ pub extern fn vcx_connection_update_state(command_handle: u32,
             Err(x) => {
                 warn!("vcx_connection_update_state_cb(command_handle: {}, rc: {}, connection_handle: {}, state: {}), source_id: {:?}",
                       // TODO: Refactor Error
-                      command_handle, error_string(x.to_error_code()), connection_handle, get_state(connection_handle), source_id);
-                x.to_error_code()
+                      command_handle, x, connection_handle, get_state(connection_handle), source_id);
+                x.ffi_code()
             },
         };
         let state = get_state(connection_handle);
@@ -360,6 +378,8 @@ pub extern fn vcx_connection_update_state(command_handle: u32,
 pub extern fn vcx_connection_get_state(command_handle: u32,
                                        connection_handle: u32,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -390,31 +410,44 @@ pub extern fn vcx_connection_get_state(command_handle: u32,
 ///
 /// connection_handle: was provided during creation. Used to identify connection object
 ///
-/// abbreviated: abbreviated connection details for QR codes or not
+/// format: how to render the details
+///     0 - invite details json
+///     1 - abbreviated invite details json
+///     2 - shareable url carrying a base64url encoded connection invitation
+///     3 - connection invitation json, suited for QR codes
+///     The invitations of 2 and 3 carry a VCX only "~legacy" decorator with the agency details other
+///     VCX instances need to accept them; agents following the connections protocol ignore it.
 ///
-/// cb: Callback that provides the json string of details
+/// cb: Callback that provides the details in the requested format
 ///
 /// #Returns
 /// Error code as a u32
 #[no_mangle]
 pub extern fn vcx_connection_invite_details(command_handle: u32,
                                             connection_handle: u32,
-                                            abbreviated: bool,
+                                            format: u32,
                                             cb: Option<extern fn(xcommand_handle: u32, err: u32, details: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     let source_id = get_source_id(connection_handle).unwrap_or_default();
-    info!("vcx_connection_invite_details(command_handle: {}, connection_handle: {}, abbreviated: {}), source_id: {:?}",
-          command_handle, connection_handle, abbreviated, source_id);
+    info!("vcx_connection_invite_details(command_handle: {}, connection_handle: {}, format: {}), source_id: {:?}",
+          command_handle, connection_handle, format, source_id);
 
     if !is_valid_handle(connection_handle) {
         error!("vcx_connection_get_state - invalid handle");
         return error::INVALID_CONNECTION_HANDLE.code_num;
     }
 
+    let format = match InviteFormat::from_u32(format) {
+        Ok(x) => x,
+        Err(x) => return x.ffi_code(),
+    };
+
     spawn(move|| {
-        match get_invite_details(connection_handle, abbreviated){
+        match get_invite_details_with_format(connection_handle, format){
             Ok(str) => {
                 info!("vcx_connection_invite_details_cb(command_handle: {}, connection_handle: {}, rc: {}, details: {}), source_id: {:?}",
                       command_handle, connection_handle, error_string(0), str, source_id);
@@ -423,8 +456,8 @@ pub extern fn vcx_connection_invite_details(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_connection_invite_details_cb(command_handle: {}, connection_handle: {}, rc: {}, details: {}, source_id: {:?})",
-                      command_handle, connection_handle, error_string(x.to_error_code()), "null", source_id);
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                      command_handle, connection_handle, x, "null", source_id);
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             }
         };
 
@@ -443,12 +476,14 @@ pub extern fn vcx_connection_invite_details(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_connection_release(connection_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(get_context(connection_handle));
     let source_id = get_source_id(connection_handle).unwrap_or_default();
     match release(connection_handle) {
         Ok(_) => info!("vcx_connection_release(connection_handle: {}, rc: {}), source_id: {:?}",
                        connection_handle, error_string(0), source_id),
         Err(e) => warn!("vcx_connection_release(connection_handle: {}), rc: {}), source_id: {:?}",
-                        connection_handle, error_string(e.to_error_code()), source_id),
+                        connection_handle, e, source_id),
     };
 
     error::SUCCESS.code_num
@@ -507,6 +542,28 @@ mod tests {
         assert!(invite_details.is_some());
     }
 
+    #[test]
+    fn test_vcx_connection_invite_details_formats() {
+        init!("true");
+        let handle = build_connection("test_vcx_connection_invite_details_formats").unwrap();
+        ::connection::set_invite_details(handle, ::connection::parse_invite_detail(::utils::constants::INVITE_DETAIL_STRING).unwrap()).unwrap();
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_connection_invite_details(cb.command_handle, handle, 2, Some(cb.get_callback())), error::SUCCESS.code_num);
+        let url = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+        assert!(url.contains("?c_i="));
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_connection_create_with_invite(cb.command_handle,
+                                                     CString::new("invitee").unwrap().into_raw(),
+                                                     CString::new(url).unwrap().into_raw(),
+                                                     Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert!(cb.receive(Some(Duration::from_secs(10))).unwrap() > 0);
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_connection_invite_details(cb.command_handle, handle, 4, Some(cb.get_callback())), error::INVALID_OPTION.code_num);
+    }
+
     #[test]
     fn test_vcx_connection_update_state() {
         init!("true");
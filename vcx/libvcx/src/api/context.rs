@@ -0,0 +1,240 @@
+extern crate libc;
+
+use self::libc::c_char;
+use utils::error;
+use context;
+use api::{ connection, credential, credential_def, disclosed_proof, issuer_credential, proof, schema, utils, wallet };
+
+// Calls on an object run in the context the object was created in. The calls below do not take
+// an object, so every one of them has a variant taking the context explicitly, for hosts whose
+// calls do not stay on the thread that selected a context with vcx_context_set_current.
+
+/// Defines `$name`, which calls `$module::$target` in the context given by its first argument.
+macro_rules! in_context {
+    ($(#[$attr:meta])* $name:ident => $module:ident :: $target:ident ($($arg:ident: $ty:ty),*)) => {
+        $(#[$attr])*
+        #[no_mangle]
+        pub extern fn $name(context_handle: u32, $($arg: $ty),*) -> u32 {
+            if !context::is_valid_handle(context_handle) {
+                ::error::reset_current_error();
+                return error::INVALID_CONTEXT_HANDLE.code_num;
+            }
+            context::with_context(context_handle, || $module::$target($($arg),*))
+        }
+    }
+}
+
+in_context!(
+    /// Like vcx_connection_create, in the context given by context_handle
+    vcx_connection_create_in_context => connection::vcx_connection_create(command_handle: u32, source_id: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, connection_handle: u32)>));
+in_context!(
+    /// Like vcx_connection_create_with_invite, in the context given by context_handle
+    vcx_connection_create_with_invite_in_context => connection::vcx_connection_create_with_invite(command_handle: u32, source_id: *const c_char, invite_details: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>));
+in_context!(
+    /// Like vcx_connection_deserialize, in the context given by context_handle
+    vcx_connection_deserialize_in_context => connection::vcx_connection_deserialize(command_handle: u32, connection_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, connection_handle: u32)>));
+
+in_context!(
+    /// Like vcx_credential_create_with_offer, in the context given by context_handle
+    vcx_credential_create_with_offer_in_context => credential::vcx_credential_create_with_offer(command_handle: u32, source_id: *const c_char, offer: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>));
+in_context!(
+    /// Like vcx_credential_deserialize, in the context given by context_handle
+    vcx_credential_deserialize_in_context => credential::vcx_credential_deserialize(command_handle: u32, credential_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, handle: u32)>));
+
+in_context!(
+    /// Like vcx_credentialdef_create, in the context given by context_handle
+    vcx_credentialdef_create_in_context => credential_def::vcx_credentialdef_create(command_handle: u32, source_id: *const c_char, credentialdef_name: *const c_char, schema_id: *const c_char, issuer_did: *const c_char, tag: *const c_char, config: *const c_char, payment_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>));
+in_context!(
+    /// Like vcx_credentialdef_prepare_from_ledger, in the context given by context_handle
+    vcx_credentialdef_prepare_from_ledger_in_context => credential_def::vcx_credentialdef_prepare_from_ledger(command_handle: u32, source_id: *const c_char, cred_def_id: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>));
+in_context!(
+    /// Like vcx_credentialdef_prepare_for_endorser, in the context given by context_handle
+    vcx_credentialdef_prepare_for_endorser_in_context => credential_def::vcx_credentialdef_prepare_for_endorser(command_handle: u32, source_id: *const c_char, credentialdef_name: *const c_char, schema_id: *const c_char, issuer_did: *const c_char, tag: *const c_char, endorser: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32, credentialdef_txn: *const c_char)>));
+in_context!(
+    /// Like vcx_credentialdef_deserialize, in the context given by context_handle
+    vcx_credentialdef_deserialize_in_context => credential_def::vcx_credentialdef_deserialize(command_handle: u32, credentialdef_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>));
+in_context!(
+    /// Like vcx_credentialdef_list, in the context given by context_handle
+    vcx_credentialdef_list_in_context => credential_def::vcx_credentialdef_list(command_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, cred_defs: *const c_char)>));
+
+in_context!(
+    /// Like vcx_disclosed_proof_create_with_request, in the context given by context_handle
+    vcx_disclosed_proof_create_with_request_in_context => disclosed_proof::vcx_disclosed_proof_create_with_request(command_handle: u32, source_id: *const c_char, proof_req: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, handle: u32)>));
+in_context!(
+    /// Like vcx_disclosed_proof_deserialize, in the context given by context_handle
+    vcx_disclosed_proof_deserialize_in_context => disclosed_proof::vcx_disclosed_proof_deserialize(command_handle: u32, proof_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, handle: u32)>));
+
+in_context!(
+    /// Like vcx_issuer_create_credential, in the context given by context_handle
+    vcx_issuer_create_credential_in_context => issuer_credential::vcx_issuer_create_credential(command_handle: u32, source_id: *const c_char, cred_def_id: *const c_char, issuer_did: *const c_char, credential_data: *const c_char, credential_name: *const c_char, price: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>));
+in_context!(
+    /// Like vcx_issuer_credential_deserialize, in the context given by context_handle
+    vcx_issuer_credential_deserialize_in_context => issuer_credential::vcx_issuer_credential_deserialize(command_handle: u32, credential_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>));
+in_context!(
+    /// Like vcx_issuer_credential_batch_create, in the context given by context_handle
+    vcx_issuer_credential_batch_create_in_context => issuer_credential::vcx_issuer_credential_batch_create(command_handle: u32, source_id: *const c_char, cred_def_id: *const c_char, issuer_did: *const c_char, credential_data: *const c_char, connections: *const c_char, credential_name: *const c_char, price: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, batch_handle: u32)>));
+
+in_context!(
+    /// Like vcx_proof_create, in the context given by context_handle
+    vcx_proof_create_in_context => proof::vcx_proof_create(command_handle: u32, source_id: *const c_char, requested_attrs: *const c_char, requested_predicates: *const c_char, revocation_interval: *const c_char, name: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32)>));
+in_context!(
+    /// Like vcx_proof_template_create, in the context given by context_handle
+    vcx_proof_template_create_in_context => proof::vcx_proof_template_create(command_handle: u32, name: *const c_char, requested_attrs: *const c_char, requested_predicates: *const c_char, revocation_interval: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_proof_template_get, in the context given by context_handle
+    vcx_proof_template_get_in_context => proof::vcx_proof_template_get(command_handle: u32, name: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, template: *const c_char)>));
+in_context!(
+    /// Like vcx_proof_template_list, in the context given by context_handle
+    vcx_proof_template_list_in_context => proof::vcx_proof_template_list(command_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, templates: *const c_char)>));
+in_context!(
+    /// Like vcx_proof_template_delete, in the context given by context_handle
+    vcx_proof_template_delete_in_context => proof::vcx_proof_template_delete(command_handle: u32, name: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_proof_create_from_template, in the context given by context_handle
+    vcx_proof_create_from_template_in_context => proof::vcx_proof_create_from_template(command_handle: u32, source_id: *const c_char, template_name: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32)>));
+in_context!(
+    /// Like vcx_proof_deserialize, in the context given by context_handle
+    vcx_proof_deserialize_in_context => proof::vcx_proof_deserialize(command_handle: u32, proof_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32)>));
+
+in_context!(
+    /// Like vcx_schema_create, in the context given by context_handle
+    vcx_schema_create_in_context => schema::vcx_schema_create(command_handle: u32, source_id: *const c_char, schema_name: *const c_char, version: *const c_char, schema_data: *const c_char, payment_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>));
+in_context!(
+    /// Like vcx_schema_prepare_for_endorser, in the context given by context_handle
+    vcx_schema_prepare_for_endorser_in_context => schema::vcx_schema_prepare_for_endorser(command_handle: u32, source_id: *const c_char, schema_name: *const c_char, version: *const c_char, schema_data: *const c_char, endorser: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, schema_handle: u32, schema_txn: *const c_char)>));
+in_context!(
+    /// Like vcx_schema_deserialize, in the context given by context_handle
+    vcx_schema_deserialize_in_context => schema::vcx_schema_deserialize(command_handle: u32, schema_data: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, schema_handle: u32)>));
+in_context!(
+    /// Like vcx_schema_get_attributes, in the context given by context_handle
+    vcx_schema_get_attributes_in_context => schema::vcx_schema_get_attributes(command_handle: u32, source_id: *const c_char, schema_id: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, s_handle: u32, schema_attrs: *const c_char)>));
+in_context!(
+    /// Like vcx_schema_list, in the context given by context_handle
+    vcx_schema_list_in_context => schema::vcx_schema_list(command_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, schemas: *const c_char)>));
+
+in_context!(
+    /// Like vcx_agent_provision_async, in the context given by context_handle
+    vcx_agent_provision_async_in_context => utils::vcx_agent_provision_async(command_handle: u32, config: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, _config: *const c_char)>));
+in_context!(
+    /// Like vcx_agent_update_info, in the context given by context_handle
+    vcx_agent_update_info_in_context => utils::vcx_agent_update_info(command_handle: u32, json: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_ledger_get_fees, in the context given by context_handle
+    vcx_ledger_get_fees_in_context => utils::vcx_ledger_get_fees(command_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, fees: *const c_char)>));
+in_context!(
+    /// Like vcx_ledger_cache_invalidate, in the context given by context_handle
+    vcx_ledger_cache_invalidate_in_context => utils::vcx_ledger_cache_invalidate(ledger_id: *const c_char));
+in_context!(
+    /// Like vcx_endorse_transaction, in the context given by context_handle
+    vcx_endorse_transaction_in_context => utils::vcx_endorse_transaction(command_handle: u32, transaction: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, endorsed_txn: *const c_char)>));
+in_context!(
+    /// Like vcx_messages_download, in the context given by context_handle
+    vcx_messages_download_in_context => utils::vcx_messages_download(command_handle: u32, message_status: *const c_char, uids: *const c_char, pw_dids: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, messages: *const c_char)>));
+in_context!(
+    /// Like vcx_messages_update_status, in the context given by context_handle
+    vcx_messages_update_status_in_context => utils::vcx_messages_update_status(command_handle: u32, message_status: *const c_char, msg_json: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_messages_dispatch, in the context given by context_handle
+    vcx_messages_dispatch_in_context => utils::vcx_messages_dispatch(command_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err: u32, changes: *const c_char)>));
+in_context!(
+    /// Like vcx_messages_start_dispatcher, in the context given by context_handle
+    vcx_messages_start_dispatcher_in_context => utils::vcx_messages_start_dispatcher(interval_ms: u32));
+in_context!(
+    /// Like vcx_messages_stop_dispatcher, in the context given by context_handle
+    vcx_messages_stop_dispatcher_in_context => utils::vcx_messages_stop_dispatcher());
+
+in_context!(
+    /// Like vcx_wallet_get_token_info, in the context given by context_handle
+    vcx_wallet_get_token_info_in_context => wallet::vcx_wallet_get_token_info(command_handle: u32, payment_handle: u32, cb: Option<extern fn(xcommand_handle: u32, err:u32, *const c_char)>));
+in_context!(
+    /// Like vcx_wallet_create_payment_address, in the context given by context_handle
+    vcx_wallet_create_payment_address_in_context => wallet::vcx_wallet_create_payment_address(command_handle: u32, seed: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err:u32, address: *const c_char)>));
+in_context!(
+    /// Like vcx_wallet_add_record, in the context given by context_handle
+    vcx_wallet_add_record_in_context => wallet::vcx_wallet_add_record(command_handle: u32, type_: *const c_char, id: *const c_char, value: *const c_char, tags_json: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_wallet_update_record_value, in the context given by context_handle
+    vcx_wallet_update_record_value_in_context => wallet::vcx_wallet_update_record_value(command_handle: u32, type_: *const c_char, id: *const c_char, value: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_wallet_update_record_tags, in the context given by context_handle
+    vcx_wallet_update_record_tags_in_context => wallet::vcx_wallet_update_record_tags(command_handle: u32, type_: *const c_char, id: *const c_char, tags: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_wallet_add_record_tags, in the context given by context_handle
+    vcx_wallet_add_record_tags_in_context => wallet::vcx_wallet_add_record_tags(command_handle: u32, type_: *const c_char, id: *const c_char, tags: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_wallet_delete_record_tags, in the context given by context_handle
+    vcx_wallet_delete_record_tags_in_context => wallet::vcx_wallet_delete_record_tags(command_handle: u32, type_: *const c_char, id: *const c_char, tags: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_wallet_get_record, in the context given by context_handle
+    vcx_wallet_get_record_in_context => wallet::vcx_wallet_get_record(command_handle: u32, type_: *const c_char, id: *const c_char, options_json: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, record_json: *const c_char)>));
+in_context!(
+    /// Like vcx_wallet_delete_record, in the context given by context_handle
+    vcx_wallet_delete_record_in_context => wallet::vcx_wallet_delete_record(command_handle: u32, type_: *const c_char, id: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+in_context!(
+    /// Like vcx_wallet_send_tokens, in the context given by context_handle
+    vcx_wallet_send_tokens_in_context => wallet::vcx_wallet_send_tokens(command_handle: u32, payment_handle: u32, tokens: *const c_char, recipient: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32, receipt: *const c_char)>));
+in_context!(
+    /// Like vcx_wallet_export, in the context given by context_handle
+    vcx_wallet_export_in_context => wallet::vcx_wallet_export(command_handle: u32, path: *const c_char, backup_key: *const c_char, cb: Option<extern fn(xcommand_handle: u32, err: u32)>));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::time::Duration;
+    use utils::libindy::return_types_u32;
+    use api::{ vcx, VcxStateType };
+
+    fn create_context() -> u32 {
+        let config = json!({
+            "enable_test_mode": "true",
+            "wallet_key": "tenant_wallet_key",
+            "wallet_name": "tenant_wallet",
+        }).to_string();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx::vcx_context_create_with_config(cb.command_handle,
+                                                       CString::new(config).unwrap().into_raw(),
+                                                       Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        cb.receive(Some(Duration::from_secs(10))).unwrap()
+    }
+
+    #[test]
+    fn test_objects_are_used_in_their_context() {
+        init!("true");
+        let context_handle = create_context();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_connection_create_in_context(context_handle,
+                                                    cb.command_handle,
+                                                    CString::new("tenant_connection").unwrap().into_raw(),
+                                                    Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let handle = cb.receive(Some(Duration::from_secs(10))).unwrap();
+        assert_eq!(::connection::get_context(handle), context_handle);
+        assert!(!::connection::is_valid_handle(handle));
+
+        // the calling thread never selected the context
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(connection::vcx_connection_get_state(cb.command_handle, handle, Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), VcxStateType::VcxStateInitialized as u32);
+
+        assert_eq!(vcx::vcx_context_release(context_handle, false), error::SUCCESS.code_num);
+        assert_eq!(connection::vcx_connection_get_state(0, handle, Some(cb.get_callback())),
+                   error::INVALID_CONNECTION_HANDLE.code_num);
+    }
+
+    #[test]
+    fn test_invalid_context() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_connection_create_in_context(1_000_000,
+                                                    cb.command_handle,
+                                                    CString::new("tenant_connection").unwrap().into_raw(),
+                                                    Some(cb.get_callback())),
+                   error::INVALID_CONTEXT_HANDLE.code_num);
+        assert_eq!(vcx_messages_stop_dispatcher_in_context(1_000_000), error::INVALID_CONTEXT_HANDLE.code_num);
+    }
+}
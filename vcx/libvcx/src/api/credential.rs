@@ -8,9 +8,8 @@ use utils::error::error_string;
 use connection;
 use credential;
 use std::ptr;
-use error::credential::CredentialError;
-use error::ToErrorCode;
 use utils::threadpool::spawn;
+use context;
 
 /// Retrieves Payment Info from a Credential
 ///
@@ -28,6 +27,8 @@ use utils::threadpool::spawn;
 pub extern fn vcx_credential_get_payment_info(command_handle: u32,
                                                credential_handle: u32,
                                                cb: Option<extern fn(xcommand_handle: u32, err: u32, *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(credential_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     spawn(move|| {
         match credential::get_payment_information(credential_handle) {
@@ -49,9 +50,9 @@ pub extern fn vcx_credential_get_payment_info(command_handle: u32,
             Err(e) => {
                 warn!("vcx_credential_get_payment_info(command_handle: {}, rc: {}, msg: {})",
                       command_handle,
-                      e.to_error_code(),
+                      e.ffi_code(),
                       "{}".to_string());
-                cb(command_handle, e.to_error_code(), ptr::null_mut());
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
             }
         };
 
@@ -82,6 +83,7 @@ pub extern fn vcx_credential_create_with_offer(command_handle: u32,
                                                source_id: *const c_char,
                                                offer: *const c_char,
                                                cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
@@ -99,8 +101,8 @@ pub extern fn vcx_credential_create_with_offer(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_credential_create_with_offer_cb(command_handle: {}, source_id: {}, rc: {}, handle: {})",
-                      command_handle, source_id, x.to_error_code(), 0);
-                cb(command_handle, x.to_error_code(), 0);
+                      command_handle, source_id, x.ffi_code(), 0);
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -127,9 +129,11 @@ pub extern fn vcx_credential_create_with_offer(command_handle: u32,
 pub extern fn vcx_get_credential(command_handle: u32,
                                  credential_handle: u32,
                                  cb: Option<extern fn(xcommand_handle:u32, err: u32, credential: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(credential_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     if !credential::is_valid_handle(credential_handle) {
-        return CredentialError::InvalidHandle().to_error_code();
+        return error::INVALID_CREDENTIAL_HANDLE.code_num;
     }
 
     let source_id = credential::get_source_id(credential_handle).unwrap_or_default();
@@ -146,8 +150,8 @@ pub extern fn vcx_get_credential(command_handle: u32,
             },
             Err(e) => {
                 error!("vcx_get_credential_cb(commmand_handle: {}, rc: {}, msg: {}) source_id: {}",
-                    command_handle, e.to_error_code(), "".to_string(), source_id);
-                cb(command_handle, e.to_error_code(), ptr::null_mut());
+                    command_handle, e.ffi_code(), "".to_string(), source_id);
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -180,6 +184,8 @@ pub extern fn vcx_credential_create_with_msgid(command_handle: u32,
                                                connection_handle: u32,
                                                msg_id: *const c_char,
                                                cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32, offer: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(connection::get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
@@ -204,12 +210,12 @@ pub extern fn vcx_credential_create_with_msgid(command_handle: u32,
                     },
                     Err(e) => {
                         warn!("vcx_credential_create_with_offer_cb(command_handle: {}, source_id: {}, rc: {}, handle: {}) source_id: {}",
-                              command_handle, source_id, e.to_error_code(), 0, source_id);
-                        cb(command_handle, e.to_error_code(), 0, ptr::null_mut());
+                              command_handle, source_id, e.ffi_code(), 0, source_id);
+                        cb(command_handle, e.ffi_code(), 0, ptr::null_mut());
                     },
                 };
             },
-            Err(e) => cb(command_handle, e.to_error_code(), 0, ptr::null_mut()),
+            Err(e) => cb(command_handle, e.ffi_code(), 0, ptr::null_mut()),
         };
 
         Ok(())
@@ -238,6 +244,8 @@ pub extern fn vcx_credential_send_request(command_handle: u32,
                                           connection_handle: u32,
                                           payment_handle: u32,
                                           cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -263,7 +271,7 @@ pub extern fn vcx_credential_send_request(command_handle: u32,
             Err(e) => {
                 warn!("vcx_credential_send_request_cb(command_handle: {}, rc: {}) source_id: {}",
                       command_handle, e.to_string(), source_id);
-                cb(command_handle,e.to_error_code());
+                cb(command_handle,e.ffi_code());
             },
         };
 
@@ -289,6 +297,8 @@ pub extern fn vcx_credential_send_request(command_handle: u32,
 pub extern fn vcx_credential_get_offers(command_handle: u32,
                                    connection_handle: u32,
                                    cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_offers: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(connection::get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -310,7 +320,7 @@ pub extern fn vcx_credential_get_offers(command_handle: u32,
             Err(x) => {
                 error!("vcx_credential_get_offers_cb(command_handle: {}, rc: {}, msg: null)",
                       command_handle, x.to_string());
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -337,6 +347,8 @@ pub extern fn vcx_credential_get_offers(command_handle: u32,
 pub extern fn vcx_credential_update_state(command_handle: u32,
                                           credential_handle: u32,
                                           cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -353,8 +365,8 @@ pub extern fn vcx_credential_update_state(command_handle: u32,
             Ok(_) => (),
             Err(e) => {
                 error!("vcx_credential_update_state_cb(command_handle: {}, rc: {}, state: {}), source_id: {:?}",
-                      command_handle, error_string(e), 0, source_id);
-                cb(command_handle, e, 0)
+                      command_handle, e, 0, source_id);
+                cb(command_handle, e.ffi_code(), 0)
             }
         }
 
@@ -366,8 +378,8 @@ pub extern fn vcx_credential_update_state(command_handle: u32,
             },
             Err(e) => {
                 error!("vcx_credential_update_state_cb(command_handle: {}, rc: {}, state: {}), source_id: {:?}",
-                      command_handle, error_string(e.to_error_code()), 0, source_id);
-                cb(command_handle, e.to_error_code(), 0)
+                      command_handle, e, 0, source_id);
+                cb(command_handle, e.ffi_code(), 0)
             }
         };
 
@@ -391,6 +403,8 @@ pub extern fn vcx_credential_update_state(command_handle: u32,
 pub extern fn vcx_credential_get_state(command_handle: u32,
                                        handle: u32,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     if !credential::is_valid_handle(handle) {
@@ -410,8 +424,8 @@ pub extern fn vcx_credential_get_state(command_handle: u32,
             },
             Err(e) => {
                 error!("vcx_credential_get_state_cb(command_handle: {}, rc: {}, state: {}), source_id: {:?}",
-                      command_handle, error_string(e.to_error_code()), 0, source_id);
-                cb(command_handle, e.to_error_code(), 0)
+                      command_handle, e, 0, source_id);
+                cb(command_handle, e.ffi_code(), 0)
             }
         };
 
@@ -437,6 +451,8 @@ pub extern fn vcx_credential_get_state(command_handle: u32,
 pub extern fn vcx_credential_serialize(command_handle: u32,
                                        handle: u32,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, data: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -458,8 +474,8 @@ pub extern fn vcx_credential_serialize(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_credential_serialize_cb(command_handle: {}, rc: {}, data: {}), source_id: {:?}",
-                    command_handle, error_string(x), 0, source_id);
-                cb(command_handle,x,ptr::null_mut());
+                    command_handle, x, 0, source_id);
+                cb(command_handle,x.ffi_code(),ptr::null_mut());
             },
         };
 
@@ -485,6 +501,7 @@ pub extern fn vcx_credential_serialize(command_handle: u32,
 pub extern fn vcx_credential_deserialize(command_handle: u32,
                                          credential_data: *const c_char,
                                          cb: Option<extern fn(xcommand_handle: u32, err: u32, handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(credential_data, error::INVALID_OPTION.code_num);
@@ -502,8 +519,8 @@ pub extern fn vcx_credential_deserialize(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_credential_deserialize_cb(command_handle: {}, rc: {}, credential_handle: {}) source_id: {}",
-                      command_handle, error_string(x), 0, "");
-                cb(command_handle, x, 0);
+                      command_handle, x, 0, "");
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -522,12 +539,14 @@ pub extern fn vcx_credential_deserialize(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_credential_release(handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(handle));
     let source_id = credential::get_source_id(handle).unwrap_or_default();
     match credential::release(handle) {
         Ok(_) => info!("vcx_credential_release(handle: {}, rc: {}), source_id: {:?}",
                        handle, error_string(0), source_id),
         Err(e) => error!("vcx_credential_release(handle: {}, rc: {}), source_id: {:?}",
-                         handle, error_string(e.to_error_code()), source_id),
+                         handle, e, source_id),
     };
     error::SUCCESS.code_num
 }
@@ -554,6 +573,8 @@ pub extern fn vcx_credential_release(handle: u32) -> u32 {
 pub extern fn vcx_credential_get_payment_txn(command_handle: u32,
                                              handle: u32,
                                              cb: Option<extern fn(xcommand_handle: u32, err: u32, txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential::get_context(handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -581,7 +602,7 @@ pub extern fn vcx_credential_get_payment_txn(command_handle: u32,
             Err(x) => {
                 error!("vcx_credential_get_payment_txn_cb(command_handle: {}, rc: {}, txn: {}), source_id: {}",
                        command_handle, x.to_string(), "null", credential::get_source_id(handle).unwrap_or_default());
-                cb(command_handle, x.to_error_code(), ptr::null());
+                cb(command_handle, x.ffi_code(), ptr::null());
             },
         };
 
@@ -721,7 +742,7 @@ mod tests {
         cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
 
         let cb = return_types_u32::Return_U32_STR::new().unwrap();
-        assert_eq!(vcx_get_credential(cb.command_handle, bad_handle, Some(cb.get_callback())), CredentialError::InvalidHandle().to_error_code());
+        assert_eq!(vcx_get_credential(cb.command_handle, bad_handle, Some(cb.get_callback())), error::INVALID_CREDENTIAL_HANDLE.code_num);
 
         let handle = credential::from_string(DEFAULT_SERIALIZED_CREDENTIAL).unwrap();
         let cb = return_types_u32::Return_U32_STR::new().unwrap();
@@ -8,8 +8,8 @@ use utils::error::error_string;
 use std::ptr;
 use credential_def;
 use settings;
-use error::ToErrorCode;
 use utils::threadpool::spawn;
+use context;
 
 /// Create a new CredentialDef object that can create credential definitions on the ledger
 ///
@@ -26,8 +26,9 @@ use utils::threadpool::spawn;
 ///
 /// tag: way to create a unique credential def with the same schema and issuer did.
 ///
-//Todo: Provide more info about the config
 /// config: revocation info
+///     {"support_revocation": true, "tails_file": "/tmp/tails", "max_creds": 5}
+///     tails_file is the directory the tails file is written to. Use "{}" for no revocation support.
 ///
 /// cb: Callback that provides CredentialDef handle and error status of request.
 ///
@@ -45,6 +46,7 @@ pub extern fn vcx_credentialdef_create(command_handle: u32,
                                        config: *const c_char,
                                        payment_handle: u32,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(credentialdef_name, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
@@ -58,7 +60,7 @@ pub extern fn vcx_credentialdef_create(command_handle: u32,
     } else {
         match settings::get_config_value(settings::CONFIG_INSTITUTION_DID) {
             Ok(x) => x,
-            Err(x) => return x
+            Err(x) => return x.ffi_code()
         }
     };
     info!("vcx_credential_def_create(command_handle: {}, source_id: {}, credentialdef_name: {} schema_id: {}, issuer_did: {}, tag: {}, config: {})",
@@ -84,8 +86,8 @@ pub extern fn vcx_credentialdef_create(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_credential_def_create_cb(command_handle: {}, rc: {}, credentialdef_handle: {}), source_id: {:?}",
-                      command_handle, error_string(x.to_error_code()), 0, "");
-                (x.to_error_code(), 0)
+                      command_handle, x, 0, "");
+                (x.ffi_code(), 0)
             },
         };
         cb(command_handle, rc, handle);
@@ -96,6 +98,232 @@ pub extern fn vcx_credentialdef_create(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Create a new CredentialDef object from a credential definition already written to the ledger
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Enterprise's personal identification for the user.
+///
+/// cred_def_id: id of the credential definition on the ledger
+///
+/// cb: Callback that provides CredentialDef handle and error status of request.
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_credentialdef_prepare_from_ledger(command_handle: u32,
+                                                    source_id: *const c_char,
+                                                    cred_def_id: *const c_char,
+                                                    cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(cred_def_id, error::INVALID_OPTION.code_num);
+    info!("vcx_credentialdef_prepare_from_ledger(command_handle: {}, source_id: {}, cred_def_id: {})",
+          command_handle, source_id, cred_def_id);
+
+    spawn(move|| {
+        match credential_def::prepare_from_ledger(source_id, &cred_def_id) {
+            Ok(x) => {
+                info!("vcx_credentialdef_prepare_from_ledger_cb(command_handle: {}, rc: {}, credentialdef_handle: {}), source_id: {:?}",
+                      command_handle, error_string(0), x, credential_def::get_source_id(x).unwrap_or_default());
+                cb(command_handle, error::SUCCESS.code_num, x);
+            },
+            Err(x) => {
+                warn!("vcx_credentialdef_prepare_from_ledger_cb(command_handle: {}, rc: {}, credentialdef_handle: {})",
+                      command_handle, x, 0);
+                cb(command_handle, x.ffi_code(), 0);
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Create a new CredentialDef object and the transaction writing it to the ledger, for an endorser
+/// to sign and write instead of the issuer. Used when issuer_did has no permission to write to the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Enterprise's personal identification for the user.
+///
+/// credentialdef_name: Name of credential definition
+///
+/// schema_id: The schema id given during the creation of the schema
+///
+/// issuer_did: did corresponding to entity issuing a credential, NULL to use the institution_did
+///
+/// tag: way to create a unique credential def with the same schema and issuer did.
+///
+/// endorser: DID of the endorser that is going to write the credential definition to the ledger
+///
+/// cb: Callback that provides CredentialDef handle, the transaction to be endorsed and error status of request.
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_credentialdef_prepare_for_endorser(command_handle: u32,
+                                                     source_id: *const c_char,
+                                                     credentialdef_name: *const c_char,
+                                                     schema_id: *const c_char,
+                                                     issuer_did: *const c_char,
+                                                     tag: *const c_char,
+                                                     endorser: *const c_char,
+                                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32, credentialdef_txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(credentialdef_name, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(schema_id, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(tag, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(endorser, error::INVALID_OPTION.code_num);
+
+    let issuer_did: String = if !issuer_did.is_null() {
+        check_useful_c_str!(issuer_did, error::INVALID_OPTION.code_num);
+        issuer_did.to_owned()
+    } else {
+        match settings::get_config_value(settings::CONFIG_INSTITUTION_DID) {
+            Ok(x) => x,
+            Err(x) => return x.ffi_code()
+        }
+    };
+    info!("vcx_credentialdef_prepare_for_endorser(command_handle: {}, source_id: {}, credentialdef_name: {} schema_id: {}, issuer_did: {}, tag: {}, endorser: {})",
+          command_handle,
+          source_id,
+          credentialdef_name,
+          schema_id,
+          issuer_did,
+          tag,
+          endorser);
+
+    spawn(move|| {
+        match credential_def::prepare_credentialdef_for_endorser(source_id,
+                                                                 credentialdef_name,
+                                                                 issuer_did,
+                                                                 schema_id,
+                                                                 tag,
+                                                                 &endorser) {
+            Ok((handle, txn)) => {
+                info!("vcx_credentialdef_prepare_for_endorser_cb(command_handle: {}, rc: {}, credentialdef_handle: {}, txn: {}), source_id: {:?}",
+                      command_handle, error_string(0), handle, txn, credential_def::get_source_id(handle).unwrap_or_default());
+                let txn = CStringUtils::string_to_cstring(txn);
+                cb(command_handle, error::SUCCESS.code_num, handle, txn.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_credentialdef_prepare_for_endorser_cb(command_handle: {}, rc: {}, credentialdef_handle: {}, txn: {})",
+                      command_handle, x, 0, "null");
+                cb(command_handle, x.ffi_code(), 0, ptr::null());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Write the credential definition transaction, signed by the endorser, to the ledger
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// credentialdef_handle: handle of the credentialdef returned by vcx_credentialdef_prepare_for_endorser
+///
+/// endorsed_txn: the transaction from vcx_credentialdef_prepare_for_endorser signed by the endorser
+///
+/// cb: Callback that provides error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_credentialdef_submit_endorsed(command_handle: u32,
+                                                credentialdef_handle: u32,
+                                                endorsed_txn: *const c_char,
+                                                cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential_def::get_context(credentialdef_handle));
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(endorsed_txn, error::INVALID_OPTION.code_num);
+
+    let source_id = credential_def::get_source_id(credentialdef_handle).unwrap_or_default();
+    info!("vcx_credentialdef_submit_endorsed(command_handle: {}, credentialdef_handle: {}, endorsed_txn: {}), source_id: {:?}",
+          command_handle, credentialdef_handle, endorsed_txn, source_id);
+
+    if !credential_def::is_valid_handle(credentialdef_handle) {
+        return error::INVALID_CREDENTIAL_DEF_HANDLE.code_num;
+    };
+
+    spawn(move|| {
+        match credential_def::submit_endorsed(credentialdef_handle, &endorsed_txn) {
+            Ok(()) => {
+                info!("vcx_credentialdef_submit_endorsed_cb(command_handle: {}, credentialdef_handle: {}, rc: {}), source_id: {:?}",
+                      command_handle, credentialdef_handle, error_string(0), source_id);
+                cb(command_handle, error::SUCCESS.code_num);
+            },
+            Err(x) => {
+                warn!("vcx_credentialdef_submit_endorsed_cb(command_handle: {}, credentialdef_handle: {}, rc: {}), source_id: {:?}",
+                      command_handle, credentialdef_handle, x, source_id);
+                cb(command_handle, x.ffi_code());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get the state of the credentialdef: 0 - built for an endorser, 1 - written to the ledger
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// credentialdef_handle: Credentialdef handle that was provided during creation. Used to access credentialdef object
+///
+/// cb: Callback that provides the state and error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_credentialdef_get_state(command_handle: u32,
+                                          credentialdef_handle: u32,
+                                          cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential_def::get_context(credentialdef_handle));
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    let source_id = credential_def::get_source_id(credentialdef_handle).unwrap_or_default();
+    info!("vcx_credentialdef_get_state(command_handle: {}, credentialdef_handle: {}), source_id: {:?}",
+          command_handle, credentialdef_handle, source_id);
+
+    if !credential_def::is_valid_handle(credentialdef_handle) {
+        return error::INVALID_CREDENTIAL_DEF_HANDLE.code_num;
+    };
+
+    spawn(move|| {
+        match credential_def::get_state(credentialdef_handle) {
+            Ok(x) => {
+                info!("vcx_credentialdef_get_state_cb(command_handle: {}, credentialdef_handle: {}, rc: {}, state: {}), source_id: {:?}",
+                      command_handle, credentialdef_handle, error_string(0), x, source_id);
+                cb(command_handle, error::SUCCESS.code_num, x);
+            },
+            Err(x) => {
+                warn!("vcx_credentialdef_get_state_cb(command_handle: {}, credentialdef_handle: {}, rc: {}, state: {}), source_id: {:?}",
+                      command_handle, credentialdef_handle, x, 0, source_id);
+                cb(command_handle, x.ffi_code(), 0);
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Takes the credentialdef object and returns a json string of all its attributes
 ///
 /// #Params
@@ -111,6 +339,8 @@ pub extern fn vcx_credentialdef_create(command_handle: u32,
 pub extern fn vcx_credentialdef_serialize(command_handle: u32,
                                      credentialdef_handle: u32,
                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_state: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential_def::get_context(credentialdef_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -132,8 +362,8 @@ pub extern fn vcx_credentialdef_serialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_credentialdef_serialize_cb(command_handle: {}, credentialdef_handle: {}, rc: {}, state: {}), source_id: {:?}",
-                      command_handle, credentialdef_handle, error_string(x), "null", source_id);
-                cb(command_handle, x, ptr::null_mut());
+                      command_handle, credentialdef_handle, x, "null", source_id);
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -158,6 +388,7 @@ pub extern fn vcx_credentialdef_serialize(command_handle: u32,
 pub extern fn vcx_credentialdef_deserialize(command_handle: u32,
                                        credentialdef_data: *const c_char,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(credentialdef_data, error::INVALID_OPTION.code_num);
@@ -172,7 +403,7 @@ pub extern fn vcx_credentialdef_deserialize(command_handle: u32,
                 (error::SUCCESS.code_num, x)
             },
             Err(e) => {
-                let error_code = e.to_error_code();
+                let error_code = e.ffi_code();
                 warn!("vcx_credentialdef_deserialize_cb(command_handle: {}, rc: {}, handle: {}), source_id: {}",
                       command_handle, error_code, 0, "");
                 (error_code, 0)
@@ -199,6 +430,8 @@ pub extern fn vcx_credentialdef_deserialize(command_handle: u32,
 pub extern fn vcx_credentialdef_get_cred_def_id(command_handle: u32,
                                                 cred_def_handle: u32,
                                                 cb: Option<extern fn(xcommand_handle: u32, err: u32, cred_def_id: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential_def::get_context(cred_def_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     let source_id = credential_def::get_source_id(cred_def_handle).unwrap_or_default();
@@ -218,7 +451,7 @@ pub extern fn vcx_credentialdef_get_cred_def_id(command_handle: u32,
             Err(x) => {
                 warn!("vcx_credentialdef_get_cred_def_id(command_handle: {}, cred_def_handle: {}, rc: {}, cred_def_id: {}) source_id: {}",
                       command_handle, cred_def_handle, x.to_string(), "", source_id);
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -250,6 +483,8 @@ pub extern fn vcx_credentialdef_get_cred_def_id(command_handle: u32,
 pub extern fn vcx_credentialdef_get_payment_txn(command_handle: u32,
                                          handle: u32,
                                          cb: Option<extern fn(xcommand_handle: u32, err: u32, txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential_def::get_context(handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -277,7 +512,46 @@ pub extern fn vcx_credentialdef_get_payment_txn(command_handle: u32,
             Err(x) => {
                 error!("vcx_credentialdef_get_payment_txn_cb(command_handle: {}, rc: {}, txn: {}), source_id: {}",
                        command_handle, x.to_string(), "null", credential_def::get_source_id(handle).unwrap_or_default());
-                cb(command_handle, x.to_error_code(), ptr::null());
+                cb(command_handle, x.ffi_code(), ptr::null());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// List the credential definitions created with this wallet
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides the credential definitions and error status of request
+///
+/// #Returns
+/// Error code as a u32
+///
+/// # Example cred_defs -> [{"source_id":"1","cred_def_id":"2hoqvcwupRTUNkXn6ArYzs:3:CL:1697","name":"name","schema_id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4","tag":"tag"}]
+#[no_mangle]
+pub extern fn vcx_credentialdef_list(command_handle: u32,
+                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, cred_defs: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    info!("vcx_credentialdef_list(command_handle: {})", command_handle);
+
+    spawn(move|| {
+        match credential_def::list_created() {
+            Ok(x) => {
+                info!("vcx_credentialdef_list_cb(command_handle: {}, rc: {}, cred_defs: {})",
+                      command_handle, error_string(0), x);
+                let msg = CStringUtils::string_to_cstring(x);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_credentialdef_list_cb(command_handle: {}, rc: {}, cred_defs: {})",
+                      command_handle, x, "null");
+                cb(command_handle, x.ffi_code(), ptr::null());
             },
         };
 
@@ -296,6 +570,8 @@ pub extern fn vcx_credentialdef_get_payment_txn(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_credentialdef_release(credentialdef_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(credential_def::get_context(credentialdef_handle));
     let source_id = credential_def::get_source_id(credentialdef_handle).unwrap_or_default();
     match credential_def::release(credentialdef_handle) {
         Ok(_) => info!("vcx_credentialdef_release(credentialdef_handle: {}, rc: {}), source_id: {}",
@@ -315,7 +591,7 @@ mod tests {
     use std::time::Duration;
     use settings;
     use utils::libindy::return_types_u32;
-    use utils::constants::{SCHEMA_ID};
+    use utils::constants::{SCHEMA_ID, CRED_DEF_ID};
 
     #[test]
     fn test_vcx_create_credentialdef_success() {
@@ -415,4 +691,54 @@ mod tests {
         let rc = vcx_credentialdef_get_payment_txn(cb.command_handle, handle, Some(cb.get_callback()));
         cb.receive(Some(Duration::from_secs(10))).unwrap();
     }
+
+    #[test]
+    fn test_vcx_credentialdef_prepare_from_ledger() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_credentialdef_prepare_from_ledger(cb.command_handle,
+                                                         CString::new("Test Source ID").unwrap().into_raw(),
+                                                         CString::new(CRED_DEF_ID).unwrap().into_raw(),
+                                                         Some(cb.get_callback())), error::SUCCESS.code_num);
+        let handle = cb.receive(Some(Duration::from_secs(10))).unwrap();
+        assert_eq!(credential_def::get_cred_def_id(handle).unwrap(), CRED_DEF_ID);
+    }
+
+    #[test]
+    fn test_vcx_credentialdef_endorser_flow() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_U32_STR::new().unwrap();
+        assert_eq!(vcx_credentialdef_prepare_for_endorser(cb.command_handle,
+                                                          CString::new("Test Source ID").unwrap().into_raw(),
+                                                          CString::new("Test Credential Def").unwrap().into_raw(),
+                                                          CString::new(SCHEMA_ID).unwrap().into_raw(),
+                                                          ptr::null(),
+                                                          CString::new("tag").unwrap().into_raw(),
+                                                          CString::new("V4SGRU86Z58d6TV7PBUe6f").unwrap().into_raw(),
+                                                          Some(cb.get_callback())), error::SUCCESS.code_num);
+        let (handle, txn) = cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_credentialdef_get_state(cb.command_handle, handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), ::api::PublicEntityStateType::Built as u32);
+
+        let cb = return_types_u32::Return_U32::new().unwrap();
+        assert_eq!(vcx_credentialdef_submit_endorsed(cb.command_handle,
+                                                     handle,
+                                                     CString::new(txn.unwrap()).unwrap().into_raw(),
+                                                     Some(cb.get_callback())), error::SUCCESS.code_num);
+        cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_credentialdef_get_state(cb.command_handle, handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), ::api::PublicEntityStateType::Published as u32);
+    }
+
+    #[test]
+    fn test_vcx_credentialdef_list() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_credentialdef_list(cb.command_handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), Some("[]".to_string()));
+    }
 }
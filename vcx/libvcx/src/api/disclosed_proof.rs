@@ -7,8 +7,8 @@ use utils::error::error_string;
 use connection;
 use disclosed_proof;
 use std::ptr;
-use error::ToErrorCode;
 use utils::threadpool::spawn;
+use context;
 
 /// Create a proof for fulfilling a corresponding proof request
 ///
@@ -30,6 +30,7 @@ pub extern fn vcx_disclosed_proof_create_with_request(command_handle: u32,
                                                       source_id: *const c_char,
                                                       proof_req: *const c_char,
                                                       cb: Option<extern fn(xcommand_handle: u32, err: u32, handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
@@ -47,8 +48,8 @@ pub extern fn vcx_disclosed_proof_create_with_request(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_disclosed_proof_create_with_request_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
-                      command_handle, error_string(x.to_error_code()), 0, source_id);
-                cb(command_handle, x.to_error_code(), 0);
+                      command_handle, x, 0, source_id);
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -82,6 +83,8 @@ pub extern fn vcx_disclosed_proof_create_with_msgid(command_handle: u32,
                                                     connection_handle: u32,
                                                     msg_id: *const c_char,
                                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32, proof_req: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(connection::get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
@@ -104,11 +107,11 @@ pub extern fn vcx_disclosed_proof_create_with_msgid(command_handle: u32,
                         warn!("vcx_disclosed_proof_create_with_msgid_cb(command_handle: {}, rc: {}, handle: {}, proof_req: {}) source_id: {}",
                               command_handle, e.to_string(), 0, request, source_id);
                         let msg = CStringUtils::string_to_cstring(request);
-                        cb(command_handle, e.to_error_code(), 0, msg.as_ptr());
+                        cb(command_handle, e.ffi_code(), 0, msg.as_ptr());
                     },
                 };
             },
-            Err(e) => cb(command_handle, e.to_error_code(), 0, ptr::null()),
+            Err(e) => cb(command_handle, e.ffi_code(), 0, ptr::null()),
         };
 
         Ok(())
@@ -136,6 +139,8 @@ pub extern fn vcx_disclosed_proof_send_proof(command_handle: u32,
                                              proof_handle: u32,
                                              connection_handle: u32,
                                              cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -160,8 +165,8 @@ pub extern fn vcx_disclosed_proof_send_proof(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_disclosed_proof_send_proof_cb(command_handle: {}, rc: {}) source_id: {}",
-                      command_handle, error_string(x.to_error_code()), source_id);
-                cb(command_handle,x.to_error_code());
+                      command_handle, x, source_id);
+                cb(command_handle,x.ffi_code());
             },
         };
 
@@ -187,6 +192,8 @@ pub extern fn vcx_disclosed_proof_send_proof(command_handle: u32,
 pub extern fn vcx_disclosed_proof_get_requests(command_handle: u32,
                                                connection_handle: u32,
                                                cb: Option<extern fn(xcommand_handle: u32, err: u32, requests: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(connection::get_context(connection_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -208,7 +215,7 @@ pub extern fn vcx_disclosed_proof_get_requests(command_handle: u32,
             Err(x) => {
                 error!("vcx_disclosed_proof_get_requests_cb(command_handle: {}, rc: {}, msg: {})",
                       command_handle, error_string(0), x);
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -233,6 +240,8 @@ pub extern fn vcx_disclosed_proof_get_requests(command_handle: u32,
 pub extern fn vcx_disclosed_proof_get_state(command_handle: u32,
                                             proof_handle: u32,
                                             cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -253,8 +262,8 @@ pub extern fn vcx_disclosed_proof_get_state(command_handle: u32,
             },
             Err(e) => {
                 error!("vcx_disclosed_proof_get_state_cb(command_handle: {}, rc: {}, state: {}) source_id: {}",
-                       command_handle, error_string(e), 0, source_id);
-                cb(command_handle, e, 0)
+                       command_handle, e, 0, source_id);
+                cb(command_handle, e.ffi_code(), 0)
             }
         };
 
@@ -279,6 +288,8 @@ pub extern fn vcx_disclosed_proof_get_state(command_handle: u32,
 pub extern fn vcx_disclosed_proof_update_state(command_handle: u32,
                                                proof_handle: u32,
                                                cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -299,8 +310,8 @@ pub extern fn vcx_disclosed_proof_update_state(command_handle: u32,
             },
             Err(e) => {
                 error!("vcx_disclosed_proof_update_state_cb(command_handle: {}, rc: {}, state: {}) source_id: {}",
-                       command_handle, error_string(e), 0, source_id);
-                cb(command_handle, e, 0)
+                       command_handle, e, 0, source_id);
+                cb(command_handle, e.ffi_code(), 0)
             }
         };
 
@@ -325,6 +336,8 @@ pub extern fn vcx_disclosed_proof_update_state(command_handle: u32,
 pub extern fn vcx_disclosed_proof_serialize(command_handle: u32,
                                             proof_handle: u32,
                                             cb: Option<extern fn(xcommand_handle: u32, err: u32, data: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -346,8 +359,8 @@ pub extern fn vcx_disclosed_proof_serialize(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_disclosed_proof_serialize_cb(command_handle: {}, rc: {}, data: {}) source_id: {}",
-                       command_handle, error_string(x), 0, source_id);
-                cb(command_handle,x,ptr::null_mut());
+                       command_handle, x, 0, source_id);
+                cb(command_handle,x.ffi_code(),ptr::null_mut());
             },
         };
 
@@ -373,6 +386,7 @@ pub extern fn vcx_disclosed_proof_serialize(command_handle: u32,
 pub extern fn vcx_disclosed_proof_deserialize(command_handle: u32,
                                               proof_data: *const c_char,
                                               cb: Option<extern fn(xcommand_handle: u32, err: u32, handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(proof_data, error::INVALID_OPTION.code_num);
@@ -390,8 +404,8 @@ pub extern fn vcx_disclosed_proof_deserialize(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_disclosed_proof_deserialize_cb(command_handle: {}, rc: {}, proof_handle: {}) source_id: {}",
-                       command_handle, error_string(x.to_error_code()), 0, "");
-                cb(command_handle, x.to_error_code(), 0);
+                       command_handle, x, 0, "");
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -416,6 +430,8 @@ pub extern fn vcx_disclosed_proof_deserialize(command_handle: u32,
 pub extern fn vcx_disclosed_proof_retrieve_credentials(command_handle: u32,
                                                        proof_handle: u32,
                                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, data: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -437,8 +453,70 @@ pub extern fn vcx_disclosed_proof_retrieve_credentials(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_disclosed_proof_retrieve_credentials(command_handle: {}, rc: {}, data: {}) source_id: {}",
-                       command_handle, error_string(x.to_error_code()), 0, source_id);
-                cb(command_handle,x.to_error_code(),ptr::null_mut());
+                       command_handle, x, 0, source_id);
+                cb(command_handle,x.ffi_code(),ptr::null_mut());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Takes the disclosed proof object and picks a credential from wallet for each attribute and predicate of the proof request
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// handle: Proof handle that was provided during creation. Used to identify the disclosed proof object
+///
+/// selection_options: a json string with the strategy used to pick among the matching credentials, all fields are optional
+///     strategy: "newest" (default) takes the credential stored last by vcx, credentials stored otherwise count as the oldest,
+///               "preferred_issuer" takes a credential of the first issuer in preferred_issuers that has one,
+///               "least_revealing" uses as few credentials as possible, then the ones holding the fewest attributes
+///     preferred_issuers: issuer DIDs, most preferred first
+///     overrides: credential id (referent of its cred_info) to use for a proof request referent, whatever the strategy picks,
+///                under "attrs" or "predicates" like in selected_credentials
+/// # Examples selection_options -> "{"strategy":"preferred_issuer","preferred_issuers":["V4SGRU86Z58d6TV7PBUe6f"],"overrides":{"attrs":{"height_1":"92556f60-d290-4b58-9a43-05c25aac214e"}}}"
+///
+/// cb: Callback that provides json string of the selection, selected_credentials can be passed to vcx_disclosed_proof_generate_proof
+///     and unsatisfied lists the referents no credential in wallet can be used for
+/// # Example selection -> "{"selected_credentials":{"attrs":{"height_1":{"cred_info":{...},"interval":null}},"predicates":{}},"unsatisfied":["zip_2"]}"
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_disclosed_proof_select_credentials(command_handle: u32,
+                                                     proof_handle: u32,
+                                                     selection_options: *const c_char,
+                                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, data: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(selection_options, error::INVALID_OPTION.code_num);
+
+    if !disclosed_proof::is_valid_handle(proof_handle) {
+        return error::INVALID_DISCLOSED_PROOF_HANDLE.code_num;
+    }
+
+    let source_id = disclosed_proof::get_source_id(proof_handle).unwrap_or_default();
+    info!("vcx_disclosed_proof_select_credentials(command_handle: {}, proof_handle: {}, selection_options: {}) source_id: {}",
+          command_handle, proof_handle, selection_options, source_id);
+
+    spawn(move|| {
+        match disclosed_proof::select_credentials(proof_handle, selection_options) {
+            Ok(x) => {
+                info!("vcx_disclosed_proof_select_credentials_cb(command_handle: {}, rc: {}, data: {}) source_id: {}",
+                      command_handle, error_string(0), x, source_id);
+                let msg = CStringUtils::string_to_cstring(x);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                error!("vcx_disclosed_proof_select_credentials_cb(command_handle: {}, rc: {}, data: {}) source_id: {}",
+                       command_handle, x, 0, source_id);
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -457,12 +535,16 @@ pub extern fn vcx_disclosed_proof_retrieve_credentials(command_handle: u32,
 /// handle: Proof handle that was provided during creation. Used to identify the disclosed proof object
 ///
 /// selected_credentials: a json string with a credential for each proof request attribute.
-/// List of possible credentials for each attribute is returned from vcx_disclosed_proof_retrieve_credentials
+/// List of possible credentials for each attribute is returned from vcx_disclosed_proof_retrieve_credentials,
+/// vcx_disclosed_proof_select_credentials picks one of them for each attribute
 /// # Examples selected_credential -> "{"req_attr_0":cred_info}" Where cred_info is returned from retrieve credentials
 ///
 /// self_attested_attrs: a json string with attributes self attested by user
 /// # Examples self_attested_attrs -> "{"self_attested_attr_0":"attested_val"}"
 ///
+/// Revocable credentials need the tails file of their revocation registry, it is looked up by its tailsHash
+/// in the "tails_dir" directory of the configuration
+///
 /// cb: Callback that returns error status
 ///
 /// #Returns
@@ -473,6 +555,8 @@ pub extern fn vcx_disclosed_proof_generate_proof(command_handle: u32,
                                                  selected_credentials: *const c_char,
                                                  self_attested_attrs: *const c_char,
                                                  cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(proof_handle));
 
     check_useful_c_str!(selected_credentials, error::INVALID_OPTION.code_num);
     check_useful_c_str!(self_attested_attrs, error::INVALID_OPTION.code_num);
@@ -495,8 +579,8 @@ pub extern fn vcx_disclosed_proof_generate_proof(command_handle: u32,
             },
             Err(x) => {
                 error!("vcx_disclosed_proof_generate_proof(command_handle: {}, rc: {}) source_id: {}",
-                       command_handle, error_string(x.to_error_code()), source_id);
-                cb(command_handle,x.to_error_code());
+                       command_handle, x, source_id);
+                cb(command_handle,x.ffi_code());
             },
         };
 
@@ -516,6 +600,8 @@ pub extern fn vcx_disclosed_proof_generate_proof(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_disclosed_proof_release(handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(disclosed_proof::get_context(handle));
     let source_id = disclosed_proof::get_source_id(handle).unwrap_or_default();
     match disclosed_proof::release(handle) {
         Ok(_) => info!("vcx_disclosed_proof_release(handle: {}, rc: {}), source_id: {:?}",
@@ -652,6 +738,34 @@ mod tests {
         let credentials = cb.receive(None).unwrap().unwrap();
     }
 
+    #[test]
+    fn test_vcx_disclosed_proof_select_credentials() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_disclosed_proof_create_with_request(cb.command_handle,
+                                                           CString::new("test_create").unwrap().into_raw(),
+                                                           CString::new(::utils::constants::PROOF_REQUEST_JSON).unwrap().into_raw(),
+                                                           Some(cb.get_callback())), error::SUCCESS.code_num);
+        let handle = cb.receive(Some(Duration::from_secs(2))).unwrap();
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_disclosed_proof_select_credentials(cb.command_handle,
+                                                          handle,
+                                                          CString::new(r#"{"strategy":"least_revealing"}"#).unwrap().into_raw(),
+                                                          Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let selection = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+        assert!(selection.contains(r#""unsatisfied":[]"#));
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_disclosed_proof_select_credentials(cb.command_handle,
+                                                          handle,
+                                                          CString::new(r#"{"overrides":{"attrs":{"height_1":"unknown"}}}"#).unwrap().into_raw(),
+                                                          Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).err(), Some(error::INVALID_CREDENTIAL_SELECTION.code_num));
+    }
+
     #[test]
     fn test_vcx_disclosed_proof_generate_proof() {
         init!("true");
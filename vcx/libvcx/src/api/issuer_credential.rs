@@ -8,9 +8,11 @@ use utils::error::error_string;
 use connection;
 use settings;
 use issuer_credential;
+use issuer_credential_batch;
 use std::ptr;
-use error::ToErrorCode;
+use error::{ VcxError, VcxResult };
 use utils::threadpool::spawn;
+use context;
 
 /// Create a Issuer Credential object that provides a credential for an enterprise's user
 /// Assumes a credential definition has been written to the ledger.
@@ -24,7 +26,8 @@ use utils::threadpool::spawn;
 ///
 /// issuer_did: did corresponding to entity issuing a credential. Needs to have Trust Anchor permissions on ledger
 ///
-/// credential_data: data attributes offered to person in the credential
+/// credential_data: data attributes offered to person in the credential, they must be the attributes of the schema
+/// of the credential definition. On a mismatch the error details from vcx_get_current_error list the attribute names.
 ///
 /// credential_name: Name of the credential - ex. Drivers Licence
 ///
@@ -46,6 +49,7 @@ pub extern fn vcx_issuer_create_credential(command_handle: u32,
                                       credential_name: *const c_char,
                                       price: *const c_char,
                                       cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(credential_data, error::INVALID_OPTION.code_num);
@@ -60,7 +64,7 @@ pub extern fn vcx_issuer_create_credential(command_handle: u32,
     } else {
         match settings::get_config_value(settings::CONFIG_INSTITUTION_DID) {
             Ok(x) => x,
-            Err(x) => return x
+            Err(x) => return x.ffi_code()
         }
     };
 
@@ -86,8 +90,8 @@ pub extern fn vcx_issuer_create_credential(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_issuer_create_credential_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
-                      command_handle, error_string(x.to_error_code()), 0, "");
-                (x.to_error_code(), 0)
+                      command_handle, x, 0, "");
+                (x.ffi_code(), 0)
             },
         };
 
@@ -117,6 +121,8 @@ pub extern fn vcx_issuer_send_credential_offer(command_handle: u32,
                                           credential_handle: u32,
                                           connection_handle: u32,
                                           cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -141,8 +147,8 @@ pub extern fn vcx_issuer_send_credential_offer(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_issuer_send_credential_cb(command_handle: {}, credential_handle: {}, rc: {}) source_id: {})",
-                      command_handle, credential_handle, error_string(x.to_error_code()), source_id);
-                x.to_error_code()
+                      command_handle, credential_handle, x, source_id);
+                x.ffi_code()
             },
         };
 
@@ -169,6 +175,8 @@ pub extern fn vcx_issuer_send_credential_offer(command_handle: u32,
 pub extern fn vcx_issuer_credential_update_state(command_handle: u32,
                                             credential_handle: u32,
                                             cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -190,7 +198,7 @@ pub extern fn vcx_issuer_credential_update_state(command_handle: u32,
             Err(x) => {
                 warn!("vcx_issuer_credential_update_state_cb(command_handle: {}, credential_handle: {}, rc: {}, state: {}) source_id: {}",
                       command_handle, credential_handle, x.to_string(), 0, source_id);
-                cb(command_handle, x.to_error_code(), 0);
+                cb(command_handle, x.ffi_code(), 0);
             }
         };
 
@@ -216,6 +224,8 @@ pub extern fn vcx_issuer_credential_update_state(command_handle: u32,
 pub extern fn vcx_issuer_credential_get_state(command_handle: u32,
                                          credential_handle: u32,
                                          cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -236,8 +246,8 @@ pub extern fn vcx_issuer_credential_get_state(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_issuer_credential_get_state_cb(command_handle: {}, credential_handle: {}, rc: {}, state: {}) source_id: {}",
-                      command_handle, credential_handle, error_string(x), 0, source_id);
-                cb(command_handle, x, 0);
+                      command_handle, credential_handle, x, 0, source_id);
+                cb(command_handle, x.ffi_code(), 0);
             },
         };
 
@@ -270,6 +280,8 @@ pub extern fn vcx_issuer_send_credential(command_handle: u32,
                                     credential_handle: u32,
                                     connection_handle: u32,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -293,8 +305,8 @@ pub extern fn vcx_issuer_send_credential(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_issuer_send_credential_cb(command_handle: {}, credential_handle: {}, rc: {}) source_id: {}",
-                      command_handle, credential_handle, error_string(x.to_error_code()), source_id);
-                x.to_error_code()
+                      command_handle, credential_handle, x, source_id);
+                x.ffi_code()
             },
         };
 
@@ -306,6 +318,56 @@ pub extern fn vcx_issuer_send_credential(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Revoke Credential that was issued with a revocable credential definition. Updates the
+/// revocation registry on the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+///
+/// cb: Callback that provides error status of revoking the credential
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_revoke_credential(command_handle: u32,
+                                           credential_handle: u32,
+                                           cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    if !issuer_credential::is_valid_handle(credential_handle) {
+        return error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num;
+    }
+
+    let source_id = issuer_credential::get_source_id(credential_handle).unwrap_or_default();
+    info!("vcx_issuer_revoke_credential(command_handle: {}, credential_handle: {}) source_id: {}",
+          command_handle, credential_handle, source_id);
+    spawn(move|| {
+        let err = match issuer_credential::revoke_credential(credential_handle) {
+            Ok(x) => {
+                info!("vcx_issuer_revoke_credential_cb(command_handle: {}, credential_handle: {}, rc: {}) source_id: {}",
+                      command_handle, credential_handle, error_string(x), source_id);
+                x
+            },
+            Err(x) => {
+                warn!("vcx_issuer_revoke_credential_cb(command_handle: {}, credential_handle: {}, rc: {}) source_id: {}",
+                      command_handle, credential_handle, x, source_id);
+                x.ffi_code()
+            },
+        };
+
+        cb(command_handle, err);
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 #[allow(unused_variables)]
 pub extern fn vcx_issuer_terminate_credential(credential_handle: u32, termination_type: u32, msg: *const c_char) -> u32 { error::SUCCESS.code_num }
 
@@ -324,6 +386,8 @@ pub extern fn vcx_issuer_terminate_credential(credential_handle: u32, terminatio
 pub extern fn vcx_issuer_credential_serialize(command_handle: u32,
                                          credential_handle: u32,
                                          cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_state: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -344,8 +408,8 @@ pub extern fn vcx_issuer_credential_serialize(command_handle: u32,
             },
             Err(x) => {
                 info!("vcx_issuer_credential_serialize_cb(command_handle: {}, credential_handle: {}, rc: {}, state: {}) source_id: {})",
-                      command_handle, credential_handle, error_string(x.to_error_code()), "null", source_id);
-                cb(command_handle,x.to_error_code(),ptr::null_mut());
+                      command_handle, credential_handle, x, "null", source_id);
+                cb(command_handle,x.ffi_code(),ptr::null_mut());
             },
         };
 
@@ -370,6 +434,7 @@ pub extern fn vcx_issuer_credential_serialize(command_handle: u32,
 pub extern fn vcx_issuer_credential_deserialize(command_handle: u32,
                                       credential_data: *const c_char,
                                       cb: Option<extern fn(xcommand_handle: u32, err: u32, credential_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(credential_data, error::INVALID_OPTION.code_num);
@@ -386,7 +451,7 @@ pub extern fn vcx_issuer_credential_deserialize(command_handle: u32,
             Err(x) => {
                 warn!("vcx_issuer_credential_deserialize_cb(command_handle: {}, rc: {}, handle: {})",
                       command_handle, x.to_string(), 0);
-                (x.to_error_code(), 0)
+                (x.ffi_code(), 0)
             },
         };
 
@@ -407,12 +472,14 @@ pub extern fn vcx_issuer_credential_deserialize(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_issuer_credential_release(credential_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(credential_handle));
     let source_id = issuer_credential::get_source_id(credential_handle).unwrap_or_default();
     match issuer_credential::release(credential_handle) {
         Ok(_) => info!("(vcx_issuer_credential_release credential_handle: {}, rc: {}), source_id: {}",
                        credential_handle, error_string(0), source_id),
         Err(e) => warn!("(vcx_issuer_credential_release credential_handle: {}, rc: {}), source_id: {}",
-                       credential_handle, error_string(e.to_error_code()), source_id),
+                       credential_handle, e, source_id),
     };
     error::SUCCESS.code_num
 }
@@ -439,6 +506,8 @@ pub extern fn vcx_issuer_credential_release(credential_handle: u32) -> u32 {
 pub extern fn vcx_issuer_credential_get_payment_txn(command_handle: u32,
                                                 handle: u32,
                                                 cb: Option<extern fn(xcommand_handle: u32, err: u32, txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential::get_context(handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -466,16 +535,260 @@ pub extern fn vcx_issuer_credential_get_payment_txn(command_handle: u32,
             Err(x) => {
                 error!("vcx_issuer_credential_get_payment_txn_cb(command_handle: {}, rc: {}, txn: {}) source_id: {}",
                        command_handle, x.to_string(), "null", source_id);
-                cb(command_handle, x.to_error_code(), ptr::null());
+                cb(command_handle, x.ffi_code(), ptr::null());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Create a batch of issuer credentials from one credential definition, one issuer credential per connection.
+/// Connections that cannot be used are reported as failed entries in the batch status.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Enterprise's personal identification for the batch, each credential gets "<source_id>-<connection_handle>".
+///
+/// cred_def_id: id of credential definition given during creation of the credential definition
+///
+/// issuer_did: did corresponding to entity issuing a credential. Uses the institution did from the config if null
+///
+/// credential_data: attribute template shared by all credentials in the batch
+///
+/// connections: json list of connection handles, an entry may override attributes of the template
+/// example: [123, {"connection_handle": 456, "attributes": {"name": ["Bob"]}}]
+///
+/// credential_name: Name of the credential - ex. Drivers Licence
+///
+/// price: price of each credential
+///
+/// cb: Callback that provides batch handle and error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_credential_batch_create(command_handle: u32,
+                                                 source_id: *const c_char,
+                                                 cred_def_id: *const c_char,
+                                                 issuer_did: *const c_char,
+                                                 credential_data: *const c_char,
+                                                 connections: *const c_char,
+                                                 credential_name: *const c_char,
+                                                 price: *const c_char,
+                                                 cb: Option<extern fn(xcommand_handle: u32, err: u32, batch_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(credential_data, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(connections, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(credential_name, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(price, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(cred_def_id, error::INVALID_OPTION.code_num);
+
+    let issuer_did: String = if !issuer_did.is_null() {
+        check_useful_c_str!(issuer_did, error::INVALID_OPTION.code_num);
+        issuer_did.to_owned()
+    } else {
+        match settings::get_config_value(settings::CONFIG_INSTITUTION_DID) {
+            Ok(x) => x,
+            Err(x) => return x.ffi_code()
+        }
+    };
+
+    let price: u64 = match price.parse::<u64>() {
+        Ok(x) => x,
+        Err(_) => return error::INVALID_OPTION.code_num,
+    };
+
+    info!("vcx_issuer_credential_batch_create(command_handle: {}, source_id: {}, cred_def_id: {}, issuer_did: {}, credential_data: {}, connections: {}, credential_name: {})",
+          command_handle, source_id, cred_def_id, issuer_did, credential_data, connections, credential_name);
+
+    spawn(move|| {
+        let (rc, handle) = match issuer_credential_batch::create(source_id.clone(), cred_def_id, issuer_did, credential_name, &credential_data, &connections, price) {
+            Ok(x) => {
+                info!("vcx_issuer_credential_batch_create_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
+                      command_handle, error_string(0), x, source_id);
+                (error::SUCCESS.code_num, x)
+            },
+            Err(x) => {
+                warn!("vcx_issuer_credential_batch_create_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
+                      command_handle, x, 0, source_id);
+                (x.ffi_code(), 0)
             },
         };
 
+        cb(command_handle, rc, handle);
+
         Ok(())
     });
 
     error::SUCCESS.code_num
 }
 
+fn batch_status_callback(name: &'static str,
+                         command_handle: u32,
+                         cb: extern fn(xcommand_handle: u32, err: u32, status: *const c_char))
+                         -> impl FnOnce(VcxResult<issuer_credential_batch::BatchStatus>) + Send + 'static {
+    move |status| {
+        match status.and_then(|s| serde_json::to_string(&s).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))) {
+            Ok(x) => {
+                info!("{}_cb(command_handle: {}, rc: {}, status: {})", name, command_handle, error_string(0), x);
+                let msg = CStringUtils::string_to_cstring(x);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                warn!("{}_cb(command_handle: {}, rc: {}, status: {})", name, command_handle, x, "null");
+                cb(command_handle, x.ffi_code(), ptr::null());
+            },
+        }
+    }
+}
+
+/// Sends the credential offer to every connection of the batch concurrently.
+/// Offers that failed are sent again when this is called again.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// batch_handle: Batch handle that was provided during creation.
+///
+/// cb: Callback that provides the batch status once every offer has been attempted
+///
+/// #Returns
+/// Error code as a u32
+///
+/// # Example status -> {"source_id":"diplomas","total":2,"offers_sent":1,"requests_received":0,"issued":0,"failed":1,
+///     "entries":[{"connection_handle":123,"credential_handle":789,"state":2},
+///                {"connection_handle":456,"credential_handle":790,"state":1,"error_code":1093,"error_message":"Agency could not be reached"}]}
+#[no_mangle]
+pub extern fn vcx_issuer_credential_batch_send_offers(command_handle: u32,
+                                                      batch_handle: u32,
+                                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, status: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential_batch::get_context(batch_handle));
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    info!("vcx_issuer_credential_batch_send_offers(command_handle: {}, batch_handle: {})", command_handle, batch_handle);
+
+    if !issuer_credential_batch::is_valid_handle(batch_handle) {
+        return error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num;
+    }
+
+    spawn(move|| {
+        let done = batch_status_callback("vcx_issuer_credential_batch_send_offers", command_handle, cb);
+        if let Err(x) = issuer_credential_batch::send_offers(batch_handle, done) {
+            warn!("vcx_issuer_credential_batch_send_offers_cb(command_handle: {}, rc: {})", command_handle, x);
+            cb(command_handle, x.ffi_code(), ptr::null());
+        }
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Checks every outstanding offer of the batch concurrently and sends the credential to each
+/// connection whose credential request has arrived.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// batch_handle: Batch handle that was provided during creation.
+///
+/// cb: Callback that provides the batch status once every connection has been checked
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_credential_batch_update_state(command_handle: u32,
+                                                       batch_handle: u32,
+                                                       cb: Option<extern fn(xcommand_handle: u32, err: u32, status: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential_batch::get_context(batch_handle));
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    info!("vcx_issuer_credential_batch_update_state(command_handle: {}, batch_handle: {})", command_handle, batch_handle);
+
+    if !issuer_credential_batch::is_valid_handle(batch_handle) {
+        return error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num;
+    }
+
+    spawn(move|| {
+        let done = batch_status_callback("vcx_issuer_credential_batch_update_state", command_handle, cb);
+        if let Err(x) = issuer_credential_batch::update_state(batch_handle, done) {
+            warn!("vcx_issuer_credential_batch_update_state_cb(command_handle: {}, rc: {})", command_handle, x);
+            cb(command_handle, x.ffi_code(), ptr::null());
+        }
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get the progress of the batch without contacting the agency
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// batch_handle: Batch handle that was provided during creation.
+///
+/// cb: Callback that provides the batch status
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_credential_batch_get_status(command_handle: u32,
+                                                     batch_handle: u32,
+                                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, status: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential_batch::get_context(batch_handle));
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    info!("vcx_issuer_credential_batch_get_status(command_handle: {}, batch_handle: {})", command_handle, batch_handle);
+
+    if !issuer_credential_batch::is_valid_handle(batch_handle) {
+        return error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num;
+    }
+
+    spawn(move|| {
+        let done = batch_status_callback("vcx_issuer_credential_batch_get_status", command_handle, cb);
+        done(issuer_credential_batch::get_status(batch_handle));
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Releases the batch and the issuer credentials created for it
+///
+/// #Params
+/// batch_handle: Batch handle that was provided during creation.
+///
+/// #Returns
+/// Success
+#[no_mangle]
+pub extern fn vcx_issuer_credential_batch_release(batch_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(issuer_credential_batch::get_context(batch_handle));
+    match issuer_credential_batch::release(batch_handle) {
+        Ok(_) => info!("(vcx_issuer_credential_batch_release batch_handle: {}, rc: {})",
+                       batch_handle, error_string(0)),
+        Err(e) => warn!("(vcx_issuer_credential_batch_release batch_handle: {}, rc: {})",
+                       batch_handle, e),
+    };
+    error::SUCCESS.code_num
+}
+
 #[cfg(test)]
 mod tests {
     extern crate serde_json;
@@ -658,4 +971,62 @@ mod tests {
         vcx_issuer_credential_get_payment_txn(cb.command_handle, handle, Some(cb.get_callback()));
         cb.receive(Some(Duration::from_secs(10))).unwrap();
     }
+
+    #[test]
+    fn test_vcx_issuer_revoke_credential() {
+        init!("true");
+        let handle = issuer_credential::from_string(ISSUER_CREDENTIAL_STATE_ACCEPTED).unwrap();
+
+        // credential has not been issued yet
+        let cb = return_types_u32::Return_U32::new().unwrap();
+        assert_eq!(vcx_issuer_revoke_credential(cb.command_handle,
+                                                handle,
+                                                Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).err(), Some(error::NOT_READY.code_num));
+
+        assert_eq!(vcx_issuer_revoke_credential(cb.command_handle,
+                                                0,
+                                                Some(cb.get_callback())),
+                   error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num);
+    }
+
+    #[test]
+    fn test_vcx_issuer_credential_batch() {
+        init!("true");
+        let first = connection::build_connection("test_batch_first").unwrap();
+        let second = connection::build_connection("test_batch_second").unwrap();
+        let connections = json!([first, {"connection_handle": second, "attributes": {"attr": "other"}}]).to_string();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_issuer_credential_batch_create(cb.command_handle,
+                                                      CString::new("batch").unwrap().into_raw(),
+                                                      CString::new(CRED_DEF_ID).unwrap().into_raw(),
+                                                      CString::new(DEFAULT_DID).unwrap().into_raw(),
+                                                      CString::new(DEFAULT_ATTR).unwrap().into_raw(),
+                                                      CString::new(connections).unwrap().into_raw(),
+                                                      CString::new(DEFAULT_CREDENTIAL_NAME).unwrap().into_raw(),
+                                                      CString::new("0").unwrap().into_raw(),
+                                                      Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let handle = cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_issuer_credential_batch_send_offers(cb.command_handle, handle, Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let status: serde_json::Value = serde_json::from_str(&cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap()).unwrap();
+        assert_eq!(status["total"], json!(2));
+        assert_eq!(status["offers_sent"], json!(2));
+        assert_eq!(status["failed"], json!(0));
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_issuer_credential_batch_get_status(cb.command_handle, handle, Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let status: serde_json::Value = serde_json::from_str(&cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap()).unwrap();
+        assert_eq!(status["entries"][1]["connection_handle"], json!(second));
+
+        assert_eq!(vcx_issuer_credential_batch_release(handle), error::SUCCESS.code_num);
+        assert_eq!(vcx_issuer_credential_batch_send_offers(cb.command_handle, handle, Some(cb.get_callback())),
+                   error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num);
+    }
 }
@@ -10,6 +10,7 @@ pub mod schema;
 pub mod credential;
 pub mod disclosed_proof;
 pub mod wallet;
+pub mod context;
 
 use std::fmt;
 
@@ -88,13 +89,37 @@ enum_number!(VcxStateType
 // undefined is correlated with VcxStateNon -> Haven't received Proof
 // Validated is both validated by indy-sdk and by comparing proof-request
 // Invalid is that it failed one or both of validation processes
+// Tampered is that a revealed value is not the value the issuer signed
 enum_number!(ProofStateType
 {
     ProofUndefined = 0,
     ProofValidated = 1,
     ProofInvalid = 2,
+    ProofTampered = 3,
 });
 
+// Kind of object reported to the state change callback
+enum_number!(VcxObjectType
+{
+    Connection = 0,
+    IssuerCredential = 1,
+    Credential = 2,
+    Proof = 3,
+    DisclosedProof = 4,
+});
+
+// Whether a schema or credential definition is on the ledger, or was built for an endorser to write it
+enum_number!(PublicEntityStateType
+{
+    Built = 0,
+    Published = 1,
+});
+
+// Objects from before the endorser flow were always written right away
+impl Default for PublicEntityStateType {
+    fn default() -> PublicEntityStateType { PublicEntityStateType::Published }
+}
+
 #[repr(C)]
 pub struct VcxStatus {
     pub handle: ::std::os::raw::c_int,
@@ -5,10 +5,11 @@ use utils::cstring::CStringUtils;
 use utils::error;
 use utils::error::error_string;
 use proof;
+use proof_template;
 use connection;
 use std::ptr;
-use error::ToErrorCode;
 use utils::threadpool::spawn;
+use context;
 
 /// Create a new Proof object that requests a proof for an enterprise
 ///
@@ -21,10 +22,27 @@ use utils::threadpool::spawn;
 ///
 /// # Example requested_attrs -> "[{"name":"attrName","restrictions":["issuer_did":"did","schema_id":"id","schema_issuer_did":"did","schema_name":"name","schema_version":"1.1.1","cred_def_id":"id"}]]"
 ///
+/// An entry may ask for "names":["attrName1","attrName2"] instead of a name, all of them have to come from the same credential
+/// This is a VCX extension, only VCX provers answer such entries. It has to be enabled with "attribute_names":"true"
+/// in the configuration, otherwise vcx_proof_send_request fails with error 1103.
+///
 /// requested_predicates: predicate specifications prover must provide claim for
 ///
-/// # Example requested_predicates -> "[{"name":"attrName","p_type":"GE","p_value":9,"restrictions":["issuer_did":"did","schema_id":"id","schema_issuer_did":"did","schema_name":"name","schema_version":"1.1.1","cred_def_id":"id"}]]"
+/// # Example requested_predicates -> "[{"name":"attrName","p_type":">=","p_value":9,"restrictions":["issuer_did":"did","schema_id":"id","schema_issuer_did":"did","schema_name":"name","schema_version":"1.1.1","cred_def_id":"id"}]]"
+///
+/// Any one of the restrictions of an entry has to be met. A restriction may group others with {"$and":[...]} or
+/// {"$or":[...]} and may require a value of the credential with "attr::<attrName>::value":"<value>"
+///
+/// p_type is one of "<", "<=", ">", ">=" (or "LT", "LE", "GT", "GE"), any other value fails with error 1101
+///
+/// Both requested_attrs and requested_predicates entries may contain a "non_revoked" interval that overrides
+/// revocation_interval for that entry, e.g. "non_revoked":{"from":1540498474,"to":1540498474}
+///
+/// revocation_interval: interval applied to the whole proof in which the prover must show the credentials were not revoked
+///
+/// # Example revocation_interval -> "{"from":1540498474,"to":1540498474}", "{}" if no interval is required
 ///
+/// name: Name of the proof request
 ///
 /// cb: Callback that provides proof handle and error status of request.
 ///
@@ -35,20 +53,23 @@ pub extern fn vcx_proof_create(command_handle: u32,
                                source_id: *const c_char,
                                requested_attrs: *const c_char,
                                requested_predicates: *const c_char,
+                               revocation_interval: *const c_char,
                                name: *const c_char,
                                cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(requested_attrs, error::INVALID_OPTION.code_num);
     check_useful_c_str!(requested_predicates, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(revocation_interval, error::INVALID_OPTION.code_num);
     check_useful_c_str!(name, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
 
-    info!("vcx_proof_create(command_handle: {}, source_id: {}, requested_attrs: {}, requested_predicates: {}, name: {})",
-          command_handle, source_id, requested_attrs, requested_predicates, name);
+    info!("vcx_proof_create(command_handle: {}, source_id: {}, requested_attrs: {}, requested_predicates: {}, revocation_interval: {}, name: {})",
+          command_handle, source_id, requested_attrs, requested_predicates, revocation_interval, name);
 
     spawn(move|| {
-        let ( rc, handle) = match proof::create_proof(source_id, requested_attrs, requested_predicates, name) {
+        let ( rc, handle) = match proof::create_proof(source_id, requested_attrs, requested_predicates, revocation_interval, name) {
             Ok(x) => {
                 info!("vcx_proof_create_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
                       command_handle, error_string(0), x, proof::get_source_id(x).unwrap_or_default());
@@ -56,8 +77,8 @@ pub extern fn vcx_proof_create(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_proof_create_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
-                      command_handle, error_string(x.to_error_code()), 0, proof::get_source_id(x.to_error_code()).unwrap_or_default());
-                (x.to_error_code(), 0)
+                      command_handle, x, 0, proof::get_source_id(x.ffi_code()).unwrap_or_default());
+                (x.ffi_code(), 0)
             },
         };
         cb(command_handle, rc, handle);
@@ -68,6 +89,238 @@ pub extern fn vcx_proof_create(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Stores a named proof template in the wallet, a template stored before under the name is replaced
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// name: Name of the template, proofs created from it have this name
+///
+/// requested_attrs: attributes/claims prover must provide in proof, as for vcx_proof_create
+///
+/// requested_predicates: predicate specifications prover must provide claim for, as for vcx_proof_create
+///
+/// revocation_interval: interval applied to the whole proof, as for vcx_proof_create
+///
+/// cb: Callback that provides error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_proof_template_create(command_handle: u32,
+                                        name: *const c_char,
+                                        requested_attrs: *const c_char,
+                                        requested_predicates: *const c_char,
+                                        revocation_interval: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(name, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(requested_attrs, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(requested_predicates, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(revocation_interval, error::INVALID_OPTION.code_num);
+
+    info!("vcx_proof_template_create(command_handle: {}, name: {}, requested_attrs: {}, requested_predicates: {}, revocation_interval: {})",
+          command_handle, name, requested_attrs, requested_predicates, revocation_interval);
+
+    spawn(move|| {
+        match proof_template::create_template(&name, &requested_attrs, &requested_predicates, &revocation_interval) {
+            Ok(_) => {
+                info!("vcx_proof_template_create_cb(command_handle: {}, rc: {}) name: {}",
+                      command_handle, error_string(0), name);
+                cb(command_handle, error::SUCCESS.code_num);
+            },
+            Err(x) => {
+                warn!("vcx_proof_template_create_cb(command_handle: {}, rc: {}) name: {}",
+                      command_handle, x, name);
+                cb(command_handle, x.ffi_code());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Retrieves a proof template stored in the wallet
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// name: Name of the template
+///
+/// cb: Callback that provides the template and error status of request
+///
+/// # Example template -> "{"name":"kyc","requested_attrs":[{"name":"attrName"}],"requested_predicates":[],"revocation_interval":{}}"
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_proof_template_get(command_handle: u32,
+                                     name: *const c_char,
+                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, template: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(name, error::INVALID_OPTION.code_num);
+
+    info!("vcx_proof_template_get(command_handle: {}, name: {})", command_handle, name);
+
+    spawn(move|| {
+        match proof_template::get_template(&name) {
+            Ok(x) => {
+                let template = json!(x).to_string();
+                info!("vcx_proof_template_get_cb(command_handle: {}, rc: {}, template: {})",
+                      command_handle, error_string(0), template);
+                let msg = CStringUtils::string_to_cstring(template);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_proof_template_get_cb(command_handle: {}, rc: {}, template: {})",
+                      command_handle, x, "null");
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Lists the proof templates stored in the wallet
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides the templates, ordered by name, and error status of request
+///
+/// # Example templates -> "[{"name":"kyc","requested_attrs":[{"name":"attrName"}],"requested_predicates":[],"revocation_interval":{}}]"
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_proof_template_list(command_handle: u32,
+                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, templates: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    info!("vcx_proof_template_list(command_handle: {})", command_handle);
+
+    spawn(move|| {
+        match proof_template::list_templates() {
+            Ok(x) => {
+                let templates = json!(x).to_string();
+                info!("vcx_proof_template_list_cb(command_handle: {}, rc: {}, templates: {})",
+                      command_handle, error_string(0), templates);
+                let msg = CStringUtils::string_to_cstring(templates);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_proof_template_list_cb(command_handle: {}, rc: {}, templates: {})",
+                      command_handle, x, "null");
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Removes a proof template from the wallet
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// name: Name of the template
+///
+/// cb: Callback that provides error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_proof_template_delete(command_handle: u32,
+                                        name: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(name, error::INVALID_OPTION.code_num);
+
+    info!("vcx_proof_template_delete(command_handle: {}, name: {})", command_handle, name);
+
+    spawn(move|| {
+        match proof_template::delete_template(&name) {
+            Ok(_) => {
+                info!("vcx_proof_template_delete_cb(command_handle: {}, rc: {}) name: {}",
+                      command_handle, error_string(0), name);
+                cb(command_handle, error::SUCCESS.code_num);
+            },
+            Err(x) => {
+                warn!("vcx_proof_template_delete_cb(command_handle: {}, rc: {}) name: {}",
+                      command_handle, x, name);
+                cb(command_handle, x.ffi_code());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Create a new Proof object requesting what a stored proof template asks for, with a fresh nonce
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Enterprise's personal identification for the user.
+///
+/// template_name: Name of a template stored with vcx_proof_template_create
+///
+/// cb: Callback that provides proof handle and error status of request.
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_proof_create_from_template(command_handle: u32,
+                                             source_id: *const c_char,
+                                             template_name: *const c_char,
+                                             cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(template_name, error::INVALID_OPTION.code_num);
+
+    info!("vcx_proof_create_from_template(command_handle: {}, source_id: {}, template_name: {})",
+          command_handle, source_id, template_name);
+
+    spawn(move|| {
+        match proof_template::create_proof(source_id.clone(), &template_name) {
+            Ok(x) => {
+                info!("vcx_proof_create_from_template_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
+                      command_handle, error_string(0), x, source_id);
+                cb(command_handle, error::SUCCESS.code_num, x);
+            },
+            Err(x) => {
+                warn!("vcx_proof_create_from_template_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
+                      command_handle, x, 0, source_id);
+                cb(command_handle, x.ffi_code(), 0);
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Checks for any state change and updates the proof state attribute
 ///
 /// #Params
@@ -83,6 +336,8 @@ pub extern fn vcx_proof_create(command_handle: u32,
 pub extern fn vcx_proof_update_state(command_handle: u32,
                                      proof_handle: u32,
                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(proof::get_context(proof_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     let source_id = proof::get_source_id(proof_handle).unwrap_or_default();
@@ -103,7 +358,7 @@ pub extern fn vcx_proof_update_state(command_handle: u32,
             Err(x) => {
                 warn!("vcx_proof_update_state_cb(command_handle: {}, rc: {}, proof_handle: {}, state: {}) source_id: {}",
                       command_handle, x.to_string(), proof_handle, 0, source_id);
-                cb(command_handle, x.to_error_code(), 0);
+                cb(command_handle, x.ffi_code(), 0);
             }
         }
 
@@ -128,6 +383,8 @@ pub extern fn vcx_proof_update_state(command_handle: u32,
 pub extern fn vcx_proof_get_state(command_handle: u32,
                                   proof_handle: u32,
                                   cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(proof::get_context(proof_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     let source_id = proof::get_source_id(proof_handle).unwrap_or_default();
@@ -148,7 +405,7 @@ pub extern fn vcx_proof_get_state(command_handle: u32,
             Err(x) => {
                 warn!("vcx_proof_get_state_cb(command_handle: {}, rc: {}, proof_handle: {}, state: {}) source_id: {}",
                       command_handle, x.to_string(), proof_handle, 0, source_id);
-                cb(command_handle, x.to_error_code(), 0);
+                cb(command_handle, x.ffi_code(), 0);
             }
         }
 
@@ -173,6 +430,8 @@ pub extern fn vcx_proof_get_state(command_handle: u32,
 pub extern fn vcx_proof_serialize(command_handle: u32,
                                   proof_handle: u32,
                                   cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_state: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -193,8 +452,8 @@ pub extern fn vcx_proof_serialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_proof_serialize_cb(command_handle: {}, proof_handle: {}, rc: {}, state: {}) source_id: {}",
-                      command_handle, proof_handle, error_string(x.to_error_code()), "null", source_id);
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                      command_handle, proof_handle, x, "null", source_id);
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -219,6 +478,7 @@ pub extern fn vcx_proof_serialize(command_handle: u32,
 pub extern fn vcx_proof_deserialize(command_handle: u32,
                                     proof_data: *const c_char,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(proof_data, error::INVALID_OPTION.code_num);
@@ -235,8 +495,8 @@ pub extern fn vcx_proof_deserialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_proof_deserialize_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
-                      command_handle, error_string(x.to_error_code()), 0, "");
-                (x.to_error_code(), 0)
+                      command_handle, x, 0, "");
+                (x.ffi_code(), 0)
             },
         };
         cb(command_handle, rc, handle);
@@ -256,12 +516,14 @@ pub extern fn vcx_proof_deserialize(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_proof_release(proof_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(proof::get_context(proof_handle));
     let source_id = proof::get_source_id(proof_handle).unwrap_or_default();
     match proof::release(proof_handle) {
         Ok(x) => info!("vcx_proof_release(proof_handle: {}, rc: {}), source_id: {}",
                        proof_handle, error_string(0), source_id),
         Err(e) => warn!("vcx_proof_release(proof_handle: {}, rc: {}), source_id: {}",
-                       proof_handle, error_string(e.to_error_code()), source_id),
+                       proof_handle, e, source_id),
     };
     error::SUCCESS.code_num
 }
@@ -284,6 +546,8 @@ pub extern fn vcx_proof_send_request(command_handle: u32,
                                      proof_handle: u32,
                                      connection_handle: u32,
                                      cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(proof::get_context(proof_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -307,8 +571,8 @@ pub extern fn vcx_proof_send_request(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_proof_send_request_cb(command_handle: {}, rc: {}, proof_handle: {}) source_id: {}",
-                      command_handle, x.to_error_code(), proof_handle, source_id);
-                x.to_error_code()
+                      command_handle, x.ffi_code(), proof_handle, source_id);
+                x.ffi_code()
             },
         };
 
@@ -338,6 +602,8 @@ pub extern fn vcx_get_proof(command_handle: u32,
                             proof_handle: u32,
                             connection_handle: u32,
                             cb: Option<extern fn(xcommand_handle: u32, err: u32, proof_state:u32, response_data: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(proof::get_context(proof_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     let source_id = proof::get_source_id(proof_handle).unwrap_or_default();
@@ -365,8 +631,8 @@ pub extern fn vcx_get_proof(command_handle: u32,
                 cb(command_handle, error::SUCCESS.code_num, proof::get_proof_state(proof_handle).unwrap_or(0), msg.as_ptr());
             },
             Err(x) => {
-                warn!("vcx_get_proof_cb(command_handle: {}, proof_handle: {}, rc: {}, proof: {}) source_id: {}", command_handle, proof_handle, x.to_error_code(), "null", source_id);
-                cb(command_handle, x.to_error_code(), proof::get_proof_state(proof_handle).unwrap_or(0), ptr::null_mut());
+                warn!("vcx_get_proof_cb(command_handle: {}, proof_handle: {}, rc: {}, proof: {}) source_id: {}", command_handle, proof_handle, x.ffi_code(), "null", source_id);
+                cb(command_handle, x.ffi_code(), proof::get_proof_state(proof_handle).unwrap_or(0), ptr::null_mut());
             },
         };
 
@@ -383,6 +649,8 @@ pub extern fn vcx_proof_accepted(proof_handle: u32, response_data: *const c_char
 
 #[cfg(test)]
 mod tests {
+    extern crate serde_json;
+
     use super::*;
     use std::ffi::CString;
     use std::ptr;
@@ -404,6 +672,7 @@ mod tests {
                                     CString::new(DEFAULT_PROOF_NAME).unwrap().into_raw(),
                                     CString::new(REQUESTED_ATTRS).unwrap().into_raw(),
                                     CString::new(REQUESTED_PREDICATES).unwrap().into_raw(),
+                                    CString::new(r#"{"from":1540498474,"to":1540498474}"#).unwrap().into_raw(),
                                     CString::new("optional").unwrap().into_raw(),
                                     Some(cb.get_callback()));
         (cb, rc)
@@ -417,6 +686,57 @@ mod tests {
         cb.receive(Some(Duration::from_secs(10))).unwrap();
     }
 
+    #[test]
+    fn test_vcx_proof_templates() {
+        init!("false");
+
+        let cb = return_types_u32::Return_U32::new().unwrap();
+        assert_eq!(vcx_proof_template_create(cb.command_handle,
+                                             CString::new("kyc").unwrap().into_raw(),
+                                             CString::new(REQUESTED_ATTRS).unwrap().into_raw(),
+                                             CString::new(REQUESTED_PREDICATES).unwrap().into_raw(),
+                                             CString::new("{}").unwrap().into_raw(),
+                                             Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_proof_template_get(cb.command_handle, CString::new("kyc").unwrap().into_raw(), Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let template = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+        assert!(template.contains(r#""name":"kyc""#));
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_proof_template_list(cb.command_handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        let templates: serde_json::Value = serde_json::from_str(&cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap()).unwrap();
+        assert_eq!(templates.as_array().unwrap().len(), 1);
+        assert_eq!(templates[0]["name"], "kyc");
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_proof_create_from_template(cb.command_handle,
+                                                  CString::new("1").unwrap().into_raw(),
+                                                  CString::new("kyc").unwrap().into_raw(),
+                                                  Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let handle = cb.receive(Some(Duration::from_secs(10))).unwrap();
+        assert!(proof::is_valid_handle(handle));
+
+        let cb = return_types_u32::Return_U32::new().unwrap();
+        assert_eq!(vcx_proof_template_delete(cb.command_handle, CString::new("kyc").unwrap().into_raw(), Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_proof_create_from_template(cb.command_handle,
+                                                  CString::new("1").unwrap().into_raw(),
+                                                  CString::new("kyc").unwrap().into_raw(),
+                                                  Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).err(), Some(error::PROOF_TEMPLATE_NOT_FOUND.code_num));
+
+        teardown!("false");
+    }
+
     #[test]
     fn test_vcx_create_proof_fails() {
         init!("true");
@@ -426,6 +746,7 @@ mod tests {
                                     ptr::null(),
                                     ptr::null(),
                                     ptr::null(),
+                                    ptr::null(),
                                     None),
                    error::INVALID_OPTION.code_num);
     }
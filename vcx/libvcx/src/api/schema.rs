@@ -8,8 +8,8 @@ use utils::error::error_string;
 use std::ptr;
 use schema;
 use settings;
-use error::ToErrorCode;
 use utils::threadpool::spawn;
+use context;
 
 /// Create a new Schema object that can create or look up schemas on the ledger
 ///
@@ -40,6 +40,7 @@ pub extern fn vcx_schema_create(command_handle: u32,
                                 schema_data: *const c_char,
                                 payment_handle: u32,
                                 cb: Option<extern fn(xcommand_handle: u32, err: u32, credentialdef_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(schema_name, error::INVALID_OPTION.code_num);
     check_useful_c_str!(version, error::INVALID_OPTION.code_num);
@@ -48,7 +49,7 @@ pub extern fn vcx_schema_create(command_handle: u32,
 
     let issuer_did = match settings::get_config_value(settings::CONFIG_INSTITUTION_DID) {
         Ok(x) => x,
-        Err(x) => return x
+        Err(x) => return x.ffi_code()
     };
     info!(target:"vcx","vcx_schema_create(command_handle: {}, source_id: {}, schema_name: {},  schema_data: {})",
           command_handle, source_id, schema_name, schema_data);
@@ -66,8 +67,8 @@ pub extern fn vcx_schema_create(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_schema_create_cb(command_handle: {}, rc: {}, handle: {}) source_id: {}",
-                      command_handle, error_string(x.to_error_code()), 0, source_id);
-                (x.to_error_code(), 0) },
+                      command_handle, x, 0, source_id);
+                (x.ffi_code(), 0) },
         };
 
         cb(command_handle, rc, handle);
@@ -78,6 +79,172 @@ pub extern fn vcx_schema_create(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Create a new Schema object and the transaction writing it to the ledger, for an endorser to sign
+/// and write instead of the institution. Used when institution_did has no permission to write to the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Enterprise's personal identification for the user.
+///
+/// schema_name: Name of schema
+///
+/// version: version of schema
+///
+/// schema_data: list of attributes that will make up the schema
+///
+/// endorser: DID of the endorser that is going to write the schema to the ledger
+///
+/// cb: Callback that provides Schema handle, the transaction to be endorsed and error status of request.
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_schema_prepare_for_endorser(command_handle: u32,
+                                              source_id: *const c_char,
+                                              schema_name: *const c_char,
+                                              version: *const c_char,
+                                              schema_data: *const c_char,
+                                              endorser: *const c_char,
+                                              cb: Option<extern fn(xcommand_handle: u32, err: u32, schema_handle: u32, schema_txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(schema_name, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(version, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(schema_data, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(endorser, error::INVALID_OPTION.code_num);
+
+    let issuer_did = match settings::get_config_value(settings::CONFIG_INSTITUTION_DID) {
+        Ok(x) => x,
+        Err(x) => return x.ffi_code()
+    };
+    info!("vcx_schema_prepare_for_endorser(command_handle: {}, source_id: {}, schema_name: {},  schema_data: {}, endorser: {})",
+          command_handle, source_id, schema_name, schema_data, endorser);
+
+    spawn(move|| {
+        match schema::prepare_schema_for_endorser(&source_id,
+                                                  issuer_did,
+                                                  schema_name,
+                                                  version,
+                                                  schema_data,
+                                                  &endorser) {
+            Ok((handle, txn)) => {
+                info!("vcx_schema_prepare_for_endorser_cb(command_handle: {}, rc: {}, handle: {}, txn: {}) source_id: {}",
+                      command_handle, error_string(0), handle, txn, source_id);
+                let txn = CStringUtils::string_to_cstring(txn);
+                cb(command_handle, error::SUCCESS.code_num, handle, txn.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_schema_prepare_for_endorser_cb(command_handle: {}, rc: {}, handle: {}, txn: {}) source_id: {}",
+                      command_handle, x, 0, "null", source_id);
+                cb(command_handle, x.ffi_code(), 0, ptr::null());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Write the schema transaction, signed by the endorser, to the ledger
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// schema_handle: handle of the schema returned by vcx_schema_prepare_for_endorser
+///
+/// endorsed_txn: the transaction from vcx_schema_prepare_for_endorser signed by the endorser
+///
+/// cb: Callback that provides error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_schema_submit_endorsed(command_handle: u32,
+                                         schema_handle: u32,
+                                         endorsed_txn: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(schema::get_context(schema_handle));
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(endorsed_txn, error::INVALID_OPTION.code_num);
+
+    let source_id = schema::get_source_id(schema_handle).unwrap_or_default();
+    info!("vcx_schema_submit_endorsed(command_handle: {}, schema_handle: {}, endorsed_txn: {}) source_id: {}",
+          command_handle, schema_handle, endorsed_txn, source_id);
+
+    if !schema::is_valid_handle(schema_handle) {
+        return error::INVALID_SCHEMA_HANDLE.code_num;
+    }
+
+    spawn(move|| {
+        match schema::submit_endorsed(schema_handle, &endorsed_txn) {
+            Ok(()) => {
+                info!("vcx_schema_submit_endorsed_cb(command_handle: {}, schema_handle: {}, rc: {}) source_id: {}",
+                      command_handle, schema_handle, error_string(0), source_id);
+                cb(command_handle, error::SUCCESS.code_num);
+            },
+            Err(x) => {
+                warn!("vcx_schema_submit_endorsed_cb(command_handle: {}, schema_handle: {}, rc: {}) source_id: {}",
+                      command_handle, schema_handle, x, source_id);
+                cb(command_handle, x.ffi_code());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get the state of the schema: 0 - built for an endorser, 1 - written to the ledger
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// schema_handle: Schema handle that was provided during creation. Used to access schema object
+///
+/// cb: Callback that provides the state and error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_schema_get_state(command_handle: u32,
+                                   schema_handle: u32,
+                                   cb: Option<extern fn(xcommand_handle: u32, err: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(schema::get_context(schema_handle));
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    let source_id = schema::get_source_id(schema_handle).unwrap_or_default();
+    info!("vcx_schema_get_state(command_handle: {}, schema_handle: {}) source_id: {}",
+          command_handle, schema_handle, source_id);
+
+    if !schema::is_valid_handle(schema_handle) {
+        return error::INVALID_SCHEMA_HANDLE.code_num;
+    }
+
+    spawn(move|| {
+        match schema::get_state(schema_handle) {
+            Ok(x) => {
+                info!("vcx_schema_get_state_cb(command_handle: {}, schema_handle: {}, rc: {}, state: {}) source_id: {}",
+                      command_handle, schema_handle, error_string(0), x, source_id);
+                cb(command_handle, error::SUCCESS.code_num, x);
+            },
+            Err(x) => {
+                warn!("vcx_schema_get_state_cb(command_handle: {}, schema_handle: {}, rc: {}, state: {}) source_id: {}",
+                      command_handle, schema_handle, x, 0, source_id);
+                cb(command_handle, x.ffi_code(), 0);
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
 
 /// Takes the schema object and returns a json string of all its attributes
 ///
@@ -94,6 +261,8 @@ pub extern fn vcx_schema_create(command_handle: u32,
 pub extern fn vcx_schema_serialize(command_handle: u32,
                                    schema_handle: u32,
                                    cb: Option<extern fn(xcommand_handle: u32, err: u32, schema_state: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(schema::get_context(schema_handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -115,8 +284,8 @@ pub extern fn vcx_schema_serialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_schema_serialize_cb(command_handle: {}, schema_handle: {}, rc: {}, state: {}) source_id: {}",
-                      command_handle, schema_handle, error_string(x.to_error_code()), "null", source_id);
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                      command_handle, schema_handle, x, "null", source_id);
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -141,6 +310,7 @@ pub extern fn vcx_schema_serialize(command_handle: u32,
 pub extern fn vcx_schema_deserialize(command_handle: u32,
                                      schema_data: *const c_char,
                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, schema_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(schema_data, error::INVALID_OPTION.code_num);
@@ -155,8 +325,8 @@ pub extern fn vcx_schema_deserialize(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_schema_deserialize_cb(command_handle: {}, rc: {}, handle: {}), source_id: {}",
-                      command_handle, error_string(x.to_error_code()), 0, "");
-                (x.to_error_code(), 0)
+                      command_handle, x, 0, "");
+                (x.ffi_code(), 0)
             },
         };
         cb(command_handle, rc, handle);
@@ -176,12 +346,14 @@ pub extern fn vcx_schema_deserialize(command_handle: u32,
 /// Success
 #[no_mangle]
 pub extern fn vcx_schema_release(schema_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(schema::get_context(schema_handle));
     let source_id = schema::get_source_id(schema_handle).unwrap_or_default();
     match schema::release(schema_handle) {
         Ok(x) => info!("vcx_schema_release(schema_handle: {}, rc: {}), source_id: {}",
                        schema_handle, error_string(0), source_id),
         Err(e) => warn!("vcx_schema_release(schema_handle: {}, rc: {}), source_id: {}",
-                       schema_handle, error_string(e.to_error_code()), source_id),
+                       schema_handle, e, source_id),
     };
     error::SUCCESS.code_num
 }
@@ -199,6 +371,8 @@ pub extern fn vcx_schema_release(schema_handle: u32) -> u32 {
 pub extern fn vcx_schema_get_schema_id(command_handle: u32,
                                        schema_handle: u32,
                                        cb: Option<extern fn(xcommand_handle: u32, err: u32, schema_id: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(schema::get_context(schema_handle));
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
     info!("vcx_schema_get_schema_id(command_handle: {}, schema_handle: {})", command_handle, schema_handle);
@@ -217,7 +391,7 @@ pub extern fn vcx_schema_get_schema_id(command_handle: u32,
             Err(x) => {
                 warn!("vcx_schema_get_schema_id(command_handle: {}, schema_handle: {}, rc: {}, schema_seq_no: {})",
                       command_handle, schema_handle, x.to_string(), "");
-                cb(command_handle, x.to_error_code(), ptr::null_mut());
+                cb(command_handle, x.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -245,6 +419,7 @@ pub extern fn vcx_schema_get_attributes(command_handle: u32,
                                         source_id: *const c_char,
                                         schema_id: *const c_char,
                                         cb: Option<extern fn(xcommand_handle: u32, err: u32, s_handle: u32, schema_attrs: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(source_id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(schema_id, error::INVALID_OPTION.code_num);
@@ -263,8 +438,47 @@ pub extern fn vcx_schema_get_attributes(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_schema_get_attributes_cb(command_handle: {}, rc: {}, handle: {}, attrs: {})",
-                      command_handle, error_string(x.to_error_code()), 0, "");
-                cb(command_handle, x.to_error_code(), 0, ptr::null_mut());
+                      command_handle, x, 0, "");
+                cb(command_handle, x.ffi_code(), 0, ptr::null_mut());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// List the schemas created with this wallet
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides the schemas and error status of request
+///
+/// #Returns
+/// Error code as a u32
+///
+/// # Example schemas -> [{"source_id":"1","schema_id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4","name":"test-licence","version":"4.4.4"}]
+#[no_mangle]
+pub extern fn vcx_schema_list(command_handle: u32,
+                              cb: Option<extern fn(xcommand_handle: u32, err: u32, schemas: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    info!("vcx_schema_list(command_handle: {})", command_handle);
+
+    spawn(move|| {
+        match schema::list_created() {
+            Ok(x) => {
+                info!("vcx_schema_list_cb(command_handle: {}, rc: {}, schemas: {})",
+                      command_handle, error_string(0), x);
+                let msg = CStringUtils::string_to_cstring(x);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_schema_list_cb(command_handle: {}, rc: {}, schemas: {})",
+                      command_handle, x, "null");
+                cb(command_handle, x.ffi_code(), ptr::null());
             },
         };
 
@@ -296,6 +510,8 @@ pub extern fn vcx_schema_get_attributes(command_handle: u32,
 pub extern fn vcx_schema_get_payment_txn(command_handle: u32,
                                              handle: u32,
                                              cb: Option<extern fn(xcommand_handle: u32, err: u32, txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    let _context = context::enter(schema::get_context(handle));
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -322,7 +538,7 @@ pub extern fn vcx_schema_get_payment_txn(command_handle: u32,
             Err(x) => {
                 error!("vcx_schema_get_payment_txn_cb(command_handle: {}, rc: {}, txn: {}), source_id: {:?}",
                        command_handle, x.to_string(), "null", schema::get_source_id(handle).unwrap_or_default());
-                cb(command_handle, x.to_error_code(), ptr::null());
+                cb(command_handle, x.ffi_code(), ptr::null());
             },
         };
 
@@ -465,6 +681,44 @@ mod tests {
         assert_eq!(schema_as_json["data"].to_string(), data);
     }
 
+    #[test]
+    fn test_vcx_schema_list() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_schema_list(cb.command_handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), Some("[]".to_string()));
+    }
+
+    #[test]
+    fn test_vcx_schema_endorser_flow() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_U32_STR::new().unwrap();
+        assert_eq!(vcx_schema_prepare_for_endorser(cb.command_handle,
+                                                   CString::new("Test Source ID").unwrap().into_raw(),
+                                                   CString::new("Test Schema").unwrap().into_raw(),
+                                                   CString::new("0.0").unwrap().into_raw(),
+                                                   CString::new("[\"attr\", \"att2\"]").unwrap().into_raw(),
+                                                   CString::new("V4SGRU86Z58d6TV7PBUe6f").unwrap().into_raw(),
+                                                   Some(cb.get_callback())), error::SUCCESS.code_num);
+        let (handle, txn) = cb.receive(Some(Duration::from_secs(10))).unwrap();
+        assert!(handle > 0);
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_schema_get_state(cb.command_handle, handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), ::api::PublicEntityStateType::Built as u32);
+
+        let cb = return_types_u32::Return_U32::new().unwrap();
+        assert_eq!(vcx_schema_submit_endorsed(cb.command_handle,
+                                              handle,
+                                              CString::new(txn.unwrap()).unwrap().into_raw(),
+                                              Some(cb.get_callback())), error::SUCCESS.code_num);
+        cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_schema_get_state(cb.command_handle, handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), ::api::PublicEntityStateType::Published as u32);
+    }
+
     #[test]
     fn test_get_payment_txn() {
         init!("true");
@@ -1,7 +1,7 @@
 extern crate libc;
 extern crate serde_json;
 
-use self::libc::c_char;
+use self::libc::{c_char, c_void};
 use messages;
 use std::ptr;
 use utils::httpclient;
@@ -10,7 +10,10 @@ use utils::cstring::CStringUtils;
 use utils::error;
 use utils::error::error_string;
 use utils::threadpool::spawn;
+use utils::logger::{ LoggerUtils, EnabledCB, LogCB, FlushCB };
 use std::thread;
+use std::slice;
+use std::sync::Arc;
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct UpdateAgentInfo {
@@ -29,6 +32,7 @@ pub struct UpdateAgentInfo {
 
 #[no_mangle]
 pub extern fn vcx_provision_agent(config: *const c_char) -> *mut c_char {
+    ::error::reset_current_error();
     check_useful_c_str!(config, ptr::null_mut());
 
     match messages::agent_utils::connect_register_provision(&config) {
@@ -64,19 +68,22 @@ pub extern fn vcx_provision_agent(config: *const c_char) -> *mut c_char {
 pub extern fn vcx_agent_provision_async(command_handle : u32,
                                config: *const c_char,
                                cb: Option<extern fn(xcommand_handle: u32, err: u32, _config: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(config, error::INVALID_OPTION.code_num);
 
     info!("vcx_agent_provision_async(command_handle: {}, json: {})",
           command_handle, config);
 
+    let context = ::context::current();
     thread::spawn(move|| {
+        let _context = ::context::enter(context);
         match messages::agent_utils::connect_register_provision(&config) {
             Err(e) => {
                 // Ensure state of libvcx is clean
                 ::api::vcx::vcx_shutdown(false);
-                error!("vcx_agent_provision_async_cb(command_handle: {}, rc: {}, config: NULL", command_handle, error_string(e));
-                cb(command_handle, e, ptr::null_mut());
+                error!("vcx_agent_provision_async_cb(command_handle: {}, rc: {}, config: NULL", command_handle, e);
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
             },
             Ok(s) => {
                 info!("vcx_agent_provision_async_cb(command_handle: {}, rc: {}, config: {})",
@@ -106,6 +113,7 @@ pub extern fn vcx_agent_provision_async(command_handle : u32,
 pub extern fn vcx_agent_update_info(command_handle: u32,
                                     json: *const c_char,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(json, error::INVALID_OPTION.code_num);
@@ -129,8 +137,8 @@ pub extern fn vcx_agent_update_info(command_handle: u32,
             },
             Err(e) => {
                 error!("vcx_agent_update_info_cb(command_handle: {}, rc: {})",
-                      command_handle, error::error_string(e));
-                cb(command_handle, e);
+                      command_handle, e);
+                cb(command_handle, e.ffi_code());
             },
         };
 
@@ -153,6 +161,7 @@ pub extern fn vcx_agent_update_info(command_handle: u32,
 #[no_mangle]
 pub extern fn vcx_ledger_get_fees(command_handle: u32,
                                   cb: Option<extern fn(xcommand_handle: u32, err: u32, fees: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     info!("vcx_ledger_get_fees(command_handle: {})",
@@ -169,9 +178,72 @@ pub extern fn vcx_ledger_get_fees(command_handle: u32,
             },
             Err(e) => {
                 warn!("vcx_ledget_get_fees_cb(command_handle: {}, rc: {}, fees: {})",
-                      command_handle, error_string(e), "null");
+                      command_handle, e, "null");
 
-                cb(command_handle, e, ptr::null_mut());
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Remove schemas and credential definitions from the ledger cache, the next lookup fetches them from the ledger
+///
+/// #Params
+/// ledger_id: id of the schema or credential definition to remove, NULL to clear the whole cache
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_ledger_cache_invalidate(ledger_id: *const c_char) -> u32 {
+    ::error::reset_current_error();
+    check_useful_opt_c_str!(ledger_id, error::INVALID_OPTION.code_num);
+    info!("vcx_ledger_cache_invalidate(ledger_id: {:?})", ledger_id);
+
+    match ledger_id {
+        Some(id) => ::ledger_cache::invalidate(&id),
+        None => ::ledger_cache::clear(),
+    }
+
+    error::SUCCESS.code_num
+}
+
+/// Endorse a schema or credential definition transaction prepared by another issuer, the institution_did
+/// must be the endorser named in the transaction. The issuer writes the endorsed transaction to the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// transaction: transaction returned by vcx_schema_prepare_for_endorser or vcx_credentialdef_prepare_for_endorser
+///
+/// cb: Callback that provides the endorsed transaction and error status of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_endorse_transaction(command_handle: u32,
+                                      transaction: *const c_char,
+                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, endorsed_txn: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+    check_useful_c_str!(transaction, error::INVALID_OPTION.code_num);
+    info!("vcx_endorse_transaction(command_handle: {}, transaction: {})", command_handle, transaction);
+
+    spawn(move|| {
+        match ::utils::libindy::ledger::endorse_request(&transaction) {
+            Ok(x) => {
+                info!("vcx_endorse_transaction_cb(command_handle: {}, rc: {}, endorsed_txn: {})",
+                      command_handle, error_string(0), x);
+                let msg = CStringUtils::string_to_cstring(x);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(e) => {
+                warn!("vcx_endorse_transaction_cb(command_handle: {}, rc: {}, endorsed_txn: {})",
+                      command_handle, e, "null");
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -183,6 +255,7 @@ pub extern fn vcx_ledger_get_fees(command_handle: u32,
 
 #[no_mangle]
 pub extern fn vcx_set_next_agency_response(message_index: u32) {
+    ::error::reset_current_error();
     let message = match message_index {
         1 => CREATE_KEYS_RESPONSE.to_vec(),
         2 => UPDATE_PROFILE_RESPONSE.to_vec(),
@@ -219,6 +292,7 @@ pub extern fn vcx_messages_download(command_handle: u32,
                                     uids: *const c_char,
                                     pw_dids: *const c_char,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, messages: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -273,9 +347,9 @@ pub extern fn vcx_messages_download(command_handle: u32,
             },
             Err(e) => {
                 warn!("vcx_messages_download_cb(command_handle: {}, rc: {}, messages: {})",
-                      command_handle, error_string(e), "null");
+                      command_handle, e, "null");
 
-                cb(command_handle, e, ptr::null_mut());
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -285,6 +359,69 @@ pub extern fn vcx_messages_download(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Route all agency traffic through the host instead of the built in http client.
+/// For every message libvcx calls send_cb and waits for vcx_http_transport_response to be called
+/// with the same command handle.
+///
+/// #Params
+/// send_cb: Callback that receives the url and the encrypted bundle to post, NULL restores the default client
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_set_http_transport(send_cb: Option<extern fn(xcommand_handle: u32, url: *const c_char, body: *const u8, body_len: u32)>) -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_set_http_transport(send_cb: {:?})", send_cb.is_some());
+
+    match send_cb {
+        Some(cb) => httpclient::set_transport(Arc::new(httpclient::CallbackTransport::new(cb))),
+        None => httpclient::reset_transport(),
+    };
+
+    error::SUCCESS.code_num
+}
+
+/// Provide the agency response for a message handed out by the callback registered with vcx_set_http_transport
+///
+/// #Params
+/// command_handle: command handle received by the transport callback
+///
+/// err: 0 if the message was delivered, 1093 (agency unreachable) if it could not be delivered
+///      and may be retried, any other value if the agency rejected it
+///
+/// response: body of the agency response
+///
+/// response_len: length of the response
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_http_transport_response(command_handle: u32,
+                                          err: u32,
+                                          response: *const u8,
+                                          response_len: u32) -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_http_transport_response(command_handle: {}, err: {}, response_len: {})",
+          command_handle, err, response_len);
+
+    let response = match err {
+        0 => {
+            if response.is_null() && response_len > 0 { return error::INVALID_OPTION.code_num; }
+            let body = match response.is_null() {
+                true => Vec::new(),
+                false => unsafe { slice::from_raw_parts(response, response_len as usize) }.to_vec(),
+            };
+            Ok(body)
+        },
+        e => Err(e),
+    };
+
+    match httpclient::set_transport_response(command_handle, response) {
+        Ok(_) => error::SUCCESS.code_num,
+        Err(e) => e.ffi_code(),
+    }
+}
+
 /// Update the status of messages from the specified connection
 ///
 /// #params
@@ -305,6 +442,7 @@ pub extern fn vcx_messages_update_status(command_handle: u32,
                                          message_status: *const c_char,
                                          msg_json: *const c_char,
                                          cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(message_status, error::INVALID_OPTION.code_num);
@@ -323,9 +461,90 @@ pub extern fn vcx_messages_update_status(command_handle: u32,
             },
             Err(e) => {
                 warn!("vcx_messages_set_status_cb(command_handle: {}, rc: {})",
-                      command_handle, error_string(e));
+                      command_handle, e);
+
+                cb(command_handle, e.ffi_code());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Register a callback fired whenever the message dispatcher changes the state of an object of a context
+///
+/// #Params
+/// context_handle: context whose objects are reported, 0 for the default context
+///
+/// state_cb: Callback that receives the context, the object type (0 connection, 1 issuer credential,
+///           2 credential, 3 proof, 4 disclosed proof), the handle of the object and its new state.
+///           NULL removes the callback of the context
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_set_state_change_callback(context_handle: u32,
+                                            state_cb: Option<extern fn(context_handle: u32, object_type: u32, handle: u32, state: u32)>) -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_set_state_change_callback(context_handle: {}, state_cb: {:?})", context_handle, state_cb.is_some());
+
+    if !::context::is_valid_handle(context_handle) {
+        return error::INVALID_CONTEXT_HANDLE.code_num;
+    }
+
+    ::context::with_context(context_handle, || ::dispatcher::set_state_change_callback(state_cb));
+
+    error::SUCCESS.code_num
+}
+
+/// Download the messages of all connections with a single request and update the state of every
+/// connection, credential and proof waiting for an answer, instead of calling update_state on each of them
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides the objects whose state changed
+///
+/// #Returns
+/// Error code as a u32
+///
+/// # Example changes -> [{"object_type":1,"handle":2875418925,"state":3}]
+#[no_mangle]
+pub extern fn vcx_messages_dispatch(command_handle: u32,
+                                    cb: Option<extern fn(xcommand_handle: u32, err: u32, changes: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
+
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    info!("vcx_messages_dispatch(command_handle: {})", command_handle);
 
-                cb(command_handle, e);
+    spawn(move|| {
+        match ::dispatcher::dispatch_messages() {
+            Ok(x) => {
+                match serde_json::to_string(&x) {
+                    Ok(x) => {
+                        info!("vcx_messages_dispatch_cb(command_handle: {}, rc: {}, changes: {})",
+                              command_handle, error::error_string(0), x);
+
+                        let msg = CStringUtils::string_to_cstring(x);
+                        cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+                    },
+                    Err(_) => {
+                        warn!("vcx_messages_dispatch_cb(command_handle: {}, rc: {}, changes: {})",
+                              command_handle, error_string(error::INVALID_JSON.code_num), "null");
+
+                        cb(command_handle, error::INVALID_JSON.code_num, ptr::null_mut());
+                    },
+                };
+            },
+            Err(e) => {
+                warn!("vcx_messages_dispatch_cb(command_handle: {}, rc: {}, changes: {})",
+                      command_handle, e, "null");
+
+                cb(command_handle, e.ffi_code(), ptr::null_mut());
             },
         };
 
@@ -335,6 +554,94 @@ pub extern fn vcx_messages_update_status(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Dispatch messages in the background every interval_ms milliseconds.
+/// State changes are reported through the callback registered with vcx_set_state_change_callback.
+///
+/// #Params
+/// interval_ms: time between two downloads, must be greater than 0
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_messages_start_dispatcher(interval_ms: u32) -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_messages_start_dispatcher(interval_ms: {})", interval_ms);
+
+    if interval_ms == 0 { return error::INVALID_OPTION.code_num; }
+
+    ::dispatcher::start(interval_ms as u64);
+
+    error::SUCCESS.code_num
+}
+
+/// Stop the background dispatcher started with vcx_messages_start_dispatcher
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_messages_stop_dispatcher() -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_messages_stop_dispatcher()");
+
+    ::dispatcher::stop();
+
+    error::SUCCESS.code_num
+}
+
+/// Set a custom logger receiving the logs of libvcx and libindy, instead of the built-in ones
+/// configured with RUST_LOG and log_config. Must be called before vcx_init, it can only be set once.
+///
+/// #Params
+/// context: pointer to some logger context that will be passed to logger callbacks
+///
+/// enabled: (optional) tells whether a record with given level and target should be logged.
+///          Can be used to change the levels at runtime
+///
+/// log: receives level, target, message, module path, file and line of every record
+///
+/// flush: (optional) flushes buffered records
+///
+/// max_lvl: most verbose level to log: 0 - Off, 1 - Error, 2 - Warn, 3 - Info, 4 - Debug, 5 - Trace
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_set_logger(context: *const c_void,
+                             enabled: Option<EnabledCB>,
+                             log: Option<LogCB>,
+                             flush: Option<FlushCB>,
+                             max_lvl: u32) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_callback!(log, error::INVALID_OPTION.code_num);
+
+    match LoggerUtils::set_logger(context, enabled, log, flush, max_lvl) {
+        Ok(()) => {
+            info!("vcx_set_logger(max_lvl: {}): logger set", max_lvl);
+            error::SUCCESS.code_num
+        },
+        Err(e) => {
+            warn!("vcx_set_logger(max_lvl: {}, rc: {})", max_lvl, e);
+            e.ffi_code()
+        },
+    }
+}
+
+/// Change the most verbose level logged by libvcx at runtime
+///
+/// #Params
+/// max_lvl: 0 - Off, 1 - Error, 2 - Warn, 3 - Info, 4 - Debug, 5 - Trace
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_set_log_max_lvl(max_lvl: u32) -> u32 {
+    ::error::reset_current_error();
+    match LoggerUtils::set_max_level(max_lvl) {
+        Ok(()) => error::SUCCESS.code_num,
+        Err(e) => e.ffi_code(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -343,6 +650,29 @@ mod tests {
     use std::time::Duration;
     use utils::libindy::return_types_u32;
 
+    extern fn transport_cb(command_handle: u32, url: *const c_char, body: *const u8, body_len: u32) {
+        let response = GET_MESSAGES_RESPONSE;
+        assert_eq!(vcx_http_transport_response(command_handle, 0, response.as_ptr(), response.len() as u32),
+                   error::SUCCESS.code_num);
+    }
+
+    #[test]
+    fn test_vcx_set_http_transport() {
+        init!("true");
+        let _transport = httpclient::lock_test_transport();
+        assert_eq!(vcx_set_http_transport(Some(transport_cb)), error::SUCCESS.code_num);
+        let response = httpclient::post_u8(&vec![1, 2, 3]);
+        assert_eq!(vcx_set_http_transport(None), error::SUCCESS.code_num);
+        assert_eq!(response.unwrap(), GET_MESSAGES_RESPONSE.to_vec());
+    }
+
+    #[test]
+    fn test_vcx_http_transport_response_fails_with_unknown_handle() {
+        init!("true");
+        assert_eq!(vcx_http_transport_response(0, 0, ptr::null(), 0), error::INVALID_TRANSPORT_HANDLE.code_num);
+        assert_eq!(vcx_http_transport_response(0, 0, ptr::null(), 10), error::INVALID_OPTION.code_num);
+    }
+
     #[test]
     fn test_provision_agent() {
         init!("true");
@@ -445,4 +775,59 @@ mod tests {
                    error::SUCCESS.code_num);
         cb.receive(Some(Duration::from_secs(10))).unwrap();
     }
+
+    #[test]
+    fn test_vcx_messages_dispatch() {
+        init!("true");
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_messages_dispatch(cb.command_handle, Some(cb.get_callback())), error::SUCCESS.code_num);
+        cb.receive(Some(Duration::from_secs(10))).unwrap();
+
+        assert_eq!(vcx_messages_start_dispatcher(0), error::INVALID_OPTION.code_num);
+        assert_eq!(vcx_messages_stop_dispatcher(), error::SUCCESS.code_num);
+    }
+
+    #[test]
+    fn test_set_logger_validates_params() {
+        assert_eq!(vcx_set_logger(ptr::null(), None, None, None, 5), error::INVALID_OPTION.code_num);
+        assert_eq!(vcx_set_log_max_lvl(6), error::INVALID_OPTION.code_num);
+    }
+
+    #[test]
+    fn test_endorse_transaction() {
+        init!("true");
+        let did = ::settings::get_config_value(::settings::CONFIG_INSTITUTION_DID).unwrap();
+        let (_, txn) = ::schema::prepare_schema_for_endorser("1", did.clone(), "name".to_string(), "1.0".to_string(),
+                                                             "[\"name\"]".to_string(), &did).unwrap();
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_endorse_transaction(cb.command_handle, CString::new(txn.clone()).unwrap().into_raw(), Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), Some(txn));
+
+        let (_, txn) = ::schema::prepare_schema_for_endorser("1", did, "name".to_string(), "1.0".to_string(),
+                                                             "[\"name\"]".to_string(), "V4SGRU86Z58d6TV7PBUe6f").unwrap();
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_endorse_transaction(cb.command_handle, CString::new(txn).unwrap().into_raw(), Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap_err(), error::INVALID_OPTION.code_num);
+    }
+
+    #[test]
+    fn test_ledger_cache_invalidate() {
+        use ledger_cache::{ self, LedgerObject };
+
+        init!("true");
+        ::settings::set_config_value(::settings::CONFIG_LEDGER_CACHE_TTL_SECS, "60");
+        let schema = (SCHEMA_ID.to_string(), SCHEMA_JSON.to_string());
+        ledger_cache::put(LedgerObject::Schema, SCHEMA_ID, &schema);
+
+        let id = CString::new(SCHEMA_ID).unwrap();
+        assert_eq!(vcx_ledger_cache_invalidate(id.as_ptr()), error::SUCCESS.code_num);
+        assert_eq!(ledger_cache::get(LedgerObject::Schema, SCHEMA_ID), None);
+
+        ledger_cache::put(LedgerObject::Schema, SCHEMA_ID, &schema);
+        assert_eq!(vcx_ledger_cache_invalidate(ptr::null()), error::SUCCESS.code_num);
+        assert_eq!(ledger_cache::get(LedgerObject::Schema, SCHEMA_ID), None);
+    }
 }
@@ -6,6 +6,8 @@ use self::libc::c_char;
 use utils::cstring::CStringUtils;
 use utils::libindy::{wallet, pool};
 use utils::error;
+use utils::error::error_string;
+use context;
 use settings;
 use std::ffi::CString;
 use utils::threadpool::spawn;
@@ -28,6 +30,7 @@ use utils::threadpool::spawn;
 pub extern fn vcx_init_with_config(command_handle: u32,
                                    config: *const c_char,
                                    cb: Option<extern fn(xcommand_handle: u32, err:u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(config,error::INVALID_OPTION.code_num);
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -40,13 +43,13 @@ pub extern fn vcx_init_with_config(command_handle: u32,
                 // Ensure state of libvcx is clean
                 vcx_shutdown(false);
                 error!("Invalid configuration specified: {}", e);
-                return e;
+                return e.ffi_code();
             },
             Ok(_) => (),
         }
     };
 
-    _finish_init(command_handle, cb)
+    _finish_init(move |err| cb(command_handle, err))
 }
 
 /// Initializes VCX with config file
@@ -66,6 +69,7 @@ pub extern fn vcx_init_with_config(command_handle: u32,
 pub extern fn vcx_init (command_handle: u32,
                         config_path:*const c_char,
                         cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
@@ -80,8 +84,8 @@ pub extern fn vcx_init (command_handle: u32,
                 Err(e) => {
                     // Ensure state of libvcx is clean
                     vcx_shutdown(false);
-                    println!("Invalid configuration specified: {}", e);
-                    return error::INVALID_CONFIGURATION.code_num;
+                    error!("Invalid configuration specified: {}", e);
+                    return e.ffi_code();
                 },
                 Ok(_) => (),
             };
@@ -91,17 +95,18 @@ pub extern fn vcx_init (command_handle: u32,
         return error::INVALID_CONFIGURATION.code_num;
     }
 
-    _finish_init(command_handle, cb)
+    _finish_init(move |err| cb(command_handle, err))
 }
 
-fn _finish_init(command_handle: u32, cb: extern fn(xcommand_handle: u32, err: u32)) -> u32 {
+fn _finish_init<F>(done: F) -> u32
+    where F: FnOnce(u32) + Send + 'static {
     ::utils::logger::LoggerUtils::init();
 
     ::utils::threadpool::init();
 
     match ::utils::libindy::payments::init_payments() {
         Ok(_) => (),
-        Err(x) => return x,
+        Err(x) => return x.ffi_code(),
     };
 
     settings::log_settings();
@@ -128,7 +133,7 @@ fn _finish_init(command_handle: u32, cb: extern fn(xcommand_handle: u32, err: u3
                 Ok(_) => (),
                 Err(e) => {
                     error!("Init Pool Error {}.", e);
-                    return Ok(cb(command_handle, e))
+                    return Ok(done(e))
                 },
             }
         }
@@ -136,11 +141,17 @@ fn _finish_init(command_handle: u32, cb: extern fn(xcommand_handle: u32, err: u3
         match wallet::open_wallet(&wallet_name) {
             Ok(_) => {
                 debug!("Init Wallet Successful");
-                cb(command_handle, error::SUCCESS.code_num);
+                match ::object_store::restore_all() {
+                    Ok(()) => done(error::SUCCESS.code_num),
+                    Err(e) => {
+                        error!("Restoring stored objects failed: {}", e);
+                        done(e.ffi_code());
+                    }
+                }
             },
             Err(e) => {
                 error!("Init Wallet Error {}.", e);
-                cb(command_handle, e);
+                done(e);
             }
         }
         Ok(())
@@ -155,12 +166,14 @@ lazy_static!{
 
 #[no_mangle]
 pub extern fn vcx_version() -> *const c_char {
+    ::error::reset_current_error();
     VERSION_STRING.as_ptr()
 }
 
 /// Reset libvcx to a pre-configured state, releasing/deleting any handles and freeing memory
 ///
 /// libvcx will be inoperable and must be initialized again with vcx_init_with_config
+/// Only the context selected on the calling thread is reset, see vcx_context_set_current
 ///
 /// #Params
 /// delete: specify whether wallet/pool should be deleted
@@ -169,7 +182,15 @@ pub extern fn vcx_version() -> *const c_char {
 /// Success
 #[no_mangle]
 pub extern fn vcx_shutdown(delete: bool) -> u32 {
+    ::error::reset_current_error();
 
+    _release_context(delete);
+
+    info!("vcx_shutdown(delete: {})", delete);
+    error::SUCCESS.code_num
+}
+
+fn _release_context(delete: bool) {
     match wallet::close_wallet() {
         Ok(_) => {},
         Err(_) => {},
@@ -180,13 +201,18 @@ pub extern fn vcx_shutdown(delete: bool) -> u32 {
         Err(_) => {},
     };
 
+    ::dispatcher::stop();
+    ::dispatcher::set_state_change_callback(None);
+
     ::schema::release_all();
     ::connection::release_all();
     ::issuer_credential::release_all();
+    ::issuer_credential_batch::release_all();
     ::credential_def::release_all();
     ::proof::release_all();
     ::disclosed_proof::release_all();
     ::credential::release_all();
+    ::ledger_cache::release_all();
 
     if delete {
         let pool_name = settings::get_config_value(settings::CONFIG_POOL_NAME)
@@ -207,8 +233,106 @@ pub extern fn vcx_shutdown(delete: bool) -> u32 {
     }
 
     settings::clear_config();
-    info!("vcx_shutdown(delete: {})", delete);
-    error::SUCCESS.code_num
+}
+
+/// Creates a context with its own settings, wallet, pool and objects, so that several
+/// institutions can be served by one process. Every context needs its own wallet_name
+/// and pool_name.
+///
+/// Calls on an object run in the context the object was created in. Other calls take the context
+/// explicitly in their _in_context variant, or target the default context, configured by vcx_init,
+/// until vcx_context_set_current selects another one on the calling thread.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// config: configuration of the context, same as for vcx_init_with_config
+///
+/// cb: Callback that provides error status of initialization and the handle of the context
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_context_create_with_config(command_handle: u32,
+                                             config: *const c_char,
+                                             cb: Option<extern fn(xcommand_handle: u32, err: u32, context_handle: u32)>) -> u32 {
+    ::error::reset_current_error();
+    check_useful_c_str!(config, error::INVALID_OPTION.code_num);
+    check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
+
+    info!("vcx_context_create_with_config(command_handle: {})", command_handle);
+
+    let context_handle = context::create();
+    let rc = context::with_context(context_handle, || {
+        if let Err(e) = settings::process_config_string(&config) {
+            error!("Invalid configuration specified: {}", e);
+            return e.ffi_code();
+        }
+
+        _finish_init(move |err| {
+            if err == error::SUCCESS.code_num {
+                info!("vcx_context_create_with_config_cb(command_handle: {}, rc: {}, context_handle: {})",
+                      command_handle, error_string(err), context_handle);
+                cb(command_handle, err, context_handle)
+            } else {
+                _release_context(false);
+                let _ = context::release(context_handle);
+                warn!("vcx_context_create_with_config_cb(command_handle: {}, rc: {}, context_handle: {})",
+                      command_handle, error_string(err), 0);
+                cb(command_handle, err, 0)
+            }
+        })
+    });
+
+    if rc != error::SUCCESS.code_num {
+        context::with_context(context_handle, || settings::clear_config());
+        let _ = context::release(context_handle);
+    }
+    rc
+}
+
+/// Selects the context targeted by the following calls made on this thread
+///
+/// #Params
+/// context_handle: handle of a context created with vcx_context_create_with_config, 0 for the default context
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_context_set_current(context_handle: u32) -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_context_set_current(context_handle: {})", context_handle);
+
+    match context::set_current(context_handle) {
+        Ok(()) => error::SUCCESS.code_num,
+        Err(e) => e.ffi_code(),
+    }
+}
+
+/// Closes the wallet and pool of the context and releases its objects and settings
+///
+/// #Params
+/// context_handle: handle of the context to release
+///
+/// delete: specify whether wallet/pool should be deleted
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_context_release(context_handle: u32, delete: bool) -> u32 {
+    ::error::reset_current_error();
+    info!("vcx_context_release(context_handle: {}, delete: {})", context_handle, delete);
+
+    if context_handle == context::DEFAULT_CONTEXT || !context::is_valid_handle(context_handle) {
+        return error::INVALID_CONTEXT_HANDLE.code_num;
+    }
+
+    context::with_context(context_handle, || _release_context(delete));
+
+    match context::release(context_handle) {
+        Ok(()) => error::SUCCESS.code_num,
+        Err(e) => e.ffi_code(),
+    }
 }
 
 #[no_mangle]
@@ -217,8 +341,38 @@ pub extern fn vcx_error_c_message(error_code: u32) -> *const c_char {
     error::error_c_message(&error_code).as_ptr()
 }
 
+/// Get details for the last error returned on the calling thread, either by a function or to a callback.
+/// Every API call clears the details of the previous error.
+///
+/// Error codes only tell which kind of error happened, the details say what went wrong and which
+/// errors caused it.
+///
+/// #Params
+/// error_json_p: Reference that will contain the error details (if any) or NULL otherwise.
+///     The string is valid until the next API call on the same thread.
+///     {
+///         "error": int - error code,
+///         "message": str - human-readable description of the error,
+///         "causes": [{"error": int, "message": str}] - errors that caused it, outermost first
+///     }
+///
+/// #Returns
+/// Success
+#[no_mangle]
+pub extern fn vcx_get_current_error(error_json_p: *mut *const c_char) -> u32 {
+    info!("vcx_get_current_error >>>");
+
+    if error_json_p.is_null() {
+        return error::INVALID_OPTION.code_num;
+    }
+
+    unsafe { *error_json_p = ::error::get_current_error_c_json(); }
+    error::SUCCESS.code_num
+}
+
 #[no_mangle]
 pub extern fn vcx_update_institution_info(name: *const c_char, logo_url: *const c_char) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(name, error::INVALID_CONFIGURATION.code_num);
     check_useful_c_str!(logo_url, error::INVALID_CONFIGURATION.code_num);
     info!("vcx_update_institution_info(name: {}, logo_url: {})", name, logo_url);
@@ -231,6 +385,7 @@ pub extern fn vcx_update_institution_info(name: *const c_char, logo_url: *const
 
 #[no_mangle]
 pub extern fn vcx_mint_tokens(seed: *const c_char, fees: *const c_char) {
+    ::error::reset_current_error();
 
     let seed = if !seed.is_null() {
         check_useful_opt_c_str!(seed, ());
@@ -343,7 +498,7 @@ mod tests {
         let rc = cb.receive(Some(Duration::from_secs(10)));
         thread::sleep(Duration::from_secs(1));
         assert!(rc.is_err());
-        assert_eq!(get_pool_handle(), Err(error::NO_POOL_OPEN.code_num));
+        assert_eq!(get_pool_handle().unwrap_err().code(), error::NO_POOL_OPEN.code_num);
         assert_eq!(wallet::get_wallet_handle(), 0);
         wallet::delete_wallet(wallet_name).unwrap();
     }
@@ -468,7 +623,7 @@ mod tests {
 
         //Verify shutdown was successful
         vcx_shutdown(true);
-        assert_eq!(settings::get_config_value("wallet_name"), Err(error::INVALID_CONFIGURATION.code_num));
+        assert_eq!(settings::get_config_value("wallet_name").unwrap_err().code(), error::INVALID_CONFIGURATION.code_num);
 
         // Init for the second time works
         ::utils::devsetup::tests::setup_ledger_env();
@@ -594,7 +749,7 @@ mod tests {
             settings::CONFIG_EXPORTED_WALLET_PATH: exported_path,
             settings::CONFIG_WALLET_BACKUP_KEY: settings::DEFAULT_WALLET_BACKUP_KEY,
         }).to_string();
-        assert_eq!(import(&import_config), Err(::error::wallet::WalletError::CommonError(error::WALLET_ALREADY_EXISTS.code_num)));
+        assert_eq!(import(&import_config).unwrap_err().code(), error::WALLET_ALREADY_EXISTS.code_num);
 
         delete_import_wallet_path(dir);
         vcx_shutdown(true);
@@ -641,20 +796,20 @@ mod tests {
         let data = r#"["name","male"]"#;
         let connection = ::connection::build_connection("h1").unwrap();
         let issuer_credential = ::issuer_credential::issuer_credential_create("cred_id".to_string(),"1".to_string(),"8XFh8yBzrpJQmNyZzgoTqB".to_owned(),"credential_name".to_string(),"{\"attr\":\"value\"}".to_owned(), 1).unwrap();
-        let proof = ::proof::create_proof("1".to_string(),"[]".to_string(), "[]".to_string(),"Optional".to_owned()).unwrap();
+        let proof = ::proof::create_proof("1".to_string(),"[]".to_string(), "[]".to_string(), "{}".to_string(),"Optional".to_owned()).unwrap();
         let credentialdef = ::credential_def::create_new_credentialdef("SID".to_string(),"NAME".to_string(),"4fUDR9R7fjwELRvH9JT6HH".to_string(), "id".to_string(), "tag".to_string(),"{}".to_string() ).unwrap();
         let schema = ::schema::create_new_schema("5",  "VsKV7grR1BUE29mG2Fm2kX".to_string(),"name".to_string(), "0.1".to_string(), data.to_string()).unwrap();
         let disclosed_proof = ::disclosed_proof::create_proof("id",::utils::constants::PROOF_REQUEST_JSON).unwrap();
         let credential = ::credential::credential_create_with_offer("name", ::utils::constants::CREDENTIAL_OFFER_JSON).unwrap();
 
         vcx_shutdown(true);
-        assert_eq!(::connection::release(connection),Err(::error::connection::ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
-        assert_eq!(::issuer_credential::release(issuer_credential),Err(::error::issuer_cred::IssuerCredError::InvalidHandle()));
-        assert_eq!(::schema::release(schema).err(),Some(::error::schema::SchemaError::InvalidHandle()));
-        assert_eq!(::proof::release(proof).err(),Some(::error::proof::ProofError::InvalidHandle()));
-        assert_eq!(::credential_def::release(credentialdef),Err(::error::cred_def::CredDefError::InvalidHandle()));
-        assert_eq!(::credential::release(credential), Err(::error::credential::CredentialError::CommonError(error::INVALID_CREDENTIAL_HANDLE.code_num)));
-        assert_eq!(::disclosed_proof::release(disclosed_proof), Result::Err(error::INVALID_DISCLOSED_PROOF_HANDLE.code_num));
+        assert_eq!(::connection::release(connection).unwrap_err().code(), error::INVALID_CONNECTION_HANDLE.code_num);
+        assert_eq!(::issuer_credential::release(issuer_credential).unwrap_err().code(), error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num);
+        assert_eq!(::schema::release(schema).unwrap_err().code(), error::INVALID_SCHEMA_HANDLE.code_num);
+        assert_eq!(::proof::release(proof).unwrap_err().code(), error::INVALID_PROOF_HANDLE.code_num);
+        assert_eq!(::credential_def::release(credentialdef).unwrap_err().code(), error::INVALID_CREDENTIAL_DEF_HANDLE.code_num);
+        assert_eq!(::credential::release(credential).unwrap_err().code(), error::INVALID_CREDENTIAL_HANDLE.code_num);
+        assert_eq!(::disclosed_proof::release(disclosed_proof).unwrap_err().code(), error::INVALID_DISCLOSED_PROOF_HANDLE.code_num);
         assert_eq!(wallet::get_wallet_handle(), 0);
     }
 
@@ -674,6 +829,53 @@ mod tests {
         assert_eq!(c_message,error::INVALID_ATTRIBUTES_STRUCTURE.message);
     }
 
+    #[test]
+    fn test_context_create_and_release() {
+        init!("true");
+        let config = json!({
+            "enable_test_mode": "true",
+            "wallet_key": "tenant_wallet_key",
+            "wallet_name": "tenant_wallet",
+        }).to_string();
+
+        let cb = return_types_u32::Return_U32_U32::new().unwrap();
+        assert_eq!(vcx_context_create_with_config(cb.command_handle,
+                                                  CString::new(config).unwrap().into_raw(),
+                                                  Some(cb.get_callback())),
+                   error::SUCCESS.code_num);
+        let context_handle = cb.receive(Some(Duration::from_secs(10))).unwrap();
+        assert_ne!(context_handle, context::DEFAULT_CONTEXT);
+
+        context::with_context(context_handle, || {
+            assert_eq!(settings::get_config_value(settings::CONFIG_WALLET_NAME).unwrap(), "tenant_wallet");
+            assert_eq!(wallet::get_wallet_handle(), 1);
+        });
+        assert_eq!(settings::get_config_value(settings::CONFIG_WALLET_NAME).unwrap(), settings::DEFAULT_WALLET_NAME);
+
+        assert_eq!(vcx_context_release(context_handle, false), error::SUCCESS.code_num);
+        assert_eq!(vcx_context_set_current(context_handle), error::INVALID_CONTEXT_HANDLE.code_num);
+        assert_eq!(vcx_context_release(context::DEFAULT_CONTEXT, false), error::INVALID_CONTEXT_HANDLE.code_num);
+        assert_eq!(settings::get_config_value(settings::CONFIG_WALLET_NAME).unwrap(), settings::DEFAULT_WALLET_NAME);
+    }
+
+    #[test]
+    fn test_get_current_error() {
+        let err = ::error::VcxError::from(error::INVALID_DID.code_num)
+            .context(error::INVALID_CONFIGURATION.code_num, "institution_did is not a valid DID");
+        let code = err.ffi_code();
+        assert_eq!(code, error::INVALID_CONFIGURATION.code_num);
+
+        let mut error_json_p: *const c_char = ptr::null();
+        assert_eq!(vcx_get_current_error(&mut error_json_p), error::SUCCESS.code_num);
+        let error_json = CStringUtils::c_str_to_string(error_json_p).unwrap().unwrap();
+        let error_json: serde_json::Value = serde_json::from_str(&error_json).unwrap();
+        assert_eq!(error_json["error"], json!(error::INVALID_CONFIGURATION.code_num));
+        assert_eq!(error_json["message"], json!("institution_did is not a valid DID"));
+        assert_eq!(error_json["causes"][0]["error"], json!(error::INVALID_DID.code_num));
+
+        assert_eq!(vcx_get_current_error(ptr::null_mut()), error::INVALID_OPTION.code_num);
+    }
+
     #[test]
     fn test_vcx_version() {
         let return_version = CStringUtils::c_str_to_string(vcx_version()).unwrap().unwrap();
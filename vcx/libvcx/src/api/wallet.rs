@@ -4,14 +4,26 @@ use self::libc::c_char;
 use utils::cstring::CStringUtils;
 use utils::error;
 use utils::error::error_string;
-use error::ToErrorCode;
 use utils::libindy::payments::{pay_a_payee, get_wallet_token_info, create_address};
 use utils::libindy::wallet::{export, import, get_wallet_handle};
 use utils::libindy::wallet;
+use messages::pack;
+use error::{ VcxError, VcxResult };
 use std::path::Path;
 use utils::threadpool::spawn;
 use std::thread;
 
+// the records vcx keeps secrets in are not for apps to read or change
+fn check_record_type(type_: &str) -> VcxResult<()> {
+    match pack::is_reserved_record_type(type_) {
+        true => {
+            warn!("wallet record type {} is reserved for vcx", type_);
+            Err(VcxError::from_code(error::RESERVED_WALLET_RECORD_TYPE.code_num))
+        },
+        false => Ok(()),
+    }
+}
+
 extern {
     pub fn indy_add_wallet_record(command_handle: i32,
                                   wallet_handle: i32,
@@ -59,6 +71,7 @@ extern {
 pub extern fn vcx_wallet_get_token_info(command_handle: u32,
                                      payment_handle: u32,
                                      cb: Option<extern fn(xcommand_handle: u32, err:u32, *const c_char)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     info!("vcx_wallet_get_token_info(command_handle: {}, payment_handle: {})",
@@ -75,10 +88,10 @@ pub extern fn vcx_wallet_get_token_info(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_wallet_get_token_info_cb(command_handle: {}, rc: {}, info: {})",
-                    command_handle, error_string(x), "null");
+                    command_handle, x, "null");
 
         		let msg = CStringUtils::string_to_cstring("".to_string());
-                cb(command_handle, x, msg.as_ptr());
+                cb(command_handle, x.ffi_code(), msg.as_ptr());
             },
         };
 
@@ -101,6 +114,7 @@ pub extern fn vcx_wallet_get_token_info(command_handle: u32,
 pub extern fn vcx_wallet_create_payment_address(command_handle: u32,
                                                 seed: *const c_char,
                                                 cb: Option<extern fn(xcommand_handle: u32, err:u32, address: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     let seed = if !seed.is_null() {
@@ -124,10 +138,10 @@ pub extern fn vcx_wallet_create_payment_address(command_handle: u32,
             },
             Err(x) => {
                 warn!("vcx_wallet_create_payment_address_cb(command_handle: {}, rc: {}, address: {})",
-                    command_handle, error_string(x), "null");
+                    command_handle, x, "null");
 
         		let msg = CStringUtils::string_to_cstring("".to_string());
-                cb(command_handle, x, msg.as_ptr());
+                cb(command_handle, x.ffi_code(), msg.as_ptr());
             },
         };
 
@@ -145,6 +159,7 @@ pub extern fn vcx_wallet_create_payment_address(command_handle: u32,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+/// The 'vcx_packing_key' type holds secret keys of vcx, the record functions fail on it with error 1107
 ///
 /// id: the id ("key") of the record.
 ///
@@ -173,12 +188,15 @@ pub extern fn vcx_wallet_add_record(command_handle: u32,
                                     value: *const c_char,
                                     tags_json: *const c_char,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(value, error::INVALID_OPTION.code_num);
     check_useful_c_str!(tags_json, error::INVALID_OPTION.code_num);
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
+    if let Err(err) = check_record_type(&type_) { return err.ffi_code() }
+
 
     info!("vcx_wallet_add_record(command_handle: {}, type_: {}, id: {}, value: {}, tags_json: {})",
           command_handle, type_, id, value, tags_json);
@@ -193,9 +211,9 @@ pub extern fn vcx_wallet_add_record(command_handle: u32,
             },
             Err(x) => {
                 info!("vcx_wallet_add_record(command_handle: {}, rc: {})",
-                      command_handle, error_string(x));
+                      command_handle, x);
 
-                cb(command_handle, x);
+                cb(command_handle, x.ffi_code());
             },
         };
 
@@ -229,11 +247,14 @@ pub extern fn vcx_wallet_update_record_value(command_handle: u32,
                                              id: *const c_char,
                                              value: *const c_char,
                                              cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(value, error::INVALID_OPTION.code_num);
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
+    if let Err(err) = check_record_type(&type_) { return err.ffi_code() }
+
     info!("vcx_wallet_update_record_value(command_handle: {}, type_: {}, id: {}, value: {})",
           command_handle, type_, id, value);
 
@@ -247,9 +268,9 @@ pub extern fn vcx_wallet_update_record_value(command_handle: u32,
             },
             Err(x) => {
                 info!("vcx_wallet_update_record_value(command_handle: {}, rc: {})",
-                      command_handle, error_string(x));
+                      command_handle, x);
 
-                cb(command_handle, x);
+                cb(command_handle, x.ffi_code());
             },
         };
 
@@ -283,6 +304,7 @@ pub extern fn vcx_wallet_update_record_tags(command_handle: u32,
                                              id: *const c_char,
                                              tags: *const c_char,
                                              cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(tags, error::INVALID_OPTION.code_num);
@@ -320,6 +342,7 @@ pub extern fn vcx_wallet_add_record_tags(command_handle: u32,
                                             id: *const c_char,
                                             tags: *const c_char,
                                             cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(tags, error::INVALID_OPTION.code_num);
@@ -356,6 +379,7 @@ pub extern fn vcx_wallet_delete_record_tags(command_handle: u32,
                                          id: *const c_char,
                                          tags: *const c_char,
                                          cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(tags, error::INVALID_OPTION.code_num);
@@ -391,12 +415,15 @@ pub extern fn vcx_wallet_get_record(command_handle: u32,
                                     id: *const c_char,
                                     options_json: *const c_char,
                                     cb: Option<extern fn(xcommand_handle: u32, err: u32, record_json: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_str!(options_json, error::INVALID_OPTION.code_num);
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
+    if let Err(err) = check_record_type(&type_) { return err.ffi_code() }
+
     info!("vcx_wallet_get_record(command_handle: {}, type_: {}, id: {}, options: {})",
           command_handle, type_, id, options_json);
 
@@ -412,10 +439,10 @@ pub extern fn vcx_wallet_get_record(command_handle: u32,
             },
             Err(x) => {
                 info!("vcx_wallet_get_record(command_handle: {}, rc: {}, record_json: {})",
-                      command_handle, error_string(x), "null");
+                      command_handle, x, "null");
 
                 let msg = CStringUtils::string_to_cstring("".to_string());
-                cb(command_handle, x, msg.as_ptr());
+                cb(command_handle, x.ffi_code(), msg.as_ptr());
             },
         };
 
@@ -447,10 +474,13 @@ pub extern fn vcx_wallet_delete_record(command_handle: u32,
                                             type_: *const c_char,
                                             id: *const c_char,
                                             cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_str!(type_, error::INVALID_OPTION.code_num);
     check_useful_c_str!(id, error::INVALID_OPTION.code_num);
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
 
+    if let Err(err) = check_record_type(&type_) { return err.ffi_code() }
+
     info!("vcx_wallet_delete_record(command_handle: {}, type_: {}, id: {})",
           command_handle, type_, id);
 
@@ -464,9 +494,9 @@ pub extern fn vcx_wallet_delete_record(command_handle: u32,
             },
             Err(x) => {
                 info!("vcx_wallet_delete_record(command_handle: {}, rc: {})",
-                      command_handle, error_string(x));
+                      command_handle, x);
 
-                cb(command_handle, x);
+                cb(command_handle, x.ffi_code());
             },
         };
 
@@ -500,6 +530,7 @@ pub extern fn vcx_wallet_send_tokens(command_handle: u32,
                                      tokens: *const c_char,
                                      recipient: *const c_char,
                                      cb: Option<extern fn(xcommand_handle: u32, err: u32, receipt: *const c_char)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(recipient, error::INVALID_OPTION.code_num);
@@ -522,9 +553,9 @@ pub extern fn vcx_wallet_send_tokens(command_handle: u32,
             },
             Err(e) => {
                 let msg = "Failed to send tokens".to_string();
-                info!("vcx_wallet_send_tokens_cb(command_handle: {}, rc: {}, reciept: {})", command_handle, e.to_error_code(), msg);
+                info!("vcx_wallet_send_tokens_cb(command_handle: {}, rc: {}, reciept: {})", command_handle, e.ffi_code(), msg);
                 let msg = CStringUtils::string_to_cstring("".to_string());
-                cb(command_handle, e.to_error_code(), msg.as_ptr());
+                cb(command_handle, e.ffi_code(), msg.as_ptr());
             },
         };
 
@@ -633,6 +664,7 @@ pub  extern fn vcx_wallet_search_next_records(command_handle: i32,
 pub extern fn vcx_wallet_close_search(command_handle: u32,
                                         search_handle: u32,
                                         cb: Option<extern fn(xcommand_handle: u32, err:u32)>) -> u32 {
+    ::error::reset_current_error();
 
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     info!("vcx_wallet_close_search(command_handle: {}, search_handle: {})",
@@ -668,6 +700,7 @@ pub extern fn vcx_wallet_export(command_handle: u32,
                                 backup_key: *const c_char,
                                 cb: Option<extern fn(xcommand_handle: u32,
                                                      err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(path,  error::INVALID_OPTION.code_num);
     check_useful_c_str!(backup_key, error::INVALID_OPTION.code_num);
@@ -681,7 +714,7 @@ pub extern fn vcx_wallet_export(command_handle: u32,
                 cb(command_handle, return_code);
             }
             Err(e) => {
-                let return_code = e.to_error_code();
+                let return_code = e.ffi_code();
                 warn!("vcx_wallet_export(command_handle: {}, rc: {})", command_handle, return_code);
                 cb(command_handle, return_code);
             }
@@ -713,10 +746,13 @@ pub extern fn vcx_wallet_import(command_handle: u32,
                                 config: *const c_char,
                                 cb: Option<extern fn(xcommand_handle: u32,
                                                      err: u32)>) -> u32 {
+    ::error::reset_current_error();
     check_useful_c_callback!(cb, error::INVALID_OPTION.code_num);
     check_useful_c_str!(config,  error::INVALID_OPTION.code_num);
 
+    let context = ::context::current();
     thread::spawn(move|| {
+        let _context = ::context::enter(context);
         info!("vcx_wallet_import(command_handle: {}, config: ****)", command_handle);
         match import(&config) {
             Ok(_) => {
@@ -725,7 +761,7 @@ pub extern fn vcx_wallet_import(command_handle: u32,
                 cb(command_handle, return_code);
             }
             Err(e) => {
-                let return_code = e.to_error_code();
+                let return_code = e.ffi_code();
                 warn!("vcx_wallet_import(command_handle: {}, rc: {})", command_handle, return_code);
                 cb(command_handle, return_code);
             }
@@ -901,6 +937,29 @@ pub mod tests {
         assert_eq!(cb.receive(Some(Duration::from_secs(10))).err(), Some(error::WALLET_RECORD_NOT_FOUND.code_num));
     }
 
+    #[test]
+    fn test_packing_key_records_are_reserved() {
+        init!("false");
+        let (_, verkey) = pack::create_packing_did().unwrap();
+        let xtype = CStringUtils::string_to_cstring("vcx_packing_key".to_string());
+        let id = CStringUtils::string_to_cstring(verkey);
+        let value = CStringUtils::string_to_cstring("000000000000000000000000Trustee1".to_string());
+        let tags = CStringUtils::string_to_cstring("{}".to_string());
+        let options = CStringUtils::string_to_cstring(json!({"retrieveValue": true}).to_string());
+
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        assert_eq!(vcx_wallet_get_record(cb.command_handle, xtype.as_ptr(), id.as_ptr(), options.as_ptr(), Some(cb.get_callback())),
+                   error::RESERVED_WALLET_RECORD_TYPE.code_num);
+        let cb = return_types_u32::Return_U32::new().unwrap();
+        assert_eq!(vcx_wallet_add_record(cb.command_handle, xtype.as_ptr(), id.as_ptr(), value.as_ptr(), tags.as_ptr(), Some(cb.get_callback())),
+                   error::RESERVED_WALLET_RECORD_TYPE.code_num);
+        assert_eq!(vcx_wallet_update_record_value(cb.command_handle, xtype.as_ptr(), id.as_ptr(), value.as_ptr(), Some(cb.get_callback())),
+                   error::RESERVED_WALLET_RECORD_TYPE.code_num);
+        assert_eq!(vcx_wallet_delete_record(cb.command_handle, xtype.as_ptr(), id.as_ptr(), Some(cb.get_callback())),
+                   error::RESERVED_WALLET_RECORD_TYPE.code_num);
+        teardown!("false");
+    }
+
     #[test]
     fn test_get_record_value_success() {
         init!("false");
@@ -6,7 +6,6 @@ extern crate libc;
 
 use utils::libindy::wallet;
 use utils::error;
-use utils::libindy::signus::create_and_store_my_did;
 use utils::libindy::crypto;
 use utils::json::mapped_key_rewrite;
 use api::VcxStateType;
@@ -14,27 +13,41 @@ use settings;
 use messages::GeneralMessage;
 use messages;
 use messages::invite::{InviteDetail, SenderDetail};
-use messages::get_message::Message;
+use messages::get_message::{ Message, MessagesByDid };
+use messages::pack::{ self, EnvelopeFormat, DidCommService };
+use messages::invitation::{ self, ConnectionInvitation, InviteFormat };
 use serde::Deserialize;
 use self::rmp_serde::{encode, Deserializer};
 use messages::MessageResponseCode::{ MessageAccepted };
 use serde_json::Value;
 use utils::json::KeyMatch;
-use error::connection::ConnectionError;
-use error::ToErrorCode;
+use error::{ VcxError, VcxResult, VcxResultExt };
 use object_cache::ObjectCache;
-use utils::constants::DEFAULT_SERIALIZE_VERSION;
+use utils::serialization::{ self, Versioned };
 
 lazy_static! {
-    static ref CONNECTION_MAP: ObjectCache<Connection> = Default::default();
+    static ref CONNECTION_MAP: ObjectCache<Connection> = ObjectCache::persistent("vcx_connection", Connection::to_string);
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 struct ConnectionOptions {
     #[serde(default)]
     connection_type: Option<String>,
     #[serde(default)]
     phone: Option<String>,
+    #[serde(default)]
+    envelope_format: Option<EnvelopeFormat>,
+}
+
+impl ConnectionOptions {
+    fn from_opt_str(options: &Option<String>) -> VcxResult<ConnectionOptions> {
+        match *options {
+            Some(ref opt) if !opt.trim().is_empty() => serde_json::from_str(opt.trim())
+                // TODO: Implement Correct Error
+                .or(Err(VcxError::from_code(error::CONNECTION_ERROR.code_num))),
+            _ => Ok(ConnectionOptions::default()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -52,35 +65,20 @@ struct Connection {
     agent_vk: String,
     their_pw_did: String,
     their_pw_verkey: String, // used by proofs/credentials when sending to edge device
+    #[serde(default)]
+    envelope_format: EnvelopeFormat,
+    // known when the connection was made from the other agent's invitation
+    #[serde(default)]
+    their_service: Option<DidCommService>,
 }
 
+impl Versioned for Connection {}
+
 impl Connection {
-    fn _connect_send_invite(&mut self, options: Option<String>) -> Result<u32, ConnectionError> {
+    fn _connect_send_invite(&mut self, options: Option<String>) -> VcxResult<u32> {
         debug!("sending invite for connection {}", self.source_id);
 
-        let options_obj: ConnectionOptions = match options {
-            Some(opt) => {
-                match opt.trim().is_empty() {
-                    true => ConnectionOptions {
-                        connection_type: None,
-                        phone: None
-                    },
-                    false => match serde_json::from_str(opt.trim()) {
-                        Ok(val) => val,
-                        // TODO: Refactor Error
-//                        TODO: Implement Correct Error
-//                        Err(_) => return Err(error::INVALID_OPTION.code_num),
-                        Err(_) => return Err(ConnectionError::GeneralConnectionError()),
-                    }
-                }
-            },
-            None => {
-                ConnectionOptions {
-                    connection_type: None,
-                    phone: None
-                }
-            }
-        };
+        let options_obj = ConnectionOptions::from_opt_str(&options)?;
         match messages::send_invite()
             .to(&self.pw_did)
             .to_vk(&self.pw_verkey)
@@ -91,7 +89,7 @@ impl Connection {
             Err(ec) => {
                 // TODO: Refactor Error
                 // TODO: Implement Correct Error
-                return Err(ConnectionError::CommonError(ec))
+                return Err(ec)
             },
             Ok(response) => {
                 self.state = VcxStateType::VcxStateOfferSent;
@@ -101,7 +99,7 @@ impl Connection {
                         error!("error when sending invite for connection {}: {}", self.source_id, x);
                         // TODO: Refactor Error
                         // TODO: Implement Correct Error
-                        return Err(ConnectionError::GeneralConnectionError())
+                        return Err(VcxError::from_code(error::CONNECTION_ERROR.code_num))
                     },
                 };
                 self.invite_url = Some(response[1].clone());
@@ -109,7 +107,7 @@ impl Connection {
             }
         }
     }
-    pub fn delete_connection(&mut self) -> Result<u32, ConnectionError> {
+    pub fn delete_connection(&mut self) -> VcxResult<u32> {
         match messages::delete_connection()
             .to(&self.pw_did)
             .to_vk(&self.pw_verkey)
@@ -117,7 +115,7 @@ impl Connection {
             .agent_vk(&self.agent_vk)
             .send_secure() {
             Err(ec) => {
-                return Err(ConnectionError::CannotDeleteConnection())
+                return Err(VcxError::from_code(error::CANNOT_DELETE_CONNECTION.code_num))
             },
             Ok(response) => {
                 self.state = VcxStateType::VcxStateNone;
@@ -127,7 +125,7 @@ impl Connection {
 
     }
 
-    fn _connect_accept_invite(&mut self, options: Option<String>) -> Result<u32,ConnectionError> {
+    fn _connect_accept_invite(&mut self, options: Option<String>) -> VcxResult<u32> {
         debug!("accepting invite for connection {}", self.source_id);
 
         if let Some(ref details) = self.invite_detail {
@@ -144,7 +142,7 @@ impl Connection {
                 Err(_) => {
                     // TODO: Refactor Error
                     // TODO: Implement Correct Error
-                    Err(ConnectionError::GeneralConnectionError())
+                    Err(VcxError::from_code(error::CONNECTION_ERROR.code_num))
                 },
                 Ok(response) => {
                     self.state = VcxStateType::VcxStateAccepted;
@@ -156,12 +154,24 @@ impl Connection {
             warn!("{} can not connect without invite details", self.source_id);
             // TODO: Refactor Error
             // TODO: Implement Correct Error
-            Err(ConnectionError::GeneralConnectionError())
+            Err(VcxError::from_code(error::CONNECTION_ERROR.code_num))
         }
     }
 
 
-    fn connect(&mut self, options: Option<String>) -> Result<u32,ConnectionError> {
+    fn connect(&mut self, options: Option<String>) -> VcxResult<u32> {
+        if let Some(format) = ConnectionOptions::from_opt_str(&options)?.envelope_format {
+            // connections made before DIDComm support have keys that only libindy can use
+            if format == EnvelopeFormat::DIDComm {
+                pack::check_packing_key(&self.pw_verkey).map_err(|err| {
+                    warn!("connection {} can not use didcomm envelopes: {}", self.source_id, err);
+                    err
+                })?;
+            }
+            debug!("connection {} will use {:?} envelopes", self.source_id, format);
+            self.envelope_format = format;
+        }
+
         match self.state {
             VcxStateType::VcxStateInitialized
                 | VcxStateType::VcxStateOfferSent => self._connect_send_invite(options),
@@ -170,7 +180,7 @@ impl Connection {
                 warn!("connection {} in state {} not ready to connect",self.source_id, self.state as u32);
                 // TODO: Refactor Error
                 // TODO: Implement Correct Error
-                Err(ConnectionError::GeneralConnectionError())
+                Err(VcxError::from_code(error::CONNECTION_ERROR.code_num))
             }
         }
     }
@@ -207,6 +217,12 @@ impl Connection {
 
     fn get_source_id(&self) -> &String { &self.source_id }
 
+    fn get_envelope_format(&self) -> EnvelopeFormat { self.envelope_format }
+    fn set_envelope_format(&mut self, format: EnvelopeFormat) { self.envelope_format = format; }
+
+    fn get_their_service(&self) -> &Option<DidCommService> { &self.their_service }
+    fn set_their_service(&mut self, service: DidCommService) { self.their_service = Some(service); }
+
     fn ready_to_connect(&self) -> bool {
         if self.state == VcxStateType::VcxStateNone || self.state == VcxStateType::VcxStateAccepted {
             false
@@ -215,19 +231,12 @@ impl Connection {
         }
     }
 
-    fn from_str(s: &str) -> Result<Self, ConnectionError> {
-        let s:Value = serde_json::from_str(&s)
-            .or(Err(ConnectionError::InvalidJson()))?;
-        let connection: Connection = serde_json::from_value(s["data"].clone())
-            .or(Err(ConnectionError::InvalidJson()))?;
-        Ok(connection)
+    fn from_str(s: &str) -> VcxResult<Self> {
+        serialization::deserialize(s).context(error::INVALID_JSON.code_num, "cannot deserialize connection")
     }
 
     fn to_string(&self) -> String {
-        json!({
-            "version": DEFAULT_SERIALIZE_VERSION,
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
 }
 
@@ -235,108 +244,113 @@ pub fn is_valid_handle(handle: u32) -> bool {
     CONNECTION_MAP.has_handle(handle)
 }
 
-pub fn set_agent_did(handle: u32, did: &str) -> Result<(), ConnectionError> {
+/// The context the connection was created in, calls on the connection run in it.
+pub fn get_context(handle: u32) -> u32 {
+    CONNECTION_MAP.context_of(handle)
+}
+
+pub fn set_agent_did(handle: u32, did: &str) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_agent_did(did);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_agent_did(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_agent_did(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_agent_did().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_pw_did(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_pw_did(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_pw_did().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn set_pw_did(handle: u32, did: &str) -> Result<(), ConnectionError> {
+pub fn set_pw_did(handle: u32, did: &str) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_pw_did(did);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_their_pw_did(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_their_pw_did(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_their_pw_did().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn set_their_pw_did(handle: u32, did: &str) -> Result<(), ConnectionError>{
+pub fn set_their_pw_did(handle: u32, did: &str) -> VcxResult<()>{
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_their_pw_did(did);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_their_pw_verkey(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_their_pw_verkey(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_their_pw_verkey().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn set_their_pw_verkey(handle: u32, did: &str) -> Result<(), ConnectionError> {
+pub fn set_their_pw_verkey(handle: u32, did: &str) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| { cxn.set_their_pw_verkey(did); Ok(()) }).map_err(|e| {
-        ConnectionError::InvalidHandle()
+        VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)
     })
 }
 
-pub fn get_uuid(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_uuid(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_uuid().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn set_uuid(handle: u32, uuid: &str) -> Result<(), ConnectionError> {
+pub fn set_uuid(handle: u32, uuid: &str) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_uuid(uuid);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
 // TODO: Add NO_ENDPOINT error to connection error
-pub fn get_endpoint(handle: u32) -> Result<String, u32> {
+pub fn get_endpoint(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_endpoint().clone())
-    }).or(Err(error::NO_ENDPOINT.code_num))
+    }).or(Err(VcxError::from_code(error::NO_ENDPOINT.code_num)))
 }
 
-pub fn set_endpoint(handle: u32, endpoint: &str) -> Result<(), ConnectionError> {
+pub fn set_endpoint(handle: u32, endpoint: &str) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_endpoint(endpoint);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_agent_verkey(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_agent_verkey(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_agent_verkey().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn set_agent_verkey(handle: u32, verkey: &str) -> Result<(), ConnectionError>{
+pub fn set_agent_verkey(handle: u32, verkey: &str) -> VcxResult<()>{
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_agent_verkey(verkey);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_pw_verkey(handle: u32) -> Result<String, ConnectionError> {
+pub fn get_pw_verkey(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_pw_verkey().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn set_pw_verkey(handle: u32, verkey: &str) -> Result<(), ConnectionError> {
+pub fn set_pw_verkey(handle: u32, verkey: &str) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_pw_verkey(verkey);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
 pub fn get_state(handle: u32) -> u32 {
@@ -348,20 +362,51 @@ pub fn get_state(handle: u32) -> u32 {
     }
 }
 
-pub fn set_state(handle: u32, state: VcxStateType) -> Result<(), ConnectionError> {
+pub fn set_state(handle: u32, state: VcxStateType) -> VcxResult<()> {
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_state(state);
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
+}
+
+pub fn get_envelope_format(handle: u32) -> VcxResult<EnvelopeFormat> {
+    CONNECTION_MAP.get(handle, |cxn| {
+        Ok(cxn.get_envelope_format())
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
+}
+
+pub fn set_envelope_format(handle: u32, format: EnvelopeFormat) -> VcxResult<()> {
+    CONNECTION_MAP.get_mut(handle, |cxn| {
+        cxn.set_envelope_format(format);
+        Ok(())
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn get_source_id(handle: u32) -> Result<String, ConnectionError> {
+pub fn set_their_service(handle: u32, service: DidCommService) -> VcxResult<()> {
+    CONNECTION_MAP.get_mut(handle, |cxn| {
+        cxn.set_their_service(service.clone());
+        Ok(())
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
+}
+
+/// Service messages are delivered to directly, instead of going through our agent.
+/// Only DIDComm connections made from the other agent's invitation have one.
+pub fn get_didcomm_service(handle: u32) -> VcxResult<Option<DidCommService>> {
+    CONNECTION_MAP.get(handle, |cxn| {
+        match cxn.get_envelope_format() {
+            EnvelopeFormat::DIDComm => Ok(cxn.get_their_service().clone()),
+            EnvelopeFormat::Bundled => Ok(None),
+        }
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
+}
+
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |cxn| {
         Ok(cxn.get_source_id().clone())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn create_agent_pairwise(handle: u32) -> Result<u32, ConnectionError> {
+pub fn create_agent_pairwise(handle: u32) -> VcxResult<u32> {
     debug!("creating pairwise keys on agent for connection {}", get_source_id(handle).unwrap_or_default());
     let pw_did = get_pw_did(handle)?;
     let pw_verkey = get_pw_verkey(handle)?;
@@ -370,24 +415,24 @@ pub fn create_agent_pairwise(handle: u32) -> Result<u32, ConnectionError> {
         .for_did(&pw_did)
         .for_verkey(&pw_verkey)
         .send_secure()
-        .or(Err(ConnectionError::InvalidWalletSetup()))?;   // Throw a context specific error
+        .or(Err(VcxError::from_code(error::INVALID_WALLET_SETUP.code_num)))?;   // Throw a context specific error
     debug!("create key for connection: {} with did/vk: {:?}",  get_source_id(handle).unwrap_or_default(),  result);
     set_agent_did(handle,&result[0]).err();
     set_agent_verkey(handle,&result[1]).err();
     Ok(error::SUCCESS.code_num)
 }
 
-pub fn update_agent_profile(handle: u32) -> Result<u32, ConnectionError> {
+pub fn update_agent_profile(handle: u32) -> VcxResult<u32> {
     debug!("updating agent config for connection {}", get_source_id(handle).unwrap_or_default());
     let pw_did = get_pw_did(handle)?;
     if let Ok(name) = settings::get_config_value(settings::CONFIG_INSTITUTION_NAME) {
         match messages::update_data()
             .to(&pw_did)
             .name(&name)
-            .logo_url(&settings::get_config_value(settings::CONFIG_INSTITUTION_LOGO_URL).map_err(|e| ConnectionError::CommonError(e))?)
+            .logo_url(&settings::get_config_value(settings::CONFIG_INSTITUTION_LOGO_URL)?)
             .send_secure() {
             Ok(_) => Ok(error::SUCCESS.code_num),
-            Err(ec) => Err(ConnectionError::CommonError(ec)),
+            Err(ec) => Err(ec),
         }
     } else { Ok(error::SUCCESS.code_num) }
 }
@@ -397,7 +442,7 @@ pub fn update_agent_profile(handle: u32) -> Result<u32, ConnectionError> {
 //       you can call create_connection without test_mode and you don't have to build a wallet or
 //       mock the agency during the connection phase
 //
-fn create_connection(source_id: &str) -> Result<u32, ConnectionError> {
+fn create_connection(source_id: &str) -> VcxResult<u32> {
     // This is a new connection
 
     let c = Connection {
@@ -413,20 +458,22 @@ fn create_connection(source_id: &str) -> Result<u32, ConnectionError> {
         agent_vk: String::new(),
         their_pw_did: String::new(),
         their_pw_verkey: String::new(),
+        envelope_format: EnvelopeFormat::default(),
+        their_service: None,
     };
 
-    let new_handle = CONNECTION_MAP.add(c).map_err(|key| ConnectionError::CreateError(key))?;
+    let new_handle = CONNECTION_MAP.add(c).map_err(|key| VcxError::from_code(error::CREATE_CONNECTION_ERROR.code_num))?;
     debug!("creating connection: {} {}", new_handle, source_id);
     Ok(new_handle)
 
 }
 
-fn init_connection(handle: u32) -> Result<u32, ConnectionError> {
-    let (my_did, my_verkey) = match create_and_store_my_did(None) {
+fn init_connection(handle: u32) -> VcxResult<u32> {
+    let (my_did, my_verkey) = match pack::create_packing_did() {
         Ok(y) => y,
         Err(x) => {
             error!("{} could not create DID/VK: {}", get_source_id(handle).unwrap_or_default(), x);
-            return Err(ConnectionError::CommonError(x))
+            return Err(x)
         },
     };
 
@@ -457,7 +504,7 @@ fn init_connection(handle: u32) -> Result<u32, ConnectionError> {
     Ok(error::SUCCESS.code_num)
 }
 
-pub fn build_connection(source_id: &str) -> Result<u32,ConnectionError> {
+pub fn build_connection(source_id: &str) -> VcxResult<u32> {
     let new_handle = create_connection(source_id)?;
 
     match init_connection(new_handle) {
@@ -469,25 +516,10 @@ pub fn build_connection(source_id: &str) -> Result<u32,ConnectionError> {
     }
 }
 
-pub fn build_connection_with_invite(source_id: &str, details: &str) -> Result<u32,ConnectionError> {
+pub fn build_connection_with_invite(source_id: &str, details: &str) -> VcxResult<u32> {
     debug!("using invite to create connection {}", source_id);
 
-    let details:Value = serde_json::from_str(&details)
-        .or(Err(ConnectionError::CommonError(error::INVALID_JSON.code_num)))?;
-
-    let invite_details:InviteDetail = match serde_json::from_value(details.clone()) {
-        Ok(x) => x,
-        Err(x) => {
-            // Try converting to abbreviated
-            match unabbrv_event_detail(details) {
-                Ok(x) => match serde_json::from_value(x) {
-                    Ok(x) => x,
-                    Err(x) => return Err(ConnectionError::CommonError(error::INVALID_JSON.code_num)),
-                }
-                Err(_) => return Err(ConnectionError::CommonError(error::INVALID_JSON.code_num)),
-            }
-        },
-    };
+    let invite_details = parse_invite(details)?;
 
     let new_handle = create_connection(source_id)?;
 
@@ -501,6 +533,7 @@ pub fn build_connection_with_invite(source_id: &str, details: &str) -> Result<u3
 
     set_their_pw_did(new_handle, invite_details.sender_detail.did.as_str()).err();
     set_their_pw_verkey(new_handle, invite_details.sender_detail.verkey.as_str()).err();
+    set_their_service(new_handle, ConnectionInvitation::from_invite_detail(&invite_details).service()).err();
 
     set_invite_details(new_handle, invite_details).err();
 
@@ -509,16 +542,44 @@ pub fn build_connection_with_invite(source_id: &str, details: &str) -> Result<u3
     Ok(new_handle)
 }
 
-pub fn parse_acceptance_details(handle: u32, message: &Message) -> Result<SenderDetail, ConnectionError> {
+/// Accepts the invite json (full or abbreviated), a connection invitation or an invitation url.
+fn parse_invite(details: &str) -> VcxResult<InviteDetail> {
+    if invitation::is_invitation_url(details) {
+        return ConnectionInvitation::from_url(details.trim())
+            .and_then(|invitation| invitation.to_invite_detail());
+    }
+
+    let details:Value = serde_json::from_str(&details)
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    if invitation::is_connection_invitation(&details) {
+        let invitation: ConnectionInvitation = serde_json::from_value(details)
+            .or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))?;
+        return invitation.to_invite_detail();
+    }
+
+    match serde_json::from_value(details.clone()) {
+        Ok(x) => Ok(x),
+        Err(x) => {
+            // Try converting to abbreviated
+            match unabbrv_event_detail(details) {
+                Ok(x) => serde_json::from_value(x).or(Err(VcxError::from(error::INVALID_JSON.code_num))),
+                Err(_) => Err(VcxError::from(error::INVALID_JSON.code_num)),
+            }
+        },
+    }
+}
+
+pub fn parse_acceptance_details(handle: u32, message: &Message) -> VcxResult<SenderDetail> {
     debug!("connection {} parsing acceptance details for message {:?}", get_source_id(handle).unwrap_or_default(), message);
-    let my_vk = settings::get_config_value(settings::CONFIG_SDK_TO_REMOTE_VERKEY).map_err(|e| ConnectionError::CommonError(e))?;
+    let my_vk = settings::get_config_value(settings::CONFIG_SDK_TO_REMOTE_VERKEY)?;
     let payload = messages::to_u8(
         message.payload
         .as_ref()
-        .ok_or(ConnectionError::CommonError(error::INVALID_MSGPACK.code_num))?
+        .ok_or(VcxError::from(error::INVALID_MSGPACK.code_num))?
     );
     // TODO: check returned verkey
-    let (_, payload) = crypto::parse_msg(&my_vk,&payload).map_err(|e| {ConnectionError::CommonError(e)})?;
+    let (_, payload) = crypto::parse_msg(&my_vk,&payload)?;
 
     trace!("deserializing GetMsgResponse: {:?}", payload);
 
@@ -527,18 +588,18 @@ pub fn parse_acceptance_details(handle: u32, message: &Message) -> Result<Sender
         Ok(x) => x,
         Err(x) => {
             error!("Could not parse outer msg: {}", x);
-            return Err(ConnectionError::CommonError(error::INVALID_MSGPACK.code_num))
+            return Err(VcxError::from(error::INVALID_MSGPACK.code_num))
         },
     };
 
     let payload = messages::to_u8(&response.msg);
     // TODO: Refactor Error
-    let details = messages::invite::parse_invitation_acceptance_details(payload).map_err(|e| {ConnectionError::CommonError(e)})?;
+    let details = messages::invite::parse_invitation_acceptance_details(payload)?;
 
     Ok(details)
 }
 
-pub fn update_state(handle: u32) -> Result<u32, ConnectionError> {
+pub fn update_state(handle: u32) -> VcxResult<u32> {
     debug!("updating state for connection {}", get_source_id(handle).unwrap_or_default());
     // TODO: Refactor Error
     let pw_did = get_pw_did(handle)?;
@@ -554,69 +615,77 @@ pub fn update_state(handle: u32) -> Result<u32, ConnectionError> {
         .send_secure() {
         Err(x) => {
             error!("could not update state for handle {}: {}",  handle, x);
-            // TODO: Refactor Error
-            Err(ConnectionError::CommonError(error::POST_MSG_FAILURE.code_num))
+            Err(x)
         }
         Ok(response) => {
             debug!("connection {} update state response: {:?}", get_source_id(handle).unwrap_or_default(), response);
-            if get_state(handle) == VcxStateType::VcxStateOfferSent as u32 || get_state(handle) == VcxStateType::VcxStateInitialized as u32{
-                 for i in response {
-                     if i.status_code == MessageAccepted.as_string() && i.msg_type == "connReqAnswer" {
-                         // TODO: Refactor Error
-                          let details = parse_acceptance_details(handle, &i)?;
-                          set_their_pw_did(handle, &details.did).ok();
-                          set_their_pw_verkey(handle, &details.verkey).ok();
-                          set_state(handle, VcxStateType::VcxStateAccepted).ok();
-                     }
-                 }
-            };
+            process_acceptance(handle, &response)?;
 
             Ok(error::SUCCESS.code_num)
             //TODO: add expiration handling
         },
     }
 }
-pub fn delete_connection(handle:u32) -> Result<u32, ConnectionError> {
+
+fn process_acceptance(handle: u32, messages: &[Message]) -> VcxResult<()> {
+    if get_state(handle) == VcxStateType::VcxStateOfferSent as u32 || get_state(handle) == VcxStateType::VcxStateInitialized as u32{
+         for i in messages {
+             if i.status_code == MessageAccepted.as_string() && i.msg_type == "connReqAnswer" {
+                 // TODO: Refactor Error
+                  let details = parse_acceptance_details(handle, &i)?;
+                  set_their_pw_did(handle, &details.did).ok();
+                  set_their_pw_verkey(handle, &details.verkey).ok();
+                  set_state(handle, VcxStateType::VcxStateAccepted).ok();
+             }
+         }
+    };
+    Ok(())
+}
+
+/// Updates the state from messages the dispatcher already downloaded instead of polling the agency.
+pub fn update_state_with_messages(handle: u32, messages: &MessagesByDid) -> VcxResult<u32> {
+    let pw_did = get_pw_did(handle)?;
+    if let Some(messages) = messages.get(&pw_did) {
+        process_acceptance(handle, messages)?;
+    }
+    Ok(get_state(handle))
+}
+
+pub fn get_handles() -> Vec<u32> {
+    CONNECTION_MAP.handles()
+}
+
+pub fn delete_connection(handle:u32) -> VcxResult<u32> {
     CONNECTION_MAP.get_mut(handle, |t| {
-        match t.delete_connection() {
-            Ok(x) => Ok(x),
-            Err(e) => {
-                return Err(e.to_error_code())
-            },
-        }
-    }).or(Err(ConnectionError::CannotDeleteConnection())).and(release(handle))
+        t.delete_connection()
+    }).or(Err(VcxError::from_code(error::CANNOT_DELETE_CONNECTION.code_num))).and(release(handle))
 }
 
-pub fn connect(handle: u32, options: Option<String>) -> Result<u32, ConnectionError> {
+pub fn connect(handle: u32, options: Option<String>) -> VcxResult<u32> {
     CONNECTION_MAP.get_mut(handle, |t| {
-        t.connect(options.clone()).map_err(|ec| ec.to_error_code())
-    }).map_err(|ec| ConnectionError::CommonError(ec))
+        t.connect(options.clone())
+    })
 }
 
-pub fn to_string(handle: u32) -> Result<String,u32> {
+pub fn to_string(handle: u32) -> VcxResult<String> {
     CONNECTION_MAP.get(handle, |t| {
-        // TODO: Make this an error.to_error_code and back again?
         Ok(Connection::to_string(&t))
-    }).or(Err(error::INVALID_CONNECTION_HANDLE.code_num))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn from_string(connection_data: &str) -> Result<u32, ConnectionError> {
-    let derived_connection: Connection = match Connection::from_str(connection_data) {
-        Ok(x) => x,
-        Err(_) => return Err(ConnectionError::CommonError(error::INVALID_JSON.code_num)),
-    };
+pub fn from_string(connection_data: &str) -> VcxResult<u32> {
+    let derived_connection: Connection = Connection::from_str(connection_data)?;
 
-
-    let new_handle = CONNECTION_MAP.add(derived_connection).map_err(|ec| ConnectionError::CommonError(ec))?;
+    let new_handle = CONNECTION_MAP.add(derived_connection)?;
     debug!("inserting handle {} source_id {} into connection table", new_handle, get_source_id(new_handle).unwrap_or_default());
 
     Ok(new_handle)
 }
 
-pub fn release(handle: u32) -> Result< u32, ConnectionError> {
+pub fn release(handle: u32) -> VcxResult<u32> {
     match CONNECTION_MAP.release(handle) {
-        Ok(_) => Ok(ConnectionError::CommonError(error::SUCCESS.code_num).to_error_code()),
-        Err(_) => Err(ConnectionError::InvalidHandle())
+        Ok(_) => Ok(error::SUCCESS.code_num),
+        Err(_) => Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))
     }
 }
 
@@ -628,49 +697,66 @@ pub fn release_all() {
     };
 }
 
-pub fn get_invite_details(handle: u32, abbreviated:bool) -> Result<String, ConnectionError> {
-    CONNECTION_MAP.get(handle, |t| {
-        match abbreviated {
-            false => {
-                Ok(serde_json::to_string(&t.invite_detail)
-                    .or(Err(ConnectionError::InviteDetailError())))
-            },
-            true => {
-                let details = serde_json::to_value(&t.invite_detail).or(Err(ConnectionError::InviteDetailError().to_error_code()))?;
-                let abbr = abbrv_event_detail(details)?;
-                Ok(serde_json::to_string(&abbr).or(Err(ConnectionError::InviteDetailError())))
-            },
-        }
-    }).or(Err(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)))?
+/// Reloads the stored objects, see object_store
+pub fn restore_all() -> VcxResult<usize> {
+    CONNECTION_MAP.restore(|data| Connection::from_str(data).ok())
+}
 
+pub fn get_invite_details(handle: u32, abbreviated:bool) -> VcxResult<String> {
+    match abbreviated {
+        false => get_invite_details_with_format(handle, InviteFormat::Json),
+        true => get_invite_details_with_format(handle, InviteFormat::Abbreviated),
+    }
 }
 
-pub fn set_invite_details(handle: u32, invite_detail: InviteDetail) -> Result<(), ConnectionError>{
+pub fn get_invite_details_with_format(handle: u32, format: InviteFormat) -> VcxResult<String> {
+    let invite_detail = CONNECTION_MAP.get(handle, |t| Ok(t.invite_detail.clone()))
+        .or(Err(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)))?;
+
+    match format {
+        InviteFormat::Json => {
+            serde_json::to_string(&invite_detail).or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))
+        },
+        InviteFormat::Abbreviated => {
+            let details = serde_json::to_value(&invite_detail).or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))?;
+            let abbr = abbrv_event_detail(details)?;
+            serde_json::to_string(&abbr).or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))
+        },
+        InviteFormat::Url | InviteFormat::QrCode => {
+            let invite_detail = invite_detail.ok_or(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num))?;
+            let invitation = ConnectionInvitation::from_invite_detail(&invite_detail);
+            let rendered = match format {
+                InviteFormat::Url => invitation.to_url(),
+                _ => invitation.to_json(),
+            };
+            rendered
+        },
+    }
+}
+
+pub fn set_invite_details(handle: u32, invite_detail: InviteDetail) -> VcxResult<()>{
     CONNECTION_MAP.get_mut(handle, |cxn| {
         cxn.set_invite_detail(invite_detail.clone());
 //        TODO: Verify that this is ok to do...seems not rusty.
         Ok(())
-    }).or(Err(ConnectionError::InvalidHandle()))
+    }).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))
 }
 
-pub fn parse_invite_detail(response: &str) -> Result<InviteDetail, ConnectionError> {
+pub fn parse_invite_detail(response: &str) -> VcxResult<InviteDetail> {
 
     let details: InviteDetail = match serde_json::from_str(response) {
         Ok(x) => x,
         Err(x) => {
             debug!("Connect called without a valid response from server: {}", x);
-            return Err(ConnectionError::InviteDetailError());
+            return Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num));
         },
     };
 
     Ok(details)
 }
 
-// TODO: Refactor Error
-// this will become a CommonError, because multiple types (Connection/Issuer Credential) use this function
-// Possibly this function moves out of this file.
-// On second thought, this should stick as a ConnectionError.
-pub fn generate_encrypted_payload(my_vk: &str, their_vk: &str, data: &str, msg_type: &str) -> Result<Vec<u8>, ConnectionError> {
+// TODO: Possibly this function moves out of this file, multiple types (Connection/Issuer Credential) use it.
+pub fn generate_encrypted_payload(my_vk: &str, their_vk: &str, data: &str, msg_type: &str) -> VcxResult<Vec<u8>> {
     let my_payload = messages::Payload {
         msg_info: messages::MsgInfo { name: msg_type.to_string(), ver: "1.0".to_string(), fmt: "json".to_string(), },
         msg: data.to_string(),
@@ -679,11 +765,27 @@ pub fn generate_encrypted_payload(my_vk: &str, their_vk: &str, data: &str, msg_t
         Ok(x) => x,
         Err(x) => {
             error!("could not encode create_keys msg: {}", x);
-            return Err(ConnectionError::InvalidMessagePack());
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
         },
     };
     trace!("Sending payload: {:?}", bytes);
-    crypto::prep_msg(wallet::get_wallet_handle(),&my_vk, &their_vk, &bytes).map_err(|ec| ConnectionError::CommonError(ec))
+    crypto::prep_msg(wallet::get_wallet_handle(),&my_vk, &their_vk, &bytes)
+}
+
+/// Encrypts a payload for the other side of the connection using the connection's envelope format.
+pub fn generate_payload_for_connection(handle: u32, my_vk: &str, their_vk: &str, data: &str, msg_type: &str) -> VcxResult<Vec<u8>> {
+    match get_envelope_format(handle)? {
+        EnvelopeFormat::Bundled => generate_encrypted_payload(my_vk, their_vk, data, msg_type),
+        EnvelopeFormat::DIDComm => {
+            let my_payload = messages::Payload {
+                msg_info: messages::MsgInfo { name: msg_type.to_string(), ver: "1.0".to_string(), fmt: "json".to_string(), },
+                msg: data.to_string(),
+            };
+            let bytes = serde_json::to_vec(&my_payload).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+            trace!("Packing payload: {:?}", bytes);
+            pack::pack_message(&bytes, &[their_vk.to_string()], Some(my_vk))
+        },
+    }
 }
 
 
@@ -758,11 +860,11 @@ lazy_static!{
     };
 }
 
-fn abbrv_event_detail(val: Value) -> Result<Value, u32> {
+fn abbrv_event_detail(val: Value) -> VcxResult<Value> {
     mapped_key_rewrite(val, &ABBREVIATIONS)
 }
 
-fn unabbrv_event_detail(val: Value) -> Result<Value, u32> {
+fn unabbrv_event_detail(val: Value) -> VcxResult<Value> {
     mapped_key_rewrite(val, &UNABBREVIATIONS)
 }
 
@@ -801,7 +903,7 @@ pub mod tests {
         init!("true");
         settings::set_config_value(settings::CONFIG_ENABLE_TEST_MODE,"false");
         assert_eq!(build_connection("This Should Fail").err(),
-                   Some(ConnectionError::CommonError(error::INVALID_WALLET_HANDLE.code_num)));
+                   Some(VcxError::from(error::INVALID_WALLET_HANDLE.code_num)));
         assert!(build_connection_with_invite("This Should Fail", "BadDetailsFoobar").is_err());
     }
     #[test]
@@ -835,7 +937,7 @@ pub mod tests {
     fn test_connection_release_fails() {
         let rc = release(1);
         assert_eq!(rc.err(),
-                   Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
+                   Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
     }
 
     #[test]
@@ -856,7 +958,7 @@ pub mod tests {
     fn test_parse_invite_details() {
         let invite = parse_invite_detail(INVITE_DETAIL_STRING).unwrap();
         assert_eq!(invite.sender_detail.verkey,"ESE6MnqAyjRigduPG454vfLvKhMbmaZjy9vqxCnSKQnp");
-        assert_eq!(parse_invite_detail(BAD_INVITE_DETAIL_STRING).err(), Some(ConnectionError::InviteDetailError()));
+        assert_eq!(parse_invite_detail(BAD_INVITE_DETAIL_STRING).err(), Some(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)));
     }
 
     #[test]
@@ -876,6 +978,7 @@ pub mod tests {
             agent_vk: "EkVTa7SCJ5SntpYyX7CSb2pcBhiVGT9kWSagA8a9T69A".to_string(),
             their_pw_did: String::new(),
             their_pw_verkey: String::new(),
+            envelope_format: EnvelopeFormat::default(),
         };
 
         let handle = CONNECTION_MAP.add(c).unwrap();
@@ -886,7 +989,7 @@ pub mod tests {
         let details = get_invite_details(handle, true).unwrap();
         assert!(details.contains("\"dp\":"));
         assert_eq!(get_invite_details(12345, true).err(),
-                   Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
+                   Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
     }
 
     #[test]
@@ -913,6 +1016,22 @@ pub mod tests {
         assert_eq!(first_string,second_string);
     }
 
+    #[test]
+    fn test_deserialize_unversioned() {
+        init!("true");
+        let handle = build_connection("test_deserialize_unversioned").unwrap();
+        let serialized: Value = serde_json::from_str(&to_string(handle).unwrap()).unwrap();
+
+        // connections serialized before the version envelope was added
+        let handle = from_string(&serialized["data"].to_string()).unwrap();
+        assert_eq!(get_source_id(handle).unwrap(), "test_deserialize_unversioned");
+
+        let newer = json!({"version": "99.0", "data": serialized["data"]}).to_string();
+        let err = from_string(&newer).unwrap_err();
+        assert_eq!(err.code(), error::INVALID_JSON.code_num);
+        assert_eq!(err.cause().map(|cause| cause.code()), Some(error::UNKNOWN_SERIALIZE_VERSION.code_num));
+    }
+
     #[test]
     fn test_retry_connection() {
         init!("true");
@@ -923,11 +1042,59 @@ pub mod tests {
         connect(handle, Some(String::new())).unwrap();
     }
 
+    #[test]
+    fn test_connect_with_didcomm_envelopes() {
+        init!("true");
+        let handle = build_connection("test_connect_with_didcomm_envelopes").unwrap();
+        assert_eq!(get_envelope_format(handle).unwrap(), EnvelopeFormat::Bundled);
+        let legacy = generate_payload_for_connection(handle, VERKEY, VERKEY, "{}", "PROOF").unwrap();
+        assert!(!pack::is_packed(&legacy));
+
+        connect(handle, Some(json!({"envelope_format": "didcomm"}).to_string())).unwrap();
+        assert_eq!(get_envelope_format(handle).unwrap(), EnvelopeFormat::DIDComm);
+
+        let payload = generate_payload_for_connection(handle, VERKEY, VERKEY, "{\"hello\":\"world\"}", "PROOF").unwrap();
+        assert!(pack::is_packed(&payload));
+        let (_, data) = messages::decrypt_payload(VERKEY, &payload).unwrap();
+        assert_eq!(messages::extract_json_payload(&data).unwrap(), "{\"hello\":\"world\"}");
+
+        // connections serialized before envelopes were selectable keep the legacy format
+        let mut serialized: Value = serde_json::from_str(&to_string(handle).unwrap()).unwrap();
+        assert_eq!(serialized["data"]["envelope_format"], json!("didcomm"));
+        serialized["data"].as_object_mut().unwrap().remove("envelope_format");
+        let handle = from_string(&serialized.to_string()).unwrap();
+        assert_eq!(get_envelope_format(handle).unwrap(), EnvelopeFormat::Bundled);
+
+        assert_eq!(get_envelope_format(0).err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(generate_payload_for_connection(0, VERKEY, VERKEY, "{}", "PROOF").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+    }
+
+    #[test]
+    fn test_didcomm_service_from_invitation() {
+        init!("true");
+        let handle = build_connection("test_didcomm_service_from_invitation").unwrap();
+        connect(handle, Some(json!({"envelope_format": "didcomm"}).to_string())).unwrap();
+        assert_eq!(get_didcomm_service(handle).unwrap(), None);
+
+        let details: InviteDetail = serde_json::from_str(INVITE_DETAIL_STRING).unwrap();
+        let handle = build_connection_with_invite("alice", INVITE_DETAIL_STRING).unwrap();
+        assert_eq!(get_didcomm_service(handle).unwrap(), None);
+
+        connect(handle, Some(json!({"envelope_format": "didcomm"}).to_string())).unwrap();
+        let service = get_didcomm_service(handle).unwrap().unwrap();
+        assert_eq!(service.recipient_keys, vec![details.sender_detail.verkey.clone()]);
+        assert_eq!(service.routing_keys, vec![details.sender_detail.agent_key_dlg_proof.agent_delegated_key.clone(),
+                                              details.sender_agency_detail.verkey.clone()]);
+        assert_eq!(service.service_endpoint, details.sender_agency_detail.endpoint);
+
+        assert_eq!(get_didcomm_service(0).err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+    }
+
     #[test]
     fn test_bad_wallet_connection_fails() {
         init!("true");
         settings::set_config_value(settings::CONFIG_ENABLE_TEST_MODE,"false");
-        assert_eq!(build_connection("test_bad_wallet_connection_fails").unwrap_err().to_error_code(),error::INVALID_WALLET_HANDLE.code_num);
+        assert_eq!(build_connection("test_bad_wallet_connection_fails").unwrap_err().code(),error::INVALID_WALLET_HANDLE.code_num);
     }
 
     #[test]
@@ -960,6 +1127,7 @@ pub mod tests {
             agent_vk: "EkVTa7SCJ5SntpYyX7CSb2pcBhiVGT9kWSagA8a9T69A".to_string(),
             their_pw_did: String::new(),
             their_pw_verkey: String::new(),
+            envelope_format: EnvelopeFormat::default(),
         };
 
         let handle = CONNECTION_MAP.add(c).unwrap();
@@ -982,7 +1150,7 @@ pub mod tests {
             Ok(_) => assert_eq!(0,1), // we should not receive this
             // TODO: Refactor Error
             // TODO: Fix this test to be a correct Error Type
-            Err(e) => assert_eq!(e, ConnectionError::CommonError(1019)),
+            Err(e) => assert_eq!(e, VcxError::from(1019)),
         }
     }
 
@@ -1058,11 +1226,11 @@ pub mod tests {
         let h4 = build_connection("rel4").unwrap();
         let h5 = build_connection("rel5").unwrap();
         release_all();
-        assert_eq!(release(h1).err(),Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
-        assert_eq!(release(h2).err(),Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
-        assert_eq!(release(h3).err(),Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
-        assert_eq!(release(h4).err(),Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
-        assert_eq!(release(h5).err(),Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(release(h1).err(),Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(release(h2).err(),Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(release(h3).err(),Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(release(h4).err(),Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(release(h5).err(),Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
     }
 
     #[test]
@@ -1082,19 +1250,53 @@ pub mod tests {
         connect(handle_2,Some("{}".to_string())).unwrap();
     }
 
+    #[test]
+    fn test_invitation_formats() {
+        init!("true");
+        let details: InviteDetail = serde_json::from_str(INVITE_DETAIL_STRING).unwrap();
+        let handle = build_connection("test_invitation_formats").unwrap();
+        set_invite_details(handle, details.clone()).unwrap();
+
+        let url = get_invite_details_with_format(handle, InviteFormat::Url).unwrap();
+        assert!(url.starts_with("localhost:9001/agency/msg?c_i="));
+        let qr = get_invite_details_with_format(handle, InviteFormat::QrCode).unwrap();
+        assert!(qr.contains("spec/connections/1.0/invitation"));
+
+        for invite in vec![url, qr, INVITE_DETAIL_STRING.to_string(), get_invite_details(handle, true).unwrap()] {
+            let invitee = build_connection_with_invite("bob", &invite).unwrap();
+            assert_eq!(get_their_pw_verkey(invitee).unwrap(), details.sender_detail.verkey);
+            assert_eq!(get_invite_details_with_format(invitee, InviteFormat::Json).unwrap(),
+                       serde_json::to_string(&Some(details.clone())).unwrap());
+        }
+
+        let foreign = json!({
+            "@type": "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/1.0/invitation",
+            "@id": "12345678900987654321",
+            "label": "Alice",
+            "recipientKeys": ["8HH5gYEeNc3z7PYXmd54d4x6qAfCNrqQqEB3nS7Zfu7K"],
+            "serviceEndpoint": "https://example.com/endpoint",
+        }).to_string();
+        assert_eq!(build_connection_with_invite("bob", &foreign).err(),
+                   Some(VcxError::from(error::UNSUPPORTED_INVITATION.code_num)));
+        assert_eq!(build_connection_with_invite("bob", "https://example.com/?c_i=e30").err(),
+                   Some(VcxError::from(error::INVALID_INVITE_DETAILS.code_num)));
+
+        let empty = build_connection("no_invite").unwrap();
+        assert_eq!(get_invite_details_with_format(empty, InviteFormat::Url).err(), Some(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)));
+    }
+
     #[test]
     fn test_create_with_invalid_invite_details() {
         init!("true");
         let bad_details = r#"{"id":"mtfjmda","s":{"d":"abc"},"l":"abc","n":"Evernym","v":"avc"},"sa":{"d":"abc","e":"abc","v":"abc"},"sc":"MS-101","sm":"message created","t":"there"}"#;
         match build_connection_with_invite("alice",&bad_details) {
             Ok(_) => panic!("should have failed"),
-            Err(x) => assert_eq!(x, ConnectionError::CommonError(error::INVALID_JSON.code_num)),
+            Err(x) => assert_eq!(x, VcxError::from(error::INVALID_JSON.code_num)),
         };
     }
 
     #[test]
     fn test_connect_with_invalid_details() {
-        use error::connection::ConnectionError;
         init!("true");
         let test_name = "test_connect_with_invalid_details";
 
@@ -1111,32 +1313,31 @@ pub mod tests {
             agent_vk: "EkVTa7SCJ5SntpYyX7CSb2pcBhiVGT9kWSagA8a9T69A".to_string(),
             their_pw_did: String::new(),
             their_pw_verkey: String::new(),
+            envelope_format: EnvelopeFormat::default(),
         };
 
         let handle = CONNECTION_MAP.add(c).unwrap();
 
-        assert_eq!(connect(handle, Some("{}".to_string())).err(), Some(ConnectionError::CommonError(error::CONNECTION_ERROR.code_num)));
+        assert_eq!(connect(handle, Some("{}".to_string())).err(), Some(VcxError::from(error::CONNECTION_ERROR.code_num)));
 
-        // from_string throws a ConnectionError
-        assert_eq!(from_string("").err(), Some(ConnectionError::CommonError(1016)));
+        assert_eq!(from_string("").err(), Some(VcxError::from(1016)));
 
-        // release throws a connection Error
         assert_eq!(release(1234).err(),
-                   Some(ConnectionError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num)));
+                   Some(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num)));
 
     }
 
     #[test]
     fn test_void_functions_actually_have_results(){
-        assert_eq!(set_their_pw_verkey(1, "blah").err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_state(1, VcxStateType::VcxStateNone).err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_pw_did(1, "blah").err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_their_pw_did(1,"blah").err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_uuid(1,"blah").err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_endpoint(1,"blah").err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_agent_verkey(1,"blah").err(), Some(ConnectionError::InvalidHandle()));
+        assert_eq!(set_their_pw_verkey(1, "blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_state(1, VcxStateType::VcxStateNone).err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_pw_did(1, "blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_their_pw_did(1,"blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_uuid(1,"blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_endpoint(1,"blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_agent_verkey(1,"blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
         let details: InviteDetail = serde_json::from_str(INVITE_DETAIL_STRING).unwrap();
-        assert_eq!(set_invite_details(1, details).err(), Some(ConnectionError::InvalidHandle()));
-        assert_eq!(set_pw_verkey(1, "blah").err(), Some(ConnectionError::InvalidHandle()));
+        assert_eq!(set_invite_details(1, details).err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+        assert_eq!(set_pw_verkey(1, "blah").err(), Some(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
     }
 }
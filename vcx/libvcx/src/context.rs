@@ -0,0 +1,107 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use utils::error;
+use error::{ VcxError, VcxResult };
+
+/// Context used by every call until another one is selected. It exists for the whole lifetime of
+/// the library and holds what vcx_init configures.
+pub const DEFAULT_CONTEXT: u32 = 0;
+
+lazy_static! {
+    static ref CONTEXTS: RwLock<HashSet<u32>> = Default::default();
+}
+
+static NEXT_CONTEXT: AtomicUsize = AtomicUsize::new(DEFAULT_CONTEXT as usize + 1);
+
+// Settings, wallet and pool handles and objects are looked up in the context of the calling thread.
+// Jobs spawned on the threadpool inherit the context of the thread that spawned them.
+thread_local! {
+    static CURRENT_CONTEXT: Cell<u32> = Cell::new(DEFAULT_CONTEXT);
+}
+
+/// Restores the previous context of the thread when dropped.
+pub struct ContextGuard {
+    previous: u32,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CURRENT_CONTEXT.with(|current| current.set(self.previous));
+    }
+}
+
+pub fn create() -> u32 {
+    let handle = NEXT_CONTEXT.fetch_add(1, Ordering::SeqCst) as u32;
+    CONTEXTS.write().unwrap().insert(handle);
+    handle
+}
+
+pub fn is_valid_handle(handle: u32) -> bool {
+    handle == DEFAULT_CONTEXT || CONTEXTS.read().unwrap().contains(&handle)
+}
+
+pub fn current() -> u32 {
+    CURRENT_CONTEXT.with(|current| current.get())
+}
+
+/// Makes every following call on this thread target `handle`.
+pub fn set_current(handle: u32) -> VcxResult<()> {
+    if !is_valid_handle(handle) {
+        return Err(VcxError::from_code(error::INVALID_CONTEXT_HANDLE.code_num));
+    }
+    CURRENT_CONTEXT.with(|current| current.set(handle));
+    Ok(())
+}
+
+/// Switches this thread to `handle` until the returned guard is dropped.
+pub fn enter(handle: u32) -> ContextGuard {
+    let previous = current();
+    CURRENT_CONTEXT.with(|current| current.set(handle));
+    ContextGuard { previous }
+}
+
+pub fn with_context<F, R>(handle: u32, closure: F) -> R
+    where F: FnOnce() -> R {
+    let _guard = enter(handle);
+    closure()
+}
+
+/// Forgets the context. Its objects, settings and handles have to be released beforehand.
+pub fn release(handle: u32) -> VcxResult<()> {
+    if handle == DEFAULT_CONTEXT || !CONTEXTS.write().unwrap().remove(&handle) {
+        return Err(VcxError::from_code(error::INVALID_CONTEXT_HANDLE.code_num));
+    }
+
+    CURRENT_CONTEXT.with(|current| if current.get() == handle { current.set(DEFAULT_CONTEXT) });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_context_restores_previous() {
+        let handle = create();
+        assert_eq!(current(), DEFAULT_CONTEXT);
+
+        with_context(handle, || assert_eq!(current(), handle));
+        assert_eq!(current(), DEFAULT_CONTEXT);
+
+        release(handle).unwrap();
+    }
+
+    #[test]
+    fn test_set_current() {
+        let handle = create();
+        set_current(handle).unwrap();
+        assert_eq!(current(), handle);
+
+        release(handle).unwrap();
+        assert_eq!(current(), DEFAULT_CONTEXT);
+        assert_eq!(set_current(handle), Err(VcxError::from_code(error::INVALID_CONTEXT_HANDLE.code_num)));
+        assert_eq!(release(DEFAULT_CONTEXT), Err(VcxError::from_code(error::INVALID_CONTEXT_HANDLE.code_num)));
+    }
+}
@@ -14,25 +14,30 @@ use credential_request::{ CredentialRequest };
 use messages;
 use messages::to_u8;
 use messages::GeneralMessage;
+use messages::get_message::MessagesByDid;
 use messages::send_message::parse_msg_uid;
 use messages::extract_json_payload;
 
 use utils::libindy::anoncreds::{libindy_prover_create_credential_req, libindy_prover_store_credential};
-use utils::libindy::crypto;
 use utils::libindy::payments::{pay_a_payee, PaymentTxn};
+use utils::libindy::wallet;
 
 use credential_def::retrieve_credential_def;
 use connection;
 
 use settings;
-use utils::constants::DEFAULT_SERIALIZE_VERSION;
+use utils::serialization::{ self, Versioned };
 
-use error::{ToErrorCode, credential::CredentialError};
+use error::{ VcxError, VcxResult, VcxResultExt };
 use serde_json::Value;
+use std::time::{ SystemTime, UNIX_EPOCH };
 
+// libindy does not keep when a credential was stored, the newest credential selection strategy
+// orders credentials by the time recorded here. Credentials stored outside of vcx have none.
+static STORED_AT_RECORD_TYPE: &str = "vcx_credential_stored_at";
 
 lazy_static! {
-    static ref HANDLE_MAP: ObjectCache<Credential>  = Default::default();
+    static ref HANDLE_MAP: ObjectCache<Credential>  = ObjectCache::persistent("vcx_credential", Credential::to_string);
 }
 
 impl Default for Credential {
@@ -81,26 +86,26 @@ pub struct Credential {
     payment_txn: Option<PaymentTxn>,
 }
 
+impl Versioned for Credential {}
+
 impl Credential {
 
-    pub fn build_request(&self, my_did: &str, their_did: &str) -> Result<CredentialRequest, CredentialError> {
-        if self.state != VcxStateType::VcxStateRequestReceived { return Err(CredentialError::NotReady())}
+    pub fn build_request(&self, my_did: &str, their_did: &str) -> VcxResult<CredentialRequest> {
+        if self.state != VcxStateType::VcxStateRequestReceived { return Err(VcxError::from_code(error::NOT_READY.code_num))}
 
-        let prover_did = self.my_did.as_ref().ok_or(CredentialError::CommonError(error::INVALID_DID.code_num))?;
-        let credential_offer = self.credential_offer.as_ref().ok_or(CredentialError::InvalidCredentialJson())?;
+        let prover_did = self.my_did.as_ref().ok_or(VcxError::from(error::INVALID_DID.code_num))?;
+        let credential_offer = self.credential_offer.as_ref().ok_or(VcxError::from_code(error::INVALID_JSON.code_num))?;
 
-        let (cred_def_id, cred_def_json) = retrieve_credential_def(&credential_offer.cred_def_id)
-            .map_err(|err| CredentialError::CommonError(err.to_error_code()))?;
+        let (cred_def_id, cred_def_json) = retrieve_credential_def(&credential_offer.cred_def_id)?;
 
 /*
         debug!("storing credential offer: {}", credential_offer);
-        libindy_prover_store_credential_offer(wallet_h, &credential_offer).map_err(|ec| CredentialError::CommonError(ec))?;
+        libindy_prover_store_credential_offer(wallet_h, &credential_offer)?;
 */
 
         let (req, req_meta) = libindy_prover_create_credential_req(&prover_did,
                                                                    &credential_offer.libindy_offer,
-                                                                   &cred_def_json)
-            .map_err(|ec| CredentialError::CommonError(ec))?;
+                                                                   &cred_def_json)?;
 
         Ok(CredentialRequest {
             libindy_cred_req: req,
@@ -115,14 +120,14 @@ impl Credential {
         })
     }
 
-    fn send_request(&mut self, connection_handle: u32) -> Result<u32, CredentialError> {
+    fn send_request(&mut self, connection_handle: u32) -> VcxResult<u32> {
         debug!("sending credential request {} via connection: {}", self.source_id, connection::get_source_id(connection_handle).unwrap_or_default());
-        self.my_did = Some(connection::get_pw_did(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
-        self.my_vk = Some(connection::get_pw_verkey(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
-        self.agent_did = Some(connection::get_agent_did(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
-        self.agent_vk = Some(connection::get_agent_verkey(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
-        self.their_did = Some(connection::get_their_pw_did(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
-        self.their_vk = Some(connection::get_their_pw_verkey(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
+        self.my_did = Some(connection::get_pw_did(connection_handle)?);
+        self.my_vk = Some(connection::get_pw_verkey(connection_handle)?);
+        self.agent_did = Some(connection::get_agent_did(connection_handle)?);
+        self.agent_vk = Some(connection::get_agent_verkey(connection_handle)?);
+        self.their_did = Some(connection::get_their_pw_did(connection_handle)?);
+        self.their_vk = Some(connection::get_their_pw_verkey(connection_handle)?);
 
         debug!("verifier_did: {:?} -- verifier_vk: {:?} -- agent_did: {:?} -- agent_vk: {:?} -- remote_vk: {:?}",
                self.my_did,
@@ -131,20 +136,21 @@ impl Credential {
                self.their_vk,
                self.my_vk);
 
-        let local_their_did = self.their_did.as_ref().ok_or(CredentialError::InvalidHandle())?;
-        let local_their_vk = self.their_vk.as_ref().ok_or(CredentialError::InvalidHandle())?;
-        let local_agent_did = self.agent_did.as_ref().ok_or(CredentialError::InvalidHandle())?;
-        let local_agent_vk = self.agent_vk.as_ref().ok_or(CredentialError::InvalidHandle())?;
-        let local_my_did = self.my_did.as_ref().ok_or(CredentialError::InvalidHandle())?;
-        let local_my_vk = self.my_vk.as_ref().ok_or(CredentialError::InvalidHandle())?;
+        let local_their_did = self.their_did.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num))?;
+        let local_their_vk = self.their_vk.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num))?;
+        let local_agent_did = self.agent_did.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num))?;
+        let local_agent_vk = self.agent_vk.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num))?;
+        let local_my_did = self.my_did.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num))?;
+        let local_my_vk = self.my_vk.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num))?;
 
         // if test mode, just get this.
         let req: CredentialRequest = self.build_request(local_my_did, local_their_did)?;
         self.credential_request = Some(req.clone());
-        let req = serde_json::to_string(&req).or(Err(CredentialError::InvalidCredentialJson()))?;
-        let data: Vec<u8> = connection::generate_encrypted_payload(local_my_vk, local_their_vk, &req, "CRED_REQ").map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-        let offer_msg_id = self.credential_offer.as_ref().ok_or(CredentialError::CommonError(error::CREATE_CREDENTIAL_REQUEST_ERROR.code_num))?
-            .msg_ref_id.as_ref().ok_or(CredentialError::CommonError(error::CREATE_CREDENTIAL_REQUEST_ERROR.code_num))?;
+        let req = serde_json::to_string(&req).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+        let data: Vec<u8> = connection::generate_payload_for_connection(connection_handle, local_my_vk, local_their_vk, &req, "CRED_REQ")?;
+        let service = connection::get_didcomm_service(connection_handle)?;
+        let offer_msg_id = self.credential_offer.as_ref().ok_or(VcxError::from(error::CREATE_CREDENTIAL_REQUEST_ERROR.code_num))?
+            .msg_ref_id.as_ref().ok_or(VcxError::from(error::CREATE_CREDENTIAL_REQUEST_ERROR.code_num))?;
 
         if self.payment_info.is_some() {
             let (payment_txn, _) = self.submit_payment()?;
@@ -154,24 +160,25 @@ impl Credential {
         match messages::send_message().to(local_my_did)
             .to_vk(local_my_vk)
             .msg_type("credReq")
+            .didcomm_service(service)
             .agent_did(local_agent_did)
             .agent_vk(local_agent_vk)
             .edge_agent_payload(&data)
             .ref_msg_id(offer_msg_id)
             .send_secure() {
             Ok(response) => {
-                self.msg_uid = Some(parse_msg_uid(&response[0]).map_err(|ec| CredentialError::CommonError(ec))?);
+                self.msg_uid = Some(parse_msg_uid(&response[0])?);
                 self.state = VcxStateType::VcxStateOfferSent;
                 return Ok(error::SUCCESS.code_num)
             },
             Err(x) => {
                 warn!("{} could not send proof: {}", self.source_id, x);
-                return Err(CredentialError::CommonError(x));
+                return Err(x);
             }
         }
     }
 
-    fn _check_msg(&mut self) -> Result<(), u32> {
+    fn _check_msg(&mut self, messages: Option<&MessagesByDid>) -> VcxResult<()> {
         let e_code: u32 = error::INVALID_CONNECTION_HANDLE.code_num;
 
         let agent_did = self.agent_did.as_ref().ok_or(e_code)?;
@@ -180,36 +187,46 @@ impl Credential {
         let my_vk = self.my_vk.as_ref().ok_or(e_code)?;
         let msg_uid = self.msg_uid.as_ref().ok_or(e_code)?;
 
-        let (_, payload) = messages::get_message::get_ref_msg(msg_uid, my_did, my_vk, agent_did, agent_vk)?;
+        let (_, payload) = messages::get_message::get_ref_msg_from(messages, msg_uid, my_did, my_vk, agent_did, agent_vk)?;
 
         let credential = extract_json_payload(&payload)?;
 
         let credential_msg: CredentialMessage = serde_json::from_str(&credential)
-            .or(Err(error::INVALID_CREDENTIAL_JSON.code_num))?;
+            .or(Err(VcxError::from_code(error::INVALID_CREDENTIAL_JSON.code_num)))?;
 
         let cred_req: &CredentialRequest = self.credential_request.as_ref()
-            .ok_or(CredentialError::InvalidCredentialJson().to_error_code())?;
+            .ok_or(VcxError::from_code(error::INVALID_JSON.code_num))?;
+
+        let (_, cred_def_json) = ::credential_def::retrieve_credential_def(&cred_req.cred_def_id)?;
 
-        let (_, cred_def_json) = ::credential_def::retrieve_credential_def(&cred_req.cred_def_id)
-            .map_err(|err| CredentialError::CommonError(err.to_error_code()).to_error_code())?;
+        let rev_reg_def_json = if credential_msg.rev_reg_def_json.is_empty() {
+            None
+        } else {
+            Some(credential_msg.rev_reg_def_json.as_str())
+        };
 
         self.credential = Some(credential);
-        self.cred_id = Some(libindy_prover_store_credential(None,
-                                                            &cred_req.libindy_cred_req_meta,
-                                                            &credential_msg.libindy_cred,
-                                                            &cred_def_json,
-                                                            None)?);
+        let cred_id = libindy_prover_store_credential(None,
+                                                      &cred_req.libindy_cred_req_meta,
+                                                      &credential_msg.libindy_cred,
+                                                      &cred_def_json,
+                                                      rev_reg_def_json)?;
+        // the credential is stored either way, it only ranks as the oldest in selections
+        if let Err(err) = record_stored_at(&cred_id) {
+            warn!("could not record when credential {} was stored: {}", cred_id, err);
+        }
+        self.cred_id = Some(cred_id);
         self.state = VcxStateType::VcxStateAccepted;
 
         Ok(())
     }
 
-    fn update_state(&mut self) {
+    fn update_state(&mut self, messages: Option<&MessagesByDid>) {
         debug!("updating state for credential {} with msg_id {:?}", self.source_id, self.msg_uid);
         match self.state {
             VcxStateType::VcxStateOfferSent => {
                 //Check for messages
-                let _ = self._check_msg();
+                let _ = self._check_msg(messages);
             },
             VcxStateType::VcxStateAccepted => {
                 //Check for revocation
@@ -225,30 +242,30 @@ impl Credential {
         state
     }
 
-    fn get_credential(&self) -> Result<String, CredentialError> {
+    fn get_credential(&self) -> VcxResult<String> {
         if self.state == VcxStateType::VcxStateAccepted {
             match self.credential {
                 Some(ref x) => Ok(self.to_cred_string(x)),
-                None => Err(CredentialError::InvalidState()),
+                None => Err(VcxError::from_code(error::INVALID_STATE.code_num)),
             }
         }
         else {
-            Err(CredentialError::InvalidState())
+            Err(VcxError::from_code(error::INVALID_STATE.code_num))
         }
     }
 
-    fn get_credential_offer(&self) -> Result<String, CredentialError> {
+    fn get_credential_offer(&self) -> VcxResult<String> {
         if self.state == VcxStateType::VcxStateRequestReceived {
             match self.credential_offer {
                 Some(ref x) => match serde_json::to_string(x) {
                     Ok(x) => Ok(self.to_cred_offer_string(&x)),
-                    Err(_) => Err(CredentialError::InvalidCredentialJson()),
+                    Err(_) => Err(VcxError::from_code(error::INVALID_JSON.code_num)),
                 }
-                None => Err(CredentialError::InvalidState()),
+                None => Err(VcxError::from_code(error::INVALID_STATE.code_num)),
             }
         }
         else {
-            Err(CredentialError::InvalidState())
+            Err(VcxError::from_code(error::INVALID_STATE.code_num))
         }
     }
 
@@ -277,10 +294,10 @@ impl Credential {
 
     fn get_source_id(&self) -> &String {&self.source_id}
 
-    fn get_payment_txn(&self) -> Result<PaymentTxn, u32> {
+    fn get_payment_txn(&self) -> VcxResult<PaymentTxn> {
         match self.payment_txn {
             Some(ref payment_txn) if self.payment_info.is_some() => Ok(payment_txn.clone()),
-            _ => Err(error::NO_PAYMENT_INFORMATION.code_num)
+            _ => Err(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num))
         }
     }
 
@@ -292,7 +309,7 @@ impl Credential {
         self.payment_info.is_some()
     }
 
-    fn submit_payment(&self) -> Result<(PaymentTxn, String), CredentialError> {
+    fn submit_payment(&self) -> VcxResult<(PaymentTxn, String)> {
         debug!("{} submitting payment for premium credential", self.source_id);
         match &self.payment_info {
             &Some(ref pi) => {
@@ -301,43 +318,51 @@ impl Credential {
                 let (payment_txn, receipt) = pay_a_payee(price, address)?;
                 Ok((payment_txn, receipt))
             },
-            &None => Err(CredentialError::NoPaymentInformation()),
+            &None => Err(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num)),
         }
     }
 
-    fn get_payment_info(&self) -> Result<Option<PaymentInfo>, CredentialError> {
+    fn get_payment_info(&self) -> VcxResult<Option<PaymentInfo>> {
         Ok(self.payment_info.clone())
     }
 
     fn to_string(&self) -> String {
-        json!({
-            "version": DEFAULT_SERIALIZE_VERSION,
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
 
-    fn from_str(s: &str) -> Result<Credential, CredentialError> {
-        let s:Value = serde_json::from_str(&s)
-            .or(Err(CredentialError::InvalidCredentialJson()))?;
-        let obj: Credential = serde_json::from_value(s["data"].clone())
-            .or(Err(CredentialError::InvalidCredentialJson()))?;
-        Ok(obj)
+    fn from_str(s: &str) -> VcxResult<Credential> {
+        serialization::deserialize(s).context(error::INVALID_JSON.code_num, "cannot deserialize credential")
     }
 }
 
 //********************************************
 //         HANDLE FUNCTIONS
 //********************************************
-fn handle_err(code_num: u32) -> CredentialError {
-    if code_num == error::INVALID_OBJ_HANDLE.code_num {
-        CredentialError::InvalidHandle()
+fn handle_err(err: VcxError) -> VcxError {
+    if err.code() == error::INVALID_OBJ_HANDLE.code_num {
+        VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num)
     }
     else {
-        CredentialError::CommonError(code_num)
+        err
     }
 }
 
-pub fn credential_create_with_offer(source_id: &str, offer: &str) -> Result<u32, CredentialError> {
+/// Records now as the time the credential was stored in the wallet.
+pub fn record_stored_at(cred_id: &str) -> VcxResult<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let now_ms = now.as_secs() * 1000 + now.subsec_nanos() as u64 / 1_000_000;
+    wallet::add_record(STORED_AT_RECORD_TYPE, cred_id, &now_ms.to_string(), "{}")
+}
+
+/// The time in milliseconds since the epoch the credential was stored at, if vcx stored it.
+pub fn get_stored_at(cred_id: &str) -> Option<u64> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let record = wallet::get_record(STORED_AT_RECORD_TYPE, cred_id, &options).ok()?;
+    let record: Value = serde_json::from_str(&record).ok()?;
+    record["value"].as_str().and_then(|value| value.parse::<u64>().ok())
+}
+
+pub fn credential_create_with_offer(source_id: &str, offer: &str) -> VcxResult<u32> {
     let mut new_credential = _credential_create(source_id);
 
     let (offer, payment_info) = parse_json_offer(offer)?;
@@ -347,7 +372,7 @@ pub fn credential_create_with_offer(source_id: &str, offer: &str) -> Result<u32,
     new_credential.state = VcxStateType::VcxStateRequestReceived;
 
     debug!("inserting credential {} into handle map", source_id);
-    Ok(HANDLE_MAP.add(new_credential).map_err(|ec|CredentialError::CommonError(ec))?)
+    Ok(HANDLE_MAP.add(new_credential)?)
 }
 
 fn _credential_create(source_id: &str) -> Credential {
@@ -360,55 +385,67 @@ fn _credential_create(source_id: &str) -> Credential {
     new_credential
 }
 
-pub fn update_state(handle: u32) -> Result<u32, u32> {
+pub fn update_state(handle: u32) -> VcxResult<u32> {
     HANDLE_MAP.get_mut(handle, |obj|{
-        obj.update_state();
+        obj.update_state(None);
         Ok(error::SUCCESS.code_num)
     })
 
 }
 
-pub fn get_credential(handle: u32) -> Result<String, CredentialError> {
+/// Updates the state from messages the dispatcher already downloaded instead of polling the agency.
+pub fn update_state_with_messages(handle: u32, messages: &MessagesByDid) -> VcxResult<u32> {
+    HANDLE_MAP.get_mut(handle, |obj|{
+        obj.update_state(Some(messages));
+        Ok(obj.get_state())
+    })
+}
+
+pub fn get_handles() -> Vec<u32> {
+    HANDLE_MAP.handles()
+}
+
+pub fn get_credential(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj| {
-        obj.get_credential().map_err(|e| e.to_error_code())
-    }).map_err(|ec| CredentialError::CommonError(ec))
+        obj.get_credential()
+    })
 }
 
-pub fn get_payment_txn(handle: u32) -> Result<PaymentTxn, CredentialError> {
+pub fn get_payment_txn(handle: u32) -> VcxResult<PaymentTxn> {
     HANDLE_MAP.get(handle, |obj| {
         obj.get_payment_txn()
-    }).or(Err(CredentialError::NoPaymentInformation()))
+    }).or(Err(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num)))
 }
 
-pub fn get_credential_offer(handle: u32) -> Result<String, CredentialError> {
+pub fn get_credential_offer(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj| {
-        obj.get_credential_offer().map_err(|e| e.to_error_code())
-    }).map_err(|ec| CredentialError::CommonError(ec))
+        obj.get_credential_offer()
+    })
 }
 
-pub fn get_credential_id(handle: u32) -> Result<String, CredentialError> {
+pub fn get_credential_id(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj| {
         Ok(obj.get_credential_id())
-    }).map_err(|ec| CredentialError::CommonError(ec))
+    })
 }
 
-pub fn get_state(handle: u32) -> Result<u32, CredentialError> {
+pub fn get_state(handle: u32) -> VcxResult<u32> {
     HANDLE_MAP.get(handle, |obj| {
         Ok(obj.get_state())
     }).map_err(handle_err)
 }
 
-pub fn send_credential_request(handle: u32, connection_handle: u32) -> Result<u32, CredentialError> {
+pub fn send_credential_request(handle: u32, connection_handle: u32) -> VcxResult<u32> {
     HANDLE_MAP.get_mut(handle, |obj| {
-        obj.send_request(connection_handle).map_err(|e| e.to_error_code())
+        obj.send_request(connection_handle)
     }).map_err(handle_err)
 }
 
-pub fn get_credential_offer_msg(connection_handle: u32, msg_id: &str) -> Result<String, CredentialError> {
-    let my_did = connection::get_pw_did(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-    let my_vk = connection::get_pw_verkey(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-    let agent_did = connection::get_agent_did(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-    let agent_vk = connection::get_agent_verkey(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
+pub fn get_credential_offer_msg(connection_handle: u32, msg_id: &str) -> VcxResult<String> {
+    let my_did = connection::get_pw_did(connection_handle)?;
+    let my_vk = connection::get_pw_verkey(connection_handle)?;
+    let agent_did = connection::get_agent_did(connection_handle)?;
+    let agent_vk = connection::get_agent_verkey(connection_handle)?;
 
     if settings::test_agency_mode_enabled() { ::utils::httpclient::set_next_u8_response(::utils::constants::NEW_CREDENTIAL_OFFER_RESPONSE.to_vec()); }
 
@@ -416,18 +453,18 @@ pub fn get_credential_offer_msg(connection_handle: u32, msg_id: &str) -> Result<
                                                                  &my_vk,
                                                                  &agent_did,
                                                                  &agent_vk,
-                                                                 Some(vec![msg_id.to_string()])).map_err(|ec| CredentialError::CommonError(ec))?;
+                                                                 Some(vec![msg_id.to_string()]))?;
 
     if message[0].msg_type.eq("credOffer") {
         let (_, msg_data) = match message[0].payload {
             Some(ref data) => {
                 let data = to_u8(data);
-                crypto::parse_msg(&my_vk, data.as_slice()).map_err(|ec| CredentialError::CommonError(ec))?
+                messages::decrypt_payload(&my_vk, data.as_slice())?
             },
-            None => return Err(CredentialError::CommonError(error::INVALID_MESSAGES.code_num))
+            None => return Err(VcxError::from(error::INVALID_MESSAGES.code_num))
         };
 
-        let offer = extract_json_payload(&msg_data).map_err(|ec| CredentialError::CommonError(ec))?;
+        let offer = extract_json_payload(&msg_data)?;
         let (mut offer, payment_info) = parse_json_offer(&offer)?;
 
         offer.msg_ref_id = Some(message[0].uid.to_owned());
@@ -435,18 +472,18 @@ pub fn get_credential_offer_msg(connection_handle: u32, msg_id: &str) -> Result<
         payload.push(json!(offer));
         if let Some(p) = payment_info { payload.push(json!(p)); }
 
-        Ok(serde_json::to_string_pretty(&payload).or(Err(CredentialError::CommonError(error::INVALID_MESSAGES.code_num)))?)
+        Ok(serde_json::to_string_pretty(&payload).or(Err(VcxError::from(error::INVALID_MESSAGES.code_num)))?)
     } else {
-        Err(CredentialError::CommonError(error::INVALID_MESSAGES.code_num))
+        Err(VcxError::from(error::INVALID_MESSAGES.code_num))
     }
 }
 
-pub fn get_credential_offer_messages(connection_handle: u32) -> Result<String, CredentialError> {
+pub fn get_credential_offer_messages(connection_handle: u32) -> VcxResult<String> {
     debug!("checking agent for credential offers from connection {}", connection::get_source_id(connection_handle).unwrap_or_default());
-    let my_did = connection::get_pw_did(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-    let my_vk = connection::get_pw_verkey(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-    let agent_did = connection::get_agent_did(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
-    let agent_vk = connection::get_agent_verkey(connection_handle).map_err(|e| CredentialError::CommonError(e.to_error_code()))?;
+    let my_did = connection::get_pw_did(connection_handle)?;
+    let my_vk = connection::get_pw_verkey(connection_handle)?;
+    let agent_did = connection::get_agent_did(connection_handle)?;
+    let agent_vk = connection::get_agent_verkey(connection_handle)?;
 
     if settings::test_agency_mode_enabled() { ::utils::httpclient::set_next_u8_response(::utils::constants::NEW_CREDENTIAL_OFFER_RESPONSE.to_vec()); }
 
@@ -454,7 +491,7 @@ pub fn get_credential_offer_messages(connection_handle: u32) -> Result<String, C
                                                                  &my_vk,
                                                                  &agent_did,
                                                                  &agent_vk,
-                                                                 None).map_err(|ec| CredentialError::CommonError(ec))?;
+                                                                 None)?;
 
     let mut messages = Vec::new();
 
@@ -463,12 +500,12 @@ pub fn get_credential_offer_messages(connection_handle: u32) -> Result<String, C
             let (_, msg_data) = match msg.payload {
                 Some(ref data) => {
                     let data = to_u8(data);
-                    crypto::parse_msg( &my_vk, data.as_slice()).map_err(|ec| CredentialError::CommonError(ec))?
+                    messages::decrypt_payload(&my_vk, data.as_slice())?
                 },
-                None => return Err(CredentialError::CommonError(error::INVALID_MESSAGES.code_num))
+                None => return Err(VcxError::from(error::INVALID_MESSAGES.code_num))
             };
 
-            let offer = extract_json_payload(&msg_data).map_err(|ec| CredentialError::CommonError(ec))?;
+            let offer = extract_json_payload(&msg_data)?;
             let (mut offer, payment_info) = parse_json_offer(&offer)?;
 
             offer.msg_ref_id = Some(msg.uid.to_owned());
@@ -480,11 +517,11 @@ pub fn get_credential_offer_messages(connection_handle: u32) -> Result<String, C
         }
     }
 
-    Ok(serde_json::to_string_pretty(&messages).or(Err(CredentialError::CommonError(error::INVALID_MESSAGES.code_num)))?)
+    Ok(serde_json::to_string_pretty(&messages).or(Err(VcxError::from(error::INVALID_MESSAGES.code_num)))?)
 }
 
-pub fn parse_json_offer(offer: &str) -> Result<(CredentialOffer, Option<PaymentInfo>), CredentialError> {
-    let paid_offer: Value = serde_json::from_str(offer).or(Err(CredentialError::InvalidCredentialJson()))?;
+pub fn parse_json_offer(offer: &str) -> VcxResult<(CredentialOffer, Option<PaymentInfo>)> {
+    let paid_offer: Value = serde_json::from_str(offer).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
 
     let mut payment: Option<PaymentInfo> = None;
     let mut offer: Option<CredentialOffer> = None;
@@ -492,18 +529,18 @@ pub fn parse_json_offer(offer: &str) -> Result<(CredentialOffer, Option<PaymentI
     if let Some(i) = paid_offer.as_array() {
         for entry in i.iter() {
             if entry.get("libindy_offer").is_some() {
-                offer = Some(serde_json::from_value(entry.clone()).or(Err(CredentialError::InvalidCredentialJson()))?);
+                offer = Some(serde_json::from_value(entry.clone()).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?);
             }
 
             if entry.get("payment_addr").is_some() {
-                payment = Some(serde_json::from_value(entry.clone()).or(Err(CredentialError::InvalidCredentialJson()))?);
+                payment = Some(serde_json::from_value(entry.clone()).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?);
             }
         }
     }
-    Ok((offer.ok_or(CredentialError::InvalidCredentialJson())?, payment))
+    Ok((offer.ok_or(VcxError::from_code(error::INVALID_JSON.code_num))?, payment))
 }
 
-pub fn release(handle: u32) -> Result<(), CredentialError> {
+pub fn release(handle: u32) -> VcxResult<()> {
     HANDLE_MAP.release(handle).map_err(handle_err)
 }
 
@@ -514,27 +551,34 @@ pub fn release_all() {
     };
 }
 
+/// Reloads the stored objects, see object_store
+pub fn restore_all() -> VcxResult<usize> {
+    HANDLE_MAP.restore(|data| Credential::from_str(data).ok())
+}
+
 pub fn is_valid_handle(handle: u32) -> bool {
     HANDLE_MAP.has_handle(handle)
 }
 
-pub fn to_string(handle: u32) -> Result<String, u32> {
+/// The context the credential was created in, calls on the credential run in it.
+pub fn get_context(handle: u32) -> u32 {
+    HANDLE_MAP.context_of(handle)
+}
+
+pub fn to_string(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj|{
         Ok(Credential::to_string(&obj))
     })
 }
 
-pub fn get_source_id(handle: u32) -> Result<String, CredentialError> {
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj| {
         Ok(obj.get_source_id().clone())
     }).map_err(handle_err)
 }
 
-pub fn from_string(credential_data: &str) -> Result<u32, u32> {
-    let credential: Credential = match Credential::from_str(credential_data) {
-        Ok(x) => x,
-        Err(y) => return Err(error::INVALID_JSON.code_num),
-    };
+pub fn from_string(credential_data: &str) -> VcxResult<u32> {
+    let credential: Credential = Credential::from_str(credential_data)?;
 
     let new_handle = HANDLE_MAP.add(credential)?;
 
@@ -543,22 +587,22 @@ pub fn from_string(credential_data: &str) -> Result<u32, u32> {
     Ok(new_handle)
 }
 
-pub fn is_payment_required(handle: u32) -> Result<bool, CredentialError> {
+pub fn is_payment_required(handle: u32) -> VcxResult<bool> {
     HANDLE_MAP.get(handle, |obj| {
         Ok(obj.is_payment_required())
     }).map_err(handle_err)
 }
 
-pub fn submit_payment(handle: u32) -> Result<(PaymentTxn, String), CredentialError> {
+pub fn submit_payment(handle: u32) -> VcxResult<(PaymentTxn, String)> {
     HANDLE_MAP.get_mut(handle, |obj| {
-        obj.submit_payment().map_err(|e| e.to_error_code())
+        obj.submit_payment()
     }).map_err(handle_err)
 
 }
 
-pub fn get_payment_information(handle: u32) -> Result<Option<PaymentInfo>, CredentialError> {
+pub fn get_payment_information(handle: u32) -> VcxResult<Option<PaymentInfo>> {
     HANDLE_MAP.get(handle, |obj| {
-        obj.get_payment_info().map_err(|e| e.to_error_code())
+        obj.get_payment_info()
     }).map_err(handle_err)
 }
 
@@ -597,7 +641,7 @@ pub mod tests {
     #[test]
     fn test_credential_defaults() {
         let credential = Credential::default();
-        assert_eq!(credential.build_request("test1","test2").err(), Some(CredentialError::NotReady()));
+        assert_eq!(credential.build_request("test1","test2").err(), Some(VcxError::from_code(error::NOT_READY.code_num)));
     }
 
     #[test]
@@ -610,7 +654,7 @@ pub mod tests {
     fn test_credential_create_with_bad_offer() {
         match credential_create_with_offer("test_credential_create_with_bad_offer",BAD_CREDENTIAL_OFFER) {
             Ok(_) => panic!("should have failed with bad credential offer"),
-            Err(x) => assert_eq!(x.to_error_code(),error::INVALID_JSON.code_num),
+            Err(x) => assert_eq!(x.code(),error::INVALID_JSON.code_num),
         };
     }
 
@@ -619,7 +663,7 @@ pub mod tests {
         let handle = credential_create_with_offer("test_credential_serialize_deserialize", ::utils::constants::CREDENTIAL_OFFER_JSON).unwrap();
         let credential_string = to_string(handle).unwrap();
         release(handle).unwrap();
-        assert_eq!(release(handle).err(), Some(CredentialError::InvalidHandle()));
+        assert_eq!(release(handle).err(), Some(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num)));
         let handle = from_string(&credential_string).unwrap();
         let cred1: Credential = Credential::from_str(&credential_string).unwrap();
         assert_eq!(cred1.get_state(), 3);
@@ -681,7 +725,7 @@ pub mod tests {
         init!("true");
         let cred: Credential = Credential::from_str(DEFAULT_SERIALIZED_CREDENTIAL).unwrap();
         assert!(cred.payment_info.is_none());
-        assert_eq!(cred.submit_payment().err(), Some(CredentialError::NoPaymentInformation()));
+        assert_eq!(cred.submit_payment().err(), Some(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num)));
     }
 
     #[test]
@@ -700,7 +744,7 @@ pub mod tests {
         let handle2 = from_string(DEFAULT_SERIALIZED_CREDENTIAL).unwrap();
         assert!(!is_payment_required(handle2).unwrap());
         let invalid_handle = 12345;
-        assert_eq!(is_payment_required(invalid_handle).err(), Some(CredentialError::InvalidHandle()));
+        assert_eq!(is_payment_required(invalid_handle).err(), Some(VcxError::from_code(error::INVALID_CREDENTIAL_HANDLE.code_num)));
     }
 
     #[test]
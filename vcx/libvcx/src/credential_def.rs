@@ -5,21 +5,28 @@ extern crate libc;
 use utils::error;
 use settings;
 use schema::LedgerSchema;
-use utils::constants::{ CRED_DEF_ID, CRED_DEF_JSON, CRED_DEF_TXN_TYPE };
+use utils::constants::{ CRED_DEF_ID, CRED_DEF_JSON, CRED_DEF_TXN_TYPE, CRED_DEF_REQ };
 use utils::libindy::payments::{pay_for_txn, PaymentTxn};
-use utils::libindy::anoncreds::{libindy_create_and_store_credential_def};
+use utils::libindy::anoncreds::{libindy_create_and_store_credential_def, create_rev_reg_def, publish_rev_reg_delta};
+use utils::libindy::wallet;
+use utils::serialization::{ self, Versioned };
+use ledger_cache::{ self, LedgerObject };
+use api::PublicEntityStateType;
 use utils::libindy::ledger::{libindy_submit_request,
                              libindy_build_get_credential_def_txn,
                              libindy_build_create_credential_def_txn,
-                             libindy_parse_get_cred_def_response};
-use error::ToErrorCode;
-use error::cred_def::CredDefError;
+                             libindy_parse_get_cred_def_response,
+                             libindy_prepare_request_for_endorser};
+use error::{ VcxError, VcxResult, VcxResultExt };
 use object_cache::ObjectCache;
 
 lazy_static! {
     static ref CREDENTIALDEF_MAP: ObjectCache<CredentialDef> = Default::default();
 }
 
+static REV_REG_RECORD_TYPE: &str = "cred_def_revocation";
+static CREATED_CRED_DEFS_LIST: &str = "created_cred_defs";
+
 #[derive(Deserialize, Debug, Serialize, PartialEq)]
 pub struct CredentialDef {
     id: String,
@@ -27,8 +34,15 @@ pub struct CredentialDef {
     name: String,
     source_id: String,
     payment_txn: Option<PaymentTxn>,
+    revocation: Option<RevocationDetails>,
+    #[serde(default)]
+    schema_id: Option<String>,
+    #[serde(default)]
+    state: PublicEntityStateType,
 }
 
+impl Versioned for CredentialDef {}
+
 impl Default for CredentialDef {
     fn default() -> CredentialDef {
         CredentialDef {
@@ -37,14 +51,39 @@ impl Default for CredentialDef {
             name: String::new(),
             source_id: String::new(),
             payment_txn: None,
+            revocation: None,
+            schema_id: None,
+            state: PublicEntityStateType::Published,
         }
     }
 }
 
+/// Revocation registry created alongside a credential definition that supports revocation.
+/// It is also stored in the wallet (keyed by cred_def_id) so credentials can be issued and
+/// revoked against it without holding on to the CredentialDef handle.
+#[derive(Deserialize, Debug, Serialize, PartialEq, Clone)]
+pub struct RevocationDetails {
+    pub rev_reg_id: String,
+    pub rev_reg_def: String,
+    pub tails_file: String,
+    pub max_creds: u32,
+}
+
+// Config accepted by vcx_credentialdef_create, ie:
+// {"support_revocation":true, "tails_file": "/tmp/tails", "max_creds": 5}
+#[derive(Deserialize, Debug, Default)]
+struct CredDefConfig {
+    #[serde(default)]
+    support_revocation: bool,
+    tails_file: Option<String>,
+    max_creds: Option<u32>,
+}
+
 impl CredentialDef {
 
-    pub fn from_str(input: &str) -> Result<CredentialDef, CredDefError> {
-        CredentialDef::from_string_with_version(&input).or(Err(CredDefError::CreateCredDefError()))
+    pub fn from_str(input: &str) -> VcxResult<CredentialDef> {
+        CredentialDef::from_string_with_version(&input)
+            .context(error::CREATE_CREDENTIAL_DEF_ERR.code_num, "cannot deserialize credential definition")
     }
 
     pub fn to_string(&self) -> String {
@@ -59,20 +98,18 @@ impl CredentialDef {
 
     pub fn set_source_id(&mut self, source_id: String) { self.source_id = source_id.clone(); }
 
-    fn get_payment_txn(&self) -> Result<PaymentTxn, u32> { Ok(self.payment_txn.clone().ok_or(error::NOT_READY.code_num)?) }
+    fn get_payment_txn(&self) -> VcxResult<PaymentTxn> { Ok(self.payment_txn.clone().ok_or(VcxError::from_code(error::NOT_READY.code_num))?) }
+
+    pub fn get_rev_reg_id(&self) -> Option<String> { self.revocation.as_ref().map(|r| r.rev_reg_id.clone()) }
+
+    pub fn get_tails_file(&self) -> Option<String> { self.revocation.as_ref().map(|r| r.tails_file.clone()) }
 
     fn to_string_with_version(&self) -> String {
-        json!({
-            "version": "1.0",
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
 
-    fn from_string_with_version(data: &str) -> Result<CredentialDef, CredDefError> {
-        let values:serde_json::Value = serde_json::from_str(data).or(Err(CredDefError::CommonError(error::INVALID_JSON.code_num)))?;
-        let version = values["version"].to_string();
-        let data = values["data"].to_string();
-        serde_json::from_str(&data).or(Err(CredDefError::CreateCredDefError()))
+    fn from_string_with_version(data: &str) -> VcxResult<CredentialDef> {
+        serialization::deserialize(data)
     }
 }
 
@@ -81,16 +118,26 @@ pub fn create_new_credentialdef(source_id: String,
                                 issuer_did: String,
                                 schema_id: String,
                                 tag: String,
-                                config_json: String) -> Result<u32, CredDefError> {
-    let schema_json = LedgerSchema::new_from_ledger(&schema_id)
-        .map_err(|x| CredDefError::CommonError(x.to_error_code()))?.schema_json;
+                                config_json: String) -> VcxResult<u32> {
+    let schema_json = LedgerSchema::new_from_ledger(&schema_id)?.schema_json;
+
+    let config: CredDefConfig = serde_json::from_str(&config_json)
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    let indy_config = json!({"support_revocation": config.support_revocation}).to_string();
 
     debug!("creating credentialdef with source_id: {}, name: {}, issuer_did: {}, schema_id: {}", source_id, name, issuer_did, schema_id);
     let (id, payment_txn) = _create_and_store_credential_def( &issuer_did,
                                                    &schema_json,
                                                    &tag,
                                                    None,
-                                                   &config_json)?;
+                                                   &indy_config)?;
+
+    let revocation = if config.support_revocation {
+        Some(_create_and_store_rev_reg(&issuer_did, &id, &config)?)
+    } else {
+        None
+    };
 
     let new_cred_def = CredentialDef {
         source_id,
@@ -98,105 +145,324 @@ pub fn create_new_credentialdef(source_id: String,
         tag,
         id,
         payment_txn,
+        revocation,
+        schema_id: Some(schema_id),
+        state: PublicEntityStateType::Published,
     };
 
-    let new_handle = CREDENTIALDEF_MAP.add(new_cred_def).map_err(|key|CredDefError::CreateCredDefError())?;
+    _record_created(&new_cred_def);
+
+    let new_handle = CREDENTIALDEF_MAP.add(new_cred_def).map_err(|key|VcxError::from_code(error::CREATE_CREDENTIAL_DEF_ERR.code_num))?;
 
     Ok(new_handle)
 }
 
+/// Builds the credential definition transaction for `endorser` to write it to the ledger, instead of
+/// writing it right away. Returns the credentialdef handle and the transaction signed by issuer_did.
+/// Revocation registries have to be written by the issuer, so revocation is not supported.
+pub fn prepare_credentialdef_for_endorser(source_id: String,
+                                          name: String,
+                                          issuer_did: String,
+                                          schema_id: String,
+                                          tag: String,
+                                          endorser: &str) -> VcxResult<(u32, String)> {
+    debug!("preparing credentialdef for endorser {} with source_id: {}, name: {}, issuer_did: {}, schema_id: {}", endorser, source_id, name, issuer_did, schema_id);
+    let (id, request) = if settings::test_indy_mode_enabled() {
+        (CRED_DEF_ID.to_string(), CRED_DEF_REQ.to_string())
+    } else {
+        let schema_json = LedgerSchema::new_from_ledger(&schema_id)?.schema_json;
+
+        let indy_config = json!({"support_revocation": false}).to_string();
+        _build_credential_def_request(&issuer_did, &schema_json, &tag, None, &indy_config)?
+    };
+
+    let request = libindy_prepare_request_for_endorser(&issuer_did, &request, endorser)?;
+
+    let new_cred_def = CredentialDef {
+        source_id,
+        name,
+        tag,
+        id,
+        payment_txn: None,
+        revocation: None,
+        schema_id: Some(schema_id),
+        state: PublicEntityStateType::Built,
+    };
+
+    let new_handle = CREDENTIALDEF_MAP.add(new_cred_def).map_err(|key|VcxError::from_code(error::CREATE_CREDENTIAL_DEF_ERR.code_num))?;
+
+    Ok((new_handle, request))
+}
+
+/// Writes the credential definition transaction signed by the endorser to the ledger.
+pub fn submit_endorsed(handle: u32, endorsed_request: &str) -> VcxResult<()> {
+    CREDENTIALDEF_MAP.get_mut(handle, |c| {
+        if c.state == PublicEntityStateType::Published {
+            warn!("credentialdef {} is already on the ledger", c.id);
+            return Err(VcxError::from_code(error::NOT_READY.code_num));
+        }
+
+        if !settings::test_indy_mode_enabled() {
+            let response = libindy_submit_request(endorsed_request)?;
+            let response: serde_json::Value = serde_json::from_str(&response).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+            if response.get("result").is_none() {
+                warn!("credentialdef {} was not written to the ledger: {}", c.id, response["reason"]);
+                return Err(VcxError::from_code(error::CREATE_CREDENTIAL_DEF_ERR.code_num));
+            }
+        }
+
+        c.state = PublicEntityStateType::Published;
+        _record_created(c);
+        Ok(())
+    })
+}
+
+pub fn get_state(handle: u32) -> VcxResult<u32> {
+    CREDENTIALDEF_MAP.get(handle, |c| {
+        Ok(c.state as u32)
+    })
+}
+
+/// Creates a CredentialDef object for a credential definition already written to the ledger.
+pub fn prepare_from_ledger(source_id: String, cred_def_id: &str) -> VcxResult<u32> {
+    debug!("preparing credentialdef with source_id: {} from ledger id: {}", source_id, cred_def_id);
+    let (id, cred_def_json) = retrieve_credential_def(cred_def_id)?;
+
+    let cred_def: serde_json::Value = serde_json::from_str(&cred_def_json)
+        .or(Err(VcxError::from_code(error::RETRIEVE_CREDENTIAL_DEF_ERR.code_num)))?;
+    let tag = cred_def["tag"].as_str().unwrap_or_default().to_string();
+
+    let new_cred_def = CredentialDef {
+        source_id,
+        name: tag.clone(),
+        tag,
+        revocation: get_revocation_details(&id)?,
+        schema_id: None,
+        id,
+        payment_txn: None,
+        state: PublicEntityStateType::Published,
+    };
+
+    CREDENTIALDEF_MAP.add(new_cred_def)
+}
+
+// The credential definition is on the ledger already, not being able to list it is no reason to fail
+fn _record_created(cred_def: &CredentialDef) {
+    if settings::test_indy_mode_enabled() { return; }
+
+    let entry = json!({
+        "source_id": cred_def.source_id,
+        "cred_def_id": cred_def.id,
+        "name": cred_def.name,
+        "schema_id": cred_def.schema_id,
+        "tag": cred_def.tag,
+    });
+
+    if let Err(err) = ::object_store::append(CREATED_CRED_DEFS_LIST, entry) {
+        warn!("could not record created credentialdef {}: {}", cred_def.id, err);
+    }
+}
+
+/// The credential definitions created with this wallet as a json array of their
+/// source_id, cred_def_id, name, schema_id and tag.
+pub fn list_created() -> VcxResult<String> {
+    if settings::test_indy_mode_enabled() { return Ok("[]".to_string()); }
+
+    let cred_defs = ::object_store::list(CREATED_CRED_DEFS_LIST)?;
+    Ok(json!(cred_defs).to_string())
+}
+
 //Todo: possibly move _create_and_store_credential_def and retrieve_cred_def to a common trait
 fn _create_and_store_credential_def(issuer_did: &str,
                                    schema_json: &str,
                                    tag: &str,
                                    sig_type: Option<&str>,
-                                   config_json: &str) -> Result<(String, Option<PaymentTxn>), CredDefError> {
+                                   config_json: &str) -> VcxResult<(String, Option<PaymentTxn>)> {
     if settings::test_indy_mode_enabled() {
         return Ok((CRED_DEF_ID.to_string(), Some(PaymentTxn::from_parts(r#"["pay:null:9UFgyjuJxi1i1HD"]"#,r#"[{"amount":4,"extra":null,"recipient":"pay:null:xkIsxem0YNtHrRO"}]"#,1, false).unwrap())));
     }
 
+    let (id, cred_def_req) = _build_credential_def_request(issuer_did, schema_json, tag, sig_type, config_json)?;
+
+    let (payment, response) = pay_for_txn(&cred_def_req, CRED_DEF_TXN_TYPE)?;
+
+    Ok((id, payment))
+}
+
+fn _build_credential_def_request(issuer_did: &str,
+                                 schema_json: &str,
+                                 tag: &str,
+                                 sig_type: Option<&str>,
+                                 config_json: &str) -> VcxResult<(String, String)> {
     let (id, cred_def_json) = libindy_create_and_store_credential_def(issuer_did,
                                                                       schema_json,
                                                                       tag,
                                                                       sig_type,
                                                                       config_json)
         .map_err(|err| {
-            match err {
-                //Todo: Find out how to match on Cred...code_num
+            match err.code() {
                 x if x == error::CREDENTIAL_DEF_ALREADY_CREATED.code_num => {
                     error!("cred_def for issuer_did {} already in wallet", issuer_did);
-                    CredDefError::CredDefAlreadyCreatedError()
+                    VcxError::from_code(error::CREDENTIAL_DEF_ALREADY_CREATED.code_num)
                 },
                 _ => {
                     error!("{} with: {}", error::CREATE_CREDENTIAL_DEF_ERR.message, err);
-                    CredDefError::CreateCredDefError()
+                    VcxError::from_code(error::CREATE_CREDENTIAL_DEF_ERR.code_num)
                 }
             }
         })?;
 
 
     let cred_def_req = libindy_build_create_credential_def_txn(issuer_did, &cred_def_json)
-        .or(Err(CredDefError::CreateCredDefError()))?;
+        .or(Err(VcxError::from_code(error::CREATE_CREDENTIAL_DEF_ERR.code_num)))?;
 
-    let (payment, response) = pay_for_txn(&cred_def_req, CRED_DEF_TXN_TYPE)
-        .map_err(|err| CredDefError::CommonError(err))?;
+    Ok((id, cred_def_req))
+}
 
-    Ok((id, payment))
+fn _create_and_store_rev_reg(issuer_did: &str,
+                             cred_def_id: &str,
+                             config: &CredDefConfig) -> VcxResult<RevocationDetails> {
+    let tails_file = config.tails_file.clone().ok_or(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num))?;
+    let max_creds = config.max_creds.ok_or(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num))?;
+
+    let (rev_reg_id, rev_reg_def, rev_reg_entry, _) = create_rev_reg_def(issuer_did,
+                                                                         cred_def_id,
+                                                                         &tails_file,
+                                                                         max_creds)
+        .map_err(|err| {
+            error!("{} with: {}", error::INVALID_REV_REG_DEF_CREATION.message, err);
+            VcxError::from_code(error::INVALID_REV_REG_DEF_CREATION.code_num)
+        })?;
+
+    publish_rev_reg_delta(issuer_did, &rev_reg_id, &rev_reg_entry)
+        .map_err(|err| {
+            error!("{} with: {}", error::INVALID_REV_ENTRY.message, err);
+            VcxError::from_code(error::INVALID_REV_REG_DEF_CREATION.code_num)
+        })?;
+
+    let details = RevocationDetails { rev_reg_id, rev_reg_def, tails_file, max_creds };
+
+    let record = serde_json::to_string(&details).or(Err(VcxError::from(error::SERIALIZATION_ERROR.code_num)))?;
+    wallet::add_record(REV_REG_RECORD_TYPE, cred_def_id, &record, "{}")?;
+
+    Ok(details)
+}
+
+/// Looks up the revocation registry the issuer created for cred_def_id.
+/// Returns None when the credential definition does not support revocation.
+pub fn get_revocation_details(cred_def_id: &str) -> VcxResult<Option<RevocationDetails>> {
+    if settings::test_indy_mode_enabled() { return Ok(None); }
+
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let record = match wallet::get_record(REV_REG_RECORD_TYPE, cred_def_id, &options) {
+        Ok(x) => x,
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => return Ok(None),
+        Err(x) => return Err(x),
+    };
+
+    let record: serde_json::Value = serde_json::from_str(&record)
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    let value = record["value"].as_str().ok_or(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num))?;
+
+    serde_json::from_str(value)
+        .map(|details| Some(details))
+        .or(Err(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num)))
 }
 
-pub fn retrieve_credential_def(cred_def_id: &str) -> Result<(String, String), CredDefError> {
+pub fn retrieve_credential_def(cred_def_id: &str) -> VcxResult<(String, String)> {
     if settings::test_indy_mode_enabled() { return Ok((CRED_DEF_ID.to_string(), CRED_DEF_JSON.to_string())); }
 
+    if let Some(cred_def) = ledger_cache::get(LedgerObject::CredDef, cred_def_id) { return Ok(cred_def); }
+
     let get_cred_def_req = libindy_build_get_credential_def_txn(cred_def_id)
-        .or(Err(CredDefError::BuildCredDefRequestError()))?;
+        .or(Err(VcxError::from_code(error::BUILD_CREDENTIAL_DEF_REQ_ERR.code_num)))?;
+
+    let get_cred_def_response = libindy_submit_request(&get_cred_def_req)?;
+
+    let cred_def = libindy_parse_get_cred_def_response(&get_cred_def_response)
+        .or(Err(VcxError::from_code(error::RETRIEVE_CREDENTIAL_DEF_ERR.code_num)))?;
+
+    ledger_cache::put(LedgerObject::CredDef, cred_def_id, &cred_def);
+    Ok(cred_def)
+}
 
-    let get_cred_def_response = libindy_submit_request(&get_cred_def_req)
-        .map_err(|err| CredDefError::CommonError(err))?;
+/// The id of the schema the credential definition was created from.
+pub fn get_schema_id(cred_def_id: &str) -> VcxResult<String> {
+    let (_, cred_def_json) = retrieve_credential_def(cred_def_id)?;
 
-    libindy_parse_get_cred_def_response(&get_cred_def_response)
-        .or(Err(CredDefError::RetrieveCredDefError()))
+    let cred_def: serde_json::Value = serde_json::from_str(&cred_def_json)
+        .or(Err(VcxError::from_code(error::RETRIEVE_CREDENTIAL_DEF_ERR.code_num)))?;
+
+    // The ledger names the schema by its sequence number
+    let schema_ref = match cred_def["schemaId"].as_str() {
+        Some(x) => x,
+        None => return Err(VcxError::from_code(error::RETRIEVE_CREDENTIAL_DEF_ERR.code_num)),
+    };
+
+    match schema_ref.parse::<u32>() {
+        Ok(sequence_num) => LedgerSchema::id_from_seq_no(sequence_num),
+        Err(_) => Ok(schema_ref.to_string()),
+    }
 }
 
 pub fn is_valid_handle(handle: u32) -> bool {
     CREDENTIALDEF_MAP.has_handle(handle)
 }
 
-pub fn to_string(handle: u32) -> Result<String, u32> {
+/// The context the credential definition was created in, calls on the credential definition run in it.
+pub fn get_context(handle: u32) -> u32 {
+    CREDENTIALDEF_MAP.context_of(handle)
+}
+
+pub fn to_string(handle: u32) -> VcxResult<String> {
     CREDENTIALDEF_MAP.get(handle, |cd| {
         Ok(CredentialDef::to_string_with_version(&cd))
     })
 }
 
-pub fn from_string(credentialdef_data: &str) -> Result<u32, CredDefError> {
+pub fn from_string(credentialdef_data: &str) -> VcxResult<u32> {
     let derived_credentialdef: CredentialDef = CredentialDef::from_str(credentialdef_data)?;
     let source_id = derived_credentialdef.source_id.clone();
-    let new_handle = CREDENTIALDEF_MAP.add(derived_credentialdef).map_err(|ec|CredDefError::CommonError(ec))?;
+    let new_handle = CREDENTIALDEF_MAP.add(derived_credentialdef)?;
 
     Ok(new_handle)
 }
 
-pub fn get_source_id(handle: u32) -> Result<String, CredDefError> {
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     CREDENTIALDEF_MAP.get(handle,|c| {
         Ok(c.get_source_id().clone())
-    }).map_err(|ec|CredDefError::CommonError(ec))
+    })
 }
 
-pub fn get_payment_txn(handle: u32) -> Result<PaymentTxn, CredDefError> {
+pub fn get_payment_txn(handle: u32) -> VcxResult<PaymentTxn> {
     CREDENTIALDEF_MAP.get(handle,|c| {
         c.get_payment_txn()
-    }).or(Err(CredDefError::NoPaymentInformation()))
+    }).or(Err(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num)))
 }
 
-pub fn get_cred_def_id(handle: u32) -> Result<String, CredDefError> {
+pub fn get_cred_def_id(handle: u32) -> VcxResult<String> {
     CREDENTIALDEF_MAP.get(handle,|c| {
         Ok(c.get_cred_def_id().clone())
-    }).map_err(|ec|CredDefError::CommonError(ec))
+    })
+}
+
+pub fn get_rev_reg_id(handle: u32) -> VcxResult<Option<String>> {
+    CREDENTIALDEF_MAP.get(handle,|c| {
+        Ok(c.get_rev_reg_id())
+    })
 }
 
-pub fn release(handle: u32) -> Result<(), CredDefError> {
+pub fn get_tails_file(handle: u32) -> VcxResult<Option<String>> {
+    CREDENTIALDEF_MAP.get(handle,|c| {
+        Ok(c.get_tails_file())
+    })
+}
+
+pub fn release(handle: u32) -> VcxResult<()> {
     match CREDENTIALDEF_MAP.release(handle) {
         Ok(_) => Ok(()),
-        Err(_) => Err(CredDefError::InvalidHandle()),
+        Err(_) => Err(VcxError::from_code(error::INVALID_CREDENTIAL_DEF_HANDLE.code_num)),
     }
 }
 
@@ -209,7 +475,7 @@ pub fn release_all() {
 
 #[cfg(test)]
 pub mod tests {
-    use utils::constants::{SCHEMA_ID, SCHEMAS_JSON};
+    use utils::constants::{SCHEMA_ID, SCHEMAS_JSON, REV_REG_ID, TEST_TAILS_FILE};
     use super::*;
 
     static CREDENTIAL_DEF_NAME: &str = "Test Credential Definition";
@@ -266,6 +532,35 @@ pub mod tests {
         assert!(payment.len() > 0);
     }
 
+    #[cfg(feature = "pool_tests")]
+    #[test]
+    fn test_list_and_prepare_created_credential_def_real() {
+        init!("ledger");
+
+        let (schema_id, _) = ::utils::libindy::anoncreds::tests::create_and_write_test_schema(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let handle = create_new_credentialdef("listed".to_string(),
+                                              "name".to_string(),
+                                              did,
+                                              schema_id.clone(),
+                                              "tag_1".to_string(),
+                                              r#"{"support_revocation":false}"#.to_string()).unwrap();
+        let cred_def_id = get_cred_def_id(handle).unwrap();
+
+        let created: serde_json::Value = serde_json::from_str(&list_created().unwrap()).unwrap();
+        assert!(created.as_array().unwrap().contains(&json!({
+            "source_id": "listed",
+            "cred_def_id": cred_def_id,
+            "name": "name",
+            "schema_id": schema_id,
+            "tag": "tag_1",
+        })));
+
+        let prepared = prepare_from_ledger("prepared".to_string(), &cred_def_id).unwrap();
+        assert_eq!(get_cred_def_id(prepared).unwrap(), cred_def_id);
+        assert_eq!(get_source_id(prepared).unwrap(), "prepared");
+    }
+
     #[cfg(feature = "pool_tests")]
     #[test]
     fn test_create_credential_def_no_fees_real() {
@@ -284,6 +579,28 @@ pub mod tests {
                                           r#"{"support_revocation":false}"#.to_string()).unwrap();
     }
 
+    #[cfg(feature = "pool_tests")]
+    #[test]
+    fn test_create_revocable_credential_def_real() {
+        init!("ledger");
+        let (schema_id, _) = ::utils::libindy::anoncreds::tests::create_and_write_test_schema(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let config = json!({"support_revocation": true, "tails_file": TEST_TAILS_FILE, "max_creds": 2}).to_string();
+
+        let handle = create_new_credentialdef("1".to_string(),
+                                              "name".to_string(),
+                                              did,
+                                              schema_id,
+                                              "tag_1".to_string(),
+                                              config).unwrap();
+
+        let rev_reg_id = get_rev_reg_id(handle).unwrap().unwrap();
+        let cred_def_id = get_cred_def_id(handle).unwrap();
+        let details = get_revocation_details(&cred_def_id).unwrap().unwrap();
+        assert_eq!(details.rev_reg_id, rev_reg_id);
+        assert_eq!(details.tails_file, TEST_TAILS_FILE);
+    }
+
     #[test]
     fn test_create_credential_def_and_store_in_wallet() {
         init!("true");
@@ -313,7 +630,7 @@ pub mod tests {
                                           "tag_1".to_string(),
                                           r#"{"support_revocation":false}"#.to_string());
 
-        assert_eq!(rc.err(), Some(CredDefError::CredDefAlreadyCreatedError()));
+        assert_eq!(rc.err(), Some(VcxError::from_code(error::CREDENTIAL_DEF_ALREADY_CREATED.code_num)));
     }
 
     #[test]
@@ -328,6 +645,61 @@ pub mod tests {
         assert!(handle > 0);
     }
 
+    #[test]
+    fn test_prepare_credentialdef_for_endorser() {
+        init!("true");
+        let endorser = "V4SGRU86Z58d6TV7PBUe6f";
+        let (handle, request) = prepare_credentialdef_for_endorser("SourceId".to_string(),
+                                                                   CREDENTIAL_DEF_NAME.to_string(),
+                                                                   ISSUER_DID.to_string(),
+                                                                   SCHEMA_ID.to_string(),
+                                                                   "tag".to_string(),
+                                                                   endorser).unwrap();
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["endorser"], json!(endorser));
+        assert_eq!(get_state(handle).unwrap(), PublicEntityStateType::Built as u32);
+
+        submit_endorsed(handle, &request.to_string()).unwrap();
+        assert_eq!(get_state(handle).unwrap(), PublicEntityStateType::Published as u32);
+        assert_eq!(submit_endorsed(handle, &request.to_string()).err(),
+                   Some(VcxError::from(error::NOT_READY.code_num)));
+    }
+
+    #[test]
+    fn test_create_revocable_credentialdef() {
+        init!("true");
+        let config = json!({"support_revocation": true, "tails_file": TEST_TAILS_FILE, "max_creds": 5}).to_string();
+        let handle = create_new_credentialdef("SourceId".to_string(),
+                                              CREDENTIAL_DEF_NAME.to_string(),
+                                              ISSUER_DID.to_string(),
+                                              SCHEMA_ID.to_string(),
+                                              "tag".to_string(),
+                                              config).unwrap();
+        assert_eq!(get_rev_reg_id(handle).unwrap(), Some(REV_REG_ID.to_string()));
+        assert_eq!(get_tails_file(handle).unwrap(), Some(TEST_TAILS_FILE.to_string()));
+    }
+
+    #[test]
+    fn test_create_revocable_credentialdef_fails_without_tails_file() {
+        init!("true");
+        let config = json!({"support_revocation": true, "max_creds": 5}).to_string();
+        let rc = create_new_credentialdef("SourceId".to_string(),
+                                          CREDENTIAL_DEF_NAME.to_string(),
+                                          ISSUER_DID.to_string(),
+                                          SCHEMA_ID.to_string(),
+                                          "tag".to_string(),
+                                          config);
+        assert_eq!(rc.err(), Some(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num)));
+
+        let handle = create_new_credentialdef("SourceId".to_string(),
+                                              CREDENTIAL_DEF_NAME.to_string(),
+                                              ISSUER_DID.to_string(),
+                                              SCHEMA_ID.to_string(),
+                                              "tag".to_string(),
+                                              "{}".to_string()).unwrap();
+        assert_eq!(get_rev_reg_id(handle).unwrap(), None);
+    }
+
     #[test]
     fn test_to_string_succeeds() {
         init!("true");
@@ -359,7 +731,23 @@ pub mod tests {
         let credentialdef1: CredentialDef = CredentialDef::from_str(&credentialdef_data).unwrap();
         let credentialdef2: CredentialDef = CredentialDef::from_str(&new_credentialdef_data).unwrap();
         assert_eq!(credentialdef1,credentialdef2);
-        assert_eq!(CredentialDef::from_str("{}").err(), Some(CredDefError::CreateCredDefError()));
+        assert_eq!(CredentialDef::from_str("{}").err(), Some(VcxError::from_code(error::CREATE_CREDENTIAL_DEF_ERR.code_num)));
+    }
+
+    #[test]
+    fn test_get_schema_id() {
+        init!("true");
+        assert_eq!(get_schema_id(CRED_DEF_ID).unwrap(), SCHEMA_ID);
+    }
+
+    #[test]
+    fn test_prepare_from_ledger() {
+        init!("true");
+        let handle = prepare_from_ledger("SourceId".to_string(), CRED_DEF_ID).unwrap();
+        assert_eq!(get_cred_def_id(handle).unwrap(), CRED_DEF_ID);
+        assert_eq!(get_source_id(handle).unwrap(), "SourceId");
+        assert_eq!(get_payment_txn(handle).err(), Some(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num)));
+        assert_eq!(list_created().unwrap(), "[]");
     }
 
     #[test]
@@ -371,10 +759,10 @@ pub mod tests {
         let h4 = create_new_credentialdef("SourceId".to_string(), CREDENTIAL_DEF_NAME.to_string(), ISSUER_DID.to_string(), SCHEMA_ID.to_string(), "tag".to_string(), "{}".to_string()).unwrap();
         let h5 = create_new_credentialdef("SourceId".to_string(), CREDENTIAL_DEF_NAME.to_string(), ISSUER_DID.to_string(), SCHEMA_ID.to_string(), "tag".to_string(), "{}".to_string()).unwrap();
         release_all();
-        assert_eq!(release(h1),Err(CredDefError::InvalidHandle()));
-        assert_eq!(release(h2),Err(CredDefError::InvalidHandle()));
-        assert_eq!(release(h3),Err(CredDefError::InvalidHandle()));
-        assert_eq!(release(h4),Err(CredDefError::InvalidHandle()));
-        assert_eq!(release(h5),Err(CredDefError::InvalidHandle()));
+        assert_eq!(release(h1),Err(VcxError::from_code(error::INVALID_CREDENTIAL_DEF_HANDLE.code_num)));
+        assert_eq!(release(h2),Err(VcxError::from_code(error::INVALID_CREDENTIAL_DEF_HANDLE.code_num)));
+        assert_eq!(release(h3),Err(VcxError::from_code(error::INVALID_CREDENTIAL_DEF_HANDLE.code_num)));
+        assert_eq!(release(h4),Err(VcxError::from_code(error::INVALID_CREDENTIAL_DEF_HANDLE.code_num)));
+        assert_eq!(release(h5),Err(VcxError::from_code(error::INVALID_CREDENTIAL_DEF_HANDLE.code_num)));
     }
 }
@@ -1,6 +1,7 @@
 extern crate serde_json;
 
-use error::issuer_cred::IssuerCredError;
+use utils::error;
+use error::{ VcxError, VcxResult };
 
 static ISSUER_DID: &'static str = "issuer_did";
 static SEQUENCE_NUMBER: &'static str = "schema_seq_no";
@@ -35,12 +36,12 @@ impl CredentialRequest {
        }
     }
 
-    pub fn from_str(payload:&str) -> Result<CredentialRequest, IssuerCredError> {
+    pub fn from_str(payload:&str) -> VcxResult<CredentialRequest> {
         match serde_json::from_str(payload) {
             Ok(p) => Ok(p),
             Err(_) => {
-                warn!("{}", IssuerCredError::InvalidCredRequest());
-                Err(IssuerCredError::InvalidCredRequest())
+                warn!("{}", VcxError::from_code(error::INVALID_CREDENTIAL_REQUEST.code_num));
+                Err(VcxError::from_code(error::INVALID_CREDENTIAL_REQUEST.code_num))
             }
         }
     }
@@ -98,7 +99,7 @@ mod tests {
     fn test_error() {
         let invalid_json = r#"{bad:json"#;
         let cred_req = CredentialRequest::from_str(invalid_json);
-        assert_eq!(cred_req.err(), Some(IssuerCredError::InvalidCredRequest()));
+        assert_eq!(cred_req.err(), Some(VcxError::from_code(error::INVALID_CREDENTIAL_REQUEST.code_num)));
     }
 }
 
@@ -0,0 +1,360 @@
+extern crate serde_json;
+
+use std::cmp::Reverse;
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use serde_json::Value;
+use utils::error;
+use error::{ VcxError, VcxResult };
+
+// libindy lists every credential usable for a referent, in an order of its own which is neither
+// the order they were stored in nor the order they were issued in, when vcx stored a credential
+// is kept in the wallet instead. The selection picks one of them for each referent in the shape
+// generate_proof takes, the app can still choose the credential of any referent itself.
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// The credential stored last, credentials stored outside of vcx count as the oldest.
+    Newest,
+    /// A credential of the first preferred issuer that has one, the first listed otherwise.
+    PreferredIssuer,
+    /// As few credentials as possible, then the ones holding the fewest attributes.
+    LeastRevealing,
+}
+
+impl Default for Strategy {
+    fn default() -> Strategy { Strategy::Newest }
+}
+
+/// Credential ids chosen by the app, by referent of each section as in selected_credentials. The
+/// same referent may be asked for as an attribute and as a predicate.
+#[derive(Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    #[serde(default)]
+    pub attrs: HashMap<String, String>,
+    #[serde(default)]
+    pub predicates: HashMap<String, String>,
+}
+
+impl Overrides {
+    fn get(&self, &(ref section, ref referent): &ReferentKey) -> Option<&String> {
+        match section.as_str() {
+            "attrs" => self.attrs.get(referent),
+            "predicates" => self.predicates.get(referent),
+            _ => None,
+        }
+    }
+
+    fn keys(&self) -> Vec<ReferentKey> {
+        let attrs = self.attrs.keys().map(|referent| ("attrs".to_string(), referent.to_string()));
+        let predicates = self.predicates.keys().map(|referent| ("predicates".to_string(), referent.to_string()));
+        attrs.chain(predicates).collect()
+    }
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct SelectionOptions {
+    #[serde(default)]
+    pub strategy: Strategy,
+    #[serde(default)]
+    pub preferred_issuers: Vec<String>,
+    /// Used whatever the strategy.
+    #[serde(default)]
+    pub overrides: Overrides,
+}
+
+impl SelectionOptions {
+    pub fn from_str(options: &str) -> VcxResult<SelectionOptions> {
+        let options: SelectionOptions = serde_json::from_str(options)
+            .map_err(|err| {
+                warn!("invalid credential selection options: {}", err);
+                VcxError::from(error::INVALID_JSON.code_num)
+            })?;
+
+        if options.strategy == Strategy::PreferredIssuer && options.preferred_issuers.is_empty() {
+            warn!("preferred_issuer strategy needs preferred_issuers");
+            return Err(VcxError::from(error::INVALID_OPTION.code_num));
+        }
+
+        Ok(options)
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct CredentialSelection {
+    pub selected_credentials: Value,
+    pub unsatisfied: Vec<String>,
+}
+
+type ReferentKey = (String, String);
+
+fn cred_id(candidate: &Value) -> Option<&str> {
+    candidate["cred_info"]["referent"].as_str()
+}
+
+fn issuer_did(candidate: &Value) -> &str {
+    candidate["cred_info"]["cred_def_id"].as_str()
+        .and_then(|cred_def_id| cred_def_id.split(':').next())
+        .unwrap_or_default()
+}
+
+fn attr_count(candidate: &Value) -> usize {
+    candidate["cred_info"]["attrs"].as_object().map(|attrs| attrs.len()).unwrap_or_default()
+}
+
+/// Picks a credential for each referent of `credentials`, as returned by retrieve_credentials.
+/// Referents without any credential are reported as unsatisfied.
+pub fn select_credentials(credentials: &str, options: &SelectionOptions) -> VcxResult<CredentialSelection> {
+    let credentials: Value = serde_json::from_str(credentials)
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    let mut candidates: BTreeMap<ReferentKey, Vec<Value>> = BTreeMap::new();
+    for section in &["attrs", "predicates"] {
+        if let Some(referents) = credentials[*section].as_object() {
+            for (referent, found) in referents {
+                candidates.insert((section.to_string(), referent.to_string()),
+                                  found.as_array().cloned().unwrap_or_default());
+            }
+        }
+    }
+
+    for key in options.overrides.keys() {
+        if !candidates.contains_key(&key) {
+            warn!("credential chosen for {} {} which the proof request does not ask for", key.0, key.1);
+            return Err(VcxError::from(error::INVALID_CREDENTIAL_SELECTION.code_num));
+        }
+    }
+
+    let mut selected: BTreeMap<ReferentKey, Value> = BTreeMap::new();
+    let mut unsatisfied = Vec::new();
+    for (key, found) in candidates.iter() {
+        if let Some(id) = options.overrides.get(key) {
+            let candidate = found.iter().find(|candidate| cred_id(candidate) == Some(id.as_str()))
+                .ok_or_else(|| {
+                    warn!("credential {} can not be used for {}", id, key.1);
+                    VcxError::from(error::INVALID_CREDENTIAL_SELECTION.code_num)
+                })?;
+            selected.insert(key.clone(), candidate.clone());
+        } else if found.is_empty() {
+            unsatisfied.push(key.1.clone());
+        }
+    }
+
+    let open: Vec<(&ReferentKey, &Vec<Value>)> = candidates.iter()
+        .filter(|&(key, found)| !found.is_empty() && !selected.contains_key(key))
+        .collect();
+
+    match options.strategy {
+        Strategy::Newest => {
+            let stored_at: HashMap<String, u64> = open.iter()
+                .flat_map(|&(_, found)| found.iter().filter_map(cred_id))
+                .filter_map(|id| ::credential::get_stored_at(id).map(|time| (id.to_string(), time)))
+                .collect();
+            _pick_newest(&open, &stored_at, &mut selected)
+        },
+        Strategy::PreferredIssuer => _pick_each(&open, &options.preferred_issuers, &mut selected),
+        Strategy::LeastRevealing => _pick_fewest(open, &mut selected),
+    }
+    debug!("selected credentials for {} referents, {} unsatisfied", selected.len(), unsatisfied.len());
+
+    let mut selected_credentials = json!({"attrs": {}, "predicates": {}});
+    for ((section, referent), candidate) in selected {
+        selected_credentials[section.as_str()][referent.as_str()] = candidate;
+    }
+
+    Ok(CredentialSelection { selected_credentials, unsatisfied })
+}
+
+// Ties, credentials stored in the same millisecond or outside of vcx, go to the first listed.
+fn _pick_newest(open: &[(&ReferentKey, &Vec<Value>)],
+                stored_at: &HashMap<String, u64>,
+                selected: &mut BTreeMap<ReferentKey, Value>) {
+    for &(key, found) in open {
+        let newest = found.iter().enumerate()
+            .max_by_key(|&(index, candidate)| {
+                let time = cred_id(candidate).and_then(|id| stored_at.get(id)).cloned();
+                (time, Reverse(index))
+            })
+            .map(|(_, candidate)| candidate.clone());
+        if let Some(newest) = newest {
+            selected.insert(key.clone(), newest);
+        }
+    }
+}
+
+// Issuers earlier in the list win, the first listed credential of the best issuer is taken.
+fn _pick_each(open: &[(&ReferentKey, &Vec<Value>)],
+              preferred_issuers: &[String],
+              selected: &mut BTreeMap<ReferentKey, Value>) {
+    let rank = |candidate: &Value| preferred_issuers.iter()
+        .position(|did| did == issuer_did(candidate))
+        .unwrap_or(preferred_issuers.len());
+
+    for &(key, found) in open {
+        let best = found.iter().enumerate()
+            .min_by_key(|&(index, candidate)| (rank(candidate), index))
+            .map(|(_, candidate)| candidate.clone());
+        if let Some(best) = best {
+            selected.insert(key.clone(), best);
+        }
+    }
+}
+
+// Every credential in the proof discloses its schema and credential definition, so the
+// credentials already selected are reused first, then the ones serving the most referents.
+fn _pick_fewest(mut open: Vec<(&ReferentKey, &Vec<Value>)>, selected: &mut BTreeMap<ReferentKey, Value>) {
+    while !open.is_empty() {
+        let best_id = {
+            let used: HashSet<&str> = selected.values().filter_map(cred_id).collect();
+            let mut serves: HashMap<&str, (usize, &Value)> = HashMap::new();
+            for &(_, found) in open.iter() {
+                for candidate in found.iter() {
+                    if let Some(id) = cred_id(candidate) {
+                        serves.entry(id).or_insert((0, candidate)).0 += 1;
+                    }
+                }
+            }
+
+            match serves.iter().max_by_key(|&(id, &(count, candidate))| (used.contains(id), count, Reverse(attr_count(candidate)), *id)) {
+                Some((id, _)) => id.to_string(),
+                None => return,
+            }
+        };
+
+        open.retain(|&(key, found)| {
+            match found.iter().find(|candidate| cred_id(candidate) == Some(best_id.as_str())) {
+                Some(candidate) => {
+                    selected.insert(key.clone(), candidate.clone());
+                    false
+                },
+                None => true,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, issuer: &str, attrs: Value) -> Value {
+        json!({
+            "cred_info": {
+                "referent": id,
+                "attrs": attrs,
+                "schema_id": format!("{}:2:test-licence:4.4.4", issuer),
+                "cred_def_id": format!("{}:3:CL:2471", issuer),
+                "rev_reg_id": null,
+                "cred_rev_id": null
+            },
+            "interval": null
+        })
+    }
+
+    fn credentials() -> String {
+        let licence = candidate("licence", "2hoqvcwupRTUNkXn6ArYzs", json!({"name": "Bob", "height": "4'11", "age": "111"}));
+        let passport = candidate("passport", "V4SGRU86Z58d6TV7PBUe6f", json!({"name": "Bob", "age": "111"}));
+        let address = candidate("address", "2hoqvcwupRTUNkXn6ArYzs", json!({"name": "Bob", "zip": "87121"}));
+        json!({
+            "attrs": {
+                "name_1": [licence.clone(), passport.clone(), address.clone()],
+                "height_2": [licence.clone()],
+                "zip_3": [address.clone()],
+                "email_4": []
+            },
+            "predicates": {
+                "age_5": [licence.clone(), passport.clone()]
+            }
+        }).to_string()
+    }
+
+    fn selected_ids(selection: &CredentialSelection) -> Vec<(String, String)> {
+        let mut ids = Vec::new();
+        for section in &["attrs", "predicates"] {
+            for (referent, candidate) in selection.selected_credentials[*section].as_object().unwrap() {
+                ids.push((referent.to_string(), cred_id(candidate).unwrap().to_string()));
+            }
+        }
+        ids.sort();
+        ids
+    }
+
+    fn ids(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(referent, id)| (referent.to_string(), id.to_string())).collect()
+    }
+
+    #[test]
+    fn test_select_newest() {
+        init!("true");
+        // no credential has a stored time in test mode, the first listed is taken
+        let selection = select_credentials(&credentials(), &SelectionOptions::from_str("{}").unwrap()).unwrap();
+        assert_eq!(selected_ids(&selection), ids(&[("age_5", "licence"), ("height_2", "licence"), ("name_1", "licence"), ("zip_3", "address")]));
+        assert_eq!(selection.unsatisfied, vec!["email_4"]);
+
+        let credentials: Value = serde_json::from_str(&credentials()).unwrap();
+        let name = ("attrs".to_string(), "name_1".to_string());
+        let age = ("predicates".to_string(), "age_5".to_string());
+        let name_found = credentials["attrs"]["name_1"].as_array().cloned().unwrap();
+        let age_found = credentials["predicates"]["age_5"].as_array().cloned().unwrap();
+        let open = vec![(&name, &name_found), (&age, &age_found)];
+
+        let mut stored_at = HashMap::new();
+        stored_at.insert("licence".to_string(), 1000);
+        stored_at.insert("passport".to_string(), 3000);
+        stored_at.insert("address".to_string(), 2000);
+        let mut selected = BTreeMap::new();
+        _pick_newest(&open, &stored_at, &mut selected);
+        assert_eq!(cred_id(&selected[&name]), Some("passport"));
+        assert_eq!(cred_id(&selected[&age]), Some("passport"));
+
+        // credentials without a stored time are older than any other
+        stored_at.remove("passport");
+        let mut selected = BTreeMap::new();
+        _pick_newest(&open, &stored_at, &mut selected);
+        assert_eq!(cred_id(&selected[&name]), Some("address"));
+        assert_eq!(cred_id(&selected[&age]), Some("licence"));
+    }
+
+    #[test]
+    fn test_select_preferred_issuer() {
+        let options = SelectionOptions::from_str(r#"{"strategy":"preferred_issuer","preferred_issuers":["V4SGRU86Z58d6TV7PBUe6f"]}"#).unwrap();
+        let selection = select_credentials(&credentials(), &options).unwrap();
+        assert_eq!(selected_ids(&selection), ids(&[("age_5", "passport"), ("height_2", "licence"), ("name_1", "passport"), ("zip_3", "address")]));
+
+        assert_eq!(SelectionOptions::from_str(r#"{"strategy":"preferred_issuer"}"#).err(),
+                   Some(VcxError::from(error::INVALID_OPTION.code_num)));
+        assert_eq!(SelectionOptions::from_str(r#"{"strategy":"oldest"}"#).err(),
+                   Some(VcxError::from(error::INVALID_JSON.code_num)));
+    }
+
+    #[test]
+    fn test_select_least_revealing() {
+        let options = SelectionOptions::from_str(r#"{"strategy":"least_revealing"}"#).unwrap();
+        let selection = select_credentials(&credentials(), &options).unwrap();
+        assert_eq!(selected_ids(&selection), ids(&[("age_5", "licence"), ("height_2", "licence"), ("name_1", "licence"), ("zip_3", "address")]));
+        assert_eq!(selection.unsatisfied, vec!["email_4"]);
+    }
+
+    #[test]
+    fn test_select_with_overrides() {
+        let options = SelectionOptions::from_str(r#"{"strategy":"least_revealing","overrides":{"attrs":{"name_1":"address"}}}"#).unwrap();
+        let selection = select_credentials(&credentials(), &options).unwrap();
+        assert_eq!(selected_ids(&selection), ids(&[("age_5", "licence"), ("height_2", "licence"), ("name_1", "address"), ("zip_3", "address")]));
+
+        let options = SelectionOptions::from_str(r#"{"overrides":{"attrs":{"zip_3":"licence"}}}"#).unwrap();
+        assert_eq!(select_credentials(&credentials(), &options).err(),
+                   Some(VcxError::from(error::INVALID_CREDENTIAL_SELECTION.code_num)));
+
+        let options = SelectionOptions::from_str(r#"{"overrides":{"attrs":{"phone_6":"licence"}}}"#).unwrap();
+        assert_eq!(select_credentials(&credentials(), &options).err(),
+                   Some(VcxError::from(error::INVALID_CREDENTIAL_SELECTION.code_num)));
+
+        // overrides name the section of the referent
+        let options = SelectionOptions::from_str(r#"{"overrides":{"predicates":{"name_1":"address"}}}"#).unwrap();
+        assert_eq!(select_credentials(&credentials(), &options).err(),
+                   Some(VcxError::from(error::INVALID_CREDENTIAL_SELECTION.code_num)));
+        assert_eq!(SelectionOptions::from_str(r#"{"overrides":{"name_1":"address"}}"#).err(),
+                   Some(VcxError::from(error::INVALID_JSON.code_num)));
+    }
+}
@@ -1,6 +1,7 @@
 extern crate serde_json;
 
-use std::collections::HashMap;
+use std::collections::{ HashMap, HashSet };
+use std::path::Path;
 use object_cache::ObjectCache;
 use api::VcxStateType;
 use utils::error;
@@ -8,28 +9,32 @@ use connection;
 use messages;
 use messages::GeneralMessage;
 use messages::proofs::proof_message::{ProofMessage };
-use messages::proofs::proof_request::{ ProofRequestMessage };
+use messages::proofs::proof_request::{ ProofRequestMessage, ProofRequestData, NonRevokedInterval, Restriction,
+                                      attr_common_view, check_restrictions };
 use messages::extract_json_payload;
 use messages::to_u8;
+use messages::MessageResponseCode::MessagePending;
+use messages::get_message::{ Message, MessagesByDid };
 
 use credential_def::{ retrieve_credential_def };
+use credential_selection::{ self, SelectionOptions };
 use schema::{ LedgerSchema };
 
 use utils::libindy::anoncreds;
-use utils::libindy::crypto;
+use utils::openssl::encode;
 use utils::serde_utils;
 
 use settings;
 use utils::httpclient;
-use utils::constants::{ DEFAULT_SERIALIZE_VERSION, CREDS_FROM_PROOF_REQ, DEFAULT_GENERATED_PROOF };
+use utils::constants::{ CREDS_FROM_PROOF_REQ, DEFAULT_GENERATED_PROOF };
+use utils::serialization::{ self, Versioned };
 
 use serde_json::{Value};
 
-use error::ToErrorCode;
-use error::proof::ProofError;
+use error::{ VcxError, VcxResult, VcxResultExt };
 
 lazy_static! {
-    static ref HANDLE_MAP: ObjectCache<DisclosedProof>  = Default::default();
+    static ref HANDLE_MAP: ObjectCache<DisclosedProof>  = ObjectCache::persistent("vcx_disclosed_proof", DisclosedProof::to_string);
 }
 
 impl Default for DisclosedProof {
@@ -66,6 +71,8 @@ pub struct DisclosedProof {
     agent_vk: Option<String>,
 }
 
+impl Versioned for DisclosedProof {}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RequestedCreds {
     pub self_attested_attributes: HashMap<String, String>,
@@ -73,37 +80,172 @@ pub struct RequestedCreds {
     pub requested_predicates: HashMap<String, String>
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct CredInfo {
+    pub requested_attr: String,
     pub referent: String,
     pub schema_id: String,
     pub cred_def_id: String,
+    pub rev_reg_id: Option<String>,
+    pub cred_rev_id: Option<String>,
+    pub revocation_interval: Option<NonRevokedInterval>,
+    pub timestamp: Option<u64>,
+    #[serde(default)]
+    pub is_predicate: bool,
 }
 
-fn credential_def_identifiers(credentials: &str) -> Result<Vec<(String, String, String, String)>, ProofError> {
+fn credential_def_identifiers(credentials: &str, proof_req: &ProofRequestData) -> VcxResult<Vec<CredInfo>> {
     let mut rtn = Vec::new();
 
     let credentials: Value = serde_json::from_str(credentials)
-        .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?;
-
-    if let Value::Object(ref attrs) = credentials["attrs"] {
-        for (requested_attr, value) in attrs {
-            if let Some(ref attr_obj) = value.get("cred_info") {
-                rtn.push((
-                    requested_attr.to_string(),
-                    serde_utils::get_value_to_string("referent", attr_obj)
-                        .map_err(|e| ProofError::CommonError(e))?,
-                    serde_utils::get_value_to_string("schema_id", attr_obj)
-                             .map_err(|e| ProofError::CommonError(e))?,
-                    serde_utils::get_value_to_string("cred_def_id", attr_obj)
-                             .map_err(|e| ProofError::CommonError(e))?
-                 ));
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    for (section, is_predicate) in vec![("attrs", false), ("predicates", true)] {
+        if let Value::Object(ref attrs) = credentials[section] {
+            for (requested_attr, value) in attrs {
+                if let Some(ref attr_obj) = value.get("cred_info") {
+                    let cred_info = CredInfo {
+                        requested_attr: requested_attr.to_string(),
+                        referent: serde_utils::get_value_to_string("referent", attr_obj)?,
+                        schema_id: serde_utils::get_value_to_string("schema_id", attr_obj)?,
+                        cred_def_id: serde_utils::get_value_to_string("cred_def_id", attr_obj)?,
+                        rev_reg_id: attr_obj["rev_reg_id"].as_str().map(|x| x.to_string()),
+                        cred_rev_id: attr_obj["cred_rev_id"].as_str().map(|x| x.to_string()),
+                        revocation_interval: _get_revocation_interval(requested_attr, is_predicate, value, proof_req)?,
+                        timestamp: None,
+                        is_predicate,
+                    };
+
+                    if is_predicate {
+                        rtn.push(cred_info);
+                        continue;
+                    }
+
+                    // the credential selected for a referent with names proves each of the names
+                    for indy_referent in proof_req.indy_referents(requested_attr) {
+                        rtn.push(CredInfo { requested_attr: indy_referent, ..cred_info.clone() });
+                    }
+                }
             }
         }
     }
     Ok(rtn)
 }
 
+// libindy reports the interval that applies to each retrieved credential, but credentials
+// selected by the caller may omit it so fall back to the intervals in the proof request.
+fn _get_revocation_interval(requested_attr: &str,
+                            is_predicate: bool,
+                            value: &Value,
+                            proof_req: &ProofRequestData) -> VcxResult<Option<NonRevokedInterval>> {
+    if let Some(interval) = value.get("interval") {
+        if !interval.is_null() {
+            let interval: NonRevokedInterval = serde_json::from_value(interval.clone())
+                .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+            return Ok(Some(interval));
+        }
+    }
+
+    let attr_interval = match is_predicate {
+        true => proof_req.requested_predicates.get(requested_attr)
+            .and_then(|predicate| predicate.non_revoked.clone()),
+        false => proof_req.requested_attributes.get(requested_attr)
+            .and_then(|attr| attr.non_revoked.clone()),
+    };
+
+    Ok(attr_interval.or(proof_req.non_revoked.clone()))
+}
+
+// libindy only applies plain restrictions, the credentials not meeting restriction groups or
+// attribute values are dropped here. Referents with names are asked for with a referent per
+// name, only the credentials found for all of them are returned for the referent.
+fn filter_credentials(credentials: &str, proof_req: &ProofRequestData) -> VcxResult<String> {
+    let mut credentials: Value = serde_json::from_str(credentials)
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    for (referent, attr) in proof_req.requested_attributes.iter().filter(|&(_, attr)| attr.names.is_some()) {
+        if let Some(attrs) = credentials.get_mut("attrs").and_then(|attrs| attrs.as_object_mut()) {
+            let found: Vec<Vec<Value>> = proof_req.indy_referents(referent).iter()
+                .map(|indy_referent| attrs.remove(indy_referent)
+                    .and_then(|candidates| candidates.as_array().cloned())
+                    .unwrap_or_default())
+                .collect();
+
+            let in_all = |candidate: &Value| found.iter().all(|candidates| {
+                candidates.iter().any(|other| other["cred_info"]["referent"] == candidate["cred_info"]["referent"])
+            });
+            let candidates: Vec<Value> = found.first().map(|first| first.iter().filter(|c| in_all(*c)).cloned().collect())
+                .unwrap_or_default();
+            debug!("{} credentials hold all of {:?}", candidates.len(), attr.names);
+
+            attrs.insert(referent.to_string(), Value::Array(candidates));
+        }
+    }
+
+    for (referent, attr) in proof_req.requested_attributes.iter() {
+        if let Some(candidates) = credentials.get_mut("attrs").and_then(|attrs| attrs.get_mut(referent)) {
+            _retain_meeting_restrictions(candidates, &attr.restrictions);
+        }
+    }
+    for (referent, predicate) in proof_req.requested_predicates.iter() {
+        if let Some(candidates) = credentials.get_mut("predicates").and_then(|predicates| predicates.get_mut(referent)) {
+            _retain_meeting_restrictions(candidates, &predicate.restrictions);
+        }
+    }
+
+    Ok(credentials.to_string())
+}
+
+// The verifier compares the encoded values (see proof_compliance), "007" meets a restriction on "7"
+fn _retain_meeting_restrictions(candidates: &mut Value, restrictions: &Option<Vec<Restriction>>) {
+    if let Value::Array(ref mut candidates) = *candidates {
+        candidates.retain(|candidate| {
+            let cred_info = &candidate["cred_info"];
+            let has_value = |name: &str, value: &str| {
+                let expected = match encode(value) {
+                    Ok(x) => x,
+                    Err(_) => return false,
+                };
+                match cred_info["attrs"].as_object() {
+                    Some(attrs) => attrs.iter().any(|(attr, raw)| {
+                        attr_common_view(attr) == attr_common_view(name)
+                            && raw.as_str().map_or(false, |raw| encode(raw).ok().as_ref() == Some(&expected))
+                    }),
+                    None => false,
+                }
+            };
+
+            check_restrictions(restrictions,
+                               cred_info["schema_id"].as_str().unwrap_or_default(),
+                               cred_info["cred_def_id"].as_str().unwrap_or_default(),
+                               &has_value)
+        });
+    }
+}
+
+// The tailsLocation of a revocation registry is where the issuer keeps the tails file, holders get
+// the file from the issuer and keep it in tails_dir named by its tailsHash, as libindy reads it.
+fn _get_tails_dir(rev_reg_def_json: &str) -> VcxResult<String> {
+    let rev_reg_def: Value = serde_json::from_str(rev_reg_def_json)
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+    let tails_hash = rev_reg_def["value"]["tailsHash"].as_str()
+        .ok_or(VcxError::from(error::INVALID_REV_STATE.code_num))?;
+
+    let tails_dir = settings::get_config_value(settings::CONFIG_TAILS_DIR)
+        .map_err(|_| {
+            warn!("tails_dir is not configured, revocable credentials can not be proven");
+            VcxError::from(error::INVALID_CONFIGURATION.code_num)
+        })?;
+
+    if !Path::new(&tails_dir).join(tails_hash).is_file() {
+        warn!("tails file {} is not in {}", tails_hash, tails_dir);
+        return Err(VcxError::from(error::INVALID_REV_STATE.code_num));
+    }
+
+    Ok(tails_dir)
+}
+
 impl DisclosedProof {
 
     fn set_proof_request(&mut self, req: ProofRequestMessage) {self.proof_request = Some(req)}
@@ -111,110 +253,163 @@ impl DisclosedProof {
     fn get_state(&self) -> u32 {self.state as u32}
     fn set_state(&mut self, state: VcxStateType) {self.state = state}
 
-    fn retrieve_credentials(&self) -> Result<String, ProofError> {
+    fn retrieve_credentials(&self) -> VcxResult<String> {
         if settings::test_indy_mode_enabled() {return Ok(CREDS_FROM_PROOF_REQ.to_string())}
 
-        let proof_req = self.proof_request.as_ref().ok_or(ProofError::ProofNotReadyError())?;
-        let indy_proof_req = serde_json::to_string(&proof_req.proof_request_data)
-            .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?;
+        let proof_req = self.proof_request.as_ref().ok_or(VcxError::from_code(error::NOT_READY.code_num))?;
+        let indy_proof_req = proof_req.proof_request_data.to_indy_json()?;
+
+        let credentials = anoncreds::libindy_prover_get_credentials_for_proof_req(&indy_proof_req)?;
+
+        filter_credentials(&credentials, &proof_req.proof_request_data)
+    }
+
+    fn select_credentials(&self, options: &str) -> VcxResult<String> {
+        let options = SelectionOptions::from_str(options)?;
+        let credentials = self.retrieve_credentials()?;
 
-        anoncreds::libindy_prover_get_credentials_for_proof_req(&indy_proof_req)
-            .map_err(|err| ProofError::CommonError(err))
+        let selection = credential_selection::select_credentials(&credentials, &options)?;
+        if !selection.unsatisfied.is_empty() {
+            info!("no credential in wallet for {:?} of proof {}", selection.unsatisfied, self.source_id);
+        }
+
+        serde_json::to_string(&selection)
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))
     }
 
-    fn _find_schemas(&self, credentials_identifiers: &Vec<(String, String, String, String)>) -> Result<String, ProofError> {
+    fn _find_schemas(&self, credentials_identifiers: &Vec<CredInfo>) -> VcxResult<String> {
         if credentials_identifiers.len() == 0 { return Ok("{}".to_string()); }
 
         let mut rtn: HashMap<String, Value> = HashMap::new();
 
-        for &(ref attr_id, ref cred_uuid, ref schema_id, ref cred_def_id) in credentials_identifiers {
-            if !rtn.contains_key(schema_id) {
-                let schema = LedgerSchema::new_from_ledger(schema_id).or( Err(ProofError::InvalidSchema()))?;
-                let schema_json = serde_json::from_str(&schema.schema_json).or(Err(ProofError::InvalidSchema()))?;
-                rtn.insert(schema_id.to_owned(), schema_json);
+        for cred_info in credentials_identifiers {
+            if !rtn.contains_key(&cred_info.schema_id) {
+                let schema = LedgerSchema::new_from_ledger(&cred_info.schema_id).or( Err(VcxError::from_code(error::INVALID_SCHEMA.code_num)))?;
+                let schema_json = serde_json::from_str(&schema.schema_json).or(Err(VcxError::from_code(error::INVALID_SCHEMA.code_num)))?;
+                rtn.insert(cred_info.schema_id.to_owned(), schema_json);
             }
         }
 
         match rtn.is_empty() {
             false => Ok(serde_json::to_string(&rtn)
-                .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?),
-            true => Err(ProofError::CommonError(error::INVALID_JSON.code_num))
+                .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?),
+            true => Err(VcxError::from(error::INVALID_JSON.code_num))
         }
     }
 
-    fn _find_credential_def(&self, credentials_identifiers: &Vec<(String, String, String, String)>) -> Result<String, ProofError> {
+    fn _find_credential_def(&self, credentials_identifiers: &Vec<CredInfo>) -> VcxResult<String> {
         if credentials_identifiers.len() == 0 { return Ok("{}".to_string()); }
 
         let mut rtn: HashMap<String, Value> = HashMap::new();
 
-        for &(ref attr_id, ref cred_uuid, ref schema_id, ref cred_def_id) in credentials_identifiers {
-            if !rtn.contains_key(cred_def_id) {
-                let (_, credential_def) = retrieve_credential_def(cred_def_id)
-                    .or(Err(ProofError::InvalidCredData()))?;
+        for cred_info in credentials_identifiers {
+            if !rtn.contains_key(&cred_info.cred_def_id) {
+                let (_, credential_def) = retrieve_credential_def(&cred_info.cred_def_id)
+                    .or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))?;
                 let credential_def = serde_json::from_str(&credential_def)
-                    .or(Err(ProofError::InvalidCredData()))?;
-                rtn.insert(cred_def_id.to_owned(), credential_def);
+                    .or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))?;
+                rtn.insert(cred_info.cred_def_id.to_owned(), credential_def);
             }
         }
 
         match rtn.is_empty() {
             false => Ok(serde_json::to_string(&rtn)
-                .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?),
-            true => Err(ProofError::CommonError(error::INVALID_JSON.code_num))
+                .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?),
+            true => Err(VcxError::from(error::INVALID_JSON.code_num))
+        }
+
+    }
+
+    // Builds {rev_reg_id: {timestamp: rev_state}} for every revocable credential the proof
+    // request asks a non-revocation interval for, and records the timestamp used on the CredInfo.
+    fn _build_rev_states_json(&self, credentials_identifiers: &mut Vec<CredInfo>) -> VcxResult<String> {
+        let mut rtn: Value = json!({});
+
+        for cred_info in credentials_identifiers.iter_mut() {
+            let (rev_reg_id, cred_rev_id, interval) = match (&cred_info.rev_reg_id, &cred_info.cred_rev_id, &cred_info.revocation_interval) {
+                (&Some(ref rev_reg_id), &Some(ref cred_rev_id), &Some(ref interval)) => (rev_reg_id.clone(), cred_rev_id.clone(), interval.clone()),
+                _ => continue,
+            };
+
+            let (_, rev_reg_def_json) = anoncreds::get_rev_reg_def_json(&rev_reg_id)?;
+
+            // the witness is computed from the whole registry history, so the delta always starts at creation
+            let (_, rev_reg_delta_json, timestamp) = anoncreds::get_rev_reg_delta_json(&rev_reg_id, None, interval.to)?;
+
+            if rtn[&rev_reg_id][timestamp.to_string()].is_null() {
+                let tails_dir = _get_tails_dir(&rev_reg_def_json)?;
+                let rev_state_json = anoncreds::libindy_create_revocation_state(&tails_dir,
+                                                                               &rev_reg_def_json,
+                                                                               &rev_reg_delta_json,
+                                                                               timestamp,
+                                                                               &cred_rev_id)
+                    .map_err(|ec| {
+                        warn!("could not create revocation state for {}: {}", rev_reg_id, ec);
+                        VcxError::from(error::INVALID_REV_STATE.code_num)
+                    })?;
+                let rev_state: Value = serde_json::from_str(&rev_state_json)
+                    .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+                rtn[&rev_reg_id][timestamp.to_string()] = rev_state;
+            }
+
+            cred_info.timestamp = Some(timestamp);
         }
 
+        Ok(rtn.to_string())
     }
 
     fn _build_requested_credentials(&self,
-                                    credentials_identifiers: &Vec<(String, String, String, String)>,
-                                    self_attested_attrs: &str) -> Result<String, ProofError> {
+                                    credentials_identifiers: &Vec<CredInfo>,
+                                    self_attested_attrs: &str) -> VcxResult<String> {
         let mut rtn: Value = json!({
               "self_attested_attributes":{},
               "requested_attributes":{},
               "requested_predicates":{}
         });
-        //Todo: need to do same for predicates and self_attested
         //Todo: need to handle if the attribute is not revealed
-        if let Value::Object(ref mut map) = rtn["requested_attributes"] {
-            for &(ref attr_id, ref cred_uuid, ref schema_id, ref cred_def_id) in credentials_identifiers {
-
-                let insert_val = json!({"cred_id": cred_uuid, "revealed": true});
-                map.insert(attr_id.to_owned(), insert_val);
+        for cred_info in credentials_identifiers {
+            let (section, mut insert_val) = match cred_info.is_predicate {
+                true => ("requested_predicates", json!({"cred_id": cred_info.referent})),
+                false => ("requested_attributes", json!({"cred_id": cred_info.referent, "revealed": true})),
+            };
+            if let Some(timestamp) = cred_info.timestamp {
+                insert_val["timestamp"] = json!(timestamp);
             }
+            rtn[section][&cred_info.requested_attr] = insert_val;
         }
 
         let self_attested_attrs: Value = serde_json::from_str(self_attested_attrs)
-            .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?;
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
         rtn["self_attested_attributes"] = self_attested_attrs;
 
         let rtn = serde_json::to_string(&rtn)
-            .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?;
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
 
         Ok(rtn)
     }
 
-    fn generate_proof(&mut self, credentials: &str, self_attested_attrs: &str) -> Result<u32, ProofError> {
+    fn generate_proof(&mut self, credentials: &str, self_attested_attrs: &str) -> VcxResult<u32> {
         debug!("generating proof {}", self.source_id);
         if settings::test_indy_mode_enabled() {return Ok(error::SUCCESS.code_num)}
 
         let proof_req = self.proof_request.as_ref()
-            .ok_or(ProofError::CreateProofError())?;
-        let proof_req_data_json = serde_json::to_string(&proof_req.proof_request_data)
-            .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?;
+            .ok_or(VcxError::from_code(error::CREATE_PROOF_ERROR.code_num))?;
+        let proof_req_data_json = proof_req.proof_request_data.to_indy_json()?;
 
 
-        let credentials_identifiers = credential_def_identifiers(credentials)?;
+        let mut credentials_identifiers = credential_def_identifiers(credentials, &proof_req.proof_request_data)?;
+        let revoc_states_json = self._build_rev_states_json(&mut credentials_identifiers)?;
         let requested_credentials = self._build_requested_credentials(&credentials_identifiers,
                                                                       self_attested_attrs)?;
         let schemas = self._find_schemas(&credentials_identifiers)?;
         let credential_defs_json = self._find_credential_def(&credentials_identifiers)?;
-        let revoc_regs_json = Some("{}");
         let proof = anoncreds::libindy_prover_create_proof(&proof_req_data_json,
                                                            &requested_credentials,
                                                           &self.link_secret_alias,
                                                            &schemas,
                                                           &credential_defs_json,
-                                                          revoc_regs_json).map_err(|ec| ProofError::CommonError(ec))?;
+                                                          Some(&revoc_states_json))?;
         let mut proof_msg = ProofMessage::new();
         proof_msg.libindy_proof = proof;
         self.proof = Some(proof_msg);
@@ -222,15 +417,15 @@ impl DisclosedProof {
         Ok(error::SUCCESS.code_num)
     }
 
-    fn send_proof(&mut self, connection_handle: u32) -> Result<u32, ProofError> {
+    fn send_proof(&mut self, connection_handle: u32) -> VcxResult<u32> {
         debug!("sending proof {} via connection: {}", self.source_id, connection::get_source_id(connection_handle).unwrap_or_default());
         // There feels like there's a much more rusty way to do the below.
-        self.my_did = Some(connection::get_pw_did(connection_handle).or(Err(ProofError::ProofConnectionError()))?);
-        self.my_vk = Some(connection::get_pw_verkey(connection_handle).or(Err(ProofError::ProofConnectionError()))?);
-        self.agent_did = Some(connection::get_agent_did(connection_handle).or(Err(ProofError::ProofConnectionError()))?);
-        self.agent_vk = Some(connection::get_agent_verkey(connection_handle).or(Err(ProofError::ProofConnectionError()))?);
-        self.their_did = Some(connection::get_their_pw_did(connection_handle).or(Err(ProofError::ProofConnectionError()))?);
-        self.their_vk = Some(connection::get_their_pw_verkey(connection_handle).or(Err(ProofError::ProofConnectionError()))?);
+        self.my_did = Some(connection::get_pw_did(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?);
+        self.my_vk = Some(connection::get_pw_verkey(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?);
+        self.agent_did = Some(connection::get_agent_did(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?);
+        self.agent_vk = Some(connection::get_agent_verkey(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?);
+        self.their_did = Some(connection::get_their_pw_did(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?);
+        self.their_vk = Some(connection::get_their_pw_verkey(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?);
 
 
         debug!("verifier_did: {:?} -- verifier_vk: {:?} -- agent_did: {:?} -- agent_vk: {:?} -- remote_vk: {:?}",
@@ -242,30 +437,32 @@ impl DisclosedProof {
 
         let e_code: u32 = error::INVALID_CONNECTION_HANDLE.code_num;
 
-        let local_their_did = self.their_did.as_ref().ok_or(ProofError::ProofConnectionError())?;
-        let local_their_vk = self.their_vk.as_ref().ok_or(ProofError::ProofConnectionError())?;
-        let local_agent_did = self.agent_did.as_ref().ok_or(ProofError::ProofConnectionError())?;
-        let local_agent_vk = self.agent_vk.as_ref().ok_or(ProofError::ProofConnectionError())?;
-        let local_my_did = self.my_did.as_ref().ok_or(ProofError::ProofConnectionError())?;
-        let local_my_vk = self.my_vk.as_ref().ok_or(ProofError::ProofConnectionError())?;
+        let local_their_did = self.their_did.as_ref().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let local_their_vk = self.their_vk.as_ref().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let local_agent_did = self.agent_did.as_ref().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let local_agent_vk = self.agent_vk.as_ref().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let local_my_did = self.my_did.as_ref().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let local_my_vk = self.my_vk.as_ref().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
 
-        let proof_req = self.proof_request.as_ref().ok_or(ProofError::CreateProofError())?;
-        let ref_msg_uid = proof_req.msg_ref_id.as_ref().ok_or(ProofError::CreateProofError())?;
+        let proof_req = self.proof_request.as_ref().ok_or(VcxError::from_code(error::CREATE_PROOF_ERROR.code_num))?;
+        let ref_msg_uid = proof_req.msg_ref_id.as_ref().ok_or(VcxError::from_code(error::CREATE_PROOF_ERROR.code_num))?;
 
         let proof = match settings::test_indy_mode_enabled() {
             false => {
-                let proof: &ProofMessage = self.proof.as_ref().ok_or(ProofError::CreateProofError())?;
-                serde_json::to_string(&proof).or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?
+                let proof: &ProofMessage = self.proof.as_ref().ok_or(VcxError::from_code(error::CREATE_PROOF_ERROR.code_num))?;
+                serde_json::to_string(&proof).or(Err(VcxError::from(error::INVALID_JSON.code_num)))?
             },
             true => DEFAULT_GENERATED_PROOF.to_string(),
         };
 
-        let data: Vec<u8> = connection::generate_encrypted_payload(local_my_vk, local_their_vk, &proof, "PROOF")
-            .or(Err(ProofError::ProofConnectionError()))?;
+        let data: Vec<u8> = connection::generate_payload_for_connection(connection_handle, local_my_vk, local_their_vk, &proof, "PROOF")
+            .or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?;
+        let service = connection::get_didcomm_service(connection_handle).or(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)))?;
 
         match messages::send_message().to(local_my_did)
             .to_vk(local_my_vk)
             .msg_type("proof")
+            .didcomm_service(service)
             .agent_did(local_agent_did)
             .agent_vk(local_agent_vk)
             .edge_agent_payload(&data)
@@ -277,7 +474,7 @@ impl DisclosedProof {
             },
             Err(x) => {
                 warn!("could not send proof: {}", x);
-                return Err(ProofError::CommonError(x));
+                return Err(x);
             }
         }
     }
@@ -285,79 +482,101 @@ impl DisclosedProof {
     fn set_source_id(&mut self, id: &str) { self.source_id = id.to_string(); }
     fn get_source_id(&self) -> &String { &self.source_id }
     fn to_string(&self) -> String {
-        json!({
-            "version": DEFAULT_SERIALIZE_VERSION,
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
-    fn from_str(s: &str) -> Result<DisclosedProof, ProofError> {
-        let s:Value = serde_json::from_str(&s)
-            .or(Err(ProofError::InvalidJson()))?;
-        let proof: DisclosedProof= serde_json::from_value(s["data"].clone())
-            .or(Err(ProofError::InvalidJson()))?;
-        Ok(proof)
+    fn from_str(s: &str) -> VcxResult<DisclosedProof> {
+        serialization::deserialize(s).context(error::INVALID_JSON.code_num, "cannot deserialize disclosed proof")
     }
 }
 
 //********************************************
 //         HANDLE FUNCTIONS
 //********************************************
-fn handle_err(code_num: u32) -> u32 {
-    if code_num == error::INVALID_OBJ_HANDLE.code_num {
-        error::INVALID_DISCLOSED_PROOF_HANDLE.code_num
+fn handle_err(err: VcxError) -> VcxError {
+    if err.code() == error::INVALID_OBJ_HANDLE.code_num {
+        VcxError::from_code(error::INVALID_DISCLOSED_PROOF_HANDLE.code_num)
     }
     else {
-        code_num
+        err
     }
 }
 
-pub fn create_proof(source_id: &str, proof_req: &str) -> Result<u32, ProofError> {
+pub fn create_proof(source_id: &str, proof_req: &str) -> VcxResult<u32> {
     debug!("creating disclosed proof with id: {}", source_id);
 
     let mut new_proof: DisclosedProof = Default::default();
 
     new_proof.set_source_id(source_id);
     new_proof.set_proof_request(serde_json::from_str(proof_req)
-        .map_err(|_| ProofError::CommonError(error::INVALID_JSON.code_num))?);
+        .map_err(|_| VcxError::from(error::INVALID_JSON.code_num))?);
 
     new_proof.set_state(VcxStateType::VcxStateRequestReceived);
 
-    Ok(HANDLE_MAP.add(new_proof).map_err(|ec| ProofError::CommonError(ec))?)
+    Ok(HANDLE_MAP.add(new_proof)?)
 }
 
-pub fn get_state(handle: u32) -> Result<u32, u32> {
+pub fn get_state(handle: u32) -> VcxResult<u32> {
     HANDLE_MAP.get(handle, |obj| {
         Ok(obj.get_state())
     }).map_err(handle_err)
 }
 
+/// Creates a disclosed proof for every pending proof request of the connection among messages the
+/// dispatcher downloaded, unless one was created for it already. The request uid is the source id.
+pub fn create_from_messages(connection_handle: u32, messages: &MessagesByDid) -> VcxResult<Vec<u32>> {
+    let my_did = connection::get_pw_did(connection_handle)?;
+    let my_vk = connection::get_pw_verkey(connection_handle)?;
+
+    let received = match messages.get(&my_did) {
+        Some(received) => received,
+        None => return Ok(Vec::new()),
+    };
+
+    let known: HashSet<String> = HANDLE_MAP.handles().into_iter()
+        .filter_map(|handle| HANDLE_MAP.get(handle, |obj| {
+            Ok(obj.proof_request.as_ref().and_then(|request| request.msg_ref_id.clone()))
+        }).ok().and_then(|uid| uid))
+        .collect();
+
+    let mut handles = Vec::new();
+    for msg in received.iter() {
+        if msg.sender_did.eq(&my_did) || !msg.msg_type.eq("proofReq") { continue; }
+        if msg.status_code != MessagePending.as_string() || known.contains(&msg.uid) { continue; }
+
+        let request = _parse_proof_request(&my_vk, msg)?;
+        let request = serde_json::to_string(&request).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+        let handle = create_proof(&msg.uid, &request)?;
+        debug!("dispatched proof request {} to disclosed proof {}", msg.uid, handle);
+        handles.push(handle);
+    }
+
+    Ok(handles)
+}
+
 // update_state is just the same as get_state for disclosed_proof
-pub fn update_state(handle: u32) -> Result<u32, u32> {
+pub fn update_state(handle: u32) -> VcxResult<u32> {
     HANDLE_MAP.get(handle, |obj|{
         Ok(obj.get_state())
     })
 }
 
-pub fn to_string(handle: u32) -> Result<String, u32> {
+pub fn to_string(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj|{
         Ok(DisclosedProof::to_string(&obj))
     })
 }
 
-pub fn from_string(proof_data: &str) -> Result<u32, ProofError> {
-    let derived_proof: DisclosedProof = match DisclosedProof::from_str(proof_data) {
-        Ok(x) => x,
-        Err(y) => return Err(ProofError::CommonError(error::INVALID_JSON.code_num)),
-    };
+pub fn from_string(proof_data: &str) -> VcxResult<u32> {
+    let derived_proof: DisclosedProof = DisclosedProof::from_str(proof_data)?;
 
-    let new_handle = HANDLE_MAP.add(derived_proof).map_err(|ec| ProofError::CommonError(ec))?;
+    let new_handle = HANDLE_MAP.add(derived_proof)?;
 
     info!("inserting handle {} into proof table", new_handle);
 
     Ok(new_handle)
 }
 
-pub fn release(handle: u32) -> Result<(), u32> {
+pub fn release(handle: u32) -> VcxResult<()> {
     HANDLE_MAP.release(handle).map_err(handle_err)
 }
 
@@ -368,34 +587,50 @@ pub fn release_all() {
     };
 }
 
-pub fn send_proof(handle: u32, connection_handle: u32) -> Result<u32, ProofError> {
+/// Reloads the stored objects, see object_store
+pub fn restore_all() -> VcxResult<usize> {
+    HANDLE_MAP.restore(|data| DisclosedProof::from_str(data).ok())
+}
+
+pub fn send_proof(handle: u32, connection_handle: u32) -> VcxResult<u32> {
     HANDLE_MAP.get_mut(handle, |obj|{
-        obj.send_proof(connection_handle).map_err(|e| e.to_error_code())
-    }).map_err(|ec| ProofError::CommonError(ec))
+        obj.send_proof(connection_handle)
+    })
 }
 
-pub fn generate_proof(handle: u32, credentials: String, self_attested_attrs: String) -> Result<u32, ProofError> {
+pub fn generate_proof(handle: u32, credentials: String, self_attested_attrs: String) -> VcxResult<u32> {
     HANDLE_MAP.get_mut(handle, |obj|{
-        obj.generate_proof(&credentials, &self_attested_attrs).map_err(|e| e.to_error_code())
-    }).map_err(|ec| ProofError::CommonError(ec))
+        obj.generate_proof(&credentials, &self_attested_attrs)
+    })
 }
 
-pub fn retrieve_credentials(handle: u32) -> Result<String, ProofError> {
+pub fn retrieve_credentials(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get_mut(handle, |obj|{
-        obj.retrieve_credentials().map_err(|e| e.to_error_code())
-    }).map_err(|ec| ProofError::CommonError(ec))
+        obj.retrieve_credentials()
+    })
+}
+
+pub fn select_credentials(handle: u32, options: String) -> VcxResult<String> {
+    HANDLE_MAP.get(handle, |obj|{
+        obj.select_credentials(&options)
+    })
 }
 
 pub fn is_valid_handle(handle: u32) -> bool {
     HANDLE_MAP.has_handle(handle)
 }
 
+/// The context the disclosed proof was created in, calls on the disclosed proof run in it.
+pub fn get_context(handle: u32) -> u32 {
+    HANDLE_MAP.context_of(handle)
+}
+
 //TODO one function with credential
-pub fn get_proof_request(connection_handle: u32, msg_id: &str) -> Result<String, ProofError> {
-    let my_did = connection::get_pw_did(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
-    let my_vk = connection::get_pw_verkey(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
-    let agent_did = connection::get_agent_did(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
-    let agent_vk = connection::get_agent_verkey(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
+pub fn get_proof_request(connection_handle: u32, msg_id: &str) -> VcxResult<String> {
+    let my_did = connection::get_pw_did(connection_handle)?;
+    let my_vk = connection::get_pw_verkey(connection_handle)?;
+    let agent_did = connection::get_agent_did(connection_handle)?;
+    let agent_vk = connection::get_agent_verkey(connection_handle)?;
 
     if settings::test_agency_mode_enabled() { httpclient::set_next_u8_response(::utils::constants::NEW_PROOF_REQUEST_RESPONSE.to_vec()); }
 
@@ -403,34 +638,22 @@ pub fn get_proof_request(connection_handle: u32, msg_id: &str) -> Result<String,
                                                                  &my_vk,
                                                                  &agent_did,
                                                                  &agent_vk,
-                                                                 Some(vec![msg_id.to_string()])).map_err(|ec| ProofError::CommonError(ec))?;
+                                                                 Some(vec![msg_id.to_string()]))?;
 
     if message[0].msg_type.eq("proofReq") {
-        let (_, msg_data) = match message[0].payload {
-            Some(ref data) => {
-                let data = to_u8(data);
-                crypto::parse_msg(&my_vk, data.as_slice()).map_err(|ec| ProofError::CommonError(ec))?
-            },
-            None => return Err(ProofError::CommonError(error::INVALID_HTTP_RESPONSE.code_num))
-        };
-
-        let request = extract_json_payload(&msg_data).map_err(|ec| ProofError::CommonError(ec))?;
-        let mut request: ProofRequestMessage = serde_json::from_str(&request)
-           .or(Err(ProofError::CommonError(error::INVALID_HTTP_RESPONSE.code_num)))?;
-
-        request.msg_ref_id = Some(message[0].uid.to_owned());
-        Ok(serde_json::to_string_pretty(&request).or(Err(ProofError::InvalidJson()))?)
+        let request = _parse_proof_request(&my_vk, &message[0])?;
+        Ok(serde_json::to_string_pretty(&request).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?)
     } else {
-        Err(ProofError::CommonError(error::INVALID_MESSAGES.code_num))
+        Err(VcxError::from(error::INVALID_MESSAGES.code_num))
     }
 }
 
 //TODO one function with credential
-pub fn get_proof_request_messages(connection_handle: u32, match_name: Option<&str>) -> Result<String, ProofError> {
-    let my_did = connection::get_pw_did(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
-    let my_vk = connection::get_pw_verkey(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
-    let agent_did = connection::get_agent_did(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
-    let agent_vk = connection::get_agent_verkey(connection_handle).map_err(|e| ProofError::CommonError(e.to_error_code()))?;
+pub fn get_proof_request_messages(connection_handle: u32, match_name: Option<&str>) -> VcxResult<String> {
+    let my_did = connection::get_pw_did(connection_handle)?;
+    let my_vk = connection::get_pw_verkey(connection_handle)?;
+    let agent_did = connection::get_agent_did(connection_handle)?;
+    let agent_vk = connection::get_agent_verkey(connection_handle)?;
 
     if settings::test_agency_mode_enabled() { httpclient::set_next_u8_response(::utils::constants::NEW_PROOF_REQUEST_RESPONSE.to_vec()); }
 
@@ -438,7 +661,7 @@ pub fn get_proof_request_messages(connection_handle: u32, match_name: Option<&st
                                                                  &my_vk,
                                                                  &agent_did,
                                                                  &agent_vk,
-                                                                 None).map_err(|ec| ProofError::CommonError(ec))?;
+                                                                 None)?;
 
     let mut messages: Vec<ProofRequestMessage> = Default::default();
 
@@ -446,29 +669,32 @@ pub fn get_proof_request_messages(connection_handle: u32, match_name: Option<&st
         if msg.sender_did.eq(&my_did){ continue; }
 
         if msg.msg_type.eq("proofReq") {
-            let (_, msg_data) = match msg.payload {
-                Some(ref data) => {
-                    let data = to_u8(data);
-                    crypto::parse_msg(&my_vk, data.as_slice())
-                        .map_err(|ec| ProofError::CommonError(ec))?
-                },
-                None => return Err(ProofError::CommonError(error::INVALID_HTTP_RESPONSE.code_num))
-            };
+            messages.push(_parse_proof_request(&my_vk, &msg)?);
+        }
+    }
+
+    Ok(serde_json::to_string_pretty(&messages).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?)
+}
 
-            let req = extract_json_payload(&msg_data).map_err(|ec| ProofError::CommonError(ec))?;
+fn _parse_proof_request(my_vk: &str, msg: &Message) -> VcxResult<ProofRequestMessage> {
+    let (_, msg_data) = match msg.payload {
+        Some(ref data) => {
+            let data = to_u8(data);
+            messages::decrypt_payload(my_vk, data.as_slice())?
+        },
+        None => return Err(VcxError::from(error::INVALID_HTTP_RESPONSE.code_num))
+    };
 
-            let mut req: ProofRequestMessage = serde_json::from_str(&req)
-                .or(Err(ProofError::CommonError(error::INVALID_HTTP_RESPONSE.code_num)))?;
+    let req = extract_json_payload(&msg_data)?;
 
-            req.msg_ref_id = Some(msg.uid.to_owned());
-            messages.push(req);
-        }
-    }
+    let mut req: ProofRequestMessage = serde_json::from_str(&req)
+        .or(Err(VcxError::from(error::INVALID_HTTP_RESPONSE.code_num)))?;
 
-    Ok(serde_json::to_string_pretty(&messages).or(Err(ProofError::InvalidJson()))?)
+    req.msg_ref_id = Some(msg.uid.to_owned());
+    Ok(req)
 }
 
-pub fn get_source_id(handle: u32) -> Result<String, u32> {
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     HANDLE_MAP.get(handle, |obj| {
         Ok(obj.get_source_id().clone())
     }).map_err(handle_err)
@@ -481,6 +707,21 @@ mod tests {
     use super::*;
     use utils::constants::{ ADDRESS_CRED_ID, LICENCE_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID, CRED_DEF_ID, SCHEMA_ID };
     use serde_json::Value;
+    use messages::proof_request;
+
+    fn create_cred_info(requested_attr: &str, referent: &str, schema_id: &str, cred_def_id: &str) -> CredInfo {
+        CredInfo {
+            requested_attr: requested_attr.to_string(),
+            referent: referent.to_string(),
+            schema_id: schema_id.to_string(),
+            cred_def_id: cred_def_id.to_string(),
+            rev_reg_id: None,
+            cred_rev_id: None,
+            revocation_interval: None,
+            timestamp: None,
+            is_predicate: false,
+        }
+    }
 
     #[test]
     fn test_create_proof() {
@@ -492,7 +733,7 @@ mod tests {
     fn test_create_fails() {
         init!("true");
         assert_eq!(create_proof("1","{}").err(),
-                   Some(ProofError::CommonError(error::INVALID_JSON.code_num)));
+                   Some(VcxError::from(error::INVALID_JSON.code_num)));
     }
 
     #[test]
@@ -533,14 +774,14 @@ mod tests {
     #[test]
     fn test_deserialize_fails() {
         assert_eq!(from_string("{}").err(),
-        Some(ProofError::CommonError(error::INVALID_JSON.code_num)));
+        Some(VcxError::from(error::INVALID_JSON.code_num)));
     }
 
     #[test]
     fn test_find_schemas() {
         init!("true");
-        let cred1 = ("height_1".to_string(), LICENCE_CRED_ID.to_string(), SCHEMA_ID.to_string(), CRED_DEF_ID.to_string() );
-        let cred2 = ("zip_2".to_string(), ADDRESS_CRED_ID.to_string(), ADDRESS_SCHEMA_ID.to_string(), ADDRESS_CRED_DEF_ID.to_string() );
+        let cred1 = create_cred_info("height_1", LICENCE_CRED_ID, SCHEMA_ID, CRED_DEF_ID);
+        let cred2 = create_cred_info("zip_2", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
         let creds = vec![cred1, cred2];
 
         let proof: DisclosedProof = Default::default();
@@ -554,17 +795,17 @@ mod tests {
         init!("false");
 
         let mut credential_ids = Vec::new();
-        credential_ids.push(("1".to_string(), "2".to_string(), "3".to_string(), "4".to_string()));
+        credential_ids.push(create_cred_info("1", "2", "3", "4"));
         let proof: DisclosedProof = Default::default();
         assert_eq!(proof._find_schemas(&credential_ids).err(),
-                   Some(ProofError::InvalidSchema()));
+                   Some(VcxError::from_code(error::INVALID_SCHEMA.code_num)));
     }
 
     #[test]
     fn test_find_credential_def() {
         init!("true");
-        let cred1 = ("height_1".to_string(), LICENCE_CRED_ID.to_string(), SCHEMA_ID.to_string(), CRED_DEF_ID.to_string() );
-        let cred2 = ("zip_2".to_string(), ADDRESS_CRED_ID.to_string(), ADDRESS_SCHEMA_ID.to_string(), ADDRESS_CRED_DEF_ID.to_string() );
+        let cred1 = create_cred_info("height_1", LICENCE_CRED_ID, SCHEMA_ID, CRED_DEF_ID);
+        let cred2 = create_cred_info("zip_2", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
         let creds = vec![cred1, cred2];
 
         let proof: DisclosedProof = Default::default();
@@ -578,18 +819,20 @@ mod tests {
         init!("false");
 
         let mut credential_ids = Vec::new();
-        credential_ids.push(("1".to_string(), "2".to_string(), "3".to_string(), "4".to_string()));
+        credential_ids.push(create_cred_info("1", "2", "3", "4"));
         let proof: DisclosedProof = Default::default();
         assert_eq!(proof._find_credential_def(&credential_ids).err(),
-                   Some(ProofError::InvalidCredData()));
+                   Some(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)));
     }
 
     #[test]
     fn test_build_requested_credentials() {
         init!("true");
-        let cred1 = ("height_1".to_string(), LICENCE_CRED_ID.to_string(), SCHEMA_ID.to_string(), CRED_DEF_ID.to_string() );
-        let cred2 = ("zip_2".to_string(), ADDRESS_CRED_ID.to_string(), ADDRESS_SCHEMA_ID.to_string(), ADDRESS_CRED_DEF_ID.to_string() );
-        let creds = vec![cred1, cred2];
+        let cred1 = create_cred_info("height_1", LICENCE_CRED_ID, SCHEMA_ID, CRED_DEF_ID);
+        let cred2 = create_cred_info("zip_2", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
+        let mut cred3 = create_cred_info("zip_3", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
+        cred3.is_predicate = true;
+        let creds = vec![cred1, cred2, cred3];
         let self_attested_attrs = json!({
             "self_attested_attr_3": "my self attested 1",
             "self_attested_attr_4": "my self attested 2",
@@ -604,7 +847,9 @@ mod tests {
                   "height_1": {"cred_id": LICENCE_CRED_ID, "revealed": true },
                   "zip_2": {"cred_id": ADDRESS_CRED_ID, "revealed": true },
               },
-              "requested_predicates":{}
+              "requested_predicates":{
+                  "zip_3": {"cred_id": ADDRESS_CRED_ID },
+              }
         });
 
         let proof: DisclosedProof = Default::default();
@@ -682,18 +927,110 @@ mod tests {
         assert!(retrieved_creds3.contains(r#""zip":"84000""#));
     }
 
+    #[test]
+    fn test_filter_credentials() {
+        let mut req: ProofRequestData = serde_json::from_value(json!({
+           "nonce":"123432421212",
+           "name":"proof_req_1",
+           "version":"0.1",
+           "requested_attributes": {
+               "height_1": {
+                   "name":"height",
+                   "restrictions": [{"$and": [{"schema_name": "test-licence"}, {"attr::Name::value": "Bob"}]}]
+               },
+               "zip_2": {
+                   "name":"zip",
+                   "restrictions": [{"$or": [{"issuer_did": "V4SGRU86Z58d6TV7PBUe6f"}, {"attr::zip::value": "87121"}]}]
+               }
+           },
+           "requested_predicates": {},
+        })).unwrap();
+
+        let filtered: Value = serde_json::from_str(&filter_credentials(CREDS_FROM_PROOF_REQ, &req).unwrap()).unwrap();
+        assert_eq!(filtered["attrs"]["height_1"].as_array().unwrap().len(), 1);
+        assert_eq!(filtered["attrs"]["zip_2"].as_array().unwrap().len(), 1);
+
+        // values are compared by their encoding, like the verifier does
+        req.requested_attributes.get_mut("zip_2").unwrap().restrictions = serde_json::from_value(json!([
+            {"attr::zip::value": "087121"}
+        ])).unwrap();
+        let filtered: Value = serde_json::from_str(&filter_credentials(CREDS_FROM_PROOF_REQ, &req).unwrap()).unwrap();
+        assert_eq!(filtered["attrs"]["zip_2"].as_array().unwrap().len(), 1);
+
+        req.requested_attributes.get_mut("height_1").unwrap().restrictions = serde_json::from_value(json!([
+            {"attr::name::value": "Alice"}
+        ])).unwrap();
+        req.requested_attributes.get_mut("zip_2").unwrap().restrictions = serde_json::from_value(json!([
+            {"$and": [{"schema_name": "Home Address"}, {"schema_version": "1.0"}]}
+        ])).unwrap();
+
+        let filtered: Value = serde_json::from_str(&filter_credentials(CREDS_FROM_PROOF_REQ, &req).unwrap()).unwrap();
+        assert_eq!(filtered["attrs"]["height_1"], json!([]));
+        assert_eq!(filtered["attrs"]["zip_2"], json!([]));
+    }
+
+    #[test]
+    fn test_credentials_for_names() {
+        let req: ProofRequestData = serde_json::from_value(json!({
+           "nonce":"123432421212",
+           "name":"proof_req_1",
+           "version":"0.1",
+           "requested_attributes": {
+               "identity": {"names": ["name", "sex"]}
+           },
+           "requested_predicates": {},
+        })).unwrap();
+
+        let cred_info = |referent: &str| json!({"cred_info": {"referent": referent, "attrs": {}, "schema_id": SCHEMA_ID, "cred_def_id": CRED_DEF_ID}, "interval": null});
+        let credentials = json!({
+            "attrs": {
+                "identity::name": [cred_info(LICENCE_CRED_ID), cred_info(ADDRESS_CRED_ID)],
+                "identity::sex": [cred_info(LICENCE_CRED_ID)]
+            },
+            "predicates": {}
+        });
+
+        let filtered: Value = serde_json::from_str(&filter_credentials(&credentials.to_string(), &req).unwrap()).unwrap();
+        assert_eq!(filtered["attrs"], json!({"identity": [cred_info(LICENCE_CRED_ID)]}));
+
+        let selected = json!({"attrs": {"identity": cred_info(LICENCE_CRED_ID)}});
+        let mut identifiers: Vec<String> = credential_def_identifiers(&selected.to_string(), &req).unwrap().into_iter()
+            .map(|cred_info| cred_info.requested_attr)
+            .collect();
+        identifiers.sort();
+        assert_eq!(identifiers, vec!["identity::name", "identity::sex"]);
+    }
+
+    #[test]
+    fn test_select_credentials() {
+        init!("true");
+
+        let mut proof: DisclosedProof = Default::default();
+        proof.proof_request = Some(ProofRequestMessage::create());
+
+        let selection: Value = serde_json::from_str(&proof.select_credentials("{}").unwrap()).unwrap();
+        let retrieved: Value = serde_json::from_str(CREDS_FROM_PROOF_REQ).unwrap();
+        assert_eq!(selection["selected_credentials"]["attrs"]["height_1"], retrieved["attrs"]["height_1"][0]);
+        assert_eq!(selection["selected_credentials"]["attrs"]["zip_2"], retrieved["attrs"]["zip_2"][0]);
+        assert_eq!(selection["unsatisfied"], json!([]));
+
+        let identifiers = credential_def_identifiers(&selection["selected_credentials"].to_string(),
+                                                     &proof.proof_request.unwrap().proof_request_data).unwrap();
+        assert_eq!(identifiers.len(), 2);
+    }
+
     #[test]
     fn test_retrieve_credentials_fails_with_no_proof_req() {
         init!("false");
 
         let proof: DisclosedProof = Default::default();
-        assert_eq!(proof.retrieve_credentials(), Err(ProofError::ProofNotReadyError()));
+        assert_eq!(proof.retrieve_credentials(), Err(VcxError::from_code(error::NOT_READY.code_num)));
     }
 
     #[test]
     fn test_credential_def_identifiers() {
-        let cred1 = ("height_1".to_string(), LICENCE_CRED_ID.to_string(), SCHEMA_ID.to_string(), CRED_DEF_ID.to_string() );
-        let cred2 = ("zip_2".to_string(), ADDRESS_CRED_ID.to_string(), ADDRESS_SCHEMA_ID.to_string(), ADDRESS_CRED_DEF_ID.to_string() );
+        let cred1 = create_cred_info("height_1", LICENCE_CRED_ID, SCHEMA_ID, CRED_DEF_ID);
+        let cred2 = create_cred_info("zip_2", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
         let selected_credentials : Value = json!({
            "attrs":{
               "height_1":{
@@ -734,10 +1071,174 @@ mod tests {
 
            }
         });
-        let creds = credential_def_identifiers(&selected_credentials.to_string()).unwrap();
+        let proof_req = ProofRequestMessage::create().proof_request_data;
+        let creds = credential_def_identifiers(&selected_credentials.to_string(), &proof_req).unwrap();
         assert_eq!(creds, vec![cred1, cred2]);
     }
 
+    #[test]
+    fn test_credential_def_identifiers_with_revocation() {
+        use utils::constants::REV_REG_ID;
+
+        let selected_credentials : Value = json!({
+           "attrs":{
+              "height_1":{
+                "cred_info":{
+                   "referent":LICENCE_CRED_ID,
+                   "attrs":{ "height":"4'11" },
+                   "schema_id": SCHEMA_ID,
+                   "cred_def_id": CRED_DEF_ID,
+                   "rev_reg_id":REV_REG_ID,
+                   "cred_rev_id":"1"
+                },
+                "interval":{"to":1540498474}
+              },
+              "zip_2":{
+                "cred_info":{
+                   "referent":ADDRESS_CRED_ID,
+                   "attrs":{ "zip":"87121" },
+                   "schema_id":ADDRESS_SCHEMA_ID,
+                   "cred_def_id":ADDRESS_CRED_DEF_ID,
+                   "rev_reg_id":REV_REG_ID,
+                   "cred_rev_id":"2"
+                }
+              }
+           },
+           "predicates":{
+              "zip_3":{
+                "cred_info":{
+                   "referent":ADDRESS_CRED_ID,
+                   "attrs":{ "zip":"87121" },
+                   "schema_id":ADDRESS_SCHEMA_ID,
+                   "cred_def_id":ADDRESS_CRED_DEF_ID,
+                   "rev_reg_id":REV_REG_ID,
+                   "cred_rev_id":"2"
+                }
+              }
+           }
+        });
+        let mut proof_req = proof_request().non_revoked(r#"{"from":100,"to":200}"#).clone().proof_request_data;
+        proof_req.requested_predicates.insert("zip_3".to_string(), serde_json::from_value(json!({
+            "name": "zip", "p_type": ">=", "p_value": 84000, "non_revoked": {"to": 300}
+        })).unwrap());
+
+        let creds = credential_def_identifiers(&selected_credentials.to_string(), &proof_req).unwrap();
+        let height = creds.iter().find(|c| c.requested_attr == "height_1").unwrap();
+        assert_eq!(height.rev_reg_id, Some(REV_REG_ID.to_string()));
+        assert_eq!(height.cred_rev_id, Some("1".to_string()));
+        assert_eq!(height.revocation_interval, Some(NonRevokedInterval { from: None, to: Some(1540498474) }));
+        let zip = creds.iter().find(|c| c.requested_attr == "zip_2").unwrap();
+        assert_eq!(zip.revocation_interval, Some(NonRevokedInterval { from: Some(100), to: Some(200) }));
+        // a predicate uses its own interval rather than the one of the request
+        let zip_predicate = creds.iter().find(|c| c.requested_attr == "zip_3").unwrap();
+        assert!(zip_predicate.is_predicate);
+        assert_eq!(zip_predicate.revocation_interval, Some(NonRevokedInterval { from: None, to: Some(300) }));
+    }
+
+    #[test]
+    fn test_credential_def_identifiers_with_predicates() {
+        let selected_credentials : Value = json!({
+           "attrs":{
+              "zip_2":{
+                "cred_info":{
+                   "referent":ADDRESS_CRED_ID,
+                   "attrs":{ "zip":"87121" },
+                   "schema_id":ADDRESS_SCHEMA_ID,
+                   "cred_def_id":ADDRESS_CRED_DEF_ID,
+                   "rev_reg_id":null,
+                   "cred_rev_id":null
+                },
+                "interval":null
+              }
+           },
+           "predicates":{
+              "zip_3":{
+                "cred_info":{
+                   "referent":ADDRESS_CRED_ID,
+                   "attrs":{ "zip":"87121" },
+                   "schema_id":ADDRESS_SCHEMA_ID,
+                   "cred_def_id":ADDRESS_CRED_DEF_ID,
+                   "rev_reg_id":null,
+                   "cred_rev_id":null
+                },
+                "interval":null
+              }
+           }
+        });
+        let proof_req = ProofRequestMessage::create().proof_request_data;
+
+        let creds = credential_def_identifiers(&selected_credentials.to_string(), &proof_req).unwrap();
+        let mut predicate = create_cred_info("zip_3", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
+        predicate.is_predicate = true;
+        assert_eq!(creds, vec![create_cred_info("zip_2", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID), predicate]);
+
+        let proof: DisclosedProof = Default::default();
+        let requested_credentials: Value = serde_json::from_str(&proof._build_requested_credentials(&creds, "{}").unwrap()).unwrap();
+        assert_eq!(requested_credentials["requested_attributes"], json!({"zip_2": {"cred_id": ADDRESS_CRED_ID, "revealed": true}}));
+        assert_eq!(requested_credentials["requested_predicates"], json!({"zip_3": {"cred_id": ADDRESS_CRED_ID}}));
+    }
+
+    fn create_tails_file() {
+        use utils::constants::{ REV_REG_DEF_JSON, TEST_TAILS_FILE };
+
+        let rev_reg_def: Value = serde_json::from_str(REV_REG_DEF_JSON).unwrap();
+        let tails_hash = rev_reg_def["value"]["tailsHash"].as_str().unwrap();
+        ::std::fs::create_dir_all(TEST_TAILS_FILE).unwrap();
+        ::std::fs::File::create(Path::new(TEST_TAILS_FILE).join(tails_hash)).unwrap();
+        settings::set_config_value(settings::CONFIG_TAILS_DIR, TEST_TAILS_FILE);
+    }
+
+    #[test]
+    fn test_get_tails_dir() {
+        use utils::constants::{ REV_REG_DEF_JSON, TEST_TAILS_FILE };
+        init!("true");
+
+        assert_eq!(_get_tails_dir(REV_REG_DEF_JSON).unwrap_err().code(), error::INVALID_CONFIGURATION.code_num);
+
+        settings::set_config_value(settings::CONFIG_TAILS_DIR, "/tmp/no_tails_here");
+        assert_eq!(_get_tails_dir(REV_REG_DEF_JSON).unwrap_err().code(), error::INVALID_REV_STATE.code_num);
+
+        create_tails_file();
+        assert_eq!(_get_tails_dir(REV_REG_DEF_JSON).unwrap(), TEST_TAILS_FILE);
+    }
+
+    #[test]
+    fn test_build_rev_states_json() {
+        use utils::constants::{ REV_REG_ID, REV_STATE_JSON, REV_REG_DELTA_TIMESTAMP };
+        init!("true");
+        create_tails_file();
+
+        let mut cred1 = create_cred_info("height_1", LICENCE_CRED_ID, SCHEMA_ID, CRED_DEF_ID);
+        cred1.rev_reg_id = Some(REV_REG_ID.to_string());
+        cred1.cred_rev_id = Some("1".to_string());
+        cred1.revocation_interval = Some(NonRevokedInterval { from: None, to: Some(REV_REG_DELTA_TIMESTAMP) });
+        let cred2 = create_cred_info("zip_2", ADDRESS_CRED_ID, ADDRESS_SCHEMA_ID, ADDRESS_CRED_DEF_ID);
+        let mut cred3 = create_cred_info("height_3", LICENCE_CRED_ID, SCHEMA_ID, CRED_DEF_ID);
+        cred3.rev_reg_id = Some(REV_REG_ID.to_string());
+        cred3.cred_rev_id = Some("1".to_string());
+        cred3.revocation_interval = Some(NonRevokedInterval { from: None, to: Some(REV_REG_DELTA_TIMESTAMP) });
+        cred3.is_predicate = true;
+        let mut creds = vec![cred1, cred2, cred3];
+
+        let proof: DisclosedProof = Default::default();
+        let rev_states = proof._build_rev_states_json(&mut creds).unwrap();
+        let rev_states: Value = serde_json::from_str(&rev_states).unwrap();
+        let expected: Value = serde_json::from_str(REV_STATE_JSON).unwrap();
+        assert_eq!(rev_states[REV_REG_ID][REV_REG_DELTA_TIMESTAMP.to_string()], expected);
+        assert_eq!(creds[0].timestamp, Some(REV_REG_DELTA_TIMESTAMP));
+        assert_eq!(creds[1].timestamp, None);
+        assert_eq!(creds[2].timestamp, Some(REV_REG_DELTA_TIMESTAMP));
+
+        let requested_credentials = proof._build_requested_credentials(&creds, "{}").unwrap();
+        let requested_credentials: Value = serde_json::from_str(&requested_credentials).unwrap();
+        assert_eq!(requested_credentials["requested_attributes"]["height_1"],
+                   json!({"cred_id": LICENCE_CRED_ID, "revealed": true, "timestamp": REV_REG_DELTA_TIMESTAMP}));
+        assert_eq!(requested_credentials["requested_attributes"]["zip_2"],
+                   json!({"cred_id": ADDRESS_CRED_ID, "revealed": true}));
+        assert_eq!(requested_credentials["requested_predicates"]["height_3"],
+                   json!({"cred_id": LICENCE_CRED_ID, "timestamp": REV_REG_DELTA_TIMESTAMP}));
+    }
+
     #[cfg(feature = "pool_tests")]
     #[test]
     fn test_generate_proof() {
@@ -818,6 +1319,106 @@ mod tests {
         assert!(generated_proof.is_ok());
     }
 
+    #[cfg(feature = "pool_tests")]
+    #[test]
+    fn test_generate_proof_with_predicates() {
+        init!("ledger");
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let (schema_id, _, cred_def_id, _, _, _, _, cred_id) = ::utils::libindy::anoncreds::tests::create_and_store_credential(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+
+        for &(p_type, p_value) in [("GE", 84000), ("GT", 83999), ("LE", 84000), ("LT", 84001)].iter() {
+            let mut proof_req = ProofRequestMessage::create();
+            let indy_proof_req = json!({
+               "nonce":"123432421212",
+               "name":"proof_req_1",
+               "version":"0.1",
+               "requested_attributes": json!({}),
+               "requested_predicates": json!({
+                   "zip_3": json!({
+                       "name":"zip",
+                       "p_type": p_type,
+                       "p_value": p_value,
+                       "restrictions": [json!({ "issuer_did": did })]
+                   }),
+               }),
+            }).to_string();
+            proof_req.proof_request_data = serde_json::from_str(&indy_proof_req).unwrap();
+
+            let selected_credentials : Value = json!({
+               "attrs":{ },
+               "predicates":{
+                  "zip_3":{
+                    "cred_info":{
+                       "referent":cred_id,
+                       "attrs":{ "zip":"84000" },
+                       "schema_id":schema_id,
+                       "cred_def_id":cred_def_id,
+                       "rev_reg_id":null,
+                       "cred_rev_id":null
+                    },
+                    "interval":null
+                  }
+               }
+            });
+
+            let mut proof: DisclosedProof = Default::default();
+            proof.proof_request = Some(proof_req);
+            proof.link_secret_alias = "main".to_string();
+            proof.generate_proof(&selected_credentials.to_string(), "{}").unwrap();
+
+            let libindy_proof: Value = serde_json::from_str(&proof.proof.unwrap().libindy_proof).unwrap();
+            assert!(libindy_proof["requested_proof"]["predicates"]["zip_3"].is_object(), "{} {}", p_type, p_value);
+        }
+    }
+
+    #[cfg(feature = "pool_tests")]
+    #[test]
+    fn test_select_credentials_generates_proof() {
+        init!("ledger");
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let (_, _, _, _, _, _, _, old_cred_id) = ::utils::libindy::anoncreds::tests::create_and_store_credential(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+        ::credential::record_stored_at(&old_cred_id).unwrap();
+        let (_, _, _, _, _, _, _, new_cred_id) = ::utils::libindy::anoncreds::tests::create_and_store_credential(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+        ::credential::record_stored_at(&new_cred_id).unwrap();
+
+        let mut proof_req = ProofRequestMessage::create();
+        let indy_proof_req = json!({
+           "nonce":"123432421212",
+           "name":"proof_req_1",
+           "version":"0.1",
+           "requested_attributes": json!({
+               "address1_1": json!({
+                   "name":"address1",
+                   "restrictions": [json!({ "issuer_did": did })]
+               }),
+           }),
+           "requested_predicates": json!({
+               "zip_3": json!({
+                   "name":"zip",
+                   "p_type":"GE",
+                   "p_value":84000,
+                   "restrictions": [json!({ "issuer_did": did })]
+               }),
+           }),
+        }).to_string();
+        proof_req.proof_request_data = serde_json::from_str(&indy_proof_req).unwrap();
+
+        let mut proof: DisclosedProof = Default::default();
+        proof.proof_request = Some(proof_req);
+        proof.link_secret_alias = "main".to_string();
+
+        let selection: Value = serde_json::from_str(&proof.select_credentials("{}").unwrap()).unwrap();
+        assert_eq!(selection["unsatisfied"], json!([]));
+        let selected = &selection["selected_credentials"];
+        assert_eq!(selected["attrs"]["address1_1"]["cred_info"]["referent"], json!(new_cred_id));
+        assert_eq!(selected["predicates"]["zip_3"]["cred_info"]["referent"], json!(new_cred_id));
+
+        proof.generate_proof(&selected.to_string(), "{}").unwrap();
+        let libindy_proof: Value = serde_json::from_str(&proof.proof.unwrap().libindy_proof).unwrap();
+        assert!(libindy_proof["requested_proof"]["revealed_attrs"]["address1_1"].is_object());
+        assert!(libindy_proof["requested_proof"]["predicates"]["zip_3"].is_object());
+    }
+
     #[cfg(feature = "pool_tests")]
     #[test]
     fn test_generate_self_attested_proof() {
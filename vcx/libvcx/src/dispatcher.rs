@@ -0,0 +1,301 @@
+use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use std::thread;
+use std::time::Duration;
+use api::{ VcxStateType, VcxObjectType };
+use connection;
+use credential;
+use disclosed_proof;
+use issuer_credential;
+use proof;
+use messages;
+use messages::get_message::{ MessagesByDid, group_by_pairwise_did };
+use error::VcxResult;
+
+pub type StateChangeCallback = extern fn(context_handle: u32, object_type: u32, handle: u32, state: u32);
+
+lazy_static! {
+    // Every context registers its own callback, it only hears about the objects of that context
+    static ref STATE_CHANGE_CBS: Mutex<HashMap<u32, StateChangeCallback>> = Default::default();
+}
+
+// Every background dispatcher gets its own generation, a running loop exits as soon as
+// its context no longer maps to the generation it was started with.
+static DISPATCHER_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+lazy_static! {
+    static ref RUNNING_DISPATCHERS: Mutex<HashMap<u32, usize>> = Default::default();
+}
+
+fn is_running(context: u32, generation: usize) -> bool {
+    RUNNING_DISPATCHERS.lock().unwrap().get(&context) == Some(&generation)
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct StateChange {
+    pub object_type: VcxObjectType,
+    pub handle: u32,
+    pub state: u32,
+}
+
+/// Registers the callback of the current context, None removes it.
+pub fn set_state_change_callback(cb: Option<StateChangeCallback>) {
+    if let Ok(mut callbacks) = STATE_CHANGE_CBS.lock() {
+        let context = ::context::current();
+        match cb {
+            Some(cb) => { callbacks.insert(context, cb); },
+            None => { callbacks.remove(&context); },
+        }
+    }
+}
+
+fn notify(change: &StateChange) {
+    let context = ::context::current();
+    let cb = match STATE_CHANGE_CBS.lock() {
+        Ok(callbacks) => callbacks.get(&context).cloned(),
+        Err(_) => None,
+    };
+
+    if let Some(cb) = cb {
+        cb(context, change.object_type as u32, change.handle, change.state);
+    }
+}
+
+fn waiting_for_answer(state: u32) -> bool {
+    state == VcxStateType::VcxStateOfferSent as u32
+}
+
+fn waiting_connections() -> Vec<u32> {
+    connection::get_handles().into_iter().filter(|h| {
+        let state = connection::get_state(*h);
+        state == VcxStateType::VcxStateInitialized as u32 || waiting_for_answer(state)
+    }).collect()
+}
+
+/// Hands the downloaded messages to every object that is waiting for an answer and
+/// reports the objects whose state changed.
+pub fn route_messages(messages: &MessagesByDid) -> Vec<StateChange> {
+    let mut changes = Vec::new();
+
+    {
+        let mut route = |object_type: VcxObjectType, handle: u32, before: u32, after: VcxResult<u32>| {
+            match after {
+                Ok(state) if state != before => changes.push(StateChange { object_type, handle, state }),
+                Ok(_) => (),
+                Err(ec) => warn!("could not route messages to {:?} {}: {}", object_type, handle, ec),
+            }
+        };
+
+        for handle in waiting_connections() {
+            let before = connection::get_state(handle);
+            route(VcxObjectType::Connection, handle, before,
+                  connection::update_state_with_messages(handle, messages));
+        }
+
+        for handle in issuer_credential::get_handles() {
+            match issuer_credential::get_state(handle) {
+                Ok(before) if waiting_for_answer(before) => route(VcxObjectType::IssuerCredential, handle, before,
+                    issuer_credential::update_state_with_messages(handle, messages)),
+                _ => (),
+            }
+        }
+
+        for handle in credential::get_handles() {
+            match credential::get_state(handle) {
+                Ok(before) if waiting_for_answer(before) => route(VcxObjectType::Credential, handle, before,
+                    credential::update_state_with_messages(handle, messages)),
+                _ => (),
+            }
+        }
+
+        for handle in proof::get_handles() {
+            match proof::get_state(handle) {
+                Ok(before) if waiting_for_answer(before) => route(VcxObjectType::Proof, handle, before,
+                    proof::update_state_with_messages(handle, messages)),
+                _ => (),
+            }
+        }
+    }
+
+    let accepted = connection::get_handles().into_iter()
+        .filter(|h| connection::get_state(*h) == VcxStateType::VcxStateAccepted as u32);
+    for handle in accepted {
+        match disclosed_proof::create_from_messages(handle, messages) {
+            Ok(created) => changes.extend(created.into_iter().map(|handle| StateChange {
+                object_type: VcxObjectType::DisclosedProof,
+                handle,
+                state: VcxStateType::VcxStateRequestReceived as u32,
+            })),
+            Err(ec) => warn!("could not route proof requests of connection {}: {}", handle, ec),
+        }
+    }
+
+    for change in changes.iter() {
+        notify(change);
+    }
+    changes
+}
+
+/// Downloads the messages of every pairwise connection with a single request and routes them
+/// to the objects they belong to. Registered callbacks are fired for every state change.
+pub fn dispatch_messages() -> VcxResult<Vec<StateChange>> {
+    let pw_dids: Vec<String> = connection::get_handles().into_iter()
+        .filter_map(|h| connection::get_pw_did(h).ok())
+        .collect();
+
+    if pw_dids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let downloaded = messages::get_messages()
+        .pairwise_dids(Some(pw_dids))
+        .download_encrypted_messages()?;
+
+    Ok(route_messages(&group_by_pairwise_did(downloaded)))
+}
+
+/// Dispatches messages of the current context every `interval_ms` on a dedicated thread
+/// until stop is called in that context.
+pub fn start(interval_ms: u64) {
+    let generation = DISPATCHER_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let context = ::context::current();
+    RUNNING_DISPATCHERS.lock().unwrap().insert(context, generation);
+    info!("starting message dispatcher, polling every {}ms", interval_ms);
+
+    thread::spawn(move || {
+        let _context = ::context::enter(context);
+        while is_running(context, generation) {
+            if let Err(ec) = dispatch_messages() {
+                warn!("message dispatcher could not download messages: {}", ec);
+            }
+            thread::sleep(Duration::from_millis(interval_ms));
+        }
+        debug!("message dispatcher {} stopped", generation);
+    });
+}
+
+pub fn stop() {
+    RUNNING_DISPATCHERS.lock().unwrap().remove(&::context::current());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{ channel, Sender };
+    use messages::get_message::get_connection_messages;
+    use utils::constants::*;
+    use utils::httpclient;
+
+    lazy_static! {
+        static ref CHANGES: Mutex<Option<Sender<(u32, u32, u32, u32)>>> = Default::default();
+    }
+
+    extern fn state_changed(context_handle: u32, object_type: u32, handle: u32, state: u32) {
+        if let Some(ref sender) = *CHANGES.lock().unwrap() {
+            sender.send((context_handle, object_type, handle, state)).unwrap();
+        }
+    }
+
+    // issued_did and msg_uid of issuer_credential::tests::create_standard_issuer_credential
+    static ISSUED_DID: &str = "8XFh8yBzrpJQmNyZzgoTqB";
+    static OFFER_UID: &str = "1234";
+
+    fn credential_request_messages(pw_did: &str) -> MessagesByDid {
+        httpclient::set_next_u8_response(CREDENTIAL_REQ_RESPONSE.to_vec());
+        httpclient::set_next_u8_response(UPDATE_CREDENTIAL_RESPONSE.to_vec());
+        let mut offer = get_connection_messages(DID, VERKEY, DID, VERKEY, None).unwrap().remove(0);
+        let request = get_connection_messages(DID, VERKEY, DID, VERKEY, None).unwrap().remove(0);
+        offer.uid = OFFER_UID.to_string();
+        offer.ref_msg_id = Some(request.uid.clone());
+
+        let mut by_did = MessagesByDid::new();
+        by_did.insert(pw_did.to_string(), vec![offer, request]);
+        by_did
+    }
+
+    #[test]
+    fn test_route_credential_request() {
+        init!("true");
+        let (sender, receiver) = channel();
+        *CHANGES.lock().unwrap() = Some(sender);
+        set_state_change_callback(Some(state_changed));
+
+        let credential = issuer_credential::tests::create_standard_issuer_credential();
+        let handle = issuer_credential::from_string(&credential.to_string()).unwrap();
+
+        let changes = route_messages(&credential_request_messages(ISSUED_DID));
+        let expected = StateChange {
+            object_type: VcxObjectType::IssuerCredential,
+            handle,
+            state: VcxStateType::VcxStateRequestReceived as u32,
+        };
+        assert!(changes.contains(&expected));
+        assert_eq!(issuer_credential::get_state(handle).unwrap(), VcxStateType::VcxStateRequestReceived as u32);
+
+        let notified: Vec<(u32, u32, u32, u32)> = receiver.try_iter().collect();
+        assert!(notified.contains(&(::context::DEFAULT_CONTEXT, VcxObjectType::IssuerCredential as u32, handle, VcxStateType::VcxStateRequestReceived as u32)));
+
+        // the request was already handled, nothing changes the second time
+        let changes = route_messages(&credential_request_messages(ISSUED_DID));
+        assert!(!changes.iter().any(|c| c.handle == handle));
+
+        set_state_change_callback(None);
+    }
+
+    #[test]
+    fn test_messages_for_other_connections_are_ignored() {
+        init!("true");
+        let credential = issuer_credential::tests::create_standard_issuer_credential();
+        let handle = issuer_credential::from_string(&credential.to_string()).unwrap();
+
+        let changes = route_messages(&credential_request_messages("UnknownPairwiseDid"));
+        assert!(!changes.iter().any(|c| c.handle == handle));
+        assert_eq!(issuer_credential::get_state(handle).unwrap(), VcxStateType::VcxStateOfferSent as u32);
+    }
+
+    #[test]
+    fn test_state_change_callback_is_per_context() {
+        init!("true");
+        let (sender, receiver) = channel();
+        *CHANGES.lock().unwrap() = Some(sender);
+
+        let tenant = ::context::create();
+        ::context::with_context(tenant, || set_state_change_callback(Some(state_changed)));
+
+        let credential = issuer_credential::tests::create_standard_issuer_credential();
+        let handle = issuer_credential::from_string(&credential.to_string()).unwrap();
+        let changes = route_messages(&credential_request_messages(ISSUED_DID));
+        assert!(changes.iter().any(|c| c.handle == handle));
+        assert_eq!(receiver.try_iter().count(), 0);
+
+        ::context::with_context(tenant, || set_state_change_callback(None));
+        ::context::release(tenant).unwrap();
+    }
+
+    #[test]
+    fn test_route_proof_request_to_new_disclosed_proof() {
+        init!("true");
+        let connection = connection::build_connection("test_route_proof_request").unwrap();
+        connection::set_state(connection, VcxStateType::VcxStateAccepted).unwrap();
+        let pw_did = connection::get_pw_did(connection).unwrap();
+
+        httpclient::set_next_u8_response(NEW_PROOF_REQUEST_RESPONSE.to_vec());
+        let mut request = get_connection_messages(DID, VERKEY, DID, VERKEY, None).unwrap().remove(0);
+        request.sender_did = "VerifierPairwiseDid".to_string();
+        request.msg_type = "proofReq".to_string();
+        request.status_code = messages::MessageResponseCode::MessagePending.as_string();
+        let mut by_did = MessagesByDid::new();
+        by_did.insert(pw_did, vec![request.clone()]);
+
+        let changes: Vec<StateChange> = route_messages(&by_did).into_iter()
+            .filter(|c| c.object_type == VcxObjectType::DisclosedProof)
+            .collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].state, VcxStateType::VcxStateRequestReceived as u32);
+        assert_eq!(disclosed_proof::get_source_id(changes[0].handle).unwrap(), request.uid);
+
+        // a disclosed proof exists for the request already
+        assert!(!route_messages(&by_did).iter().any(|c| c.object_type == VcxObjectType::DisclosedProof));
+    }
+}
@@ -1,23 +1,203 @@
-pub mod base;
-pub mod connection;
-pub mod schema;
-pub mod cred_def;
-pub mod issuer_cred;
-pub mod proof;
-pub mod credential;
-pub mod wallet;
-pub mod messages;
-pub mod payment;
+extern crate serde_json;
 
+use std::fmt;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::ptr;
+use std::os::raw::c_char;
+use utils::error;
 
-pub trait ToErrorCode {
-    fn to_error_code(&self) -> u32;
+pub type VcxResult<T> = Result<T, VcxError>;
+
+/// Error used across the crate. It carries the error code handed out through the FFI,
+/// a message describing what went wrong and the error that caused it, if any.
+///
+/// Only the api layer turns it into a bare code, with `ffi_code`.
+#[derive(Debug, Clone)]
+pub struct VcxError {
+    code: u32,
+    msg: String,
+    cause: Option<Box<VcxError>>,
+}
+
+impl VcxError {
+    pub fn from_code(code: u32) -> VcxError {
+        VcxError { code, msg: error::error_message(&code), cause: None }
+    }
+
+    pub fn from_msg<D: fmt::Display>(code: u32, msg: D) -> VcxError {
+        VcxError { code, msg: msg.to_string(), cause: None }
+    }
+
+    pub fn code(&self) -> u32 { self.code }
+
+    /// The code handed out through the FFI. The details stay available to vcx_get_current_error
+    /// until the next API call on this thread.
+    pub fn ffi_code(&self) -> u32 {
+        set_current_error(self);
+        self.code
+    }
+
+    pub fn message(&self) -> &str { &self.msg }
+
+    pub fn cause(&self) -> Option<&VcxError> {
+        self.cause.as_ref().map(|c| &**c)
+    }
+
+    /// Wraps this error into a new one, keeping it as the cause.
+    pub fn context<D: fmt::Display>(self, code: u32, msg: D) -> VcxError {
+        VcxError { code, msg: msg.to_string(), cause: Some(Box::new(self)) }
+    }
+
+    /// This error followed by its causes, outermost first.
+    pub fn chain(&self) -> Vec<&VcxError> {
+        let mut chain = vec![self];
+        let mut current = self;
+        while let Some(cause) = current.cause() {
+            chain.push(cause);
+            current = cause;
+        }
+        chain
+    }
+
+    pub fn to_json(&self) -> String {
+        let causes: Vec<serde_json::Value> = self.chain().iter().skip(1)
+            .map(|e| json!({"error": e.code, "message": e.msg}))
+            .collect();
+
+        json!({
+            "error": self.code,
+            "message": self.msg,
+            "causes": causes,
+        }).to_string()
+    }
+}
+
+impl fmt::Display for VcxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (Error Num:{})", self.msg, self.code)?;
+        if let Some(cause) = self.cause() {
+            write!(f, ", caused by: {}", cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for VcxError {
+    fn eq(&self, other: &VcxError) -> bool {
+        self.code == other.code
+    }
+}
+
+impl From<u32> for VcxError {
+    fn from(code: u32) -> VcxError {
+        VcxError::from_code(code)
+    }
+}
+
+pub trait VcxResultExt<T> {
+    /// Wraps the error into a new one with `code` and `msg`, keeping the original as the cause.
+    fn context<D: fmt::Display>(self, code: u32, msg: D) -> VcxResult<T>;
+
+    /// Like `context`, but the new error keeps the code of the original one.
+    fn with_msg<D: fmt::Display>(self, msg: D) -> VcxResult<T>;
+}
+
+impl<T, E> VcxResultExt<T> for Result<T, E> where E: Into<VcxError> {
+    fn context<D: fmt::Display>(self, code: u32, msg: D) -> VcxResult<T> {
+        self.map_err(|err| err.into().context(code, msg))
+    }
+
+    fn with_msg<D: fmt::Display>(self, msg: D) -> VcxResult<T> {
+        self.map_err(|err| {
+            let err = err.into();
+            let code = err.code;
+            err.context(code, msg)
+        })
+    }
+}
+
+thread_local! {
+    static CURRENT_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_current_error(err: &VcxError) {
+    CURRENT_ERROR.with(|current| {
+        *current.borrow_mut() = CString::new(err.to_json()).ok();
+    });
+}
+
+/// Forgets the error handed out before, every API call starts with a clean slate.
+pub fn reset_current_error() {
+    CURRENT_ERROR.with(|current| {
+        *current.borrow_mut() = None;
+    });
+}
+
+/// The details of the last error handed out on this thread since the last API call started.
+pub fn get_current_error_c_json() -> *const c_char {
+    CURRENT_ERROR.with(|current| {
+        match *current.borrow() {
+            Some(ref err) => err.as_ptr(),
+            None => ptr::null(),
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_error_chain() {
+        let err = VcxError::from(error::INVALID_DID.code_num)
+            .context(error::INVALID_CONFIGURATION.code_num, "institution_did is not a valid DID");
+
+        assert_eq!(err.code(), error::INVALID_CONFIGURATION.code_num);
+        assert_eq!(err.cause().unwrap().code(), error::INVALID_DID.code_num);
+        assert_eq!(err.chain().len(), 2);
+        assert_eq!(err.to_string(),
+                   "institution_did is not a valid DID (Error Num:1004), caused by: Invalid DID (Error Num:1008)");
+
+        let result: Result<(), u32> = Err(error::INVALID_JSON.code_num);
+        let err = result.context(error::INVALID_CONFIGURATION.code_num, "config is not valid json").unwrap_err();
+        assert_eq!(err.cause().unwrap().code(), error::INVALID_JSON.code_num);
+    }
+
+    #[test]
+    fn test_with_msg_keeps_code() {
+        let result: Result<(), u32> = Err(error::INVALID_JSON.code_num);
+        let err = result.with_msg("config is not valid json").unwrap_err();
+        assert_eq!(err.code(), error::INVALID_JSON.code_num);
+        assert_eq!(err.message(), "config is not valid json");
+        assert_eq!(err.cause().unwrap().code(), error::INVALID_JSON.code_num);
+    }
+
+    #[test]
+    fn test_errors_are_only_current_once_handed_out() {
+        reset_current_error();
+        let err = VcxError::from_msg(error::INVALID_URL.code_num, "agency_endpoint is not a url");
+        assert!(get_current_error_c_json().is_null());
+
+        assert_eq!(err.ffi_code(), error::INVALID_URL.code_num);
+        assert!(!get_current_error_c_json().is_null());
+
+        reset_current_error();
+        assert!(get_current_error_c_json().is_null());
+    }
+
     #[test]
-    fn test_to_error_code(){
+    fn test_current_error() {
+        let err = VcxError::from_msg(error::INVALID_URL.code_num, "agency_endpoint is not a url")
+            .context(error::INVALID_CONFIGURATION.code_num, "invalid configuration");
+        let code = err.ffi_code();
+        assert_eq!(code, error::INVALID_CONFIGURATION.code_num);
 
+        let json = unsafe { CStr::from_ptr(get_current_error_c_json()) }.to_str().unwrap().to_string();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["error"], json!(error::INVALID_CONFIGURATION.code_num));
+        assert_eq!(json["message"], json!("invalid configuration"));
+        assert_eq!(json["causes"][0]["message"], json!("agency_endpoint is not a url"));
     }
 }
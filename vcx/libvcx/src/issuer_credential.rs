@@ -6,21 +6,29 @@ use api::VcxStateType;
 use messages;
 use settings;
 use messages::{ GeneralMessage, MessageResponseCode::MessageAccepted, send_message::parse_msg_uid };
+use messages::get_message::MessagesByDid;
 use connection;
+use credential_def;
+use schema::LedgerSchema;
 use credential_request::{ CredentialRequest };
 use utils::{error,
             error::INVALID_JSON,
-            libindy::{ anoncreds::{ libindy_issuer_create_credential, libindy_issuer_create_credential_offer}, payments },
+            libindy::{ anoncreds::{ libindy_issuer_create_credential,
+                                    libindy_issuer_create_credential_offer,
+                                    get_rev_reg_def_json,
+                                    revoke_credential },
+                       blob_storage::open_tails_reader,
+                       payments },
             constants::CRED_MSG,
             openssl::encode
 };
-use error::{ issuer_cred::IssuerCredError, ToErrorCode, payment::PaymentError};
-use utils::constants::DEFAULT_SERIALIZE_VERSION;
+use error::{ VcxError, VcxResult, VcxResultExt };
+use utils::serialization::{ self, Versioned };
 use serde_json::Value;
 use object_cache::ObjectCache;
 
 lazy_static! {
-    static ref ISSUER_CREDENTIAL_MAP: ObjectCache<IssuerCredential> = Default::default();
+    static ref ISSUER_CREDENTIAL_MAP: ObjectCache<IssuerCredential> = ObjectCache::persistent("vcx_issuer_credential", IssuerCredential::to_string);
 }
 
 static CREDENTIAL_OFFER_ID_KEY: &str = "claim_offer_id";
@@ -48,8 +56,14 @@ pub struct IssuerCredential {
     issued_vk: String,
     remote_did: String, //their_pw_did for this relationship
     remote_vk: String,
+    // the following 3 are only set when the cred_def supports revocation
+    rev_reg_id: Option<String>,
+    tails_file: Option<String>,
+    cred_rev_id: Option<String>,
 }
 
+impl Versioned for IssuerCredential {}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct CredentialOffer {
     pub msg_type: String,
@@ -84,41 +98,48 @@ pub struct PaymentInfo {
 }
 
 impl PaymentInfo {
-    pub fn get_address(&self) -> Result<String, PaymentError> {
+    pub fn get_address(&self) -> VcxResult<String> {
         Ok(self.payment_addr.to_string())
     }
 
-    pub fn get_price(&self) -> Result<u64, PaymentError> {
+    pub fn get_price(&self) -> VcxResult<u64> {
         Ok(self.price)
     }
 
-    pub fn to_string(&self) -> Result<String, PaymentError> { serde_json::to_string(&self).or(Err(PaymentError::InvalidWalletJson()))}
+    pub fn to_string(&self) -> VcxResult<String> { serde_json::to_string(&self).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))}
 }
 
 impl IssuerCredential {
-    fn validate_credential_offer(&self) -> Result<u32, IssuerCredError> {
-        //TODO: validate credential_attributes against credential_def
+    fn validate_credential_offer(&self) -> VcxResult<()> {
+        if settings::test_indy_mode_enabled() { return Ok(()); }
+
+        let schema_id = credential_def::get_schema_id(&self.cred_def_id)?;
+        let schema = LedgerSchema::new_from_ledger(&schema_id)?;
+        check_attributes_match_schema(&schema.get_attr_names()?, &self.credential_attributes)?;
+
         debug!("successfully validated issuer_credential {}", self.source_id);
-        Ok(error::SUCCESS.code_num)
+        Ok(())
     }
 
-    fn send_credential_offer(&mut self, connection_handle: u32) -> Result<u32, IssuerCredError> {
+    fn send_credential_offer(&mut self, connection_handle: u32) -> VcxResult<u32> {
         debug!("sending credential offer for issuer_credential {} to connection {}", self.source_id, connection::get_source_id(connection_handle).unwrap_or_default());
         if self.state != VcxStateType::VcxStateInitialized {
             warn!("credential {} has invalid state {} for sending credentialOffer", self.source_id, self.state as u32);
-            return Err(IssuerCredError::NotReadyError())
+            return Err(VcxError::from_code(error::NOT_READY.code_num))
         }
 
         if connection::is_valid_handle(connection_handle) == false {
             warn!("invalid connection handle ({})", connection_handle);
-            return Err(IssuerCredError::CommonError(error::INVALID_CONNECTION_HANDLE.code_num));
+            return Err(VcxError::from(error::INVALID_CONNECTION_HANDLE.code_num));
         }
 
-        self.agent_did = connection::get_agent_did(connection_handle).map_err(|e| IssuerCredError::CommonError(e.to_error_code()))?;
-        self.agent_vk = connection::get_agent_verkey(connection_handle).map_err(|e| IssuerCredError::CommonError(e.to_error_code()))?;
-        self.issued_did = connection::get_pw_did(connection_handle).map_err(|x| IssuerCredError::CommonError(x.to_error_code()))?;
-        self.issued_vk = connection::get_pw_verkey(connection_handle).map_err(|x| IssuerCredError::CommonError(x.to_error_code()))?;
-        self.remote_vk = connection::get_their_pw_verkey(connection_handle).map_err(|x| IssuerCredError::CommonError(x.to_error_code()))?;
+        self.validate_credential_offer()?;
+
+        self.agent_did = connection::get_agent_did(connection_handle)?;
+        self.agent_vk = connection::get_agent_verkey(connection_handle)?;
+        self.issued_did = connection::get_pw_did(connection_handle)?;
+        self.issued_vk = connection::get_pw_verkey(connection_handle)?;
+        self.remote_vk = connection::get_their_pw_verkey(connection_handle)?;
 
         let payment = self.generate_payment_info()?;
         let credential_offer = self.generate_credential_offer(&self.issued_did)?;
@@ -129,17 +150,18 @@ impl IssuerCredential {
         payload.push(cred_json);
         let payload = match serde_json::to_string(&payload) {
             Ok(p) => p,
-            Err(_) => return Err(IssuerCredError::CommonError(error::INVALID_JSON.code_num))
+            Err(_) => return Err(VcxError::from(error::INVALID_JSON.code_num))
         };
 
         debug!("credential offer data: {}", payload);
 
-        let data = connection::generate_encrypted_payload(&self.issued_vk, &self.remote_vk, &payload, "CRED_OFFER")
-            .map_err(|e| IssuerCredError::CommonError(e.to_error_code()))?;
+        let data = connection::generate_payload_for_connection(connection_handle, &self.issued_vk, &self.remote_vk, &payload, "CRED_OFFER")?;
+        let service = connection::get_didcomm_service(connection_handle)?;
 
         match messages::send_message().to(&self.issued_did)
             .to_vk(&self.issued_vk)
             .msg_type("credOffer")
+            .didcomm_service(service)
             .edge_agent_payload(&data)
             .agent_did(&self.agent_did)
             .agent_vk(&self.agent_vk)
@@ -147,10 +169,10 @@ impl IssuerCredential {
             .send_secure() {
             Err(x) => {
                 warn!("could not send credentialOffer: {}", x);
-                return Err(IssuerCredError::CommonError(x));
+                return Err(x);
             },
             Ok(response) => {
-                self.msg_uid = parse_msg_uid(&response[0]).map_err(|ec| IssuerCredError::CommonError(ec))?;
+                self.msg_uid = parse_msg_uid(&response[0])?;
                 self.state = VcxStateType::VcxStateOfferSent;
                 self.credential_offer = Some(credential_offer);
                 debug!("sent credential offer for: {}", self.source_id);
@@ -159,37 +181,38 @@ impl IssuerCredential {
         }
     }
 
-    fn send_credential(&mut self, connection_handle: u32) -> Result<u32, IssuerCredError> {
+    fn send_credential(&mut self, connection_handle: u32) -> VcxResult<u32> {
         debug!("sending credential for issuer_credential {} to connection {}", self.source_id, connection::get_source_id(connection_handle).unwrap_or_default());
         if self.state != VcxStateType::VcxStateRequestReceived {
             warn!("credential {} has invalid state {} for sending credential", self.source_id, self.state as u32);
-            return Err(IssuerCredError::NotReadyError());
+            return Err(VcxError::from_code(error::NOT_READY.code_num));
         }
 
         if connection::is_valid_handle(connection_handle) == false {
             warn!("invalid connection handle ({}) in send_credential_offer", connection_handle);
-            return Err(IssuerCredError::InvalidHandle());
+            return Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num));
         }
 
-        self.verify_payment().map_err(|e| IssuerCredError::CommonError(e))?;
+        self.verify_payment()?;
 
-        let to = connection::get_pw_did(connection_handle).map_err(|e| IssuerCredError::CommonError(e.to_error_code()))?;
+        let to = connection::get_pw_did(connection_handle)?;
         let attrs_with_encodings = self.create_attributes_encodings()?;
         let data = if settings::test_indy_mode_enabled() { CRED_MSG.to_string()} else {
             let cred = self.generate_credential(&attrs_with_encodings, &to)?;
-            serde_json::to_string(&cred).or(Err(IssuerCredError::InvalidCred()))?
+            serde_json::to_string(&cred).or(Err(VcxError::from_code(error::INVALID_CREDENTIAL_JSON.code_num)))?
         };
 
         debug!("credential data: {}", data);
 
-        let cred_req_msg_id = self.credential_request.as_ref().ok_or(IssuerCredError::InvalidCredRequest())?
-            .msg_ref_id.as_ref().ok_or(IssuerCredError::InvalidCredRequest())?;
-        let data = connection::generate_encrypted_payload(&self.issued_vk, &self.remote_vk, &data, "CRED")
-            .map_err(|e| IssuerCredError::CommonError(e.to_error_code()))?;
+        let cred_req_msg_id = self.credential_request.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_REQUEST.code_num))?
+            .msg_ref_id.as_ref().ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_REQUEST.code_num))?;
+        let data = connection::generate_payload_for_connection(connection_handle, &self.issued_vk, &self.remote_vk, &data, "CRED")?;
+        let service = connection::get_didcomm_service(connection_handle)?;
 
         match messages::send_message().to(&self.issued_did)
             .to_vk(&self.issued_vk)
             .msg_type("cred")
+            .didcomm_service(service)
             .status_code(&MessageAccepted.as_string())
             .edge_agent_payload(&data)
             .agent_did(&self.agent_did)
@@ -198,10 +221,10 @@ impl IssuerCredential {
             .send_secure() {
             Err(x) => {
                 warn!("could not send credential: {}", x);
-                return Err(IssuerCredError::CommonError(x));
+                return Err(x);
             },
             Ok(response) => {
-                self.msg_uid = parse_msg_uid(&response[0]).map_err(|ec| IssuerCredError::CommonError(ec))?;
+                self.msg_uid = parse_msg_uid(&response[0])?;
                 self.state = VcxStateType::VcxStateAccepted;
                 debug!("issued credential: {}", self.source_id);
                 return Ok(error::SUCCESS.code_num);
@@ -209,13 +232,13 @@ impl IssuerCredential {
         }
     }
 
-    pub fn create_attributes_encodings(&self) -> Result<String, IssuerCredError> {
+    pub fn create_attributes_encodings(&self) -> VcxResult<String> {
         encode_attributes(&self.credential_attributes)
     }
 
     // TODO: The error arm of this Result is never used in any calling functions.
     // So currently there is no way to test the error status.
-    fn get_credential_offer_status(&mut self) -> Result<u32, IssuerCredError> {
+    fn get_credential_offer_status(&mut self, messages: Option<&MessagesByDid>) -> VcxResult<u32> {
         debug!("updating state for credential offer: {} msg_uid: {:?}", self.source_id, self.msg_uid);
         if self.state == VcxStateType::VcxStateRequestReceived {
             return Ok(self.get_state());
@@ -224,12 +247,12 @@ impl IssuerCredential {
 
             return Ok(self.get_state());
         }
-        let (offer_uid, payload) = messages::get_message::get_ref_msg(&self.msg_uid,
+        let (offer_uid, payload) = messages::get_message::get_ref_msg_from(messages,
+                                                               &self.msg_uid,
                                                                &self.issued_did,
                                                                &self.issued_vk,
                                                                &self.agent_did,
-                                                               &self.agent_vk)
-            .map_err(|wc|IssuerCredError::CommonError(wc))?;
+                                                               &self.agent_vk)?;
 
         self.credential_request = Some(parse_credential_req_payload(offer_uid, &payload)?);
         debug!("received credential request for credential offer: {}", self.source_id);
@@ -237,15 +260,15 @@ impl IssuerCredential {
         Ok(self.get_state())
     }
 
-    fn update_state(&mut self) -> Result<u32, IssuerCredError> {
-        self.get_credential_offer_status()
+    fn update_state(&mut self) -> VcxResult<u32> {
+        self.get_credential_offer_status(None)
         //There will probably be more things here once we do other things with the credential
     }
 
     fn get_state(&self) -> u32 { let state = self.state as u32; state }
     fn get_offer_uid(&self) -> &String { &self.msg_uid }
     fn set_offer_uid(&mut self, uid: &str) {self.msg_uid = uid.to_owned();}
-    fn set_credential_request(&mut self, credential_request:CredentialRequest) -> Result<u32,u32> {
+    fn set_credential_request(&mut self, credential_request:CredentialRequest) -> VcxResult<u32> {
         self.credential_request = Some(credential_request);
         Ok(error::SUCCESS.code_num)
     }
@@ -253,21 +276,31 @@ impl IssuerCredential {
     fn get_credential_attributes(&self) -> &String { &self.credential_attributes}
     fn get_source_id(&self) -> &String { &self.source_id }
 
-    fn generate_credential(&self, credential_data: &str, did: &str) -> Result<CredentialMessage, IssuerCredError> {
-        let indy_cred_offer = &self.credential_offer.as_ref()
-            .ok_or(IssuerCredError::InvalidCred())?.libindy_offer;
+    fn generate_credential(&mut self, credential_data: &str, did: &str) -> VcxResult<CredentialMessage> {
+        let indy_cred_offer = self.credential_offer.as_ref()
+            .ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_JSON.code_num))?.libindy_offer.clone();
+
+        let indy_cred_req = self.credential_request.as_ref()
+            .ok_or(VcxError::from_code(error::INVALID_CREDENTIAL_REQUEST.code_num))?.libindy_cred_req.clone();
 
-        let indy_cred_req = &self.credential_request.as_ref()
-            .ok_or(IssuerCredError::InvalidCredRequest())?.libindy_cred_req;
+        let blob_storage_reader_handle = match self.tails_file {
+            Some(ref tails_file) => Some(open_tails_reader(tails_file)?),
+            None => None,
+        };
 
-        //Todo: rev_reg_id and blob_storage need to be provided when we do revocation
-        let (cred, cred_revoc_id, revoc_reg_delta_json) = libindy_issuer_create_credential(
+        let (cred, cred_rev_id, _) = libindy_issuer_create_credential(
             &indy_cred_offer,
             &indy_cred_req,
             credential_data,
-            None,
-            None)
-            .map_err(|x| IssuerCredError::CommonError(x))?;
+            self.rev_reg_id.as_ref().map(String::as_str),
+            blob_storage_reader_handle)?;
+
+        let rev_reg_def_json = match self.rev_reg_id {
+            Some(ref rev_reg_id) => get_rev_reg_def_json(rev_reg_id)?.1,
+            None => String::new(),
+        };
+
+        self.cred_rev_id = cred_rev_id;
 
         Ok(CredentialMessage {
             claim_offer_id: self.msg_uid.clone(),
@@ -275,17 +308,32 @@ impl IssuerCredential {
             version: String::from("0.1"),
             msg_type: String::from("CRED"),
             libindy_cred: cred,
-            //Todo: need to add indy api calls to populate this field
-            rev_reg_def_json: String::new(),
+            rev_reg_def_json,
             cred_def_id: self.cred_def_id.clone(),
         })
     }
 
-    fn generate_credential_offer(&self, to_did: &str) -> Result<CredentialOffer, IssuerCredError> {
+    fn revoke_cred(&mut self) -> VcxResult<u32> {
+        if self.state != VcxStateType::VcxStateAccepted {
+            warn!("credential {} has invalid state {} for revocation", self.source_id, self.state as u32);
+            return Err(VcxError::from_code(error::NOT_READY.code_num));
+        }
+
+        let tails_file = self.tails_file.as_ref().ok_or(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num))?;
+        let rev_reg_id = self.rev_reg_id.as_ref().ok_or(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num))?;
+        let cred_rev_id = self.cred_rev_id.as_ref().ok_or(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num))?;
+
+        revoke_credential(tails_file, rev_reg_id, cred_rev_id)?;
+
+        debug!("revoked credential: {}", self.source_id);
+        self.state = VcxStateType::VcxStateRevoked;
+        Ok(error::SUCCESS.code_num)
+    }
+
+    fn generate_credential_offer(&self, to_did: &str) -> VcxResult<CredentialOffer> {
         let attr_map = convert_to_map(&self.credential_attributes)?;
         //Todo: make a cred_def_offer error
-        let libindy_offer = libindy_issuer_create_credential_offer(&self.cred_def_id)
-            .map_err(|err| IssuerCredError::CommonError(err))?;
+        let libindy_offer = libindy_issuer_create_credential_offer(&self.cred_def_id)?;
         Ok(CredentialOffer {
             msg_type: String::from("CRED_OFFER"),
             version: String::from("0.1"),
@@ -301,9 +349,9 @@ impl IssuerCredential {
         })
     }
 
-    fn generate_payment_info(&mut self) -> Result<Option<PaymentInfo>, IssuerCredError> {
+    fn generate_payment_info(&mut self) -> VcxResult<Option<PaymentInfo>> {
         if self.price > 0 {
-            let address: String = ::utils::libindy::payments::create_address(None).map_err(|x| IssuerCredError::CommonError(x))?;
+            let address: String = ::utils::libindy::payments::create_address(None)?;
             self.payment_address = Some(address.clone());
             Ok(Some(PaymentInfo {
                 payment_required: "one-time".to_string(),
@@ -315,19 +363,19 @@ impl IssuerCredential {
         }
     }
 
-    fn verify_payment(&mut self) -> Result<(), u32> {
+    fn verify_payment(&mut self) -> VcxResult<()> {
         if self.price > 0 {
             let invoice_address = self.payment_address.as_ref()
-                .ok_or(error::INVALID_PAYMENT_ADDRESS.code_num)?;
+                .ok_or(VcxError::from_code(error::INVALID_PAYMENT_ADDRESS.code_num))?;
 
             let address = payments::get_address_info(&invoice_address)?;
 
-            if address.balance < self.price { return Err(error::INSUFFICIENT_TOKEN_AMOUNT.code_num); }
+            if address.balance < self.price { return Err(VcxError::from_code(error::INSUFFICIENT_TOKEN_AMOUNT.code_num)); }
         }
         Ok(())
     }
 
-    fn get_payment_txn(&self) -> Result<payments::PaymentTxn, u32> {
+    fn get_payment_txn(&self) -> VcxResult<payments::PaymentTxn> {
         match self.payment_address {
             Some(ref payment_address) if self.price > 0 => {
                 Ok(payments::PaymentTxn {
@@ -337,98 +385,88 @@ impl IssuerCredential {
                     outputs: Vec::new(),
                 })
             },
-            _ => Err(error::NO_PAYMENT_INFORMATION.code_num)
+            _ => Err(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num))
         }
     }
 
     pub fn to_string(&self) -> String {
-        json!({
-            "version": DEFAULT_SERIALIZE_VERSION,
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
 
-    fn from_str(s: &str) -> Result<IssuerCredential, IssuerCredError> {
-        let data: Value = serde_json::from_str(&s)?;
-        let cred = serde_json::from_value(data["data"].clone())?;
-        Ok(cred)
+    fn from_str(s: &str) -> VcxResult<IssuerCredential> {
+        serialization::deserialize(s).context(error::INVALID_JSON.code_num, "cannot deserialize issuer credential")
     }
 }
 
-pub fn encode_attributes(attributes: &str) -> Result<String, IssuerCredError> {
-    let mut attributes: serde_json::Value = match serde_json::from_str(attributes) {
+/// Builds the credential values libindy signs, {"attr": {"raw": "value", "encoded": "..."}}, from the
+/// credential data. Attribute values are either the value or a list holding only the value. Numbers,
+/// booleans and null are taken as their json text, so "age": 25 and "age": "25" encode the same.
+pub fn encode_attributes(attributes: &str) -> VcxResult<String> {
+    let attributes: serde_json::Map<String, Value> = match serde_json::from_str(attributes) {
         Ok(x) => x,
         Err(e) => {
             warn!("Invalid Json for Attribute data");
-            return Err(IssuerCredError::CommonError(INVALID_JSON.code_num))
+            return Err(VcxError::from(INVALID_JSON.code_num))
         }
     };
 
-    let map = match attributes.as_object_mut() {
-        Some(x) => x,
-        None => {
-            warn!("Invalid Json for Attribute data");
-            return Err(IssuerCredError::CommonError(INVALID_JSON.code_num))
-        }
-    };
-
-    for (attr, vec) in map.iter_mut(){
-        let list = match vec.as_array_mut() {
-            Some(x) => x,
-            None => {
-                warn!("Invalid Json for Attribute data");
-                return Err(IssuerCredError::CommonError(INVALID_JSON.code_num))
-            }
+    let mut encoded_attributes = serde_json::Map::new();
+    for (attr, value) in attributes {
+        let value = match value {
+            Value::Array(ref list) if list.len() > 1 => None,
+            Value::Array(list) => list.into_iter().next(),
+            value => Some(value),
         };
-        let i = list[0].clone();
-        let value = match i.as_str(){
-            Some(v) => v,
-            None => {
-                warn!("Cannot encode attribute: {}", error::INVALID_ATTRIBUTES_STRUCTURE.message);
-                return Err(IssuerCredError::CommonError(error::INVALID_ATTRIBUTES_STRUCTURE.code_num))
+
+        let raw = match value {
+            Some(Value::String(x)) => x,
+            Some(Value::Array(_)) | Some(Value::Object(_)) | None => {
+                warn!("Cannot encode attribute {}: {}", attr, error::INVALID_ATTRIBUTES_STRUCTURE.message);
+                return Err(VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num))
             },
+            Some(value) => value.to_string(),
         };
-        let encoded = encode(value).map_err(|x| IssuerCredError::CommonError(x))?;
-        let encoded_as_value: serde_json::Value = serde_json::Value::from(encoded);
-        list.push(encoded_as_value);
+
+        let encoded = encode(&raw)?;
+        encoded_attributes.insert(attr, json!({"raw": raw, "encoded": encoded}));
     }
 
-    match serde_json::to_string_pretty(&map) {
+    match serde_json::to_string_pretty(&encoded_attributes) {
         Ok(x) => Ok(x),
         Err(x) => {
             warn!("Invalid Json for Attribute data");
-            Err(IssuerCredError::CommonError(INVALID_JSON.code_num))
+            Err(VcxError::from(INVALID_JSON.code_num))
         }
     }
 }
 
-pub fn get_encoded_attributes(handle:u32) -> Result<String, IssuerCredError>{
+pub fn get_encoded_attributes(handle:u32) -> VcxResult<String>{
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
-        i.create_attributes_encodings().map_err(|ec|ec.to_error_code())
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+        i.create_attributes_encodings()
+    })
 }
 
-pub fn get_offer_uid(handle: u32) -> Result<String,u32> {
+pub fn get_offer_uid(handle: u32) -> VcxResult<String> {
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
         Ok(i.get_offer_uid().clone())
     })
 }
 
-pub fn get_payment_txn(handle: u32) -> Result<payments::PaymentTxn, IssuerCredError> {
+pub fn get_payment_txn(handle: u32) -> VcxResult<payments::PaymentTxn> {
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
         i.get_payment_txn()
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+    })
 }
 
-fn parse_credential_req_payload(offer_uid: String, payload: &Vec<u8>) -> Result<CredentialRequest, IssuerCredError> {
+fn parse_credential_req_payload(offer_uid: String, payload: &Vec<u8>) -> VcxResult<CredentialRequest> {
     debug!("parsing credentialReq payload: {:?}", payload);
-    let data = messages::extract_json_payload(payload).map_err(|ec|IssuerCredError::CommonError(ec))?;
+    let data = messages::extract_json_payload(payload)?;
 
     let mut my_credential_req = match CredentialRequest::from_str(&data) {
          Ok(x) => x,
          Err(x) => {
              warn!("invalid json {}", x);
-             return Err(IssuerCredError::CommonError(error::INVALID_JSON.code_num));
+             return Err(VcxError::from(error::INVALID_JSON.code_num));
          },
     };
     my_credential_req.msg_ref_id = Some(offer_uid);
@@ -441,7 +479,7 @@ pub fn issuer_credential_create(cred_def_id: String,
                            issuer_did: String,
                            credential_name: String,
                            credential_data: String,
-                           price: u64) -> Result<u32, IssuerCredError> {
+                           price: u64) -> VcxResult<u32> {
 
     let mut new_issuer_credential = IssuerCredential {
         credential_id: source_id.to_string(),
@@ -464,38 +502,66 @@ pub fn issuer_credential_create(cred_def_id: String,
         remote_vk: String::new(),
         agent_did: String::new(),
         agent_vk: String::new(),
+        rev_reg_id: None,
+        tails_file: None,
+        cred_rev_id: None,
         cred_def_id
     };
 
+    if let Some(revocation) = credential_def::get_revocation_details(&new_issuer_credential.cred_def_id)? {
+        new_issuer_credential.rev_reg_id = Some(revocation.rev_reg_id);
+        new_issuer_credential.tails_file = Some(revocation.tails_file);
+    }
+
     new_issuer_credential.validate_credential_offer()?;
 
     new_issuer_credential.state = VcxStateType::VcxStateInitialized;
 
-    let new_handle = ISSUER_CREDENTIAL_MAP.add(new_issuer_credential).map_err(|key|IssuerCredError::CreateError())?;
+    let new_handle = ISSUER_CREDENTIAL_MAP.add(new_issuer_credential).map_err(|key|VcxError::from_code(error::OBJECT_CACHE_ERROR.code_num))?;
     debug!("creating issuer_credential {} with handle {}", get_source_id(new_handle).unwrap_or_default(), new_handle);
 
     Ok(new_handle)
 }
 
-pub fn update_state(handle: u32) -> Result<u32, IssuerCredError> {
+pub fn update_state(handle: u32) -> VcxResult<u32> {
     ISSUER_CREDENTIAL_MAP.get_mut(handle,|i|{
         match i.update_state() {
             Ok(x) => Ok(x),
-            Err(x) => Ok(i.get_state()),
+            Err(x) => {
+                warn!("could not update state for issuer_credential {}: {}", i.get_source_id(), x);
+                Ok(i.get_state())
+            },
         }
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+    })
 }
 
-pub fn get_state(handle: u32) -> Result<u32, u32> {
+/// Updates the state from messages the dispatcher already downloaded instead of polling the agency.
+pub fn update_state_with_messages(handle: u32, messages: &MessagesByDid) -> VcxResult<u32> {
+    ISSUER_CREDENTIAL_MAP.get_mut(handle,|i|{
+        match i.get_credential_offer_status(Some(messages)) {
+            Ok(x) => Ok(x),
+            Err(x) => {
+                warn!("could not update state for issuer_credential {} from dispatched messages: {}", i.get_source_id(), x);
+                Ok(i.get_state())
+            },
+        }
+    })
+}
+
+pub fn get_handles() -> Vec<u32> {
+    ISSUER_CREDENTIAL_MAP.handles()
+}
+
+pub fn get_state(handle: u32) -> VcxResult<u32> {
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
         Ok(i.get_state())
     })
 }
 
-pub fn release(handle: u32) -> Result<(), IssuerCredError> {
+pub fn release(handle: u32) -> VcxResult<()> {
     match ISSUER_CREDENTIAL_MAP.release(handle) {
         Ok(_) => Ok(()),
-        Err(_) => Err(IssuerCredError::InvalidHandle()),
+        Err(_) => Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)),
     }
 }
 
@@ -506,36 +572,52 @@ pub fn release_all() {
     };
 }
 
+/// Reloads the stored objects, see object_store
+pub fn restore_all() -> VcxResult<usize> {
+    ISSUER_CREDENTIAL_MAP.restore(|data| IssuerCredential::from_str(data).ok())
+}
+
 pub fn is_valid_handle(handle: u32) -> bool {
     ISSUER_CREDENTIAL_MAP.has_handle(handle)
 }
 
-pub fn to_string(handle: u32) -> Result<String, IssuerCredError> {
+/// The context the issuer credential was created in, calls on the issuer credential run in it.
+pub fn get_context(handle: u32) -> u32 {
+    ISSUER_CREDENTIAL_MAP.context_of(handle)
+}
+
+pub fn to_string(handle: u32) -> VcxResult<String> {
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
         Ok(IssuerCredential::to_string(&i))
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+    })
 }
 
-pub fn from_string(credential_data: &str) -> Result<u32,IssuerCredError> {
+pub fn from_string(credential_data: &str) -> VcxResult<u32> {
     let derived_credential: IssuerCredential = IssuerCredential::from_str(credential_data)?;
     let source_id = derived_credential.source_id.clone();
-    let new_handle = ISSUER_CREDENTIAL_MAP.add(derived_credential).map_err(|ec| IssuerCredError::CommonError(ec))?;
+    let new_handle = ISSUER_CREDENTIAL_MAP.add(derived_credential)?;
     Ok(new_handle)
 }
 
-pub fn send_credential_offer(handle: u32, connection_handle: u32) -> Result<u32,IssuerCredError> {
+pub fn send_credential_offer(handle: u32, connection_handle: u32) -> VcxResult<u32> {
     ISSUER_CREDENTIAL_MAP.get_mut(handle,|i|{
-        i.send_credential_offer(connection_handle).map_err(|ec|ec.to_error_code())
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+        i.send_credential_offer(connection_handle)
+    })
 }
 
-pub fn send_credential(handle: u32, connection_handle: u32) -> Result<u32,IssuerCredError> {
+pub fn send_credential(handle: u32, connection_handle: u32) -> VcxResult<u32> {
     ISSUER_CREDENTIAL_MAP.get_mut(handle,|i|{
-        i.send_credential(connection_handle).map_err(|ec| ec.to_error_code())
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+        i.send_credential(connection_handle)
+    })
 }
 
-fn get_offer_details(response: &str) -> Result<String, IssuerCredError> {
+pub fn revoke_credential(handle: u32) -> VcxResult<u32> {
+    ISSUER_CREDENTIAL_MAP.get_mut(handle,|i|{
+        i.revoke_cred()
+    })
+}
+
+fn get_offer_details(response: &str) -> VcxResult<String> {
     match serde_json::from_str(response) {
         Ok(json) => {
             let json: serde_json::Value = json;
@@ -543,41 +625,66 @@ fn get_offer_details(response: &str) -> Result<String, IssuerCredError> {
                 Some(x) => x,
                 None => {
                     warn!("response had no uid");
-                    return Err(IssuerCredError::CommonError(error::INVALID_JSON.code_num))
+                    return Err(VcxError::from(error::INVALID_JSON.code_num))
                 },
             };
             Ok(String::from(detail))
         },
         Err(_) => {
             warn!("get_messages called without a valid response from server");
-            Err(IssuerCredError::CommonError(error::INVALID_JSON.code_num))
+            Err(VcxError::from(error::INVALID_JSON.code_num))
         },
     }
 }
 
-pub fn set_credential_request(handle: u32, credential_request: CredentialRequest) -> Result<u32, IssuerCredError>{
+pub fn set_credential_request(handle: u32, credential_request: CredentialRequest) -> VcxResult<u32>{
     ISSUER_CREDENTIAL_MAP.get_mut(handle,|i|{
         i.set_credential_request(credential_request.clone())
-    }).map_err(|ec|IssuerCredError::CommonError(ec))
+    })
 }
 
-pub fn convert_to_map(s:&str) -> Result<serde_json::Map<String, serde_json::Value>, IssuerCredError>{
+/// Fails listing the schema attributes missing from the credential data and the attributes
+/// the schema does not have.
+fn check_attributes_match_schema(schema_attrs: &[String], credential_data: &str) -> VcxResult<()> {
+    let credential_attrs = convert_to_map(credential_data)?;
+
+    let missing: Vec<&str> = schema_attrs.iter()
+        .filter(|attr| !credential_attrs.contains_key(*attr))
+        .map(|attr| attr.as_str())
+        .collect();
+    let extra: Vec<&str> = credential_attrs.keys()
+        .filter(|attr| !schema_attrs.contains(attr))
+        .map(|attr| attr.as_str())
+        .collect();
+
+    if missing.is_empty() && extra.is_empty() { return Ok(()); }
+
+    let mut mismatches = Vec::new();
+    if !missing.is_empty() { mismatches.push(format!("missing attributes: {}", missing.join(", "))); }
+    if !extra.is_empty() { mismatches.push(format!("attributes not in the schema: {}", extra.join(", "))); }
+
+    let msg = format!("credential attributes do not match the schema, {}", mismatches.join("; "));
+    warn!("{}", msg);
+    Err(VcxError::from_msg(error::INVALID_ATTRIBUTES_STRUCTURE.code_num, msg))
+}
+
+pub fn convert_to_map(s:&str) -> VcxResult<serde_json::Map<String, serde_json::Value>>{
     let v:serde_json::Map<String, serde_json::Value> = match serde_json::from_str(s) {
         Ok(m) => m,
         Err(_) => {
             warn!("{}", error::INVALID_ATTRIBUTES_STRUCTURE.message);
-            return Err(IssuerCredError::CommonError(error::INVALID_ATTRIBUTES_STRUCTURE.code_num))
+            return Err(VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num))
         },
     };
     Ok(v)
 }
 
-pub fn get_credential_attributes(handle:u32) -> Result<String, u32> {
+pub fn get_credential_attributes(handle:u32) -> VcxResult<String> {
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
         Ok(i.get_credential_attributes().clone())
     })
 }
-pub fn get_source_id(handle: u32) -> Result<String, u32> {
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     ISSUER_CREDENTIAL_MAP.get(handle,|i|{
         Ok(i.get_source_id().clone())
     })
@@ -597,7 +704,6 @@ pub mod tests {
                                        libindy_prover_create_credential_req },
                           wallet::get_wallet_handle, wallet},
     };
-    use error::{ issuer_cred::IssuerCredError };
 
     static DEFAULT_CREDENTIAL_NAME: &str = "Credential";
     static DEFAULT_CREDENTIAL_ID: &str = "defaultCredentialId";
@@ -655,6 +761,9 @@ pub mod tests {
             agent_did: DID.to_string(),
             agent_vk: VERKEY.to_string(),
             cred_def_id: CRED_DEF_ID.to_string(),
+            rev_reg_id: None,
+            tails_file: None,
+            cred_rev_id: None,
         };
         issuer_credential
     }
@@ -699,6 +808,9 @@ pub mod tests {
             remote_vk: String::new(),
             agent_did: String::new(),
             agent_vk: String::new(),
+            rev_reg_id: None,
+            tails_file: None,
+            cred_rev_id: None,
             cred_def_id
         };
 
@@ -808,7 +920,7 @@ pub mod tests {
 
         set_libindy_rc(error::TIMEOUT_LIBINDY_ERROR.code_num);
         assert_eq!(credential.send_credential(connection_handle),
-                   Err(IssuerCredError::CommonError(error::TIMEOUT_LIBINDY_ERROR.code_num)));
+                   Err(VcxError::from(error::TIMEOUT_LIBINDY_ERROR.code_num)));
         assert_eq!(credential.msg_uid, "1234");
         assert_eq!(credential.state, VcxStateType::VcxStateRequestReceived);
         // Retry sending the credential, use the mocked http. Show that you can retry sending the credential
@@ -863,6 +975,9 @@ pub mod tests {
             remote_vk: VERKEY.to_string(),
             agent_did: DID.to_string(),
             agent_vk: VERKEY.to_string(),
+            rev_reg_id: None,
+            tails_file: None,
+            cred_rev_id: None,
         };
 
         ::utils::httpclient::set_next_u8_response(CREDENTIAL_REQ_RESPONSE.to_vec());
@@ -908,10 +1023,24 @@ pub mod tests {
                 error!("basic_add_attribute_encoding test should raise error.");
                 assert_ne!(1, 1);
             },
-            Err(e) => assert_eq!(e, IssuerCredError::CommonError(error::INVALID_JSON.code_num)),
+            Err(e) => assert_eq!(e, VcxError::from(error::INVALID_JSON.code_num)),
         }
     }
 
+    #[test]
+    fn test_encode_attributes() {
+        let encoded: Value = serde_json::from_str(&encode_attributes(r#"{"name":["Alex"],"age":28,"height":"175","member":true,"nickname":null}"#).unwrap()).unwrap();
+        assert_eq!(encoded["name"], json!({"raw": "Alex", "encoded": "99262857098057710338306967609588410025648622308394250666849665532448612202874"}));
+        assert_eq!(encoded["age"], json!({"raw": "28", "encoded": "28"}));
+        assert_eq!(encoded["height"], json!({"raw": "175", "encoded": "175"}));
+        assert_eq!(encoded["member"], json!({"raw": "true", "encoded": "82205459161612687361280696578706529610747648852743065596896330207015226302763"}));
+        assert_eq!(encoded["nickname"], json!({"raw": "null", "encoded": "52530672535577884712458350945238153986666188697374769927409463847120313432331"}));
+
+        assert_eq!(encode_attributes(r#"{"name":[]}"#).unwrap_err(), VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num));
+        assert_eq!(encode_attributes(r#"{"name":["Alex","Bob"]}"#).unwrap_err(), VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num));
+        assert_eq!(encode_attributes(r#"{"name":{"first":"Alex"}}"#).unwrap_err(), VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num));
+    }
+
     #[test]
     fn test_that_test_mode_enabled_bypasses_libindy_create_credential(){
         init!("true");
@@ -934,23 +1063,38 @@ pub mod tests {
         let h4 = issuer_credential_create(CRED_DEF_ID.to_string(),"1".to_string(),"8XFh8yBzrpJQmNyZzgoTqB".to_owned(),"credential_name".to_string(),"{\"attr\":\"value\"}".to_owned(),1).unwrap();
         let h5 = issuer_credential_create(CRED_DEF_ID.to_string(),"1".to_string(),"8XFh8yBzrpJQmNyZzgoTqB".to_owned(),"credential_name".to_string(),"{\"attr\":\"value\"}".to_owned(),1).unwrap();
         release_all();
-        assert_eq!(release(h1),Err(IssuerCredError::InvalidHandle()));
-        assert_eq!(release(h2),Err(IssuerCredError::InvalidHandle()));
-        assert_eq!(release(h3),Err(IssuerCredError::InvalidHandle()));
-        assert_eq!(release(h4),Err(IssuerCredError::InvalidHandle()));
-        assert_eq!(release(h5),Err(IssuerCredError::InvalidHandle()));
+        assert_eq!(release(h1),Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
+        assert_eq!(release(h2),Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
+        assert_eq!(release(h3),Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
+        assert_eq!(release(h4),Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
+        assert_eq!(release(h5),Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
     }
 
     #[test]
     fn test_errors(){
         init!("false");
         let invalid_handle = 478620;
-        assert_eq!(to_string(invalid_handle).err(), Some(IssuerCredError::CommonError(error::INVALID_OBJ_HANDLE.code_num)));
-        assert_eq!(release(invalid_handle).err(), Some(IssuerCredError::InvalidHandle()));
+        assert_eq!(to_string(invalid_handle).err(), Some(VcxError::from(error::INVALID_OBJ_HANDLE.code_num)));
+        assert_eq!(release(invalid_handle).err(), Some(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
+    }
+
+    #[test]
+    fn test_attributes_must_match_schema() {
+        let schema_attrs: Vec<String> = serde_json::from_str(DEFAULT_SCHEMA_ATTRS).unwrap();
+        assert!(check_attributes_match_schema(&schema_attrs, CREDENTIAL_DATA).is_ok());
+
+        let err = check_attributes_match_schema(&schema_attrs, r#"{"address1":["101 Tela Lane"],"zip":["87121"],"city":["SLC"],"state":["UT"],"country":["US"]}"#).unwrap_err();
+        assert_eq!(err.code(), error::INVALID_ATTRIBUTES_STRUCTURE.code_num);
+        assert!(err.message().contains("missing attributes: address2"));
+        assert!(err.message().contains("attributes not in the schema: country"));
+
+        let err = check_attributes_match_schema(&schema_attrs, "not json").unwrap_err();
+        assert_eq!(err.code(), error::INVALID_ATTRIBUTES_STRUCTURE.code_num);
     }
 
     #[test]
     fn test_encoding(){
+        init!("true");
         let issuer_credential_handle = self::issuer_credential_create(CRED_DEF_ID.to_string(),
                                                                       "IssuerCredentialName".to_string(),
                                                                       "000000000000000000000000Issuer02".to_string(),
@@ -990,11 +1134,11 @@ pub mod tests {
 
         // Err - Wrong payment amount
         credential.price = 200;
-        assert_eq!(credential.verify_payment(), Err(error::INSUFFICIENT_TOKEN_AMOUNT.code_num));
+        assert_eq!(credential.verify_payment(), Err(VcxError::from_code(error::INSUFFICIENT_TOKEN_AMOUNT.code_num)));
 
         // Err - address not set
         credential.payment_address = None;
-        assert_eq!(credential.verify_payment(), Err(error::INVALID_PAYMENT_ADDRESS.code_num));
+        assert_eq!(credential.verify_payment(), Err(VcxError::from_code(error::INVALID_PAYMENT_ADDRESS.code_num)));
     }
 
     #[test]
@@ -1019,4 +1163,31 @@ pub mod tests {
         let payment = serde_json::to_string(&credential.get_payment_txn().unwrap()).unwrap();
         assert!(payment.len() > 20);
     }
+
+    #[test]
+    fn test_revoke_credential() {
+        init!("true");
+        let mut credential = create_standard_issuer_credential();
+
+        // Not issued yet
+        credential.rev_reg_id = Some(REV_REG_ID.to_string());
+        credential.tails_file = Some(TEST_TAILS_FILE.to_string());
+        credential.cred_rev_id = Some("1".to_string());
+        assert_eq!(credential.revoke_cred(), Err(VcxError::from_code(error::NOT_READY.code_num)));
+
+        // Cred def doesn't support revocation
+        credential.state = VcxStateType::VcxStateAccepted;
+        credential.rev_reg_id = None;
+        assert_eq!(credential.revoke_cred(), Err(VcxError::from_code(error::INVALID_REVOCATION_DETAILS.code_num)));
+        assert_eq!(credential.state, VcxStateType::VcxStateAccepted);
+
+        // Libindy failure leaves the credential accepted
+        credential.rev_reg_id = Some(REV_REG_ID.to_string());
+        set_libindy_rc(error::TIMEOUT_LIBINDY_ERROR.code_num);
+        assert_eq!(credential.revoke_cred(), Err(VcxError::from(error::TIMEOUT_LIBINDY_ERROR.code_num)));
+        assert_eq!(credential.state, VcxStateType::VcxStateAccepted);
+
+        credential.revoke_cred().unwrap();
+        assert_eq!(credential.state, VcxStateType::VcxStateRevoked);
+    }
 }
@@ -0,0 +1,387 @@
+extern crate serde_json;
+
+use std::sync::{ Arc, Mutex };
+use serde_json::{ Map, Value };
+use api::VcxStateType;
+use connection;
+use issuer_credential;
+use object_cache::ObjectCache;
+use error::{ VcxError, VcxResult };
+use utils::error;
+use utils::threadpool::spawn;
+
+lazy_static! {
+    static ref BATCH_MAP: ObjectCache<IssuerCredentialBatch> = Default::default();
+}
+
+/// A recipient of the batch, either a bare connection handle or a connection handle
+/// with attribute values that override the batch template for that connection only.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum BatchRecipient {
+    Connection(u32),
+    WithAttributes {
+        connection_handle: u32,
+        #[serde(default)]
+        attributes: Map<String, Value>,
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BatchEntry {
+    pub connection_handle: u32,
+    pub credential_handle: u32,
+    pub state: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+impl BatchEntry {
+    fn record(&mut self, result: VcxResult<u32>) {
+        match result {
+            Ok(state) => {
+                self.state = state;
+                self.error_code = None;
+                self.error_message = None;
+            },
+            Err(ec) => {
+                self.error_code = Some(ec.code());
+                self.error_message = Some(ec.message().to_string());
+            },
+        }
+    }
+}
+
+/// Progress of a batch as reported to the caller after every operation.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BatchStatus {
+    pub source_id: String,
+    pub total: usize,
+    pub offers_sent: usize,
+    pub requests_received: usize,
+    pub issued: usize,
+    pub failed: usize,
+    pub entries: Vec<BatchEntry>,
+}
+
+#[derive(Debug)]
+pub struct IssuerCredentialBatch {
+    source_id: String,
+    entries: Vec<BatchEntry>,
+}
+
+impl IssuerCredentialBatch {
+    fn count(&self, state: VcxStateType) -> usize {
+        self.entries.iter().filter(|e| e.error_code.is_none() && e.state == state as u32).count()
+    }
+
+    fn get_status(&self) -> BatchStatus {
+        BatchStatus {
+            source_id: self.source_id.clone(),
+            total: self.entries.len(),
+            offers_sent: self.count(VcxStateType::VcxStateOfferSent),
+            requests_received: self.count(VcxStateType::VcxStateRequestReceived),
+            issued: self.count(VcxStateType::VcxStateAccepted),
+            failed: self.entries.iter().filter(|e| e.error_code.is_some()).count(),
+            entries: self.entries.clone(),
+        }
+    }
+
+    /// Entries that still have an issuer credential to work on, including ones whose last step failed.
+    fn pending_entries(&self, states: &[VcxStateType]) -> Vec<(usize, BatchEntry)> {
+        self.entries.iter()
+            .enumerate()
+            .filter(|&(_, e)| e.credential_handle != 0 && states.iter().any(|s| *s as u32 == e.state))
+            .map(|(i, e)| (i, e.clone()))
+            .collect()
+    }
+}
+
+fn merge_attributes(template: &Map<String, Value>, overrides: &Map<String, Value>) -> String {
+    let mut attributes = template.clone();
+    for (name, value) in overrides.iter() {
+        attributes.insert(name.clone(), value.clone());
+    }
+    Value::Object(attributes).to_string()
+}
+
+/// Creates one issuer credential per recipient from the attribute template.
+/// Recipients that cannot be used are recorded as failed entries rather than failing the batch.
+pub fn create(source_id: String,
+              cred_def_id: String,
+              issuer_did: String,
+              credential_name: String,
+              attributes_template: &str,
+              recipients: &str,
+              price: u64) -> VcxResult<u32> {
+    let template: Map<String, Value> = serde_json::from_str(attributes_template)
+        .or(Err(VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num)))?;
+    let recipients: Vec<BatchRecipient> = serde_json::from_str(recipients)
+        .or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+
+    let no_overrides = Map::new();
+    let entries = recipients.iter().map(|recipient| {
+        let (connection_handle, overrides) = match *recipient {
+            BatchRecipient::Connection(handle) => (handle, &no_overrides),
+            BatchRecipient::WithAttributes { connection_handle, ref attributes } => (connection_handle, attributes),
+        };
+
+        let mut entry = BatchEntry {
+            connection_handle,
+            credential_handle: 0,
+            state: VcxStateType::VcxStateNone as u32,
+            error_code: None,
+            error_message: None,
+        };
+
+        if !connection::is_valid_handle(connection_handle) {
+            entry.record(Err(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num)));
+            return entry;
+        }
+
+        match issuer_credential::issuer_credential_create(cred_def_id.clone(),
+                                                          format!("{}-{}", source_id, connection_handle),
+                                                          issuer_did.clone(),
+                                                          credential_name.clone(),
+                                                          merge_attributes(&template, overrides),
+                                                          price) {
+            Ok(handle) => {
+                entry.credential_handle = handle;
+                entry.record(issuer_credential::get_state(handle));
+            },
+            Err(err) => entry.record(Err(err)),
+        }
+        entry
+    }).collect();
+
+    let batch = IssuerCredentialBatch { source_id, entries };
+    debug!("creating issuer credential batch {} for {} connections", batch.source_id, batch.entries.len());
+
+    BATCH_MAP.add(batch).map_err(|_| VcxError::from_code(error::OBJECT_CACHE_ERROR.code_num))
+}
+
+struct Pending<D> {
+    remaining: usize,
+    done: Option<D>,
+}
+
+/// Runs `work` for every entry on the threadpool and calls `done` with the batch status once
+/// the last of them has finished. Nothing blocks waiting for the jobs, so this is safe to call
+/// from a threadpool thread.
+fn run_concurrently<W, D>(handle: u32, entries: Vec<(usize, BatchEntry)>, work: W, done: D)
+    where W: Fn(&BatchEntry) -> VcxResult<u32> + Send + Sync + 'static,
+          D: FnOnce(VcxResult<BatchStatus>) + Send + 'static {
+    if entries.is_empty() {
+        done(get_status(handle));
+        return;
+    }
+
+    let work = Arc::new(work);
+    let pending = Arc::new(Mutex::new(Pending { remaining: entries.len(), done: Some(done) }));
+
+    for (index, entry) in entries {
+        let work = work.clone();
+        let pending = pending.clone();
+
+        spawn(move || {
+            let result = work(&entry);
+            if let Err(ref ec) = result {
+                warn!("batch entry for connection {} failed: {}", entry.connection_handle, ec);
+            }
+
+            let _ = BATCH_MAP.get_mut(handle, |batch| {
+                if let Some(e) = batch.entries.get_mut(index) {
+                    e.record(result.clone());
+                }
+                Ok(())
+            });
+
+            let done = match pending.lock() {
+                Ok(mut p) => {
+                    p.remaining -= 1;
+                    if p.remaining == 0 { p.done.take() } else { None }
+                },
+                Err(_) => None,
+            };
+
+            if let Some(done) = done {
+                done(get_status(handle));
+            }
+
+            Ok(())
+        });
+    }
+}
+
+/// Sends the credential offer for every entry that has not received one yet.
+/// Entries whose offer failed are retried when this is called again.
+pub fn send_offers<D>(handle: u32, done: D) -> VcxResult<()>
+    where D: FnOnce(VcxResult<BatchStatus>) + Send + 'static {
+    let entries = BATCH_MAP.get(handle, |batch| {
+        Ok(batch.pending_entries(&[VcxStateType::VcxStateInitialized]))
+    }).or(Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)))?;
+
+    run_concurrently(handle, entries, |entry| {
+        issuer_credential::send_credential_offer(entry.credential_handle, entry.connection_handle)?;
+        issuer_credential::get_state(entry.credential_handle)
+    }, done);
+
+    Ok(())
+}
+
+/// Polls every outstanding offer and sends the credential to each connection whose
+/// credential request has arrived.
+pub fn update_state<D>(handle: u32, done: D) -> VcxResult<()>
+    where D: FnOnce(VcxResult<BatchStatus>) + Send + 'static {
+    let entries = BATCH_MAP.get(handle, |batch| {
+        Ok(batch.pending_entries(&[VcxStateType::VcxStateOfferSent, VcxStateType::VcxStateRequestReceived]))
+    }).or(Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)))?;
+
+    run_concurrently(handle, entries, |entry| {
+        let state = issuer_credential::update_state(entry.credential_handle)?;
+
+        if state == VcxStateType::VcxStateRequestReceived as u32 {
+            issuer_credential::send_credential(entry.credential_handle, entry.connection_handle)?;
+        }
+        issuer_credential::get_state(entry.credential_handle)
+    }, done);
+
+    Ok(())
+}
+
+pub fn get_status(handle: u32) -> VcxResult<BatchStatus> {
+    BATCH_MAP.get(handle, |batch| {
+        Ok(batch.get_status())
+    }).or(Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)))
+}
+
+pub fn get_status_json(handle: u32) -> VcxResult<String> {
+    let status = get_status(handle)?;
+    serde_json::to_string(&status).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
+}
+
+/// Releases the batch together with the issuer credentials it created.
+pub fn release(handle: u32) -> VcxResult<()> {
+    let status = get_status(handle)?;
+    for entry in status.entries.iter().filter(|e| e.credential_handle != 0) {
+        let _ = issuer_credential::release(entry.credential_handle);
+    }
+    BATCH_MAP.release(handle).or(Err(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)))
+}
+
+pub fn release_all() {
+    match BATCH_MAP.drain() {
+        Ok(_) => (),
+        Err(_) => (),
+    };
+}
+
+pub fn is_valid_handle(handle: u32) -> bool {
+    BATCH_MAP.has_handle(handle)
+}
+
+/// The context the batch was created in, calls on the batch run in it.
+pub fn get_context(handle: u32) -> u32 {
+    BATCH_MAP.context_of(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+    use connection::build_connection;
+    use utils::constants::CRED_DEF_ID;
+
+    static ISSUER_DID: &str = "8XFh8yBzrpJQmNyZzgoTqB";
+    static TEMPLATE: &str = r#"{"name":["Alice"],"degree":["Maths"]}"#;
+
+    fn create_batch(recipients: &str) -> u32 {
+        create("batch".to_string(),
+               CRED_DEF_ID.to_string(),
+               ISSUER_DID.to_string(),
+               "Diploma".to_string(),
+               TEMPLATE,
+               recipients,
+               0).unwrap()
+    }
+
+    #[test]
+    fn test_create_batch_with_overrides() {
+        init!("true");
+        let first = build_connection("batch_first").unwrap();
+        let second = build_connection("batch_second").unwrap();
+
+        let recipients = json!([first, {"connection_handle": second, "attributes": {"name": ["Bob"]}}]).to_string();
+        let handle = create_batch(&recipients);
+
+        let status = get_status(handle).unwrap();
+        assert_eq!(status.total, 2);
+        assert_eq!(status.failed, 0);
+
+        let attrs: Value = serde_json::from_str(&issuer_credential::get_credential_attributes(status.entries[1].credential_handle).unwrap()).unwrap();
+        assert_eq!(attrs, json!({"name": ["Bob"], "degree": ["Maths"]}));
+        let attrs: Value = serde_json::from_str(&issuer_credential::get_credential_attributes(status.entries[0].credential_handle).unwrap()).unwrap();
+        assert_eq!(attrs, json!({"name": ["Alice"], "degree": ["Maths"]}));
+    }
+
+    #[test]
+    fn test_invalid_connection_is_reported() {
+        init!("true");
+        let connection_handle = build_connection("batch_invalid").unwrap();
+        let recipients = json!([connection_handle, 1]).to_string();
+        let handle = create_batch(&recipients);
+
+        let status = get_status(handle).unwrap();
+        assert_eq!(status.failed, 1);
+        assert_eq!(status.entries[1].error_code, Some(error::INVALID_CONNECTION_HANDLE.code_num));
+        assert_eq!(status.entries[1].credential_handle, 0);
+    }
+
+    #[test]
+    fn test_send_offers() {
+        init!("true");
+        let recipients: Vec<u32> = (0..5).map(|i| build_connection(&format!("batch_offer_{}", i)).unwrap()).collect();
+        let handle = create_batch(&json!(recipients).to_string());
+
+        let (sender, receiver) = channel();
+        send_offers(handle, move |status| { sender.send(status).unwrap(); }).unwrap();
+        let status = receiver.recv_timeout(Duration::from_secs(10)).unwrap().unwrap();
+
+        assert_eq!(status.total, 5);
+        assert_eq!(status.offers_sent, 5);
+        assert_eq!(status.failed, 0);
+        assert!(status.entries.iter().all(|e| e.state == VcxStateType::VcxStateOfferSent as u32));
+
+        // nothing left to offer, the status is reported straight away
+        let (sender, receiver) = channel();
+        send_offers(handle, move |status| { sender.send(status).unwrap(); }).unwrap();
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(1)).unwrap().unwrap().offers_sent, 5);
+    }
+
+    #[test]
+    fn test_release_batch() {
+        init!("true");
+        let connection_handle = build_connection("batch_release").unwrap();
+        let handle = create_batch(&json!([connection_handle]).to_string());
+        let credential_handle = get_status(handle).unwrap().entries[0].credential_handle;
+
+        release(handle).unwrap();
+        assert!(!is_valid_handle(handle));
+        assert!(!issuer_credential::is_valid_handle(credential_handle));
+        assert_eq!(get_status(handle).err(), Some(VcxError::from_code(error::INVALID_ISSUER_CREDENTIAL_HANDLE.code_num)));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        init!("true");
+        assert_eq!(create("batch".to_string(), CRED_DEF_ID.to_string(), ISSUER_DID.to_string(),
+                          "Diploma".to_string(), "[]", "[]", 0).err(),
+                   Some(VcxError::from(error::INVALID_ATTRIBUTES_STRUCTURE.code_num)));
+        assert_eq!(create("batch".to_string(), CRED_DEF_ID.to_string(), ISSUER_DID.to_string(),
+                          "Diploma".to_string(), TEMPLATE, "{}", 0).err(),
+                   Some(VcxError::from_code(error::INVALID_JSON.code_num)));
+    }
+}
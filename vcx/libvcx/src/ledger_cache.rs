@@ -0,0 +1,250 @@
+extern crate serde_json;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{ SystemTime, UNIX_EPOCH };
+use settings;
+use utils::error;
+use error::VcxResult;
+use utils::libindy::wallet;
+
+// Schemas and credential definitions never change once they are written to the ledger, caching
+// them saves a pool request for every credential and proof using them. Entries live for
+// ledger_cache_ttl_secs, with ledger_cache_persist they are stored in the wallet as well so they
+// survive restarts and can be used while the pool is unreachable.
+
+// Persisted entries cached before this time are ignored
+static CLEARED_RECORD_TYPE: &str = "vcx_ledger_cache";
+static CLEARED_RECORD_ID: &str = "cleared_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LedgerObject {
+    Schema,
+    CredDef,
+}
+
+impl LedgerObject {
+    fn record_type(&self) -> &'static str {
+        match *self {
+            LedgerObject::Schema => "vcx_ledger_schema",
+            LedgerObject::CredDef => "vcx_ledger_cred_def",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Entry {
+    id: String,
+    json: String,
+    cached_at: u64,
+}
+
+lazy_static! {
+    // Every context may be connected to a different pool
+    static ref CACHE: RwLock<HashMap<(u32, LedgerObject, String), Entry>> = Default::default();
+}
+
+fn now_ms() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs() * 1000 + now.subsec_nanos() as u64 / 1_000_000
+}
+
+fn ttl_ms() -> u64 {
+    settings::get_ledger_cache_ttl_secs() * 1000
+}
+
+/// Caching is enabled by a ledger_cache_ttl_secs greater than 0.
+pub fn is_enabled() -> bool {
+    ttl_ms() > 0
+}
+
+fn is_persisted() -> bool {
+    match settings::get_config_value(settings::CONFIG_LEDGER_CACHE_PERSIST) {
+        Ok(value) => value == "true",
+        Err(_) => false,
+    }
+}
+
+fn is_fresh(entry: &Entry) -> bool {
+    entry.cached_at + ttl_ms() > now_ms()
+}
+
+fn get_record_value(record_type: &str, id: &str) -> Option<String> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let record = wallet::get_record(record_type, id, &options).ok()?;
+    let record: serde_json::Value = serde_json::from_str(&record).ok()?;
+    record["value"].as_str().map(|value| value.to_string())
+}
+
+fn set_record_value(record_type: &str, id: &str, value: &str) -> VcxResult<()> {
+    match wallet::update_record_value(record_type, id, value) {
+        Ok(_) => Ok(()),
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => wallet::add_record(record_type, id, value, "{}"),
+        Err(x) => Err(x),
+    }
+}
+
+fn get_persisted(object: LedgerObject, id: &str) -> Option<Entry> {
+    let entry: Entry = serde_json::from_str(&get_record_value(object.record_type(), id)?).ok()?;
+
+    let cleared_at = get_record_value(CLEARED_RECORD_TYPE, CLEARED_RECORD_ID)
+        .and_then(|cleared_at| cleared_at.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    if entry.cached_at <= cleared_at { None } else { Some(entry) }
+}
+
+/// The id and json of the ledger object, if it was cached less than the ttl ago.
+pub fn get(object: LedgerObject, id: &str) -> Option<(String, String)> {
+    if !is_enabled() { return None; }
+
+    let key = (::context::current(), object, id.to_string());
+
+    let cached = CACHE.read().unwrap().get(&key).cloned();
+    let entry = match cached {
+        Some(entry) => entry,
+        None if is_persisted() => {
+            let entry = get_persisted(object, id)?;
+            CACHE.write().unwrap().insert(key, entry.clone());
+            entry
+        },
+        None => return None,
+    };
+
+    if !is_fresh(&entry) {
+        debug!("cached {:?} {} expired", object, id);
+        return None;
+    }
+
+    trace!("using cached {:?} {}", object, id);
+    Some((entry.id, entry.json))
+}
+
+/// Caches the id and json of a ledger object that was just retrieved.
+pub fn put(object: LedgerObject, id: &str, retrieved: &(String, String)) {
+    if !is_enabled() { return; }
+
+    let entry = Entry { id: retrieved.0.clone(), json: retrieved.1.clone(), cached_at: now_ms() };
+
+    if is_persisted() {
+        let value = json!(entry).to_string();
+        if let Err(err) = set_record_value(object.record_type(), id, &value) {
+            warn!("could not store {:?} {} in the ledger cache: {}", object, id, err);
+        }
+    }
+
+    CACHE.write().unwrap().insert((::context::current(), object, id.to_string()), entry);
+}
+
+/// Drops the schema or credential definition with this id from the cache.
+pub fn invalidate(id: &str) {
+    let context = ::context::current();
+    CACHE.write().unwrap().retain(|&(ref c, _, ref i), _| !(*c == context && i == id));
+
+    if is_persisted() {
+        for object in [LedgerObject::Schema, LedgerObject::CredDef].iter() {
+            match wallet::delete_record(object.record_type(), id) {
+                Ok(_) => (),
+                Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => (),
+                Err(x) => warn!("could not remove {:?} {} from the ledger cache: {}", object, id, x),
+            }
+        }
+    }
+}
+
+/// Drops every cached ledger object of the current context.
+pub fn clear() {
+    let context = ::context::current();
+    CACHE.write().unwrap().retain(|&(ref c, _, _), _| *c != context);
+
+    if is_persisted() {
+        if let Err(err) = set_record_value(CLEARED_RECORD_TYPE, CLEARED_RECORD_ID, &now_ms().to_string()) {
+            warn!("could not clear the persisted ledger cache: {}", err);
+        }
+    }
+}
+
+/// Forgets the in-memory entries of the current context, called when the context is released.
+pub fn release_all() {
+    let context = ::context::current();
+    CACHE.write().unwrap().retain(|&(ref c, _, _), _| *c != context);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::constants::{ SCHEMA_ID, SCHEMA_JSON, CRED_DEF_ID, CRED_DEF_JSON };
+
+    fn schema() -> (String, String) { (SCHEMA_ID.to_string(), SCHEMA_JSON.to_string()) }
+
+    #[test]
+    fn test_cache_disabled_by_default() {
+        init!("true");
+        put(LedgerObject::Schema, SCHEMA_ID, &schema());
+        assert_eq!(get(LedgerObject::Schema, SCHEMA_ID), None);
+    }
+
+    #[test]
+    fn test_cache_get_put_and_invalidate() {
+        init!("true");
+        settings::set_config_value(settings::CONFIG_LEDGER_CACHE_TTL_SECS, "60");
+
+        assert_eq!(get(LedgerObject::Schema, SCHEMA_ID), None);
+        put(LedgerObject::Schema, SCHEMA_ID, &schema());
+        put(LedgerObject::CredDef, CRED_DEF_ID, &(CRED_DEF_ID.to_string(), CRED_DEF_JSON.to_string()));
+        assert_eq!(get(LedgerObject::Schema, SCHEMA_ID), Some(schema()));
+        assert_eq!(get(LedgerObject::CredDef, SCHEMA_ID), None);
+
+        invalidate(SCHEMA_ID);
+        assert_eq!(get(LedgerObject::Schema, SCHEMA_ID), None);
+        assert!(get(LedgerObject::CredDef, CRED_DEF_ID).is_some());
+
+        clear();
+        assert_eq!(get(LedgerObject::CredDef, CRED_DEF_ID), None);
+    }
+
+    #[test]
+    fn test_cache_expires() {
+        init!("true");
+        settings::set_config_value(settings::CONFIG_LEDGER_CACHE_TTL_SECS, "60");
+
+        let key = (::context::current(), LedgerObject::Schema, "expired_schema".to_string());
+        let entry = Entry { id: SCHEMA_ID.to_string(), json: SCHEMA_JSON.to_string(), cached_at: now_ms() - 61_000 };
+        CACHE.write().unwrap().insert(key, entry);
+
+        assert_eq!(get(LedgerObject::Schema, "expired_schema"), None);
+    }
+
+    #[test]
+    fn test_cache_entries_are_per_context() {
+        init!("true");
+        settings::set_config_value(settings::CONFIG_LEDGER_CACHE_TTL_SECS, "60");
+        put(LedgerObject::Schema, "context_schema", &schema());
+
+        let context = ::context::create();
+        ::context::with_context(context, || {
+            settings::set_config_value(settings::CONFIG_LEDGER_CACHE_TTL_SECS, "60");
+            assert_eq!(get(LedgerObject::Schema, "context_schema"), None);
+            settings::clear_config();
+        });
+        ::context::release(context).unwrap();
+
+        assert_eq!(get(LedgerObject::Schema, "context_schema"), Some(schema()));
+    }
+
+    #[test]
+    fn test_persisted_cache_survives_restart() {
+        init!("false");
+        settings::set_config_value(settings::CONFIG_LEDGER_CACHE_TTL_SECS, "60");
+        settings::set_config_value(settings::CONFIG_LEDGER_CACHE_PERSIST, "true");
+
+        put(LedgerObject::Schema, SCHEMA_ID, &schema());
+        release_all();
+        assert_eq!(get(LedgerObject::Schema, SCHEMA_ID), Some(schema()));
+
+        clear();
+        assert_eq!(get(LedgerObject::Schema, SCHEMA_ID), None);
+
+        teardown!("false");
+    }
+}
@@ -24,20 +24,27 @@ extern crate lazy_static;
 #[macro_use]
 pub mod utils;
 pub mod settings;
+pub mod context;
 pub mod messages;
 
 pub mod api;
 pub mod connection;
 pub mod issuer_credential;
+pub mod issuer_credential_batch;
 pub mod credential_request;
 pub mod proof;
 pub mod schema;
 pub mod credential_def;
 pub mod proof_compliance;
+pub mod proof_template;
 pub mod error;
 pub mod credential;
 pub mod object_cache;
+pub mod object_store;
+pub mod ledger_cache;
 pub mod disclosed_proof;
+pub mod credential_selection;
+pub mod dispatcher;
 
 #[allow(unused_imports)]
 #[cfg(test)]
@@ -140,7 +147,7 @@ mod tests {
             attrs.as_array_mut().unwrap().push(json!({ "name":format!("key{}", i), "restrictions": [restrictions]}));
         }
         let requested_attrs = attrs.to_string();
-        let proof_req_handle = proof::create_proof("1".to_string(), requested_attrs, "[]".to_string(), "name".to_string()).unwrap();
+        let proof_req_handle = proof::create_proof("1".to_string(), requested_attrs, "[]".to_string(), "{}".to_string(), "name".to_string()).unwrap();
         println!("sending proof request");
         proof::send_proof_request(proof_req_handle, alice).unwrap();
         thread::sleep(Duration::from_millis(2000));
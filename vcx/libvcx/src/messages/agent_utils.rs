@@ -9,6 +9,7 @@ use serde_json;
 use settings;
 use utils::constants::*;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use utils::libindy::wallet;
 use utils::libindy::signus::create_and_store_my_did;
 use utils::httpclient;
@@ -78,10 +79,10 @@ pub struct Config {
     path: Option<String>,
 }
 
-pub fn connect_register_provision(config: &str) -> Result<String,u32> {
+pub fn connect_register_provision(config: &str) -> VcxResult<String> {
 
     trace!("***Registering with agency");
-    let my_config: Config = serde_json::from_str(&config).or(Err(error::INVALID_CONFIGURATION.code_num))?;
+    let my_config: Config = serde_json::from_str(&config).or(Err(VcxError::from_code(error::INVALID_CONFIGURATION.code_num)))?;
 
     let (wallet_name_string, wallet_name) = match my_config.wallet_name {
         Some(x) => (format!("\"wallet_name\":\"{}\",", x), x),
@@ -136,17 +137,17 @@ pub fn connect_register_provision(config: &str) -> Result<String,u32> {
         from_vk: my_vk.to_string(),
     };
     let data = Bundled::create(
-        encode::to_vec_named(&payload).or(Err(error::UNKNOWN_ERROR.code_num))?
+        encode::to_vec_named(&payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?
     ).encode()?;
     let data = bundle_for_agency(data, &my_config.agency_did)?;
     let data = unbundle_from_agency(
-        httpclient::post_u8(&data).map_err(|e|error::INVALID_HTTP_RESPONSE.code_num)?
+        httpclient::post_u8(&data)?
     )?;
 
     trace!("deserializing connect response: {:?}", data);
     let mut de = Deserializer::new(&data[0][..]);
-    let response: ConnectResponseMsg = Deserialize::deserialize(&mut de).map_err(|ec| {error::INVALID_OPTION.code_num})?;
-    //self.my_vk = Some(connection::get_pw_verkey(connection_handle).map_err(|ec| CredentialError::CommonError(ec.to_error_code()))?);
+    let response: ConnectResponseMsg = Deserialize::deserialize(&mut de).map_err(|ec| VcxError::from_code(error::INVALID_OPTION.code_num))?;
+    //self.my_vk = Some(connection::get_pw_verkey(connection_handle)?);
     let agency_pw_vk = response.from_vk.to_owned();
     let agency_pw_did = response.from_did.to_owned();
 
@@ -159,19 +160,19 @@ pub fn connect_register_provision(config: &str) -> Result<String,u32> {
     };
 
     let data = encode::to_vec_named(&payload)
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
     let data = Bundled::create(data)
         .encode()
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
     let data = bundle_for_agency(data, &agency_pw_did)?;
     let data = unbundle_from_agency(
-        httpclient::post_u8(&data).map_err(|e|error::INVALID_HTTP_RESPONSE.code_num)?
+        httpclient::post_u8(&data)?
     )?;
 
     trace!("deserializing register response: {:?}", data);
     let mut de = Deserializer::new(&data[0][..]);
     let response: RegisterResponse = Deserialize::deserialize(&mut de)
-        .map_err(|e|error::INVALID_HTTP_RESPONSE.code_num)?;
+        .map_err(|e|VcxError::from_code(error::INVALID_HTTP_RESPONSE.code_num))?;
 
     /* STEP 3 - CREATE AGENT */
     let payload = GenericMsg {
@@ -179,18 +180,18 @@ pub fn connect_register_provision(config: &str) -> Result<String,u32> {
     };
 
     let data = encode::to_vec_named(&payload)
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
     let data = Bundled::create(data).encode()
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
     let data = bundle_for_agency(data, &agency_pw_did)?;
     let data = unbundle_from_agency(
-        httpclient::post_u8(&data).map_err(|e|error::INVALID_HTTP_RESPONSE.code_num)?
+        httpclient::post_u8(&data)?
     )?;
 
     trace!("deserializing provision response: {:?}", data);
     let mut de = Deserializer::new(&data[0][..]);
     let response: ConnectResponseMsg = Deserialize::deserialize(&mut de)
-        .map_err(|e|error::INVALID_HTTP_RESPONSE.code_num)?;
+        .map_err(|e|VcxError::from_code(error::INVALID_HTTP_RESPONSE.code_num))?;
     let agent_did = response.from_did;
     let agent_vk = response.from_vk;
 
@@ -219,7 +220,7 @@ pub fn connect_register_provision(config: &str) -> Result<String,u32> {
     Ok(final_config.to_string())
 }
 
-pub fn update_agent_info(id: &str, value: &str) -> Result<(), u32> {
+pub fn update_agent_info(id: &str, value: &str) -> VcxResult<()> {
     let new_config = UpdateAgentMsg {
         msg_type: MsgType { name: "UPDATE_COM_METHOD".to_string(), ver: "1.0".to_string(), },
         com_method: ComMethod { id: id.to_string(), e_type: 1, value: value.to_string(), },
@@ -233,15 +234,15 @@ pub fn update_agent_info(id: &str, value: &str) -> Result<(), u32> {
     let endpoint = settings::get_config_value(settings::CONFIG_AGENCY_ENDPOINT)?;
 
     let data = encode::to_vec_named(&new_config)
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
     let data = Bundled::create(data).encode()
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
     let data = bundle_for_agency(data, &to_did)?;
 
     let data = unbundle_from_agency(
-        httpclient::post_u8(&data) .map_err(|e|error::INVALID_HTTP_RESPONSE.code_num)?
+        httpclient::post_u8(&data)?
     )?;
 
     Ok(())
@@ -300,7 +301,7 @@ mod tests {
 
         match update_agent_info("123", "value") {
             Ok(_) => assert_eq!(0,0),
-            Err(x) => assert_eq!(x, 0), // should fail here
+            Err(x) => assert_eq!(x.code(), 0), // should fail here
         };
 
     }
@@ -7,6 +7,7 @@ use self::rmp_serde::encode;
 use settings;
 use utils::httpclient;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use messages::*;
 use serde::Deserialize;
 use self::rmp_serde::Deserializer;
@@ -71,7 +72,7 @@ impl CreateKeyMsg{
                 self
             },
             Err(x) => {
-                self.validate_rc = x;
+                self.validate_rc = x.code();
                 self
             },
         }
@@ -84,13 +85,13 @@ impl CreateKeyMsg{
                 self
             },
             Err(x) => {
-                self.validate_rc = x;
+                self.validate_rc = x.code();
                 self
             },
         }
     }
 
-    pub fn send_secure(&mut self) -> Result<Vec<String>, u32> {
+    pub fn send_secure(&mut self) -> VcxResult<Vec<String>> {
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
@@ -102,7 +103,7 @@ impl CreateKeyMsg{
 
         let mut result = Vec::new();
         match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+            Err(err) => return Err(err),
             Ok(response) => {
                 let (did, vk) = parse_create_keys_response(response)?;
                 result.push(did);
@@ -124,15 +125,15 @@ impl GeneralMessage for CreateKeyMsg  {
     fn set_validate_rc(&mut self, rc: u32){ self.validate_rc = rc; }
     fn set_to_vk(&mut self, to_vk: String){ /* nothing to do here for CreateKeymsg */ }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>,u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
         let data = match encode::to_vec_named(&self.payload) {
             Ok(x) => x,
             Err(x) => {
                 error!("could not encode create_keys msg: {}", x);
-                return Err(error::INVALID_MSGPACK.code_num);
+                return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
             },
         };
         debug!("create_keys inner bundle: {:?}", data);
@@ -143,12 +144,12 @@ impl GeneralMessage for CreateKeyMsg  {
     }
 }
 
-pub fn parse_create_keys_response(response: Vec<u8>) -> Result<(String, String), u32> {
+pub fn parse_create_keys_response(response: Vec<u8>) -> VcxResult<(String, String)> {
     let data = unbundle_from_agency(response)?;
 
     debug!("create keys response inner bundle: {:?}", data[0]);
     let mut de = Deserializer::new(&data[0][..]);
-    let response: CreateKeyResponse = Deserialize::deserialize(&mut de).or(Err(error::UNKNOWN_ERROR.code_num))?;
+    let response: CreateKeyResponse = Deserialize::deserialize(&mut de).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
     Ok((response.for_did, response.for_verkey))
 }
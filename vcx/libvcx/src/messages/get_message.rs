@@ -3,12 +3,13 @@ extern crate serde_json;
 extern crate serde;
 extern crate rmp_serde;
 
+use std::collections::HashMap;
 use settings;
 use utils::httpclient;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use messages::*;
 use messages::MessageResponseCode::{ MessageAccepted, MessagePending };
-use utils::libindy::crypto;
 
 #[derive(Clone, Serialize, Debug, PartialEq, PartialOrd)]
 #[serde(rename_all = "camelCase")]
@@ -89,14 +90,14 @@ impl GetMessages{
         self
     }
 
-    pub fn send_secure(&mut self) -> Result<Vec<Message>, u32> {
+    pub fn send_secure(&mut self) -> VcxResult<Vec<Message>> {
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
         };
 
-        match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+        match httpclient::post_u8_idempotent(&data) {
+            Err(err) => return Err(err),
             Ok(response) => if settings::test_agency_mode_enabled() && response.len() == 0 {
                 return Ok(Vec::new());
             } else {
@@ -105,13 +106,20 @@ impl GetMessages{
         }
     }
 
-    pub fn download_messages(&mut self) -> Result<Vec<ConnectionMessages>, u32> {
+    pub fn download_messages(&mut self) -> VcxResult<Vec<ConnectionMessages>> {
+        let connections = self.download_encrypted_messages()?;
+        decrypt_connection_messages(connections)
+    }
+
+    /// Same as download_messages but leaves the payloads as they were received, so they can
+    /// be handed to the objects owning the connections.
+    pub fn download_encrypted_messages(&mut self) -> VcxResult<Vec<ConnectionMessages>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
 
         self.payload.msg_type.name = "GET_MSGS_BY_CONNS".to_string();
-        let data = encode::to_vec_named(&self.payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
+        let data = encode::to_vec_named(&self.payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
         trace!("get_message content: {:?}", data);
 
         let msg = Bundled::create(data).encode()?;
@@ -119,8 +127,8 @@ impl GetMessages{
         let to_did = settings::get_config_value(settings::CONFIG_REMOTE_TO_SDK_DID)?;
         let data = bundle_for_agency(msg, &to_did)?;
 
-        match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+        match httpclient::post_u8_idempotent(&data) {
+            Err(err) => return Err(err),
             Ok(response) => if settings::test_agency_mode_enabled() && response.len() == 0 {
                 return Ok(Vec::new());
             } else {
@@ -140,12 +148,12 @@ impl GeneralMessage for GetMessages{
     fn set_validate_rc(&mut self, rc: u32){ self.validate_rc = rc; }
     fn set_to_vk(&mut self, to_vk: String){ self.to_vk = to_vk; }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>,u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
 
-        let data = encode::to_vec_named(&self.payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
+        let data = encode::to_vec_named(&self.payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
         trace!("get_message content: {:?}", data);
 
         let msg = Bundled::create(data).encode()?;
@@ -215,7 +223,7 @@ impl Message {
         let mut new_message = self.clone();
         if let Some(ref payload) = self.payload {
             let payload = ::messages::to_u8(payload);
-            match ::utils::libindy::crypto::parse_msg(&vk, &payload) {
+            match ::messages::decrypt_payload(&vk, &payload) {
                 Ok(x) => {
                     new_message.decrypted_payload = to_json(&x.1)
                         .map(|i| i.to_string())
@@ -237,7 +245,7 @@ pub struct GetMessagesResponse {
     msgs: Vec<Message>,
 }
 
-fn parse_get_messages_response(response: Vec<u8>) -> Result<Vec<Message>, u32> {
+fn parse_get_messages_response(response: Vec<u8>) -> VcxResult<Vec<Message>> {
     let data = unbundle_from_agency(response)?;
 
     trace!("get_message response: {:?}", data[0]);
@@ -247,7 +255,7 @@ fn parse_get_messages_response(response: Vec<u8>) -> Result<Vec<Message>, u32> {
         Err(x) => {
             error!("Could not parse messagepack: {}", x);
 
-            return Err(error::INVALID_MSGPACK.code_num)
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num))
         },
     };
 
@@ -269,7 +277,7 @@ pub struct ConnectionMessages {
     pub msgs: Vec<Message>,
 }
 
-fn parse_get_connection_messages_response(response: Vec<u8>) -> Result<Vec<ConnectionMessages>, u32> {
+fn parse_get_connection_messages_response(response: Vec<u8>) -> VcxResult<Vec<ConnectionMessages>> {
     let data = unbundle_from_agency(response)?;
 
     trace!("parse_get_connection_message response: {:?}", data[0]);
@@ -279,12 +287,16 @@ fn parse_get_connection_messages_response(response: Vec<u8>) -> Result<Vec<Conne
         Err(x) => {
             error!("Could not parse messagepack: {}", x);
 
-            return Err(error::INVALID_MSGPACK.code_num)
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num))
         },
     };
 
+    Ok(response.msgs_by_conns)
+}
+
+fn decrypt_connection_messages(connections: Vec<ConnectionMessages>) -> VcxResult<Vec<ConnectionMessages>> {
     let mut connection_messages = Vec::new();
-    for connection in response.msgs_by_conns.iter() {
+    for connection in connections.iter() {
         let vk = ::utils::libindy::signus::get_local_verkey(&connection.pairwise_did)?;
         let mut new_messages = Vec::new();
         for message in connection.msgs.iter() {
@@ -298,7 +310,7 @@ fn parse_get_connection_messages_response(response: Vec<u8>) -> Result<Vec<Conne
     Ok(connection_messages)
 }
 
-pub fn get_connection_messages(pw_did: &str, pw_vk: &str, agent_did: &str, agent_vk: &str, msg_uid: Option<Vec<String>>) -> Result<Vec<Message>, u32> {
+pub fn get_connection_messages(pw_did: &str, pw_vk: &str, agent_did: &str, agent_vk: &str, msg_uid: Option<Vec<String>>) -> VcxResult<Vec<Message>> {
 
     match get_messages()
         .to(&pw_did)
@@ -309,11 +321,11 @@ pub fn get_connection_messages(pw_did: &str, pw_vk: &str, agent_did: &str, agent
         .send_secure() {
         Err(x) => {
             error!("could not post get_messages: {}", x);
-            Err(error::POST_MSG_FAILURE.code_num)
+            Err(x)
         },
         Ok(response) => {
             if response.len() == 0 {
-                Err(error::POST_MSG_FAILURE.code_num)
+                Err(VcxError::from_code(error::POST_MSG_FAILURE.code_num))
             } else {
                 trace!("message returned: {:?}", response[0]);
                 Ok(response)
@@ -322,31 +334,68 @@ pub fn get_connection_messages(pw_did: &str, pw_vk: &str, agent_did: &str, agent
     }
 }
 
-pub fn get_ref_msg(msg_id: &str, pw_did: &str, pw_vk: &str, agent_did: &str, agent_vk: &str) -> Result<(String, Vec<u8>), u32> {
+pub fn get_ref_msg(msg_id: &str, pw_did: &str, pw_vk: &str, agent_did: &str, agent_vk: &str) -> VcxResult<(String, Vec<u8>)> {
     let message = get_connection_messages(pw_did, pw_vk, agent_did, agent_vk, Some(vec![msg_id.to_string()]))?;
     trace!("checking for ref_msg: {:?}", message);
 
-    let msg_id = match message[0].ref_msg_id.clone() {
-        Some(ref ref_msg_id) if message[0].status_code == MessageAccepted.as_string() => ref_msg_id.to_string(),
-        _ => return Err(error::NOT_READY.code_num),
-    };
+    let msg_id = answer_msg_id(&message[0])?;
 
     let message = get_connection_messages(pw_did, pw_vk, agent_did, agent_vk, Some(vec![msg_id]))?;
 
     trace!("checking for pending message: {:?}", message);
 
-    // this will work for both credReq and proof types
-    match message[0].payload.clone() {
-        Some(ref payload) if message[0].status_code == MessagePending.as_string() => {
+    pending_payload(&message[0], pw_vk)
+}
+
+/// Messages already downloaded for each pairwise DID, so objects can update their state
+/// without asking the agency again.
+pub type MessagesByDid = HashMap<String, Vec<Message>>;
+
+pub fn group_by_pairwise_did(connections: Vec<ConnectionMessages>) -> MessagesByDid {
+    let mut messages = MessagesByDid::new();
+    for connection in connections {
+        messages.entry(connection.pairwise_did).or_insert_with(Vec::new).extend(connection.msgs);
+    }
+    messages
+}
+
+/// Same as get_ref_msg, but looks both messages up in `messages` instead of asking the agency.
+pub fn find_ref_msg(msg_id: &str, pw_did: &str, pw_vk: &str, messages: &MessagesByDid) -> VcxResult<(String, Vec<u8>)> {
+    let messages = messages.get(pw_did).ok_or(VcxError::from_code(error::NOT_READY.code_num))?;
+    let find = |uid: &str| messages.iter().find(|m| m.uid == uid).ok_or(VcxError::from_code(error::NOT_READY.code_num));
+
+    let msg_id = answer_msg_id(find(msg_id)?)?;
+    pending_payload(find(msg_id.as_str())?, pw_vk)
+}
+
+/// Uses the downloaded messages when there are some, asks the agency otherwise.
+pub fn get_ref_msg_from(messages: Option<&MessagesByDid>, msg_id: &str, pw_did: &str, pw_vk: &str, agent_did: &str, agent_vk: &str) -> VcxResult<(String, Vec<u8>)> {
+    match messages {
+        Some(messages) => find_ref_msg(msg_id, pw_did, pw_vk, messages),
+        None => get_ref_msg(msg_id, pw_did, pw_vk, agent_did, agent_vk),
+    }
+}
+
+fn answer_msg_id(message: &Message) -> VcxResult<String> {
+    match message.ref_msg_id {
+        Some(ref ref_msg_id) if message.status_code == MessageAccepted.as_string() => Ok(ref_msg_id.to_string()),
+        _ => Err(VcxError::from_code(error::NOT_READY.code_num)),
+    }
+}
+
+// this will work for both credReq and proof types
+fn pending_payload(message: &Message, pw_vk: &str) -> VcxResult<(String, Vec<u8>)> {
+    match message.payload {
+        Some(ref payload) if message.status_code == MessagePending.as_string() => {
             // TODO: check returned verkey
-            let (_, msg) = crypto::parse_msg(&pw_vk, &to_u8(payload))?;
-            Ok((message[0].uid.clone(), msg))
+            let (_, msg) = decrypt_payload(&pw_vk, &to_u8(payload))?;
+            Ok((message.uid.clone(), msg))
         },
-        _ => Err(error::INVALID_HTTP_RESPONSE.code_num)
+        _ => Err(VcxError::from_code(error::INVALID_HTTP_RESPONSE.code_num))
     }
 }
 
-pub fn download_messages(pairwise_dids: Option<Vec<String>>, status_codes: Option<Vec<String>>, uids: Option<Vec<String>>) -> Result<Vec<ConnectionMessages>, u32> {
+pub fn download_messages(pairwise_dids: Option<Vec<String>>, status_codes: Option<Vec<String>>, uids: Option<Vec<String>>) -> VcxResult<Vec<ConnectionMessages>> {
 
     if settings::test_agency_mode_enabled() {
         ::utils::httpclient::set_next_u8_response(::utils::constants::GET_ALL_MESSAGES_RESPONSE.to_vec());
@@ -359,7 +408,7 @@ pub fn download_messages(pairwise_dids: Option<Vec<String>>, status_codes: Optio
         .download_messages() {
         Err(x) => {
             error!("could not post get_messages: {}", x);
-            Err(error::POST_MSG_FAILURE.code_num)
+            Err(x)
         },
         Ok(response) => {
             trace!("message returned: {:?}", response[0]);
@@ -476,7 +525,7 @@ mod tests {
         // Agency returns a bad request response for invalid dids
         let invalid_did = "abc".to_string();
         let bad_req = download_messages(Some(vec![invalid_did]), None, None);
-        assert_eq!(bad_req, Err(error::POST_MSG_FAILURE.code_num));
+        assert_eq!(bad_req, Err(VcxError::from_code(error::AGENCY_REJECTED_MESSAGE.code_num)));
         teardown!("agency");
     }
 }
@@ -0,0 +1,275 @@
+extern crate base64;
+extern crate serde_json;
+
+use messages::invite::{ InviteDetail, SenderDetail, SenderAgencyDetail, KeyDlgProofPayload };
+use messages::pack::DidCommService;
+use utils::error;
+use error::{ VcxError, VcxResult };
+
+pub static CONNECTIONS_PROTOCOL: &str = "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/";
+pub static CONNECTIONS_PROTOCOL_VERSION: &str = "1.0";
+pub static INVITATION_URL_PARAM: &str = "c_i";
+
+/// How vcx_connection_invite_details renders an invitation.
+/// The first two values match the former `abbreviated` flag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InviteFormat {
+    Json = 0,
+    Abbreviated = 1,
+    Url = 2,
+    QrCode = 3,
+}
+
+impl InviteFormat {
+    pub fn from_u32(format: u32) -> VcxResult<InviteFormat> {
+        match format {
+            0 => Ok(InviteFormat::Json),
+            1 => Ok(InviteFormat::Abbreviated),
+            2 => Ok(InviteFormat::Url),
+            3 => Ok(InviteFormat::QrCode),
+            _ => Err(VcxError::from_code(error::INVALID_OPTION.code_num)),
+        }
+    }
+}
+
+/// Fields of the proprietary invite that have no counterpart in the connection invitation.
+/// They are carried in the "~legacy" decorator so that our own invitations can still be accepted through
+/// the agency. The decorator is VCX only, it is not part of the connections protocol and other agents ignore it.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyInviteDetail {
+    #[serde(rename = "DID")]
+    pub did: String,
+    pub agent_key_dlg_proof: KeyDlgProofPayload,
+    #[serde(rename = "agencyDID")]
+    pub agency_did: String,
+    pub status_code: String,
+    pub status_msg: String,
+    pub target_name: String,
+}
+
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionInvitation {
+    #[serde(rename = "@type")]
+    pub msg_type: String,
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub label: String,
+    pub recipient_keys: Vec<String>,
+    #[serde(default)]
+    pub routing_keys: Vec<String>,
+    pub service_endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    #[serde(rename = "~legacy", default, skip_serializing_if = "Option::is_none")]
+    pub legacy: Option<LegacyInviteDetail>,
+}
+
+impl ConnectionInvitation {
+    pub fn from_invite_detail(details: &InviteDetail) -> ConnectionInvitation {
+        let sender = &details.sender_detail;
+        let agency = &details.sender_agency_detail;
+
+        ConnectionInvitation {
+            msg_type: format!("{}{}/invitation", CONNECTIONS_PROTOCOL, CONNECTIONS_PROTOCOL_VERSION),
+            id: details.conn_req_id.clone(),
+            label: sender.name.clone().unwrap_or_default(),
+            recipient_keys: vec![sender.verkey.clone()],
+            routing_keys: vec![sender.agent_key_dlg_proof.agent_delegated_key.clone(), agency.verkey.clone()],
+            service_endpoint: agency.endpoint.clone(),
+            image_url: sender.logo_url.clone(),
+            legacy: Some(LegacyInviteDetail {
+                did: sender.did.clone(),
+                agent_key_dlg_proof: sender.agent_key_dlg_proof.clone(),
+                agency_did: agency.did.clone(),
+                status_code: details.status_code.clone(),
+                status_msg: details.status_msg.clone(),
+                target_name: details.target_name.clone(),
+            }),
+        }
+    }
+
+    /// Where the inviter receives DIDComm messages.
+    pub fn service(&self) -> DidCommService {
+        DidCommService {
+            service_endpoint: self.service_endpoint.clone(),
+            recipient_keys: self.recipient_keys.clone(),
+            routing_keys: self.routing_keys.clone(),
+        }
+    }
+
+    /// Checks the invitation type against the versions of the connections protocol we speak.
+    pub fn validate_protocol(&self) -> VcxResult<()> {
+        let version = self.msg_type.trim_left_matches(CONNECTIONS_PROTOCOL);
+        if version.len() == self.msg_type.len() || !version.ends_with("/invitation") {
+            warn!("not a connection invitation: {}", self.msg_type);
+            return Err(VcxError::from_code(error::UNSUPPORTED_INVITATION.code_num));
+        }
+
+        let major = version.split('.').next().unwrap_or_default();
+        if Some(major) != CONNECTIONS_PROTOCOL_VERSION.split('.').next() {
+            warn!("unsupported connections protocol version: {}", self.msg_type);
+            return Err(VcxError::from_code(error::UNSUPPORTED_INVITATION.code_num));
+        }
+
+        Ok(())
+    }
+
+    /// Only invitations created through an agency carry what is needed to answer them.
+    pub fn to_invite_detail(&self) -> VcxResult<InviteDetail> {
+        self.validate_protocol()?;
+
+        let legacy = self.legacy.as_ref().ok_or_else(|| {
+            warn!("invitation {} was not created through an agency", self.id);
+            VcxError::from_code(error::UNSUPPORTED_INVITATION.code_num)
+        })?;
+
+        let verkey = self.recipient_keys.first().ok_or(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num))?;
+        let agency_verkey = self.routing_keys.last().ok_or(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num))?;
+
+        Ok(InviteDetail {
+            status_code: legacy.status_code.clone(),
+            conn_req_id: self.id.clone(),
+            sender_detail: SenderDetail {
+                name: Some(self.label.clone()),
+                agent_key_dlg_proof: legacy.agent_key_dlg_proof.clone(),
+                did: legacy.did.clone(),
+                logo_url: self.image_url.clone(),
+                verkey: verkey.clone(),
+            },
+            sender_agency_detail: SenderAgencyDetail {
+                did: legacy.agency_did.clone(),
+                verkey: agency_verkey.clone(),
+                endpoint: self.service_endpoint.clone(),
+            },
+            target_name: legacy.target_name.clone(),
+            status_msg: legacy.status_msg.clone(),
+        })
+    }
+
+    pub fn to_json(&self) -> VcxResult<String> {
+        serde_json::to_string(self).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
+    }
+
+    pub fn from_json(invitation: &str) -> VcxResult<ConnectionInvitation> {
+        serde_json::from_str(invitation).or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))
+    }
+
+    /// Renders the invitation as `<service endpoint>?c_i=<base64url encoded invitation>`.
+    pub fn to_url(&self) -> VcxResult<String> {
+        let encoded = base64::encode_config(self.to_json()?.as_bytes(), base64::URL_SAFE_NO_PAD);
+        Ok(format!("{}?{}={}", self.service_endpoint, INVITATION_URL_PARAM, encoded))
+    }
+
+    pub fn from_url(url: &str) -> VcxResult<ConnectionInvitation> {
+        let encoded = invitation_param(url).ok_or(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num))?;
+
+        // accept padded and standard alphabet encodings produced by other agents
+        let encoded = encoded.trim_right_matches('=').replace('+', "-").replace('/', "_");
+        let decoded = base64::decode_config(&encoded, base64::URL_SAFE_NO_PAD)
+            .or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))?;
+
+        let invitation = String::from_utf8(decoded).or(Err(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)))?;
+        ConnectionInvitation::from_json(&invitation)
+    }
+}
+
+fn invitation_param(url: &str) -> Option<&str> {
+    let query = url.splitn(2, '?').nth(1)?;
+    query.split('&')
+        .filter_map(|pair| {
+            let mut kv = pair.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some(key), Some(value)) if key == INVITATION_URL_PARAM => Some(value),
+                _ => None,
+            }
+        })
+        .next()
+}
+
+pub fn is_invitation_url(details: &str) -> bool {
+    let details = details.trim();
+    !details.starts_with('{') && invitation_param(details).is_some()
+}
+
+pub fn is_connection_invitation(details: &serde_json::Value) -> bool {
+    match details["@type"].as_str() {
+        Some(msg_type) => msg_type.starts_with(CONNECTIONS_PROTOCOL),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::constants::INVITE_DETAIL_STRING;
+
+    fn invite_detail() -> InviteDetail {
+        serde_json::from_str(INVITE_DETAIL_STRING).unwrap()
+    }
+
+    #[test]
+    fn test_invite_detail_round_trip() {
+        let details = invite_detail();
+        let invitation = ConnectionInvitation::from_invite_detail(&details);
+        assert_eq!(invitation.msg_type, "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/1.0/invitation");
+        assert_eq!(invitation.recipient_keys, vec![details.sender_detail.verkey.clone()]);
+        assert_eq!(invitation.service_endpoint, details.sender_agency_detail.endpoint);
+        assert_eq!(invitation.to_invite_detail().unwrap(), details);
+    }
+
+    #[test]
+    fn test_url_round_trip() {
+        let invitation = ConnectionInvitation::from_invite_detail(&invite_detail());
+        let url = invitation.to_url().unwrap();
+        assert!(url.starts_with("localhost:9001/agency/msg?c_i="));
+        assert!(is_invitation_url(&url));
+        assert_eq!(ConnectionInvitation::from_url(&url).unwrap(), invitation);
+
+        let padded = format!("https://example.com/?foo=bar&c_i={}",
+                             base64::encode_config(invitation.to_json().unwrap().as_bytes(), base64::URL_SAFE));
+        assert_eq!(ConnectionInvitation::from_url(&padded).unwrap(), invitation);
+
+        assert!(!is_invitation_url(INVITE_DETAIL_STRING));
+        assert_eq!(ConnectionInvitation::from_url("https://example.com/?c_i=!!").err(), Some(VcxError::from_code(error::INVALID_INVITE_DETAILS.code_num)));
+    }
+
+    #[test]
+    fn test_protocol_versions() {
+        let mut invitation = ConnectionInvitation::from_invite_detail(&invite_detail());
+        invitation.msg_type = format!("{}1.1/invitation", CONNECTIONS_PROTOCOL);
+        assert!(invitation.to_invite_detail().is_ok());
+
+        invitation.msg_type = format!("{}2.0/invitation", CONNECTIONS_PROTOCOL);
+        assert_eq!(invitation.to_invite_detail().err(), Some(VcxError::from_code(error::UNSUPPORTED_INVITATION.code_num)));
+
+        invitation.msg_type = format!("{}1.0/request", CONNECTIONS_PROTOCOL);
+        assert_eq!(invitation.to_invite_detail().err(), Some(VcxError::from_code(error::UNSUPPORTED_INVITATION.code_num)));
+    }
+
+    #[test]
+    fn test_foreign_invitation() {
+        let invitation = json!({
+            "@type": "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/1.0/invitation",
+            "@id": "12345678900987654321",
+            "label": "Alice",
+            "recipientKeys": ["8HH5gYEeNc3z7PYXmd54d4x6qAfCNrqQqEB3nS7Zfu7K"],
+            "serviceEndpoint": "https://example.com/endpoint",
+            "routingKeys": ["8HH5gYEeNc3z7PYXmd54d4x6qAfCNrqQqEB3nS7Zfu7K"]
+        });
+        assert!(is_connection_invitation(&invitation));
+
+        let invitation = ConnectionInvitation::from_json(&invitation.to_string()).unwrap();
+        assert_eq!(invitation.label, "Alice");
+        assert_eq!(invitation.to_invite_detail().err(), Some(VcxError::from_code(error::UNSUPPORTED_INVITATION.code_num)));
+    }
+
+    #[test]
+    fn test_invite_format() {
+        assert_eq!(InviteFormat::from_u32(0), Ok(InviteFormat::Json));
+        assert_eq!(InviteFormat::from_u32(1), Ok(InviteFormat::Abbreviated));
+        assert_eq!(InviteFormat::from_u32(3), Ok(InviteFormat::QrCode));
+        assert_eq!(InviteFormat::from_u32(4), Err(VcxError::from_code(error::INVALID_OPTION.code_num)));
+    }
+}
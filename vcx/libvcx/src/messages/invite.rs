@@ -7,6 +7,7 @@ extern crate base64;
 use settings;
 use utils::httpclient;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use messages::*;
 use messages::send_message::CreateMessagePayload;
 use utils::constants::*;
@@ -118,12 +119,12 @@ pub struct SenderAgencyDetail {
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq, PartialOrd)]
 #[serde(rename_all = "camelCase")]
 pub struct InviteDetail {
-    status_code: String,
+    pub status_code: String,
     pub conn_req_id: String,
     pub sender_detail: SenderDetail,
     pub sender_agency_detail: SenderAgencyDetail,
-    target_name: String,
-    status_msg: String,
+    pub target_name: String,
+    pub status_msg: String,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq, PartialOrd)]
@@ -196,7 +197,7 @@ impl SendInvite{
                 self
             }
             Err(x) => {
-                self.validate_rc = x;
+                self.validate_rc = x.code();
                 self
             }
         }
@@ -209,14 +210,14 @@ impl SendInvite{
                     self.payload.msg_detail_payload.phone = Some(x);
                 }
                 Err(x) => {
-                    self.validate_rc = x;
+                    self.validate_rc = x.code();
                 }
             };
         }
         self
     }
 
-    pub fn generate_signature(&mut self) -> Result<u32, u32> {
+    pub fn generate_signature(&mut self) -> VcxResult<u32> {
         let signature = format!("{}{}", self.payload.msg_detail_payload.key_proof.agent_did, self.payload.msg_detail_payload.key_proof.agent_delegated_key);
         let signature = crypto::sign(wallet::get_wallet_handle(), &self.to_vk, signature.as_bytes())?;
         let signature = base64::encode(&signature);
@@ -224,7 +225,7 @@ impl SendInvite{
         Ok(error::SUCCESS.code_num)
     }
 
-    pub fn send_secure(&mut self) -> Result<Vec<String>, u32> {
+    pub fn send_secure(&mut self) -> VcxResult<Vec<String>> {
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
@@ -234,7 +235,7 @@ impl SendInvite{
 
         let mut result = Vec::new();
         match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+            Err(err) => return Err(err),
             Ok(response) => {
                 let (invite, url) = parse_response(response)?;
                 result.push(invite);
@@ -295,7 +296,7 @@ impl AcceptInvite{
                 self
             }
             Err(x) => {
-                self.validate_rc = x;
+                self.validate_rc = x.code();
                 self
             }
         }
@@ -321,7 +322,7 @@ impl AcceptInvite{
         self
     }
 
-    pub fn generate_signature(&mut self) -> Result<u32, u32> {
+    pub fn generate_signature(&mut self) -> VcxResult<u32> {
         let signature = format!("{}{}", self.payload.msg_detail_payload.key_proof.agent_did, self.payload.msg_detail_payload.key_proof.agent_delegated_key);
         let signature = crypto::sign(wallet::get_wallet_handle(), &self.to_vk, signature.as_bytes())?;
         let signature = base64::encode(&signature);
@@ -329,7 +330,7 @@ impl AcceptInvite{
         Ok(error::SUCCESS.code_num)
     }
 
-    pub fn send_secure(&mut self) -> Result<String, u32> {
+    pub fn send_secure(&mut self) -> VcxResult<String> {
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
@@ -338,7 +339,7 @@ impl AcceptInvite{
         if settings::test_agency_mode_enabled() { httpclient::set_next_u8_response(ACCEPT_INVITE_RESPONSE.to_vec()); }
 
         match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+            Err(err) => return Err(err),
             Ok(response) => {
                 parse_send_accept_response(response)
             },
@@ -365,15 +366,15 @@ impl GeneralMessage for SendInvite{
     fn set_to_vk(&mut self, to_vk: String) { self.to_vk = to_vk; }
     fn set_validate_rc(&mut self, rc: u32){ self.validate_rc = rc; }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>,u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
 
         self.generate_signature()?;
         debug!("connection invitation details: {}", serde_json::to_string(&self.payload.msg_detail_payload).unwrap_or("failure".to_string()));
-        let create = encode::to_vec_named(&self.payload.create_payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
-        let details = encode::to_vec_named(&self.payload.msg_detail_payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
+        let create = encode::to_vec_named(&self.payload.create_payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
+        let details = encode::to_vec_named(&self.payload.msg_detail_payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
         let mut bundle = Bundled::create(create);
         bundle.bundled.push(details);
@@ -401,15 +402,15 @@ impl GeneralMessage for AcceptInvite{
     fn set_to_vk(&mut self, to_vk: String) { self.to_vk = to_vk; }
     fn set_validate_rc(&mut self, rc: u32){ self.validate_rc = rc; }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>,u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
 
         self.generate_signature()?;
         debug!("connection invitation details: {}", serde_json::to_string(&self.payload.msg_detail_payload).unwrap_or("failure".to_string()));
-        let create = encode::to_vec_named(&self.payload.create_payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
-        let details = encode::to_vec_named(&self.payload.msg_detail_payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
+        let create = encode::to_vec_named(&self.payload.create_payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
+        let details = encode::to_vec_named(&self.payload.msg_detail_payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
         let mut bundle = Bundled::create(create);
         bundle.bundled.push(details);
@@ -420,12 +421,12 @@ impl GeneralMessage for AcceptInvite{
     }
 }
 
-fn parse_response(response: Vec<u8>) -> Result<(String, String), u32> {
+fn parse_response(response: Vec<u8>) -> VcxResult<(String, String)> {
     let data = unbundle_from_agency(response)?;
 
     if data.len() != 3 {
         error!("expected 3 messages (got {})", data.len());
-        return Err(error::INVALID_MSGPACK.code_num);
+        return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
     }
     debug!("invite details response: {:?}", data[1]);
     let mut de = Deserializer::new(&data[1][..]);
@@ -433,24 +434,24 @@ fn parse_response(response: Vec<u8>) -> Result<(String, String), u32> {
         Ok(x) => x,
         Err(x) => {
             error!("Could not parse messagepack: {}", x);
-            return Err(error::INVALID_MSGPACK.code_num)
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num))
         },
     };
 
     debug!("Invite Details: {:?}", response.invite_detail);
     match serde_json::to_string(&response.invite_detail) {
         Ok(x) => Ok((x, response.url_to_invite_detail)),
-        Err(_) => Err(error::INVALID_JSON.code_num),
+        Err(_) => Err(VcxError::from_code(error::INVALID_JSON.code_num)),
     }
 }
 
 const ACCEPT_BUNDLE_LEN: usize = 2;
-fn parse_send_accept_response(response: Vec<u8>) -> Result<String, u32> {
+fn parse_send_accept_response(response: Vec<u8>) -> VcxResult<String> {
     let data = unbundle_from_agency(response)?;
 
     if data.len() != ACCEPT_BUNDLE_LEN {
         error!("expected {} messages (got {})",ACCEPT_BUNDLE_LEN, data.len());
-        return Err(error::INVALID_MSGPACK.code_num);
+        return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
     }
     debug!("create msg response: {:?}", data[0]);
     let mut de = Deserializer::new(&data[0][..]);
@@ -458,14 +459,14 @@ fn parse_send_accept_response(response: Vec<u8>) -> Result<String, u32> {
         Ok(x) => x,
         Err(x) => {
             error!("Could not parse messagepack: {}", x);
-            return Err(error::INVALID_MSGPACK.code_num)
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num))
         },
     };
 
     Ok(response.uid.to_owned())
 }
 
-pub fn parse_invitation_acceptance_details(payload: Vec<u8>) -> Result<SenderDetail,u32> {
+pub fn parse_invitation_acceptance_details(payload: Vec<u8>) -> VcxResult<SenderDetail> {
     #[serde(rename_all = "camelCase")]
     #[derive(Serialize, Deserialize, Debug, PartialEq, PartialOrd, Clone)]
     struct Details {
@@ -476,7 +477,7 @@ pub fn parse_invitation_acceptance_details(payload: Vec<u8>) -> Result<SenderDet
     let mut de = Deserializer::new(&payload[..]);
     let response: Details = match Deserialize::deserialize(&mut de) {
         Ok(x) => x,
-        Err(x) => return Err(error::INVALID_MSGPACK.code_num),
+        Err(x) => return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num)),
     };
     Ok(response.sender_detail.to_owned())
 }
@@ -12,12 +12,15 @@ pub mod proofs;
 pub mod agent_utils;
 pub mod update_connection;
 pub mod update_message;
+pub mod pack;
+pub mod invitation;
 
 use std::u8;
 use settings;
 use utils::libindy::crypto;
 use utils::libindy::wallet;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use self::rmp_serde::encode;
 use self::create_key::CreateKeyMsg;
 use self::update_connection::DeleteConnection;
@@ -103,12 +106,12 @@ impl<T> Bundled<T> {
         }
     }
 
-    pub fn encode(&self) -> Result<Vec<u8>, u32> where T: serde::Serialize {
+    pub fn encode(&self) -> VcxResult<Vec<u8>> where T: serde::Serialize {
         let result = match encode::to_vec_named(self) {
             Ok(x) => x,
             Err(x) => {
                 error!("Could not convert bundle to messagepack: {}", x);
-                return Err(error::INVALID_MSGPACK.code_num);
+                return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
             },
         };
 
@@ -116,13 +119,13 @@ impl<T> Bundled<T> {
     }
 }
 
-pub fn try_i8_bundle(data: Vec<u8>) -> Result<Bundled<Vec<u8>>, u32> {
+pub fn try_i8_bundle(data: Vec<u8>) -> VcxResult<Bundled<Vec<u8>>> {
     let mut de = Deserializer::new(&data[..]);
     let bundle: Bundled<Vec<i8>> = match Deserialize::deserialize(&mut de) {
         Ok(x) => x,
         Err(_) => {
             warn!("could not deserialize bundle with i8, will try u8");
-            return Err(error::INVALID_MSGPACK.code_num);
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
         },
     };
 
@@ -147,15 +150,15 @@ pub fn to_i8(bytes: &Vec<u8>) -> Vec<i8> {
     buf.to_owned()
 }
 
-pub fn to_json(bytes: &Vec<u8>) -> Result<Value, u32> {
+pub fn to_json(bytes: &Vec<u8>) -> VcxResult<Value> {
     let mut de = Deserializer::new(&bytes[..]);
     match Deserialize::deserialize(&mut de) {
         Ok(x) => Ok(x),
-        Err(x) => Err(error::INVALID_JSON.code_num),
+        Err(x) => Err(VcxError::from_code(error::INVALID_JSON.code_num)),
     }
 }
 
-pub fn bundle_from_u8(data: Vec<u8>) -> Result<Bundled<Vec<u8>>, u32> {
+pub fn bundle_from_u8(data: Vec<u8>) -> VcxResult<Bundled<Vec<u8>>> {
     let bundle = match try_i8_bundle(data.clone()) {
         Ok(x) => x,
         Err(x) => {
@@ -164,7 +167,7 @@ pub fn bundle_from_u8(data: Vec<u8>) -> Result<Bundled<Vec<u8>>, u32> {
                 Ok(x) => x,
                 Err(x) => {
                     error!("could not deserialize bundle with i8 or u8: {}", x);
-                    return Err(error::INVALID_MSGPACK.code_num);
+                    return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
                 },
             };
             bundle
@@ -174,20 +177,36 @@ pub fn bundle_from_u8(data: Vec<u8>) -> Result<Bundled<Vec<u8>>, u32> {
     Ok(bundle)
 }
 
-pub fn extract_json_payload(data: &Vec<u8>) -> Result<String, u32> {
+pub fn extract_json_payload(data: &Vec<u8>) -> VcxResult<String> {
     let mut de = Deserializer::new(&data[..]);
     let my_payload: Payload = match Deserialize::deserialize(&mut de) {
         Ok(x) => x,
         Err(x) => {
             error!("could not deserialize bundle with i8 or u8: {}", x);
-            return Err(error::INVALID_MSGPACK.code_num);
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num));
             },
         };
 
     Ok(my_payload.msg.to_owned())
 }
 
-pub fn bundle_for_agency(message: Vec<u8>, did: &str) -> Result<Vec<u8>, u32> {
+/// Decrypts a payload sent over a connection, whatever envelope the sender used.
+/// DIDComm payloads carry the Payload as json and are converted to the msgpack form
+/// the legacy authcrypted payloads use, so callers can keep using extract_json_payload.
+pub fn decrypt_payload(my_vk: &str, payload: &[u8]) -> VcxResult<(String, Vec<u8>)> {
+    if !pack::is_packed(payload) {
+        return crypto::parse_msg(my_vk, payload);
+    }
+
+    let unpacked = pack::unpack_message(payload)?;
+    let sender_vk = unpacked.sender_verkey.ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+    let my_payload: Payload = serde_json::from_slice(&unpacked.message).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+    let data = encode::to_vec_named(&my_payload).or(Err(VcxError::from_code(error::INVALID_MSGPACK.code_num)))?;
+
+    Ok((sender_vk, data))
+}
+
+pub fn bundle_for_agency(message: Vec<u8>, did: &str) -> VcxResult<Vec<u8>> {
     let agency_vk = settings::get_config_value(settings::CONFIG_AGENCY_VERKEY)?;
     let agent_vk = settings::get_config_value(settings::CONFIG_REMOTE_TO_SDK_VERKEY)?;
     let my_vk = settings::get_config_value(settings::CONFIG_SDK_TO_REMOTE_VERKEY)?;
@@ -201,7 +220,7 @@ pub fn bundle_for_agency(message: Vec<u8>, did: &str) -> Result<Vec<u8>, u32> {
         fwd: did.to_owned(),
         msg,
     };
-    let outer = encode::to_vec_named(&outer).or(Err(error::UNKNOWN_ERROR.code_num))?;
+    let outer = encode::to_vec_named(&outer).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
     trace!("forward bundle: {:?}", outer);
     let msg = Bundled::create(outer).encode()?;
@@ -209,7 +228,7 @@ pub fn bundle_for_agency(message: Vec<u8>, did: &str) -> Result<Vec<u8>, u32> {
     crypto::prep_anonymous_msg(&agency_vk, &msg[..])
 }
 
-pub fn bundle_for_agent(message: Vec<u8>, pw_vk: &str, agent_did: &str, agent_vk: &str) -> Result<Vec<u8>, u32> {
+pub fn bundle_for_agent(message: Vec<u8>, pw_vk: &str, agent_did: &str, agent_vk: &str) -> VcxResult<Vec<u8>> {
     debug!("pre encryption msg: {:?}", message);
     let msg = crypto::prep_msg(wallet::get_wallet_handle(), &pw_vk, agent_vk, &message[..])?;
 
@@ -220,7 +239,7 @@ pub fn bundle_for_agent(message: Vec<u8>, pw_vk: &str, agent_did: &str, agent_vk
         fwd: agent_did.to_string(),
         msg,
     };
-    let inner = encode::to_vec_named(&inner).or(Err(error::UNKNOWN_ERROR.code_num))?;
+    let inner = encode::to_vec_named(&inner).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
     debug!("inner forward: {:?}", inner);
 
     let msg = Bundled::create(inner).encode()?;
@@ -229,7 +248,7 @@ pub fn bundle_for_agent(message: Vec<u8>, pw_vk: &str, agent_did: &str, agent_vk
     bundle_for_agency(msg, &to_did)
 }
 
-pub fn unbundle_from_agency(message: Vec<u8>) -> Result<Vec<Vec<u8>>, u32> {
+pub fn unbundle_from_agency(message: Vec<u8>) -> VcxResult<Vec<Vec<u8>>> {
 
     let my_vk = settings::get_config_value(settings::CONFIG_SDK_TO_REMOTE_VERKEY)?;
 
@@ -264,7 +283,7 @@ pub trait GeneralMessage{
             },
             Err(x) => {
                 warn!("could not validate recipient did");
-                self.set_validate_rc(x);
+                self.set_validate_rc(x.code());
                 self
             },
         }
@@ -278,7 +297,7 @@ pub trait GeneralMessage{
             },
             Err(x) => {
                 warn!("could not validate recipient vk");
-                self.set_validate_rc(x);
+                self.set_validate_rc(x.code());
                 self
             },
         }
@@ -292,7 +311,7 @@ pub trait GeneralMessage{
             },
             Err(x) => {
                 warn!("could not validate agent_did");
-                self.set_validate_rc(x);
+                self.set_validate_rc(x.code());
                 self
             },
         }
@@ -306,7 +325,7 @@ pub trait GeneralMessage{
             },
             Err(x) => {
                 warn!("could not validate agent_vk");
-                self.set_validate_rc(x);
+                self.set_validate_rc(x.code());
                 self
             },
         }
@@ -317,7 +336,7 @@ pub trait GeneralMessage{
     fn set_agent_did(&mut self, did: String);
     fn set_agent_vk(&mut self, vk: String);
     fn set_validate_rc(&mut self, rc: u32);
-    fn msgpack(&mut self) -> Result<Vec<u8>, u32>;
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>>;
 }
 
 pub fn create_keys() -> CreateKeyMsg { CreateKeyMsg::create() }
@@ -0,0 +1,456 @@
+extern crate base64;
+extern crate rust_base58;
+extern crate serde_json;
+extern crate sodiumoxide;
+
+use std::sync::{ Once, ONCE_INIT };
+use serde_json::Value;
+use self::rust_base58::{ ToBase58, FromBase58 };
+use self::sodiumoxide::crypto::{ box_, sealedbox, sign };
+use self::sodiumoxide::crypto::sign::ed25519;
+use self::sodiumoxide::crypto::aead::xchacha20poly1305_ietf as aead;
+use self::sodiumoxide::randombytes::randombytes;
+use settings;
+use utils::libindy::signus::create_and_store_my_did;
+use utils::libindy::wallet;
+use utils::error;
+use error::{ VcxError, VcxResult };
+
+pub static FORWARD_TYPE: &str = "did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/routing/1.0/forward";
+static ENVELOPE_TYP: &str = "JWM/1.0";
+static ENVELOPE_ENC: &str = "xchacha20poly1305_ietf";
+static ALG_AUTHCRYPT: &str = "Authcrypt";
+static ALG_ANONCRYPT: &str = "Anoncrypt";
+// holds the seeds of the packing keys, the wallet api of vcx does not reach records of this type
+static KEY_RECORD_TYPE: &str = "vcx_packing_key";
+// every key of the mocked libindy is the one of this seed
+static TEST_SEED: &str = "000000000000000000000000Trustee1";
+
+static SODIUM_INIT: Once = ONCE_INIT;
+
+/// How messages exchanged over a connection are wrapped.
+/// Bundled is the msgpack format understood by Evernym agencies; DIDComm uses the
+/// envelopes of the Aries encryption envelope RFC (0019) so that other agents can read them,
+/// and delivers them to the service endpoint of the other agent through its routing keys.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum EnvelopeFormat {
+    #[serde(rename = "bundled")]
+    Bundled,
+    #[serde(rename = "didcomm")]
+    DIDComm,
+}
+
+impl Default for EnvelopeFormat {
+    fn default() -> EnvelopeFormat { EnvelopeFormat::Bundled }
+}
+
+/// Where the other agent of a DIDComm connection receives its messages.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DidCommService {
+    pub service_endpoint: String,
+    pub recipient_keys: Vec<String>,
+    #[serde(default)]
+    pub routing_keys: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct RecipientHeader {
+    kid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    sender: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    iv: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct Recipient {
+    encrypted_key: String,
+    header: RecipientHeader,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct ProtectedHeader {
+    enc: String,
+    typ: String,
+    alg: String,
+    recipients: Vec<Recipient>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+struct Envelope {
+    protected: String,
+    iv: String,
+    ciphertext: String,
+    tag: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Forward {
+    #[serde(rename = "@type")]
+    msg_type: String,
+    to: String,
+    msg: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnpackedMessage {
+    pub message: Vec<u8>,
+    pub sender_verkey: Option<String>,
+    pub recipient_verkey: String,
+}
+
+fn b64_encode(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE)
+}
+
+// padding is optional in the envelope, other agents leave it out
+fn b64_decode(data: &str) -> VcxResult<Vec<u8>> {
+    base64::decode_config(data.trim_right_matches('='), base64::URL_SAFE_NO_PAD)
+        .or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))
+}
+
+fn sodium_init() {
+    SODIUM_INIT.call_once(|| { sodiumoxide::init().ok(); });
+}
+
+/// Creates a DID whose key can pack and unpack DIDComm envelopes.
+/// libindy never hands out secret keys, so the key is created from a random seed that is
+/// kept in the wallet next to it, in a record type only vcx reads.
+pub fn create_packing_did() -> VcxResult<(String, String)> {
+    sodium_init();
+    let seed = base64::encode(&randombytes(24));
+    let (did, verkey) = create_and_store_my_did(Some(&seed))?;
+
+    if settings::test_indy_mode_enabled() { return Ok((did, verkey)) }
+
+    let (public_key, _) = keypair_from_seed(&seed)?;
+    if public_key.0.to_base58() != verkey {
+        error!("key created by libindy for {} does not match its seed", did);
+        return Err(VcxError::from_code(error::UNKNOWN_LIBINDY_ERROR.code_num));
+    }
+
+    wallet::add_record(KEY_RECORD_TYPE, &verkey, &seed, "{}")?;
+    Ok((did, verkey))
+}
+
+/// Records of this type hold secrets, apps can not read or change them through the wallet api.
+pub fn is_reserved_record_type(xtype: &str) -> bool {
+    xtype == KEY_RECORD_TYPE
+}
+
+/// Whether the verkey was created by create_packing_did, other keys can not pack or unpack.
+pub fn check_packing_key(verkey: &str) -> VcxResult<()> {
+    signing_keypair(verkey).map(|_| ())
+}
+
+fn keypair_from_seed(seed: &str) -> VcxResult<(sign::PublicKey, sign::SecretKey)> {
+    let seed = sign::Seed::from_slice(seed.as_bytes()).ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+    Ok(sign::keypair_from_seed(&seed))
+}
+
+fn signing_keypair(verkey: &str) -> VcxResult<(sign::PublicKey, sign::SecretKey)> {
+    if settings::test_indy_mode_enabled() { return keypair_from_seed(TEST_SEED) }
+
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let record = match wallet::get_record(KEY_RECORD_TYPE, verkey, &options) {
+        Ok(x) => x,
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => return Err(VcxError::from_code(error::PACKING_KEY_NOT_FOUND.code_num)),
+        Err(x) => return Err(x),
+    };
+    let record: Value = serde_json::from_str(&record).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+    let seed = record["value"].as_str().ok_or(VcxError::from_code(error::PACKING_KEY_NOT_FOUND.code_num))?;
+
+    keypair_from_seed(seed)
+}
+
+fn box_public_key(verkey: &str) -> VcxResult<box_::PublicKey> {
+    let ed25519_pk = match settings::test_indy_mode_enabled() {
+        true => keypair_from_seed(TEST_SEED)?.0,
+        false => verkey.from_base58().ok()
+            .and_then(|key| sign::PublicKey::from_slice(&key))
+            .ok_or(VcxError::from_code(error::INVALID_VERKEY.code_num))?,
+    };
+
+    // same conversion libindy uses to turn verkeys into crypto_box keys
+    ed25519::to_curve25519_pk(&ed25519_pk)
+        .map(|curve25519_pk| box_::PublicKey(curve25519_pk.0))
+        .or(Err(VcxError::from_code(error::INVALID_VERKEY.code_num)))
+}
+
+fn box_keypair(verkey: &str) -> VcxResult<(box_::PublicKey, box_::SecretKey)> {
+    let (_, ed25519_sk) = signing_keypair(verkey)?;
+
+    let curve25519_sk = ed25519::to_curve25519_sk(&ed25519_sk)
+        .or(Err(VcxError::from_code(error::PACKING_KEY_NOT_FOUND.code_num)))?;
+    Ok((box_public_key(verkey)?, box_::SecretKey(curve25519_sk.0)))
+}
+
+/// Encrypts message for every recipient verkey.
+/// With a sender verkey the content key is authcrypted (the recipients learn who sent it),
+/// without one it is anoncrypted. The sender key must come from create_packing_did, libindy
+/// keeps the secret key of any other verkey to itself so packing with it fails with
+/// PACKING_KEY_NOT_FOUND. Connections made before DIDComm support have such keys and stay on
+/// bundled envelopes.
+pub fn pack_message(message: &[u8], recipient_vks: &[String], sender_vk: Option<&str>) -> VcxResult<Vec<u8>> {
+    if recipient_vks.is_empty() { return Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)) }
+    sodium_init();
+
+    let cek = aead::gen_key();
+    let sender = match sender_vk {
+        Some(sender_vk) => Some((sender_vk, box_keypair(sender_vk)?.1)),
+        None => None,
+    };
+
+    let mut recipients = Vec::new();
+    for recipient_vk in recipient_vks {
+        let recipient_pk = box_public_key(recipient_vk)?;
+        let recipient = match sender {
+            Some((sender_vk, ref sender_sk)) => {
+                let nonce = box_::gen_nonce();
+                Recipient {
+                    encrypted_key: b64_encode(&box_::seal(&cek.0, &nonce, &recipient_pk, sender_sk)),
+                    header: RecipientHeader {
+                        kid: recipient_vk.to_string(),
+                        sender: Some(b64_encode(&sealedbox::seal(sender_vk.as_bytes(), &recipient_pk))),
+                        iv: Some(b64_encode(&nonce.0)),
+                    },
+                }
+            },
+            None => Recipient {
+                encrypted_key: b64_encode(&sealedbox::seal(&cek.0, &recipient_pk)),
+                header: RecipientHeader { kid: recipient_vk.to_string(), sender: None, iv: None },
+            },
+        };
+        recipients.push(recipient);
+    }
+
+    let protected = ProtectedHeader {
+        enc: ENVELOPE_ENC.to_string(),
+        typ: ENVELOPE_TYP.to_string(),
+        alg: match sender_vk { Some(_) => ALG_AUTHCRYPT.to_string(), None => ALG_ANONCRYPT.to_string() },
+        recipients,
+    };
+    let protected = b64_encode(serde_json::to_string(&protected).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?.as_bytes());
+
+    let iv = aead::gen_nonce();
+    let mut ciphertext = aead::seal(message, Some(protected.as_bytes()), &iv, &cek);
+    let tag = ciphertext.split_off(message.len());
+
+    let envelope = Envelope {
+        protected,
+        iv: b64_encode(&iv.0),
+        ciphertext: b64_encode(&ciphertext),
+        tag: b64_encode(&tag),
+    };
+
+    serde_json::to_vec(&envelope).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
+}
+
+/// Decrypts a message packed for one of the verkeys created with create_packing_did.
+pub fn unpack_message(packed: &[u8]) -> VcxResult<UnpackedMessage> {
+    sodium_init();
+
+    let envelope: Envelope = serde_json::from_slice(packed)
+        .or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?;
+
+    let protected: ProtectedHeader = serde_json::from_slice(&b64_decode(&envelope.protected)?)
+        .or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?;
+
+    if protected.enc != ENVELOPE_ENC {
+        warn!("unsupported content encryption: {}", protected.enc);
+        return Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num));
+    }
+
+    let authcrypted = match protected.alg.as_str() {
+        x if x == ALG_AUTHCRYPT => true,
+        x if x == ALG_ANONCRYPT => false,
+        x => {
+            warn!("unsupported key wrapping: {}", x);
+            return Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num));
+        },
+    };
+
+    // the first recipient we hold the key of is us
+    let (recipient, (my_pk, my_sk)) = protected.recipients.iter()
+        .filter_map(|recipient| box_keypair(&recipient.header.kid).ok().map(|keys| (recipient, keys)))
+        .next()
+        .ok_or_else(|| {
+            warn!("message was not packed for any of our keys");
+            VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)
+        })?;
+
+    let encrypted_key = b64_decode(&recipient.encrypted_key)?;
+    let (sender_vk, cek) = match authcrypted {
+        true => {
+            let sender = recipient.header.sender.as_ref().ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+            let sender_vk = sealedbox::open(&b64_decode(sender)?, &my_pk, &my_sk)
+                .ok()
+                .and_then(|sender_vk| String::from_utf8(sender_vk).ok())
+                .ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+
+            let nonce = recipient.header.iv.as_ref().ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+            let nonce = box_::Nonce::from_slice(&b64_decode(nonce)?).ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+            let cek = box_::open(&encrypted_key, &nonce, &box_public_key(&sender_vk)?, &my_sk)
+                .or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?;
+            (Some(sender_vk), cek)
+        },
+        false => (None, sealedbox::open(&encrypted_key, &my_pk, &my_sk).or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?),
+    };
+
+    let cek = aead::Key::from_slice(&cek).ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+    let iv = aead::Nonce::from_slice(&b64_decode(&envelope.iv)?).ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+    let mut ciphertext = b64_decode(&envelope.ciphertext)?;
+    ciphertext.extend(b64_decode(&envelope.tag)?);
+
+    let message = aead::open(&ciphertext, Some(envelope.protected.as_bytes()), &iv, &cek)
+        .or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?;
+
+    Ok(UnpackedMessage { message, sender_verkey: sender_vk, recipient_verkey: recipient.header.kid.clone() })
+}
+
+/// Wraps a packed message in forward messages so it can travel through mediators.
+/// routing_vks are listed in the order the message travels, the last one being the
+/// mediator that hands it to to_vk.
+pub fn pack_forward(packed: Vec<u8>, to_vk: &str, routing_vks: &[String]) -> VcxResult<Vec<u8>> {
+    let mut packed = packed;
+    let mut to = to_vk.to_string();
+
+    for routing_vk in routing_vks.iter().rev() {
+        let forward = Forward {
+            msg_type: FORWARD_TYPE.to_string(),
+            to: to.clone(),
+            msg: serde_json::from_slice(&packed).or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?,
+        };
+        let forward = serde_json::to_vec(&forward).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+
+        debug!("forwarding packed message for {} through {}", to, routing_vk);
+        packed = pack_message(&forward, &[routing_vk.to_string()], None)?;
+        to = routing_vk.to_string();
+    }
+
+    Ok(packed)
+}
+
+/// Opens a forward message addressed to one of our routing keys.
+/// Returns the key the inner message should be delivered to and the inner packed message.
+pub fn unpack_forward(packed: &[u8]) -> VcxResult<(String, Vec<u8>)> {
+    let unpacked = unpack_message(packed)?;
+
+    let forward: Forward = serde_json::from_slice(&unpacked.message)
+        .or(Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)))?;
+
+    if forward.msg_type != FORWARD_TYPE { return Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)) }
+
+    Ok((forward.to, serde_json::to_vec(&forward.msg).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?))
+}
+
+/// Tells DIDComm envelopes apart from legacy authcrypted msgpack.
+pub fn is_packed(data: &[u8]) -> bool {
+    serde_json::from_slice::<Envelope>(data).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::constants::VERKEY;
+
+    static OTHER_VERKEY: &str = "91qMFrZjXDoi2Vc8Mm14Ys112tEZdDegBZZoembFEATE";
+
+    #[test]
+    fn test_pack_unpack_authcrypt() {
+        init!("true");
+        let packed = pack_message(b"hello", &[OTHER_VERKEY.to_string()], Some(VERKEY)).unwrap();
+        assert!(is_packed(&packed));
+
+        let unpacked = unpack_message(&packed).unwrap();
+        assert_eq!(unpacked.message, b"hello".to_vec());
+        assert_eq!(unpacked.recipient_verkey, OTHER_VERKEY);
+        assert_eq!(unpacked.sender_verkey, Some(VERKEY.to_string()));
+    }
+
+    #[test]
+    fn test_pack_unpack_anoncrypt() {
+        init!("true");
+        let packed = pack_message(b"hello", &[OTHER_VERKEY.to_string(), VERKEY.to_string()], None).unwrap();
+        let unpacked = unpack_message(&packed).unwrap();
+        assert_eq!(unpacked.message, b"hello".to_vec());
+        assert_eq!(unpacked.sender_verkey, None);
+    }
+
+    #[test]
+    fn test_envelope_matches_rfc_0019() {
+        init!("true");
+        let packed = pack_message(b"hello", &[OTHER_VERKEY.to_string()], Some(VERKEY)).unwrap();
+        let envelope: Value = serde_json::from_slice(&packed).unwrap();
+        let protected: Value = serde_json::from_slice(&b64_decode(envelope["protected"].as_str().unwrap()).unwrap()).unwrap();
+
+        assert_eq!(protected["enc"], json!("xchacha20poly1305_ietf"));
+        assert_eq!(protected["typ"], json!("JWM/1.0"));
+        assert_eq!(protected["alg"], json!("Authcrypt"));
+        assert_eq!(protected["recipients"][0]["header"]["kid"], json!(OTHER_VERKEY));
+        assert_eq!(b64_decode(protected["recipients"][0]["header"]["iv"].as_str().unwrap()).unwrap().len(), box_::NONCEBYTES);
+        assert_eq!(b64_decode(envelope["iv"].as_str().unwrap()).unwrap().len(), aead::NONCEBYTES);
+        assert_eq!(b64_decode(envelope["tag"].as_str().unwrap()).unwrap().len(), aead::TAGBYTES);
+
+        // other agents may leave out the base64 padding
+        let mut unpadded: Envelope = serde_json::from_slice(&packed).unwrap();
+        unpadded.iv = unpadded.iv.trim_right_matches('=').to_string();
+        unpadded.tag = unpadded.tag.trim_right_matches('=').to_string();
+        let unpadded = serde_json::to_vec(&unpadded).unwrap();
+        assert_eq!(unpack_message(&unpadded).unwrap().message, b"hello".to_vec());
+    }
+
+    #[test]
+    fn test_unpack_rejects_tampered_message() {
+        init!("true");
+        let packed = pack_message(b"hello", &[OTHER_VERKEY.to_string()], None).unwrap();
+        let mut envelope: Envelope = serde_json::from_slice(&packed).unwrap();
+        envelope.ciphertext = b64_encode(b"jello");
+        let tampered = serde_json::to_vec(&envelope).unwrap();
+
+        assert_eq!(unpack_message(&tampered), Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)));
+        assert_eq!(unpack_message(b"not an envelope"), Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)));
+        assert_eq!(pack_message(b"hello", &[], None), Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)));
+        assert!(!is_packed(b"not an envelope"));
+    }
+
+    #[test]
+    fn test_forward_through_mediators() {
+        init!("true");
+        let packed = pack_message(b"hello", &[VERKEY.to_string()], Some(OTHER_VERKEY)).unwrap();
+        let routed = pack_forward(packed.clone(), VERKEY, &["mediator1".to_string(), "mediator2".to_string()]).unwrap();
+
+        let (to, inner) = unpack_forward(&routed).unwrap();
+        assert_eq!(to, "mediator2");
+        let (to, inner) = unpack_forward(&inner).unwrap();
+        assert_eq!(to, VERKEY);
+        assert_eq!(unpack_message(&inner).unwrap().message, b"hello".to_vec());
+
+        assert_eq!(pack_forward(packed.clone(), VERKEY, &[]).unwrap(), packed);
+    }
+
+    #[test]
+    fn test_pack_unpack_with_wallet_keys() {
+        init!("false");
+        let (_, sender_vk) = create_packing_did().unwrap();
+        let (_, recipient_vk) = create_packing_did().unwrap();
+
+        let packed = pack_message(b"hello", &[OTHER_VERKEY.to_string(), recipient_vk.clone()], Some(&sender_vk)).unwrap();
+        let unpacked = unpack_message(&packed).unwrap();
+        assert_eq!(unpacked.message, b"hello".to_vec());
+        assert_eq!(unpacked.sender_verkey, Some(sender_vk.clone()));
+        assert_eq!(unpacked.recipient_verkey, recipient_vk);
+
+        // keys libindy created on its own can not pack
+        let (_, plain_vk) = create_and_store_my_did(None).unwrap();
+        assert_eq!(pack_message(b"hello", &[recipient_vk.clone()], Some(&plain_vk)), Err(VcxError::from_code(error::PACKING_KEY_NOT_FOUND.code_num)));
+        assert_eq!(check_packing_key(&plain_vk), Err(VcxError::from_code(error::PACKING_KEY_NOT_FOUND.code_num)));
+        check_packing_key(&sender_vk).unwrap();
+        let packed = pack_message(b"hello", &[plain_vk], Some(&sender_vk)).unwrap();
+        assert_eq!(unpack_message(&packed), Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)));
+        teardown!("false");
+    }
+}
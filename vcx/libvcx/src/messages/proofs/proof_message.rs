@@ -1,8 +1,9 @@
 extern crate serde_json;
 
+use std::collections::HashMap;
 use utils::{ error, serde_utils };
 use serde_json::Value;
-use error::proof::ProofError;
+use error::{ VcxError, VcxResult };
 
 static ISSUER_DID: &'static str = "issuer_did";
 static SEQUENCE_NUMBER: &'static str = "schema_seq_no";
@@ -30,43 +31,118 @@ impl ProofMessage {
         }
     }
 
-    pub fn to_string(&self) -> Result<String, u32> {
+    pub fn to_string(&self) -> VcxResult<String> {
         serde_json::to_string(&self).map_err(|err| {
             error!("{} with: {}", error::INVALID_PROOF.message, err);
-        error::INVALID_PROOF.code_num
+        VcxError::from_code(error::INVALID_PROOF.code_num)
         })
     }
 
-    pub fn from_str(payload:&str) -> Result<ProofMessage, u32> {
+    pub fn from_str(payload:&str) -> VcxResult<ProofMessage> {
         serde_json::from_str(payload)
             .map_err(|err| {
                 error!("{} with serde error: {}",error::INVALID_PROOF.message, err);
-                error::INVALID_PROOF.code_num
+                VcxError::from_code(error::INVALID_PROOF.code_num)
             })
     }
 
-    pub fn get_credential_info(&self) -> Result<Vec<(String, String, String)>, ProofError> {
+    pub fn get_credential_info(&self) -> VcxResult<Vec<CredInfo>> {
         let mut rtn = Vec::new();
 
         let credentials: Value = serde_json::from_str(&self.libindy_proof)
-            .or(Err(ProofError::CommonError(error::INVALID_JSON.code_num)))?;
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
 
         if let Value::Array(ref identifiers) = credentials["identifiers"] {
             for identifier in identifiers {
-                let reg_rev_id = String::new();
-                rtn.push((
-                    serde_utils::get_value_to_string("schema_id", identifier)
-                        .map_err(|e| ProofError::CommonError(e))?,
-                    serde_utils::get_value_to_string("cred_def_id", identifier)
-                        .map_err(|e| ProofError::CommonError(e))?,
-                    reg_rev_id
-                ));
+                rtn.push(CredInfo {
+                    schema_id: serde_utils::get_value_to_string("schema_id", identifier)?,
+                    cred_def_id: serde_utils::get_value_to_string("cred_def_id", identifier)?,
+                    rev_reg_id: identifier["rev_reg_id"].as_str().map(|x| x.to_string()),
+                    timestamp: identifier["timestamp"].as_u64(),
+                });
             }
         }
 
         Ok(rtn)
     }
 
+    /// The referents of the proof request and the credentials answering them.
+    pub fn get_requested_proof(&self) -> VcxResult<RequestedProof> {
+        let proof: Value = serde_json::from_str(&self.libindy_proof)
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+        serde_json::from_value(proof["requested_proof"].clone())
+            .or(Err(VcxError::from_code(error::INVALID_PROOF.code_num)))
+    }
+
+    /// The predicates proved with the credential at sub_proof_index.
+    pub fn get_proved_predicates(&self, sub_proof_index: usize) -> VcxResult<Vec<ProvedPredicate>> {
+        let proof: Value = serde_json::from_str(&self.libindy_proof)
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+        let ge_proofs = &proof["proof"]["proofs"][sub_proof_index]["primary_proof"]["ge_proofs"];
+        let ge_proofs = match ge_proofs.as_array() {
+            Some(x) => x,
+            None => return Ok(Vec::new()),
+        };
+
+        ge_proofs.iter()
+            .map(|ge_proof| serde_json::from_value(ge_proof["predicate"].clone()).or(Err(VcxError::from_code(error::INVALID_PROOF.code_num))))
+            .collect()
+    }
+
+    /// The encoded values of the attributes revealed from the credential at sub_proof_index, by
+    /// attribute name.
+    pub fn get_revealed_encodings(&self, sub_proof_index: usize) -> VcxResult<HashMap<String, String>> {
+        let proof: Value = serde_json::from_str(&self.libindy_proof)
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+        let revealed_attrs = &proof["proof"]["proofs"][sub_proof_index]["primary_proof"]["eq_proof"]["revealed_attrs"];
+        if revealed_attrs.is_null() {
+            return Ok(HashMap::new());
+        }
+
+        serde_json::from_value(revealed_attrs.clone()).or(Err(VcxError::from_code(error::INVALID_PROOF.code_num)))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CredInfo {
+    pub schema_id: String,
+    pub cred_def_id: String,
+    pub rev_reg_id: Option<String>,
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct RequestedProof {
+    #[serde(default)]
+    pub revealed_attrs: HashMap<String, RevealedAttr>,
+    #[serde(default)]
+    pub unrevealed_attrs: HashMap<String, SubProofReferent>,
+    #[serde(default)]
+    pub self_attested_attrs: HashMap<String, String>,
+    #[serde(default)]
+    pub predicates: HashMap<String, SubProofReferent>,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct RevealedAttr {
+    pub sub_proof_index: usize,
+    pub raw: String,
+    pub encoded: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct SubProofReferent {
+    pub sub_proof_index: usize,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Clone)]
+pub struct ProvedPredicate {
+    pub attr_name: String,
+    pub p_type: String,
+    pub value: i32,
 }
 
 #[cfg(test)]
@@ -102,8 +178,28 @@ pub mod tests {
         init!("true");
         let proof = create_default_proof();
         let credential_data = proof.get_credential_info().unwrap();
-        assert_eq!(&credential_data[0].0, "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0");
-        assert_eq!(&credential_data[0].1, "NcYxiDXkpYi6ov5FcYDi1e:3:CL:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0");
-        assert_eq!(&credential_data[0].2, "");
+        assert_eq!(credential_data[0].schema_id, "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0");
+        assert_eq!(credential_data[0].cred_def_id, "NcYxiDXkpYi6ov5FcYDi1e:3:CL:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0");
+        assert_eq!(credential_data[0].rev_reg_id, None);
+        assert_eq!(credential_data[0].timestamp, None);
+    }
+
+    #[test]
+    fn test_get_requested_proof() {
+        init!("true");
+        let proof = create_default_proof();
+        let requested_proof = proof.get_requested_proof().unwrap();
+        assert_eq!(requested_proof.revealed_attrs["attr1_referent"].raw, "Alex");
+        assert_eq!(requested_proof.unrevealed_attrs["attr2_referent"].sub_proof_index, 0);
+        assert_eq!(requested_proof.self_attested_attrs["attr3_referent"], "8-800-300");
+        assert_eq!(requested_proof.predicates["predicate1_referent"].sub_proof_index, 0);
+
+        let predicates = proof.get_proved_predicates(0).unwrap();
+        assert_eq!(predicates, vec![ProvedPredicate { attr_name: "age".to_string(), p_type: "GE".to_string(), value: 18 }]);
+        assert_eq!(proof.get_proved_predicates(1).unwrap(), Vec::new());
+
+        let encodings = proof.get_revealed_encodings(0).unwrap();
+        assert_eq!(encodings["name"], "1139481716457488690172217916278103335");
+        assert!(proof.get_revealed_encodings(1).unwrap().is_empty());
     }
 }
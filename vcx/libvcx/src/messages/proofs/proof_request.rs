@@ -4,7 +4,9 @@ extern crate serde_json;
 use std::collections::HashMap;
 use std::vec::Vec;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use messages::validation;
+use settings;
 
 static PROOF_REQUEST: &str = "PROOF_REQUEST";
 static PROOF_DATA: &str = "proof_request_data";
@@ -26,9 +28,23 @@ struct ProofTopic {
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct AttrInfo {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub name: String,
+    // every one of names has to come from the same credential
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub restrictions: Option<Vec<Filter>>,
+    pub names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restrictions: Option<Vec<Restriction>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct NonRevokedInterval {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -38,7 +54,34 @@ pub struct Filter {
     pub schema_name: Option<String>,
     pub schema_version: Option<String>,
     pub issuer_did: Option<String>,
-    pub cred_def_id: Option<String>
+    pub cred_def_id: Option<String>,
+    // "attr::<name>::value": "<value>", the credential has to hold exactly this value
+    #[serde(flatten)]
+    pub attr_values: HashMap<String, String>,
+}
+
+// A list of restrictions is met when any one of them is, groups nest further
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum Restriction {
+    And(AndGroup),
+    Or(OrGroup),
+    Filter(Filter),
+}
+
+// Groups hold nothing besides "$and"/"$or", a filter mixed into a group would be dropped otherwise
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AndGroup {
+    #[serde(rename = "$and")]
+    pub and: Vec<Restriction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OrGroup {
+    #[serde(rename = "$or")]
+    pub or: Vec<Restriction>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -47,7 +90,18 @@ pub struct PredicateInfo {
     pub p_type: String,
     pub p_value: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub restrictions: Option<Vec<Filter>>,
+    pub restrictions: Option<Vec<Restriction>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+// libindy expects the operators as symbols, earlier requests spelled them out
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PredicateType {
+    GE,
+    GT,
+    LE,
+    LT,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -63,6 +117,8 @@ pub struct ProofRequestData{
     data_version: String,
     pub requested_attributes: HashMap<String, AttrInfo>,
     pub requested_predicates: HashMap<String, PredicateInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_revoked: Option<NonRevokedInterval>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -77,6 +133,250 @@ pub struct ProofRequestMessage{
     pub msg_ref_id: Option<String>,
 }
 
+impl NonRevokedInterval {
+    pub fn is_empty(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+
+    pub fn validate(&self) -> VcxResult<()> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from > to => {
+                debug!("Invalid non_revoked interval: from {} is after to {}", from, to);
+                Err(VcxError::from_code(error::INVALID_REVOCATION_INTERVAL.code_num))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+impl PredicateType {
+    pub fn parse(p_type: &str) -> VcxResult<PredicateType> {
+        match p_type {
+            ">=" | "GE" => Ok(PredicateType::GE),
+            ">" | "GT" => Ok(PredicateType::GT),
+            "<=" | "LE" => Ok(PredicateType::LE),
+            "<" | "LT" => Ok(PredicateType::LT),
+            _ => {
+                debug!("Invalid predicate type: {}", p_type);
+                Err(VcxError::from_code(error::INVALID_PREDICATE.code_num))
+            },
+        }
+    }
+
+    /// The operator as it is written in proof requests.
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            PredicateType::GE => ">=",
+            PredicateType::GT => ">",
+            PredicateType::LE => "<=",
+            PredicateType::LT => "<",
+        }
+    }
+
+    /// The operator as it is written in the predicates of a proof.
+    pub fn proof_name(&self) -> &'static str {
+        match *self {
+            PredicateType::GE => "GE",
+            PredicateType::GT => "GT",
+            PredicateType::LE => "LE",
+            PredicateType::LT => "LT",
+        }
+    }
+}
+
+/// Attribute names are compared the way libindy does, ignoring case and spaces.
+pub fn attr_common_view(name: &str) -> String {
+    name.replace(" ", "").to_lowercase()
+}
+
+impl Filter {
+    fn attr_constraints(&self) -> VcxResult<Vec<(&str, &str)>> {
+        self.attr_values.iter()
+            .map(|(key, value)| {
+                let parts: Vec<&str> = key.split("::").collect();
+                match parts.len() {
+                    3 if parts[0] == "attr" && !parts[1].is_empty() && parts[2] == "value" => Ok((parts[1], value.as_str())),
+                    _ => {
+                        debug!("Invalid restriction key: {}", key);
+                        Err(VcxError::from_code(error::INVALID_RESTRICTION.code_num))
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// has_value tells whether the credential holds the value for the attribute.
+    pub fn is_met(&self, schema_id: &str, cred_def_id: &str, has_value: &Fn(&str, &str) -> bool) -> bool {
+        // schema ids are <issuer did>:2:<name>:<version>, credential definition ids <issuer did>:3:...
+        let schema_parts: Vec<&str> = schema_id.split(':').collect();
+        let (schema_issuer_did, schema_name, schema_version) = match schema_parts.len() {
+            4 => (schema_parts[0], schema_parts[2], schema_parts[3]),
+            _ => ("", "", ""),
+        };
+        let issuer_did = cred_def_id.split(':').next().unwrap_or_default();
+
+        let attrs_met = match self.attr_constraints() {
+            Ok(constraints) => constraints.iter().all(|&(name, value)| has_value(name, value)),
+            Err(_) => false,
+        };
+
+        attrs_met
+            && check_value(&self.schema_id, schema_id)
+            && check_value(&self.schema_issuer_did, schema_issuer_did)
+            && check_value(&self.schema_name, schema_name)
+            && check_value(&self.schema_version, schema_version)
+            && check_value(&self.issuer_did, issuer_did)
+            && check_value(&self.cred_def_id, cred_def_id)
+    }
+}
+
+fn check_value(control: &Option<String>, val: &str) -> bool {
+    match *control {
+        Some(ref control) => control == val,
+        None => true,
+    }
+}
+
+impl Restriction {
+    pub fn validate(&self) -> VcxResult<()> {
+        match *self {
+            Restriction::And(AndGroup { and: ref group }) | Restriction::Or(OrGroup { or: ref group }) => {
+                if group.is_empty() {
+                    debug!("Invalid restriction: empty group");
+                    return Err(VcxError::from_code(error::INVALID_RESTRICTION.code_num));
+                }
+                group.iter().map(|restriction| restriction.validate()).collect()
+            },
+            Restriction::Filter(ref filter) => filter.attr_constraints().map(|_| ()),
+        }
+    }
+
+    pub fn is_met(&self, schema_id: &str, cred_def_id: &str, has_value: &Fn(&str, &str) -> bool) -> bool {
+        match *self {
+            Restriction::And(AndGroup { ref and }) => and.iter().all(|r| r.is_met(schema_id, cred_def_id, has_value)),
+            Restriction::Or(OrGroup { ref or }) => or.iter().any(|r| r.is_met(schema_id, cred_def_id, has_value)),
+            Restriction::Filter(ref filter) => filter.is_met(schema_id, cred_def_id, has_value),
+        }
+    }
+
+    fn is_group(&self) -> bool {
+        match *self {
+            Restriction::Filter(_) => false,
+            _ => true,
+        }
+    }
+}
+
+pub fn has_restrictions(restrictions: &Option<Vec<Restriction>>) -> bool {
+    match *restrictions {
+        Some(ref restrictions) => !restrictions.is_empty(),
+        None => false,
+    }
+}
+
+/// Any one of the restrictions has to be met, no restrictions are always met.
+pub fn check_restrictions(restrictions: &Option<Vec<Restriction>>,
+                          schema_id: &str,
+                          cred_def_id: &str,
+                          has_value: &Fn(&str, &str) -> bool) -> bool {
+    if !has_restrictions(restrictions) {
+        return true;
+    }
+
+    restrictions.iter().flat_map(|restrictions| restrictions.iter())
+        .any(|restriction| restriction.is_met(schema_id, cred_def_id, has_value))
+}
+
+// Referents with names are answered through a referent per name (see indy_referents), which only
+// VCX provers do. Other agents ignore names, so a verifier has to opt in when it knows its provers.
+fn attribute_names_enabled() -> bool {
+    match settings::get_config_value(settings::CONFIG_ATTRIBUTE_NAMES) {
+        Ok(value) => value == "true",
+        Err(_) => false,
+    }
+}
+
+fn validate_restrictions(restrictions: &Option<Vec<Restriction>>) -> VcxResult<()> {
+    restrictions.iter().flat_map(|restrictions| restrictions.iter())
+        .map(|restriction| restriction.validate())
+        .collect()
+}
+
+// libindy only knows plain filters, requests with groups are left to vcx to enforce. Attribute
+// values are ignored by libindy, vcx checks those as well.
+fn indy_restrictions(restrictions: &Option<Vec<Restriction>>) -> Option<Vec<Restriction>> {
+    match *restrictions {
+        Some(ref list) if list.iter().any(|restriction| restriction.is_group()) => None,
+        ref other => other.clone(),
+    }
+}
+
+/// libindy asks for a single attribute per referent, each of the names of a referent is asked for
+/// with a referent of its own.
+pub fn indy_referent(referent: &str, name: &str) -> String {
+    format!("{}::{}", referent, name)
+}
+
+impl AttrInfo {
+    pub fn validate(&self) -> VcxResult<()> {
+        match self.names {
+            Some(ref names) if names.is_empty() || names.iter().any(|name| name.is_empty()) || !self.name.is_empty() => {
+                debug!("Invalid requested attribute, names has to be a list of attribute names without name: {:?}", self);
+                Err(VcxError::from_code(error::INVALID_REQUESTED_ATTRIBUTE.code_num))
+            },
+            None if self.name.is_empty() => {
+                debug!("Invalid requested attribute, either name or names is required: {:?}", self);
+                Err(VcxError::from_code(error::INVALID_REQUESTED_ATTRIBUTE.code_num))
+            },
+            _ => validate_restrictions(&self.restrictions),
+        }
+    }
+}
+
+impl PredicateInfo {
+    pub fn predicate_type(&self) -> VcxResult<PredicateType> {
+        PredicateType::parse(&self.p_type)
+    }
+}
+
+impl ProofRequestData {
+    /// The referents libindy is asked for the attributes of a referent with.
+    pub fn indy_referents(&self, referent: &str) -> Vec<String> {
+        match self.requested_attributes.get(referent).and_then(|attr| attr.names.as_ref()) {
+            Some(names) => names.iter().map(|name| indy_referent(referent, name)).collect(),
+            None => vec![referent.to_string()],
+        }
+    }
+
+    /// The request as libindy expects it, with every predicate operator written as a symbol and a
+    /// referent for each of the names of a referent.
+    pub fn to_indy_json(&self) -> VcxResult<String> {
+        let mut data = self.clone();
+        data.requested_attributes = HashMap::new();
+        for (referent, attr) in self.requested_attributes.iter() {
+            let mut attr = attr.clone();
+            attr.restrictions = indy_restrictions(&attr.restrictions);
+            match attr.names.take() {
+                Some(names) => {
+                    for name in names {
+                        let attr = AttrInfo { name: name.clone(), ..attr.clone() };
+                        data.requested_attributes.insert(indy_referent(referent, &name), attr);
+                    }
+                },
+                None => {
+                    data.requested_attributes.insert(referent.to_string(), attr);
+                },
+            }
+        }
+        for predicate in data.requested_predicates.values_mut() {
+            predicate.p_type = predicate.predicate_type()?.symbol().to_string();
+            predicate.restrictions = indy_restrictions(&predicate.restrictions);
+        }
+
+        serde_json::to_string(&data).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
+    }
+}
+
 impl ProofPredicates {
     pub fn create() -> ProofPredicates {
         ProofPredicates {
@@ -102,6 +402,7 @@ impl ProofRequestMessage {
                 data_version: String::new(),
                 requested_attributes:HashMap::new(),
                 requested_predicates: HashMap::new(),
+                non_revoked: None,
             },
             validate_rc: 0,
             msg_ref_id: None,
@@ -130,7 +431,7 @@ impl ProofRequestMessage {
                 self
             },
             Err(x) => {
-                self.validate_rc = x;
+                self.validate_rc = x.code();
                 self
             },
         }
@@ -160,10 +461,23 @@ impl ProofRequestMessage {
 
         let mut index = 1;
         for attr in proof_attrs {
-            if check_req_attrs.contains_key(&attr.name) {
-                check_req_attrs.insert(format!("{}_{}", attr.name, index), attr);
+            if let Err(x) = attr.validate() {
+                self.validate_rc = x.code();
+                return self
+            }
+            if attr.names.is_some() && !attribute_names_enabled() {
+                debug!("Requested attribute names are disabled, set {} to use them", settings::CONFIG_ATTRIBUTE_NAMES);
+                self.validate_rc = error::INVALID_REQUESTED_ATTRIBUTE.code_num;
+                return self
+            }
+            let name = match attr.names {
+                Some(ref names) => names.join("_"),
+                None => attr.name.clone(),
+            };
+            if check_req_attrs.contains_key(&name) {
+                check_req_attrs.insert(format!("{}_{}", name, index), attr);
             } else {
-                check_req_attrs.insert(attr.name.clone(), attr);
+                check_req_attrs.insert(name, attr);
             }
             index= index + 1;
         }
@@ -183,7 +497,18 @@ impl ProofRequestMessage {
         };
 
         let mut index = 1;
-        for attr in attr_values {
+        for mut attr in attr_values {
+            attr.p_type = match attr.predicate_type() {
+                Ok(p_type) => p_type.symbol().to_string(),
+                Err(x) => {
+                    self.validate_rc = x.code();
+                    return self
+                },
+            };
+            if let Err(x) = validate_restrictions(&attr.restrictions) {
+                self.validate_rc = x.code();
+                return self
+            }
             if check_predicates.contains_key(&attr.name) {
                 check_predicates.insert(format!("{}_{}", attr.name, index), attr);
             } else {
@@ -196,14 +521,36 @@ impl ProofRequestMessage {
         self
     }
 
-    pub fn serialize_message(&mut self) -> Result<String, u32> {
+    pub fn non_revoked(&mut self, interval: &str) -> &mut Self {
+        let interval: NonRevokedInterval = match serde_json::from_str(interval) {
+            Ok(i) => i,
+            Err(e) => {
+                debug!("Cannot parse non_revoked interval: {}", e);
+                self.validate_rc = error::INVALID_JSON.code_num;
+                return self
+            },
+        };
+
+        if let Err(x) = interval.validate() {
+            self.validate_rc = x.code();
+            return self
+        }
+
+        self.proof_request_data.non_revoked = match interval.is_empty() {
+            true => None,
+            false => Some(interval),
+        };
+        self
+    }
+
+    pub fn serialize_message(&mut self) -> VcxResult<String> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
 
         match serde_json::to_string(self) {
             Ok(x) => Ok(x),
-            Err(_) => Err(error::INVALID_JSON.code_num)
+            Err(_) => Err(VcxError::from_code(error::INVALID_JSON.code_num))
         }
     }
 
@@ -211,10 +558,10 @@ impl ProofRequestMessage {
         json!(self)[PROOF_DATA].to_string()
     }
 
-    pub fn to_string(&self) -> Result<String, u32> {
+    pub fn to_string(&self) -> VcxResult<String> {
         match serde_json::to_string(&self){
             Ok(s) => Ok(s),
-            Err(_) => Err(error::INVALID_JSON.code_num),
+            Err(_) => Err(VcxError::from_code(error::INVALID_JSON.code_num)),
         }
     }
 }
@@ -235,6 +582,7 @@ mod tests {
             data_version: String::new(),
             requested_attributes: HashMap::new(),
             requested_predicates: HashMap::new(),
+            non_revoked: None,
         };
         assert_eq!(request.proof_request_data, proof_data);
     }
@@ -285,13 +633,144 @@ mod tests {
     #[test]
     fn test_requested_predicates_constructed_correctly() {
         let mut check_predicates: HashMap<String, PredicateInfo> = HashMap::new();
-        let attr_info1: PredicateInfo = serde_json::from_str(r#"{ "name":"age","p_type":"GE","p_value":22, "restrictions":[ { "schema_id": "6XFh8yBzrpJQmNyZzgoTqB:2:schema_name:0.0.11", "schema_name":"Faber Student Info", "schema_version":"1.0", "schema_issuer_did":"6XFh8yBzrpJQmNyZzgoTqB", "issuer_did":"8XFh8yBzrpJQmNyZzgoTqB", "cred_def_id": "8XFh8yBzrpJQmNyZzgoTqB:3:CL:1766" }, { "schema_id": "5XFh8yBzrpJQmNyZzgoTqB:2:schema_name:0.0.11", "schema_name":"BYU Student Info", "schema_version":"1.0", "schema_issuer_did":"5XFh8yBzrpJQmNyZzgoTqB", "issuer_did":"66Fh8yBzrpJQmNyZzgoTqB", "cred_def_id": "66Fh8yBzrpJQmNyZzgoTqB:3:CL:1766" } ] }"#).unwrap();
+        let attr_info1: PredicateInfo = serde_json::from_str(r#"{ "name":"age","p_type":">=","p_value":22, "restrictions":[ { "schema_id": "6XFh8yBzrpJQmNyZzgoTqB:2:schema_name:0.0.11", "schema_name":"Faber Student Info", "schema_version":"1.0", "schema_issuer_did":"6XFh8yBzrpJQmNyZzgoTqB", "issuer_did":"8XFh8yBzrpJQmNyZzgoTqB", "cred_def_id": "8XFh8yBzrpJQmNyZzgoTqB:3:CL:1766" }, { "schema_id": "5XFh8yBzrpJQmNyZzgoTqB:2:schema_name:0.0.11", "schema_name":"BYU Student Info", "schema_version":"1.0", "schema_issuer_did":"5XFh8yBzrpJQmNyZzgoTqB", "issuer_did":"66Fh8yBzrpJQmNyZzgoTqB", "cred_def_id": "66Fh8yBzrpJQmNyZzgoTqB:3:CL:1766" } ] }"#).unwrap();
         check_predicates.insert("age".to_string(), attr_info1);
 
         let request = proof_request().requested_predicates(REQUESTED_PREDICATES).clone();
         assert_eq!(request.proof_request_data.requested_predicates, check_predicates);
     }
 
+    #[test]
+    fn test_requested_predicate_types() {
+        let request = proof_request()
+            .requested_predicates(r#"[{"name":"age","p_type":"<","p_value":65},{"name":"height","p_type":"GT","p_value":150}]"#)
+            .clone();
+        assert_eq!(request.proof_request_data.requested_predicates["age"].p_type, "<");
+        assert_eq!(request.proof_request_data.requested_predicates["height"].p_type, ">");
+
+        let mut request = proof_request().requested_predicates(r#"[{"name":"age","p_type":"=>","p_value":18}]"#).clone();
+        assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_PREDICATE.code_num)));
+    }
+
+    #[test]
+    fn test_restrictions() {
+        let restrictions: Vec<Restriction> = serde_json::from_str(r#"[{"$or":[{"issuer_did":"8XFh8yBzrpJQmNyZzgoTqB"},{"$and":[{"schema_name":"gvt"},{"attr::Sex::value":"male"}]}]}]"#).unwrap();
+        let restrictions = Some(restrictions);
+        let no_values = |_: &str, _: &str| false;
+        let male = |name: &str, value: &str| attr_common_view(name) == "sex" && value == "male";
+
+        assert!(check_restrictions(&restrictions, "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0", "8XFh8yBzrpJQmNyZzgoTqB:3:CL:1766", &no_values));
+        assert!(check_restrictions(&restrictions, "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0", "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1766", &male));
+        assert!(!check_restrictions(&restrictions, "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0", "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1766", &no_values));
+        assert!(!check_restrictions(&restrictions, "NcYxiDXkpYi6ov5FcYDi1e:2:xyz:1.0", "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1766", &male));
+        assert!(check_restrictions(&None, "", "", &no_values));
+        assert!(check_restrictions(&Some(Vec::new()), "", "", &no_values));
+
+        assert!(serde_json::from_str::<Restriction>(r#"{"$and":[{"schema_name":"gvt"}],"issuer_did":"8XFh8yBzrpJQmNyZzgoTqB"}"#).is_err());
+        assert!(serde_json::from_str::<Restriction>(r#"{"$or":[{"schema_name":"gvt"}],"attr::sex::value":"male"}"#).is_err());
+        assert!(serde_json::from_str::<Restriction>(r#"{"$and":[{"schema_name":"gvt"}],"$or":[{"schema_name":"xyz"}]}"#).is_err());
+
+        let mut request = proof_request().requested_attrs(r#"[{"name":"age","restrictions":[{"$and":[]}]}]"#).clone();
+        assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_RESTRICTION.code_num)));
+
+        let mut request = proof_request().requested_attrs(r#"[{"name":"age","restrictions":[{"attr::age":"18"}]}]"#).clone();
+        assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_RESTRICTION.code_num)));
+    }
+
+    #[test]
+    fn test_check_value() {
+        assert!(check_value(&Some("sdf".to_string()), "sdf"));
+        assert!(!check_value(&Some("eee".to_string()), "sdf"));
+        assert!(check_value(&None, "sdf"));
+    }
+
+    #[test]
+    fn test_requested_attrs_with_names() {
+        settings::set_config_value(settings::CONFIG_ATTRIBUTE_NAMES, "true");
+        let request = proof_request()
+            .requested_attrs(r#"[{"names":["first_name","last_name"],"restrictions":[{"schema_name":"id"}]},{"name":"age"}]"#)
+            .clone();
+        let data = &request.proof_request_data;
+        assert_eq!(data.requested_attributes["first_name_last_name"].names, Some(vec!["first_name".to_string(), "last_name".to_string()]));
+        assert_eq!(data.indy_referents("first_name_last_name"), vec!["first_name_last_name::first_name", "first_name_last_name::last_name"]);
+        assert_eq!(data.indy_referents("age"), vec!["age"]);
+
+        let indy_json: serde_json::Value = serde_json::from_str(&data.to_indy_json().unwrap()).unwrap();
+        assert_eq!(indy_json["requested_attributes"]["first_name_last_name::last_name"], json!({"name": "last_name", "restrictions": [{"schema_id": null, "schema_issuer_did": null, "schema_name": "id", "schema_version": null, "issuer_did": null, "cred_def_id": null}]}));
+        assert!(indy_json["requested_attributes"].get("first_name_last_name").is_none());
+        assert_eq!(indy_json["requested_attributes"]["age"], json!({"name": "age"}));
+
+        for invalid in [r#"[{"names":[]}]"#, r#"[{"name":"age","names":["age"]}]"#, r#"[{"restrictions":[]}]"#].iter() {
+            let mut request = proof_request().requested_attrs(invalid).clone();
+            assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_REQUESTED_ATTRIBUTE.code_num)));
+        }
+
+        settings::set_config_value(settings::CONFIG_ATTRIBUTE_NAMES, "false");
+        let mut request = proof_request().requested_attrs(r#"[{"names":["first_name","last_name"]}]"#).clone();
+        assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_REQUESTED_ATTRIBUTE.code_num)));
+    }
+
+    #[test]
+    fn test_to_indy_json_leaves_groups_to_vcx() {
+        let mut data: ProofRequestData = serde_json::from_str(::utils::constants::INDY_PROOF_REQ_JSON).unwrap();
+        data.requested_attributes.get_mut("attr1_referent").unwrap().restrictions =
+            serde_json::from_str(r#"[{"$and":[{"schema_name":"gvt"},{"schema_version":"1.0"}]}]"#).unwrap();
+        data.requested_attributes.get_mut("attr2_referent").unwrap().restrictions =
+            serde_json::from_str(r#"[{"schema_name":"gvt"}]"#).unwrap();
+
+        let indy_json: serde_json::Value = serde_json::from_str(&data.to_indy_json().unwrap()).unwrap();
+        assert!(indy_json["requested_attributes"]["attr1_referent"].get("restrictions").is_none());
+        assert_eq!(indy_json["requested_attributes"]["attr2_referent"]["restrictions"][0]["schema_name"], "gvt");
+    }
+
+    #[test]
+    fn test_predicate_type() {
+        assert_eq!(PredicateType::parse(">=").unwrap(), PredicateType::GE);
+        assert_eq!(PredicateType::parse("LE").unwrap(), PredicateType::LE);
+        assert_eq!(PredicateType::parse("ge"), Err(VcxError::from_code(error::INVALID_PREDICATE.code_num)));
+        assert_eq!(PredicateType::parse("=="), Err(VcxError::from_code(error::INVALID_PREDICATE.code_num)));
+
+        assert_eq!(PredicateType::parse("GT").unwrap().symbol(), ">");
+        assert_eq!(PredicateType::parse("<").unwrap().proof_name(), "LT");
+    }
+
+    #[test]
+    fn test_to_indy_json_writes_symbols() {
+        let mut data: ProofRequestData = serde_json::from_str(::utils::constants::INDY_PROOF_REQ_JSON).unwrap();
+        data.requested_predicates.get_mut("predicate1_referent").unwrap().p_type = "LT".to_string();
+        assert!(data.to_indy_json().unwrap().contains(r#""p_type":"<""#));
+
+        data.requested_predicates.get_mut("predicate1_referent").unwrap().p_type = "less".to_string();
+        assert_eq!(data.to_indy_json(), Err(VcxError::from_code(error::INVALID_PREDICATE.code_num)));
+    }
+
+    #[test]
+    fn test_proof_request_non_revoked() {
+        let mut request = proof_request()
+            .nonce("123432421212")
+            .requested_attrs(r#"[{"name":"age","non_revoked":{"to":1540498474}}]"#)
+            .requested_predicates("[]")
+            .non_revoked(r#"{"from":100,"to":1540498474}"#)
+            .clone();
+
+        let serialized_msg = request.serialize_message().unwrap();
+        assert!(serialized_msg.contains(r#""non_revoked":{"from":100,"to":1540498474}"#));
+        assert!(serialized_msg.contains(r#""age":{"name":"age","non_revoked":{"to":1540498474}}"#));
+
+        let request = proof_request().non_revoked("{}").clone();
+        assert_eq!(request.proof_request_data.non_revoked, None);
+        assert!(!json!(request).to_string().contains("non_revoked"));
+    }
+
+    #[test]
+    fn test_proof_request_invalid_non_revoked() {
+        let mut request = proof_request().non_revoked(r#"{"from":200,"to":100}"#).clone();
+        assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_REVOCATION_INTERVAL.code_num)));
+
+        let mut request = proof_request().non_revoked("invalid").clone();
+        assert_eq!(request.serialize_message(), Err(VcxError::from_code(error::INVALID_JSON.code_num)));
+    }
+
     #[test]
     fn test_indy_proof_req_parses_correctly() {
         let proof_req: ProofRequestData = serde_json::from_str(::utils::constants::INDY_PROOF_REQ_JSON).unwrap();
@@ -2,13 +2,17 @@ extern crate rust_base58;
 extern crate serde_json;
 extern crate serde;
 extern crate rmp_serde;
+extern crate rand;
 
 use settings;
 use utils::httpclient;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use serde::Deserialize;
 use self::rmp_serde::Deserializer;
 use messages::*;
+use messages::pack::{ self, DidCommService };
+use self::rand::Rng;
 
 pub struct SendMessage {
     message: String,
@@ -24,6 +28,7 @@ pub struct SendMessage {
     uid: String,
     title: Option<String>,
     detail: Option<String>,
+    service: Option<DidCommService>,
 }
 
 #[derive(Serialize, Debug, PartialEq, PartialOrd, Clone)]
@@ -68,6 +73,7 @@ impl SendMessage{
             uid: String::new(),
             title: None,
             detail: None,
+            service: None,
         }
     }
 
@@ -101,7 +107,18 @@ impl SendMessage{
         self
     }
 
-    pub fn send_secure(&mut self) -> Result<Vec<String>, u32> {
+    /// Delivers the payload, which must be a DIDComm envelope, to the service of the other
+    /// agent instead of handing it to our agent.
+    pub fn didcomm_service(&mut self, service: Option<DidCommService>) -> &mut Self {
+        self.service = service;
+        self
+    }
+
+    pub fn send_secure(&mut self) -> VcxResult<Vec<String>> {
+        if let Some(service) = self.service.clone() {
+            return self.send_to_service(&service);
+        }
+
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
@@ -115,13 +132,35 @@ impl SendMessage{
         }
 
         match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+            Err(err) => return Err(err),
             Ok(response) => result.push(parse_send_message_response(response)?),
         };
         debug!("sent message to agency");
         Ok(result.to_owned())
     }
 
+    fn send_to_service(&self, service: &DidCommService) -> VcxResult<Vec<String>> {
+        if self.validate_rc != error::SUCCESS.code_num {
+            return Err(VcxError::from_code(self.validate_rc))
+        }
+
+        let recipient_vk = service.recipient_keys.first().ok_or(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num))?;
+        let data = pack::pack_forward(self.payload.clone(), recipient_vk, &service.routing_keys)?;
+
+        debug!("sending packed message to {}", service.service_endpoint);
+        if !settings::test_agency_mode_enabled() {
+            httpclient::post_to_endpoint(&service.service_endpoint, &data)?;
+        }
+        debug!("sent message to {}", service.service_endpoint);
+
+        // the other agent does not answer with a uid, ours identifies the message in replies
+        let response = SendMessageResponse {
+            msg_type: MsgType { name: "MSGS_SENT".to_string(), ver: "1.0".to_string() },
+            uids: vec![rand::thread_rng().gen_ascii_chars().take(16).collect::<String>()],
+        };
+        Ok(vec![serde_json::to_string(&response).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?])
+    }
+
     pub fn set_title(&mut self, title: &str) -> &mut Self {
         self.title = Some(title.to_string());
         self
@@ -143,9 +182,9 @@ impl GeneralMessage for SendMessage{
     fn set_validate_rc(&mut self, rc: u32){ self.validate_rc = rc; }
     fn set_to_vk(&mut self, to_vk: String){ self.to_vk = to_vk; }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>, u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
 
         let create = CreateMessagePayload { msg_type: MsgType { name: "CREATE_MSG".to_string(), ver: "1.0".to_string(), }, mtype: self.message.to_string(), reply_to_msg_id: self.ref_msg_id.clone(), send_msg: true};
@@ -157,8 +196,8 @@ impl GeneralMessage for SendMessage{
         };
 
         debug!("SendMessage details: {:?}", detail);
-        let create = encode::to_vec_named(&create).or(Err(error::UNKNOWN_ERROR.code_num))?;
-        let detail = encode::to_vec_named(&detail).or(Err(error::UNKNOWN_ERROR.code_num))?;
+        let create = encode::to_vec_named(&create).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
+        let detail = encode::to_vec_named(&detail).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
         let mut bundle = Bundled::create(create);
         bundle.bundled.push(detail);
@@ -176,30 +215,30 @@ pub struct SendMessageResponse {
     uids: Vec<String>,
 }
 
-fn parse_send_message_response(response: Vec<u8>) -> Result<String, u32> {
+fn parse_send_message_response(response: Vec<u8>) -> VcxResult<String> {
     let data = unbundle_from_agency(response)?;
 
     if data.len() <= 1 {
-        return Err(error::INVALID_HTTP_RESPONSE.code_num);
+        return Err(VcxError::from_code(error::INVALID_HTTP_RESPONSE.code_num));
     }
 
     let mut de = Deserializer::new(&data[1][..]);
     let response: SendMessageResponse = Deserialize::deserialize(&mut de)
-        .or(Err(error::INVALID_MSGPACK.code_num))?;
+        .or(Err(VcxError::from_code(error::INVALID_MSGPACK.code_num)))?;
 
     debug!("messages: {:?}", response);
-    serde_json::to_string(&response).or(Err(error::INVALID_JSON.code_num))
+    serde_json::to_string(&response).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
 }
 
 
-pub fn parse_msg_uid(response: &str) -> Result<String,u32> {
+pub fn parse_msg_uid(response: &str) -> VcxResult<String> {
     serde_json::from_str::<serde_json::Value>(response)
-        .or(Err(error::INVALID_JSON.code_num))?["uids"]
+        .or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?["uids"]
         .as_array()
-        .map_or(Err(error::INVALID_JSON.code_num), |uids| {
+        .map_or(Err(VcxError::from_code(error::INVALID_JSON.code_num)), |uids| {
             Ok(uids[0]
                 .as_str()
-                .ok_or(error::INVALID_JSON.code_num)?
+                .ok_or(VcxError::from_code(error::INVALID_JSON.code_num))?
                 .to_string()
             )
         })
@@ -229,6 +268,7 @@ mod tests {
             uid: "123".to_string(),
             title: Some("this is the title".to_string()),
             detail: Some("this is the detail".to_string()),
+            service: None,
         };
 
         /* just check that it doesn't panic */
@@ -243,6 +283,30 @@ mod tests {
         assert_eq!("{\"@type\":{\"name\":\"MSG_SENT\",\"ver\":\"1.0\"},\"uids\":[\"ntc2ytb\"]}", result);
     }
 
+    #[test]
+    fn test_send_to_didcomm_service() {
+        init!("true");
+        let packed = pack::pack_message(b"hello", &[::utils::constants::VERKEY.to_string()], None).unwrap();
+        let service = DidCommService {
+            service_endpoint: "http://localhost:8080/didcomm".to_string(),
+            recipient_keys: vec![::utils::constants::VERKEY.to_string()],
+            routing_keys: vec!["mediator".to_string()],
+        };
+
+        let response = ::messages::send_message()
+            .edge_agent_payload(&packed)
+            .didcomm_service(Some(service.clone()))
+            .send_secure()
+            .unwrap();
+        assert_eq!(parse_msg_uid(&response[0]).unwrap().len(), 16);
+
+        let response = ::messages::send_message()
+            .edge_agent_payload(&b"not an envelope".to_vec())
+            .didcomm_service(Some(service))
+            .send_secure();
+        assert_eq!(response, Err(VcxError::from_code(error::INVALID_MESSAGE_ENVELOPE.code_num)));
+    }
+
     #[test]
     fn test_parse_send_message_bad_response() {
         init!("true");
@@ -268,14 +332,14 @@ mod tests {
         });
 
         let to_str = serde_json::to_string(&test_json).unwrap();
-        let uid = parse_msg_uid(&to_str).unwrap_err();
+        let uid = parse_msg_uid(&to_str).unwrap_err().code();
         assert_eq!(error::INVALID_JSON.code_num, uid);
 
         let test_val = "devin";
         let test_json = json!({});
 
         let to_str = serde_json::to_string(&test_json).unwrap();
-        let uid = parse_msg_uid(&to_str).unwrap_err();
+        let uid = parse_msg_uid(&to_str).unwrap_err().code();
         assert_eq!(error::INVALID_JSON.code_num, uid);
     }
 }
@@ -1,7 +1,7 @@
 extern crate rmp_serde;
 extern crate serde_json;
 
-use error::{ToErrorCode, messages};
+use error::{ VcxError, VcxResult };
 use serde::Deserialize;
 use self::rmp_serde::{encode, Deserializer};
 use messages::{Bundled, MsgType, bundle_for_agent, unbundle_from_agency, GeneralMessage};
@@ -47,13 +47,12 @@ impl DeleteConnectionPayload {
     }
 
     pub fn deserialize(data: Vec<u8>) ->
-    Result< DeleteConnectionPayload,
-        messages::MessageError> {
+    VcxResult<DeleteConnectionPayload> {
         let mut de = Deserializer::new(&data[..]);
         let message: Self = match Deserialize::deserialize(&mut de) {
             Ok(x) => x,
             Err(x) => {
-                return Err(messages::MessageError::MessagePackError())
+                return Err(VcxError::from_code(error::MESSAGE_PACK_ERROR.code_num))
             },
         };
         Ok(message)
@@ -71,7 +70,7 @@ impl DeleteConnection {
             agent_vk: String::new(),
         }
     }
-    pub fn send_secure(&mut self) -> Result<Vec<String>, u32> {
+    pub fn send_secure(&mut self) -> VcxResult<Vec<String>> {
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
@@ -81,7 +80,7 @@ impl DeleteConnection {
 
         let mut result = Vec::new();
         match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+            Err(err) => return Err(err),
             Ok(response) => {
                 let response = self.parse_response_as_delete_connection_payload(&response)?;
                 result.push(response);
@@ -91,15 +90,14 @@ impl DeleteConnection {
         Ok(result.to_owned())
     }
 
-    pub fn parse_response_as_delete_connection_payload(&self, response: &Vec<u8> ) -> Result<String, u32> {
+    pub fn parse_response_as_delete_connection_payload(&self, response: &Vec<u8> ) -> VcxResult<String> {
         if settings::test_agency_mode_enabled() {
             let data = response.clone();
             return Ok(serde_json::to_string(&DeleteConnectionPayload::deserialize(data.to_owned()).unwrap()).unwrap())
         }
         let data = unbundle_from_agency(response.clone())?;
-        let response = DeleteConnectionPayload::deserialize(data[0].to_owned())
-            .map_err(|e| e.to_error_code())?;
-        serde_json::to_string(&response).or(Err(error::INVALID_JSON.code_num))
+        let response = DeleteConnectionPayload::deserialize(data[0].to_owned())?;
+        serde_json::to_string(&response).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
     }
 
     fn print_info(&self) {
@@ -128,11 +126,11 @@ impl GeneralMessage for DeleteConnection{
     fn set_to_vk(&mut self, to_vk: String) { self.to_vk = to_vk; }
     fn set_validate_rc(&mut self, rc: u32){ self.validate_rc = rc; }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>,u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
-        let payload = encode::to_vec_named(&self.payload).or(Err(error::INVALID_JSON.code_num))?;
+        let payload = encode::to_vec_named(&self.payload).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
 
         let bundle = Bundled::create(payload);
 
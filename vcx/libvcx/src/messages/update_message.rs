@@ -6,6 +6,7 @@ extern crate rmp_serde;
 use settings;
 use utils::httpclient;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use messages::*;
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, PartialOrd)]
@@ -36,8 +37,8 @@ struct UpdateMessagesResponse {
 
 impl UpdateMessages{
 
-    pub fn send_secure(&mut self) -> Result<(), u32> {
-        let data = encode::to_vec_named(&self).or(Err(error::UNKNOWN_ERROR.code_num))?;
+    pub fn send_secure(&mut self) -> VcxResult<()> {
+        let data = encode::to_vec_named(&self).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
         trace!("update_message content: {:?}", data);
 
         let msg = Bundled::create(data).encode()?;
@@ -49,8 +50,8 @@ impl UpdateMessages{
             ::utils::httpclient::set_next_u8_response(::utils::constants::UPDATE_MESSAGES_RESPONSE.to_vec());
         }
 
-        match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+        match httpclient::post_u8_idempotent(&data) {
+            Err(err) => return Err(err),
             Ok(response) => if settings::test_agency_mode_enabled() && response.len() == 0 {
                 return Ok(());
             } else {
@@ -60,7 +61,7 @@ impl UpdateMessages{
     }
 }
 
-fn parse_update_messages_response(response: Vec<u8>) -> Result<(), u32> {
+fn parse_update_messages_response(response: Vec<u8>) -> VcxResult<()> {
     let data = unbundle_from_agency(response)?;
 
     debug!("parse_update_messages_response: {:?}", data[0]);
@@ -70,18 +71,18 @@ fn parse_update_messages_response(response: Vec<u8>) -> Result<(), u32> {
         Err(x) => {
             error!("Could not parse messagepack: {}", x);
 
-            return Err(error::INVALID_MSGPACK.code_num)
+            return Err(VcxError::from_code(error::INVALID_MSGPACK.code_num))
         },
     };
 
     Ok(())
 }
 
-pub fn update_agency_messages(status_code: &str, msg_json: &str) -> Result<(), u32> {
+pub fn update_agency_messages(status_code: &str, msg_json: &str) -> VcxResult<()> {
 
     debug!("updating agency messages {} to status code: {}", msg_json, status_code);
     let uids_by_conns: Vec<UIDsByConn> = serde_json::from_str(msg_json)
-        .map_err(|ec| {error::INVALID_JSON.code_num})?;
+        .map_err(|ec| VcxError::from_code(error::INVALID_JSON.code_num))?;
     let mut messages = UpdateMessages {
         msg_type: MsgType { name: "UPDATE_MSG_STATUS_BY_CONNS".to_string(), ver: "1.0".to_string(), },
         uids_by_conns,
@@ -90,7 +91,7 @@ pub fn update_agency_messages(status_code: &str, msg_json: &str) -> Result<(), u
 
     match messages.send_secure() {
         Ok(x) => Ok(x),
-        Err(x) => Err(error::POST_MSG_FAILURE.code_num)
+        Err(x) => Err(x)
     }
 }
 
@@ -6,6 +6,7 @@ extern crate rmp_serde;
 use settings;
 use utils::httpclient;
 use utils::error;
+use error::{ VcxError, VcxResult };
 use messages::*;
 use utils::constants::*;
 use serde::Deserialize;
@@ -74,13 +75,13 @@ impl UpdateProfileData{
                 self
             }
             Err(x) => {
-                self.validate_rc = x;
+                self.validate_rc = x.code();
                 self
             }
         }
     }
 
-    pub fn send_secure(&mut self) -> Result<Vec<String>, u32> {
+    pub fn send_secure(&mut self) -> VcxResult<Vec<String>> {
         let data = match self.msgpack() {
             Ok(x) => x,
             Err(x) => return Err(x),
@@ -93,7 +94,7 @@ impl UpdateProfileData{
         }
 
         match httpclient::post_u8(&data) {
-            Err(_) => return Err(error::POST_MSG_FAILURE.code_num),
+            Err(err) => return Err(err),
             Ok(response) => {
                 let response = parse_update_profile_response(response)?;
                 result.push(response);
@@ -118,11 +119,11 @@ impl GeneralMessage for UpdateProfileData{
     }
     fn set_to_vk(&mut self, to_vk: String){ /* nothing to do here for CreateKeymsg */ }
 
-    fn msgpack(&mut self) -> Result<Vec<u8>,u32> {
+    fn msgpack(&mut self) -> VcxResult<Vec<u8>> {
         if self.validate_rc != error::SUCCESS.code_num {
-            return Err(self.validate_rc)
+            return Err(VcxError::from_code(self.validate_rc))
         }
-        let data = encode::to_vec_named(&self.payload).or(Err(error::UNKNOWN_ERROR.code_num))?;
+        let data = encode::to_vec_named(&self.payload).or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
         trace!("update profile inner bundle: {:?}", data);
         let msg = Bundled::create(data).encode()?;
 
@@ -131,15 +132,15 @@ impl GeneralMessage for UpdateProfileData{
     }
 }
 
-fn parse_update_profile_response(response: Vec<u8>) -> Result<String, u32> {
+fn parse_update_profile_response(response: Vec<u8>) -> VcxResult<String> {
     let data = unbundle_from_agency(response)?;
 
     let mut de = Deserializer::new(&data[0][..]);
 
     let response: UpdateProfileResponse = Deserialize::deserialize(&mut de)
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
 
-    serde_json::to_string(&response).or(Err(error::INVALID_JSON.code_num))
+    serde_json::to_string(&response).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))
 }
 
 #[cfg(test)]
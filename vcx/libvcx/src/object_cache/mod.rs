@@ -1,34 +1,90 @@
 extern crate rand;
 
 use rand::Rng;
+use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use utils::error;
+use error::{ VcxError, VcxResult };
+use context;
+use object_store;
 
+/// Objects are reference counted so that the store lock is only held while looking up a handle.
+/// Closures on different handles can then run concurrently, e.g. from the threadpool.
+///
+/// Every object belongs to the context it was added in, handles of other contexts are invalid.
+///
+/// Persistent caches also store their objects as wallet records whenever they are added or
+/// successfully changed, so that they can be restored under the same handles, see object_store.
 pub struct ObjectCache<T>{
-    store: Mutex<HashMap<u32, Mutex<T>>>,
+    store: Mutex<HashMap<u32, Entry<T>>>,
+    persistence: Option<Persistence<T>>,
+}
+
+struct Persistence<T> {
+    record_type: &'static str,
+    serialize: fn(&T) -> String,
+}
+
+struct Entry<T> {
+    context: u32,
+    object: Arc<Mutex<T>>,
 }
 
 impl<T> Default for ObjectCache<T> {
     fn default() -> ObjectCache<T>
     {
         ObjectCache {
-            store: Default::default()
+            store: Default::default(),
+            persistence: None,
         }
     }
 }
 
 impl<T> ObjectCache<T> {
 
-    fn _lock_store(&self) -> Result<MutexGuard<HashMap<u32, Mutex<T>>>, u32> {
+    pub fn persistent(record_type: &'static str, serialize: fn(&T) -> String) -> ObjectCache<T> {
+        ObjectCache {
+            store: Default::default(),
+            persistence: Some(Persistence { record_type, serialize }),
+        }
+    }
+
+    // the stored form of the object, when it is stored
+    fn _serialize(&self, obj: &T) -> Option<String> {
+        match self.persistence {
+            Some(ref persistence) if object_store::is_enabled() => Some((persistence.serialize)(obj)),
+            _ => None,
+        }
+    }
+
+    fn _persist(&self, handle: u32, data: &str) {
+        if let Some(ref persistence) = self.persistence {
+            if let Err(err) = object_store::save(persistence.record_type, handle, data) {
+                error!("could not store {} {}, it will be restored as it was last stored: {}", persistence.record_type, handle, err);
+            }
+        }
+    }
+
+    fn _forget(&self, handle: u32) {
+        if let Some(ref persistence) = self.persistence {
+            if !object_store::is_enabled() { return; }
+
+            if let Err(err) = object_store::delete(persistence.record_type, handle) {
+                warn!("could not delete stored {} {}: {}", persistence.record_type, handle, err);
+            }
+        }
+    }
+
+    fn _lock_store(&self) -> VcxResult<MutexGuard<HashMap<u32, Entry<T>>>> {
         match self.store.lock() {
             Ok(g) => Ok(g),
             Err(e) => {
                 error!("Unable to lock Object Store: {:?}", e);
-                Err(10)
+                Err(VcxError::from_msg(10, "unable to lock object store"))
             }
         }
     }
@@ -38,36 +94,75 @@ impl<T> ObjectCache<T> {
             Ok(g) => g,
             Err(_) => return false
         };
-        store.contains_key(&handle)
+        store.get(&handle).map_or(false, |entry| entry.context == context::current())
     }
 
-    pub fn get<F,R>(&self, handle:u32, closure: F) -> Result<R,u32>
-        where F: Fn(&T) -> Result<R,u32> {
+    /// The context the object was added in. Unknown handles get the current context, in which
+    /// they are invalid as well.
+    pub fn context_of(&self, handle: u32) -> u32 {
+        let store = match self._lock_store() {
+            Ok(g) => g,
+            Err(_) => return context::current()
+        };
+        store.get(&handle).map_or_else(context::current, |entry| entry.context)
+    }
 
+    pub fn handles(&self) -> Vec<u32> {
+        let store = match self._lock_store() {
+            Ok(g) => g,
+            Err(_) => return Vec::new()
+        };
+        let context = context::current();
+        let handles = store.iter()
+            .filter(|&(_, entry)| entry.context == context)
+            .map(|(handle, _)| *handle)
+            .collect();
+        handles
+    }
+
+    fn _get_object(&self, handle: u32) -> VcxResult<Arc<Mutex<T>>> {
         let store = self._lock_store()?;
         match store.get(&handle) {
-            Some(m) => match m.lock() {
-                Ok(obj) => closure(obj.deref()),
-                Err(err) => return Err(10) //TODO better error
-            },
-            None => return Err(error::INVALID_OBJ_HANDLE.code_num)
+            Some(entry) if entry.context == context::current() => Ok(entry.object.clone()),
+            _ => Err(VcxError::from_code(error::INVALID_OBJ_HANDLE.code_num))
         }
     }
 
-    pub fn get_mut<F, R>(&self, handle:u32, closure: F) -> Result<R,u32>
-        where F: Fn(&mut T) -> Result<R,u32> {
+    pub fn get<F,R>(&self, handle:u32, closure: F) -> VcxResult<R>
+        where F: Fn(&T) -> VcxResult<R> {
 
-        let mut store = self._lock_store()?;
-        match store.get_mut(&handle) {
-            Some(m) => match m.lock() {
-                Ok(mut obj) => closure(obj.deref_mut()),
-                Err(err) => return Err(10) //TODO better error
+        let object = self._get_object(handle)?;
+        let result = match object.lock() {
+            Ok(obj) => closure(obj.deref()),
+            Err(err) => return Err(VcxError::from_msg(10, "unable to lock object")) //TODO better error
+        };
+        result
+    }
+
+    pub fn get_mut<F, R>(&self, handle:u32, closure: F) -> VcxResult<R>
+        where F: Fn(&mut T) -> VcxResult<R> {
+
+        let object = self._get_object(handle)?;
+        let result = match object.lock() {
+            Ok(mut obj) => {
+                let stored = self._serialize(obj.deref());
+                let result = closure(obj.deref_mut());
+                // failed closures may have changed the object half way, closures only reading
+                // it leave nothing to store
+                if let (true, Some(stored)) = (result.is_ok(), stored) {
+                    match self._serialize(obj.deref()) {
+                        Some(ref data) if *data != stored => self._persist(handle, data),
+                        _ => (),
+                    }
+                }
+                result
             },
-            None => return Err(error::INVALID_OBJ_HANDLE.code_num)
-        }
+            Err(err) => return Err(VcxError::from_msg(10, "unable to lock object")) //TODO better error
+        };
+        result
     }
 
-    pub fn add(&self, obj:T) -> Result<u32, u32> {
+    pub fn add(&self, obj:T) -> VcxResult<u32> {
         let mut store = self._lock_store()?;
 
         let mut new_handle = rand::thread_rng().gen::<u32>();
@@ -78,23 +173,69 @@ impl<T> ObjectCache<T> {
             new_handle = rand::thread_rng().gen::<u32>();
         }
 
-        match store.insert(new_handle, Mutex::new(obj)){
-            Some(_) => Ok(new_handle),
-            None => Ok(new_handle)
+        let object = Arc::new(Mutex::new(obj));
+        store.insert(new_handle, Entry { context: context::current(), object: object.clone() });
+        drop(store);
+
+        if self.persistence.is_some() {
+            if let Ok(obj) = object.lock() {
+                if let Some(data) = self._serialize(obj.deref()) {
+                    self._persist(new_handle, &data);
+                }
+            }
         }
+        Ok(new_handle)
     }
 
-    pub fn release(&self, handle:u32) -> Result<(),u32> {
+    /// Releases the object, a persistent cache also deletes its stored copy.
+    pub fn release(&self, handle:u32) -> VcxResult<()> {
+        {
+            let mut store = self._lock_store()?;
+            match store.get(&handle) {
+                Some(entry) if entry.context == context::current() => (),
+                _ => return Err(VcxError::from_code(error::INVALID_OBJ_HANDLE.code_num))
+            };
+            store.remove(&handle);
+        }
+        self._forget(handle);
+        Ok(())
+    }
+
+    /// Adds the stored objects of a persistent cache under the handles they were stored with.
+    pub fn restore<F>(&self, deserialize: F) -> VcxResult<usize>
+        where F: Fn(&str) -> Option<T> {
+        let record_type = match self.persistence {
+            Some(ref persistence) => persistence.record_type,
+            None => return Ok(0),
+        };
+
+        let objects = object_store::load(record_type)?;
+
         let mut store = self._lock_store()?;
-        match store.remove(&handle) {
-            Some(_) => Ok(()),
-            None => Err(error::INVALID_OBJ_HANDLE.code_num)
+        let mut restored = 0;
+        for (handle, data) in objects {
+            if store.contains_key(&handle) {
+                warn!("{} {} is already in use, not restored", record_type, handle);
+                continue;
+            }
+
+            match deserialize(&data) {
+                Some(obj) => {
+                    store.insert(handle, Entry { context: context::current(), object: Arc::new(Mutex::new(obj)) });
+                    restored += 1;
+                },
+                None => warn!("stored {} {} could not be restored", record_type, handle),
+            }
         }
+        debug!("restored {} {} objects", restored, record_type);
+        Ok(restored)
     }
 
-    pub fn drain(&self) -> Result<(), u32> {
+    /// Releases every object of the current context.
+    pub fn drain(&self) -> VcxResult<()> {
         let mut store = self._lock_store()?;
-        Ok(store.clear())
+        let context = context::current();
+        Ok(store.retain(|_, entry| entry.context != context))
     }
 }
 
@@ -128,6 +269,91 @@ mod tests{
 
     }
 
+    #[test]
+    fn other_handles_are_not_blocked() {
+        use std::sync::Arc;
+        use std::sync::mpsc::channel;
+        use std::thread;
+        use std::time::Duration;
+
+        let test: Arc<ObjectCache<u32>> = Arc::new(Default::default());
+        let busy = test.add(1).unwrap();
+        let free = test.add(2).unwrap();
+
+        let (started, wait_started) = channel();
+        let (release, wait_release) = channel::<()>();
+        let wait_release = ::std::sync::Mutex::new(wait_release);
+        let cache = test.clone();
+        let worker = thread::spawn(move || {
+            cache.get_mut(busy, |obj| {
+                started.send(()).unwrap();
+                wait_release.lock().unwrap().recv_timeout(Duration::from_secs(10)).unwrap();
+                *obj += 1;
+                Ok(())
+            }).unwrap();
+        });
+
+        wait_started.recv().unwrap();
+        assert_eq!(test.get(free, |obj| Ok(*obj)).unwrap(), 2);
+        release.send(()).unwrap();
+        worker.join().unwrap();
+        assert_eq!(test.get(busy, |obj| Ok(*obj)).unwrap(), 2);
+    }
+
+    #[test]
+    fn objects_are_only_visible_in_their_context() {
+        let test: ObjectCache<u32> = Default::default();
+        let default_handle = test.add(1).unwrap();
+
+        let tenant = ::context::create();
+        let tenant_handle = ::context::with_context(tenant, || {
+            assert!(test.get(default_handle, |obj| Ok(*obj)).is_err());
+            assert!(test.release(default_handle).is_err());
+            test.add(2).unwrap()
+        });
+
+        assert!(!test.has_handle(tenant_handle));
+        assert_eq!(test.handles(), vec![default_handle]);
+        assert_eq!(test.context_of(tenant_handle), tenant);
+        assert_eq!(test.context_of(default_handle), ::context::DEFAULT_CONTEXT);
+        assert_eq!(test.context_of(0), ::context::DEFAULT_CONTEXT);
+
+        ::context::with_context(tenant, || {
+            assert_eq!(test.get(tenant_handle, |obj| Ok(*obj)).unwrap(), 2);
+            test.drain().unwrap();
+            assert!(!test.has_handle(tenant_handle));
+        });
+        assert_eq!(test.get(default_handle, |obj| Ok(*obj)).unwrap(), 1);
+
+        ::context::release(tenant).unwrap();
+    }
+
+    #[test]
+    fn get_mut_stores_successful_changes() {
+        use error::VcxError;
+        use object_store;
+        use settings;
+        use utils::error;
+
+        init!("false");
+        settings::set_config_value(settings::CONFIG_PERSIST_OBJECTS, "true");
+        let test: ObjectCache<u32> = ObjectCache::persistent("test_object_cache", |obj| obj.to_string());
+        let handle = test.add(1).unwrap();
+        let stored = || object_store::load("test_object_cache").unwrap();
+        assert_eq!(stored(), vec![(handle, "1".to_string())]);
+
+        let failed: Result<(), VcxError> = test.get_mut(handle, |obj| {
+            *obj = 2;
+            Err(VcxError::from_code(error::INVALID_STATE.code_num))
+        });
+        assert!(failed.is_err());
+        assert_eq!(stored(), vec![(handle, "1".to_string())]);
+
+        test.get_mut(handle, |obj| { *obj = 3; Ok(()) }).unwrap();
+        assert_eq!(stored(), vec![(handle, "3".to_string())]);
+        teardown!("false");
+    }
+
     fn mut_object_test(){
         let test:ObjectCache<String> = Default::default();
         let handle = test.add(String::from("TEST")).unwrap();
@@ -0,0 +1,205 @@
+extern crate serde_json;
+
+use std::sync::Mutex;
+use settings;
+use utils::error;
+use utils::libindy::wallet;
+use error::{ VcxError, VcxResult };
+
+// Holds the handles stored for every record type, wallet records can't be listed without a search
+static INDEX_RECORD_TYPE: &str = "vcx_object_index";
+
+// Holds a json array per list name, for entries that are only ever added
+static LIST_RECORD_TYPE: &str = "vcx_object_list";
+
+lazy_static! {
+    // Serializes read-modify-write of the index records
+    static ref INDEX_LOCK: Mutex<()> = Default::default();
+}
+
+/// Objects are stored when persist_objects is set to "true" in the configuration.
+pub fn is_enabled() -> bool {
+    match settings::get_config_value(settings::CONFIG_PERSIST_OBJECTS) {
+        Ok(value) => value == "true",
+        Err(_) => false,
+    }
+}
+
+fn get_record_value(record_type: &str, id: &str) -> VcxResult<Option<String>> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let record = match wallet::get_record(record_type, id, &options) {
+        Ok(x) => x,
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => return Ok(None),
+        Err(x) => return Err(x),
+    };
+
+    let record: serde_json::Value = serde_json::from_str(&record)
+        .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid {} record {}: {}", record_type, id, err)))?;
+
+    Ok(record["value"].as_str().map(|value| value.to_string()))
+}
+
+fn get_index(record_type: &str) -> VcxResult<Vec<u32>> {
+    match get_record_value(INDEX_RECORD_TYPE, record_type)? {
+        Some(value) => serde_json::from_str(&value)
+            .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid index of {}: {}", record_type, err))),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn update_index<F>(record_type: &str, update: F) -> VcxResult<()>
+    where F: FnOnce(&mut Vec<u32>) {
+    let _lock = INDEX_LOCK.lock().unwrap();
+
+    let existing = get_record_value(INDEX_RECORD_TYPE, record_type)?.is_some();
+    let mut handles = get_index(record_type)?;
+    update(&mut handles);
+
+    let value = json!(handles).to_string();
+    if existing {
+        wallet::update_record_value(INDEX_RECORD_TYPE, record_type, &value)?;
+    } else {
+        wallet::add_record(INDEX_RECORD_TYPE, record_type, &value, "{}")?;
+    }
+    Ok(())
+}
+
+/// Stores the serialized object under its handle, replacing the previous state.
+pub fn save(record_type: &str, handle: u32, data: &str) -> VcxResult<()> {
+    let id = handle.to_string();
+
+    match wallet::update_record_value(record_type, &id, data) {
+        Ok(_) => Ok(()),
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => {
+            wallet::add_record(record_type, &id, data, "{}")?;
+            update_index(record_type, |handles| if !handles.contains(&handle) { handles.push(handle) })
+        },
+        Err(x) => Err(x),
+    }
+}
+
+pub fn delete(record_type: &str, handle: u32) -> VcxResult<()> {
+    match wallet::delete_record(record_type, &handle.to_string()) {
+        Ok(_) => (),
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => (),
+        Err(x) => return Err(x),
+    };
+
+    update_index(record_type, |handles| handles.retain(|h| *h != handle))
+}
+
+/// Every stored object of the given type with its handle.
+pub fn load(record_type: &str) -> VcxResult<Vec<(u32, String)>> {
+    let mut objects = Vec::new();
+
+    for handle in get_index(record_type)? {
+        match get_record_value(record_type, &handle.to_string())? {
+            Some(data) => objects.push((handle, data)),
+            None => warn!("{} {} is indexed but was not stored", record_type, handle),
+        }
+    }
+    Ok(objects)
+}
+
+/// Adds an entry to the named list kept in the wallet.
+pub fn append(list: &str, entry: serde_json::Value) -> VcxResult<()> {
+    let _lock = INDEX_LOCK.lock().unwrap();
+
+    let mut entries = get_list(list)?;
+    let existing = !entries.is_empty();
+    entries.push(entry);
+
+    let value = json!(entries).to_string();
+    if existing {
+        wallet::update_record_value(LIST_RECORD_TYPE, list, &value)?;
+    } else {
+        wallet::add_record(LIST_RECORD_TYPE, list, &value, "{}")?;
+    }
+    Ok(())
+}
+
+fn get_list(list: &str) -> VcxResult<Vec<serde_json::Value>> {
+    match get_record_value(LIST_RECORD_TYPE, list)? {
+        Some(value) => serde_json::from_str(&value)
+            .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid list {}: {}", list, err))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The entries of the named list, oldest first.
+pub fn list(list: &str) -> VcxResult<Vec<serde_json::Value>> {
+    let _lock = INDEX_LOCK.lock().unwrap();
+    get_list(list)
+}
+
+/// Reloads the stored connections, credentials and proofs under their previous handles.
+/// Called when the wallet is opened.
+pub fn restore_all() -> VcxResult<()> {
+    if !is_enabled() || settings::test_indy_mode_enabled() { return Ok(()); }
+
+    ::connection::restore_all()?;
+    ::issuer_credential::restore_all()?;
+    ::credential::restore_all()?;
+    ::proof::restore_all()?;
+    ::disclosed_proof::restore_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_cache::ObjectCache;
+
+    #[test]
+    fn test_save_load_and_delete() {
+        init!("false");
+        settings::set_config_value(settings::CONFIG_PERSIST_OBJECTS, "true");
+
+        save("test_object", 1234, r#"{"state":1}"#).unwrap();
+        save("test_object", 1234, r#"{"state":2}"#).unwrap();
+        save("test_object", 5678, r#"{"state":1}"#).unwrap();
+
+        let mut objects = load("test_object").unwrap();
+        objects.sort();
+        assert_eq!(objects, vec![(1234, r#"{"state":2}"#.to_string()), (5678, r#"{"state":1}"#.to_string())]);
+
+        delete("test_object", 1234).unwrap();
+        assert_eq!(load("test_object").unwrap(), vec![(5678, r#"{"state":1}"#.to_string())]);
+
+        teardown!("false");
+    }
+
+    #[test]
+    fn test_append_and_list() {
+        init!("false");
+
+        assert_eq!(list("test_list").unwrap(), Vec::<serde_json::Value>::new());
+        append("test_list", json!({"source_id": "first"})).unwrap();
+        append("test_list", json!({"source_id": "second"})).unwrap();
+        assert_eq!(list("test_list").unwrap(), vec![json!({"source_id": "first"}), json!({"source_id": "second"})]);
+
+        teardown!("false");
+    }
+
+    #[test]
+    fn test_objects_are_restored_under_their_handle() {
+        init!("false");
+        settings::set_config_value(settings::CONFIG_PERSIST_OBJECTS, "true");
+
+        let cache: ObjectCache<String> = ObjectCache::persistent("test_string", |s| s.clone());
+        let handle = cache.add("created".to_string()).unwrap();
+        cache.get_mut(handle, |s| { *s = "updated".to_string(); Ok(()) }).unwrap();
+
+        // shutdown releases the objects but keeps them stored
+        cache.drain().unwrap();
+        assert!(!cache.has_handle(handle));
+
+        assert_eq!(cache.restore(|data| Some(data.to_string())).unwrap(), 1);
+        assert_eq!(cache.get(handle, |s| Ok(s.clone())).unwrap(), "updated");
+
+        cache.release(handle).unwrap();
+        assert_eq!(cache.restore(|data| Some(data.to_string())).unwrap(), 0);
+
+        teardown!("false");
+    }
+}
@@ -8,23 +8,26 @@ use settings;
 use connection;
 use api::{ VcxStateType, ProofStateType };
 use std::collections::HashMap;
-use messages::proofs::proof_message::{ProofMessage};
+use messages::proofs::proof_message::{ProofMessage, CredInfo};
 use messages;
-use messages::proofs::proof_request::{ ProofRequestMessage };
+use messages::proofs::proof_request::{ ProofRequestMessage, NonRevokedInterval, PredicateInfo };
 use messages::GeneralMessage;
+use messages::get_message::MessagesByDid;
 use utils::error;
 use utils::constants::*;
-use utils::libindy::anoncreds::libindy_verifier_verify_proof;
+use utils::libindy::anoncreds::{libindy_verifier_verify_proof, get_rev_reg_def_json, get_rev_reg};
 use credential_def::{ retrieve_credential_def };
 use schema::{ LedgerSchema };
-use error::proof::ProofError;
-use error::ToErrorCode;
+use error::{ VcxError, VcxResult, VcxResultExt };
 use serde_json::Value;
-use utils::constants::DEFAULT_SERIALIZE_VERSION;
+use utils::serialization::{ self, Versioned };
 use object_cache::ObjectCache;
+use proof_compliance::proof_compliance;
+
+fn default_revocation_interval() -> String { String::from("{}") }
 
 lazy_static! {
-    static ref PROOF_MAP: ObjectCache<Proof> = Default::default();
+    static ref PROOF_MAP: ObjectCache<Proof> = ObjectCache::persistent("vcx_proof", Proof::to_string);
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -32,6 +35,8 @@ pub struct Proof {
     source_id: String,
     requested_attrs: String,
     requested_predicates: String,
+    #[serde(default = "default_revocation_interval")]
+    revocation_interval: String,
     msg_uid: String,
     ref_msg_id: String,
     prover_did: String,
@@ -49,10 +54,12 @@ pub struct Proof {
     agent_vk: String,
 }
 
+impl Versioned for Proof {}
+
 impl Proof {
     // leave this returning a u32 until we actually implement this method to do something
     // other than return success.
-    fn validate_proof_request(&self) -> Result<u32, u32> {
+    fn validate_proof_request(&self) -> VcxResult<u32> {
         //TODO: validate proof request
         Ok(error::SUCCESS.code_num)
     }
@@ -63,7 +70,7 @@ impl Proof {
                            schemas_json: &str,
                            credential_defs_json: &str,
                            rev_reg_defs_json: &str,
-                           rev_regs_json: &str) -> Result<u32, ProofError> {
+                           rev_regs_json: &str) -> VcxResult<u32> {
         if settings::test_indy_mode_enabled() {return Ok(error::SUCCESS.code_num);}
 
         debug!("starting libindy proof verification for {}", self.source_id);
@@ -75,7 +82,7 @@ impl Proof {
                                                   rev_regs_json).map_err(|err| {
                 error!("Error: {}, Proof {} wasn't valid", err, self.source_id);
                 self.proof_state = ProofStateType::ProofInvalid;
-                ProofError::InvalidProof()
+                VcxError::from_code(error::INVALID_PROOF.code_num)
         })?;
 
         if !valid {
@@ -88,80 +95,136 @@ impl Proof {
         Ok(error::SUCCESS.code_num)
     }
 
-    fn build_credential_defs_json(&self, credential_data: &Vec<(String, String, String)>) -> Result<String, ProofError> {
+    fn build_credential_defs_json(&self, credential_data: &Vec<CredInfo>) -> VcxResult<String> {
         debug!("{} building credentialdef json for proof validation", self.source_id);
         let mut credential_json: HashMap<String, serde_json::Value> = HashMap::new();
 
-        for &(_, ref cred_def_id, _) in credential_data.iter() {
+        for &CredInfo { ref cred_def_id, .. } in credential_data.iter() {
             if !credential_json.contains_key(cred_def_id) {
-                let (_, credential_def) = retrieve_credential_def(cred_def_id)
-                    .map_err(|ec| ProofError::CommonError(ec.to_error_code()))?;
+                let (_, credential_def) = retrieve_credential_def(cred_def_id)?;
 
                 let credential_def = serde_json::from_str(&credential_def)
-                    .or(Err(ProofError::InvalidCredData()))?;
+                    .or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))?;
 
                 credential_json.insert(cred_def_id.to_string(), credential_def);
             }
         }
 
         serde_json::to_string(&credential_json).map_err(|err| {
-            ProofError::CommonError(error::INVALID_CREDENTIAL_DEF_JSON.code_num)
+            VcxError::from(error::INVALID_CREDENTIAL_DEF_JSON.code_num)
         })
     }
 
-    fn build_proof_json(&self) -> Result<String, ProofError> {
+    fn build_proof_json(&self) -> VcxResult<String> {
         debug!("{} building proof json for proof validation", self.source_id);
         match self.proof {
             Some(ref x) => Ok(x.libindy_proof.clone()),
-            None => Err(ProofError::InvalidProof()),
+            None => Err(VcxError::from_code(error::INVALID_PROOF.code_num)),
         }
     }
 
-    fn build_schemas_json(&self, credential_data: &Vec<(String, String, String)>) -> Result<String, ProofError> {
+    fn build_schemas_json(&self, credential_data: &Vec<CredInfo>) -> VcxResult<String> {
         debug!("{} building schemas json for proof validation", self.source_id);
 
         let mut schema_json: HashMap<String, serde_json::Value> = HashMap::new();
 
-        for &(ref schema_id, _, _) in credential_data.iter() {
+        for &CredInfo { ref schema_id, .. } in credential_data.iter() {
             if !schema_json.contains_key(schema_id) {
                 let schema = LedgerSchema::new_from_ledger(schema_id)
-                    .or(Err(ProofError::InvalidSchema()))?;
+                    .or(Err(VcxError::from_code(error::INVALID_SCHEMA.code_num)))?;
 
                 let schema_val = serde_json::from_str(&schema.schema_json)
-                    .or(Err(ProofError::InvalidSchema()))?;
+                    .or(Err(VcxError::from_code(error::INVALID_SCHEMA.code_num)))?;
 
                 schema_json.insert(schema_id.to_string(), schema_val);
             }
         }
 
-        serde_json::to_string(&schema_json).or(Err(ProofError::InvalidSchema()))
+        serde_json::to_string(&schema_json).or(Err(VcxError::from_code(error::INVALID_SCHEMA.code_num)))
+    }
+
+    fn build_rev_reg_defs_json(&self, credential_data: &Vec<CredInfo>) -> VcxResult<String> {
+        debug!("{} building rev_reg_def json for proof validation", self.source_id);
+
+        let mut rev_reg_defs_json: HashMap<String, serde_json::Value> = HashMap::new();
+
+        for &CredInfo { ref rev_reg_id, .. } in credential_data.iter() {
+            if let Some(ref rev_reg_id) = *rev_reg_id {
+                if !rev_reg_defs_json.contains_key(rev_reg_id) {
+                    let (_, rev_reg_def) = get_rev_reg_def_json(rev_reg_id)?;
+
+                    let rev_reg_def = serde_json::from_str(&rev_reg_def)
+                        .or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))?;
+
+                    rev_reg_defs_json.insert(rev_reg_id.to_string(), rev_reg_def);
+                }
+            }
+        }
+
+        serde_json::to_string(&rev_reg_defs_json).or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))
+    }
+
+    // The accumulator for each registry is rebuilt from the ledger deltas up to the timestamp the
+    // prover used. A credential revoked before that point can't produce a valid non-revocation proof.
+    fn build_rev_regs_json(&self, credential_data: &Vec<CredInfo>) -> VcxResult<String> {
+        debug!("{} building rev_reg json for proof validation", self.source_id);
+
+        let mut rev_regs_json: HashMap<String, HashMap<String, serde_json::Value>> = HashMap::new();
+
+        for &CredInfo { ref rev_reg_id, ref timestamp, .. } in credential_data.iter() {
+            if let (&Some(ref rev_reg_id), &Some(timestamp)) = (rev_reg_id, timestamp) {
+                let timestamps = rev_regs_json.entry(rev_reg_id.to_string()).or_insert(HashMap::new());
+
+                // libindy checks non-revocation against the accumulator at the timestamp of the proof
+                if !timestamps.contains_key(&timestamp.to_string()) {
+                    let (_, rev_reg, _) = get_rev_reg(rev_reg_id, timestamp)?;
+
+                    let rev_reg = serde_json::from_str(&rev_reg)
+                        .or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))?;
+
+                    timestamps.insert(timestamp.to_string(), rev_reg);
+                }
+            }
+        }
+
+        serde_json::to_string(&rev_regs_json).or(Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num)))
     }
 
-    fn build_proof_req_json(&self) -> Result<String, ProofError> {
+    fn build_proof_req_json(&self) -> VcxResult<String> {
         debug!("{} building proof request json for proof validation", self.source_id);
         match self.proof_request {
             Some(ref x) => {
-                Ok(x.get_proof_request_data())
+                x.proof_request_data.to_indy_json()
             },
-            None => Err(ProofError::InvalidProof()),
+            None => Err(VcxError::from_code(error::INVALID_PROOF.code_num)),
         }
     }
 
-    fn proof_validation(&mut self) -> Result<u32, ProofError> {
+    fn proof_validation(&mut self) -> VcxResult<u32> {
         let proof_req_msg = match self.proof_request.clone() {
             Some(x) => x,
-            None => return Err(ProofError::InvalidProof()),
+            None => return Err(VcxError::from_code(error::INVALID_PROOF.code_num)),
         };
 
         let proof_msg = match self.proof.clone() {
             Some(x) => x,
-            None => return Err(ProofError::InvalidProof()),
+            None => return Err(VcxError::from_code(error::INVALID_PROOF.code_num)),
         };
 
+        if let Err(err) = proof_compliance(&proof_req_msg.proof_request_data, &proof_msg) {
+            warn!("Proof {} is not compliant with its request: {}", self.source_id, err);
+            self.proof_state = if err.code() == error::INVALID_REVEALED_ATTRIBUTE_VALUE.code_num {
+                ProofStateType::ProofTampered
+            } else {
+                ProofStateType::ProofInvalid
+            };
+            return Ok(error::SUCCESS.code_num);
+        }
+
         let credential_data = proof_msg.get_credential_info()?;
 
         //if credential_data.len() == 0 {
-        //    return Err(ProofError::InvalidCredData())
+        //    return Err(VcxError::from_code(error::INVALID_PROOF_CREDENTIAL_DATA.code_num))
         //}
 
         let credential_def_msg = match self.build_credential_defs_json(&credential_data) {
@@ -173,27 +236,30 @@ impl Proof {
             Ok(x) => x,
             Err(_) => format!("{{}}"),
         };
+
+        let rev_reg_defs_json = self.build_rev_reg_defs_json(&credential_data)?;
+        let rev_regs_json = self.build_rev_regs_json(&credential_data)?;
+
         let proof_json = self.build_proof_json()?;
         let proof_req_json = self.build_proof_req_json()?;
         debug!("*******\n{}\n********", credential_def_msg);
         debug!("*******\n{}\n********", schemas_json);
         debug!("*******\n{}\n********", proof_json);
         debug!("*******\n{}\n********", proof_req_json);
-//        proof_compliance(&proof_req_msg.proof_request_data, &proof_msg)?;
-        self.validate_proof_indy(&proof_req_json, &proof_json, &schemas_json, &credential_def_msg, "{}", "{}")
+        self.validate_proof_indy(&proof_req_json, &proof_json, &schemas_json, &credential_def_msg, &rev_reg_defs_json, &rev_regs_json)
     }
 
-    fn send_proof_request(&mut self, connection_handle: u32) -> Result<u32, ProofError> {
+    fn send_proof_request(&mut self, connection_handle: u32) -> VcxResult<u32> {
         if self.state != VcxStateType::VcxStateInitialized {
             warn!("proof {} has invalid state {} for sending proofRequest", self.source_id, self.state as u32);
-            return Err(ProofError::ProofNotReadyError())
+            return Err(VcxError::from_code(error::NOT_READY.code_num))
         }
         debug!("sending proof request with proof: {}, and connection {}", self.source_id, connection_handle);
-        self.prover_did = connection::get_pw_did(connection_handle).map_err(|ec| ProofError::InvalidConnection())?;
-        self.agent_did = connection::get_agent_did(connection_handle).map_err(|ec| ProofError::InvalidConnection())?;
-        self.agent_vk = connection::get_agent_verkey(connection_handle).map_err(|ec| ProofError::InvalidConnection())?;
-        self.remote_vk = connection::get_their_pw_verkey(connection_handle).map_err(|ec| ProofError::InvalidConnection())?;
-        self.prover_vk = connection::get_pw_verkey(connection_handle).map_err(|ec| ProofError::InvalidConnection())?;
+        self.prover_did = connection::get_pw_did(connection_handle).map_err(|ec| VcxError::from_code(error::CONNECTION_ERROR.code_num))?;
+        self.agent_did = connection::get_agent_did(connection_handle).map_err(|ec| VcxError::from_code(error::CONNECTION_ERROR.code_num))?;
+        self.agent_vk = connection::get_agent_verkey(connection_handle).map_err(|ec| VcxError::from_code(error::CONNECTION_ERROR.code_num))?;
+        self.remote_vk = connection::get_their_pw_verkey(connection_handle).map_err(|ec| VcxError::from_code(error::CONNECTION_ERROR.code_num))?;
+        self.prover_vk = connection::get_pw_verkey(connection_handle).map_err(|ec| VcxError::from_code(error::CONNECTION_ERROR.code_num))?;
 
         debug!("prover_did: {} -- agent_did: {} -- agent_vk: {} -- remote_vk: {} -- prover_vk: {}",
                self.prover_did,
@@ -213,16 +279,18 @@ impl Proof {
             .proof_data_version(data_version)
             .requested_attrs(&self.requested_attrs)
             .requested_predicates(&self.requested_predicates)
-            .serialize_message()
-            .map_err(|ec| ProofError::ProofMessageError(ec))?;
+            .non_revoked(&self.revocation_interval)
+            .serialize_message()?;
 
         self.proof_request = Some(proof_obj);
-        let data = connection::generate_encrypted_payload(&self.prover_vk, &self.remote_vk, &proof_request, "PROOF_REQUEST").map_err(|_| ProofError::ProofConnectionError())?;
-        let title = format!("{} wants you to share {}", settings::get_config_value(settings::CONFIG_INSTITUTION_NAME).map_err(|e| ProofError::CommonError(e))?, self.name);
+        let data = connection::generate_payload_for_connection(connection_handle, &self.prover_vk, &self.remote_vk, &proof_request, "PROOF_REQUEST").map_err(|_| VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let service = connection::get_didcomm_service(connection_handle).map_err(|_| VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+        let title = format!("{} wants you to share {}", settings::get_config_value(settings::CONFIG_INSTITUTION_NAME)?, self.name);
 
         match messages::send_message().to(&self.prover_did)
             .to_vk(&self.prover_vk)
             .msg_type("proofReq")
+            .didcomm_service(service)
             .agent_did(&self.agent_did)
             .set_title(&title)
             .set_detail(&title)
@@ -236,16 +304,16 @@ impl Proof {
             },
             Err(x) => {
                 warn!("{} could not send proofReq: {}", self.source_id, x);
-                return Err(ProofError::ProofMessageError(x));
+                return Err(x);
             }
         }
     }
 
-    fn get_proof(&self) -> Result<String, ProofError> {
-        Ok(self.proof.as_ref().ok_or(ProofError::InvalidHandle())?.libindy_proof.clone())
+    fn get_proof(&self) -> VcxResult<String> {
+        Ok(self.proof.as_ref().ok_or(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num))?.libindy_proof.clone())
     }
 
-    fn get_proof_request_status(&mut self) -> Result<u32, ProofError> {
+    fn get_proof_request_status(&mut self, messages: Option<&MessagesByDid>) -> VcxResult<u32> {
         debug!("updating state for proof {} with msg_id {:?}", self.source_id, self.msg_uid);
         if self.state == VcxStateType::VcxStateAccepted {
             return Ok(self.get_state());
@@ -254,10 +322,9 @@ impl Proof {
             return Ok(self.get_state());
         }
 
-        let (_, payload) = messages::get_message::get_ref_msg(&self.msg_uid, &self.prover_did,
+        let (_, payload) = messages::get_message::get_ref_msg_from(messages, &self.msg_uid, &self.prover_did,
                                                          &self.prover_vk, &self.agent_did,
-                                                         &self.agent_vk)
-            .map_err(|ec| ProofError::ProofMessageError(ec))?;
+                                                         &self.agent_vk)?;
 
         self.proof = match parse_proof_payload(&payload) {
             Err(err) => return Ok(self.get_state()),
@@ -268,14 +335,14 @@ impl Proof {
 
         match self.proof_validation() {
             Ok(x) => {
-                if self.proof_state != ProofStateType::ProofInvalid {
+                if self.proof_state != ProofStateType::ProofInvalid && self.proof_state != ProofStateType::ProofTampered {
                     debug!("Proof format was validated for proof {}", self.source_id);
                     self.proof_state = ProofStateType::ProofValidated;
                 }
             }
             Err(x) => {
                 self.state = VcxStateType::VcxStateRequestReceived;
-                if x == ProofError::CommonError(error::TIMEOUT_LIBINDY_ERROR.code_num) {
+                if x == VcxError::from(error::TIMEOUT_LIBINDY_ERROR.code_num) {
                     warn!("Proof {} unable to be validated", self.source_id);
                     self.proof_state = ProofStateType::ProofUndefined;
                 } else {
@@ -288,8 +355,8 @@ impl Proof {
         Ok(self.get_state())
     }
 
-    fn update_state(&mut self) -> Result<u32, ProofError> {
-        self.get_proof_request_status()
+    fn update_state(&mut self) -> VcxResult<u32> {
+        self.get_proof_request_status(None)
     }
 
     fn get_state(&self) -> u32 {let state = self.state as u32; state}
@@ -301,18 +368,11 @@ impl Proof {
     fn get_source_id(&self) -> &String { &self.source_id }
 
     fn to_string(&self) -> String {
-        json!({
-            "version": DEFAULT_SERIALIZE_VERSION,
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
 
-    fn from_str(s: &str) -> Result<Proof, ProofError> {
-        let s:Value = serde_json::from_str(&s)
-            .or(Err(ProofError::InvalidJson()))?;
-        let proof: Proof = serde_json::from_value(s["data"].clone())
-            .or(Err(ProofError::InvalidJson()))?;
-        Ok(proof)
+    fn from_str(s: &str) -> VcxResult<Proof> {
+        serialization::deserialize(s).context(error::INVALID_JSON.code_num, "cannot deserialize proof")
     }
 
 }
@@ -320,15 +380,30 @@ impl Proof {
 pub fn create_proof(source_id: String,
                     requested_attrs: String,
                     requested_predicates: String,
-                    name: String) -> Result<u32, ProofError> {
+                    revocation_interval: String,
+                    name: String) -> VcxResult<u32> {
 
     // TODO: Get this to actually validate as json, not just check length.
     let length = requested_attrs.len();
     if length <= 0 {
-        return Err(ProofError::CommonError(error::INVALID_JSON.code_num))
+        return Err(VcxError::from(error::INVALID_JSON.code_num))
     }
 
-    debug!("creating proof with source_id: {}, name: {}, requested_attrs: {}, requested_predicates: {}", source_id, name, requested_attrs, requested_predicates);
+    debug!("creating proof with source_id: {}, name: {}, requested_attrs: {}, requested_predicates: {}, revocation_interval: {}",
+           source_id, name, requested_attrs, requested_predicates, revocation_interval);
+
+    let interval: NonRevokedInterval = serde_json::from_str(&revocation_interval)
+        .map_err(|_| VcxError::from_code(error::INVALID_JSON.code_num))?;
+    interval.validate().map_err(|_| VcxError::from_code(error::INVALID_REVOCATION_INTERVAL.code_num))?;
+
+    let predicates: Vec<PredicateInfo> = serde_json::from_str(&requested_predicates)
+        .map_err(|_| VcxError::from_code(error::INVALID_JSON.code_num))?;
+    for predicate in predicates.iter() {
+        predicate.predicate_type().map_err(|_| {
+            warn!("predicate {} has invalid type {}", predicate.name, predicate.p_type);
+            VcxError::from_code(error::INVALID_PREDICATE.code_num)
+        })?;
+    }
 
     let mut new_proof = Proof {
         source_id,
@@ -336,13 +411,14 @@ pub fn create_proof(source_id: String,
         ref_msg_id: String::new(),
         requested_attrs,
         requested_predicates,
+        revocation_interval,
         prover_did: String::new(),
         prover_vk: String::new(),
         state: VcxStateType::VcxStateNone,
         proof_state: ProofStateType::ProofUndefined,
         name,
         version: String::from("1.0"),
-        nonce: generate_nonce().map_err(|ec| ProofError::CommonError(ec))?,
+        nonce: generate_nonce()?,
         proof: None,
         proof_request: None,
         remote_did: String::new(),
@@ -351,11 +427,11 @@ pub fn create_proof(source_id: String,
         agent_vk: String::new(),
     };
 
-    new_proof.validate_proof_request().map_err(|ec| ProofError::CommonError(ec))?;
+    new_proof.validate_proof_request()?;
 
     new_proof.state = VcxStateType::VcxStateInitialized;
 
-    let new_handle = PROOF_MAP.add(new_proof).map_err(|ec|ProofError::CreateProofError())?;
+    let new_handle = PROOF_MAP.add(new_proof).map_err(|ec|VcxError::from_code(error::CREATE_PROOF_ERROR.code_num))?;
 
     Ok(new_handle)
 }
@@ -364,7 +440,12 @@ pub fn is_valid_handle(handle: u32) -> bool {
     PROOF_MAP.has_handle(handle)
 }
 
-pub fn update_state(handle: u32) -> Result<u32, ProofError> {
+/// The context the proof was created in, calls on the proof run in it.
+pub fn get_context(handle: u32) -> u32 {
+    PROOF_MAP.context_of(handle)
+}
+
+pub fn update_state(handle: u32) -> VcxResult<u32> {
     PROOF_MAP.get_mut(handle,|p|{
         match p.update_state() {
             Ok(x) => Ok(x),
@@ -373,25 +454,42 @@ pub fn update_state(handle: u32) -> Result<u32, ProofError> {
                 Ok(p.get_state())
             },
         }
-    }).map_err(|ec|ProofError::CommonError(ec))
+    })
+}
+
+/// Updates the state from messages the dispatcher already downloaded instead of polling the agency.
+pub fn update_state_with_messages(handle: u32, messages: &MessagesByDid) -> VcxResult<u32> {
+    PROOF_MAP.get_mut(handle,|p|{
+        match p.get_proof_request_status(Some(messages)) {
+            Ok(x) => Ok(x),
+            Err(x) => {
+                warn!("could not update state for proof {} from dispatched messages: {}", p.get_source_id(), x);
+                Ok(p.get_state())
+            },
+        }
+    })
+}
+
+pub fn get_handles() -> Vec<u32> {
+    PROOF_MAP.handles()
 }
 
-pub fn get_state(handle: u32) -> Result<u32, ProofError> {
+pub fn get_state(handle: u32) -> VcxResult<u32> {
     PROOF_MAP.get(handle,|p|{
         Ok(p.get_state())
-    }).map_err(|ec|ProofError::CommonError(ec))
+    })
 }
 
-pub fn get_proof_state(handle: u32) -> Result<u32, ProofError> {
+pub fn get_proof_state(handle: u32) -> VcxResult<u32> {
     PROOF_MAP.get(handle,|p|{
         Ok(p.get_proof_state())
-    }).map_err(|ec|ProofError::CommonError(ec))
+    })
 }
 
-pub fn release(handle: u32) -> Result<(), ProofError> {
+pub fn release(handle: u32) -> VcxResult<()> {
     match PROOF_MAP.release(handle) {
         Ok(_) => Ok(()),
-        Err(_) => Err(ProofError::InvalidHandle()),
+        Err(_) => Err(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num)),
     }
 }
 
@@ -401,86 +499,89 @@ pub fn release_all() {
         Err(_) => (),
     };
 }
-pub fn to_string(handle: u32) -> Result<String, ProofError> {
+
+/// Reloads the stored objects, see object_store
+pub fn restore_all() -> VcxResult<usize> {
+    PROOF_MAP.restore(|data| Proof::from_str(data).ok())
+}
+
+pub fn to_string(handle: u32) -> VcxResult<String> {
     PROOF_MAP.get(handle,|p|{
         Ok(Proof::to_string(&p))
-    }).map_err(|ec|ProofError::CommonError(ec))
+    })
 }
 
-pub fn get_source_id(handle: u32) -> Result<String, ProofError> {
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     PROOF_MAP.get(handle,|p|{
         Ok(p.get_source_id().clone())
-    }).map_err(|ec|ProofError::CommonError(ec))
+    })
 }
 
-pub fn from_string(proof_data: &str) -> Result<u32, ProofError> {
-    let derived_proof: Proof = Proof::from_str(proof_data).map_err(|err| {
-        warn!("{} with serde error: {}",error::INVALID_JSON.message, err);
-        ProofError::CommonError(error::INVALID_JSON.code_num)
-    })?;
+pub fn from_string(proof_data: &str) -> VcxResult<u32> {
+    let derived_proof: Proof = Proof::from_str(proof_data)?;
 
     let source_id = derived_proof.source_id.clone();
-    let new_handle = PROOF_MAP.add(derived_proof).map_err(|ec|ProofError::CommonError(ec))?;
+    let new_handle = PROOF_MAP.add(derived_proof)?;
 
     Ok(new_handle)
 }
 
-pub fn send_proof_request(handle: u32, connection_handle: u32) -> Result<u32, ProofError> {
+pub fn send_proof_request(handle: u32, connection_handle: u32) -> VcxResult<u32> {
     PROOF_MAP.get_mut(handle,|p|{
-        p.send_proof_request(connection_handle).map_err(|ec|ec.to_error_code())
-    }).map_err(|ec|ProofError::CommonError(ec))
+        p.send_proof_request(connection_handle)
+    })
 }
 
-fn get_proof_details(response: &str) -> Result<String, ProofError> {
+fn get_proof_details(response: &str) -> VcxResult<String> {
     match serde_json::from_str(response) {
         Ok(json) => {
             let json: serde_json::Value = json;
             let detail = match json["uids"].as_array() {
-                Some(x) => x[0].as_str().ok_or(ProofError::CommonError(error::INVALID_JSON.code_num))?,
+                Some(x) => x[0].as_str().ok_or(VcxError::from(error::INVALID_JSON.code_num))?,
                 None => {
                     warn!("response had no uid");
-                    return Err(ProofError::CommonError(error::INVALID_JSON.code_num))
+                    return Err(VcxError::from(error::INVALID_JSON.code_num))
                 },
             };
             Ok(String::from(detail))
         },
         Err(_) => {
             warn!("Proof called without a valid response from server");
-            return Err(ProofError::CommonError(error::INVALID_JSON.code_num))
+            return Err(VcxError::from(error::INVALID_JSON.code_num))
         },
     }
 }
 
-pub fn get_proof_uuid(handle: u32) -> Result<String,u32> {
+pub fn get_proof_uuid(handle: u32) -> VcxResult<String> {
     PROOF_MAP.get(handle,|p|{
         Ok(p.get_proof_uuid().clone())
     })
 }
 
-fn parse_proof_payload(payload: &Vec<u8>) -> Result<ProofMessage, u32> {
+fn parse_proof_payload(payload: &Vec<u8>) -> VcxResult<ProofMessage> {
     debug!("parsing proof payload: {:?}", payload);
     let data = messages::extract_json_payload(payload)?;
 
     let my_credential_req = ProofMessage::from_str(&data).map_err(|err| {
         warn!("invalid json {}", err);
-        error::INVALID_JSON.code_num
+        VcxError::from_code(error::INVALID_JSON.code_num)
     })?;
     Ok(my_credential_req)
 }
 
-pub fn get_proof(handle: u32) -> Result<String, ProofError> {
+pub fn get_proof(handle: u32) -> VcxResult<String> {
     PROOF_MAP.get(handle,|p|{
-        p.get_proof().map_err(|ec|ec.to_error_code())
-    }).map_err(|ec|ProofError::CommonError(ec))
+        p.get_proof()
+    })
 }
 
 // TODO: This doesnt feel like it should be here (maybe utils?)
-pub fn generate_nonce() -> Result<String, u32> {
-    let mut bn = BigNum::new().map_err(|err| error::BIG_NUMBER_ERROR.code_num)?;
+pub fn generate_nonce() -> VcxResult<String> {
+    let mut bn = BigNum::new().map_err(|err| VcxError::from_code(error::BIG_NUMBER_ERROR.code_num))?;
 
     BigNumRef::rand(&mut bn, LARGE_NONCE as i32, openssl::bn::MsbOption::MAYBE_ZERO, false)
-        .map_err(|_| error::BIG_NUMBER_ERROR.code_num)?;
-    Ok(bn.to_dec_str().map_err(|err| error::BIG_NUMBER_ERROR.code_num)?.to_string())
+        .map_err(|_| VcxError::from_code(error::BIG_NUMBER_ERROR.code_num))?;
+    Ok(bn.to_dec_str().map_err(|err| VcxError::from_code(error::BIG_NUMBER_ERROR.code_num))?.to_string())
 }
 
 #[cfg(test)]
@@ -503,6 +604,7 @@ mod tests {
             ref_msg_id: String::new(),
             requested_attrs: String::from("[]"),
             requested_predicates: String::from("[]"),
+            revocation_interval: "{}".to_string(),
             prover_did: String::from("GxtnGN6ypZYgEqcftSQFnC"),
             prover_vk: VERKEY.to_string(),
             state: VcxStateType::VcxStateOfferSent,
@@ -526,6 +628,7 @@ mod tests {
         create_proof("1".to_string(),
                      REQUESTED_ATTRS.to_owned(),
                      REQUESTED_PREDICATES.to_owned(),
+                     "{}".to_owned(),
                      "Optional".to_owned()).unwrap();
     }
 
@@ -541,6 +644,7 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
         let proof_string = to_string(handle).unwrap();
         let s:Value = serde_json::from_str(&proof_string).unwrap();
@@ -554,6 +658,7 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
         let proof_data = to_string(handle).unwrap();
         let proof1: Proof = Proof::from_str(&proof_data).unwrap();
@@ -570,6 +675,7 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
         assert!(release(handle).is_ok());
         assert!(!is_valid_handle(handle));
@@ -587,6 +693,7 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
         assert_eq!(send_proof_request(handle, connection_handle).unwrap(), error::SUCCESS.code_num);
         assert_eq!(get_state(handle).unwrap(), VcxStateType::VcxStateOfferSent as u32);
@@ -594,6 +701,27 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_send_proof_request_with_revocation_interval() {
+        init!("true");
+
+        let connection_handle = build_connection("test_send_proof_request_with_revocation_interval").unwrap();
+        connection::set_agent_verkey(connection_handle, VERKEY).unwrap();
+        connection::set_agent_did(connection_handle, DID).unwrap();
+        connection::set_their_pw_verkey(connection_handle, VERKEY).unwrap();
+
+        let handle = create_proof("1".to_string(),
+                                  REQUESTED_ATTRS.to_owned(),
+                                  REQUESTED_PREDICATES.to_owned(),
+                                  r#"{"to":1540498474}"#.to_owned(),
+                                  "Optional".to_owned()).unwrap();
+        assert_eq!(send_proof_request(handle, connection_handle).unwrap(), error::SUCCESS.code_num);
+
+        let proof_request = PROOF_MAP.get(handle, |p| Ok(p.proof_request.clone().unwrap())).unwrap();
+        assert_eq!(proof_request.proof_request_data.non_revoked,
+                   Some(NonRevokedInterval { from: None, to: Some(1540498474) }));
+    }
+
     #[test]
     fn test_send_proof_request_fails_with_no_pw() {
         //This test has 2 purposes:
@@ -607,6 +735,7 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
 
         assert!(send_proof_request(handle, connection_handle).is_err());
@@ -618,6 +747,7 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
         assert!(is_valid_handle(handle));
         assert!(get_proof(handle).is_err())
@@ -635,6 +765,7 @@ mod tests {
             ref_msg_id: String::new(),
             requested_attrs: String::from("[]"),
             requested_predicates: String::from("[]"),
+            revocation_interval: "{}".to_string(),
             prover_did: String::from("GxtnGN6ypZYgEqcftSQFnC"),
             prover_vk: VERKEY.to_string(),
             state: VcxStateType::VcxStateOfferSent,
@@ -669,6 +800,7 @@ mod tests {
             ref_msg_id: String::new(),
             requested_attrs: String::from("[]"),
             requested_predicates: String::from("[]"),
+            revocation_interval: "{}".to_string(),
             prover_did: String::from("GxtnGN6ypZYgEqcftSQFnC"),
             prover_vk: VERKEY.to_string(),
             state: VcxStateType::VcxStateOfferSent,
@@ -698,15 +830,21 @@ mod tests {
         /* converting proof to a string produces non-deterministic results */
     }
 
+    fn create_cred_infos() -> Vec<CredInfo> {
+        (1..4).map(|i| CredInfo {
+            schema_id: format!("schema_key{}", i),
+            cred_def_id: format!("cred_def_key{}", i),
+            rev_reg_id: None,
+            timestamp: None,
+        }).collect()
+    }
+
     #[test]
     fn test_build_credential_defs_json_with_multiple_credentials() {
         init!("true");
         let proof = create_boxed_proof();
 
-        let cred1 = ("schema_key1".to_string(), "cred_def_key1".to_string(), "".to_string());
-        let cred2 = ("schema_key2".to_string(), "cred_def_key2".to_string(), "".to_string());
-        let cred3 = ("schema_key3".to_string(), "cred_def_key3".to_string(), "".to_string());
-        let credentials = vec![cred1.clone(), cred2.clone(), cred3.clone()];
+        let credentials = create_cred_infos();
         let credential_json = proof.build_credential_defs_json(&credentials).unwrap();
 
         assert!(credential_json.contains(r#""cred_def_key1":{"id":"2hoqvcwupRTUNkXn6ArYzs:3:CL:2471""#));
@@ -718,10 +856,7 @@ mod tests {
     fn test_build_schemas_json_with_multiple_schemas() {
         init!("true");
         let proof = create_boxed_proof();
-        let cred1 = ("schema_key1".to_string(), "cred_def_key1".to_string(), "".to_string());
-        let cred2 = ("schema_key2".to_string(), "cred_def_key2".to_string(), "".to_string());
-        let cred3 = ("schema_key3".to_string(), "cred_def_key3".to_string(), "".to_string());
-        let credentials = vec![cred1.clone(), cred2.clone(), cred3.clone()];
+        let credentials = create_cred_infos();
         let credential_json = proof.build_schemas_json(&credentials).unwrap();
 
         assert!(credential_json.contains(r#""schema_key1":{"attrNames":["height","name","sex","age"],"id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4""#));
@@ -729,6 +864,29 @@ mod tests {
         assert!(credential_json.contains(r#""schema_key3":{"attrNames":["height","name","sex","age"],"id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4""#));
     }
 
+    #[test]
+    fn test_build_rev_reg_json() {
+        init!("true");
+        let proof = create_boxed_proof();
+
+        let mut credentials = create_cred_infos();
+        assert_eq!(proof.build_rev_reg_defs_json(&credentials).unwrap(), "{}");
+        assert_eq!(proof.build_rev_regs_json(&credentials).unwrap(), "{}");
+
+        credentials[0].rev_reg_id = Some(REV_REG_ID.to_string());
+        credentials[0].timestamp = Some(REV_REG_DELTA_TIMESTAMP);
+        credentials[1].rev_reg_id = Some(REV_REG_ID.to_string());
+        credentials[1].timestamp = Some(REV_REG_DELTA_TIMESTAMP);
+
+        let rev_reg_defs: serde_json::Value = serde_json::from_str(&proof.build_rev_reg_defs_json(&credentials).unwrap()).unwrap();
+        assert_eq!(rev_reg_defs.as_object().unwrap().len(), 1);
+        assert_eq!(rev_reg_defs[REV_REG_ID]["id"], REV_REG_ID);
+
+        let rev_regs: serde_json::Value = serde_json::from_str(&proof.build_rev_regs_json(&credentials).unwrap()).unwrap();
+        let rev_reg: serde_json::Value = serde_json::from_str(REV_REG_JSON).unwrap();
+        assert_eq!(rev_regs[REV_REG_ID][REV_REG_DELTA_TIMESTAMP.to_string()], rev_reg);
+    }
+
     #[test]
     fn test_get_proof() {
         init!("true");
@@ -746,17 +904,17 @@ mod tests {
     #[test]
     fn test_release_all() {
         init!("true");
-        let h1 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "Optional".to_owned()).unwrap();
-        let h2 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "Optional".to_owned()).unwrap();
-        let h3 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "Optional".to_owned()).unwrap();
-        let h4 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "Optional".to_owned()).unwrap();
-        let h5 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "Optional".to_owned()).unwrap();
+        let h1 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "{}".to_owned(), "Optional".to_owned()).unwrap();
+        let h2 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "{}".to_owned(), "Optional".to_owned()).unwrap();
+        let h3 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "{}".to_owned(), "Optional".to_owned()).unwrap();
+        let h4 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "{}".to_owned(), "Optional".to_owned()).unwrap();
+        let h5 = create_proof("1".to_string(), REQUESTED_ATTRS.to_owned(), REQUESTED_PREDICATES.to_owned(), "{}".to_owned(), "Optional".to_owned()).unwrap();
         release_all();
-        assert_eq!(release(h1).err(), Some(ProofError::InvalidHandle()));
-        assert_eq!(release(h2).err(), Some(ProofError::InvalidHandle()));
-        assert_eq!(release(h3).err(), Some(ProofError::InvalidHandle()));
-        assert_eq!(release(h4).err(), Some(ProofError::InvalidHandle()));
-        assert_eq!(release(h5).err(), Some(ProofError::InvalidHandle()));
+        assert_eq!(release(h1).err(), Some(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num)));
+        assert_eq!(release(h2).err(), Some(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num)));
+        assert_eq!(release(h3).err(), Some(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num)));
+        assert_eq!(release(h4).err(), Some(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num)));
+        assert_eq!(release(h5).err(), Some(VcxError::from_code(error::INVALID_PROOF_HANDLE.code_num)));
     }
 
     #[ignore]
@@ -776,6 +934,7 @@ mod tests {
             ref_msg_id: String::new(),
             requested_attrs: String::from("[]"),
             requested_predicates: REQUESTED_PREDICATES.to_string(),
+            revocation_interval: "{}".to_string(),
             prover_did: String::from("GxtnGN6ypZYgEqcftSQFnC"),
             prover_vk: VERKEY.to_string(),
             state: VcxStateType::VcxStateRequestReceived,
@@ -811,9 +970,10 @@ mod tests {
         let handle = create_proof("1".to_string(),
                                   REQUESTED_ATTRS.to_owned(),
                                   REQUESTED_PREDICATES.to_owned(),
+                                  "{}".to_owned(),
                                   "Optional".to_owned()).unwrap();
         set_libindy_rc(error::TIMEOUT_LIBINDY_ERROR.code_num);
-        assert_eq!(send_proof_request(handle, connection_handle).err(), Some(ProofError::CommonError(error::TIMEOUT_LIBINDY_ERROR.code_num)));
+        assert_eq!(send_proof_request(handle, connection_handle).err(), Some(VcxError::from(error::TIMEOUT_LIBINDY_ERROR.code_num)));
         assert_eq!(get_state(handle).unwrap(), VcxStateType::VcxStateInitialized as u32);
         assert_eq!(get_proof_uuid(handle).unwrap(), "");
 
@@ -837,7 +997,7 @@ mod tests {
         httpclient::set_next_u8_response(UPDATE_PROOF_RESPONSE.to_vec());
         //httpclient::set_next_u8_response(GET_PROOF_OR_CREDENTIAL_RESPONSE.to_vec());
 
-        proof.get_proof_request_status().unwrap();
+        proof.get_proof_request_status(None).unwrap();
         assert_eq!(proof.get_state(), VcxStateType::VcxStateRequestReceived as u32);
         assert_eq!(proof.get_proof_state(), ProofStateType::ProofInvalid as u32);
 
@@ -847,12 +1007,45 @@ mod tests {
         httpclient::set_next_u8_response(UPDATE_PROOF_RESPONSE.to_vec());
         proof.state = VcxStateType::VcxStateOfferSent;
         proof.proof_state = ProofStateType::ProofUndefined;
-        proof.get_proof_request_status().unwrap();
+        proof.get_proof_request_status(None).unwrap();
         proof.update_state().unwrap();
         assert_eq!(proof.get_state(), VcxStateType::VcxStateRequestReceived as u32);
         assert_eq!(proof.get_proof_state(), ProofStateType::ProofInvalid as u32);
     }
 
+    #[test]
+    fn test_proof_validation_checks_compliance() {
+        init!("true");
+
+        let mut proof_req_msg = ProofRequestMessage::create();
+        proof_req_msg.proof_request_data = serde_json::from_str(INDY_PROOF_REQ_JSON).unwrap();
+
+        let mut libindy_proof: Value = serde_json::from_str(INDY_PROOF_JSON).unwrap();
+        libindy_proof["requested_proof"]["revealed_attrs"]["attr1_referent"]["encoded"] =
+            json!("99262857098057710338306967609588410025648622308394250666849665532448612202874");
+
+        let validate = |libindy_proof: &Value| {
+            let mut proof_msg = ProofMessage::new();
+            proof_msg.libindy_proof = libindy_proof.to_string();
+
+            let mut proof = create_boxed_proof();
+            proof.proof = Some(proof_msg);
+            proof.proof_request = Some(proof_req_msg.clone());
+            proof.proof_validation().unwrap();
+            proof.proof_state
+        };
+
+        assert_eq!(validate(&libindy_proof), ProofStateType::ProofUndefined);
+
+        let mut tampered = libindy_proof.clone();
+        tampered["requested_proof"]["revealed_attrs"]["attr1_referent"]["raw"] = json!("Bob");
+        assert_eq!(validate(&tampered), ProofStateType::ProofTampered);
+
+        let mut incomplete = libindy_proof.clone();
+        incomplete["requested_proof"]["predicates"] = json!({});
+        assert_eq!(validate(&incomplete), ProofStateType::ProofInvalid);
+    }
+
     #[test]
     fn test_proof_errors() {
         use utils::error::{ INVALID_JSON, POST_MSG_FAILURE };
@@ -861,29 +1054,44 @@ mod tests {
         let mut proof = create_boxed_proof();
 
         assert_eq!(proof.validate_proof_indy("{}", "{}", "{}", "{}","", "").err(),
-                   Some(ProofError::InvalidProof()));
+                   Some(VcxError::from_code(error::INVALID_PROOF.code_num)));
 
         let bad_handle = 100000;
         // TODO: Do something to guarantee that this handle is bad
         assert_eq!(proof.send_proof_request(bad_handle).err(),
-                   Some(ProofError::ProofNotReadyError()));
+                   Some(VcxError::from_code(error::NOT_READY.code_num)));
         // TODO: Add test that returns a INVALID_PROOF_CREDENTIAL_DATA
-        assert_eq!(proof.get_proof_request_status().err(),
-                   Some(ProofError::ProofMessageError(POST_MSG_FAILURE.code_num)));
+        assert_eq!(proof.get_proof_request_status(None).err(),
+                   Some(VcxError::from(POST_MSG_FAILURE.code_num)));
 
         let empty = r#""#;
 
         assert_eq!(create_proof("my source id".to_string(),
                                 empty.to_string(),
                                 "{}".to_string(),
+                                "{}".to_string(),
+                                "my name".to_string()).err(),
+            Some(VcxError::from(INVALID_JSON.code_num)));
+
+        assert_eq!(create_proof("my source id".to_string(),
+                                REQUESTED_ATTRS.to_string(),
+                                REQUESTED_PREDICATES.to_string(),
+                                r#"{"from":1540498474,"to":100}"#.to_string(),
+                                "my name".to_string()).err(),
+            Some(VcxError::from_code(error::INVALID_REVOCATION_INTERVAL.code_num)));
+
+        assert_eq!(create_proof("my source id".to_string(),
+                                REQUESTED_ATTRS.to_string(),
+                                r#"[{"name":"age","p_type":"=","p_value":18}]"#.to_string(),
+                                "{}".to_string(),
                                 "my name".to_string()).err(),
-            Some(ProofError::CommonError(INVALID_JSON.code_num)));
+            Some(VcxError::from_code(error::INVALID_PREDICATE.code_num)));
 
-        assert_eq!(to_string(bad_handle).err(), Some(ProofError::CommonError(error::INVALID_OBJ_HANDLE.code_num)));
-        assert_eq!(get_source_id(bad_handle).err(), Some(ProofError::CommonError(error::INVALID_OBJ_HANDLE.code_num)));
-        assert_eq!(from_string(empty).err(), Some(ProofError::CommonError(INVALID_JSON.code_num)));
+        assert_eq!(to_string(bad_handle).err(), Some(VcxError::from(error::INVALID_OBJ_HANDLE.code_num)));
+        assert_eq!(get_source_id(bad_handle).err(), Some(VcxError::from(error::INVALID_OBJ_HANDLE.code_num)));
+        assert_eq!(from_string(empty).err(), Some(VcxError::from(INVALID_JSON.code_num)));
         let mut proof_good = create_boxed_proof();
-        assert_eq!(proof_good.get_proof_request_status().err(), Some(ProofError::ProofMessageError(POST_MSG_FAILURE.code_num)));
+        assert_eq!(proof_good.get_proof_request_status(None).err(), Some(VcxError::from(POST_MSG_FAILURE.code_num)));
     }
 
     #[cfg(feature = "agency")]
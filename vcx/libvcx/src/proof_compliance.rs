@@ -1,439 +1,326 @@
-/*use messages::proofs::proof_message::{ ProofMessage, Attr, Identifier };
-use messages::proofs::proof_request::{ Filter, ProofRequestData };
+use messages::proofs::proof_message::{ ProofMessage, CredInfo, RequestedProof };
+use messages::proofs::proof_request::{ ProofRequestData, Restriction, attr_common_view, check_restrictions, has_restrictions };
 use std::collections::HashMap;
-use error::proof::ProofError;
-
-
-pub fn proof_compliance(request: &ProofRequestData, proof: &ProofMessage) -> Result<(), ProofError> {
-    verify_requested_attributes(request, proof)?;
-    verify_requested_predicates(request, proof)
+use utils::error;
+use utils::openssl::matches_encoding;
+use error::{ VcxError, VcxResult };
+
+// libindy checks the proof is valid for the proof request it is handed, not that it answers the
+// request this verifier sent. These checks make sure every referent is answered by a credential
+// meeting its restrictions and that revealed values are the values the issuer signed.
+
+/// Fails with FAILED_PROOF_COMPLIANCE when the proof does not answer the request and with
+/// INVALID_REVEALED_ATTRIBUTE_VALUE when a revealed value was tampered with.
+pub fn proof_compliance(request: &ProofRequestData, proof: &ProofMessage) -> VcxResult<()> {
+    let requested_proof = proof.get_requested_proof()?;
+    let credentials = proof.get_credential_info()?;
+
+    // a tampered value also fails the value restrictions it was forged for, check it first
+    verify_revealed_encodings(&requested_proof)?;
+    verify_requested_attributes(request, proof, &requested_proof, &credentials)?;
+    verify_requested_predicates(request, proof, &requested_proof, &credentials)
 }
 
-fn verify_requested_predicates(request: &ProofRequestData, proof: &ProofMessage) -> Result<(), ProofError> {
-    let provided_predicates = &proof.requested_proof.predicates;
-    let requested_predicates = &request.requested_predicates;
+fn not_compliant(msg: String) -> VcxError {
+    warn!("Proof Compliance: {}", msg);
+    VcxError::from_msg(error::FAILED_PROOF_COMPLIANCE.code_num, msg)
+}
 
-    for (predicate_uuid, requested_predicate) in requested_predicates.iter() {
-        let proof_id = match provided_predicates.get(predicate_uuid) {
-            Some(uuid) => uuid,
-            None => {
-                warn!("Proof Compliance: requested predicate id not found in proof");
-                return Err(ProofError::FailedProofCompliance())
-            }
-        };
+fn get_credential<'a>(credentials: &'a [CredInfo], sub_proof_index: usize, referent: &str) -> VcxResult<&'a CredInfo> {
+    credentials.get(sub_proof_index)
+        .ok_or_else(|| not_compliant(format!("no identifier for the credential of {}", referent)))
+}
 
-        let proof_data = match  proof.proof.proofs.get(proof_id) {
-            Some(x) => x,
-            None => {
-                warn!("Proof Compliance: proof id not found in proofs");
-                return Err(ProofError::FailedProofCompliance())
-            }
-        };
-        let proved_predicates = proof.get_predicates_from_credential(proof_id)
-            .map_err(|ec| ProofError::ProofMessageError(ec))?;
-        let predicate = proved_predicates.iter().find(|predicate| {
-            predicate.attr_info.clone().unwrap_or(Attr::new()).name == requested_predicate.attr_name
-        });
-
-        match predicate {
-            Some(x) => {
-                let identifier: &Identifier = proof.identifiers.get(proof_id)
-                    .ok_or(ProofError::FailedProofCompliance())?;
-
-                if !compare_specs(requested_predicate.restrictions.clone(),
-                               &identifier) {
-                    return Err(ProofError::FailedProofCompliance())
-                }
+fn verify_requested_attributes(request: &ProofRequestData, proof: &ProofMessage, requested_proof: &RequestedProof, credentials: &[CredInfo]) -> VcxResult<()> {
+    for (referent, attr) in request.requested_attributes.iter() {
+        let sub_proof_index = match attr.names {
+            Some(_) => names_sub_proof_index(request, requested_proof, referent)?,
+            None => match get_sub_proof_index(requested_proof, referent) {
+                Some(x) => x,
+                None if requested_proof.self_attested_attrs.contains_key(referent) => {
+                    if has_restrictions(&attr.restrictions) {
+                        return Err(not_compliant(format!("attribute {} was self attested but has restrictions", referent)));
+                    }
+                    debug!("attribute {} was self attested", referent);
+                    continue;
+                },
+                None => return Err(not_compliant(format!("attribute {} is missing from the proof", referent))),
             },
+        };
 
-            None => return Err(ProofError::FailedProofCompliance())
+        let credential = get_credential(credentials, sub_proof_index, referent)?;
+        if !compare_specs(&attr.restrictions, credential, proof, sub_proof_index)? {
+            return Err(not_compliant(format!("credential {} used for attribute {} does not meet its restrictions", credential.cred_def_id, referent)));
         }
     }
+
     Ok(())
 }
 
-fn verify_requested_attributes(request: &ProofRequestData, proof: &ProofMessage) -> Result<(), ProofError> {
-    let proof_revealed_attrs = &proof.requested_proof.revealed_attrs;
-    let self_attested_attrs = &proof.requested_proof.self_attested_attrs;
-    let requested_attrs = &request.requested_attrs;
-
-    for (key, val) in requested_attrs.iter() {
-        let proof_attr_data = match proof_revealed_attrs.get(key) {
-            Some(data) => data,
-            None => {
-                if val.restrictions.is_none() && self_attested(&val.name, self_attested_attrs)? {
-                    debug!("attribute: {} was self attested", val.name);
-                    continue
-                }
-                warn!("Proof Compliance: attr_id not found in proof");
-                return Err(ProofError::FailedProofCompliance())
-            }
-        };
-        let proof_id = &proof_attr_data.0;
-        let identifier = proof.identifiers.get(proof_id)
-            .ok_or(ProofError::FailedProofCompliance())?;
+fn get_sub_proof_index(requested_proof: &RequestedProof, referent: &str) -> Option<usize> {
+    requested_proof.revealed_attrs.get(referent).map(|revealed| revealed.sub_proof_index)
+        .or_else(|| requested_proof.unrevealed_attrs.get(referent).map(|unrevealed| unrevealed.sub_proof_index))
+}
 
-        if !compare_specs(val.restrictions.clone(),
-                          identifier) {
-            return Err(ProofError::FailedProofCompliance())
+// Each of the names of a referent is proved with a referent of its own, they all have to be
+// proved with the same credential.
+fn names_sub_proof_index(request: &ProofRequestData, requested_proof: &RequestedProof, referent: &str) -> VcxResult<usize> {
+    let mut sub_proof_indexes = Vec::new();
+    for indy_referent in request.indy_referents(referent) {
+        match get_sub_proof_index(requested_proof, &indy_referent) {
+            Some(x) => sub_proof_indexes.push(x),
+            None => return Err(not_compliant(format!("attribute {} is missing from the proof", indy_referent))),
         }
     }
 
-    Ok(())
+    if sub_proof_indexes.windows(2).any(|pair| pair[0] != pair[1]) {
+        return Err(not_compliant(format!("attributes of {} come from different credentials", referent)));
+    }
+
+    sub_proof_indexes.first().cloned()
+        .ok_or_else(|| not_compliant(format!("attribute {} has no names", referent)))
 }
 
-fn self_attested(attr_name: &str, self_attested_attrs: &HashMap<String, String>) -> Result<bool, ProofError> {
-    match self_attested_attrs.get(attr_name) {
-        Some(_) => Ok(true),
-        None => {
-            warn!("Proof Compliance: attr_id not found in proof");
-            Err(ProofError::FailedProofCompliance())
+fn verify_requested_predicates(request: &ProofRequestData, proof: &ProofMessage, requested_proof: &RequestedProof, credentials: &[CredInfo]) -> VcxResult<()> {
+    for (referent, predicate) in request.requested_predicates.iter() {
+        let sub_proof_index = match requested_proof.predicates.get(referent) {
+            Some(x) => x.sub_proof_index,
+            None => return Err(not_compliant(format!("predicate {} is missing from the proof", referent))),
+        };
+
+        let credential = get_credential(credentials, sub_proof_index, referent)?;
+        if !compare_specs(&predicate.restrictions, credential, proof, sub_proof_index)? {
+            return Err(not_compliant(format!("credential {} used for predicate {} does not meet its restrictions", credential.cred_def_id, referent)));
         }
-    }
-}
 
-fn compare_specs(requested_specs: Option<Vec<Filter>>, provided_spec: &Identifier) -> bool {
-    if requested_specs.is_none() {
-        return true;
+        let p_type = predicate.predicate_type().map_err(|err| {
+            let msg = format!("predicate {} has invalid type {}, expected one of <, <=, >, >=", referent, predicate.p_type);
+            warn!("Proof Compliance: {}", msg);
+            VcxError::from_msg(err, msg)
+        })?;
+
+        let proved = proof.get_proved_predicates(sub_proof_index)?.into_iter()
+            .any(|proved| proved.attr_name == predicate.name && proved.p_type == p_type.proof_name() && proved.value == predicate.p_value);
+        if !proved {
+            return Err(not_compliant(format!("predicate {} was not proved", referent)));
+        }
     }
-    let requested_specs: Vec<Filter> = requested_specs.unwrap_or_default();
-    let provided_schema_key = &provided_spec.schema_key;
-
-    for cmp_spec in requested_specs.iter() {
-        let same_issuer_did = check_value(cmp_spec.issuer_did.clone(), &provided_spec.issuer_did);
-        let same_schema_key = match cmp_spec.schema_key.clone() {
-            Some(SchemaKeyFilter{ did, version, name }) => {
-                let same_did = check_value(did, &provided_schema_key.did);
-                let same_name = check_value(name, &provided_schema_key.name);
-                let same_version = check_value(version, &provided_schema_key.version);
-                same_did && same_name && same_version
-            },
-            None => true
-        };
 
-        if same_issuer_did && same_schema_key {
-            return true;
+    Ok(())
+}
+
+/// libindy only proves the encoded values, the raw values next to them could be anything.
+fn verify_revealed_encodings(requested_proof: &RequestedProof) -> VcxResult<()> {
+    for (referent, attr) in requested_proof.revealed_attrs.iter() {
+        if !matches_encoding(&attr.raw, &attr.encoded)? {
+            let msg = format!("revealed value of attribute {} does not match its encoding", referent);
+            warn!("Proof Compliance: {}", msg);
+            return Err(VcxError::from_msg(error::INVALID_REVEALED_ATTRIBUTE_VALUE.code_num, msg));
         }
     }
-    return false;
+
+    Ok(())
 }
 
-fn check_value<T: PartialEq>(control: Option<T>, val: &T) -> bool {
-    if control.is_none() {
-        return true;
+// Attribute value restrictions can only be met by attributes revealed from the same credential,
+// their encoded values are what the issuer signed.
+fn compare_specs(restrictions: &Option<Vec<Restriction>>, credential: &CredInfo, proof: &ProofMessage, sub_proof_index: usize) -> VcxResult<bool> {
+    if !has_restrictions(restrictions) {
+        return Ok(true);
     }
-    let rtn = control.unwrap().eq(val);
 
-    rtn
+    let revealed: HashMap<String, String> = proof.get_revealed_encodings(sub_proof_index)?.into_iter()
+        .map(|(name, encoded)| (attr_common_view(&name), encoded))
+        .collect();
+
+    let has_value = |name: &str, value: &str| {
+        match revealed.get(&attr_common_view(name)) {
+            Some(encoded) => matches_encoding(value, encoded).unwrap_or(false),
+            None => false,
+        }
+    };
+
+    Ok(check_restrictions(restrictions, &credential.schema_id, &credential.cred_def_id, &has_value))
 }
 
 #[cfg(test)]
 mod tests {
     extern crate serde_json;
+
     use super::*;
-    use ::proof_compliance::{proof_compliance};
-    use ::messages::proofs::proof_message::{ ProofMessage };
-    use messages::proofs::proof_request::{ AttrInfo, PredicateInfo, ProofRequestData };
-    use serde_json::{ from_str };
-    use ::proof_compliance::check_value;
-    use utils::types::SchemaKey;
-
-    static PROOF: &'static str = r#"{
-   "proof":{
-      "proofs":{
-         "claim::bb929325-e8e6-4637-ba26-b19807b1f618":{
-            "primary_proof":{
-               "eq_proof":{
-                  "revealed_attrs":{
-                     "name":"1139481716457488690172217916278103335"
-                  },
-                  "a_prime":"123",
-                  "e":"456",
-                  "v":"5",
-                  "m":{
-                     "age":"456",
-                     "height":"4532",
-                     "sex":"444"
-                  },
-                  "m1":"5432",
-                  "m2":"211"
-               },
-               "ge_proofs":[
-                  {
-                     "u":{
-                        "2":"6",
-                        "1":"5",
-                        "0":"7",
-                        "3":"8"
-                     },
-                     "r":{
-                        "1":"9",
-                        "3":"0",
-                        "DELTA":"8",
-                        "2":"6",
-                        "0":"9"
-                     },
-                     "mj":"2",
-                     "alpha":"3",
-                     "t":{
-                        "DELTA":"4",
-                        "1":"5",
-                        "0":"6",
-                        "2":"7",
-                        "3":"8"
-                     },
-                     "predicate":{
-                        "attr_name":"age",
-                        "p_type":"GE",
-                        "value":18
-                     }
-                  }
-               ]
+    use serde_json::Value;
+
+    static SCHEMA_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+    static CRED_DEF_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+    static ALEX_ENCODED: &str = "99262857098057710338306967609588410025648622308394250666849665532448612202874";
+
+    fn request() -> ProofRequestData {
+        serde_json::from_value(json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {
+                "attr1_referent": {"name": "name", "restrictions": [{"issuer_did": "66Fh8yBzrpJQmNyZzgoTqB"}, {"issuer_did": "NcYxiDXkpYi6ov5FcYDi1e"}]},
+                "attr2_referent": {"name": "sex", "restrictions": [{"schema_name": "gvt", "schema_version": "1.0"}]},
+                "attr3_referent": {"name": "phone"}
             },
-            "non_revoc_proof":null
-         }
-      },
-      "aggregated_proof":{
-         "c_hash":"31470331269146455873134287006934967606471534525199171477580349873046877989406",
-         "c_list":[
-            [
-               182
-            ],
-            [
-               96,
-               49
-            ],
-            [
-               1
-            ]
-         ]
-      }
-   },
-   "requested_proof":{
-      "revealed_attrs":{
-         "name_1":[
-            "claim::bb929325-e8e6-4637-ba26-b19807b1f618",
-            "Alex",
-            "1139481716457488690172217916278103335"
-         ]
-      },
-      "unrevealed_attrs":{
-
-      },
-      "self_attested_attrs":{
-
-      },
-      "predicates":{
-         "age_2":"claim::bb929325-e8e6-4637-ba26-b19807b1f618"
-      }
-   },
-   "identifiers":{
-      "claim::bb929325-e8e6-4637-ba26-b19807b1f618":{
-         "issuer_did":"NcYxiDXkpYi6ov5FcYDi1e",
-         "schema_key":{
-            "name":"gvt",
-            "version":"1.0",
-            "did":"NcYxiDXkpYi6ov5FcYDi1e"
-         },
-         "rev_reg_seq_no":null
-      }
-   }
-}"#;
-
-    static REQUEST: &'static str = r#"
-    {
-      "nonce":"123432421212",
-      "name":"Home Address",
-      "version":"0.1",
-      "requested_attrs":{
-        "name_1":{
-              "name":"name",
-              "restrictions":[
-                 {
-                    "issuer_did":"NcYxiDXkpYi6ov5FcYDi1e",
-                    "schema_key":{
-                       "name":"gvt",
-                       "version":"1.0",
-                       "did":"NcYxiDXkpYi6ov5FcYDi1e"
-                    }
-                 },
-                 {
-                    "issuer_did":"66Fh8yBzrpJQmNyZzgoTqB",
-                    "schema_key":{
-                       "name":"BYU Student Info",
-                       "version":"1.0",
-                       "did":"5XFh8yBzrpJQmNyZzgoTqB"
-                    }
-                 }
-              ]
-           }
-      },
-      "requested_predicates":{
-        "age_2": {
-            "attr_name":"age",
-            "p_type":"GE",
-            "value":22,
-            "restrictions":[
-            {
-                "issuer_did":"NcYxiDXkpYi6ov5FcYDi1e",
-                "schema_key":{
-                   "name":"gvt",
-                   "version":"1.0",
-                   "did":"NcYxiDXkpYi6ov5FcYDi1e"
-                }
-            }]
-        }
-      }
+            "requested_predicates": {
+                "predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18, "restrictions": [{"cred_def_id": CRED_DEF_ID}]}
+            }
+        })).unwrap()
     }
-    "#;
 
-#[test]
-fn test_check_value(){
-        //Test equal
-        let control = "sdf".to_string();
-        let val = "sdf".to_string();
-        assert!(check_value(Some(control), &val));
+    fn libindy_proof() -> Value {
+        json!({
+            "proof": {
+                "proofs": [{
+                    "primary_proof": {
+                        "eq_proof": {"revealed_attrs": {"name": ALEX_ENCODED}},
+                        "ge_proofs": [{"predicate": {"attr_name": "age", "p_type": "GE", "value": 18}}]
+                    },
+                    "non_revoc_proof": null
+                }],
+                "aggregated_proof": {}
+            },
+            "requested_proof": {
+                "revealed_attrs": {"attr1_referent": {"sub_proof_index": 0, "raw": "Alex", "encoded": ALEX_ENCODED}},
+                "unrevealed_attrs": {"attr2_referent": {"sub_proof_index": 0}},
+                "self_attested_attrs": {"attr3_referent": "8-800-300"},
+                "predicates": {"predicate1_referent": {"sub_proof_index": 0}}
+            },
+            "identifiers": [{"schema_id": SCHEMA_ID, "cred_def_id": CRED_DEF_ID, "rev_reg_id": null, "timestamp": null}]
+        })
+    }
 
-        //Test not equal
-        let control = "eee".to_string();
-        assert!(!check_value(Some(control), &val));
+    fn proof(libindy_proof: Value) -> ProofMessage {
+        let mut proof = ProofMessage::new();
+        proof.libindy_proof = libindy_proof.to_string();
+        proof
+    }
 
-        //Test None control
-        assert!(check_value(None, &val));
+    fn assert_not_compliant(request: &ProofRequestData, libindy_proof: Value) {
+        let err = proof_compliance(request, &proof(libindy_proof)).unwrap_err();
+        assert_eq!(err.code(), error::FAILED_PROOF_COMPLIANCE.code_num);
     }
 
     #[test]
-    fn test_compare_specs() {
-        let identifier = Identifier {
-            issuer_did: "123".to_string(),
-            schema_key: SchemaKey {
-                name: "schema_name".to_string(),
-                version: "0.1".to_string(),
-                did: "234".to_string()
-            },
-            rev_reg_seq_no: None
-        };
-
-        let filter1 = Filter {
-            issuer_did: Some("123".to_string()),
-            schema_key: Some(SchemaKeyFilter {
-                name: Some("schema_name".to_string()),
-                version: Some("0.1".to_string()),
-                did: Some("234".to_string())
-            })
-        };
-        let filter2 = Filter {
-            issuer_did: Some("456".to_string()),
-            schema_key: Some(SchemaKeyFilter {
-                name: Some("schema_name2".to_string()),
-                version: Some("0.2".to_string()),
-                did: Some("567".to_string())
-            })
-        };
-        let filter3 = Filter {
-            issuer_did: Some("123".to_string()),
-            schema_key: None
-        };
-        let mut filters: Vec<Filter> = Vec::new();
-
-        // No specs in Request
-        assert!(compare_specs(None,&identifier));
-
-        // Only issuer_did specified in request
-        assert!(compare_specs(Some(vec![filter3.clone()]), &identifier));
-
-        // Proof doesn't contain specified schema_key and issuer_did
-        filters.push(filter2);
-        assert!(!compare_specs(Some(filters.clone()), &identifier));
-
-        // Proof contains specified schema_key and issuer_did
-        filters.push(filter1);
-        assert!(compare_specs(Some(filters.clone()), &identifier));
+    fn test_compliant_proof() {
+        proof_compliance(&request(), &proof(libindy_proof())).unwrap();
     }
 
     #[test]
-    fn test_proof_with_predicates() {
-        let proof = ProofMessage::from_str(PROOF).unwrap();
-
-        let proof_req: ProofRequestData = from_str(REQUEST).unwrap();
-
-        proof_compliance(&proof_req, &proof).unwrap();
+    fn test_missing_attribute_or_predicate() {
+        let mut libindy_proof = libindy_proof();
+        libindy_proof["requested_proof"]["unrevealed_attrs"] = json!({});
+        assert_not_compliant(&request(), libindy_proof);
+
+        let mut libindy_proof = self::libindy_proof();
+        libindy_proof["requested_proof"]["predicates"] = json!({});
+        assert_not_compliant(&request(), libindy_proof);
+
+        let mut libindy_proof = self::libindy_proof();
+        libindy_proof["proof"]["proofs"][0]["primary_proof"]["ge_proofs"][0]["predicate"]["value"] = json!(17);
+        assert_not_compliant(&request(), libindy_proof);
     }
 
     #[test]
-    fn test_compliance_failed_with_missing_predicate() {
-        let pred_str = r#"{ "attr_name":"missing_pred", "p_type":"GE", "value":22, "restrictions":[ { "issuer_did":"NcYxiDXkpYi6ov5FcYDi1e", "schema_key":{ "name":"gvt", "version":"1.0", "did":"NcYxiDXkpYi6ov5FcYDi1e" } }] }"#;
-        let proof = ProofMessage::from_str(PROOF).unwrap();
-
-        let mut proof_req: ProofRequestData = from_str(REQUEST).unwrap();
-
-        let predicate: PredicateInfo = serde_json::from_str(pred_str).unwrap();
-        proof_req.requested_predicates.insert("missing_pred_3".to_string(), predicate);
-        assert_eq!(proof_compliance(&proof_req, &proof), Err(ProofError::FailedProofCompliance()));
+    fn test_restrictions_must_be_met() {
+        let mut libindy_proof = libindy_proof();
+        libindy_proof["identifiers"][0]["cred_def_id"] = json!("V4SGRU86Z58d6TV7PBUe6f:3:CL:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0");
+        assert_not_compliant(&request(), libindy_proof);
+
+        let mut libindy_proof = self::libindy_proof();
+        libindy_proof["identifiers"][0]["schema_id"] = json!("NcYxiDXkpYi6ov5FcYDi1e:2:gvt:2.0");
+        assert_not_compliant(&request(), libindy_proof);
+
+        let mut libindy_proof = self::libindy_proof();
+        libindy_proof["requested_proof"]["unrevealed_attrs"] = json!({});
+        libindy_proof["requested_proof"]["self_attested_attrs"]["attr2_referent"] = json!("male");
+        assert_not_compliant(&request(), libindy_proof);
     }
 
     #[test]
-    fn test_proof_fails_with_differing_issuer_dids_schema_key_values() {
-        let mut proof = ProofMessage::from_str(PROOF).unwrap();
-        let proof_req: ProofRequestData = from_str(REQUEST).unwrap();
-        assert!(proof_compliance(&proof_req, &proof).is_ok());
-        let original_id: Identifier = proof.identifiers.get("claim::bb929325-e8e6-4637-ba26-b19807b1f618").unwrap().clone();
-        proof.identifiers.remove("claim::bb929325-e8e6-4637-ba26-b19807b1f618");
-
-        // Different issuer_did
-        let mut change_issuer_did = original_id.clone();
-        change_issuer_did .issuer_did = "changed_did".to_string();
-        proof.identifiers.insert("claim::bb929325-e8e6-4637-ba26-b19807b1f618".to_string(), change_issuer_did );
-        assert_eq!(proof_compliance(&proof_req, &proof), Err(ProofError::FailedProofCompliance()));
-        proof.identifiers.remove("claim::bb929325-e8e6-4637-ba26-b19807b1f618");
-
-        // Different schema_did
-        let mut change_schema_did = original_id.clone();
-        change_schema_did.schema_key.did = "changed_did".to_string();
-        proof.identifiers.insert("claim::bb929325-e8e6-4637-ba26-b19807b1f618".to_string(), change_schema_did);
-        assert_eq!(proof_compliance(&proof_req, &proof), Err(ProofError::FailedProofCompliance()));
-        proof.identifiers.remove("claim::bb929325-e8e6-4637-ba26-b19807b1f618");
-
-        // Different schema_name
-        let mut change_schema_name = original_id.clone();
-        change_schema_name.schema_key.name = "changed_name".to_string();
-        proof.identifiers.insert("claim::bb929325-e8e6-4637-ba26-b19807b1f618".to_string(), change_schema_name);
-        assert_eq!(proof_compliance(&proof_req, &proof), Err(ProofError::FailedProofCompliance()));
-        proof.identifiers.remove("claim::bb929325-e8e6-4637-ba26-b19807b1f618");
+    fn test_predicate_types() {
+        let mut request = request();
+        request.requested_predicates.get_mut("predicate1_referent").unwrap().p_type = "<".to_string();
+        let mut libindy_proof = libindy_proof();
+        libindy_proof["proof"]["proofs"][0]["primary_proof"]["ge_proofs"][0]["predicate"]["p_type"] = json!("LT");
+        proof_compliance(&request, &proof(libindy_proof.clone())).unwrap();
+
+        // proved a different operator than requested
+        request.requested_predicates.get_mut("predicate1_referent").unwrap().p_type = "<=".to_string();
+        assert_not_compliant(&request, libindy_proof.clone());
+
+        request.requested_predicates.get_mut("predicate1_referent").unwrap().p_type = "=<".to_string();
+        let err = proof_compliance(&request, &proof(libindy_proof)).unwrap_err();
+        assert_eq!(err.code(), error::INVALID_PREDICATE.code_num);
     }
 
     #[test]
-    fn test_proof_with_self_attested_values(){
-        let mut proof = ProofMessage::from_str(PROOF).unwrap();
-
-        let mut proof_req: ProofRequestData = from_str(REQUEST).unwrap();
+    fn test_names_come_from_one_credential() {
+        let mut request = request();
+        request.requested_attributes.insert("identity".to_string(), serde_json::from_value(json!({
+            "names": ["name", "sex"], "restrictions": [{"schema_name": "gvt"}]
+        })).unwrap());
+
+        let mut libindy_proof = libindy_proof();
+        libindy_proof["requested_proof"]["revealed_attrs"]["identity::name"] = json!({"sub_proof_index": 0, "raw": "Alex", "encoded": ALEX_ENCODED});
+        libindy_proof["requested_proof"]["unrevealed_attrs"]["identity::sex"] = json!({"sub_proof_index": 0});
+        proof_compliance(&request, &proof(libindy_proof.clone())).unwrap();
+
+        let mut missing = libindy_proof.clone();
+        missing["requested_proof"]["unrevealed_attrs"] = json!({"attr2_referent": {"sub_proof_index": 0}});
+        assert_not_compliant(&request, missing);
+
+        let mut two_credentials = libindy_proof.clone();
+        let identifier = two_credentials["identifiers"][0].clone();
+        two_credentials["identifiers"].as_array_mut().unwrap().push(identifier);
+        two_credentials["requested_proof"]["unrevealed_attrs"]["identity::sex"] = json!({"sub_proof_index": 1});
+        assert_not_compliant(&request, two_credentials);
+    }
 
-        let self_attested: AttrInfo = AttrInfo {
-            name: "self_attested_name".to_string(),
-            restrictions: None,
-        };
-        proof_req.requested_attrs.insert("self_attested_3".to_string(), self_attested);
-        proof.requested_proof.self_attested_attrs.insert("self_attested_name".to_string(), "value".to_string());
-        proof_compliance(&proof_req, &proof).unwrap();
+    #[test]
+    fn test_tampered_revealed_value() {
+        let mut libindy_proof = libindy_proof();
+        libindy_proof["requested_proof"]["revealed_attrs"]["attr1_referent"]["raw"] = json!("Bob");
+        let err = proof_compliance(&request(), &proof(libindy_proof.clone())).unwrap_err();
+        assert_eq!(err.code(), error::INVALID_REVEALED_ATTRIBUTE_VALUE.code_num);
+
+        libindy_proof["requested_proof"]["predicates"] = json!({});
+        let err = proof_compliance(&request(), &proof(libindy_proof)).unwrap_err();
+        assert_eq!(err.code(), error::INVALID_REVEALED_ATTRIBUTE_VALUE.code_num);
     }
 
     #[test]
-    fn test_self_attested_fails_when_issuer_did_expected(){
-        let mut proof = ProofMessage::from_str(PROOF).unwrap();
-
-        let mut proof_req: ProofRequestData = from_str(REQUEST).unwrap();
-
-        let self_attested: AttrInfo = AttrInfo {
-            name: "self_attested_name".to_string(),
-            restrictions: Some(vec![
-                Filter {
-                    issuer_did: Some("Some_did".to_string()),
-                    schema_key: None,
-                }
-            ]),
-        };
-        proof_req.requested_attrs.insert("self_attested_3".to_string(), self_attested);
-        proof.requested_proof.self_attested_attrs.insert("self_attested_name".to_string(), "value".to_string());
-        assert_eq!(proof_compliance(&proof_req, &proof), Err(ProofError::FailedProofCompliance()));
+    fn test_restriction_groups_and_attribute_values() {
+        let mut request = request();
+        request.requested_attributes.get_mut("attr1_referent").unwrap().restrictions = serde_json::from_value(json!([
+            {"$and": [
+                {"schema_name": "gvt"},
+                {"$or": [{"issuer_did": "66Fh8yBzrpJQmNyZzgoTqB"}, {"cred_def_id": CRED_DEF_ID}]}
+            ]}
+        ])).unwrap();
+        proof_compliance(&request, &proof(libindy_proof())).unwrap();
+
+        request.requested_attributes.get_mut("attr1_referent").unwrap().restrictions = serde_json::from_value(json!([
+            {"$and": [{"schema_name": "gvt"}, {"issuer_did": "66Fh8yBzrpJQmNyZzgoTqB"}]}
+        ])).unwrap();
+        assert_not_compliant(&request, libindy_proof());
+
+        // the value has to be revealed from the same credential
+        request.requested_attributes.get_mut("attr1_referent").unwrap().restrictions = serde_json::from_value(json!([
+            {"cred_def_id": CRED_DEF_ID, "attr::Name::value": "Alex"}
+        ])).unwrap();
+        proof_compliance(&request, &proof(libindy_proof())).unwrap();
+
+        request.requested_attributes.get_mut("attr1_referent").unwrap().restrictions = serde_json::from_value(json!([
+            {"attr::name::value": "Bob"}
+        ])).unwrap();
+        assert_not_compliant(&request, libindy_proof());
+
+        request.requested_attributes.get_mut("attr1_referent").unwrap().restrictions = serde_json::from_value(json!([
+            {"attr::sex::value": "male"}
+        ])).unwrap();
+        assert_not_compliant(&request, libindy_proof());
     }
-}*/
+}
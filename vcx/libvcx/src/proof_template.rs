@@ -0,0 +1,186 @@
+extern crate serde_json;
+
+use serde::de::DeserializeOwned;
+use messages;
+use messages::proofs::proof_request::{ AttrInfo, PredicateInfo, NonRevokedInterval };
+use proof;
+use utils::error;
+use utils::libindy::wallet;
+use error::{ VcxError, VcxResult };
+
+// Templates keep the attributes, predicates, restrictions and revocation interval of a proof
+// request in the wallet, so a verifier defines them once and creates proofs by template name.
+
+static TEMPLATE_RECORD_TYPE: &str = "vcx_proof_template";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ProofTemplate {
+    pub name: String,
+    pub requested_attrs: Vec<AttrInfo>,
+    #[serde(default)]
+    pub requested_predicates: Vec<PredicateInfo>,
+    #[serde(default)]
+    pub revocation_interval: NonRevokedInterval,
+}
+
+fn get_record_value(name: &str) -> VcxResult<Option<String>> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let record = match wallet::get_record(TEMPLATE_RECORD_TYPE, name, &options) {
+        Ok(x) => x,
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => return Ok(None),
+        Err(x) => return Err(x),
+    };
+
+    let record: serde_json::Value = serde_json::from_str(&record)
+        .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid proof template record {}: {}", name, err)))?;
+
+    Ok(record["value"].as_str().map(|value| value.to_string()))
+}
+
+fn parse<T>(name: &str, json: &str, what: &str) -> VcxResult<T> where T: DeserializeOwned {
+    serde_json::from_str(json)
+        .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid {} of proof template {}: {}", what, name, err)))
+}
+
+/// Stores the template under its name, replacing a template stored before under the same name.
+/// The template is checked the same way proof requests are when they are sent.
+pub fn create_template(name: &str,
+                       requested_attrs: &str,
+                       requested_predicates: &str,
+                       revocation_interval: &str) -> VcxResult<()> {
+    if name.is_empty() {
+        return Err(VcxError::from(error::INVALID_OPTION.code_num));
+    }
+
+    messages::proof_request()
+        .requested_attrs(requested_attrs)
+        .requested_predicates(requested_predicates)
+        .non_revoked(revocation_interval)
+        .serialize_message()
+        .map_err(|ec| {
+            warn!("proof template {} is not a valid proof request: {}", name, ec);
+            ec
+        })?;
+
+    let template = ProofTemplate {
+        name: name.to_string(),
+        requested_attrs: parse(name, requested_attrs, "requested attributes")?,
+        requested_predicates: parse(name, requested_predicates, "requested predicates")?,
+        revocation_interval: parse(name, revocation_interval, "revocation interval")?,
+    };
+    if template.requested_attrs.is_empty() && template.requested_predicates.is_empty() {
+        return Err(VcxError::from(error::INVALID_JSON.code_num));
+    }
+
+    let value = json!(template).to_string();
+    match wallet::update_record_value(TEMPLATE_RECORD_TYPE, name, &value) {
+        Ok(_) => (),
+        Err(ref x) if x.code() == error::WALLET_RECORD_NOT_FOUND.code_num => wallet::add_record(TEMPLATE_RECORD_TYPE, name, &value, "{}")?,
+        Err(x) => return Err(x),
+    }
+
+    debug!("stored proof template {}", name);
+    Ok(())
+}
+
+pub fn get_template(name: &str) -> VcxResult<ProofTemplate> {
+    let value = get_record_value(name)?
+        .ok_or(VcxError::from(error::PROOF_TEMPLATE_NOT_FOUND.code_num))?;
+
+    Ok(parse(name, &value, "record")?)
+}
+
+/// Every stored template, ordered by name.
+pub fn list_templates() -> VcxResult<Vec<ProofTemplate>> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let records = wallet::search_records(TEMPLATE_RECORD_TYPE, "{}", &options)?;
+
+    let mut templates = records.iter()
+        .map(|record| {
+            let name = record["id"].as_str().unwrap_or_default();
+            let value = record["value"].as_str()
+                .ok_or_else(|| VcxError::from_msg(error::INVALID_JSON.code_num, format!("proof template record {} has no value", name)))?;
+            parse(name, value, "record")
+        })
+        .collect::<VcxResult<Vec<ProofTemplate>>>()?;
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(templates)
+}
+
+pub fn delete_template(name: &str) -> VcxResult<()> {
+    wallet::delete_record(TEMPLATE_RECORD_TYPE, name)
+        .map_err(|ec| match ec.code() {
+            x if x == error::WALLET_RECORD_NOT_FOUND.code_num => VcxError::from(error::PROOF_TEMPLATE_NOT_FOUND.code_num),
+            _ => ec,
+        })
+}
+
+/// Creates a proof requesting what the template asks for, with a nonce of its own.
+pub fn create_proof(source_id: String, name: &str) -> VcxResult<u32> {
+    let template = get_template(name)?;
+
+    debug!("creating proof {} from template {}", source_id, name);
+    proof::create_proof(source_id,
+                        json!(template.requested_attrs).to_string(),
+                        json!(template.requested_predicates).to_string(),
+                        json!(template.revocation_interval).to_string(),
+                        template.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::constants::{ REQUESTED_ATTRS, REQUESTED_PREDICATES };
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        init!("true");
+
+        assert_eq!(create_template("", REQUESTED_ATTRS, REQUESTED_PREDICATES, "{}").err(),
+                   Some(VcxError::from(error::INVALID_OPTION.code_num)));
+        assert_eq!(create_template("kyc", "[", REQUESTED_PREDICATES, "{}").err(),
+                   Some(VcxError::from(error::INVALID_JSON.code_num)));
+        assert_eq!(create_template("kyc", "[]", "[]", "{}").err(),
+                   Some(VcxError::from(error::INVALID_JSON.code_num)));
+        assert_eq!(create_template("kyc", REQUESTED_ATTRS, r#"[{"name":"age","p_type":"=","p_value":18}]"#, "{}").err(),
+                   Some(VcxError::from_code(error::INVALID_PREDICATE.code_num)));
+        assert_eq!(create_template("kyc", REQUESTED_ATTRS, REQUESTED_PREDICATES, r#"{"from":200,"to":100}"#).err(),
+                   Some(VcxError::from_code(error::INVALID_REVOCATION_INTERVAL.code_num)));
+    }
+
+    #[test]
+    fn test_create_proof_from_template() {
+        init!("false");
+
+        create_template("kyc", REQUESTED_ATTRS, REQUESTED_PREDICATES, r#"{"to":1540498474}"#).unwrap();
+        let template = get_template("kyc").unwrap();
+        assert_eq!(template.requested_attrs.len(), 2);
+        assert_eq!(template.revocation_interval.to, Some(1540498474));
+
+        let handle1 = create_proof("1".to_string(), "kyc").unwrap();
+        let handle2 = create_proof("2".to_string(), "kyc").unwrap();
+        let proof1: serde_json::Value = serde_json::from_str(&proof::to_string(handle1).unwrap()).unwrap();
+        let proof2: serde_json::Value = serde_json::from_str(&proof::to_string(handle2).unwrap()).unwrap();
+        assert_eq!(proof1["data"]["name"], "kyc");
+        assert_eq!(proof1["data"]["requested_attrs"], proof2["data"]["requested_attrs"]);
+        assert_ne!(proof1["data"]["nonce"], proof2["data"]["nonce"]);
+
+        // storing again replaces the template
+        create_template("kyc", r#"[{"name":"age"}]"#, "[]", "{}").unwrap();
+        assert_eq!(get_template("kyc").unwrap().requested_attrs.len(), 1);
+
+        create_template("address", REQUESTED_ATTRS, "[]", "{}").unwrap();
+        let names: Vec<String> = list_templates().unwrap().into_iter().map(|template| template.name).collect();
+        assert_eq!(names, vec!["address", "kyc"]);
+        delete_template("address").unwrap();
+
+        delete_template("kyc").unwrap();
+        assert_eq!(get_template("kyc").err(), Some(VcxError::from(error::PROOF_TEMPLATE_NOT_FOUND.code_num)));
+        assert_eq!(create_proof("3".to_string(), "kyc").err(), Some(VcxError::from(error::PROOF_TEMPLATE_NOT_FOUND.code_num)));
+        assert_eq!(delete_template("kyc").err(), Some(VcxError::from(error::PROOF_TEMPLATE_NOT_FOUND.code_num)));
+        assert_eq!(list_templates().unwrap(), Vec::new());
+
+        teardown!("false");
+    }
+}
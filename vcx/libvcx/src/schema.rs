@@ -6,20 +6,26 @@ use settings;
 use std::fmt;
 use std::string::ToString;
 use utils::error;
-use utils::constants::{ SCHEMA_ID, SCHEMA_JSON, SCHEMA_TXN_TYPE };
+use utils::constants::{ SCHEMA_ID, SCHEMA_JSON, SCHEMA_TXN_TYPE, SCHEMA_REQ, SUBMIT_SCHEMA_RESPONSE };
 use utils::libindy::{
     ledger::{
         libindy_build_get_schema_request,
+        libindy_build_get_txn_request,
         libindy_submit_request,
         libindy_build_schema_request,
         libindy_parse_get_schema_response,
+        libindy_prepare_request_for_endorser,
     },
     anoncreds::libindy_issuer_create_schema,
     payments::{pay_for_txn, PaymentTxn},
 };
-use error::schema::SchemaError;
-use utils::constants::DEFAULT_SERIALIZE_VERSION;
+use error::{ VcxError, VcxResult, VcxResultExt };
+use utils::serialization::{ self, Versioned };
+use ledger_cache::{ self, LedgerObject };
 use object_cache::ObjectCache;
+use api::PublicEntityStateType;
+
+static CREATED_SCHEMAS_LIST: &str = "created_schemas";
 
 lazy_static! {
     static ref SCHEMA_MAP: ObjectCache<CreateSchema> = Default::default();
@@ -48,8 +54,12 @@ pub struct CreateSchema {
     source_id: String,
     sequence_num: u32,
     payment_txn: Option<PaymentTxn>,
+    #[serde(default)]
+    state: PublicEntityStateType,
 }
 
+impl Versioned for CreateSchema {}
+
 impl Default for CreateSchema {
     fn default() -> CreateSchema {
         CreateSchema {
@@ -60,6 +70,7 @@ impl Default for CreateSchema {
             source_id: String::new(),
             sequence_num: 0,
             payment_txn: None,
+            state: PublicEntityStateType::Published,
         }
     }
 }
@@ -67,45 +78,47 @@ impl Default for CreateSchema {
 pub trait Schema: ToString {
     type SchemaType;
 
-    fn retrieve_schema(submitter_did: &str, schema_id: &str) -> Result<(String, String), SchemaError> {
+    fn retrieve_schema(submitter_did: &str, schema_id: &str) -> VcxResult<(String, String)> {
         if settings::test_indy_mode_enabled() { return Ok((SCHEMA_ID.to_string(), SCHEMA_JSON.to_string()))}
 
-        //Todo: Change SchemaError to InvalidSchemaId
+        if let Some(schema) = ledger_cache::get(LedgerObject::Schema, schema_id) { return Ok(schema); }
+
+        //Todo: Change INVALID_SCHEMA_SEQ_NO to InvalidSchemaId
         let get_schema_req = libindy_build_get_schema_request(submitter_did, schema_id)
-            .or(Err(SchemaError::InvalidSchemaSeqNo()))?;
+            .or(Err(VcxError::from_code(error::INVALID_SCHEMA_SEQ_NO.code_num)))?;
+
+        let get_schema_response = libindy_submit_request(&get_schema_req)?;
 
-        let get_schema_response = libindy_submit_request(&get_schema_req)
-            .map_err(|err| SchemaError::CommonError(err))?;
+        let schema = libindy_parse_get_schema_response(&get_schema_response)?;
 
-        libindy_parse_get_schema_response(&get_schema_response)
-            .map_err(|err| SchemaError::CommonError(err))
+        ledger_cache::put(LedgerObject::Schema, schema_id, &schema);
+        Ok(schema)
     }
 
     fn create_schema(submitter_did: &str,
                       name: &str,
                       version: &str,
-                      data: &str) -> Result<(String, Option<PaymentTxn>), SchemaError> {
+                      data: &str) -> VcxResult<(String, Option<PaymentTxn>)> {
         if settings::test_indy_mode_enabled() {
             return Ok((SCHEMA_ID.to_string(), Some(PaymentTxn::from_parts(r#"["pay:null:9UFgyjuJxi1i1HD"]"#,r#"[{"amount":4,"extra":null,"recipient":"pay:null:xkIsxem0YNtHrRO"}]"#,1, false).unwrap())));
         }
 
         let (id, create_schema) = libindy_issuer_create_schema(submitter_did, name, version, data)
-            .or(Err(SchemaError::InvalidSchemaCreation()))?;
+            .or(Err(VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num)))?;
 
         let request = libindy_build_schema_request(submitter_did, &create_schema)
-            .or(Err(SchemaError::InvalidSchemaCreation()))?;
+            .or(Err(VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num)))?;
 
-        let (payment, response) = pay_for_txn(&request, SCHEMA_TXN_TYPE)
-            .map_err(|err| SchemaError::CommonError(err))?;
+        let (payment, response) = pay_for_txn(&request, SCHEMA_TXN_TYPE)?;
 
         Self::check_submit_schema_response(&response)?;
 
         Ok((id, payment))
     }
 
-    fn check_submit_schema_response(txn: &str) -> Result<(), SchemaError> {
+    fn check_submit_schema_response(txn: &str) -> VcxResult<()> {
         let txn_val:  Value = serde_json::from_str(txn)
-            .or(Err(SchemaError::CommonError(error::INVALID_JSON.code_num)))?;
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
 
         match txn_val.get("result") {
             Some(_) => return Ok(()),
@@ -116,13 +129,13 @@ pub trait Schema: ToString {
             Some(m) => {
                 if m == "REJECT" {
                     match txn_val.get("reason") {
-                        Some(r) => Err(SchemaError::DuplicateSchema(r.to_string())),
-                        None => Err(SchemaError::UnknownRejection()),
+                        Some(r) => Err(VcxError::from_msg(error::DUPLICATE_SCHEMA.code_num, r.to_string())),
+                        None => Err(VcxError::from_code(error::UNKNOWN_SCHEMA_REJECTION.code_num)),
                     }
                 } else {
-                    return Err(SchemaError::CommonError(error::INVALID_JSON.code_num))
+                    return Err(VcxError::from(error::INVALID_JSON.code_num))
                 }},
-            None => return Err(SchemaError::CommonError(error::INVALID_JSON.code_num))
+            None => return Err(VcxError::from(error::INVALID_JSON.code_num))
         }
     }
 }
@@ -165,10 +178,9 @@ impl fmt::Display for CreateSchema {
 
 impl LedgerSchema {
 
-    pub fn new_from_ledger(id: &str) -> Result<LedgerSchema, SchemaError>
+    pub fn new_from_ledger(id: &str) -> VcxResult<LedgerSchema>
     {
-        let submitter_did = &settings::get_config_value(settings::CONFIG_INSTITUTION_DID)
-            .map_err(|e| SchemaError::CommonError(e))?;
+        let submitter_did = &settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
 
         let (schema_id, schema_json) = LedgerSchema::retrieve_schema(submitter_did, id)?;
 
@@ -177,6 +189,40 @@ impl LedgerSchema {
             schema_json,
         })
     }
+
+    /// Credential definitions refer to their schema by its sequence number on the ledger,
+    /// the schema id is built from the schema transaction with that number.
+    pub fn id_from_seq_no(sequence_num: u32) -> VcxResult<String> {
+        if settings::test_indy_mode_enabled() { return Ok(SCHEMA_ID.to_string()); }
+
+        let submitter_did = &settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+
+        let get_txn_req = libindy_build_get_txn_request(submitter_did, sequence_num as i32)
+            .or(Err(VcxError::from_code(error::INVALID_SCHEMA_SEQ_NO.code_num)))?;
+
+        let get_txn_response = libindy_submit_request(&get_txn_req)?;
+
+        let response: Value = serde_json::from_str(&get_txn_response)
+            .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
+
+        let txn = &response["result"]["data"]["txn"];
+        if txn["type"] != json!(SCHEMA_TXN_TYPE) {
+            warn!("transaction {} is not a schema", sequence_num);
+            return Err(VcxError::from_code(error::INVALID_SCHEMA_SEQ_NO.code_num));
+        }
+
+        match (txn["metadata"]["from"].as_str(), txn["data"]["data"]["name"].as_str(), txn["data"]["data"]["version"].as_str()) {
+            (Some(did), Some(name), Some(version)) => Ok(format!("{}:2:{}:{}", did, name, version)),
+            _ => Err(VcxError::from_code(error::INVALID_SCHEMA_SEQ_NO.code_num)),
+        }
+    }
+
+    /// The names of the attributes credentials of this schema have.
+    pub fn get_attr_names(&self) -> VcxResult<Vec<String>> {
+        let data: SchemaData = serde_json::from_str(&self.schema_json)
+            .or(Err(VcxError::from(error::INVALID_SCHEMA.code_num)))?;
+        Ok(data.attr_names)
+    }
 }
 
 impl CreateSchema {
@@ -189,23 +235,16 @@ impl CreateSchema {
 
     pub fn get_schema_id(&self) -> &String { &self.schema_id }
 
-    fn get_payment_txn(&self) -> Result<PaymentTxn, u32> {
-        Ok(self.payment_txn.clone().ok_or(error::NOT_READY.code_num)?)
+    fn get_payment_txn(&self) -> VcxResult<PaymentTxn> {
+        Ok(self.payment_txn.clone().ok_or(VcxError::from_code(error::NOT_READY.code_num))?)
     }
 
     fn to_string_with_version(&self) -> String {
-        json!({
-            "version": DEFAULT_SERIALIZE_VERSION,
-            "data": json!(self),
-        }).to_string()
+        serialization::serialize(self)
     }
 
-    fn from_str(data: &str) -> Result<CreateSchema, SchemaError> {
-        let data:Value = serde_json::from_str(&data)
-            .or(Err(SchemaError::InvalidSchemaCreation()))?;
-        let schema: CreateSchema = serde_json::from_value(data["data"].clone())
-            .or(Err(SchemaError::InvalidSchemaCreation()))?;
-        Ok(schema)
+    fn from_str(data: &str) -> VcxResult<CreateSchema> {
+        serialization::deserialize(data).context(error::INVALID_JSON.code_num, "cannot deserialize schema")
     }
 }
 
@@ -213,7 +252,7 @@ pub fn create_new_schema(source_id: &str,
                          issuer_did: String,
                          name: String,
                          version: String,
-                         data: String) -> Result<u32, SchemaError> {
+                         data: String) -> VcxResult<u32> {
     debug!("creating schema with source_id: {}, name: {}, issuer_did: {}", source_id, name, issuer_did);
     let (schema_id, payment_txn) = LedgerSchema::create_schema(&issuer_did,
                                                 &name,
@@ -231,22 +270,115 @@ pub fn create_new_schema(source_id: &str,
         //Todo: Take sequence number out. Id will be used instead
         sequence_num: 0,
         payment_txn,
+        state: PublicEntityStateType::Published,
     };
 
-    let new_handle = SCHEMA_MAP.add(new_schema).map_err(|key|SchemaError::InvalidSchemaCreation())?;
+    _record_created(&new_schema);
+
+    let new_handle = SCHEMA_MAP.add(new_schema).map_err(|key|VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num))?;
 
     Ok(new_handle)
 }
 
+// The schema is on the ledger already, not being able to list it is no reason to fail
+fn _record_created(schema: &CreateSchema) {
+    if settings::test_indy_mode_enabled() { return; }
+
+    let entry = json!({
+        "source_id": schema.source_id,
+        "schema_id": schema.schema_id,
+        "name": schema.name,
+        "version": schema.version,
+    });
+
+    if let Err(err) = ::object_store::append(CREATED_SCHEMAS_LIST, entry) {
+        warn!("could not record created schema {}: {}", schema.schema_id, err);
+    }
+}
+
+/// Builds the schema transaction for `endorser` to write it to the ledger, instead of writing it right away.
+/// Returns the schema handle and the transaction signed by issuer_did.
+pub fn prepare_schema_for_endorser(source_id: &str,
+                                   issuer_did: String,
+                                   name: String,
+                                   version: String,
+                                   data: String,
+                                   endorser: &str) -> VcxResult<(u32, String)> {
+    debug!("preparing schema for endorser {} with source_id: {}, name: {}, issuer_did: {}", endorser, source_id, name, issuer_did);
+    let (schema_id, request) = if settings::test_indy_mode_enabled() {
+        (SCHEMA_ID.to_string(), SCHEMA_REQ.to_string())
+    } else {
+        let (schema_id, schema_json) = libindy_issuer_create_schema(&issuer_did, &name, &version, &data)
+            .or(Err(VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num)))?;
+
+        let request = libindy_build_schema_request(&issuer_did, &schema_json)
+            .or(Err(VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num)))?;
+
+        (schema_id, request)
+    };
 
-pub fn get_schema_attrs(source_id: String, schema_id: String) -> Result<(u32, String), SchemaError> {
-    let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)
-        .map_err(|e| SchemaError::CommonError(e))?;
+    let request = libindy_prepare_request_for_endorser(&issuer_did, &request, endorser)?;
+
+    let new_schema = CreateSchema {
+        source_id: source_id.to_string(),
+        name,
+        data: serde_json::from_str(&data).unwrap_or_default(),
+        version,
+        schema_id,
+        sequence_num: 0,
+        payment_txn: None,
+        state: PublicEntityStateType::Built,
+    };
+
+    let new_handle = SCHEMA_MAP.add(new_schema).map_err(|key|VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num))?;
+
+    Ok((new_handle, request))
+}
+
+/// Writes the schema transaction signed by the endorser to the ledger.
+pub fn submit_endorsed(handle: u32, endorsed_request: &str) -> VcxResult<()> {
+    SCHEMA_MAP.get_mut(handle, |s| {
+        if s.state == PublicEntityStateType::Published {
+            warn!("schema {} is already on the ledger", s.schema_id);
+            return Err(VcxError::from_code(error::NOT_READY.code_num));
+        }
+
+        let response = if settings::test_indy_mode_enabled() {
+            SUBMIT_SCHEMA_RESPONSE.to_string()
+        } else {
+            libindy_submit_request(endorsed_request)?
+        };
+
+        LedgerSchema::check_submit_schema_response(&response)?;
+
+        s.state = PublicEntityStateType::Published;
+        _record_created(s);
+        Ok(())
+    })
+}
+
+pub fn get_state(handle: u32) -> VcxResult<u32> {
+    SCHEMA_MAP.get(handle, |s| {
+        Ok(s.state as u32)
+    })
+}
+
+/// The schemas created with this wallet as a json array of their source_id, schema_id, name and version.
+pub fn list_created() -> VcxResult<String> {
+    if settings::test_indy_mode_enabled() { return Ok("[]".to_string()); }
+
+    let schemas = ::object_store::list(CREATED_SCHEMAS_LIST)?;
+    Ok(json!(schemas).to_string())
+}
+
+
+pub fn get_schema_attrs(source_id: String, schema_id: String) -> VcxResult<(u32, String)> {
+    let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
 
     let (schema_id, schema_json) = LedgerSchema::retrieve_schema(&submitter_did, &schema_id)?;
 
     let schema_data: SchemaData = serde_json::from_str(&schema_json)
-        .or(Err(SchemaError::CommonError(error::INVALID_JSON.code_num)))?;
+        .or(Err(VcxError::from(error::INVALID_JSON.code_num)))?;
 
     let new_schema = CreateSchema {
         source_id,
@@ -256,9 +388,10 @@ pub fn get_schema_attrs(source_id: String, schema_id: String) -> Result<(u32, St
         version: schema_data.version,
         data: schema_data.attr_names,
         payment_txn: None,
+        state: PublicEntityStateType::Published,
     };
 
-    let new_handle = SCHEMA_MAP.add(new_schema).map_err(|key|SchemaError::InvalidSchemaCreation())?;
+    let new_handle = SCHEMA_MAP.add(new_schema).map_err(|key|VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num))?;
 
     Ok((new_handle, to_string(new_handle)?))
 }
@@ -267,53 +400,54 @@ pub fn is_valid_handle(handle: u32) -> bool {
     SCHEMA_MAP.has_handle(handle)
 }
 
-pub fn get_sequence_num(handle: u32) -> Result<u32, SchemaError> {
+/// The context the schema was created in, calls on the schema run in it.
+pub fn get_context(handle: u32) -> u32 {
+    SCHEMA_MAP.context_of(handle)
+}
+
+pub fn get_sequence_num(handle: u32) -> VcxResult<u32> {
     SCHEMA_MAP.get(handle,|s|{
         Ok(s.get_sequence_num())
-    }).map_err(|ec|SchemaError::CommonError(ec))
+    })
 }
 
-pub fn to_string(handle: u32) -> Result<String, SchemaError> {
+pub fn to_string(handle: u32) -> VcxResult<String> {
     SCHEMA_MAP.get(handle,|s|{
         Ok(s.to_string_with_version().to_owned())
-    }).map_err(|ec|SchemaError::CommonError(ec))
+    })
 }
 
-pub fn get_source_id(handle: u32) -> Result<String, u32> {
+pub fn get_source_id(handle: u32) -> VcxResult<String> {
     SCHEMA_MAP.get(handle,|s|{
         Ok(s.get_source_id().clone())
     })
 }
 
-pub fn get_schema_id(handle: u32) -> Result<String, SchemaError> {
+pub fn get_schema_id(handle: u32) -> VcxResult<String> {
     SCHEMA_MAP.get(handle,|s|{
         Ok(s.get_schema_id().clone())
-    }).map_err(|ec|SchemaError::CommonError(ec))
+    })
 }
 
-pub fn get_payment_txn(handle: u32) -> Result<PaymentTxn, SchemaError> {
+pub fn get_payment_txn(handle: u32) -> VcxResult<PaymentTxn> {
     SCHEMA_MAP.get(handle,|s|{
         s.get_payment_txn()
-    }).or(Err(SchemaError::NoPaymentInformation()))
+    }).or(Err(VcxError::from_code(error::NO_PAYMENT_INFORMATION.code_num)))
 }
 
-pub fn from_string(schema_data: &str) -> Result<u32, SchemaError> {
-    let derived_schema: CreateSchema = CreateSchema::from_str(schema_data)
-        .map_err(|_| {
-            error!("Invalid Json format for CreateSchema string");
-            SchemaError::CommonError(error::INVALID_JSON.code_num)
-        })?;
+pub fn from_string(schema_data: &str) -> VcxResult<u32> {
+    let derived_schema: CreateSchema = CreateSchema::from_str(schema_data)?;
 
     let source_id = derived_schema.source_id.clone();
-    let new_handle = SCHEMA_MAP.add(derived_schema).map_err(|ec|SchemaError::CommonError(ec))?;
+    let new_handle = SCHEMA_MAP.add(derived_schema)?;
 
     Ok(new_handle)
 }
 
-pub fn release(handle: u32) -> Result<(), SchemaError> {
+pub fn release(handle: u32) -> VcxResult<()> {
     match SCHEMA_MAP.release(handle) {
         Ok(_) => Ok(()),
-        Err(_) => Err(SchemaError::InvalidHandle()),
+        Err(_) => Err(VcxError::from_code(error::INVALID_SCHEMA_HANDLE.code_num)),
     }
 }
 
@@ -350,8 +484,9 @@ pub mod tests {
             name: "schema_name".to_string(),
             sequence_num: 306,
             payment_txn: None,
+            state: PublicEntityStateType::Published,
         };
-        let create_schema_str = r#"{"data":["name","age","sex","height"],"version":"1.0","schema_id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4","name":"schema_name","source_id":"testId","sequence_num":306,"payment_txn":null}"#;
+        let create_schema_str = r#"{"data":["name","age","sex","height"],"version":"1.0","schema_id":"2hoqvcwupRTUNkXn6ArYzs:2:test-licence:4.4.4","name":"schema_name","source_id":"testId","sequence_num":306,"payment_txn":null,"state":1}"#;
         assert_eq!(create_schema.to_string(), create_schema_str.to_string());
         let value: serde_json::Value = serde_json::from_str(&create_schema.to_string_with_version()).unwrap();
         assert_eq!(value["version"], "1.0");
@@ -377,6 +512,26 @@ pub mod tests {
                                   data.to_string()).is_ok());
     }
 
+    #[test]
+    fn test_prepare_schema_for_endorser(){
+        init!("true");
+        let endorser = "V4SGRU86Z58d6TV7PBUe6f";
+        let (handle, request) = prepare_schema_for_endorser("1",
+                                                            "VsKV7grR1BUE29mG2Fm2kX".to_string(),
+                                                            "name".to_string(),
+                                                            "1.0".to_string(),
+                                                            r#"["name","male"]"#.to_string(),
+                                                            endorser).unwrap();
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["endorser"], json!(endorser));
+        assert_eq!(get_state(handle).unwrap(), PublicEntityStateType::Built as u32);
+
+        submit_endorsed(handle, &request.to_string()).unwrap();
+        assert_eq!(get_state(handle).unwrap(), PublicEntityStateType::Published as u32);
+        assert_eq!(submit_endorsed(handle, &request.to_string()).err(),
+                   Some(VcxError::from(error::NOT_READY.code_num)));
+    }
+
     #[test]
     fn test_get_schema_attrs_success(){
         init!("true");
@@ -394,7 +549,7 @@ pub mod tests {
                                        "1.0".to_string(),
                                        "".to_string());
 
-        assert_eq!(schema.err(),Some(SchemaError::InvalidSchemaCreation()));
+        assert_eq!(schema.err(),Some(VcxError::from_code(error::INVALID_SCHEMA_CREATION.code_num)));
     }
 
     #[cfg(feature = "pool_tests")]
@@ -498,20 +653,20 @@ pub mod tests {
         let h4 = create_new_schema("1", did.to_string(), "name".to_string(), version.to_string(),data.to_string()).unwrap();
         let h5 = create_new_schema("1", did.to_string(), "name".to_string(), version.to_string(),data.to_string()).unwrap();
         release_all();
-        assert_eq!(release(h1).err(),Some(SchemaError::InvalidHandle()));
-        assert_eq!(release(h2).err(),Some(SchemaError::InvalidHandle()));
-        assert_eq!(release(h3).err(),Some(SchemaError::InvalidHandle()));
-        assert_eq!(release(h4).err(),Some(SchemaError::InvalidHandle()));
-        assert_eq!(release(h5).err(),Some(SchemaError::InvalidHandle()));
+        assert_eq!(release(h1).err(),Some(VcxError::from_code(error::INVALID_SCHEMA_HANDLE.code_num)));
+        assert_eq!(release(h2).err(),Some(VcxError::from_code(error::INVALID_SCHEMA_HANDLE.code_num)));
+        assert_eq!(release(h3).err(),Some(VcxError::from_code(error::INVALID_SCHEMA_HANDLE.code_num)));
+        assert_eq!(release(h4).err(),Some(VcxError::from_code(error::INVALID_SCHEMA_HANDLE.code_num)));
+        assert_eq!(release(h5).err(),Some(VcxError::from_code(error::INVALID_SCHEMA_HANDLE.code_num)));
     }
 
     #[test]
     fn test_errors(){
         init!("false");
-        assert_eq!(get_sequence_num(145661).err(), Some(SchemaError::CommonError(error::INVALID_OBJ_HANDLE.code_num)));
-        assert_eq!(to_string(13435178).err(), Some(SchemaError::CommonError(error::INVALID_OBJ_HANDLE.code_num)));
-        let test: Result<LedgerSchema, SchemaError> = LedgerSchema::new_from_ledger(SCHEMA_ID);
-        assert_eq!(from_string("{}").err(), Some(SchemaError::CommonError(INVALID_JSON.code_num)));
+        assert_eq!(get_sequence_num(145661).err(), Some(VcxError::from(error::INVALID_OBJ_HANDLE.code_num)));
+        assert_eq!(to_string(13435178).err(), Some(VcxError::from(error::INVALID_OBJ_HANDLE.code_num)));
+        let test: VcxResult<LedgerSchema> = LedgerSchema::new_from_ledger(SCHEMA_ID);
+        assert_eq!(from_string("{}").err(), Some(VcxError::from(INVALID_JSON.code_num)));
     }
 
     #[test]
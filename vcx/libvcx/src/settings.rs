@@ -4,10 +4,13 @@ extern crate serde_json;
 use std::collections::HashMap;
 use std::sync::RwLock;
 use utils::error;
+use context;
+use error::{ VcxError, VcxResult, VcxResultExt };
 use std::path::Path;
 use url::Url;
 use messages::validation;
 use std::fs;
+use std::fmt;
 use std::io::prelude::*;
 use serde_json::Value;
 
@@ -35,6 +38,15 @@ pub static CONFIG_WALLET_TYPE: &'static str = "wallet_type";
 pub static CONFIG_WALLET_HANDLE: &'static str = "wallet_handle";
 pub static CONFIG_THREADPOOL_SIZE: &'static str = "threadpool_size";
 pub static CONFIG_WALLET_KEY_DERIVATION: &'static str = "wallet_key_derivation";
+pub static CONFIG_AGENCY_CONNECT_TIMEOUT_MS: &'static str = "agency_connect_timeout_ms";
+pub static CONFIG_AGENCY_READ_TIMEOUT_MS: &'static str = "agency_read_timeout_ms";
+pub static CONFIG_AGENCY_RETRIES: &'static str = "agency_retries";
+pub static CONFIG_AGENCY_RETRY_BACKOFF_MS: &'static str = "agency_retry_backoff_ms";
+pub static CONFIG_PERSIST_OBJECTS: &'static str = "persist_objects";
+pub static CONFIG_LEDGER_CACHE_TTL_SECS: &'static str = "ledger_cache_ttl_secs";
+pub static CONFIG_LEDGER_CACHE_PERSIST: &'static str = "ledger_cache_persist";
+pub static CONFIG_ATTRIBUTE_NAMES: &'static str = "attribute_names";
+pub static CONFIG_TAILS_DIR: &'static str = "tails_dir";
 
 pub static UNINITIALIZED_WALLET_KEY: &str = "<KEY_IS_NOT_SET>";
 pub static DEFAULT_GENESIS_PATH: &str = "/tmp/genesis.txn";
@@ -52,11 +64,32 @@ pub static DEFAULT_WALLET_KEY: &str = "foobar1234";
 pub static DEFAULT_THREADPOOL_SIZE: usize = 8;
 pub static MASK_VALUE: &str = "********";
 pub static DEFAULT_WALLET_KEY_DERIVATION: &str = "ARGON2I_INT";
+pub static DEFAULT_AGENCY_CONNECT_TIMEOUT_MS: u64 = 5000;
+pub static DEFAULT_AGENCY_READ_TIMEOUT_MS: u64 = 30000;
+pub static DEFAULT_AGENCY_RETRIES: u32 = 3;
+pub static DEFAULT_AGENCY_RETRY_BACKOFF_MS: u64 = 500;
+pub static DEFAULT_LEDGER_CACHE_TTL_SECS: u64 = 0;
 
 pub static MAX_THREADPOOL_SIZE: usize = 128;
 
 lazy_static! {
-    static ref SETTINGS: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+    // Settings of every context, calls read the ones of the context they target
+    static ref SETTINGS: RwLock<HashMap<u32, HashMap<String, String>>> = RwLock::new(HashMap::new());
+}
+
+fn with_settings<F, R>(closure: F) -> R
+    where F: FnOnce(&HashMap<String, String>) -> R {
+    let settings = SETTINGS.read().unwrap();
+    match settings.get(&context::current()) {
+        Some(context_settings) => closure(context_settings),
+        None => closure(&HashMap::new()),
+    }
+}
+
+fn with_settings_mut<F, R>(closure: F) -> R
+    where F: FnOnce(&mut HashMap<String, String>) -> R {
+    let mut settings = SETTINGS.write().unwrap();
+    closure(settings.entry(context::current()).or_insert_with(HashMap::new))
 }
 
 trait ToString {
@@ -73,86 +106,98 @@ impl ToString for HashMap<String, String> {
 pub fn set_defaults() -> u32 {
 
     // if this fails the program should exit
-    let mut settings = SETTINGS.write().unwrap();
-
-    settings.insert(CONFIG_POOL_NAME.to_string(),DEFAULT_POOL_NAME.to_string());
-    settings.insert(CONFIG_WALLET_NAME.to_string(),DEFAULT_WALLET_NAME.to_string());
-    settings.insert(CONFIG_WALLET_TYPE.to_string(),DEFAULT_DEFAULT.to_string());
-    settings.insert(CONFIG_AGENCY_ENDPOINT.to_string(),DEFAULT_URL.to_string());
-    settings.insert(CONFIG_AGENCY_DID.to_string(),DEFAULT_DID.to_string());
-    settings.insert(CONFIG_AGENCY_VERKEY.to_string(),DEFAULT_VERKEY.to_string());
-    settings.insert(CONFIG_REMOTE_TO_SDK_DID.to_string(),DEFAULT_DID.to_string());
-    settings.insert(CONFIG_REMOTE_TO_SDK_VERKEY.to_string(),DEFAULT_VERKEY.to_string());
-    settings.insert(CONFIG_INSTITUTION_DID.to_string(),DEFAULT_DID.to_string());
-    settings.insert(CONFIG_INSTITUTION_NAME.to_string(),DEFAULT_DEFAULT.to_string());
-    settings.insert(CONFIG_INSTITUTION_LOGO_URL.to_string(),DEFAULT_URL.to_string());
-    settings.insert(CONFIG_SDK_TO_REMOTE_DID.to_string(),DEFAULT_DID.to_string());
-    settings.insert(CONFIG_SDK_TO_REMOTE_VERKEY.to_string(),DEFAULT_VERKEY.to_string());
-    settings.insert(CONFIG_WALLET_KEY.to_string(),DEFAULT_WALLET_KEY.to_string());
-    settings.insert(CONFIG_LINK_SECRET_ALIAS.to_string(), DEFAULT_LINK_SECRET_ALIAS.to_string());
-    settings.insert(CONFIG_EXPORTED_WALLET_PATH.to_string(), DEFAULT_EXPORTED_WALLET_PATH.to_string());
-    settings.insert(CONFIG_WALLET_BACKUP_KEY.to_string(), DEFAULT_WALLET_BACKUP_KEY.to_string());
-    settings.insert(CONFIG_THREADPOOL_SIZE.to_string(), DEFAULT_THREADPOOL_SIZE.to_string());
+    with_settings_mut(|settings| {
+        settings.insert(CONFIG_POOL_NAME.to_string(),DEFAULT_POOL_NAME.to_string());
+        settings.insert(CONFIG_WALLET_NAME.to_string(),DEFAULT_WALLET_NAME.to_string());
+        settings.insert(CONFIG_WALLET_TYPE.to_string(),DEFAULT_DEFAULT.to_string());
+        settings.insert(CONFIG_AGENCY_ENDPOINT.to_string(),DEFAULT_URL.to_string());
+        settings.insert(CONFIG_AGENCY_DID.to_string(),DEFAULT_DID.to_string());
+        settings.insert(CONFIG_AGENCY_VERKEY.to_string(),DEFAULT_VERKEY.to_string());
+        settings.insert(CONFIG_REMOTE_TO_SDK_DID.to_string(),DEFAULT_DID.to_string());
+        settings.insert(CONFIG_REMOTE_TO_SDK_VERKEY.to_string(),DEFAULT_VERKEY.to_string());
+        settings.insert(CONFIG_INSTITUTION_DID.to_string(),DEFAULT_DID.to_string());
+        settings.insert(CONFIG_INSTITUTION_NAME.to_string(),DEFAULT_DEFAULT.to_string());
+        settings.insert(CONFIG_INSTITUTION_LOGO_URL.to_string(),DEFAULT_URL.to_string());
+        settings.insert(CONFIG_SDK_TO_REMOTE_DID.to_string(),DEFAULT_DID.to_string());
+        settings.insert(CONFIG_SDK_TO_REMOTE_VERKEY.to_string(),DEFAULT_VERKEY.to_string());
+        settings.insert(CONFIG_WALLET_KEY.to_string(),DEFAULT_WALLET_KEY.to_string());
+        settings.insert(CONFIG_LINK_SECRET_ALIAS.to_string(), DEFAULT_LINK_SECRET_ALIAS.to_string());
+        settings.insert(CONFIG_EXPORTED_WALLET_PATH.to_string(), DEFAULT_EXPORTED_WALLET_PATH.to_string());
+        settings.insert(CONFIG_WALLET_BACKUP_KEY.to_string(), DEFAULT_WALLET_BACKUP_KEY.to_string());
+        settings.insert(CONFIG_THREADPOOL_SIZE.to_string(), DEFAULT_THREADPOOL_SIZE.to_string());
+    });
 
     error::SUCCESS.code_num
 }
 
-pub fn validate_config(config: &HashMap<String, String>) -> Result<u32, u32> {
+pub fn validate_config(config: &HashMap<String, String>) -> VcxResult<u32> {
 
     //Mandatory parameters
     if config.get(CONFIG_WALLET_KEY).is_none() {
-        return Err(error::MISSING_WALLET_KEY.code_num);
+        return Err(VcxError::from_msg(error::MISSING_WALLET_KEY.code_num, "wallet_key is missing from the configuration"));
     }
 
     // If values are provided, validate they're in the correct format
-    validate_optional_config_val(config.get(CONFIG_INSTITUTION_DID), error::INVALID_DID.code_num, validation::validate_did)?;
-    validate_optional_config_val(config.get(CONFIG_INSTITUTION_VERKEY), error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
+    validate_optional_config_val(config, CONFIG_INSTITUTION_DID, error::INVALID_DID.code_num, validation::validate_did)?;
+    validate_optional_config_val(config, CONFIG_INSTITUTION_VERKEY, error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
+
+    validate_optional_config_val(config, CONFIG_AGENCY_DID, error::INVALID_DID.code_num, validation::validate_did)?;
+    validate_optional_config_val(config, CONFIG_AGENCY_VERKEY, error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
 
-    validate_optional_config_val(config.get(CONFIG_AGENCY_DID), error::INVALID_DID.code_num, validation::validate_did)?;
-    validate_optional_config_val(config.get(CONFIG_AGENCY_VERKEY), error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
+    validate_optional_config_val(config, CONFIG_SDK_TO_REMOTE_DID, error::INVALID_DID.code_num, validation::validate_did)?;
+    validate_optional_config_val(config, CONFIG_SDK_TO_REMOTE_VERKEY, error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
 
-    validate_optional_config_val(config.get(CONFIG_SDK_TO_REMOTE_DID), error::INVALID_DID.code_num, validation::validate_did)?;
-    validate_optional_config_val(config.get(CONFIG_SDK_TO_REMOTE_VERKEY), error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
+    validate_optional_config_val(config, CONFIG_REMOTE_TO_SDK_DID, error::INVALID_DID.code_num, validation::validate_did)?;
+    validate_optional_config_val(config, CONFIG_REMOTE_TO_SDK_VERKEY, error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
 
-    validate_optional_config_val(config.get(CONFIG_REMOTE_TO_SDK_DID), error::INVALID_DID.code_num, validation::validate_did)?;
-    validate_optional_config_val(config.get(CONFIG_REMOTE_TO_SDK_VERKEY), error::INVALID_VERKEY.code_num, validation::validate_verkey)?;
+    validate_optional_config_val(config, CONFIG_AGENCY_ENDPOINT, error::INVALID_URL.code_num, Url::parse)?;
+    validate_optional_config_val(config, CONFIG_INSTITUTION_LOGO_URL, error::INVALID_URL.code_num, Url::parse)?;
 
-    validate_optional_config_val(config.get(CONFIG_AGENCY_ENDPOINT), error::INVALID_URL.code_num, Url::parse)?;
-    validate_optional_config_val(config.get(CONFIG_INSTITUTION_LOGO_URL), error::INVALID_URL.code_num, Url::parse)?;
+    validate_optional_config_val(config, CONFIG_AGENCY_CONNECT_TIMEOUT_MS, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<u64>())?;
+    validate_optional_config_val(config, CONFIG_AGENCY_READ_TIMEOUT_MS, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<u64>())?;
+    validate_optional_config_val(config, CONFIG_AGENCY_RETRIES, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<u32>())?;
+    validate_optional_config_val(config, CONFIG_AGENCY_RETRY_BACKOFF_MS, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<u64>())?;
+    validate_optional_config_val(config, CONFIG_PERSIST_OBJECTS, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<bool>())?;
+    validate_optional_config_val(config, CONFIG_LEDGER_CACHE_TTL_SECS, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<u64>())?;
+    validate_optional_config_val(config, CONFIG_LEDGER_CACHE_PERSIST, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<bool>())?;
+    validate_optional_config_val(config, CONFIG_ATTRIBUTE_NAMES, error::INVALID_CONFIGURATION.code_num, |x| x.parse::<bool>())?;
 
 
     Ok(error::SUCCESS.code_num)
 }
 
-fn validate_wallet_key(key: &str) -> Result<u32, u32> {
-    if key == UNINITIALIZED_WALLET_KEY { return Err(error::MISSING_WALLET_KEY.code_num); }
+fn validate_wallet_key(key: &str) -> VcxResult<u32> {
+    if key == UNINITIALIZED_WALLET_KEY {
+        return Err(VcxError::from_msg(error::MISSING_WALLET_KEY.code_num, "wallet_key has not been set"));
+    }
     Ok(error::SUCCESS.code_num)
 }
 
-fn validate_optional_config_val<F, S, E>(val: Option<&String>, err: u32, closure: F) -> Result<u32, u32>
-    where F: Fn(&str) -> Result<S, E> {
+fn validate_optional_config_val<F, S, E>(config: &HashMap<String, String>, key: &str, err: u32, closure: F) -> VcxResult<u32>
+    where F: Fn(&str) -> Result<S, E>, E: fmt::Debug {
 
-    if val.is_none() {return Ok(error::SUCCESS.code_num)}
+    let val = match config.get(key) {
+        Some(val) => val,
+        None => return Ok(error::SUCCESS.code_num),
+    };
 
-    closure(val.as_ref().ok_or(error::INVALID_CONFIGURATION.code_num)?)
-        .or(Err(err))?;
+    closure(val).map_err(|e| VcxError::from_msg(err, format!("invalid value for {}: {:?}", key, e)))?;
 
     Ok(error::SUCCESS.code_num)
 
 }
 
 pub fn log_settings() {
-    let settings = SETTINGS.read().unwrap();
-    trace!("loaded settings: {:?}", settings.to_string());
+    with_settings(|settings| trace!("loaded settings: {:?}", settings.to_string()));
 }
 
 pub fn test_indy_mode_enabled() -> bool {
-    let config = SETTINGS.read().unwrap();
-
-    match config.get(CONFIG_ENABLE_TEST_MODE) {
-        None => false,
-        Some(value) => if value == "true" { true } else { if value == "indy" { true } else {false }},
-    }
+    with_settings(|config| {
+        match config.get(CONFIG_ENABLE_TEST_MODE) {
+            None => false,
+            Some(value) => if value == "true" { true } else { if value == "indy" { true } else {false }},
+        }
+    })
 }
 
 pub fn get_threadpool_size() -> usize {
@@ -168,47 +213,88 @@ pub fn get_threadpool_size() -> usize {
     }
 }
 
-pub fn test_agency_mode_enabled() -> bool {
-    let config = SETTINGS.read().unwrap();
+pub fn get_agency_connect_timeout_ms() -> u64 {
+    match get_config_value(CONFIG_AGENCY_CONNECT_TIMEOUT_MS) {
+        Ok(x) => x.parse::<u64>().unwrap_or(DEFAULT_AGENCY_CONNECT_TIMEOUT_MS),
+        Err(x) => DEFAULT_AGENCY_CONNECT_TIMEOUT_MS,
+    }
+}
+
+pub fn get_agency_read_timeout_ms() -> u64 {
+    match get_config_value(CONFIG_AGENCY_READ_TIMEOUT_MS) {
+        Ok(x) => x.parse::<u64>().unwrap_or(DEFAULT_AGENCY_READ_TIMEOUT_MS),
+        Err(x) => DEFAULT_AGENCY_READ_TIMEOUT_MS,
+    }
+}
+
+pub fn get_agency_retries() -> u32 {
+    match get_config_value(CONFIG_AGENCY_RETRIES) {
+        Ok(x) => x.parse::<u32>().unwrap_or(DEFAULT_AGENCY_RETRIES),
+        Err(x) => DEFAULT_AGENCY_RETRIES,
+    }
+}
 
-    match config.get(CONFIG_ENABLE_TEST_MODE) {
-        None => false,
-        Some(value) => if value == "true" { true } else { if value == "agency" { true } else {false }},
+pub fn get_agency_retry_backoff_ms() -> u64 {
+    match get_config_value(CONFIG_AGENCY_RETRY_BACKOFF_MS) {
+        Ok(x) => x.parse::<u64>().unwrap_or(DEFAULT_AGENCY_RETRY_BACKOFF_MS),
+        Err(x) => DEFAULT_AGENCY_RETRY_BACKOFF_MS,
     }
 }
 
-pub fn process_config_string(config: &str) -> Result<u32, u32> {
-    let configuration: Value = serde_json::from_str(config).or(Err(error::INVALID_JSON.code_num))?;
+pub fn get_ledger_cache_ttl_secs() -> u64 {
+    match get_config_value(CONFIG_LEDGER_CACHE_TTL_SECS) {
+        Ok(x) => x.parse::<u64>().unwrap_or(DEFAULT_LEDGER_CACHE_TTL_SECS),
+        Err(x) => DEFAULT_LEDGER_CACHE_TTL_SECS,
+    }
+}
+
+pub fn test_agency_mode_enabled() -> bool {
+    with_settings(|config| {
+        match config.get(CONFIG_ENABLE_TEST_MODE) {
+            None => false,
+            Some(value) => if value == "true" { true } else { if value == "agency" { true } else {false }},
+        }
+    })
+}
+
+pub fn process_config_string(config: &str) -> VcxResult<u32> {
+    let configuration: Value = serde_json::from_str(config)
+        .map_err(|e| VcxError::from_msg(error::INVALID_JSON.code_num, format!("configuration is not valid json: {}", e)))?;
     if let Value::Object(ref map) = configuration {
         for (key, value) in map {
-            set_config_value(key, value.as_str().ok_or(error::INVALID_JSON.code_num)?);
+            let value = value.as_str()
+                .ok_or(VcxError::from_msg(error::INVALID_JSON.code_num, format!("configuration value for {} is not a string", key)))?;
+            set_config_value(key, value);
         }
     }
 
-    validate_config(
-        &SETTINGS.read().or(Err(error::INVALID_CONFIGURATION.code_num))?.clone()
-    )
+    validate_config(&with_settings(|settings| settings.clone()))
 }
 
-pub fn process_config_file(path: &str) -> Result<u32, u32> {
+pub fn process_config_file(path: &str) -> VcxResult<u32> {
     if !Path::new(path).is_file() {
         error!("Configuration path was invalid");
-        Err(error::INVALID_CONFIGURATION.code_num)
+        Err(VcxError::from_msg(error::INVALID_CONFIGURATION.code_num, format!("configuration path {} is not a file", path)))
     } else {
-        process_config_string(&read_config_file(path)?)
+        // every failure is reported as INVALID_CONFIGURATION, like vcx_init always did
+        let config = read_config_file(path)
+            .context(error::INVALID_CONFIGURATION.code_num, format!("could not read configuration {}", path))?;
+        process_config_string(&config)
+            .context(error::INVALID_CONFIGURATION.code_num, format!("invalid configuration in {}", path))
     }
 }
 
-pub fn get_config_value(key: &str) -> Result<String, u32> {
-    SETTINGS
-        .read()
-        .or(Err(error::INVALID_CONFIGURATION.code_num))?
-        .get(key)
-        .map_or(Err(error::INVALID_CONFIGURATION.code_num), |v| Ok(v.to_string()))
+pub fn get_config_value(key: &str) -> VcxResult<String> {
+    with_settings(|settings| {
+        settings
+            .get(key)
+            .map_or(Err(VcxError::from_msg(error::INVALID_CONFIGURATION.code_num, format!("missing configuration value: {}", key))),
+                    |v| Ok(v.to_string()))
+    })
 }
 
 pub fn set_config_value(key: &str, value: &str) {
-    SETTINGS.write().unwrap().insert(key.to_string(), value.to_string());
+    with_settings_mut(|settings| settings.insert(key.to_string(), value.to_string()));
 }
 
 pub fn get_wallet_credentials() -> String {
@@ -221,19 +307,22 @@ pub fn get_wallet_credentials() -> String {
     credentials.to_string()
 }
 
-pub fn write_config_to_file(config: &str, path_string: &str) -> Result<(), u32> {
+pub fn write_config_to_file(config: &str, path_string: &str) -> VcxResult<()> {
     let mut file = fs::File::create(Path::new(path_string))
-        .or(Err(error::UNKNOWN_ERROR.code_num))?;
+        .map_err(|e| VcxError::from_msg(error::UNKNOWN_ERROR.code_num, format!("could not create {}: {}", path_string, e)))?;
 
-    file.write_all(config.as_bytes()).or(Err(error::UNKNOWN_ERROR.code_num))?;
+    file.write_all(config.as_bytes())
+        .map_err(|e| VcxError::from_msg(error::UNKNOWN_ERROR.code_num, format!("could not write {}: {}", path_string, e)))?;
 
     Ok(())
 }
 
-pub fn read_config_file(path: &str) -> Result<String, u32> {
-    let mut file = fs::File::open(path).or(Err(error::UNKNOWN_ERROR.code_num))?;
+pub fn read_config_file(path: &str) -> VcxResult<String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| VcxError::from_msg(error::UNKNOWN_ERROR.code_num, format!("could not open {}: {}", path, e)))?;
     let mut config = String::new();
-    file.read_to_string(&mut config).or(Err(error::UNKNOWN_ERROR.code_num))?;
+    file.read_to_string(&mut config)
+        .map_err(|e| VcxError::from_msg(error::UNKNOWN_ERROR.code_num, format!("could not read {}: {}", path, e)))?;
     Ok(config)
 }
 
@@ -249,7 +338,7 @@ pub fn remove_file_if_exists(filename: &str){
 
 pub fn clear_config() {
     let mut config = SETTINGS.write().unwrap();
-    config.clear();
+    config.remove(&context::current());
 }
 
 #[cfg(test)]
@@ -259,7 +348,7 @@ pub mod tests {
     #[test]
     fn test_bad_path() {
         let path = "garbage.txt";
-        assert_eq!(process_config_file(&path), Err(error::INVALID_CONFIGURATION.code_num));
+        assert_eq!(process_config_file(&path), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
     }
 
     #[test]
@@ -309,6 +398,19 @@ pub mod tests {
         assert_eq!(get_config_value("institution_name").unwrap(), "evernym enterprise".to_string());
     }
 
+    #[test]
+    fn test_process_invalid_config_file() {
+        let config_path = "/tmp/test_invalid_init.json";
+        write_config_to_file("{ not json", config_path).unwrap();
+
+        let err = process_config_file(config_path).unwrap_err();
+        assert_eq!(err.code(), error::INVALID_CONFIGURATION.code_num);
+        assert_eq!(err.cause().unwrap().code(), error::INVALID_JSON.code_num);
+
+        assert_eq!(process_config_file("/tmp/does_not_exist/config.json").unwrap_err().code(), error::INVALID_CONFIGURATION.code_num);
+        remove_file_if_exists(config_path);
+    }
+
     #[test]
     fn test_process_config_str() {
         let content = json!({
@@ -359,54 +461,83 @@ pub mod tests {
         let valid_ver = DEFAULT_VERKEY;
 
         let mut config: HashMap<String, String> = HashMap::new();
-        assert_eq!(validate_config(&config), Err(error::MISSING_WALLET_KEY.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::MISSING_WALLET_KEY.code_num)));
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_INSTITUTION_DID.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_DID.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_DID.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_INSTITUTION_VERKEY.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_VERKEY.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_VERKEY.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_AGENCY_DID.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_DID.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_DID.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_AGENCY_VERKEY.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_VERKEY.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_VERKEY.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_SDK_TO_REMOTE_DID.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_DID.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_DID.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_SDK_TO_REMOTE_VERKEY.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_VERKEY.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_VERKEY.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_REMOTE_TO_SDK_DID.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_DID.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_DID.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_SDK_TO_REMOTE_VERKEY.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_VERKEY.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_VERKEY.code_num)));
         config.drain();
 
         config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
         config.insert(CONFIG_INSTITUTION_LOGO_URL.to_string(), invalid.to_string());
-        assert_eq!(validate_config(&config), Err(error::INVALID_URL.code_num));
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_URL.code_num)));
+        config.drain();
+
+        config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
+        config.insert(CONFIG_AGENCY_READ_TIMEOUT_MS.to_string(), invalid.to_string());
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        config.drain();
+
+        config.insert(CONFIG_WALLET_KEY.to_string(), "password".to_string());
+        config.insert(CONFIG_AGENCY_RETRIES.to_string(), "-1".to_string());
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        config.remove(CONFIG_AGENCY_RETRIES);
+
+        config.insert(CONFIG_LEDGER_CACHE_TTL_SECS.to_string(), "1h".to_string());
+        assert_eq!(validate_config(&config), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
         config.drain();
     }
 
+    #[test]
+    fn test_agency_post_policy() {
+        clear_config();
+        assert_eq!(get_agency_connect_timeout_ms(), DEFAULT_AGENCY_CONNECT_TIMEOUT_MS);
+        assert_eq!(get_agency_read_timeout_ms(), DEFAULT_AGENCY_READ_TIMEOUT_MS);
+        assert_eq!(get_agency_retries(), DEFAULT_AGENCY_RETRIES);
+        assert_eq!(get_agency_retry_backoff_ms(), DEFAULT_AGENCY_RETRY_BACKOFF_MS);
+
+        set_config_value(CONFIG_AGENCY_RETRIES, "0");
+        set_config_value(CONFIG_AGENCY_READ_TIMEOUT_MS, "invalid");
+        assert_eq!(get_agency_retries(), 0);
+        assert_eq!(get_agency_read_timeout_ms(), DEFAULT_AGENCY_READ_TIMEOUT_MS);
+        clear_config();
+    }
+
     #[test]
     fn test_validate_optional_config_val() {
         let closure = Url::parse;
@@ -415,17 +546,17 @@ pub mod tests {
         config.insert("invalid".to_string(), "invalid_url".to_string());
 
         //Success
-        assert_eq!(validate_optional_config_val(config.get("valid"), error::INVALID_URL.code_num, closure),
+        assert_eq!(validate_optional_config_val(&config, "valid", error::INVALID_URL.code_num, closure),
                    Ok(error::SUCCESS.code_num));
 
         // Success with No config
-        assert_eq!(validate_optional_config_val(config.get("unknown"), error::INVALID_URL.code_num, closure),
+        assert_eq!(validate_optional_config_val(&config, "unknown", error::INVALID_URL.code_num, closure),
                    Ok(error::SUCCESS.code_num));
 
         // Fail with failed fn call
-        assert_eq!(validate_optional_config_val(config.get("invalid"),
+        assert_eq!(validate_optional_config_val(&config, "invalid",
                                                 error::INVALID_URL.code_num,
-                                                closure), Err(error::INVALID_URL.code_num));
+                                                closure), Err(VcxError::from(error::INVALID_URL.code_num)));
     }
 
     #[test]
@@ -434,7 +565,7 @@ pub mod tests {
         let value1 = "value1".to_string();
 
         // Fails with invalid key
-        assert_eq!(get_config_value(&key), Err(error::INVALID_CONFIGURATION.code_num));
+        assert_eq!(get_config_value(&key), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
 
         set_config_value(&key, &value1);
         assert_eq!(get_config_value(&key).unwrap(), value1)
@@ -463,12 +594,12 @@ pub mod tests {
         clear_config();
 
         // Fails after  config is cleared
-        assert_eq!(get_config_value("pool_name"), Err(error::INVALID_CONFIGURATION.code_num));
-        assert_eq!(get_config_value("config_name"), Err(error::INVALID_CONFIGURATION.code_num));
-        assert_eq!(get_config_value("wallet_name"), Err(error::INVALID_CONFIGURATION.code_num));
-        assert_eq!(get_config_value("institution_name"), Err(error::INVALID_CONFIGURATION.code_num));
-        assert_eq!(get_config_value("genesis_path"), Err(error::INVALID_CONFIGURATION.code_num));
-        assert_eq!(get_config_value("wallet_key"), Err(error::INVALID_CONFIGURATION.code_num));
+        assert_eq!(get_config_value("pool_name"), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        assert_eq!(get_config_value("config_name"), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        assert_eq!(get_config_value("wallet_name"), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        assert_eq!(get_config_value("institution_name"), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        assert_eq!(get_config_value("genesis_path"), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
+        assert_eq!(get_config_value("wallet_key"), Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)));
     }
 
     #[test]
@@ -477,8 +608,7 @@ pub mod tests {
         ::utils::logger::LoggerUtils::init_test_logging("trace");
         set_defaults();
         let key = "secretkeyabc123foobar";
-        {
-            let mut settings = SETTINGS.write().unwrap();
+        with_settings_mut(|settings| {
             settings.insert(CONFIG_WALLET_KEY.to_string(), key.to_string()).unwrap();
             let masked_settings = settings.to_string();
             match masked_settings.get(CONFIG_WALLET_KEY) {
@@ -487,7 +617,7 @@ pub mod tests {
                     assert_ne!(value, key);
                 },
             }
-        }
+        });
         log_settings();
     }
 }
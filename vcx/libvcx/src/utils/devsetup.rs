@@ -35,6 +35,12 @@ macro_rules! init {
             ::settings::set_config_value(::settings::CONFIG_ENABLE_TEST_MODE,"false");
             ::utils::devsetup::tests::setup_local_env();
         },
+        "mock_agency" => {
+            ::utils::libindy::wallet::tests::delete_test_wallet(&format!("{}_{}", ::utils::constants::ENTERPRISE_PREFIX, ::settings::DEFAULT_WALLET_NAME));
+            ::utils::libindy::wallet::tests::delete_test_wallet(&format!("{}_{}", ::utils::constants::CONSUMER_PREFIX, ::settings::DEFAULT_WALLET_NAME));
+            ::settings::set_config_value(::settings::CONFIG_ENABLE_TEST_MODE,"false");
+            ::utils::devsetup::tests::setup_mock_agency_env();
+        },
         _ => {panic!("Invalid test mode");},
     };
     )
@@ -46,6 +52,7 @@ macro_rules! teardown {
 
     match $x {
         "agency" => { ::utils::devsetup::tests::cleanup_local_env(); },
+        "mock_agency" => { ::utils::devsetup::tests::cleanup_local_env(); },
         "false" => {
             ::utils::libindy::wallet::tests::delete_test_wallet(::settings::DEFAULT_WALLET_NAME);
             ::utils::libindy::pool::tests::delete_test_pool();
@@ -64,6 +71,9 @@ pub mod tests {
     use utils::libindy::pool;
     use settings;
     use object_cache::ObjectCache;
+    use std::sync::Arc;
+    use utils::httpclient;
+    use utils::mock_agency::MockAgency;
 
     static mut INSTITUTION_CONFIG: u32 = 0;
     static mut CONSUMER_CONFIG: u32 = 0;
@@ -126,13 +136,14 @@ pub mod tests {
         set_consumer();
         wallet::tests::delete_test_wallet(&format!("{}_{}", constants::CONSUMER_PREFIX, settings::DEFAULT_WALLET_NAME));
         pool::tests::delete_test_pool();
+        httpclient::reset_transport();
     }
 
     pub fn set_institution() {
         settings::clear_config();
         unsafe {
             CONFIG_STRING.get(INSTITUTION_CONFIG, |t| {
-                settings::process_config_string(&t)
+                settings::process_config_string(&t).map_err(|err| err.into())
             }).unwrap();
         }
         change_wallet_handle();
@@ -142,7 +153,7 @@ pub mod tests {
         settings::clear_config();
         unsafe {
             CONFIG_STRING.get(CONSUMER_CONFIG, |t| {
-                settings::process_config_string(&t)
+                settings::process_config_string(&t).map_err(|err| err.into())
             }).unwrap();
         }
         change_wallet_handle();
@@ -150,19 +161,38 @@ pub mod tests {
 
     fn change_wallet_handle() {
         let wallet_handle = settings::get_config_value(settings::CONFIG_WALLET_HANDLE).unwrap();
-        unsafe { wallet::WALLET_HANDLE = wallet_handle.parse::<i32>().unwrap() }
+        wallet::set_wallet_handle(wallet_handle.parse::<i32>().unwrap());
     }
 
-    pub fn setup_local_env() {
+    /// Agency details for the enterprise and the consumer. With the mock_agency feature
+    /// both are served by an in-process MockAgency instead of the sandbox agencies.
+    fn agency_details() -> (serde_json::Value, serde_json::Value) {
+        if cfg!(feature = "mock_agency") {
+            return mock_agency_details();
+        }
+
+        (json!({ "url": AGENCY_ENDPOINT, "did": AGENCY_DID, "verkey": AGENCY_VERKEY }),
+         json!({ "url": C_AGENCY_ENDPOINT, "did": C_AGENCY_DID, "verkey": C_AGENCY_VERKEY }))
+    }
+
+    fn mock_agency_details() -> (serde_json::Value, serde_json::Value) {
+        httpclient::reset_transport();
+        let agency = Arc::new(MockAgency::new().unwrap());
+        let details = json!({ "url": agency.endpoint(), "did": agency.did(), "verkey": agency.verkey() });
+        httpclient::set_transport(agency);
+        (details.clone(), details)
+    }
+
+    fn setup_agents(enterprise_agency: &serde_json::Value, consumer_agency: &serde_json::Value) {
         settings::clear_config();
         settings::set_config_value(settings::CONFIG_ENABLE_TEST_MODE, "false");
         settings::set_config_value(settings::CONFIG_WALLET_KEY, settings::DEFAULT_WALLET_KEY);
 
         let enterprise_wallet_name = format!("{}_{}", constants::ENTERPRISE_PREFIX, settings::DEFAULT_WALLET_NAME);
         let config = json!({
-            "agency_url": AGENCY_ENDPOINT.to_string(),
-            "agency_did": AGENCY_DID.to_string(),
-            "agency_verkey": AGENCY_VERKEY.to_string(),
+            "agency_url": enterprise_agency["url"],
+            "agency_did": enterprise_agency["did"],
+            "agency_verkey": enterprise_agency["verkey"],
             "wallet_name": enterprise_wallet_name,
             "wallet_key": settings::DEFAULT_WALLET_KEY.to_string(),
             "enterprise_seed": TRUSTEE.to_string(),
@@ -176,9 +206,9 @@ pub mod tests {
 
         let consumer_wallet_name = format!("{}_{}", constants::CONSUMER_PREFIX, settings::DEFAULT_WALLET_NAME);
         let config = json!({
-            "agency_url": C_AGENCY_ENDPOINT.to_string(),
-            "agency_did": C_AGENCY_DID.to_string(),
-            "agency_verkey": C_AGENCY_VERKEY.to_string(),
+            "agency_url": consumer_agency["url"],
+            "agency_did": consumer_agency["did"],
+            "agency_verkey": consumer_agency["verkey"],
             "wallet_name": consumer_wallet_name,
             "wallet_key": settings::DEFAULT_WALLET_KEY.to_string(),
             "enterprise_seed": TRUSTEE.to_string(),
@@ -196,6 +226,11 @@ pub mod tests {
         unsafe {
             CONSUMER_CONFIG = CONFIG_STRING.add(_config_with_wallet_handle(&consumer_wallet_name, &consumer_config)).unwrap();
         }
+    }
+
+    pub fn setup_local_env() {
+        let (enterprise_agency, consumer_agency) = agency_details();
+        setup_agents(&enterprise_agency, &consumer_agency);
 
         pool::tests::open_sandbox_pool();
 
@@ -206,6 +241,15 @@ pub mod tests {
         ::utils::libindy::payments::tests::token_setup(None, None);
     }
 
+    /// Provisions the enterprise and the consumer against an in-process MockAgency,
+    /// without opening a pool, and leaves the enterprise active.
+    pub fn setup_mock_agency_env() {
+        let (enterprise_agency, consumer_agency) = mock_agency_details();
+        setup_agents(&enterprise_agency, &consumer_agency);
+
+        set_institution();
+    }
+
     fn _config_with_wallet_handle(wallet_n: &str, config: &str) -> String {
         let wallet_handle = wallet::open_wallet(wallet_n).unwrap();
         let mut config: serde_json::Value = serde_json::from_str(config).unwrap();
@@ -245,6 +289,9 @@ pub mod tests {
 
         let (faber, alice) = ::connection::tests::create_connected_connections();
         set_institution();
+        let agency_url = settings::get_config_value(settings::CONFIG_AGENCY_ENDPOINT).unwrap();
+        let agency_did = settings::get_config_value(settings::CONFIG_AGENCY_DID).unwrap();
+        let agency_verkey = settings::get_config_value(settings::CONFIG_AGENCY_VERKEY).unwrap();
         wallet::tests::delete_test_wallet(&format!("{}_{}", constants::ENTERPRISE_PREFIX, settings::DEFAULT_WALLET_NAME));
         pool::close().unwrap();
         settings::set_defaults();
@@ -253,9 +300,9 @@ pub mod tests {
         settings::set_config_value(settings::CONFIG_WALLET_KEY, settings::DEFAULT_WALLET_KEY);
 
         let config = json!({
-            "agency_url": AGENCY_ENDPOINT.to_string(),
-            "agency_did": AGENCY_DID.to_string(),
-            "agency_verkey": AGENCY_VERKEY.to_string(),
+            "agency_url": agency_url,
+            "agency_did": agency_did,
+            "agency_verkey": agency_verkey,
             "wallet_key": settings::DEFAULT_WALLET_KEY.to_string(),
             "name": "another_institution".to_string(),
             "logo": "http://www.logo.com".to_string(),
@@ -98,6 +98,33 @@ pub static DID_ALREADY_EXISTS_IN_WALLET: Error = Error { code_num: 1083, message
 pub static DUPLICATE_MASTER_SECRET: Error = Error { code_num: 1084, message: "Attempted to add a Master Secret that already existed in wallet"};
 pub static THREAD_ERROR: Error = Error{ code_num: 1085, message: "Unable to create thread"};
 pub static INVALID_PROOF_REQUEST: Error = Error{ code_num: 1086, message: "Proof Request Passed into Libindy Call Was Invalid"};
+pub static INVALID_REVOCATION_DETAILS: Error = Error{ code_num: 1087, message: "Invalid Revocation Details"};
+pub static INVALID_REV_REG_DEF_CREATION: Error = Error{ code_num: 1088, message: "Failed to create Revocation Registration Definition"};
+pub static INVALID_REV_ENTRY: Error = Error{ code_num: 1089, message: "Unable to Update Revocation Delta On Ledger"};
+pub static INVALID_REVOCATION_INTERVAL: Error = Error{ code_num: 1090, message: "Invalid Revocation Interval"};
+pub static INVALID_REV_STATE: Error = Error{ code_num: 1091, message: "Unable to Create Revocation State"};
+pub static INVALID_TRANSPORT_HANDLE: Error = Error{ code_num: 1092, message: "No Pending Transport Request For Command Handle"};
+pub static AGENCY_UNREACHABLE: Error = Error{ code_num: 1093, message: "Agency could not be reached"};
+pub static AGENCY_REJECTED_MESSAGE: Error = Error{ code_num: 1094, message: "Agency rejected the message"};
+pub static INVALID_MESSAGE_ENVELOPE: Error = Error{ code_num: 1095, message: "Invalid Message Envelope"};
+pub static UNSUPPORTED_INVITATION: Error = Error{ code_num: 1096, message: "Unsupported Invitation Format or Protocol Version"};
+pub static LOGGING_ERROR: Error = Error{ code_num: 1097, message: "Logger Could Not Be Set"};
+pub static INVALID_CONTEXT_HANDLE: Error = Error{ code_num: 1098, message: "Invalid Context Handle"};
+pub static UNKNOWN_SERIALIZE_VERSION: Error = Error{ code_num: 1099, message: "Object Serialized With Unknown Version"};
+pub static INVALID_REVEALED_ATTRIBUTE_VALUE: Error = Error{ code_num: 1100, message: "Revealed Attribute Value Does Not Match Its Encoding"};
+pub static INVALID_PREDICATE: Error = Error{ code_num: 1101, message: "Invalid Predicate Type, Expected One Of <, <=, >, >="};
+pub static INVALID_RESTRICTION: Error = Error{ code_num: 1102, message: "Invalid Restriction In Proof Request"};
+pub static INVALID_REQUESTED_ATTRIBUTE: Error = Error{ code_num: 1103, message: "Requested Attribute Needs Either A Name Or A List Of Names"};
+pub static PROOF_TEMPLATE_NOT_FOUND: Error = Error{ code_num: 1104, message: "No Proof Template Stored With This Name"};
+pub static INVALID_CREDENTIAL_SELECTION: Error = Error{ code_num: 1105, message: "Chosen Credential Can Not Be Used For The Proof Request Referent"};
+pub static PACKING_KEY_NOT_FOUND: Error = Error{ code_num: 1106, message: "Key Was Not Created For Packing DIDComm Messages"};
+pub static RESERVED_WALLET_RECORD_TYPE: Error = Error{ code_num: 1107, message: "Wallet Record Type Is Reserved For VCX"};
+
+// Codes handed out by the former per-domain error enums, kept so that callers see the same numbers
+pub static RETRIEVE_CREDENTIAL_DEF_ERR: Error = Error{ code_num: 7001, message: "Failed To Retrieve Credential Definition From The Ledger"};
+pub static MESSAGE_PACK_ERROR: Error = Error{ code_num: 8001, message: "Deserialize Error For Message Pack"};
+pub static UNKNOWN_SCHEMA_REJECTION: Error = Error{ code_num: 8887, message: "Unknown Rejection Of Schema Creation Request"};
+pub static DUPLICATE_SCHEMA: Error = Error{ code_num: 8888, message: "Schema Already Exists On The Ledger"};
 
 lazy_static! {
     static ref ERROR_C_MESSAGES: HashMap<u32, CString> = {
@@ -189,6 +216,31 @@ lazy_static! {
         insert_c_message(&mut m, &INVALID_LEDGER_RESPONSE);
         insert_c_message(&mut m, &THREAD_ERROR);
         insert_c_message(&mut m, &INVALID_PROOF_REQUEST);
+        insert_c_message(&mut m, &INVALID_REVOCATION_DETAILS);
+        insert_c_message(&mut m, &INVALID_REV_REG_DEF_CREATION);
+        insert_c_message(&mut m, &INVALID_REV_ENTRY);
+        insert_c_message(&mut m, &INVALID_REVOCATION_INTERVAL);
+        insert_c_message(&mut m, &INVALID_REV_STATE);
+        insert_c_message(&mut m, &INVALID_TRANSPORT_HANDLE);
+        insert_c_message(&mut m, &AGENCY_UNREACHABLE);
+        insert_c_message(&mut m, &AGENCY_REJECTED_MESSAGE);
+        insert_c_message(&mut m, &INVALID_MESSAGE_ENVELOPE);
+        insert_c_message(&mut m, &UNSUPPORTED_INVITATION);
+        insert_c_message(&mut m, &LOGGING_ERROR);
+        insert_c_message(&mut m, &INVALID_CONTEXT_HANDLE);
+        insert_c_message(&mut m, &UNKNOWN_SERIALIZE_VERSION);
+        insert_c_message(&mut m, &INVALID_REVEALED_ATTRIBUTE_VALUE);
+        insert_c_message(&mut m, &INVALID_PREDICATE);
+        insert_c_message(&mut m, &INVALID_RESTRICTION);
+        insert_c_message(&mut m, &INVALID_REQUESTED_ATTRIBUTE);
+        insert_c_message(&mut m, &PROOF_TEMPLATE_NOT_FOUND);
+        insert_c_message(&mut m, &INVALID_CREDENTIAL_SELECTION);
+        insert_c_message(&mut m, &PACKING_KEY_NOT_FOUND);
+        insert_c_message(&mut m, &RESERVED_WALLET_RECORD_TYPE);
+        insert_c_message(&mut m, &RETRIEVE_CREDENTIAL_DEF_ERR);
+        insert_c_message(&mut m, &MESSAGE_PACK_ERROR);
+        insert_c_message(&mut m, &UNKNOWN_SCHEMA_REJECTION);
+        insert_c_message(&mut m, &DUPLICATE_SCHEMA);
         m
     };
 }
@@ -431,4 +483,93 @@ mod tests {
         // map_libindy_err called with Err returned
         assert_eq!(fn_map_err(Err(0)).map_err(|x| map_libindy_err(x, default)), Err(default))
     }
+
+    #[test]
+    fn test_revocation_errors() {
+        assert_eq!(error_message(&INVALID_REVOCATION_DETAILS.code_num), INVALID_REVOCATION_DETAILS.message);
+        assert_eq!(error_message(&INVALID_REV_REG_DEF_CREATION.code_num), INVALID_REV_REG_DEF_CREATION.message);
+        assert_eq!(error_message(&INVALID_REV_ENTRY.code_num), INVALID_REV_ENTRY.message);
+        assert_eq!(error_message(&INVALID_REVOCATION_INTERVAL.code_num), INVALID_REVOCATION_INTERVAL.message);
+        assert_eq!(error_message(&INVALID_REV_STATE.code_num), INVALID_REV_STATE.message);
+    }
+
+    #[test]
+    fn test_invalid_transport_handle() {
+        assert_eq!(error_message(&INVALID_TRANSPORT_HANDLE.code_num), INVALID_TRANSPORT_HANDLE.message);
+    }
+
+    #[test]
+    fn test_agency_errors() {
+        assert_eq!(error_message(&AGENCY_UNREACHABLE.code_num), AGENCY_UNREACHABLE.message);
+        assert_eq!(error_message(&AGENCY_REJECTED_MESSAGE.code_num), AGENCY_REJECTED_MESSAGE.message);
+    }
+
+    #[test]
+    fn test_invalid_message_envelope() {
+        assert_eq!(error_message(&INVALID_MESSAGE_ENVELOPE.code_num), INVALID_MESSAGE_ENVELOPE.message);
+    }
+
+    #[test]
+    fn test_unsupported_invitation() {
+        assert_eq!(error_message(&UNSUPPORTED_INVITATION.code_num), UNSUPPORTED_INVITATION.message);
+    }
+
+    #[test]
+    fn test_logging_error() {
+        assert_eq!(error_message(&LOGGING_ERROR.code_num), LOGGING_ERROR.message);
+    }
+
+    #[test]
+    fn test_invalid_context_handle() {
+        assert_eq!(error_message(&INVALID_CONTEXT_HANDLE.code_num), INVALID_CONTEXT_HANDLE.message);
+    }
+
+    #[test]
+    fn test_unknown_serialize_version() {
+        assert_eq!(error_message(&UNKNOWN_SERIALIZE_VERSION.code_num), UNKNOWN_SERIALIZE_VERSION.message);
+    }
+
+    #[test]
+    fn test_invalid_revealed_attribute_value() {
+        assert_eq!(error_message(&INVALID_REVEALED_ATTRIBUTE_VALUE.code_num), INVALID_REVEALED_ATTRIBUTE_VALUE.message);
+    }
+
+    #[test]
+    fn test_invalid_predicate() {
+        assert_eq!(error_message(&INVALID_PREDICATE.code_num), INVALID_PREDICATE.message);
+    }
+
+    #[test]
+    fn test_invalid_restriction() {
+        assert_eq!(error_message(&INVALID_RESTRICTION.code_num), INVALID_RESTRICTION.message);
+    }
+
+    #[test]
+    fn test_invalid_requested_attribute() {
+        assert_eq!(error_message(&INVALID_REQUESTED_ATTRIBUTE.code_num), INVALID_REQUESTED_ATTRIBUTE.message);
+    }
+
+    #[test]
+    fn test_proof_template_not_found() {
+        assert_eq!(error_message(&PROOF_TEMPLATE_NOT_FOUND.code_num), PROOF_TEMPLATE_NOT_FOUND.message);
+    }
+
+    #[test]
+    fn test_invalid_credential_selection() {
+        assert_eq!(error_message(&INVALID_CREDENTIAL_SELECTION.code_num), INVALID_CREDENTIAL_SELECTION.message);
+    }
+
+    #[test]
+    fn test_packing_key_not_found() {
+        assert_eq!(error_message(&PACKING_KEY_NOT_FOUND.code_num), PACKING_KEY_NOT_FOUND.message);
+        assert_eq!(error_message(&RESERVED_WALLET_RECORD_TYPE.code_num), RESERVED_WALLET_RECORD_TYPE.message);
+    }
+
+    #[test]
+    fn test_former_domain_error_codes() {
+        assert_eq!(error_message(&RETRIEVE_CREDENTIAL_DEF_ERR.code_num), RETRIEVE_CREDENTIAL_DEF_ERR.message);
+        assert_eq!(error_message(&MESSAGE_PACK_ERROR.code_num), MESSAGE_PACK_ERROR.message);
+        assert_eq!(error_message(&UNKNOWN_SCHEMA_REJECTION.code_num), UNKNOWN_SCHEMA_REJECTION.message);
+        assert_eq!(error_message(&DUPLICATE_SCHEMA.code_num), DUPLICATE_SCHEMA.message);
+    }
 }
@@ -1,51 +1,268 @@
+extern crate libc;
+
 use settings;
 use std::io::Read;
-use std::sync::Mutex;
+use std::sync::{ Arc, Mutex, RwLock };
+use std::sync::mpsc::{ channel, Sender };
+use std::collections::HashMap;
+use std::ffi::CString;
+use self::libc::c_char;
 use reqwest;
-use reqwest::header::{ContentType};
+use reqwest::header::CONTENT_TYPE;
 use std::env;
+use std::thread;
+use std::time::Duration;
+use utils::error;
+use utils::timeout::TimeoutUtils;
+use error::{ VcxError, VcxResult };
+
+/// Sends an encrypted bundle to the agency and returns the raw response.
+/// The default implementation uses reqwest; hosts can register their own to route
+/// agency traffic through their networking stack, and tests can serve responses in-process.
+///
+/// Implementations report AGENCY_UNREACHABLE when the message may not have been
+/// delivered (these are retried for idempotent messages) and AGENCY_REJECTED_MESSAGE when
+/// the agency answered with an error.
+pub trait Transport: Send + Sync {
+    fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>>;
+}
+
+/// Called by CallbackTransport for every post. The host must answer with
+/// vcx_http_transport_response using the same command_handle.
+pub type TransportCallback = extern fn(command_handle: u32, url: *const c_char, body: *const u8, body_len: u32);
+
 lazy_static!{
     static ref NEXT_U8_RESPONSE: Mutex<Vec<Vec<u8>>> = Mutex::new(vec![]);
+    static ref TRANSPORT: RwLock<Option<Arc<Transport>>> = RwLock::new(None);
+    static ref PENDING_RESPONSES: Mutex<HashMap<u32, Sender<Result<Vec<u8>, u32>>>> = Default::default();
+    static ref HTTP_CLIENT: Mutex<Option<CachedClient>> = Mutex::new(None);
+}
+
+#[cfg(test)]
+lazy_static!{
+    // the registered transport is shared by the whole process, tests registering one take turns
+    static ref TEST_TRANSPORT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// reqwest client kept between posts so the agency connection can be reused.
+/// It is rebuilt whenever the configured timeouts change.
+struct CachedClient {
+    connect_timeout_ms: u64,
+    read_timeout_ms: u64,
+    client: reqwest::Client,
 }
 
-//Todo: change this RC to a u32
-pub fn post_u8(body_content: &Vec<u8>) -> Result<Vec<u8>,String> {
+pub struct ReqwestTransport {}
+
+impl ReqwestTransport {
+    fn client(&self) -> VcxResult<reqwest::Client> {
+        let connect_timeout_ms = settings::get_agency_connect_timeout_ms();
+        let read_timeout_ms = settings::get_agency_read_timeout_ms();
 
-    let url = format!("{}/agency/msg", settings::get_config_value(settings::CONFIG_AGENCY_ENDPOINT).or(Err("Invalid Configuration".to_string()))?);
+        let mut cached = HTTP_CLIENT.lock().or(Err(VcxError::from(error::UNKNOWN_ERROR.code_num)))?;
 
-    //Setting SSL Certs location. This is needed on android platform. Or openssl will fail to verify the certs
-    if cfg!(target_os = "android") {
-        info!("::Android code");
-        set_ssl_cert_location();
+        if let Some(ref cached) = *cached {
+            if cached.connect_timeout_ms == connect_timeout_ms && cached.read_timeout_ms == read_timeout_ms {
+                return Ok(cached.client.clone());
+            }
+        }
+
+        let client = reqwest::ClientBuilder::new()
+            .connect_timeout(Duration::from_millis(connect_timeout_ms))
+            .timeout(Duration::from_millis(read_timeout_ms))
+            .build()
+            .or(Err(VcxError::from(error::UNKNOWN_ERROR.code_num)))?;
+
+        *cached = Some(CachedClient { connect_timeout_ms, read_timeout_ms, client: client.clone() });
+        Ok(client)
     }
-    let client = reqwest::ClientBuilder::new().build().or(Err("Preparing Post failed".to_string()))?;
-    debug!("Posting encrypted bundle to: \"{}\"", url);
-    if settings::test_agency_mode_enabled() {return Ok(NEXT_U8_RESPONSE.lock().unwrap().pop().unwrap_or(Vec::new()));}
-    let mut response = match  client.post(&url).body(body_content.to_owned()).header(ContentType::octet_stream()).send() {
-        Ok(result) => {
-            trace!("got the result");
-            result
-        },
-        Err(err) => {
-            error!("error: {}", err);
-            return Err("could not connect".to_string())
-        },
-    };
-
-    trace!("Response Header: {:?}", response);
-    if !response.status().is_success() {
-        let mut content = String::new();
-        match response.read_to_string(&mut content) {
-            Ok(x) => info!("Request failed: {}", content),
-            Err(x) => info!("could not read response"),
+
+    fn reset_client(&self) {
+        if let Ok(mut cached) = HTTP_CLIENT.lock() { *cached = None; }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>> {
+        //Setting SSL Certs location. This is needed on android platform. Or openssl will fail to verify the certs
+        if cfg!(target_os = "android") {
+            info!("::Android code");
+            set_ssl_cert_location();
+        }
+        let client = self.client()?;
+        let mut response = match client.post(url).body(body.to_owned()).header(CONTENT_TYPE, "application/octet-stream").send() {
+            Ok(result) => {
+                trace!("got the result");
+                result
+            },
+            Err(err) => {
+                error!("error: {}", err);
+                // the connection may be stale, start from a fresh client next time
+                self.reset_client();
+                return Err(VcxError::from_msg(error::AGENCY_UNREACHABLE.code_num, err.to_string()))
+            },
         };
-        return Err("POST failed".to_string());
+
+        trace!("Response Header: {:?}", response);
+        if !response.status().is_success() {
+            let mut content = String::new();
+            match response.read_to_string(&mut content) {
+                Ok(x) => info!("Request failed: {}", content),
+                Err(x) => info!("could not read response"),
+            };
+            return Err(VcxError::from_msg(error::AGENCY_REJECTED_MESSAGE.code_num, format!("{}: {}", response.status(), content)));
+        }
+
+        let mut content = Vec::new();
+        match response.read_to_end(&mut content) {
+            Ok(x) => Ok(content.to_owned()),
+            Err(_) => Err(VcxError::from_code(error::INVALID_HTTP_RESPONSE.code_num)),
+        }
     }
+}
+
+/// Serves the responses queued with set_next_u8_response, used when agency mocks are enabled.
+pub struct MockTransport {}
+
+impl Transport for MockTransport {
+    fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>> {
+        Ok(NEXT_U8_RESPONSE.lock().unwrap().pop().unwrap_or(Vec::new()))
+    }
+}
+
+/// Hands every post to a host supplied C callback and waits for its answer.
+pub struct CallbackTransport {
+    cb: TransportCallback,
+}
 
-    let mut content = Vec::new();
-    match response.read_to_end(&mut content) {
-        Ok(x) => Ok(content.to_owned()),
-        Err(_) => Err("could not read response".to_string()),
+impl CallbackTransport {
+    pub fn new(cb: TransportCallback) -> CallbackTransport {
+        CallbackTransport { cb }
+    }
+}
+
+impl Transport for CallbackTransport {
+    fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>> {
+        let url = CString::new(url).or(Err(VcxError::from(error::INVALID_URL.code_num)))?;
+        let command_handle = ::utils::generate_command_handle() as u32;
+        let (sender, receiver) = channel();
+
+        PENDING_RESPONSES.lock().or(Err(VcxError::from(error::UNKNOWN_ERROR.code_num)))?.insert(command_handle, sender);
+
+        (self.cb)(command_handle, url.as_ptr(), body.as_ptr(), body.len() as u32);
+
+        let response = receiver.recv_timeout(TimeoutUtils::long_timeout());
+
+        if let Ok(mut pending) = PENDING_RESPONSES.lock() { pending.remove(&command_handle); }
+
+        match response {
+            Ok(Ok(content)) => Ok(content),
+            Ok(Err(err)) if err == error::AGENCY_UNREACHABLE.code_num => {
+                info!("Host could not reach the agency");
+                Err(VcxError::from_code(error::AGENCY_UNREACHABLE.code_num))
+            },
+            Ok(Err(err)) => {
+                info!("Request failed with transport error: {}", err);
+                Err(VcxError::from_code(err)
+                    .context(error::AGENCY_REJECTED_MESSAGE.code_num, "host transport failed"))
+            },
+            Err(_) => Err(VcxError::from_msg(error::AGENCY_UNREACHABLE.code_num, "no response from host transport".to_string())),
+        }
+    }
+}
+
+/// Completes a post started by CallbackTransport with the body or the error code the host answered.
+pub fn set_transport_response(command_handle: u32, response: Result<Vec<u8>, u32>) -> VcxResult<()> {
+    let sender = PENDING_RESPONSES.lock()
+        .or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?
+        .remove(&command_handle)
+        .ok_or(VcxError::from_code(error::INVALID_TRANSPORT_HANDLE.code_num))?;
+
+    // the request may have timed out in the meantime, in which case nobody is listening
+    let _ = sender.send(response);
+    Ok(())
+}
+
+pub fn set_transport(transport: Arc<Transport>) {
+    *TRANSPORT.write().unwrap() = Some(transport);
+}
+
+pub fn reset_transport() {
+    *TRANSPORT.write().unwrap() = None;
+}
+
+fn get_transport() -> Arc<Transport> {
+    if let Some(ref transport) = *TRANSPORT.read().unwrap() {
+        return transport.clone();
+    }
+
+    match settings::test_agency_mode_enabled() {
+        true => Arc::new(MockTransport {}),
+        false => Arc::new(ReqwestTransport {}),
+    }
+}
+
+/// Held by tests while they have a transport registered, the transport is reset when it is dropped.
+#[cfg(test)]
+pub struct TestTransportGuard {
+    _lock: ::std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl Drop for TestTransportGuard {
+    fn drop(&mut self) {
+        reset_transport();
+    }
+}
+
+/// Tests registering a transport, directly or through vcx_set_http_transport, hold the guard
+/// until they are done with it.
+#[cfg(test)]
+pub fn lock_test_transport() -> TestTransportGuard {
+    let lock = TEST_TRANSPORT_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    reset_transport();
+    TestTransportGuard { _lock: lock }
+}
+
+pub fn post_u8(body_content: &Vec<u8>) -> VcxResult<Vec<u8>> {
+    _post_u8(get_transport().as_ref(), body_content)
+}
+
+fn _post_u8(transport: &Transport, body_content: &[u8]) -> VcxResult<Vec<u8>> {
+    let url = format!("{}/agency/msg", settings::get_config_value(settings::CONFIG_AGENCY_ENDPOINT)?);
+
+    debug!("Posting encrypted bundle to: \"{}\"", url);
+    transport.post(&url, body_content)
+}
+
+/// Posts a packed DIDComm message to the service endpoint of another agent.
+pub fn post_to_endpoint(url: &str, body_content: &[u8]) -> VcxResult<Vec<u8>> {
+    debug!("Posting packed message to: \"{}\"", url);
+    get_transport().post(url, body_content)
+}
+
+/// Posts a message that is safe to deliver more than once (GET_MSGS, UPDATE_MSG_STATUS).
+/// Posts that fail because the agency could not be reached are retried up to
+/// agency_retries times, doubling the agency_retry_backoff_ms delay after every attempt.
+pub fn post_u8_idempotent(body_content: &Vec<u8>) -> VcxResult<Vec<u8>> {
+    _post_u8_idempotent(get_transport().as_ref(), body_content)
+}
+
+fn _post_u8_idempotent(transport: &Transport, body_content: &[u8]) -> VcxResult<Vec<u8>> {
+    let retries = settings::get_agency_retries();
+    let mut backoff = settings::get_agency_retry_backoff_ms();
+    let mut attempt = 0;
+
+    loop {
+        match _post_u8(transport, body_content) {
+            Err(ref err) if err.code() == error::AGENCY_UNREACHABLE.code_num && attempt < retries => {
+                attempt += 1;
+                warn!("{}, retrying in {}ms ({}/{})", err, backoff, attempt, retries);
+                thread::sleep(Duration::from_millis(backoff));
+                backoff = backoff.saturating_mul(2);
+            },
+            result => return result,
+        }
     }
 }
 
@@ -64,3 +281,130 @@ fn set_ssl_cert_location(){
     }
     info!("::SSL_CERT_FILE has been set");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::slice;
+    use std::sync::atomic::{ AtomicUsize, Ordering };
+
+    struct EchoTransport {}
+
+    impl Transport for EchoTransport {
+        fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>> {
+            Ok(body.to_vec())
+        }
+    }
+
+    /// Fails the first `failures` posts with the given error, then echoes.
+    struct FlakyTransport {
+        failures: usize,
+        rejected: bool,
+        attempts: AtomicUsize,
+    }
+
+    impl Transport for FlakyTransport {
+        fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return match self.rejected {
+                    true => Err(VcxError::from_msg(error::AGENCY_REJECTED_MESSAGE.code_num, "400 Bad Request".to_string())),
+                    false => Err(VcxError::from_msg(error::AGENCY_UNREACHABLE.code_num, "connection refused".to_string())),
+                };
+            }
+            Ok(body.to_vec())
+        }
+    }
+
+    fn flaky_transport(failures: usize, rejected: bool) -> Arc<FlakyTransport> {
+        Arc::new(FlakyTransport { failures, rejected, attempts: AtomicUsize::new(0) })
+    }
+
+    extern fn answering_cb(command_handle: u32, url: *const c_char, body: *const u8, body_len: u32) {
+        let body = unsafe { slice::from_raw_parts(body, body_len as usize) }.to_vec();
+        set_transport_response(command_handle, Ok(body)).unwrap();
+    }
+
+    extern fn failing_cb(command_handle: u32, url: *const c_char, body: *const u8, body_len: u32) {
+        set_transport_response(command_handle, Err(error::POST_MSG_FAILURE.code_num)).unwrap();
+    }
+
+    extern fn unreachable_cb(command_handle: u32, url: *const c_char, body: *const u8, body_len: u32) {
+        set_transport_response(command_handle, Err(error::AGENCY_UNREACHABLE.code_num)).unwrap();
+    }
+
+    #[test]
+    fn test_post_uses_mock_transport_in_test_mode() {
+        init!("true");
+        let _transport = lock_test_transport();
+        set_next_u8_response(vec![1, 2, 3]);
+        assert_eq!(post_u8(&vec![9]).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_registered_transport_takes_precedence() {
+        init!("true");
+        let transport = lock_test_transport();
+        set_transport(Arc::new(EchoTransport {}));
+        set_next_u8_response(vec![1, 2, 3]);
+        assert_eq!(post_u8(&vec![4, 5]).unwrap(), vec![4, 5]);
+        drop(transport);
+        assert_eq!(post_u8(&vec![9]).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_callback_transport() {
+        init!("true");
+        let transport = CallbackTransport::new(answering_cb);
+        assert_eq!(transport.post("http://localhost/agency/msg", &vec![7, 8]).unwrap(), vec![7, 8]);
+
+        let transport = CallbackTransport::new(failing_cb);
+        assert_eq!(transport.post("http://localhost/agency/msg", &vec![7, 8]).unwrap_err().code(), error::AGENCY_REJECTED_MESSAGE.code_num);
+
+        let transport = CallbackTransport::new(unreachable_cb);
+        assert_eq!(transport.post("http://localhost/agency/msg", &vec![7, 8]).unwrap_err().code(), error::AGENCY_UNREACHABLE.code_num);
+    }
+
+    #[test]
+    fn test_idempotent_post_retries_unreachable_agency() {
+        init!("true");
+        settings::set_config_value(settings::CONFIG_AGENCY_RETRY_BACKOFF_MS, "1");
+        settings::set_config_value(settings::CONFIG_AGENCY_RETRIES, "2");
+
+        let transport = flaky_transport(2, false);
+        assert_eq!(_post_u8_idempotent(transport.as_ref(), &vec![4, 5]).unwrap(), vec![4, 5]);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 3);
+
+        let transport = flaky_transport(3, false);
+        assert_eq!(_post_u8_idempotent(transport.as_ref(), &vec![4, 5]).unwrap_err().code(), error::AGENCY_UNREACHABLE.code_num);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_rejected_and_non_idempotent_posts_are_not_retried() {
+        init!("true");
+        settings::set_config_value(settings::CONFIG_AGENCY_RETRY_BACKOFF_MS, "1");
+
+        let transport = flaky_transport(1, true);
+        assert_eq!(_post_u8_idempotent(transport.as_ref(), &vec![4, 5]).unwrap_err().code(), error::AGENCY_REJECTED_MESSAGE.code_num);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+
+        let transport = flaky_transport(1, false);
+        assert_eq!(_post_u8(transport.as_ref(), &vec![4, 5]).unwrap_err().code(), error::AGENCY_UNREACHABLE.code_num);
+        assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_unreachable_agency_endpoint() {
+        init!("false");
+        settings::set_config_value(settings::CONFIG_AGENCY_CONNECT_TIMEOUT_MS, "100");
+        let transport = ReqwestTransport {};
+        // nothing listens on the discard port
+        assert_eq!(transport.post("http://127.0.0.1:9/agency/msg", &vec![1]).unwrap_err().code(),
+                   error::AGENCY_UNREACHABLE.code_num);
+    }
+
+    #[test]
+    fn test_transport_response_with_unknown_handle() {
+        assert_eq!(set_transport_response(0, Ok(Vec::new())).unwrap_err().code(), error::INVALID_TRANSPORT_HANDLE.code_num);
+    }
+}
@@ -7,6 +7,7 @@ use serde_json::Value;
 use serde_json::Map;
 use std::string::String;
 use utils::error;
+use error::{ VcxError, VcxResult };
 
 
 pub trait JsonEncodable: Serialize + Sized {
@@ -36,20 +37,20 @@ impl KeyMatch for String {
 Rewrites keys in a serde value structor to new mapped values. Returns the remapped value. Leaves
 unmapped keys as they are.
 */
-pub fn mapped_key_rewrite<T: KeyMatch>(val: Value, remap: &Vec<(T, String)>) -> Result<Value, u32> {
+pub fn mapped_key_rewrite<T: KeyMatch>(val: Value, remap: &Vec<(T, String)>) -> VcxResult<Value> {
     let mut context: Vec<String> = Default::default();
     _mapped_key_rewrite(val, &mut context, remap)
 }
 
 
-fn _mapped_key_rewrite<T: KeyMatch>(val: Value, context: &mut Vec<String>, remap: &Vec<(T, String)>) -> Result<Value, u32> {
+fn _mapped_key_rewrite<T: KeyMatch>(val: Value, context: &mut Vec<String>, remap: &Vec<(T, String)>) -> VcxResult<Value> {
     if let Value::Object(mut map) = val {
         let mut keys:Vec<String> = _collect_keys(&map);
 
         while let Some(k) = keys.pop() {
             let mut value = map.remove(&k).ok_or_else(||{
                 warn!("Unexpected key value mutation");
-                error::INVALID_JSON.code_num
+                VcxError::from_code(error::INVALID_JSON.code_num)
             })?;
 
 
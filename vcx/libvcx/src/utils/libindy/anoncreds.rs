@@ -3,18 +3,34 @@ extern crate libc;
 use serde_json;
 use serde_json::{ map::Map, Value};
 use settings;
-use utils::constants::{ LIBINDY_CRED_OFFER, REQUESTED_ATTRIBUTES, ATTRS};
-use utils::error::{ INVALID_PROOF_REQUEST, INVALID_ATTRIBUTES_STRUCTURE, INVALID_CONFIGURATION } ;
-use utils::libindy::{ error_codes::map_rust_indy_sdk_error_code, mock_libindy_rc, wallet::get_wallet_handle };
+use utils::constants::{ LIBINDY_CRED_OFFER, REQUESTED_ATTRIBUTES, ATTRS, REV_REG_ID, REV_REG_DEF_JSON,
+                        REV_REG_DELTA_JSON, REV_REG_DELTA_TIMESTAMP, REV_REG_DEF_TXN_TYPE, REV_REG_DELTA_TXN_TYPE,
+                        REV_STATE_JSON, REV_REG_JSON };
+use utils::error::{ INVALID_PROOF_REQUEST, INVALID_ATTRIBUTES_STRUCTURE, INVALID_CONFIGURATION,
+                    INVALID_REV_REG_DEF_CREATION, INVALID_REV_ENTRY } ;
+use utils::libindy::{ error_codes::map_rust_indy_sdk_error, mock_libindy_rc, wallet::get_wallet_handle };
+use utils::libindy::blob_storage::{ open_tails_writer, open_tails_reader };
+use utils::libindy::payments::{ pay_for_txn, PaymentTxn };
+use utils::libindy::ledger::{ libindy_submit_request,
+                              libindy_build_revoc_reg_def_request,
+                              libindy_build_revoc_reg_entry_request,
+                              libindy_build_get_revoc_reg_def_request,
+                              libindy_parse_get_revoc_reg_def_response,
+                              libindy_build_get_revoc_reg_request,
+                              libindy_parse_get_revoc_reg_response,
+                              libindy_build_get_revoc_reg_delta_request,
+                              libindy_parse_get_revoc_reg_delta_response };
+use std::time::{ SystemTime, UNIX_EPOCH };
 use utils::timeout::TimeoutUtils;
 use indy::anoncreds::{ Verifier, Prover, Issuer };
+use error::{ VcxError, VcxResult };
 
 pub fn libindy_verifier_verify_proof(proof_req_json: &str,
                                      proof_json: &str,
                                      schemas_json: &str,
                                      credential_defs_json: &str,
                                      rev_reg_defs_json: &str,
-                                     rev_regs_json: &str)  -> Result<bool, u32> {
+                                     rev_regs_json: &str)  -> VcxResult<bool> {
 
     Verifier::verify_proof_timeout(proof_req_json,
                                    proof_json,
@@ -23,14 +39,14 @@ pub fn libindy_verifier_verify_proof(proof_req_json: &str,
                                    rev_reg_defs_json,
                                    rev_regs_json,
                                    TimeoutUtils::long_timeout())
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 pub fn libindy_create_and_store_credential_def(issuer_did: &str,
                                                schema_json: &str,
                                                tag: &str,
                                                sig_type: Option<&str>,
-                                               config_json: &str)  -> Result<(String, String), u32>  {
+                                               config_json: &str)  -> VcxResult<(String, String)>  {
 
     Issuer::create_and_store_credential_def(get_wallet_handle(),
                                             issuer_did,
@@ -38,25 +54,25 @@ pub fn libindy_create_and_store_credential_def(issuer_did: &str,
                                             tag,
                                             sig_type,
                                             config_json)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_issuer_create_credential_offer(cred_def_id: &str) -> Result<String, u32> {
+pub fn libindy_issuer_create_credential_offer(cred_def_id: &str) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() {
         let rc = mock_libindy_rc();
-        if rc != 0 { return Err(rc) };
+        if rc != 0 { return Err(VcxError::from(rc)) };
         return Ok(LIBINDY_CRED_OFFER.to_string());
     }
     Issuer::create_credential_offer(get_wallet_handle(),
                                     cred_def_id)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 pub fn libindy_issuer_create_credential(cred_offer_json: &str,
                                         cred_req_json: &str,
                                         cred_values_json: &str,
                                         rev_reg_id: Option<&str>,
-                                        blob_storage_reader_handle: Option<i32>) -> Result<(String, Option<String>, Option<String>), u32>{
+                                        blob_storage_reader_handle: Option<i32>) -> VcxResult<(String, Option<String>, Option<String>)>{
 
     let blob_storage_reader_handle = blob_storage_reader_handle.unwrap_or(-1);
 
@@ -66,7 +82,7 @@ pub fn libindy_issuer_create_credential(cred_offer_json: &str,
                               cred_values_json,
                               rev_reg_id,
                               blob_storage_reader_handle)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 pub fn libindy_prover_create_proof(proof_req_json: &str,
@@ -74,7 +90,7 @@ pub fn libindy_prover_create_proof(proof_req_json: &str,
                                    master_secret_id: &str,
                                    schemas_json: &str,
                                    credential_defs_json: &str,
-                                   revoc_states_json: Option<&str>) -> Result<String, u32> {
+                                   revoc_states_json: Option<&str>) -> VcxResult<String> {
     let revoc_states_json = revoc_states_json.unwrap_or("{}");
     Prover::create_proof(get_wallet_handle(),
                          proof_req_json,
@@ -83,34 +99,34 @@ pub fn libindy_prover_create_proof(proof_req_json: &str,
                          schemas_json,
                          credential_defs_json,
                          revoc_states_json)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-fn fetch_credentials(search_handle: i32, requested_attributes: Map<String, Value>) -> Result<String, u32> {
+fn fetch_credentials(search_handle: i32, requested_attributes: Map<String, Value>) -> VcxResult<String> {
     let mut v: Value = json!({});
     for item_referent in requested_attributes.keys().into_iter() {
         v[ATTRS][item_referent] = serde_json::from_str(&Prover::_fetch_credentials_for_proof_req(search_handle, item_referent, 100)
-            .map_err(map_rust_indy_sdk_error_code)?)
+            .map_err(map_rust_indy_sdk_error)?)
             .map_err(|_| {
                 error!("Invalid Json Parsing of Object Returned from Libindy. Did Libindy change its structure?");
-                INVALID_CONFIGURATION.code_num
+                VcxError::from_code(INVALID_CONFIGURATION.code_num)
             })?
     }
     Ok(v.to_string())
 }
 
-fn close_search_handle(search_handle: i32) -> Result<(), u32> {
+fn close_search_handle(search_handle: i32) -> VcxResult<()> {
     Prover::_close_credentials_search_for_proof_req(search_handle).map_err(|ec| {
         error!("Error closing search handle");
-        map_rust_indy_sdk_error_code(ec)
+        map_rust_indy_sdk_error(ec)
     })
 }
 
-pub fn libindy_prover_get_credentials_for_proof_req(proof_req: &str) -> Result<String, u32> {
+pub fn libindy_prover_get_credentials_for_proof_req(proof_req: &str) -> VcxResult<String> {
     let wallet_handle = get_wallet_handle();
 
     // this may be too redundant since Prover::search_credentials will validate the proof reqeuest already.
-    let proof_request_json:Map<String, Value> = serde_json::from_str(proof_req).map_err(|_| INVALID_PROOF_REQUEST.code_num)?;
+    let proof_request_json:Map<String, Value> = serde_json::from_str(proof_req).map_err(|_| VcxError::from_code(INVALID_PROOF_REQUEST.code_num))?;
 
     // since the search_credentials_for_proof request validates that the proof_req is properly structured, this get()
     // fn should never fail, unless libindy changes their formats.
@@ -124,7 +140,7 @@ pub fn libindy_prover_get_credentials_for_proof_req(proof_req: &str) -> Result<S
         Some(attrs) => {
             let search_handle = Prover::search_credentials_for_proof_req(wallet_handle, proof_req, None).map_err(|ec| {
                 error!("Opening Indy Search for Credentials Failed");
-                map_rust_indy_sdk_error_code(ec)
+                map_rust_indy_sdk_error(ec)
             })?;
             let creds: String = fetch_credentials(search_handle, attrs)?;
             // should an error on closing a search handle throw an error, or just a warning?
@@ -133,7 +149,7 @@ pub fn libindy_prover_get_credentials_for_proof_req(proof_req: &str) -> Result<S
             Ok(creds)
         },
         None => {
-            Err(INVALID_ATTRIBUTES_STRUCTURE.code_num)
+            Err(VcxError::from_code(INVALID_ATTRIBUTES_STRUCTURE.code_num))
         }
     }
 
@@ -141,7 +157,7 @@ pub fn libindy_prover_get_credentials_for_proof_req(proof_req: &str) -> Result<S
 
 pub fn libindy_prover_create_credential_req(prover_did: &str,
                                             credential_offer_json: &str,
-                                            credential_def_json: &str) -> Result<(String, String), u32> {
+                                            credential_def_json: &str) -> VcxResult<(String, String)> {
     if settings::test_indy_mode_enabled() { return Ok((::utils::constants::CREDENTIAL_REQ_STRING.to_owned(), String::new())); }
 
     let master_secret_name = settings::DEFAULT_LINK_SECRET_ALIAS;
@@ -150,14 +166,14 @@ pub fn libindy_prover_create_credential_req(prover_did: &str,
                                   credential_offer_json,
                                   credential_def_json,
                                   master_secret_name)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 pub fn libindy_prover_store_credential(cred_id: Option<&str>,
                                        cred_req_meta: &str,
                                        cred_json: &str,
                                        cred_def_json: &str,
-                                       rev_reg_def_json: Option<&str>) -> Result<String, u32> {
+                                       rev_reg_def_json: Option<&str>) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() { return Ok("cred_id".to_string()); }
 
     Prover::store_credential(get_wallet_handle(),
@@ -166,27 +182,162 @@ pub fn libindy_prover_store_credential(cred_id: Option<&str>,
                              cred_json,
                              cred_def_json,
                              rev_reg_def_json)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_prover_create_master_secret(master_secret_id: &str) -> Result<String, u32> {
+pub fn libindy_prover_create_master_secret(master_secret_id: &str) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() { return Ok(settings::DEFAULT_LINK_SECRET_ALIAS.to_string()); }
 
     Prover::create_master_secret(get_wallet_handle(),
                                  Some(master_secret_id))
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 pub fn libindy_issuer_create_schema(issuer_did: &str,
                                     name: &str,
                                     version: &str,
-                                    attrs: &str) -> Result<(String, String), u32>{
+                                    attrs: &str) -> VcxResult<(String, String)>{
 
     Issuer::create_schema(issuer_did,
                           name,
                           version,
                           attrs)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+
+pub fn libindy_create_and_store_revoc_reg(issuer_did: &str,
+                                          cred_def_id: &str,
+                                          tails_path: &str,
+                                          max_creds: u32) -> VcxResult<(String, String, String)> {
+    trace!("creating revocation registry for cred_def: {}, tails_path: {}", cred_def_id, tails_path);
+    if settings::test_indy_mode_enabled() { return Ok((REV_REG_ID.to_string(), REV_REG_DEF_JSON.to_string(), REV_REG_DELTA_JSON.to_string())); }
+
+    let tails_writer = open_tails_writer(tails_path)?;
+    let revoc_config = json!({ "max_cred_num": max_creds, "issuance_type": "ISSUANCE_BY_DEFAULT" }).to_string();
+
+    Issuer::create_and_store_revoc_reg(get_wallet_handle(),
+                                       issuer_did,
+                                       None,
+                                       "tag1",
+                                       cred_def_id,
+                                       &revoc_config,
+                                       tails_writer)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_issuer_revoke_credential(tails_file: &str, rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<String> {
+    if settings::test_indy_mode_enabled() {
+        let rc = mock_libindy_rc();
+        if rc != 0 { return Err(VcxError::from(rc)) };
+        return Ok(REV_REG_DELTA_JSON.to_string());
+    }
+
+    let blob_handle = open_tails_reader(tails_file)?;
+
+    Issuer::revoke_credential(get_wallet_handle(), blob_handle, rev_reg_id, cred_rev_id)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_create_revocation_state(tails_file: &str,
+                                       rev_reg_def_json: &str,
+                                       rev_reg_delta_json: &str,
+                                       timestamp: u64,
+                                       cred_rev_id: &str) -> VcxResult<String> {
+    if settings::test_indy_mode_enabled() { return Ok(REV_STATE_JSON.to_string()); }
+
+    let blob_handle = open_tails_reader(tails_file)?;
+
+    Prover::create_revocation_state(blob_handle, rev_reg_def_json, rev_reg_delta_json, timestamp, cred_rev_id)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn create_rev_reg_def(issuer_did: &str,
+                          cred_def_id: &str,
+                          tails_file: &str,
+                          max_creds: u32) -> VcxResult<(String, String, String, Option<PaymentTxn>)> {
+    debug!("creating revocation registry definition with issuer_did: {}, cred_def_id: {}, tails_file: {}, max_creds: {}",
+           issuer_did, cred_def_id, tails_file, max_creds);
+    if settings::test_indy_mode_enabled() {
+        return Ok((REV_REG_ID.to_string(), REV_REG_DEF_JSON.to_string(), REV_REG_DELTA_JSON.to_string(), None));
+    }
+
+    let (rev_reg_id, rev_reg_def_json, rev_reg_entry_json) = libindy_create_and_store_revoc_reg(issuer_did,
+                                                                                                 cred_def_id,
+                                                                                                 tails_file,
+                                                                                                 max_creds)?;
+
+    let rev_reg_def_req = libindy_build_revoc_reg_def_request(issuer_did, &rev_reg_def_json)
+        .or(Err(VcxError::from_code(INVALID_REV_REG_DEF_CREATION.code_num)))?;
+
+    let (payment, _) = pay_for_txn(&rev_reg_def_req, REV_REG_DEF_TXN_TYPE)?;
+
+    Ok((rev_reg_id, rev_reg_def_json, rev_reg_entry_json, payment))
+}
+
+pub fn publish_rev_reg_delta(issuer_did: &str, rev_reg_id: &str, rev_reg_delta_json: &str) -> VcxResult<(Option<PaymentTxn>, String)> {
+    if settings::test_indy_mode_enabled() { return Ok((None, r#"{"rc":"success"}"#.to_string())); }
+
+    let rev_reg_entry_req = libindy_build_revoc_reg_entry_request(issuer_did, rev_reg_id, "CL_ACCUM", rev_reg_delta_json)
+        .or(Err(VcxError::from_code(INVALID_REV_ENTRY.code_num)))?;
+
+    pay_for_txn(&rev_reg_entry_req, REV_REG_DELTA_TXN_TYPE)
+}
+
+pub fn get_rev_reg_def_json(rev_reg_id: &str) -> VcxResult<(String, String)> {
+    if settings::test_indy_mode_enabled() { return Ok((REV_REG_ID.to_string(), REV_REG_DEF_JSON.to_string())); }
+
+    let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+
+    let req = libindy_build_get_revoc_reg_def_request(&submitter_did, rev_reg_id)?;
+
+    let response = libindy_submit_request(&req)?;
+
+    libindy_parse_get_revoc_reg_def_response(&response)
+}
+
+/// Returns (rev_reg_id, rev_reg_json, timestamp) with the accumulator of the registry as it was at
+/// `timestamp`, the timestamp returned is the one of the last entry before it.
+pub fn get_rev_reg(rev_reg_id: &str, timestamp: u64) -> VcxResult<(String, String, u64)> {
+    if settings::test_indy_mode_enabled() { return Ok((REV_REG_ID.to_string(), REV_REG_JSON.to_string(), REV_REG_DELTA_TIMESTAMP)); }
+
+    let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+
+    let req = libindy_build_get_revoc_reg_request(&submitter_did, rev_reg_id, timestamp as i64)?;
+
+    let response = libindy_submit_request(&req)?;
+
+    libindy_parse_get_revoc_reg_response(&response)
+}
+
+/// Returns (rev_reg_id, rev_reg_delta_json, timestamp) for the accumulated changes in the
+/// interval [from, to]. A missing 'to' means now.
+pub fn get_rev_reg_delta_json(rev_reg_id: &str, from: Option<u64>, to: Option<u64>) -> VcxResult<(String, String, u64)> {
+    if settings::test_indy_mode_enabled() { return Ok((REV_REG_ID.to_string(), REV_REG_DELTA_JSON.to_string(), REV_REG_DELTA_TIMESTAMP)); }
+
+    let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+
+    let from: i64 = match from { Some(x) => x as i64, None => -1 };
+    let to: i64 = match to {
+        Some(x) => x as i64,
+        None => SystemTime::now().duration_since(UNIX_EPOCH).or(Err(VcxError::from_code(INVALID_CONFIGURATION.code_num)))?.as_secs() as i64,
+    };
+
+    let req = libindy_build_get_revoc_reg_delta_request(&submitter_did, rev_reg_id, from, to)?;
+
+    let response = libindy_submit_request(&req)?;
+
+    libindy_parse_get_revoc_reg_delta_response(&response)
+}
+
+pub fn revoke_credential(tails_file: &str, rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<Option<PaymentTxn>> {
+    let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+
+    let delta = libindy_issuer_revoke_credential(tails_file, rev_reg_id, cred_rev_id)?;
+
+    let (payment, _) = publish_rev_reg_delta(&submitter_did, rev_reg_id, &delta)?;
+
+    Ok(payment)
 }
 
 
@@ -335,6 +486,54 @@ pub mod tests {
         (schemas, cred_defs, proof_req, proof)
     }
 
+    // The credential of create_and_store_credential has zip 84000, the predicate is on zip
+    pub fn create_proof_with_predicate(p_type: &str, p_value: i32) -> VcxResult<(String, String, String, String)> {
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let (schema_id, schema_json, cred_def_id, cred_def_json, offer, req, req_meta, cred_id) = create_and_store_credential(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+
+        let proof_req = json!({
+           "nonce":"123432421212",
+           "name":"proof_req_1",
+           "version":"0.1",
+           "requested_attributes": json!({}),
+           "requested_predicates": json!({
+               "zip_3": json!({
+                   "name":"zip",
+                   "p_type": p_type,
+                   "p_value": p_value,
+                   "restrictions": [json!({ "issuer_did": did })]
+               }),
+           }),
+        }).to_string();
+
+        let requested_credentials_json = json!({
+              "self_attested_attributes":{},
+              "requested_attributes":{},
+              "requested_predicates":{
+                 "zip_3": {"cred_id": cred_id}
+              }
+        }).to_string();
+
+        let schema_json: serde_json::Value = serde_json::from_str(&schema_json).unwrap();
+        let schemas = json!({
+            schema_id: schema_json,
+        }).to_string();
+
+        let cred_def_json: serde_json::Value = serde_json::from_str(&cred_def_json).unwrap();
+        let cred_defs = json!({
+            cred_def_id: cred_def_json,
+        }).to_string();
+
+        let proof = libindy_prover_create_proof(
+            &proof_req,
+            &requested_credentials_json,
+            "main",
+            &schemas,
+            &cred_defs,
+            None)?;
+        Ok((schemas, cred_defs, proof_req, proof))
+    }
+
     pub fn create_self_attested_proof() -> (String, String) {
         let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
 
@@ -397,13 +596,75 @@ pub mod tests {
         assert!(proof_validation, true);
     }
 
+    // Every predicate type goes through the libindy the crate depends on, not only >=
+    #[cfg(feature = "pool_tests")]
+    #[test]
+    fn test_prover_verify_proof_with_predicates() {
+        init!("ledger");
+        for &(p_type, p_value) in [("<", 84001), ("<=", 84000), (">", 83999), (">=", 84000)].iter() {
+            let (schemas, cred_defs, proof_req, proof) = create_proof_with_predicate(p_type, p_value).unwrap();
+
+            assert!(libindy_verifier_verify_proof(&proof_req, &proof, &schemas, &cred_defs, "{}", "{}").unwrap(), "{} {}", p_type, p_value);
+
+            let proof_req: ::messages::proofs::proof_request::ProofRequestData = serde_json::from_str(&proof_req).unwrap();
+            let mut proof_msg = ::messages::proofs::proof_message::ProofMessage::new();
+            proof_msg.libindy_proof = proof;
+            ::proof_compliance::proof_compliance(&proof_req, &proof_msg).unwrap();
+        }
+
+        for &(p_type, p_value) in [("<", 84000), ("<=", 83999), (">", 84000), (">=", 84001)].iter() {
+            assert!(create_proof_with_predicate(p_type, p_value).is_err(), "{} {}", p_type, p_value);
+        }
+    }
+
+    // A referent with names is proved through a referent per name, the generated proof has to pass compliance
+    #[cfg(feature = "pool_tests")]
+    #[test]
+    fn test_proof_with_attribute_names_is_compliant() {
+        init!("ledger");
+        settings::set_config_value(settings::CONFIG_ATTRIBUTE_NAMES, "true");
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let (schema_id, schema_json, cred_def_id, cred_def_json, offer, req, req_meta, cred_id) = create_and_store_credential(::utils::constants::DEFAULT_SCHEMA_ATTRS);
+
+        let request = ::messages::proof_request()
+            .nonce("123432421212")
+            .proof_name("proof_req_1")
+            .proof_data_version("0.1")
+            .requested_attrs(&json!([{"names": ["address1", "zip"], "restrictions": [{"issuer_did": did}]}]).to_string())
+            .requested_predicates("[]")
+            .clone();
+        let request_data = request.proof_request_data.clone();
+        let proof_req = request_data.to_indy_json().unwrap();
+
+        let requested_attributes: Map<String, Value> = request_data.indy_referents("address1_zip").into_iter()
+            .map(|referent| (referent, json!({"cred_id": cred_id, "revealed": true})))
+            .collect();
+        let requested_credentials_json = json!({
+              "self_attested_attributes":{},
+              "requested_attributes": requested_attributes,
+              "requested_predicates":{}
+        }).to_string();
+
+        let schema_json: serde_json::Value = serde_json::from_str(&schema_json).unwrap();
+        let schemas = json!({ schema_id: schema_json }).to_string();
+        let cred_def_json: serde_json::Value = serde_json::from_str(&cred_def_json).unwrap();
+        let cred_defs = json!({ cred_def_id: cred_def_json }).to_string();
+
+        let proof = libindy_prover_create_proof(&proof_req, &requested_credentials_json, "main", &schemas, &cred_defs, None).unwrap();
+        assert!(libindy_verifier_verify_proof(&proof_req, &proof, &schemas, &cred_defs, "{}", "{}").unwrap());
+
+        let mut proof_msg = ::messages::proofs::proof_message::ProofMessage::new();
+        proof_msg.libindy_proof = proof;
+        ::proof_compliance::proof_compliance(&request_data, &proof_msg).unwrap();
+    }
+
     #[cfg(feature = "pool_tests")]
     #[test]
     fn tests_libindy_prover_get_credentials() {
         init!("ledger");
         let proof_req = "{";
         let result = libindy_prover_get_credentials_for_proof_req(&proof_req);
-        assert_eq!(result.err(), Some(INVALID_PROOF_REQUEST.code_num));
+        assert_eq!(result.err(), Some(VcxError::from_code(INVALID_PROOF_REQUEST.code_num)));
         let proof_req = json!({
            "nonce":"123432421212",
            "name":"proof_req_1",
@@ -423,7 +684,7 @@ pub mod tests {
         let wallet_handle = get_wallet_handle();
         let proof_req_str:String = serde_json::to_string(&proof_req).unwrap();
         assert!(result.is_ok());
-        assert_eq!(result_malformed_json.err(), Some(INVALID_ATTRIBUTES_STRUCTURE.code_num));
+        assert_eq!(result_malformed_json.err(), Some(VcxError::from_code(INVALID_ATTRIBUTES_STRUCTURE.code_num)));
     }
 
 }
@@ -0,0 +1,25 @@
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
+use indy::blob_storage::BlobStorage;
+use error::VcxResult;
+
+static BLOB_STORAGE_TYPE: &str = "default";
+
+fn tails_config(tails_path: &str) -> String {
+    json!({
+        "base_dir": tails_path,
+        "uri_pattern": ""
+    }).to_string()
+}
+
+/// Opens a writer for the tails file of a new revocation registry.
+/// tails_path is the directory where libindy will create the file (named after its hash).
+pub fn open_tails_writer(tails_path: &str) -> VcxResult<i32> {
+    BlobStorage::open_writer(BLOB_STORAGE_TYPE, &tails_config(tails_path))
+        .map_err(map_rust_indy_sdk_error)
+}
+
+/// Opens a reader for tails files stored in tails_path.
+pub fn open_tails_reader(tails_path: &str) -> VcxResult<i32> {
+    BlobStorage::open_reader(BLOB_STORAGE_TYPE, &tails_config(tails_path))
+        .map_err(map_rust_indy_sdk_error)
+}
@@ -2,41 +2,42 @@
 extern crate libc;
 
 use utils::libindy::{ mock_libindy_rc};
-use utils::libindy::error_codes::map_rust_indy_sdk_error_code;
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
 use settings;
 use indy::crypto::Crypto;
+use error::{ VcxError, VcxResult };
 
-pub fn prep_msg(wallet_handle: i32, sender_vk: &str, recipient_vk: &str, msg: &[u8]) -> Result<Vec<u8>, u32> {
+pub fn prep_msg(wallet_handle: i32, sender_vk: &str, recipient_vk: &str, msg: &[u8]) -> VcxResult<Vec<u8>> {
     if settings::test_indy_mode_enabled() {
         let rc = mock_libindy_rc();
-        if rc != 0 { return Err(rc) };
+        if rc != 0 { return Err(VcxError::from(rc)) };
         return Ok(Vec::from(msg).to_owned());
     }
 
     Crypto::auth_crypt(wallet_handle, sender_vk, recipient_vk, msg)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn prep_anonymous_msg(recipient_vk: &str, msg: &[u8]) -> Result<Vec<u8>, u32> {
+pub fn prep_anonymous_msg(recipient_vk: &str, msg: &[u8]) -> VcxResult<Vec<u8>> {
     if settings::test_indy_mode_enabled() {return Ok(Vec::from(msg).to_owned())}
 
-    Crypto::anon_crypt(recipient_vk, msg).map_err(map_rust_indy_sdk_error_code)
+    Crypto::anon_crypt(recipient_vk, msg).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn parse_msg(recipient_vk: &str, msg: &[u8]) -> Result<(String, Vec<u8>), u32> {
+pub fn parse_msg(recipient_vk: &str, msg: &[u8]) -> VcxResult<(String, Vec<u8>)> {
     if settings::test_indy_mode_enabled() { return Ok((::utils::constants::VERKEY.to_string(), Vec::from(msg).to_owned())) }
 
-    Crypto::auth_decrypt(::utils::libindy::wallet::get_wallet_handle(), recipient_vk, msg).map_err(map_rust_indy_sdk_error_code)
+    Crypto::auth_decrypt(::utils::libindy::wallet::get_wallet_handle(), recipient_vk, msg).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn parse_anonymous_msg(wallet_handle: i32, recipient_vk: &str, msg: &[u8]) -> Result<Vec<u8>, u32> {
+pub fn parse_anonymous_msg(wallet_handle: i32, recipient_vk: &str, msg: &[u8]) -> VcxResult<Vec<u8>> {
     if settings::test_indy_mode_enabled() { return Ok(Vec::from(msg).to_owned()) }
 
-    Crypto::anon_decrypt(wallet_handle, recipient_vk, msg).map_err(map_rust_indy_sdk_error_code)
+    Crypto::anon_decrypt(wallet_handle, recipient_vk, msg).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn sign(wallet_handle: i32, my_vk: &str, msg: &[u8]) -> Result<Vec<u8>, u32> {
+pub fn sign(wallet_handle: i32, my_vk: &str, msg: &[u8]) -> VcxResult<Vec<u8>> {
     if settings::test_indy_mode_enabled() {return Ok(Vec::from(msg).to_owned())}
 
-    Crypto::sign(wallet_handle, my_vk, msg).map_err(map_rust_indy_sdk_error_code)
+    Crypto::sign(wallet_handle, my_vk, msg).map_err(map_rust_indy_sdk_error)
 }
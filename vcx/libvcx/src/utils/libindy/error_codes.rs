@@ -2,6 +2,7 @@ extern crate num_traits;
 
 use indy::ErrorCode;
 use utils::error;
+use error::VcxError;
 use std::ffi::NulError;
 use self::num_traits::int::PrimInt;
 
@@ -13,6 +14,12 @@ pub fn map_indy_error<T, C: PrimInt>(rtn: T, error_code: C) -> Result<T, u32> {
     Err(map_indy_error_code(error_code))
 }
 
+/// The error for a failed rust-indy-sdk call, the libindy error code is kept in the message.
+pub fn map_rust_indy_sdk_error(error_code: ErrorCode) -> VcxError {
+    let code = map_rust_indy_sdk_error_code(error_code);
+    VcxError::from_msg(code, format!("{} (libindy error {:?})", error::error_message(&code), error_code))
+}
+
 // Todo - this will replace map_indy_error_code once we stop using our own indy cbs and move everything to rust-indy-sdk
 // Todo - rename once it replaces map_indy_error
 pub fn map_rust_indy_sdk_error_code(error_code: ErrorCode) -> u32 {
@@ -97,4 +104,11 @@ pub mod tests {
         assert_ne!(map_rust_indy_sdk_error_code(err112), error::INVALID_LIBINDY_PARAM.code_num);
 
     }
+
+    #[test]
+    fn test_indy_error_keeps_libindy_code() {
+        let err = map_rust_indy_sdk_error(ErrorCode::WalletItemNotFound);
+        assert_eq!(err.code(), error::WALLET_RECORD_NOT_FOUND.code_num);
+        assert!(err.message().contains("WalletItemNotFound"));
+    }
 }
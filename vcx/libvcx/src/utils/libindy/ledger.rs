@@ -1,4 +1,5 @@
 extern crate libc;
+extern crate serde_json;
 
 use settings;
 use utils::libindy::{
@@ -7,62 +8,133 @@ use utils::libindy::{
 };
 use utils::error;
 use indy::ledger::Ledger;
-use utils::libindy::error_codes::map_rust_indy_sdk_error_code;
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
 use utils::timeout::TimeoutUtils;
+use error::{ VcxError, VcxResult };
 
-pub fn multisign_request(did: &str, request: &str) -> Result<String, u32> {
+pub fn multisign_request(did: &str, request: &str) -> VcxResult<String> {
    Ledger::multi_sign_request(get_wallet_handle(), did, request)
-       .map_err(map_rust_indy_sdk_error_code)
+       .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_sign_request(did: &str, request: &str) -> Result<String,u32> {
+/// Names the endorser who is going to write the request to the ledger and adds the signature of the
+/// submitter. The endorser adds its own signature with endorse_request.
+pub fn libindy_prepare_request_for_endorser(submitter_did: &str, request: &str, endorser_did: &str) -> VcxResult<String> {
+    let mut request: serde_json::Value = serde_json::from_str(request).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+    request["endorser"] = json!(endorser_did);
+
+    if settings::test_indy_mode_enabled() { return Ok(request.to_string()); }
+    multisign_request(submitter_did, &request.to_string())
+}
+
+/// Adds the signature of the institution to a request that names it as the endorser.
+pub fn endorse_request(request: &str) -> VcxResult<String> {
+    let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(VcxError::from_code(error::INVALID_CONFIGURATION.code_num)))?;
+
+    let parsed: serde_json::Value = serde_json::from_str(request).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
+    if parsed["endorser"].as_str() != Some(did.as_str()) {
+        warn!("request does not name {} as its endorser", did);
+        return Err(VcxError::from_code(error::INVALID_OPTION.code_num));
+    }
+
+    if settings::test_indy_mode_enabled() { return Ok(request.to_string()); }
+    multisign_request(&did, request)
+}
+
+pub fn libindy_sign_request(did: &str, request: &str) -> VcxResult<String> {
     Ledger::sign_request(get_wallet_handle(), did, request)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_sign_and_submit_request(issuer_did: &str, request_json: &str) -> Result<String, u32> {
+pub fn libindy_sign_and_submit_request(issuer_did: &str, request_json: &str) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() { return Ok(r#"{"rc":"success"}"#.to_string()); }
-    let pool_handle = get_pool_handle().or(Err(error::NO_POOL_OPEN.code_num))?;
+    let pool_handle = get_pool_handle().or(Err(VcxError::from_code(error::NO_POOL_OPEN.code_num)))?;
     let wallet_handle = get_wallet_handle();
 
     Ledger::sign_and_submit_request(pool_handle, wallet_handle, issuer_did, request_json)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_submit_request(request_json: &str) -> Result<String, u32> {
-    let pool_handle = get_pool_handle().or(Err(error::NO_POOL_OPEN.code_num))?;
-    Ledger::submit_request_timeout(pool_handle, request_json, TimeoutUtils::long_timeout()).map_err(map_rust_indy_sdk_error_code)
+pub fn libindy_submit_request(request_json: &str) -> VcxResult<String> {
+    let pool_handle = get_pool_handle().or(Err(VcxError::from_code(error::NO_POOL_OPEN.code_num)))?;
+    Ledger::submit_request_timeout(pool_handle, request_json, TimeoutUtils::long_timeout()).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_build_get_txn_request(submitter_did: &str, sequence_num: i32) -> Result<String, u32> {
+pub fn libindy_build_get_txn_request(submitter_did: &str, sequence_num: i32) -> VcxResult<String> {
     Ledger::build_get_txn_request(Some(submitter_did), None, sequence_num)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_build_schema_request(submitter_did: &str, data: &str) -> Result<String, u32> {
+pub fn libindy_build_schema_request(submitter_did: &str, data: &str) -> VcxResult<String> {
     Ledger::build_schema_request(submitter_did, data)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_build_get_schema_request(submitter_did: &str, schema_id: &str) -> Result<String, u32> {
+pub fn libindy_build_get_schema_request(submitter_did: &str, schema_id: &str) -> VcxResult<String> {
     Ledger::build_get_schema_request(Some(submitter_did), schema_id)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_parse_get_schema_response(get_schema_response: &str) -> Result<(String, String), u32> {
-    Ledger::parse_get_schema_response(get_schema_response).map_err(map_rust_indy_sdk_error_code)
+pub fn libindy_parse_get_schema_response(get_schema_response: &str) -> VcxResult<(String, String)> {
+    Ledger::parse_get_schema_response(get_schema_response).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_parse_get_cred_def_response(get_cred_def_response: &str) -> Result<(String, String), u32> {
-    Ledger::parse_get_cred_def_response(get_cred_def_response).map_err(map_rust_indy_sdk_error_code)
+pub fn libindy_parse_get_cred_def_response(get_cred_def_response: &str) -> VcxResult<(String, String)> {
+    Ledger::parse_get_cred_def_response(get_cred_def_response).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn libindy_build_get_credential_def_txn(cred_def_id: &str)  -> Result<String, u32>{
+pub fn libindy_build_get_credential_def_txn(cred_def_id: &str)  -> VcxResult<String>{
     let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
-    Ledger::build_get_cred_def_request(Some(&submitter_did), cred_def_id).map_err(map_rust_indy_sdk_error_code)
+    Ledger::build_get_cred_def_request(Some(&submitter_did), cred_def_id).map_err(map_rust_indy_sdk_error)
 }
 
 pub fn libindy_build_create_credential_def_txn(submitter_did: &str,
-                                               credential_def_json: &str)  -> Result<String, u32>{
-    Ledger::build_cred_def_request(submitter_did, credential_def_json).map_err(map_rust_indy_sdk_error_code)
+                                               credential_def_json: &str)  -> VcxResult<String>{
+    Ledger::build_cred_def_request(submitter_did, credential_def_json).map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_build_revoc_reg_def_request(submitter_did: &str,
+                                           rev_reg_def_json: &str) -> VcxResult<String> {
+    Ledger::build_revoc_reg_def_request(submitter_did, rev_reg_def_json).map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_build_revoc_reg_entry_request(submitter_did: &str,
+                                             rev_reg_id: &str,
+                                             rev_def_type: &str,
+                                             value: &str) -> VcxResult<String> {
+    Ledger::build_revoc_reg_entry_request(submitter_did, rev_reg_id, rev_def_type, value)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_build_get_revoc_reg_def_request(submitter_did: &str, rev_reg_id: &str) -> VcxResult<String> {
+    Ledger::build_get_revoc_reg_def_request(Some(submitter_did), rev_reg_id)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_parse_get_revoc_reg_def_response(rev_reg_def_json: &str) -> VcxResult<(String, String)> {
+    Ledger::parse_get_revoc_reg_def_response(rev_reg_def_json)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_build_get_revoc_reg_request(submitter_did: &str, rev_reg_id: &str, timestamp: i64) -> VcxResult<String> {
+    Ledger::build_get_revoc_reg_request(Some(submitter_did), rev_reg_id, timestamp)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_parse_get_revoc_reg_response(get_rev_reg_response: &str) -> VcxResult<(String, String, u64)> {
+    Ledger::parse_get_revoc_reg_response(get_rev_reg_response)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_build_get_revoc_reg_delta_request(submitter_did: &str,
+                                                 rev_reg_id: &str,
+                                                 from: i64,
+                                                 to: i64) -> VcxResult<String> {
+    Ledger::build_get_revoc_reg_delta_request(Some(submitter_did), rev_reg_id, from, to)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+pub fn libindy_parse_get_revoc_reg_delta_response(get_rev_reg_delta_response: &str) -> VcxResult<(String, String, u64)> {
+    Ledger::parse_get_revoc_reg_delta_response(get_rev_reg_delta_response)
+        .map_err(map_rust_indy_sdk_error)
 }
@@ -9,6 +9,7 @@ pub mod return_types_u32;
 pub mod pool;
 pub mod crypto;
 pub mod payments;
+pub mod blob_storage;
 
 mod error_codes;
 
@@ -17,6 +18,7 @@ extern crate libc;
 use settings;
 use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 use std::sync::Mutex;
+use error::VcxResult;
 
 lazy_static!{
     static ref NEXT_LIBINDY_RC: Mutex<Vec<i32>> = Mutex::new(vec![]);
@@ -36,7 +38,7 @@ fn next_u32_command_handle() -> u32 {
     (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as u32
 }
 
-pub fn init_pool() -> Result<(), u32>  {
+pub fn init_pool() -> VcxResult<()>  {
     if settings::test_indy_mode_enabled() {return Ok (()); }
 
     let pool_name = settings::get_config_value(settings::CONFIG_POOL_NAME)
@@ -3,12 +3,11 @@ extern crate serde_json;
 
 use utils::libindy::wallet::get_wallet_handle;
 use utils::constants::{ SUBMIT_SCHEMA_RESPONSE, TRANSFER_TXN_TYPE };
-use utils::libindy::error_codes::map_rust_indy_sdk_error_code;
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
 #[allow(unused_imports)]
 use utils::libindy::ledger::{libindy_submit_request, libindy_sign_and_submit_request, libindy_sign_request};
 use utils::error;
-use error::payment::PaymentError;
-use error::ToErrorCode;
+use error::{ VcxError, VcxResult };
 
 use indy::payments::Payment;
 use std::fmt;
@@ -88,7 +87,7 @@ fn pay_init() -> i32 { unsafe { sovtoken_init() } }
 #[cfg(feature = "sovtoken")]
 static PAYMENT_METHOD_NAME: &str = "sov";
 
-pub fn init_payments() -> Result<(), u32> {
+pub fn init_payments() -> VcxResult<()> {
     let mut rc = 0;
 
     PAYMENT_INIT.call_once(|| {
@@ -96,7 +95,7 @@ pub fn init_payments() -> Result<(), u32> {
     });
 
     if rc != 0 {
-        Err(rc as u32)
+        Err(VcxError::from_msg(rc as u32, format!("could not initialize payment plugin: {}", rc)))
     } else {
         Ok(())
     }
@@ -111,12 +110,12 @@ pub struct PaymentTxn {
 }
 
 impl PaymentTxn {
-    pub fn from_parts(inputs: &str, outputs: &str, amount: u64, credit: bool) -> Result<PaymentTxn, u32> {
+    pub fn from_parts(inputs: &str, outputs: &str, amount: u64, credit: bool) -> VcxResult<PaymentTxn> {
         let inputs: Vec<String> = serde_json::from_str(&inputs)
-            .map_err(|err| {error::INVALID_JSON.code_num})?;
+            .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid payment json: {}", err)))?;
 
         let outputs: Vec<Output> = serde_json::from_str(&outputs)
-            .map_err(|err| {error::INVALID_JSON.code_num})?;
+            .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("invalid payment json: {}", err)))?;
 
         Ok(PaymentTxn {
             amount,
@@ -127,7 +126,7 @@ impl PaymentTxn {
     }
 }
 
-pub fn create_address(seed: Option<String>) -> Result<String, u32> {
+pub fn create_address(seed: Option<String>) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() { return Ok(r#"pay:null:J81AxU9hVHYFtJc"#.to_string()); }
 
     let config = match seed {
@@ -136,10 +135,10 @@ pub fn create_address(seed: Option<String>) -> Result<String, u32> {
     };
 
     Payment::create_payment_address(get_wallet_handle() as i32, PAYMENT_METHOD_NAME, &config)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn get_address_info(address: &str) -> Result<AddressInfo, u32> {
+pub fn get_address_info(address: &str) -> VcxResult<AddressInfo> {
     if settings::test_indy_mode_enabled() {
         let utxo: Vec<UTXO> = serde_json::from_str(r#"[{"source":"pov:null:1","paymentAddress":"pay:null:zR3GN9lfbCVtHjp","amount":1,"extra":"yqeiv5SisTeUGkw"},{"source":"pov:null:2","paymentAddress":"pay:null:zR3GN9lfbCVtHjp","amount":2,"extra":"Lu1pdm7BuAN2WNi"}]"#).unwrap();
         return Ok(AddressInfo { address: address.to_string(), balance: _address_balance(&utxo), utxo})
@@ -148,32 +147,32 @@ pub fn get_address_info(address: &str) -> Result<AddressInfo, u32> {
     let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
 
     let (txn, _) = Payment::build_get_payment_sources_request(get_wallet_handle() as i32, Some(&did), address)
-        .map_err(map_rust_indy_sdk_error_code)?;
+        .map_err(map_rust_indy_sdk_error)?;
 
     let response = libindy_sign_and_submit_request(&did, &txn)?;
 
     let response = Payment::parse_get_payment_sources_response(PAYMENT_METHOD_NAME, &response)
-        .map_err(map_rust_indy_sdk_error_code)?;
+        .map_err(map_rust_indy_sdk_error)?;
 
     trace!("indy_parse_get_utxo_response() --> {}", response);
-    let utxo: Vec<UTXO> = serde_json::from_str(&response).or(Err(error::INVALID_JSON.code_num))?;
+    let utxo: Vec<UTXO> = serde_json::from_str(&response).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
 
     Ok(AddressInfo { address: address.to_string(), balance: _address_balance(&utxo), utxo })
 }
 
-pub fn list_addresses() -> Result<Vec<String>, u32> {
+pub fn list_addresses() -> VcxResult<Vec<String>> {
     if settings::test_indy_mode_enabled() {
         return Ok(serde_json::from_str(r#"["pay:null:9UFgyjuJxi1i1HD","pay:null:zR3GN9lfbCVtHjp"]"#).unwrap());
     }
 
     let addresses = Payment::list_payment_addresses(get_wallet_handle() as i32)
-        .map_err(map_rust_indy_sdk_error_code)?;
+        .map_err(map_rust_indy_sdk_error)?;
 
     trace!("--> {}", addresses);
-    Ok(serde_json::from_str(&addresses).or(Err(error::INVALID_JSON.code_num))?)
+    Ok(serde_json::from_str(&addresses).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?)
 }
 
-pub fn get_wallet_token_info() -> Result<WalletInfo, u32> {
+pub fn get_wallet_token_info() -> VcxResult<WalletInfo> {
     let addresses = list_addresses()?;
 
     let mut balance = 0;
@@ -190,35 +189,35 @@ pub fn get_wallet_token_info() -> Result<WalletInfo, u32> {
     Ok(WalletInfo { balance, balance_str: format!("{}", balance), addresses: wallet_info })
 }
 
-pub fn get_ledger_fees() -> Result<String, u32> {
+pub fn get_ledger_fees() -> VcxResult<String> {
     if settings::test_indy_mode_enabled() { return Ok(DEFAULT_FEES.to_string()); }
 
-    let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(error::INVALID_CONFIGURATION.code_num))?;
+    let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(VcxError::from_code(error::INVALID_CONFIGURATION.code_num)))?;
 
     let response = match Payment::build_get_txn_fees_req(get_wallet_handle() as i32, Some(&did), PAYMENT_METHOD_NAME) {
         Ok(txn) => libindy_sign_and_submit_request(&did, &txn)?,
-        Err(x) => return Err(map_rust_indy_sdk_error_code(x)),
+        Err(x) => return Err(map_rust_indy_sdk_error(x)),
     };
 
     let res = Payment::parse_get_txn_fees_response(PAYMENT_METHOD_NAME, &response)
-        .map_err(map_rust_indy_sdk_error_code);
+        .map_err(map_rust_indy_sdk_error);
     res
 }
 
-pub fn pay_for_txn(req: &str, txn_type: &str) -> Result<(Option<PaymentTxn>, String), u32> {
+pub fn pay_for_txn(req: &str, txn_type: &str) -> VcxResult<(Option<PaymentTxn>, String)> {
     debug!("pay_for_txn(req: {}, txn_type: {})", req, txn_type);
     if settings::test_indy_mode_enabled() { return Ok((Some(PaymentTxn::from_parts(r#"["pay:null:9UFgyjuJxi1i1HD"]"#,r#"[{"amount":4,"extra":null,"recipient":"pay:null:xkIsxem0YNtHrRO"}]"#,1, false).unwrap()), SUBMIT_SCHEMA_RESPONSE.to_string())); }
 
     let txn_price = get_txn_price(txn_type)?;
 
     if txn_price == 0 {
-        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(error::INVALID_CONFIGURATION.code_num))?;
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(VcxError::from_code(error::INVALID_CONFIGURATION.code_num)))?;
         let txn_response = libindy_sign_and_submit_request(&did, req)?;
         Ok((None, txn_response))
     } else {
-        let (refund, inputs, refund_address) = inputs(txn_price).map_err(|e| e.to_error_code())?;
+        let (refund, inputs, refund_address) = inputs(txn_price)?;
 
-        let output = outputs(refund, &refund_address, None, None).map_err(|e| e.to_error_code())?;
+        let output = outputs(refund, &refund_address, None, None)?;
 
         let (fee_response, txn_response) = _submit_fees_request(req, &inputs, &output)?;
 
@@ -227,8 +226,8 @@ pub fn pay_for_txn(req: &str, txn_type: &str) -> Result<(Option<PaymentTxn>, Str
     }
 }
 
-fn _submit_fees_request(req: &str, inputs: &str, outputs: &str) -> Result<(String, String), u32> {
-    let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(error::INVALID_CONFIGURATION.code_num))?;
+fn _submit_fees_request(req: &str, inputs: &str, outputs: &str) -> VcxResult<(String, String)> {
+    let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(VcxError::from_code(error::INVALID_CONFIGURATION.code_num)))?;
 
     let req = libindy_sign_request(&did, req)?;
 
@@ -241,45 +240,45 @@ fn _submit_fees_request(req: &str, inputs: &str, outputs: &str) -> Result<(Strin
         Ok((req, payment_method)) => {
             (libindy_submit_request(&req)?, payment_method)
         },
-        Err(x) => return Err(map_rust_indy_sdk_error_code(x)),
+        Err(x) => return Err(map_rust_indy_sdk_error(x)),
     };
 
     let parsed_response = match Payment::parse_response_with_fees(&payment_method, &response) {
         Ok(x) => x,
-        Err(x) => return Err(error::INVALID_LEDGER_RESPONSE.code_num),
+        Err(x) => return Err(VcxError::from_code(error::INVALID_LEDGER_RESPONSE.code_num)),
     };
 
     Ok((parsed_response, response))
 }
 
-pub fn pay_a_payee(price: u64, address: &str) -> Result<(PaymentTxn, String), PaymentError> {
+pub fn pay_a_payee(price: u64, address: &str) -> VcxResult<(PaymentTxn, String)> {
     info!("sending {} tokens to address {}", price, address);
 
-    let ledger_cost = get_txn_price(TRANSFER_TXN_TYPE).map_err(|e| PaymentError::CommonError(e))?;
+    let ledger_cost = get_txn_price(TRANSFER_TXN_TYPE)?;
     let (remainder, input, refund_address) = inputs(price + ledger_cost)?;
     let output = outputs(remainder, &refund_address, Some(address.to_string()), Some(price))?;
 
-    let payment = PaymentTxn::from_parts(&input, &output, price, false).map_err(|e|PaymentError::CommonError(e))?;
-    let my_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(PaymentError::CommonError(error::INVALID_CONFIGURATION.code_num)))?;
+    let payment = PaymentTxn::from_parts(&input, &output, price, false)?;
+    let my_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).or(Err(VcxError::from(error::INVALID_CONFIGURATION.code_num)))?;
 
     if settings::test_indy_mode_enabled() { return Ok((PaymentTxn::from_parts(r#"["pay:null:9UFgyjuJxi1i1HD"]"#,r#"[{"amount":4,"extra":null,"recipient":"pay:null:xkIsxem0YNtHrRO"}]"#,1, false).unwrap(), SUBMIT_SCHEMA_RESPONSE.to_string())); }
 
     match Payment::build_payment_req(get_wallet_handle(), Some(&my_did), &input, &output, None) {
         Ok((request, payment_method)) => {
-            let result = libindy_submit_request( &request).map_err(|ec| PaymentError::CommonError(ec))?;
+            let result = libindy_submit_request( &request)?;
             Ok((payment, result))
         },
         Err(ec) => {
             error!("error: {:?}", ec);
-            Err(PaymentError::CommonError(ec as u32))
+            Err(VcxError::from(ec as u32))
         },
     }
 }
 
-fn get_txn_price(txn_type: &str) -> Result<u64, u32> {
+fn get_txn_price(txn_type: &str) -> VcxResult<u64> {
     let ledger_fees = get_ledger_fees()?;
 
-    let fees: HashMap<String, u64> = serde_json::from_str(&ledger_fees) .or(Err(error::INVALID_JSON.code_num))?;
+    let fees: HashMap<String, u64> = serde_json::from_str(&ledger_fees) .or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
 
     match fees.get(txn_type) {
         Some(x) => Ok(*x),
@@ -291,15 +290,15 @@ fn _address_balance(address: &Vec<UTXO>) -> u64 {
     address.iter().fold(0, |balance, utxo| balance + utxo.amount)
 }
 
-pub fn inputs(cost: u64) -> Result<(u64, String, String), PaymentError> {
+pub fn inputs(cost: u64) -> VcxResult<(u64, String, String)> {
     let mut inputs: Vec<String> = Vec::new();
     let mut balance = 0;
-    let wallet_info: WalletInfo = get_wallet_token_info().map_err(|ec| PaymentError::CommonError(ec))?;
+    let wallet_info: WalletInfo = get_wallet_token_info()?;
     let mut refund_address = String::new();
 
     if wallet_info.balance < cost {
         warn!("not enough tokens in wallet to pay: balance: {}, cost: {}", wallet_info.balance, cost);
-        return Err(PaymentError::InsufficientFunds());
+        return Err(VcxError::from_code(error::INSUFFICIENT_TOKEN_AMOUNT.code_num));
     }
 
     // Todo: explore 'smarter' ways of selecting utxos ie bitcoin algorithms etc
@@ -307,19 +306,19 @@ pub fn inputs(cost: u64) -> Result<(u64, String, String), PaymentError> {
         refund_address = address.address.clone();
         'inner: for utxo in address.utxo.iter() {
             if balance < cost {
-                inputs.push(utxo.source.clone().ok_or(PaymentError::InsufficientFunds())?.to_string());
+                inputs.push(utxo.source.clone().ok_or(VcxError::from_code(error::INSUFFICIENT_TOKEN_AMOUNT.code_num))?.to_string());
                 balance += utxo.amount;
             } else { break 'outer }
         }
     }
 
     let remainder = balance - cost;
-    let inputs = serde_json::to_string(&inputs).or(Err(PaymentError::InvalidWalletJson()))?;
+    let inputs = serde_json::to_string(&inputs).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?;
 
     Ok((remainder, inputs, refund_address))
 }
 
-pub fn outputs(remainder: u64, refund_address: &str, payee_address: Option<String>, payee_amount: Option<u64>) -> Result<String, PaymentError> {
+pub fn outputs(remainder: u64, refund_address: &str, payee_address: Option<String>, payee_amount: Option<u64>) -> VcxResult<String> {
     // In the future we might provide a way for users to specify multiple output address for their remainder tokens
     // As of now, we only handle one output address which we create
 
@@ -335,11 +334,11 @@ pub fn outputs(remainder: u64, refund_address: &str, payee_address: Option<Strin
         }));
     }
 
-    Ok(serde_json::to_string(&outputs).or(Err(PaymentError::InvalidWalletJson()))?)
+    Ok(serde_json::to_string(&outputs).or(Err(VcxError::from_code(error::INVALID_JSON.code_num)))?)
 }
 
 // This is used for testing purposes only!!!
-pub fn mint_tokens_and_set_fees(number_of_addresses: Option<u32>, tokens_per_address: Option<u64>, fees: Option<String>, seed: Option<String>) -> Result<(), u32> {
+pub fn mint_tokens_and_set_fees(number_of_addresses: Option<u32>, tokens_per_address: Option<u64>, fees: Option<String>, seed: Option<String>) -> VcxResult<()> {
     let did_1 = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
 
     let fees = if fees.is_some() {
@@ -382,7 +381,7 @@ pub fn mint_tokens_and_set_fees(number_of_addresses: Option<u32>, tokens_per_add
 
     if fees.is_some() {
         let txn = Payment::build_set_txn_fees_req(get_wallet_handle() as i32, Some(&did_1), PAYMENT_METHOD_NAME, fees.unwrap())
-            .map_err(map_rust_indy_sdk_error_code)?;
+            .map_err(map_rust_indy_sdk_error)?;
 
         let sign1 = ::utils::libindy::ledger::multisign_request(&did_1, &txn).unwrap();
         let sign2 = ::utils::libindy::ledger::multisign_request(&did_2, &sign1).unwrap();
@@ -395,13 +394,13 @@ pub fn mint_tokens_and_set_fees(number_of_addresses: Option<u32>, tokens_per_add
     Ok(())
 }
  
-fn add_new_trustee_did() -> Result<(String, String), u32> {
+fn add_new_trustee_did() -> VcxResult<(String, String)> {
     use indy::ledger::Ledger;
 
     let institution_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
 
     let (did, verkey) = ::utils::libindy::signus::create_and_store_my_did(None).unwrap();
-    let req_nym = Ledger::build_nym_request(&institution_did, &did, Some(&verkey), None, Some("TRUSTEE")).map_err(map_rust_indy_sdk_error_code)?;
+    let req_nym = Ledger::build_nym_request(&institution_did, &did, Some(&verkey), None, Some("TRUSTEE")).map_err(map_rust_indy_sdk_error)?;
     ::utils::libindy::ledger::libindy_sign_and_submit_request(&institution_did, &req_nym)?;
     Ok((did, verkey))
 }
@@ -498,7 +497,7 @@ pub mod tests {
         assert_eq!(inputs(1).unwrap(), (0, r#"["pov:null:1"]"#.to_string(), "pay:null:9UFgyjuJxi1i1HD".to_string()));
 
         // Err - request more than wallet contains
-        assert_eq!(inputs(7).err(), Some(PaymentError::InsufficientFunds()));
+        assert_eq!(inputs(7).err(), Some(VcxError::from_code(error::INSUFFICIENT_TOKEN_AMOUNT.code_num)));
     }
 
     #[test]
@@ -599,7 +598,7 @@ pub mod tests {
 
         let price = get_my_balance() + 5;
         let result_from_paying = pay_a_payee(price, PAYMENT_ADDRESS);
-        assert_eq!(result_from_paying.err(), Some(PaymentError::InsufficientFunds()));
+        assert_eq!(result_from_paying.err(), Some(VcxError::from_code(error::INSUFFICIENT_TOKEN_AMOUNT.code_num)));
         assert_eq!(get_my_balance(), 5);
     }
 
@@ -628,7 +627,7 @@ pub mod tests {
         let price = get_my_balance() - not_enough_for_ledger_fee;
         assert!(price > 0);
         let result_from_paying = pay_a_payee(price, PAYMENT_ADDRESS);
-        assert_eq!(result_from_paying.err(), Some(PaymentError::CommonError(error::INSUFFICIENT_TOKEN_AMOUNT.code_num)));
+        assert_eq!(result_from_paying.err(), Some(VcxError::from(error::INSUFFICIENT_TOKEN_AMOUNT.code_num)));
     }
 
     #[cfg(feature = "pool_tests")]
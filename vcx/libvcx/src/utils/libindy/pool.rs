@@ -2,18 +2,25 @@ extern crate libc;
 
 use utils::{ error, timeout::TimeoutUtils };
 use std::sync::RwLock;
+use std::collections::HashMap;
+use context;
 use settings;
 use indy::pool::Pool;
 use indy::ErrorCode;
-use utils::libindy::error_codes::map_rust_indy_sdk_error_code;
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
+use error::{ VcxError, VcxResult };
 
 lazy_static! {
-    static ref POOL_HANDLE: RwLock<Option<i32>> = RwLock::new(None);
+    // Open pool of every context
+    static ref POOL_HANDLES: RwLock<HashMap<u32, i32>> = RwLock::new(HashMap::new());
 }
 
 pub fn change_pool_handle(handle: Option<i32>){
-    let mut h = POOL_HANDLE.write().unwrap();
-    *h = handle;
+    let mut handles = POOL_HANDLES.write().unwrap();
+    match handle {
+        Some(handle) => handles.insert(context::current(), handle),
+        None => handles.remove(&context::current()),
+    };
 }
 
 pub fn set_protocol_version() -> u32 {
@@ -23,49 +30,49 @@ pub fn set_protocol_version() -> u32 {
     }
 }
 
-pub fn create_pool_ledger_config(pool_name: &str, path: &str) -> Result<(), u32> {
+pub fn create_pool_ledger_config(pool_name: &str, path: &str) -> VcxResult<()> {
     let pool_config = format!(r#"{{"genesis_txn":"{}"}}"#, path);
 
     match Pool::create_ledger_config(pool_name, Some(&pool_config)) {
         Ok(_) => Ok(()),
         Err(x) => if x != ErrorCode::PoolLedgerConfigAlreadyExistsError {
-            Err(error::UNKNOWN_LIBINDY_ERROR.code_num)
+            Err(VcxError::from_msg(error::UNKNOWN_LIBINDY_ERROR.code_num, format!("could not create pool ledger config: {:?}", x)))
         } else {
             Ok(())
         }
     }
 }
 
-pub fn open_pool_ledger(pool_name: &str, config: Option<&str>) -> Result<u32, u32> {
+pub fn open_pool_ledger(pool_name: &str, config: Option<&str>) -> VcxResult<u32> {
 
     set_protocol_version();
 
-    match Pool::open_ledger_timeout(pool_name, config, TimeoutUtils::medium_timeout()).map_err(map_rust_indy_sdk_error_code) {
+    match Pool::open_ledger_timeout(pool_name, config, TimeoutUtils::medium_timeout()).map_err(map_rust_indy_sdk_error) {
         Ok(x) => {
             change_pool_handle(Some(x));
             Ok(x as u32)
         },
-        Err(_) => Err(error::UNKNOWN_LIBINDY_ERROR.code_num),
+        Err(e) => Err(e.context(error::UNKNOWN_LIBINDY_ERROR.code_num, format!("could not open pool ledger {}", pool_name))),
     }
 }
 
-pub fn close() -> Result<(), u32> {
+pub fn close() -> VcxResult<()> {
     let handle = get_pool_handle()?;
     change_pool_handle(None);
-    Pool::close_timeout(handle, TimeoutUtils::medium_timeout()).map_err(map_rust_indy_sdk_error_code)
+    Pool::close_timeout(handle, TimeoutUtils::medium_timeout()).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn delete(pool_name: &str) -> Result<(), u32> {
+pub fn delete(pool_name: &str) -> VcxResult<()> {
     if settings::test_indy_mode_enabled() {
         change_pool_handle(None);
         return Ok(())
     }
 
-    Pool::delete(pool_name).map_err(map_rust_indy_sdk_error_code)
+    Pool::delete(pool_name).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn get_pool_handle() -> Result<i32, u32> {
-    Ok(POOL_HANDLE.read().or(Err(error::NO_POOL_OPEN.code_num))?.ok_or(error::NO_POOL_OPEN.code_num)?)
+pub fn get_pool_handle() -> VcxResult<i32> {
+    Ok(*POOL_HANDLES.read().or(Err(VcxError::from_code(error::NO_POOL_OPEN.code_num)))?.get(&context::current()).ok_or(VcxError::from_code(error::NO_POOL_OPEN.code_num))?)
 }
 
 #[cfg(test)]
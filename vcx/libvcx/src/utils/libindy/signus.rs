@@ -1,22 +1,23 @@
 extern crate libc;
 
 use settings;
-use utils::libindy::error_codes::map_rust_indy_sdk_error_code;
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
 use indy::did::Did;
+use error::VcxResult;
 
-pub fn create_and_store_my_did(seed: Option<&str>) -> Result<(String, String), u32> {
+pub fn create_and_store_my_did(seed: Option<&str>) -> VcxResult<(String, String)> {
     if settings::test_indy_mode_enabled() {
         return Ok((::utils::constants::DID.to_string(), ::utils::constants::VERKEY.to_string()));
     }
 
     let my_did_json = seed.map_or("{}".to_string(), |seed| format!("{{\"seed\":\"{}\" }}", seed));
-    Did::new(::utils::libindy::wallet::get_wallet_handle(), &my_did_json).map_err(map_rust_indy_sdk_error_code)
+    Did::new(::utils::libindy::wallet::get_wallet_handle(), &my_did_json).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn get_local_verkey(did: &str) -> Result<String, u32> {
+pub fn get_local_verkey(did: &str) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() {
         return Ok(::utils::constants::VERKEY.to_string());
     }
 
-    Did::get_ver_key_local(::utils::libindy::wallet::get_wallet_handle(), did).map_err(map_rust_indy_sdk_error_code)
+    Did::get_ver_key_local(::utils::libindy::wallet::get_wallet_handle(), did).map_err(map_rust_indy_sdk_error)
 }
@@ -2,17 +2,35 @@ extern crate libc;
 extern crate serde_json;
 
 use settings;
-use utils::libindy::error_codes::map_rust_indy_sdk_error_code;
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
 use utils::error;
-use error::wallet::WalletError;
+use error::{ VcxError, VcxResult };
 use indy::wallet::Wallet;
 use indy::ErrorCode;
 use std::path::Path;
-pub static mut WALLET_HANDLE: i32 = 0;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use context;
 
-pub fn get_wallet_handle() -> i32 { unsafe { WALLET_HANDLE } }
+lazy_static! {
+    // Open wallet of every context
+    static ref WALLET_HANDLES: RwLock<HashMap<u32, i32>> = Default::default();
+}
+
+pub fn get_wallet_handle() -> i32 {
+    WALLET_HANDLES.read().unwrap().get(&context::current()).cloned().unwrap_or(0)
+}
 
-pub fn create_wallet(wallet_name: &str) -> Result<(), u32> {
+pub fn set_wallet_handle(handle: i32) {
+    let mut handles = WALLET_HANDLES.write().unwrap();
+    if handle == 0 {
+        handles.remove(&context::current());
+    } else {
+        handles.insert(context::current(), handle);
+    }
+}
+
+pub fn create_wallet(wallet_name: &str) -> VcxResult<()> {
     trace!("creating wallet: {}", wallet_name);
 
     let config = format!(r#"{{"id":"{}"}}"#, wallet_name);
@@ -21,7 +39,7 @@ pub fn create_wallet(wallet_name: &str) -> Result<(), u32> {
         Ok(x) => Ok(()),
         Err(x) => if x != ErrorCode::WalletAlreadyExistsError && x != ErrorCode::Success {
             warn!("could not create wallet {}: {:?}", wallet_name, x);
-            Err(error::INVALID_WALLET_CREATION.code_num)
+            Err(VcxError::from_msg(error::INVALID_WALLET_CREATION.code_num, format!("could not create wallet {}: {:?}", wallet_name, x)))
         } else {
             warn!("could not create wallet {}: {:?}", wallet_name, x);
             Ok(())
@@ -29,25 +47,25 @@ pub fn create_wallet(wallet_name: &str) -> Result<(), u32> {
     }
 }
 
-pub fn open_wallet(wallet_name: &str) -> Result<i32, u32> {
+pub fn open_wallet(wallet_name: &str) -> VcxResult<i32> {
     trace!("opening wallet: {}", wallet_name);
     if settings::test_indy_mode_enabled() {
-        unsafe {WALLET_HANDLE = 1;}
+        set_wallet_handle(1);
         return Ok(1);
     }
 
     let config = format!(r#"{{"id":"{}"}}"#, wallet_name);
 
     let handle = Wallet::open(&config, &settings::get_wallet_credentials())
-        .map_err(map_rust_indy_sdk_error_code)?;
+        .map_err(map_rust_indy_sdk_error)?;
 
-    unsafe { WALLET_HANDLE = handle; }
+    set_wallet_handle(handle);
     Ok(handle)
 }
 
-pub fn init_wallet(wallet_name: &str) -> Result<i32, u32> {
+pub fn init_wallet(wallet_name: &str) -> VcxResult<i32> {
     if settings::test_indy_mode_enabled() {
-        unsafe {WALLET_HANDLE = 1;}
+        set_wallet_handle(1);
         return Ok(1);
     }
 
@@ -55,19 +73,19 @@ pub fn init_wallet(wallet_name: &str) -> Result<i32, u32> {
     open_wallet(wallet_name)
 }
 
-pub fn close_wallet() -> Result<(), u32> {
+pub fn close_wallet() -> VcxResult<()> {
     if settings::test_indy_mode_enabled() {
-        unsafe { WALLET_HANDLE = 0; }
+        set_wallet_handle(0);
         return Ok(());
     }
-    let result = Wallet::close(get_wallet_handle()).map_err(map_rust_indy_sdk_error_code);
-    unsafe { WALLET_HANDLE = 0; }
+    let result = Wallet::close(get_wallet_handle()).map_err(map_rust_indy_sdk_error);
+    set_wallet_handle(0);
     result
 }
 
-pub fn delete_wallet(wallet_name: &str) -> Result<(), u32> {
+pub fn delete_wallet(wallet_name: &str) -> VcxResult<()> {
     if settings::test_indy_mode_enabled() {
-        unsafe { WALLET_HANDLE = 0;}
+        set_wallet_handle(0);
         return Ok(())
     }
 
@@ -77,61 +95,92 @@ pub fn delete_wallet(wallet_name: &str) -> Result<(), u32> {
     };
 
     let config = format!(r#"{{"id":"{}"}}"#, wallet_name);
-    Wallet::delete(&config,&settings::get_wallet_credentials()).map_err(map_rust_indy_sdk_error_code)
+    Wallet::delete(&config,&settings::get_wallet_credentials()).map_err(map_rust_indy_sdk_error)
 }
 
-pub fn add_record(xtype: &str, id: &str, value: &str, tags: &str) -> Result<(), u32> {
+pub fn add_record(xtype: &str, id: &str, value: &str, tags: &str) -> VcxResult<()> {
     if settings::test_indy_mode_enabled() { return Ok(()) }
 
     Wallet::add_record(get_wallet_handle(), xtype, id, value, Some(tags))
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 
-pub fn get_record(xtype: &str, id: &str, options: &str) -> Result<String, u32> {
+pub fn get_record(xtype: &str, id: &str, options: &str) -> VcxResult<String> {
     if settings::test_indy_mode_enabled() {
         return Ok(r#"{"id":"123","type":"record type","value":"record value","tags":null}"#.to_string())
     }
 
     Wallet::get_record(get_wallet_handle(), xtype, id, options)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
-pub fn delete_record(xtype: &str, id: &str) -> Result<(), u32> {
+pub fn delete_record(xtype: &str, id: &str) -> VcxResult<()> {
     if settings::test_indy_mode_enabled() { return Ok(()) }
     Wallet::delete_record(get_wallet_handle(), xtype, id)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
 }
 
 
-pub fn update_record_value(xtype: &str, id: &str, value: &str) -> Result<(), u32> {
+pub fn update_record_value(xtype: &str, id: &str, value: &str) -> VcxResult<()> {
     if settings::test_indy_mode_enabled() { return Ok(()) }
     Wallet::update_record_value(get_wallet_handle(), xtype, id, value)
-        .map_err(map_rust_indy_sdk_error_code)
+        .map_err(map_rust_indy_sdk_error)
+}
+
+/// Every record of the type matching the wallet query, as {"id", "type", "value", "tags"} objects
+/// with the parts the options ask for.
+pub fn search_records(xtype: &str, query: &str, options: &str) -> VcxResult<Vec<serde_json::Value>> {
+    if settings::test_indy_mode_enabled() { return Ok(Vec::new()) }
+
+    let wallet_handle = get_wallet_handle();
+    let search_handle = Wallet::open_search(wallet_handle, xtype, query, options)
+        .map_err(map_rust_indy_sdk_error)?;
+
+    let mut records = Vec::new();
+    let fetched = loop {
+        let batch = match Wallet::fetch_search_next_records(wallet_handle, search_handle, 100) {
+            Ok(x) => x,
+            Err(x) => break Err(map_rust_indy_sdk_error(x)),
+        };
+        let batch: serde_json::Value = match serde_json::from_str(&batch) {
+            Ok(x) => x,
+            Err(_) => break Err(VcxError::from(error::INVALID_JSON.code_num)),
+        };
+        match batch["records"].as_array() {
+            Some(batch) if !batch.is_empty() => records.extend(batch.iter().cloned()),
+            _ => break Ok(()),
+        }
+    };
+
+    if let Err(x) = Wallet::close_search(search_handle) {
+        warn!("could not close the search of {} records: {:?}", xtype, x);
+    }
+
+    fetched.map(|_| records)
 }
 
-pub fn export(wallet_handle: i32, path: &Path, backup_key: &str) -> Result<(), WalletError> {
+pub fn export(wallet_handle: i32, path: &Path, backup_key: &str) -> VcxResult<()> {
     let export_config = json!({ "key": backup_key, "path": &path}).to_string();
     match Wallet::export(wallet_handle, &export_config) {
         Ok(_) => Ok(()),
-        Err(e) => Err(WalletError::CommonError(map_rust_indy_sdk_error_code(e))),
+        Err(e) => Err(map_rust_indy_sdk_error(e)),
     }
 }
 
-pub fn import(config: &str) -> Result<(), WalletError> {
-    settings::process_config_string(config).map_err(|e| WalletError::CommonError(e))?;
+pub fn import(config: &str) -> VcxResult<()> {
+    settings::process_config_string(config)?;
 
-    let key = settings::get_config_value(settings::CONFIG_WALLET_KEY)
-        .map_err(|e| WalletError::CommonError(e))?;
+    let key = settings::get_config_value(settings::CONFIG_WALLET_KEY)?;
 
     let name = settings::get_config_value(settings::CONFIG_WALLET_NAME)
-        .map_err(|e| WalletError::CommonError(error::MISSING_WALLET_NAME.code_num))?;
+        .map_err(|e| VcxError::from(error::MISSING_WALLET_NAME.code_num))?;
 
     let exported_wallet_path = settings::get_config_value(settings::CONFIG_EXPORTED_WALLET_PATH)
-        .or(Err(WalletError::CommonError(error::MISSING_EXPORTED_WALLET_PATH.code_num)))?;
+        .or(Err(VcxError::from(error::MISSING_EXPORTED_WALLET_PATH.code_num)))?;
 
     let backup_key = settings::get_config_value(settings::CONFIG_WALLET_BACKUP_KEY)
-        .or(Err(WalletError::CommonError(error::MISSING_BACKUP_KEY.code_num)))?;
+        .or(Err(VcxError::from(error::MISSING_BACKUP_KEY.code_num)))?;
 
     let config = json!({"id":name}).to_string();
     let credentials = settings::get_wallet_credentials();
@@ -139,7 +188,7 @@ pub fn import(config: &str) -> Result<(), WalletError> {
 
     match Wallet::import(&config, &credentials, &import_config) {
         Ok(_) => Ok(()),
-        Err(e) => Err(WalletError::CommonError(map_rust_indy_sdk_error_code(e))),
+        Err(e) => Err(map_rust_indy_sdk_error(e)),
     }
 }
 
@@ -194,7 +243,7 @@ pub mod tests {
     fn test_wallet() {
         init!("false");
         assert!( get_wallet_handle() > 0);
-        assert_eq!(error::INVALID_WALLET_CREATION.code_num, init_wallet(&String::from("")).unwrap_err());
+        assert_eq!(error::INVALID_WALLET_CREATION.code_num, init_wallet(&String::from("")).unwrap_err().code());
     }
 
     #[test]
@@ -207,7 +256,7 @@ pub mod tests {
 
         // Open fails without Wallet Key Derivation set
         ::api::vcx::vcx_shutdown(false);
-        assert_eq!(open_wallet(wallet_n), Err(error::UNKNOWN_LIBINDY_ERROR.code_num));
+        assert_eq!(open_wallet(wallet_n), Err(VcxError::from_code(error::UNKNOWN_LIBINDY_ERROR.code_num)));
 
         // Open works when set
         settings::set_config_value(settings::CONFIG_WALLET_KEY_DERIVATION, settings::DEFAULT_WALLET_KEY_DERIVATION);
@@ -215,7 +264,7 @@ pub mod tests {
 
         // Delete fails
         ::api::vcx::vcx_shutdown(false);
-        assert_eq!(delete_wallet(wallet_n), Err(error::UNKNOWN_LIBINDY_ERROR.code_num));
+        assert_eq!(delete_wallet(wallet_n), Err(VcxError::from_code(error::UNKNOWN_LIBINDY_ERROR.code_num)));
 
         // Delete works
         settings::set_config_value(settings::CONFIG_WALLET_KEY_DERIVATION, settings::DEFAULT_WALLET_KEY_DERIVATION);
@@ -246,7 +295,7 @@ pub mod tests {
         open_wallet(&settings::DEFAULT_WALLET_NAME).unwrap();
 
         // If wallet was successfully imported, there will be an error trying to add this duplicate record
-        assert_eq!(add_record(xtype, id, value, "{}"), Err(error::DUPLICATE_WALLET_RECORD.code_num));
+        assert_eq!(add_record(xtype, id, value, "{}"), Err(VcxError::from_code(error::DUPLICATE_WALLET_RECORD.code_num)));
         thread::sleep(Duration::from_secs(1));
         ::api::vcx::vcx_shutdown(true);
         delete_import_wallet_path(dir);
@@ -258,23 +307,23 @@ pub mod tests {
         ::api::vcx::vcx_shutdown(true);
 
         // Invalid json
-        assert_eq!(import(""), Err(WalletError::CommonError(error::INVALID_JSON.code_num)));
+        assert_eq!(import(""), Err(VcxError::from(error::INVALID_JSON.code_num)));
         let mut config = json!({});
 
         // Missing wallet_key
-        assert_eq!(import(&config.to_string()), Err(WalletError::CommonError(error::MISSING_WALLET_KEY.code_num)));
+        assert_eq!(import(&config.to_string()), Err(VcxError::from(error::MISSING_WALLET_KEY.code_num)));
         config[settings::CONFIG_WALLET_KEY] = serde_json::to_value("wallet_key1").unwrap();
 
         // Missing wallet name
-        assert_eq!(import(&config.to_string()), Err(WalletError::CommonError(error::MISSING_WALLET_NAME.code_num)));
+        assert_eq!(import(&config.to_string()), Err(VcxError::from(error::MISSING_WALLET_NAME.code_num)));
         config[settings::CONFIG_WALLET_NAME] = serde_json::to_value("wallet_name1").unwrap();
 
         // Missing exported_wallet_path
-        assert_eq!(import(&config.to_string()), Err(WalletError::CommonError(error::MISSING_EXPORTED_WALLET_PATH.code_num)));
+        assert_eq!(import(&config.to_string()), Err(VcxError::from(error::MISSING_EXPORTED_WALLET_PATH.code_num)));
         config[settings::CONFIG_EXPORTED_WALLET_PATH] = serde_json::to_value(settings::DEFAULT_EXPORTED_WALLET_PATH).unwrap();
 
         // Missing backup_key
-        assert_eq!(import(&config.to_string()), Err(WalletError::CommonError(error::MISSING_BACKUP_KEY.code_num)));
+        assert_eq!(import(&config.to_string()), Err(VcxError::from(error::MISSING_BACKUP_KEY.code_num)));
     }
 
     #[test]
@@ -294,7 +343,7 @@ pub mod tests {
             settings::CONFIG_EXPORTED_WALLET_PATH: exported_path,
             settings::CONFIG_WALLET_BACKUP_KEY: backup_key,
         }).to_string();
-        assert_eq!(import(&import_config), Err(WalletError::CommonError(error::WALLET_ALREADY_EXISTS.code_num)));
+        assert_eq!(import(&import_config), Err(VcxError::from(error::WALLET_ALREADY_EXISTS.code_num)));
 
         ::api::vcx::vcx_shutdown(true);
         delete_import_wallet_path(dir);
@@ -317,7 +366,7 @@ pub mod tests {
             settings::CONFIG_EXPORTED_WALLET_PATH: "DIFFERENT_PATH",
             settings::CONFIG_WALLET_BACKUP_KEY: backup_key,
         }).to_string();
-        assert_eq!(import(&import_config), Err(WalletError::CommonError(error::IOERROR.code_num)));
+        assert_eq!(import(&import_config), Err(VcxError::from(error::IOERROR.code_num)));
 
         ::api::vcx::vcx_shutdown(true);
         delete_import_wallet_path(dir);
@@ -341,7 +390,7 @@ pub mod tests {
             settings::CONFIG_EXPORTED_WALLET_PATH: exported_path,
             settings::CONFIG_WALLET_BACKUP_KEY: bad_backup,
         }).to_string();
-        assert_eq!(import(&import_config), Err(WalletError::CommonError(error::LIBINDY_INVALID_STRUCTURE.code_num)));
+        assert_eq!(import(&import_config), Err(VcxError::from(error::LIBINDY_INVALID_STRUCTURE.code_num)));
 
         ::api::vcx::vcx_shutdown(true);
         delete_import_wallet_path(dir);
@@ -370,7 +419,7 @@ pub mod tests {
 
         add_record(record_type, id, record, "{}").unwrap();
         let rc = add_record(record_type, id, record, "{}");
-        assert_eq!(rc, Err(error::DUPLICATE_WALLET_RECORD.code_num));
+        assert_eq!(rc, Err(VcxError::from_code(error::DUPLICATE_WALLET_RECORD.code_num)));
     }
 
     #[test]
@@ -401,7 +450,7 @@ pub mod tests {
         let wallet_n = "test_retrieve_missing_record_fails";
 
         let rc = get_record(record_type, id, &options);
-        assert_eq!(rc, Err(error::WALLET_RECORD_NOT_FOUND.code_num));
+        assert_eq!(rc, Err(VcxError::from_code(error::WALLET_RECORD_NOT_FOUND.code_num)));
     }
 
     #[test]
@@ -433,7 +482,7 @@ pub mod tests {
         let id = "123";
 
         let rc = delete_record(record_type, id);
-        assert_eq!(rc, Err(error::WALLET_RECORD_NOT_FOUND.code_num));
+        assert_eq!(rc, Err(VcxError::from_code(error::WALLET_RECORD_NOT_FOUND.code_num)));
     }
 
     #[test]
@@ -453,7 +502,7 @@ pub mod tests {
         add_record(record_type, id, record, "{}").unwrap();
         delete_record(record_type, id).unwrap();
         let rc = get_record(record_type, id, &options);
-        assert_eq!(rc, Err(error::WALLET_RECORD_NOT_FOUND.code_num));
+        assert_eq!(rc, Err(VcxError::from_code(error::WALLET_RECORD_NOT_FOUND.code_num)));
     }
 
     #[test]
@@ -466,7 +515,7 @@ pub mod tests {
         let wallet_n = "test_update_record_value_fails_with_no_initial_record";
 
         let rc = update_record_value(record_type, id, record);
-        assert_eq!(rc, Err(error::WALLET_RECORD_NOT_FOUND.code_num));
+        assert_eq!(rc, Err(VcxError::from_code(error::WALLET_RECORD_NOT_FOUND.code_num)));
     }
 
     #[test]
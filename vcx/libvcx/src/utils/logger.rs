@@ -2,6 +2,7 @@ extern crate env_logger;
 extern crate log;
 extern crate log4rs;
 extern crate log_panics;
+extern crate libc;
 #[cfg(target_os = "android")]
 extern crate android_logger;
 #[cfg(target_os = "ios")]
@@ -12,13 +13,41 @@ use std::io::Write;
 use settings;
 use std::sync::{Once, ONCE_INIT};
 use std::env;
+use std::ptr;
+use std::ffi::CString;
 #[allow(unused_imports)]
 use self::log::{Level};
+use self::log::{Log, Metadata, Record, LevelFilter as MaxLevel};
+use self::libc::{c_char, c_void};
+use utils::error;
+use error::{ VcxError, VcxResult };
 #[cfg(target_os = "android")]
 use self::android_logger::Filter;
 
 pub struct LoggerUtils {}
 
+pub type EnabledCB = extern fn(context: *const c_void,
+                               level: u32,
+                               target: *const c_char) -> bool;
+
+pub type LogCB = extern fn(context: *const c_void,
+                           level: u32,
+                           target: *const c_char,
+                           message: *const c_char,
+                           module_path: *const c_char,
+                           file: *const c_char,
+                           line: u32);
+
+pub type FlushCB = extern fn(context: *const c_void);
+
+// Same signature as the libvcx one, so the callbacks of the application are handed to libindy as is.
+extern {
+    fn indy_set_logger(context: *const c_void,
+                       enabled: Option<EnabledCB>,
+                       log: Option<LogCB>,
+                       flush: Option<FlushCB>) -> i32;
+}
+
 static LOGGER_INIT: Once = ONCE_INIT;
 
 // From: https://www.tutorialspoint.com/log4j/log4j_logging_levels.htm
@@ -32,7 +61,110 @@ static LOGGER_INIT: Once = ONCE_INIT;
 //WARN	Designates potentially harmful situations.
 
 
+/// Forwards log records to the callbacks registered by the application.
+/// Levels are numbered like libindy does: 1 - Error, 2 - Warn, 3 - Info, 4 - Debug, 5 - Trace.
+pub struct LibvcxLogger {
+    context: *const c_void,
+    enabled: Option<EnabledCB>,
+    log: LogCB,
+    flush: Option<FlushCB>,
+}
+
+// The context is owned by the application, which is responsible for it being usable from any thread.
+unsafe impl Send for LibvcxLogger {}
+unsafe impl Sync for LibvcxLogger {}
+
+impl LibvcxLogger {
+    pub fn new(context: *const c_void, enabled: Option<EnabledCB>, log: LogCB, flush: Option<FlushCB>) -> LibvcxLogger {
+        LibvcxLogger { context, enabled, log, flush }
+    }
+}
+
+fn to_c_string(value: &str) -> CString {
+    CString::new(value).unwrap_or_default()
+}
+
+impl Log for LibvcxLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match self.enabled {
+            Some(enabled) => {
+                let target = to_c_string(metadata.target());
+                enabled(self.context, metadata.level() as u32, target.as_ptr())
+            },
+            None => true,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return; }
+
+        let target = to_c_string(record.target());
+        let message = to_c_string(&record.args().to_string());
+        let module_path = record.module_path().map(to_c_string);
+        let file = record.file().map(to_c_string);
+
+        (self.log)(self.context,
+                   record.level() as u32,
+                   target.as_ptr(),
+                   message.as_ptr(),
+                   module_path.as_ref().map_or(ptr::null(), |x| x.as_ptr()),
+                   file.as_ref().map_or(ptr::null(), |x| x.as_ptr()),
+                   record.line().unwrap_or(0));
+    }
+
+    fn flush(&self) {
+        if let Some(flush) = self.flush {
+            flush(self.context);
+        }
+    }
+}
+
+pub fn level_filter(max_lvl: u32) -> VcxResult<MaxLevel> {
+    match max_lvl {
+        0 => Ok(MaxLevel::Off),
+        1 => Ok(MaxLevel::Error),
+        2 => Ok(MaxLevel::Warn),
+        3 => Ok(MaxLevel::Info),
+        4 => Ok(MaxLevel::Debug),
+        5 => Ok(MaxLevel::Trace),
+        _ => Err(VcxError::from_code(error::INVALID_OPTION.code_num)),
+    }
+}
+
 impl LoggerUtils {
+    /// Sends libvcx and libindy logs to the application instead of the built-in loggers.
+    /// Has to be called before vcx_init, the logger can only be set once.
+    pub fn set_logger(context: *const c_void,
+                      enabled: Option<EnabledCB>,
+                      log: LogCB,
+                      flush: Option<FlushCB>,
+                      max_lvl: u32) -> VcxResult<()> {
+        let max_lvl = level_filter(max_lvl)?;
+
+        let mut result = Err(VcxError::from_code(error::LOGGING_ERROR.code_num));
+        LOGGER_INIT.call_once(|| {
+            log_panics::init();
+            result = log::set_boxed_logger(Box::new(LibvcxLogger::new(context, enabled, log, flush)))
+                .or(Err(VcxError::from_code(error::LOGGING_ERROR.code_num)));
+        });
+        result?;
+
+        log::set_max_level(max_lvl);
+
+        match unsafe { indy_set_logger(context, enabled, Some(log), flush) } {
+            0 => Ok(()),
+            err => {
+                warn!("libindy logger could not be set: {}", err);
+                Err(VcxError::from_code(error::LOGGING_ERROR.code_num))
+            }
+        }
+    }
+
+    pub fn set_max_level(max_lvl: u32) -> VcxResult<()> {
+        log::set_max_level(level_filter(max_lvl)?);
+        Ok(())
+    }
+
     pub fn init_test_logging(level: &str) {
         // logger for testing purposes, sends to stdout (set env RUST_LOG to configure log level
         let level = match env::var("RUST_LOG") {
@@ -110,8 +242,53 @@ mod tests {
 
     use super::*;
 
+    use std::ffi::CStr;
+    use std::sync::Mutex;
+
+    lazy_static! {
+        static ref LOGGED: Mutex<Vec<(u32, String, String, String, u32)>> = Default::default();
+    }
+
+    extern fn only_errors(_context: *const c_void, level: u32, _target: *const c_char) -> bool {
+        level == Level::Error as u32
+    }
+
+    extern fn record(_context: *const c_void, level: u32, target: *const c_char, message: *const c_char,
+                     _module_path: *const c_char, file: *const c_char, line: u32) {
+        let to_string = |s: *const c_char| unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        LOGGED.lock().unwrap().push((level, to_string(target), to_string(message), to_string(file), line));
+    }
+
     #[test]
     fn test_logger() {
         LoggerUtils::init();
     }
+
+    #[test]
+    fn test_libvcx_logger_forwards_records() {
+        let logger = LibvcxLogger::new(ptr::null(), Some(only_errors), record, None);
+
+        logger.log(&Record::builder()
+            .args(format_args!("agency unreachable"))
+            .level(Level::Error)
+            .target("vcx::httpclient")
+            .file(Some("httpclient.rs"))
+            .line(Some(42))
+            .build());
+        logger.log(&Record::builder()
+            .args(format_args!("sending message"))
+            .level(Level::Debug)
+            .target("vcx::httpclient")
+            .build());
+
+        let logged = LOGGED.lock().unwrap();
+        assert_eq!(*logged, vec![(1, "vcx::httpclient".to_string(), "agency unreachable".to_string(), "httpclient.rs".to_string(), 42)]);
+    }
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(level_filter(0).unwrap(), MaxLevel::Off);
+        assert_eq!(level_filter(5).unwrap(), MaxLevel::Trace);
+        assert_eq!(level_filter(6).unwrap_err().code(), error::INVALID_OPTION.code_num);
+    }
 }
@@ -0,0 +1,677 @@
+extern crate rmp_serde;
+extern crate serde_json;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use serde::Deserialize;
+use serde_json::Value;
+use self::rmp_serde::{encode, Deserializer};
+use indy::wallet::Wallet;
+use indy::did::Did;
+use indy::crypto::Crypto;
+use messages::to_i8;
+use messages::get_message::Message;
+use messages::invite::{KeyDlgProofPayload, SenderDetail};
+use messages::MessageResponseCode::{MessageSent, MessagePending, MessageAccepted};
+use utils::httpclient::Transport;
+use error::{ VcxError, VcxResult };
+use utils::libindy::error_codes::map_rust_indy_sdk_error;
+use utils::error;
+
+pub const MOCK_AGENCY_ENDPOINT: &str = "http://mock-agency.local";
+static MOCK_AGENCY_WALLET_KEY: &str = "8dvfYSt5d1taSd6yJdpjq4emkwsPDDLYxkNFysFD2cZY";
+
+lazy_static! {
+    static ref WALLET_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+}
+
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    bundled: Vec<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct TypeName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Typed {
+    #[serde(rename = "@type")]
+    msg_type: TypeName,
+}
+
+#[derive(Deserialize)]
+struct ForwardMsg {
+    #[serde(rename = "@fwd")]
+    fwd: String,
+    #[serde(rename = "@msg")]
+    msg: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct ConnectMsg {
+    #[serde(rename = "fromDIDVerKey")]
+    from_vk: String,
+}
+
+#[derive(Deserialize)]
+struct CreateKeyMsg {
+    #[serde(rename = "forDID")]
+    for_did: String,
+    #[serde(rename = "forDIDVerKey")]
+    for_vk: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetMsgsMsg {
+    uids: Option<Vec<String>>,
+    status_codes: Option<Vec<String>>,
+    #[serde(rename = "pairwiseDIDs")]
+    pairwise_dids: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UidsByConn {
+    #[serde(rename = "pairwiseDID")]
+    pairwise_did: String,
+    uids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateMsgStatusMsg {
+    status_code: String,
+    uids_by_conns: Vec<UidsByConn>,
+}
+
+#[derive(Deserialize)]
+struct ConfigValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct UpdateConfigsMsg {
+    configs: Vec<ConfigValue>,
+}
+
+#[derive(Deserialize)]
+struct ComMethod {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct UpdateComMethodMsg {
+    #[serde(rename = "comMethod")]
+    com_method: ComMethod,
+}
+
+#[derive(Deserialize)]
+struct UpdateConnStatusMsg {
+    #[serde(rename = "statusCode")]
+    status_code: String,
+}
+
+#[derive(Deserialize)]
+struct CreateMsg {
+    mtype: String,
+    #[serde(rename = "replyToMsgId")]
+    reply_to_msg_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct InviteMsgDetail {
+    #[serde(rename = "keyDlgProof")]
+    key_proof: KeyDlgProofPayload,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnswerMsgDetail {
+    #[serde(rename = "keyDlgProof")]
+    key_proof: KeyDlgProofPayload,
+    sender_detail: SenderDetail,
+}
+
+#[derive(Deserialize)]
+struct GeneralMsgDetail {
+    #[serde(rename = "@msg")]
+    msg: Vec<u8>,
+}
+
+struct AgencyPairwise {
+    verkey: String,
+    owner_vk: String,
+}
+
+struct Agent {
+    verkey: String,
+    owner_vk: String,
+    configs: HashMap<String, String>,
+}
+
+struct PairwiseKey {
+    verkey: String,
+    agent_did: String,
+    for_did: String,
+    for_vk: String,
+    remote: Option<String>,
+    msgs: Vec<Message>,
+}
+
+#[derive(Default)]
+struct AgencyState {
+    agency_pairwise: HashMap<String, AgencyPairwise>,
+    agents: HashMap<String, Agent>,
+    pairwise: HashMap<String, PairwiseKey>,
+    uid_counter: u32,
+}
+
+impl AgencyState {
+    fn new_uid(&mut self) -> String {
+        self.uid_counter += 1;
+        format!("mockmsg{}", self.uid_counter)
+    }
+
+    fn pairwise_key(&mut self, did: &str) -> VcxResult<&mut PairwiseKey> {
+        self.pairwise.get_mut(did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))
+    }
+
+    fn pairwise_for_did(&self, agent_did: Option<&str>, for_did: &str) -> Option<String> {
+        self.pairwise.iter()
+            .find(|&(_, key)| key.for_did == for_did && agent_did.map_or(true, |did| key.agent_did == did))
+            .map(|(did, _)| did.to_string())
+    }
+
+    fn accept_reply(&mut self, pw_did: &str, reply_to: &str, uid: &str) {
+        if let Some(key) = self.pairwise.get_mut(pw_did) {
+            for msg in key.msgs.iter_mut().filter(|msg| msg.uid == reply_to) {
+                msg.status_code = MessageAccepted.as_string();
+                msg.ref_msg_id = Some(uid.to_string());
+            }
+        }
+    }
+}
+
+/// In-process stand-in for an agency, for end-to-end tests that run without network access.
+///
+/// It understands the same encrypted msgpack bundles the sdk produces with bundle_for_agency
+/// and bundle_for_agent: provisioning (CONNECT/SIGNUP/CREATE_AGENT), pairwise keys (CREATE_KEY),
+/// invitations, messages between connected pairwise keys, and message retrieval and status updates.
+/// Messages are delivered to the other side of a connection as soon as they are sent.
+/// Register it with httpclient::set_transport and provision agents with its did/verkey.
+/// Since the sdk skips encryption when libindy is mocked, it can not be used with test mode "true".
+pub struct MockAgency {
+    wallet_name: String,
+    wallet_handle: i32,
+    did: String,
+    verkey: String,
+    state: Mutex<AgencyState>,
+}
+
+impl MockAgency {
+    pub fn new() -> VcxResult<MockAgency> {
+        let wallet_name = format!("mock_agency_wallet_{}", WALLET_COUNTER.fetch_add(1, Ordering::SeqCst));
+        let config = MockAgency::wallet_config(&wallet_name);
+        let credentials = MockAgency::wallet_credentials();
+
+        Wallet::delete(&config, &credentials).ok();
+        Wallet::create(&config, &credentials).map_err(map_rust_indy_sdk_error)?;
+        let wallet_handle = Wallet::open(&config, &credentials).map_err(map_rust_indy_sdk_error)?;
+        let (did, verkey) = Did::new(wallet_handle, "{}").map_err(map_rust_indy_sdk_error)?;
+
+        Ok(MockAgency {
+            wallet_name,
+            wallet_handle,
+            did,
+            verkey,
+            state: Mutex::new(AgencyState::default()),
+        })
+    }
+
+    pub fn did(&self) -> &str { &self.did }
+    pub fn verkey(&self) -> &str { &self.verkey }
+    pub fn endpoint(&self) -> &str { MOCK_AGENCY_ENDPOINT }
+
+    fn wallet_config(wallet_name: &str) -> String { json!({"id": wallet_name}).to_string() }
+    fn wallet_credentials() -> String { json!({"key": MOCK_AGENCY_WALLET_KEY, "key_derivation_method": "RAW"}).to_string() }
+
+    fn new_key(&self) -> VcxResult<(String, String)> {
+        Did::new(self.wallet_handle, "{}").map_err(map_rust_indy_sdk_error)
+    }
+
+    fn unpack(&self, recipient_vk: &str, msg: &[u8]) -> VcxResult<(String, Vec<Vec<u8>>)> {
+        let (sender_vk, data) = Crypto::auth_decrypt(self.wallet_handle, recipient_vk, msg)
+            .map_err(map_rust_indy_sdk_error)?;
+        let bundle: Bundle = decode(&data)?;
+        Ok((sender_vk, bundle.bundled))
+    }
+
+    fn handle(&self, body: &[u8]) -> VcxResult<Vec<u8>> {
+        let data = Crypto::anon_decrypt(self.wallet_handle, &self.verkey, body).map_err(map_rust_indy_sdk_error)?;
+        let bundle: Bundle = decode(&data)?;
+        let fwd: ForwardMsg = decode(bundle.bundled.get(0).ok_or(VcxError::from_code(error::INVALID_MSGPACK.code_num))?)?;
+
+        let mut state = self.state.lock().or(Err(VcxError::from_code(error::UNKNOWN_ERROR.code_num)))?;
+
+        let recipient_vk = if fwd.fwd == self.did {
+            self.verkey.clone()
+        } else if let Some(pairwise) = state.agency_pairwise.get(&fwd.fwd) {
+            pairwise.verkey.clone()
+        } else if let Some(agent) = state.agents.get(&fwd.fwd) {
+            agent.verkey.clone()
+        } else {
+            warn!("mock agency can not route message to {}", fwd.fwd);
+            return Err(VcxError::from_code(error::INVALID_DID.code_num));
+        };
+
+        let (sender_vk, msgs) = self.unpack(&recipient_vk, &fwd.msg)?;
+
+        let responses = if fwd.fwd == self.did {
+            self.handle_agency_msg(&mut state, &msgs)?
+        } else if state.agency_pairwise.contains_key(&fwd.fwd) {
+            self.handle_agency_pairwise_msg(&mut state, &fwd.fwd, &msgs)?
+        } else {
+            self.handle_agent_msg(&mut state, &fwd.fwd, &msgs)?
+        };
+
+        let response = encode::to_vec_named(&Bundle { bundled: responses }).or(Err(VcxError::from_code(error::INVALID_MSGPACK.code_num)))?;
+        Crypto::auth_crypt(self.wallet_handle, &recipient_vk, &sender_vk, &response).map_err(map_rust_indy_sdk_error)
+    }
+
+    fn handle_agency_msg(&self, state: &mut AgencyState, msgs: &Vec<Vec<u8>>) -> VcxResult<Vec<Vec<u8>>> {
+        match msg_name(msgs)?.as_str() {
+            "CONNECT" => {
+                let msg: ConnectMsg = decode(&msgs[0])?;
+                let (did, verkey) = self.new_key()?;
+                state.agency_pairwise.insert(did.clone(), AgencyPairwise { verkey: verkey.clone(), owner_vk: msg.from_vk });
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("CONNECTED"),
+                    "withPairwiseDID": did,
+                    "withPairwiseDIDVerKey": verkey,
+                }))?])
+            },
+            x => unsupported(x),
+        }
+    }
+
+    fn handle_agency_pairwise_msg(&self, state: &mut AgencyState, did: &str, msgs: &Vec<Vec<u8>>) -> VcxResult<Vec<Vec<u8>>> {
+        match msg_name(msgs)?.as_str() {
+            "SIGNUP" => Ok(vec![encode_json(&json!({ "@type": msg_type("SIGNED_UP") }))?]),
+            "CREATE_AGENT" => {
+                let owner_vk = state.agency_pairwise.get(did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?.owner_vk.clone();
+                let (agent_did, agent_vk) = self.new_key()?;
+                state.agents.insert(agent_did.clone(), Agent { verkey: agent_vk.clone(), owner_vk, configs: HashMap::new() });
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("AGENT_CREATED"),
+                    "withPairwiseDID": agent_did,
+                    "withPairwiseDIDVerKey": agent_vk,
+                }))?])
+            },
+            x => unsupported(x),
+        }
+    }
+
+    fn handle_agent_msg(&self, state: &mut AgencyState, agent_did: &str, msgs: &Vec<Vec<u8>>) -> VcxResult<Vec<Vec<u8>>> {
+        match msg_name(msgs)?.as_str() {
+            "FWD" => {
+                let fwd: ForwardMsg = decode(&msgs[0])?;
+                let verkey = state.pairwise_key(&fwd.fwd)?.verkey.clone();
+                let (_, msgs) = self.unpack(&verkey, &fwd.msg)?;
+                self.handle_pairwise_msg(state, &fwd.fwd, &msgs)
+            },
+            "CREATE_KEY" => {
+                let msg: CreateKeyMsg = decode(&msgs[0])?;
+                let (did, verkey) = self.new_key()?;
+                state.pairwise.insert(did.clone(), PairwiseKey {
+                    verkey: verkey.clone(),
+                    agent_did: agent_did.to_string(),
+                    for_did: msg.for_did,
+                    for_vk: msg.for_vk,
+                    remote: None,
+                    msgs: Vec::new(),
+                });
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("KEY_CREATED"),
+                    "withPairwiseDID": did,
+                    "withPairwiseDIDVerKey": verkey,
+                }))?])
+            },
+            "GET_MSGS_BY_CONNS" => {
+                let msg: GetMsgsMsg = decode(&msgs[0])?;
+                let pw_dids = match msg.pairwise_dids {
+                    Some(ref dids) => dids.iter()
+                        .map(|did| state.pairwise_for_did(Some(agent_did), did).ok_or(VcxError::from_code(error::INVALID_DID.code_num)))
+                        .collect::<VcxResult<Vec<String>>>()?,
+                    None => state.pairwise.iter()
+                        .filter(|&(_, key)| key.agent_did == agent_did)
+                        .map(|(did, _)| did.to_string())
+                        .collect(),
+                };
+
+                let mut msgs_by_conns = Vec::new();
+                for pw_did in pw_dids {
+                    let key = state.pairwise_key(&pw_did)?;
+                    msgs_by_conns.push(json!({
+                        "pairwiseDID": key.for_did,
+                        "msgs": filter_msgs(&key.msgs, &msg.uids, &msg.status_codes),
+                    }));
+                }
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("MSGS_BY_CONNS"),
+                    "msgsByConns": msgs_by_conns,
+                }))?])
+            },
+            "UPDATE_MSG_STATUS_BY_CONNS" => {
+                let msg: UpdateMsgStatusMsg = decode(&msgs[0])?;
+                for conn in msg.uids_by_conns.iter() {
+                    let pw_did = state.pairwise_for_did(Some(agent_did), &conn.pairwise_did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?;
+                    let key = state.pairwise_key(&pw_did)?;
+                    for stored in key.msgs.iter_mut().filter(|stored| conn.uids.contains(&stored.uid)) {
+                        stored.status_code = msg.status_code.clone();
+                    }
+                }
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("MSG_STATUS_UPDATED_BY_CONNS"),
+                    "statusCode": msg.status_code,
+                    "updatedUidsByConns": msg.uids_by_conns,
+                }))?])
+            },
+            "UPDATE_CONFIGS" => {
+                let msg: UpdateConfigsMsg = decode(&msgs[0])?;
+                let agent = state.agents.get_mut(agent_did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?;
+                for config in msg.configs {
+                    agent.configs.insert(config.name, config.value);
+                }
+                Ok(vec![encode_json(&json!({ "@type": msg_type("CONFIGS_UPDATED") }))?])
+            },
+            "UPDATE_COM_METHOD" => {
+                let msg: UpdateComMethodMsg = decode(&msgs[0])?;
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("COM_METHOD_UPDATED"),
+                    "id": msg.com_method.id,
+                }))?])
+            },
+            x => unsupported(x),
+        }
+    }
+
+    fn handle_pairwise_msg(&self, state: &mut AgencyState, pw_did: &str, msgs: &Vec<Vec<u8>>) -> VcxResult<Vec<Vec<u8>>> {
+        match msg_name(msgs)?.as_str() {
+            "CREATE_MSG" => {
+                let create: CreateMsg = decode(&msgs[0])?;
+                let detail = msgs.get(1).ok_or(VcxError::from_code(error::INVALID_MSGPACK.code_num))?;
+                match create.mtype.as_str() {
+                    "connReq" => self.create_invite(state, pw_did, decode(detail)?),
+                    "connReqAnswer" => self.answer_invite(state, pw_did, &create, decode(detail)?),
+                    _ => self.send_msg(state, pw_did, &create, decode(detail)?),
+                }
+            },
+            "GET_MSGS" => {
+                let msg: GetMsgsMsg = decode(&msgs[0])?;
+                let key = state.pairwise_key(pw_did)?;
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("MSGS"),
+                    "msgs": filter_msgs(&key.msgs, &msg.uids, &msg.status_codes),
+                }))?])
+            },
+            "UPDATE_CONN_STATUS" => {
+                let msg: UpdateConnStatusMsg = decode(&msgs[0])?;
+                Ok(vec![encode_json(&json!({
+                    "@type": msg_type("CONN_STATUS_UPDATED"),
+                    "statusCode": msg.status_code,
+                }))?])
+            },
+            x => unsupported(x),
+        }
+    }
+
+    fn sender_detail(&self, state: &AgencyState, pw_did: &str, key_proof: &KeyDlgProofPayload) -> VcxResult<Value> {
+        let key = state.pairwise.get(pw_did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?;
+        let agent = state.agents.get(&key.agent_did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?;
+        Ok(json!({
+            "name": agent.configs.get("name"),
+            "agentKeyDlgProof": key_proof,
+            "DID": key.for_did,
+            "logoUrl": agent.configs.get("logoUrl"),
+            "verKey": key.for_vk,
+        }))
+    }
+
+    fn create_invite(&self, state: &mut AgencyState, pw_did: &str, detail: InviteMsgDetail) -> VcxResult<Vec<Vec<u8>>> {
+        let uid = state.new_uid();
+        let sender_detail = self.sender_detail(state, pw_did, &detail.key_proof)?;
+
+        let key = state.pairwise_key(pw_did)?;
+        key.msgs.push(new_msg(&uid, "connReq", &key.for_did, MessageSent.as_string(), None));
+        let invite_url = format!("{}/agency/invite/{}?uid={}", MOCK_AGENCY_ENDPOINT, key.for_did, uid);
+
+        Ok(vec![
+            encode_json(&json!({ "@type": msg_type("MSG_CREATED"), "uid": uid }))?,
+            encode_json(&json!({
+                "@type": msg_type("MSG_DETAIL"),
+                "inviteDetail": {
+                    "statusCode": MessageSent.as_string(),
+                    "connReqId": uid,
+                    "senderDetail": sender_detail,
+                    "senderAgencyDetail": { "DID": self.did, "verKey": self.verkey, "endpoint": MOCK_AGENCY_ENDPOINT },
+                    "targetName": "there",
+                    "statusMsg": "message sent",
+                },
+                "urlToInviteDetail": invite_url,
+            }))?,
+            encode_json(&json!({ "@type": msg_type("MSG_SENT"), "uids": [uid] }))?,
+        ])
+    }
+
+    fn answer_invite(&self, state: &mut AgencyState, pw_did: &str, create: &CreateMsg, detail: AnswerMsgDetail) -> VcxResult<Vec<Vec<u8>>> {
+        let inviter_did = state.pairwise_for_did(None, &detail.sender_detail.did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?;
+        let inviter_agent_did = state.pairwise_key(&inviter_did)?.agent_did.clone();
+        let inviter_owner_vk = state.agents.get(&inviter_agent_did).ok_or(VcxError::from_code(error::INVALID_DID.code_num))?.owner_vk.clone();
+
+        let uid = state.new_uid();
+        let acceptance = json!({ "senderDetail": self.sender_detail(state, pw_did, &detail.key_proof)? });
+        let acceptance = encode_json(&json!({
+            "@type": { "name": "connReqAnswer", "ver": "1.0", "fmt": "msgpack" },
+            "@msg": to_i8(&encode_json(&acceptance)?),
+        }))?;
+        let payload = Crypto::auth_crypt(self.wallet_handle, &self.verkey, &inviter_owner_vk, &acceptance)
+            .map_err(map_rust_indy_sdk_error)?;
+
+        let acceptor_did = {
+            let key = state.pairwise_key(pw_did)?;
+            key.remote = Some(inviter_did.clone());
+            key.msgs.push(new_msg(&uid, "connReqAnswer", &key.for_did, MessageAccepted.as_string(), None));
+            key.for_did.clone()
+        };
+        {
+            let inviter = state.pairwise_key(&inviter_did)?;
+            inviter.remote = Some(pw_did.to_string());
+            inviter.msgs.push(new_msg(&uid, "connReqAnswer", &acceptor_did, MessageAccepted.as_string(), Some(payload)));
+        }
+        if let Some(ref reply_to) = create.reply_to_msg_id {
+            state.accept_reply(&inviter_did, reply_to, &uid);
+        }
+
+        Ok(vec![
+            encode_json(&json!({ "@type": msg_type("MSG_CREATED"), "uid": uid }))?,
+            encode_json(&json!({ "@type": msg_type("MSG_SENT"), "uids": [uid] }))?,
+        ])
+    }
+
+    fn send_msg(&self, state: &mut AgencyState, pw_did: &str, create: &CreateMsg, detail: GeneralMsgDetail) -> VcxResult<Vec<Vec<u8>>> {
+        let uid = state.new_uid();
+
+        let (sender_did, remote_did) = {
+            let key = state.pairwise_key(pw_did)?;
+            let remote_did = key.remote.clone().ok_or(VcxError::from_code(error::INVALID_CONNECTION_HANDLE.code_num))?;
+            key.msgs.push(new_msg(&uid, &create.mtype, &key.for_did, MessageSent.as_string(), None));
+            (key.for_did.clone(), remote_did)
+        };
+        state.pairwise_key(&remote_did)?
+            .msgs.push(new_msg(&uid, &create.mtype, &sender_did, MessagePending.as_string(), Some(detail.msg)));
+
+        if let Some(ref reply_to) = create.reply_to_msg_id {
+            state.accept_reply(pw_did, reply_to, &uid);
+            state.accept_reply(&remote_did, reply_to, &uid);
+        }
+
+        Ok(vec![
+            encode_json(&json!({ "@type": msg_type("MSG_CREATED"), "uid": uid }))?,
+            encode_json(&json!({ "@type": msg_type("MSG_SENT"), "uids": [uid] }))?,
+        ])
+    }
+}
+
+impl Transport for MockAgency {
+    fn post(&self, url: &str, body: &[u8]) -> VcxResult<Vec<u8>> {
+        self.handle(body).map_err(|err| {
+            warn!("mock agency rejected message posted to {}: {}", url, err);
+            err.context(error::AGENCY_REJECTED_MESSAGE.code_num, "mock agency rejected the message")
+        })
+    }
+}
+
+impl Drop for MockAgency {
+    fn drop(&mut self) {
+        Wallet::close(self.wallet_handle).ok();
+        Wallet::delete(&MockAgency::wallet_config(&self.wallet_name), &MockAgency::wallet_credentials()).ok();
+    }
+}
+
+fn decode<T>(data: &[u8]) -> VcxResult<T> where T: for<'de> Deserialize<'de> {
+    let mut de = Deserializer::new(data);
+    Deserialize::deserialize(&mut de).map_err(|err| {
+        warn!("mock agency could not parse messagepack: {}", err);
+        VcxError::from_code(error::INVALID_MSGPACK.code_num)
+    })
+}
+
+fn encode_json(value: &Value) -> VcxResult<Vec<u8>> {
+    encode::to_vec_named(value).or(Err(VcxError::from_code(error::INVALID_MSGPACK.code_num)))
+}
+
+fn msg_type(name: &str) -> Value {
+    json!({ "name": name, "ver": "1.0" })
+}
+
+fn msg_name(msgs: &Vec<Vec<u8>>) -> VcxResult<String> {
+    let typed: Typed = decode(msgs.get(0).ok_or(VcxError::from_code(error::INVALID_MSGPACK.code_num))?)?;
+    Ok(typed.msg_type.name)
+}
+
+fn unsupported<T>(name: &str) -> VcxResult<T> {
+    warn!("mock agency does not support message type {}", name);
+    Err(VcxError::from_code(error::INVALID_MSGPACK.code_num))
+}
+
+fn new_msg(uid: &str, msg_type: &str, sender_did: &str, status_code: String, payload: Option<Vec<u8>>) -> Message {
+    let mut msg = Message::new();
+    msg.uid = uid.to_string();
+    msg.msg_type = msg_type.to_string();
+    msg.sender_did = sender_did.to_string();
+    msg.status_code = status_code;
+    msg.payload = payload.map(|payload| to_i8(&payload));
+    msg
+}
+
+fn filter_msgs(msgs: &Vec<Message>, uids: &Option<Vec<String>>, status_codes: &Option<Vec<String>>) -> Vec<Message> {
+    msgs.iter()
+        .filter(|msg| uids.as_ref().map_or(true, |uids| uids.contains(&msg.uid)))
+        .filter(|msg| status_codes.as_ref().map_or(true, |codes| codes.contains(&msg.status_code)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use api::VcxStateType;
+    use connection;
+    use messages;
+    use messages::GeneralMessage;
+    use settings;
+    use utils::httpclient;
+    use utils::devsetup::tests::{set_institution, set_consumer};
+
+    #[test]
+    fn test_rejects_unknown_recipient() {
+        init!("false");
+        let agency = MockAgency::new().unwrap();
+        let msg = ::utils::libindy::crypto::prep_anonymous_msg(agency.verkey(), &vec![1, 2, 3]).unwrap();
+        assert_eq!(agency.handle(&msg).err(), Some(VcxError::from_code(error::INVALID_MSGPACK.code_num)));
+
+        let forward = encode_json(&json!({ "@type": msg_type("FWD"), "@fwd": "UnknownDid1111111111", "@msg": [1] })).unwrap();
+        let bundle = encode::to_vec_named(&Bundle { bundled: vec![forward] }).unwrap();
+        let msg = ::utils::libindy::crypto::prep_anonymous_msg(agency.verkey(), &bundle).unwrap();
+        assert_eq!(agency.handle(&msg).err(), Some(VcxError::from_code(error::INVALID_DID.code_num)));
+    }
+
+    #[test]
+    fn test_provision_with_mock_agency() {
+        init!("false");
+        let agency = Arc::new(MockAgency::new().unwrap());
+        let transport = httpclient::lock_test_transport();
+        httpclient::set_transport(agency.clone());
+
+        let config = json!({
+            "agency_url": agency.endpoint(),
+            "agency_did": agency.did(),
+            "agency_verkey": agency.verkey(),
+            "wallet_name": "test_provision_with_mock_agency",
+            "wallet_key": settings::DEFAULT_WALLET_KEY,
+        }).to_string();
+        let result = messages::agent_utils::connect_register_provision(&config);
+        drop(transport);
+        ::utils::libindy::wallet::tests::delete_test_wallet("test_provision_with_mock_agency");
+
+        let result: Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert!(agency.state.lock().unwrap().agents.contains_key(result["remote_to_sdk_did"].as_str().unwrap()));
+    }
+
+    #[test]
+    fn test_connection_and_messages_with_mock_agency() {
+        init!("mock_agency");
+        let (faber, alice) = connection::tests::create_connected_connections();
+
+        let alice_did = connection::get_pw_did(alice).unwrap();
+        let alice_vk = connection::get_pw_verkey(alice).unwrap();
+        let faber_vk = connection::get_their_pw_verkey(alice).unwrap();
+        let payload = connection::generate_encrypted_payload(&alice_vk, &faber_vk, "{\"hello\":\"world\"}", "hello").unwrap();
+        let uid = messages::send_message()
+            .to(&alice_did)
+            .to_vk(&alice_vk)
+            .msg_type("hello")
+            .agent_did(&connection::get_agent_did(alice).unwrap())
+            .agent_vk(&connection::get_agent_verkey(alice).unwrap())
+            .edge_agent_payload(&payload)
+            .send_secure()
+            .unwrap();
+        let uid = messages::send_message::parse_msg_uid(&uid[0]).unwrap();
+
+        set_consumer();
+        let pending = messages::get_message::download_messages(None, Some(vec![MessagePending.as_string()]), None).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].pairwise_did, connection::get_pw_did(faber).unwrap());
+        assert_eq!(pending[0].msgs.len(), 1);
+        assert_eq!(pending[0].msgs[0].uid, uid);
+        assert!(pending[0].msgs[0].decrypted_payload.is_some());
+
+        let update = json!([{"pairwiseDID": pending[0].pairwise_did, "uids": [uid]}]).to_string();
+        messages::update_message::update_agency_messages("MS-106", &update).unwrap();
+        let pending = messages::get_message::download_messages(None, Some(vec![MessagePending.as_string()]), None).unwrap();
+        assert!(pending[0].msgs.is_empty());
+
+        assert_eq!(messages::get_message::download_messages(Some(vec!["abc".to_string()]), None, None),
+                   Err(VcxError::from_code(error::AGENCY_REJECTED_MESSAGE.code_num)));
+
+        set_institution();
+        assert_eq!(connection::get_state(alice), VcxStateType::VcxStateAccepted as u32);
+        teardown!("mock_agency");
+    }
+}
@@ -12,6 +12,8 @@ pub mod devsetup;
 
 pub mod error;
 pub mod httpclient;
+#[cfg(any(test, feature = "mock_agency"))]
+pub mod mock_agency;
 pub mod constants;
 pub mod timeout;
 pub mod openssl;
@@ -19,6 +21,7 @@ pub mod json;
 pub mod libindy;
 pub mod threadpool;
 pub mod serde_utils;
+pub mod serialization;
 
 use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
 lazy_static! {
@@ -2,29 +2,43 @@ extern crate openssl;
 use self::openssl::sha::sha256;
 use self::openssl::bn::BigNum;
 use utils::error::BIG_NUMBER_ERROR;
-pub fn encode(s: &str ) -> Result<String, u32> {
-    match s.parse::<u32>() {
-        Ok(_) => Ok(s.to_string()),
+use error::{ VcxError, VcxResult };
+
+/// Encodes a credential attribute value the way every agent does: values that are 32 bit
+/// integers are kept as they are, anything else is the SHA-256 hash of the value as a decimal.
+/// Credentials issued by earlier releases kept every unsigned 32 bit integer as it was, so values
+/// from 2147483648 to 4294967295 were not hashed, see `matches_encoding`.
+pub fn encode(s: &str ) -> VcxResult<String> {
+    match s.parse::<i32>() {
+        Ok(x) => Ok(x.to_string()),
         Err(_) => {
             let hash = sha256(s.as_bytes());
             let bignum = match BigNum::from_slice(&hash) {
                 Ok(b) => b,
                 Err(_) => {
                     warn!("{}", BIG_NUMBER_ERROR.message);
-                    return Err(BIG_NUMBER_ERROR.code_num)
+                    return Err(VcxError::from_code(BIG_NUMBER_ERROR.code_num))
                 }
             };
             match bignum.to_dec_str() {
                 Ok(s) => Ok(s.to_string()),
                 Err(_) => {
                     warn!("{}", BIG_NUMBER_ERROR.message);
-                    Err(BIG_NUMBER_ERROR.code_num)
+                    Err(VcxError::from_code(BIG_NUMBER_ERROR.code_num))
                 }
             }
         }
     }
 }
 
+/// Whether encoded is the encoding of raw, either the current one or the one of earlier releases
+/// which kept any value parsing as an unsigned 32 bit integer as it was.
+pub fn matches_encoding(raw: &str, encoded: &str) -> VcxResult<bool> {
+    if raw.parse::<u32>().is_ok() && raw == encoded {
+        return Ok(true);
+    }
+    Ok(encode(raw)? == encoded)
+}
 
 #[cfg(test)]
 mod test{
@@ -56,6 +70,30 @@ mod test{
         assert_eq!(number_as_string, encode(number_as_string).unwrap());
     }
 
+    #[test]
+    fn test_encoding_int32_bounds(){
+        assert_eq!(encode("-2147483648").unwrap(), "-2147483648");
+        assert_eq!(encode("2147483647").unwrap(), "2147483647");
+        assert_eq!(encode("0").unwrap(), "0");
+
+        // out of range numbers and decimals are hashed like any other string
+        assert_eq!(encode("2147483648").unwrap(), "26221484005389514539852548961319751347124425277437769688639924217837557266135");
+        assert_eq!(encode("1.5").unwrap(), "71991296136747855077697001202532249706619088658469249105695717234028982732581");
+    }
+
+    #[test]
+    fn test_matches_encoding(){
+        assert!(matches_encoding("Cat", "32770349619296211525721019403974704547883091481854305319049714074652726739013").unwrap());
+        assert!(matches_encoding("2147483647", "2147483647").unwrap());
+        assert!(matches_encoding("2147483648", "26221484005389514539852548961319751347124425277437769688639924217837557266135").unwrap());
+
+        // earlier releases did not hash unsigned 32 bit integers
+        assert!(matches_encoding("2147483648", "2147483648").unwrap());
+        assert!(matches_encoding("4294967295", "4294967295").unwrap());
+        assert!(!matches_encoding("4294967296", "4294967296").unwrap());
+        assert!(!matches_encoding("Cat", "Cat").unwrap());
+    }
+
 
 
 }
\ No newline at end of file
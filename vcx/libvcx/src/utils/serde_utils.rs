@@ -1,14 +1,15 @@
 extern crate serde_json;
 
 use ::utils::error;
+use error::{ VcxError, VcxResult };
 use serde_json::{Value};
 
-pub fn get_value_to_string(key: &str, map: &Value) -> Result<String, u32> {
+pub fn get_value_to_string(key: &str, map: &Value) -> VcxResult<String> {
     Ok(
         map.get(key)
-            .ok_or(error::INVALID_JSON.code_num)?
+            .ok_or(VcxError::from_code(error::INVALID_JSON.code_num))?
             .as_str()
-            .ok_or(error::INVALID_JSON.code_num)?
+            .ok_or(VcxError::from_code(error::INVALID_JSON.code_num))?
             .to_string()
     )
 }
@@ -0,0 +1,126 @@
+extern crate serde_json;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use utils::constants::DEFAULT_SERIALIZE_VERSION;
+use utils::error;
+use error::{ VcxError, VcxResult };
+
+// Serialized objects are wrapped as {"version": <version>, "data": <object>}. Every field added to
+// a serialized struct since then has a serde default, so the data of earlier releases, including
+// the bare structs written before the envelope, is read by the current structs as is. Data written
+// by a newer release is refused instead of being half understood.
+
+/// Version of data serialized before objects were wrapped, it is the bare struct.
+pub static LEGACY_VERSION: &str = "0.0";
+
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Version written by this release.
+    fn version() -> &'static str { DEFAULT_SERIALIZE_VERSION }
+}
+
+pub fn serialize<T: Versioned>(object: &T) -> String {
+    json!({
+        "version": T::version(),
+        "data": object,
+    }).to_string()
+}
+
+// Legacy structs may have their own version and data fields, the envelope has nothing else
+fn unwrap(value: Value) -> VcxResult<(String, Value)> {
+    match value {
+        Value::Object(mut map) => {
+            let is_envelope = map.len() == 2 && map.contains_key("data") && map.contains_key("version");
+            if !is_envelope {
+                return Ok((LEGACY_VERSION.to_string(), Value::Object(map)));
+            }
+
+            let version = match map.remove("version") {
+                Some(Value::String(version)) => version,
+                version => return Err(VcxError::from_msg(error::INVALID_JSON.code_num,
+                                                         format!("invalid serialization version: {:?}", version))),
+            };
+            Ok((version, map.remove("data").unwrap_or(Value::Null)))
+        },
+        _ => Err(VcxError::from_msg(error::INVALID_JSON.code_num, "serialized object is not a json object")),
+    }
+}
+
+/// Parses data serialized by this or an earlier release.
+pub fn deserialize<T: Versioned>(data: &str) -> VcxResult<T> {
+    let value: Value = serde_json::from_str(data)
+        .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num, format!("cannot parse serialized object: {}", err)))?;
+
+    let (version, data) = unwrap(value)?;
+
+    if version != LEGACY_VERSION && version != T::version() {
+        return Err(VcxError::from_msg(error::UNKNOWN_SERIALIZE_VERSION.code_num,
+                                      format!("cannot read serialized object of version {}, the current version is {}", version, T::version())));
+    }
+
+    serde_json::from_value(data)
+        .map_err(|err| VcxError::from_msg(error::INVALID_JSON.code_num,
+                                          format!("serialized object does not match version {}: {}", version, err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Current {
+        name: String,
+        attrs: Vec<String>,
+    }
+
+    impl Versioned for Current {}
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Extended {
+        name: String,
+        attrs: Vec<String>,
+        #[serde(default)]
+        revocable: bool,
+    }
+
+    impl Versioned for Extended {}
+
+    fn current() -> Current {
+        Current { name: "name".to_string(), attrs: vec!["zip".to_string(), "age".to_string()] }
+    }
+
+    #[test]
+    fn test_serialize_wraps_object() {
+        let serialized = serialize(&current());
+        let value: Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(value["version"], json!(DEFAULT_SERIALIZE_VERSION));
+        assert_eq!(value["data"]["name"], json!("name"));
+
+        assert_eq!(deserialize::<Current>(&serialized).unwrap(), current());
+    }
+
+    #[test]
+    fn test_legacy_data_is_read() {
+        let legacy = serde_json::to_string(&current()).unwrap();
+        assert_eq!(deserialize::<Current>(&legacy).unwrap(), current());
+    }
+
+    #[test]
+    fn test_added_fields_get_their_default() {
+        let expected = Extended { name: "name".to_string(), attrs: current().attrs, revocable: false };
+
+        assert_eq!(deserialize::<Extended>(&serialize(&current())).unwrap(), expected);
+        assert_eq!(deserialize::<Extended>(&serde_json::to_string(&current()).unwrap()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_unknown_version_fails() {
+        let newer = json!({"version": "4.0", "data": current()}).to_string();
+        assert_eq!(deserialize::<Current>(&newer).unwrap_err().code(), error::UNKNOWN_SERIALIZE_VERSION.code_num);
+
+        assert_eq!(deserialize::<Current>("").unwrap_err().code(), error::INVALID_JSON.code_num);
+        assert_eq!(deserialize::<Current>("[]").unwrap_err().code(), error::INVALID_JSON.code_num);
+        assert_eq!(deserialize::<Current>("{}").unwrap_err().code(), error::INVALID_JSON.code_num);
+    }
+}
@@ -35,9 +35,17 @@ pub fn init() {
     }
 }
 
+/// Runs the job on the threadpool, in the context of the calling thread. The job starts
+/// without the error a previous job on the same thread handed out.
 pub fn spawn<F>(future: F)
 where
     F: FnOnce() -> Result<(), ()> + Send + 'static {
+        let context = ::context::current();
+        let future = move || {
+            ::error::reset_current_error();
+            ::context::with_context(context, future)
+        };
+
         let handle;
         unsafe { handle = TP_HANDLE; }
         if ::settings::get_threadpool_size() == 0 || handle == 0{
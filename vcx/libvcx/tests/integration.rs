@@ -2,6 +2,9 @@ extern crate vcx;
 extern crate serde;
 extern crate rand;
 extern crate indy;
+#[cfg(feature = "mock_agency")]
+#[macro_use]
+extern crate lazy_static;
 
 #[macro_use]
 extern crate serde_json;
@@ -85,6 +88,29 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "mock_agency")]
+    fn provision_mock_agent(agency: &vcx::utils::mock_agency::MockAgency, wallet_name: &str, name: &str) -> String {
+        let config = json!({
+            "wallet_name": wallet_name,
+            "wallet_key": settings::DEFAULT_WALLET_KEY,
+            "agency_url": agency.endpoint(),
+            "agency_did": agency.did(),
+            "agency_verkey": agency.verkey(),
+        });
+        let config = CStringUtils::string_to_cstring(config.to_string());
+        let cb = return_types_u32::Return_U32_STR::new().unwrap();
+        vcx_agent_provision_async(cb.command_handle, config.as_ptr(), Some(cb.get_callback()));
+        let vcx_config = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+        let mut vcx_config:serde_json::Value = serde_json::from_str(&vcx_config).unwrap();
+        let vcx_config = vcx_config.as_object_mut().unwrap();
+        vcx_config.insert( "institution_logo_url".to_string(), json!("https://robohash.org/hankhill"));
+        vcx_config.insert( "institution_name".to_string(), json!(name));
+        // no ledger is needed with the mock agency, what the ledger would return is cached instead
+        vcx_config.remove("genesis_path");
+        vcx_config.insert( "ledger_cache_ttl_secs".to_string(), json!("3600"));
+        serde_json::to_string(&vcx_config).unwrap()
+    }
+
     fn delete_indy_client(){
         use std::fs::remove_dir_all;
         use std::env::home_dir;
@@ -192,6 +218,240 @@ mod tests {
         vcx_shutdown(true);
     }
 
+    #[cfg(feature = "mock_agency")]
+    lazy_static! {
+        // the parties share the global settings, so only one mock agency test runs at a time
+        static ref MOCK_AGENCY_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    }
+
+    /// An institution and a consumer provisioned against one MockAgency and connected to each
+    /// other. Only one of them is initialized at a time, the connections are kept serialized.
+    #[cfg(feature = "mock_agency")]
+    struct MockParties {
+        institution_config: String,
+        consumer_config: String,
+        alice: String,
+        faber: String,
+        ledger: Vec<(vcx::ledger_cache::LedgerObject, (String, String))>,
+    }
+
+    #[cfg(feature = "mock_agency")]
+    impl MockParties {
+        fn connect(agency: &vcx::utils::mock_agency::MockAgency, prefix: &str) -> MockParties {
+            use vcx::api::connection::*;
+
+            let institution_config = provision_mock_agent(agency, &format!("{}_institution_wallet", prefix), "Faber");
+            let consumer_config = provision_mock_agent(agency, &format!("{}_consumer_wallet", prefix), "Alice");
+
+            // AS INSTITUTION CREATE AN INVITE
+            init_vcx(&institution_config).unwrap();
+            let cb = return_types_u32::Return_U32_U32::new().unwrap();
+            assert_eq!(vcx_connection_create(cb.command_handle, CString::new("alice").unwrap().as_ptr(), Some(cb.get_callback())), error::SUCCESS.code_num);
+            let alice = cb.receive(Some(Duration::from_secs(10))).unwrap();
+            let cb = return_types_u32::Return_U32_STR::new().unwrap();
+            assert_eq!(vcx_connection_connect(cb.command_handle, alice, CString::new("{}").unwrap().as_ptr(), Some(cb.get_callback())), error::SUCCESS.code_num);
+            cb.receive(Some(Duration::from_secs(10))).unwrap();
+            let cb = return_types_u32::Return_U32_STR::new().unwrap();
+            assert_eq!(vcx_connection_invite_details(cb.command_handle, alice, 0, Some(cb.get_callback())), error::SUCCESS.code_num);
+            let details = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+            let cb = return_types_u32::Return_U32_STR::new().unwrap();
+            assert_eq!(vcx_connection_serialize(cb.command_handle, alice, Some(cb.get_callback())), error::SUCCESS.code_num);
+            let serialized_alice = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+            vcx_shutdown(false);
+
+            // AS CONSUMER ACCEPT THE INVITE
+            init_vcx(&consumer_config).unwrap();
+            let cb = return_types_u32::Return_U32_U32::new().unwrap();
+            assert_eq!(vcx_connection_create_with_invite(cb.command_handle,
+                                                         CString::new("faber").unwrap().as_ptr(),
+                                                         CString::new(details).unwrap().as_ptr(),
+                                                         Some(cb.get_callback())), error::SUCCESS.code_num);
+            let faber = cb.receive(Some(Duration::from_secs(10))).unwrap();
+            let cb = return_types_u32::Return_U32_STR::new().unwrap();
+            assert_eq!(vcx_connection_connect(cb.command_handle, faber, CString::new("{}").unwrap().as_ptr(), Some(cb.get_callback())), error::SUCCESS.code_num);
+            cb.receive(Some(Duration::from_secs(10))).unwrap();
+            let cb = return_types_u32::Return_U32_STR::new().unwrap();
+            assert_eq!(vcx_connection_serialize(cb.command_handle, faber, Some(cb.get_callback())), error::SUCCESS.code_num);
+            let serialized_faber = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+            vcx_shutdown(false);
+
+            // AS INSTITUTION CHECK THAT THE INVITE WAS ACCEPTED
+            init_vcx(&institution_config).unwrap();
+            let cb = return_types_u32::Return_U32_U32::new().unwrap();
+            assert_eq!(vcx_connection_deserialize(cb.command_handle, CString::new(serialized_alice).unwrap().as_ptr(), Some(cb.get_callback())), error::SUCCESS.code_num);
+            let alice = cb.receive(Some(Duration::from_secs(10))).unwrap();
+            let cb = return_types_u32::Return_U32_U32::new().unwrap();
+            assert_eq!(vcx_connection_update_state(cb.command_handle, alice, Some(cb.get_callback())), error::SUCCESS.code_num);
+            assert_eq!(cb.receive(Some(Duration::from_secs(10))).unwrap(), 4);
+            let cb = return_types_u32::Return_U32_STR::new().unwrap();
+            assert_eq!(vcx_connection_serialize(cb.command_handle, alice, Some(cb.get_callback())), error::SUCCESS.code_num);
+            let serialized_alice = cb.receive(Some(Duration::from_secs(10))).unwrap().unwrap();
+            vcx_shutdown(false);
+
+            MockParties {
+                institution_config,
+                consumer_config,
+                alice: serialized_alice,
+                faber: serialized_faber,
+                ledger: Vec::new(),
+            }
+        }
+
+        fn activate(&self, config: &str) {
+            vcx_shutdown(false);
+            init_vcx(config).unwrap();
+            for &(object, ref id_json) in self.ledger.iter() {
+                vcx::ledger_cache::put(object, &id_json.0, id_json);
+            }
+        }
+
+        /// Initializes the institution and returns its connection to the consumer.
+        fn as_institution(&self) -> u32 {
+            self.activate(&self.institution_config);
+            vcx::connection::from_string(&self.alice).unwrap()
+        }
+
+        /// Initializes the consumer and returns its connection to the institution.
+        fn as_consumer(&self) -> u32 {
+            self.activate(&self.consumer_config);
+            vcx::connection::from_string(&self.faber).unwrap()
+        }
+
+        /// Makes a schema or credential definition readable as if it was written to the ledger.
+        fn publish(&mut self, object: vcx::ledger_cache::LedgerObject, id: &str, json: &str) {
+            let id_json = (id.to_string(), json.to_string());
+            vcx::ledger_cache::put(object, id, &id_json);
+            self.ledger.push((object, id_json));
+        }
+
+        fn delete_wallets(&self) {
+            vcx_shutdown(false);
+            init_vcx(&self.consumer_config).unwrap();
+            vcx_shutdown(true);
+            init_vcx(&self.institution_config).unwrap();
+            vcx_shutdown(true);
+        }
+    }
+
+    /// Issues a credential with name and age to the consumer, returns its cred_def_id and the
+    /// handle of the credential, with the consumer left initialized.
+    #[cfg(feature = "mock_agency")]
+    fn issue_mock_credential(parties: &mut MockParties) -> (String, u32) {
+        use vcx::ledger_cache::LedgerObject;
+        use vcx::utils::libindy::anoncreds;
+        use vcx::api::VcxStateType;
+        use vcx::{ issuer_credential, credential };
+
+        // AS INSTITUTION CREATE THE CREDENTIAL DEFINITION AND SEND AN OFFER
+        let alice = parties.as_institution();
+        let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+        let (schema_id, schema_json) = anoncreds::libindy_issuer_create_schema(&did, "licence", "1.0", r#"["name","age"]"#).unwrap();
+        let (cred_def_id, cred_def_json) = anoncreds::libindy_create_and_store_credential_def(&did, &schema_json, "tag1", None, r#"{"support_revocation":false}"#).unwrap();
+        parties.publish(LedgerObject::Schema, &schema_id, &schema_json);
+        parties.publish(LedgerObject::CredDef, &cred_def_id, &cred_def_json);
+
+        let credential_data = json!({"name": ["Alice"], "age": ["28"]}).to_string();
+        let offer = issuer_credential::issuer_credential_create(cred_def_id.clone(), "1".to_string(), did, "licence".to_string(), credential_data, 0).unwrap();
+        issuer_credential::send_credential_offer(offer, alice).unwrap();
+        let offer = issuer_credential::to_string(offer).unwrap();
+
+        // AS CONSUMER SEND A CREDENTIAL REQUEST
+        let faber = parties.as_consumer();
+        let offers: serde_json::Value = serde_json::from_str(&credential::get_credential_offer_messages(faber).unwrap()).unwrap();
+        let credential = credential::credential_create_with_offer("licence", &serde_json::to_string(&offers[0]).unwrap()).unwrap();
+        credential::send_credential_request(credential, faber).unwrap();
+        let credential = credential::to_string(credential).unwrap();
+
+        // AS INSTITUTION SEND THE CREDENTIAL
+        let alice = parties.as_institution();
+        let offer = issuer_credential::from_string(&offer).unwrap();
+        issuer_credential::update_state(offer).unwrap();
+        assert_eq!(issuer_credential::get_state(offer).unwrap(), VcxStateType::VcxStateRequestReceived as u32);
+        issuer_credential::send_credential(offer, alice).unwrap();
+
+        // AS CONSUMER STORE THE CREDENTIAL
+        parties.as_consumer();
+        let credential = credential::from_string(&credential).unwrap();
+        credential::update_state(credential).unwrap();
+        assert_eq!(credential::get_state(credential).unwrap(), VcxStateType::VcxStateAccepted as u32);
+
+        (cred_def_id, credential)
+    }
+
+    #[cfg(feature = "mock_agency")]
+    fn with_mock_agency<F>(prefix: &str, test: F) where F: FnOnce(&mut MockParties) {
+        use std::sync::Arc;
+        use vcx::utils::httpclient;
+        use vcx::utils::mock_agency::MockAgency;
+
+        let _lock = MOCK_AGENCY_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+        settings::set_defaults();
+        settings::set_config_value(settings::CONFIG_ENABLE_TEST_MODE, "false");
+        settings::set_config_value(settings::CONFIG_WALLET_KEY, settings::DEFAULT_WALLET_KEY);
+
+        let agency = Arc::new(MockAgency::new().unwrap());
+        httpclient::set_transport(agency.clone());
+
+        let mut parties = MockParties::connect(&agency, prefix);
+        test(&mut parties);
+        parties.delete_wallets();
+
+        httpclient::reset_transport();
+    }
+
+    #[cfg(feature = "mock_agency")]
+    #[test]
+    fn test_connection_with_mock_agency() {
+        with_mock_agency("mock_agency_connection", |parties| {
+            parties.as_institution();
+            parties.as_consumer();
+        });
+    }
+
+    #[cfg(feature = "mock_agency")]
+    #[test]
+    fn test_credential_with_mock_agency() {
+        with_mock_agency("mock_agency_credential", |parties| {
+            let (_, credential) = issue_mock_credential(parties);
+
+            let credential = vcx::credential::get_credential(credential).unwrap();
+            assert!(credential.contains("Alice"));
+        });
+    }
+
+    #[cfg(feature = "mock_agency")]
+    #[test]
+    fn test_proof_with_mock_agency() {
+        use vcx::api::ProofStateType;
+        use vcx::{ proof, disclosed_proof };
+
+        with_mock_agency("mock_agency_proof", |parties| {
+            let (cred_def_id, _) = issue_mock_credential(parties);
+
+            // AS INSTITUTION SEND A PROOF REQUEST
+            let alice = parties.as_institution();
+            let requested_attrs = json!([{"name": "name", "restrictions": [{"cred_def_id": cred_def_id}]}]).to_string();
+            let proof = proof::create_proof("1".to_string(), requested_attrs, "[]".to_string(), "{}".to_string(), "licence".to_string()).unwrap();
+            proof::send_proof_request(proof, alice).unwrap();
+            let proof = proof::to_string(proof).unwrap();
+
+            // AS CONSUMER SEND THE PROOF
+            let faber = parties.as_consumer();
+            let requests: serde_json::Value = serde_json::from_str(&disclosed_proof::get_proof_request_messages(faber, None).unwrap()).unwrap();
+            let disclosed = disclosed_proof::create_proof("licence", &serde_json::to_string(&requests[0]).unwrap()).unwrap();
+            let selection: serde_json::Value = serde_json::from_str(&disclosed_proof::select_credentials(disclosed, "{}".to_string()).unwrap()).unwrap();
+            assert_eq!(selection["unsatisfied"], json!([]));
+            disclosed_proof::generate_proof(disclosed, selection["selected_credentials"].to_string(), "{}".to_string()).unwrap();
+            disclosed_proof::send_proof(disclosed, faber).unwrap();
+
+            // AS INSTITUTION VALIDATE THE PROOF
+            parties.as_institution();
+            let proof = proof::from_string(&proof).unwrap();
+            proof::update_state(proof).unwrap();
+            assert_eq!(proof::get_proof_state(proof).unwrap(), ProofStateType::ProofValidated as u32);
+        });
+    }
+
     #[cfg(feature = "agency")]
     #[cfg(feature = "sovtoken")]
     #[cfg(feature = "pool_tests")]
@@ -354,11 +354,13 @@ pub fn create_proof_request(source_id: &str, requested_attrs: &str) -> (u32, u32
     let cb = Box::new(move|err, credential_handle|{sender.send((err, credential_handle)).unwrap();});
     let (command_handle, cb) = closure_to_create_credential(cb);
     let predicates_cstring = CString::new("[]").unwrap();
+    let revocation_interval_cstring = CString::new("{}").unwrap();
     let proof_name_cstring = CString::new("proof name").unwrap();
     let rc = api::proof::vcx_proof_create(command_handle,
                                           source_id_cstring.as_ptr(),
                                           requested_attrs.as_ptr(),
                                           predicates_cstring.as_ptr(),
+                                          revocation_interval_cstring.as_ptr(),
                                           proof_name_cstring.as_ptr(),
                                           cb);
     assert_eq!(rc, 0);
@@ -587,7 +587,7 @@ void VcxWrapperCommonNumberStringCallback(vcx_command_handle_t xcommand_handle,
     const char *requestedAttrs_char = [requestedAttrs cString];
     const char *requestedPredicates_char = [requestedPredicates cString];
     const char *proofName_char = [proofName cString];
-    ret = vcx_proof_create(handle, proofRequestId_char, requestedAttrs_char, requestedPredicates_char, proofName_char, VcxWrapperCommonStringCallback);
+    ret = vcx_proof_create(handle, proofRequestId_char, requestedAttrs_char, requestedPredicates_char, "{}", proofName_char, VcxWrapperCommonStringCallback);
 
     if ( ret != 0 )
     {
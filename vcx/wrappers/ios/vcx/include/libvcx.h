@@ -20,8 +20,16 @@ typedef enum {
   undefined = 0,
   validated = 1,
   invalid = 2,
+  tampered = 3,
 } vcx_proof_state_t;
 
+typedef enum {
+  invite_json = 0,
+  invite_abbreviated = 1,
+  invite_url = 2,
+  invite_qr_code = 3,
+} vcx_invite_format_t;
+
 typedef unsigned int vcx_error_t;
 typedef unsigned int vcx_schema_handle_t;
 typedef unsigned int vcx_credentialdef_handle_t;
@@ -140,7 +148,7 @@ vcx_error_t vcx_connection_get_state(vcx_command_handle_t command_handle, vcx_co
 vcx_error_t vcx_connection_release(vcx_connection_handle_t connection_handle);
 
 /** Get the invite details for the connection. */
-vcx_error_t vcx_connection_invite_details(vcx_command_handle_t command_handle, vcx_connection_handle_t connection_handle, int abbreviated, void (*cb)(vcx_command_handle_t xcommand_handle, vcx_error_t err, const char *details));
+vcx_error_t vcx_connection_invite_details(vcx_command_handle_t command_handle, vcx_connection_handle_t connection_handle, vcx_invite_format_t format, void (*cb)(vcx_command_handle_t xcommand_handle, vcx_error_t err, const char *details));
 
 /** Creates a connection from the invite details. */
 vcx_error_t vcx_connection_create_with_invite(vcx_command_handle_t command_handle, const char *source_id, const char *invite_details, void (*cb)(vcx_command_handle_t xcommand_handle, vcx_error_t err, vcx_connection_handle_t connection_handle));
@@ -194,7 +202,7 @@ vcx_error_t vcx_issuer_accept_credential(vcx_credential_handle_t credential_hand
  */
 
 /** Creates a proof object.  Populates a handle to the new proof. */
-vcx_error_t vcx_proof_create(vcx_command_handle_t command_handle, const char *source_id, const char *requested_attrs, const char *requested_predicates, const char *name, void (*cb)(vcx_command_handle_t command_handle, vcx_error_t err, vcx_proof_handle_t proof_handle));
+vcx_error_t vcx_proof_create(vcx_command_handle_t command_handle, const char *source_id, const char *requested_attrs, const char *requested_predicates, const char *revocation_interval, const char *name, void (*cb)(vcx_command_handle_t command_handle, vcx_error_t err, vcx_proof_handle_t proof_handle));
     
 /** Asynchronously send a proof request to the connection. */
 vcx_error_t vcx_proof_send_request(vcx_command_handle_t command_handle, vcx_proof_handle_t proof_handle, vcx_connection_handle_t connection_handle, void (*cb)(vcx_command_handle_t xcommand_handle, vcx_error_t err));
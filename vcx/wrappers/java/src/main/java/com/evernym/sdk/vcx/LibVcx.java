@@ -121,9 +121,9 @@ public abstract class LibVcx {
         public int vcx_connection_release(int connection_handle);
 
         /**
-         * Get the invite details for the connection.
+         * Get the invite details for the connection in the given InviteFormat.
          */
-        public int vcx_connection_invite_details(int command_handle, int connection_handle, int abbreviated, Callback cb);
+        public int vcx_connection_invite_details(int command_handle, int connection_handle, int format, Callback cb);
 
         /**
          * Creates a connection from the invite details.
@@ -185,7 +185,7 @@ public abstract class LibVcx {
         /**
          * Creates a proof object.  Populates a handle to the new proof.
          */
-        public int vcx_proof_create(int command_handle, String source_id, String requested_attrs, String requested_predicates, String name, Callback cb);
+        public int vcx_proof_create(int command_handle, String source_id, String requested_attrs, String requested_predicates, String revocation_interval, String name, Callback cb);
 
         /**
          * Asynchronously send a proof request to the connection.
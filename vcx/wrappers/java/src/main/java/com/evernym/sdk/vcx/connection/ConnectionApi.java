@@ -246,11 +246,12 @@ public class ConnectionApi extends VcxJava.API {
         }
     };
 
-    public static CompletableFuture<String> connectionInviteDetails(int connectionHandle, int abbreviated) throws VcxException {
-        logger.debug("connectionInviteDetails() called with: connectionHandle = [" + connectionHandle + "], abbreviated = [" + abbreviated + "]");
+    public static CompletableFuture<String> connectionInviteDetails(int connectionHandle, InviteFormat format) throws VcxException {
+        ParamGuard.notNull(format, "format");
+        logger.debug("connectionInviteDetails() called with: connectionHandle = [" + connectionHandle + "], format = [" + format + "]");
         CompletableFuture<String> future = new CompletableFuture<>();
         int commandHandle = addFuture(future);
-        int result = LibVcx.api.vcx_connection_invite_details(commandHandle, connectionHandle, abbreviated, vcxConnectionInviteDetailsCB);
+        int result = LibVcx.api.vcx_connection_invite_details(commandHandle, connectionHandle, format.value(), vcxConnectionInviteDetailsCB);
         checkResult(result);
         return future;
     }
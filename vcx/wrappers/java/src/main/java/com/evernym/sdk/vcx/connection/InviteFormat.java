@@ -0,0 +1,24 @@
+package com.evernym.sdk.vcx.connection;
+
+/**
+ * How connectionInviteDetails renders the invitation.
+ * URL and QR_CODE hold a connection invitation, which carries the VCX only "~legacy" decorator
+ * that other VCX instances need to accept it through their agency. Other agents ignore it.
+ */
+public enum InviteFormat {
+
+    JSON(0),
+    ABBREVIATED(1),
+    URL(2),
+    QR_CODE(3);
+
+    private int value;
+
+    private InviteFormat(int value) {
+        this.value = value;
+    }
+
+    public int value() {
+        return value;
+    }
+}
@@ -12,6 +12,9 @@ public class GetProofResult {
         this.response_data = response_data;
     }
 
+    /**
+     * One of the {@link ProofState} values.
+     */
     public int getProof_state() {
         return proof_state;
     }
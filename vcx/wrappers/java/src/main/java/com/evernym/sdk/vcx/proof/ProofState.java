@@ -0,0 +1,32 @@
+package com.evernym.sdk.vcx.proof;
+
+/**
+ * The proof state reported by getProof.
+ * TAMPERED means a revealed value is not the value the issuer signed.
+ */
+public enum ProofState {
+
+    UNDEFINED(0),
+    VALIDATED(1),
+    INVALID(2),
+    TAMPERED(3);
+
+    private int value;
+
+    private ProofState(int value) {
+        this.value = value;
+    }
+
+    public int value() {
+        return value;
+    }
+
+    public static ProofState valueOf(int value) {
+        for (ProofState state : values()) {
+            if (state.value == value) {
+                return state;
+            }
+        }
+        throw new IllegalArgumentException("Unknown proof state: " + value);
+    }
+}
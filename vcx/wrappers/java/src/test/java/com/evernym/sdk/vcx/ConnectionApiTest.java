@@ -3,6 +3,7 @@ package com.evernym.sdk.vcx;
 
 import com.evernym.sdk.vcx.connection.ConnectionApi;
 import com.evernym.sdk.vcx.connection.InvalidConnectionHandleException;
+import com.evernym.sdk.vcx.connection.InviteFormat;
 import com.evernym.sdk.vcx.vcx.VcxApi;
 import java9.util.concurrent.CompletableFuture;
 import org.awaitility.Awaitility;
@@ -172,7 +173,7 @@ class ConnectionApiTest {
         int connectionHandle = _createConnection();
         CompletableFuture<String> acceptInvitation = ConnectionApi.vcxConnectionConnect(connectionHandle,TestHelper.convertToValidJson(payload));
         Awaitility.await().until(acceptInvitation::isDone);
-        CompletableFuture<String> detials = ConnectionApi.connectionInviteDetails(connectionHandle,1);
+        CompletableFuture<String> detials = ConnectionApi.connectionInviteDetails(connectionHandle, InviteFormat.ABBREVIATED);
         Awaitility.await().until(detials::isDone);
         assert(detials.get().contains("dp"));
 
@@ -185,11 +186,26 @@ class ConnectionApiTest {
         int connectionHandle = _createConnection();
         CompletableFuture<String> acceptInvitation = ConnectionApi.vcxConnectionConnect(connectionHandle,TestHelper.convertToValidJson(payload));
         Awaitility.await().until(acceptInvitation::isDone);
-        CompletableFuture<String> detials = ConnectionApi.connectionInviteDetails(connectionHandle,0);
+        CompletableFuture<String> detials = ConnectionApi.connectionInviteDetails(connectionHandle, InviteFormat.JSON);
         Awaitility.await().until(detials::isDone);
         assert(detials.get().contains("senderAgencyDetail"));
 
     }
 
+    @Test
+    @DisplayName("get invite details as url and connection invitation")
+    void inviteDetailsInvitationConnection() throws VcxException, ExecutionException, InterruptedException {
+        String payload= "{ 'connection_type': 'SMS', 'phone':'7202200000' }";
+        int connectionHandle = _createConnection();
+        CompletableFuture<String> acceptInvitation = ConnectionApi.vcxConnectionConnect(connectionHandle,TestHelper.convertToValidJson(payload));
+        Awaitility.await().until(acceptInvitation::isDone);
+        CompletableFuture<String> url = ConnectionApi.connectionInviteDetails(connectionHandle, InviteFormat.URL);
+        Awaitility.await().until(url::isDone);
+        assert(url.get().contains("?c_i="));
+        CompletableFuture<String> invitation = ConnectionApi.connectionInviteDetails(connectionHandle, InviteFormat.QR_CODE);
+        Awaitility.await().until(invitation::isDone);
+        assert(invitation.get().contains("recipientKeys"));
+    }
+
 
 }
@@ -33,6 +33,18 @@ export interface IRecipientInviteInfo extends IConnectionCreateData {
   invite: IConnectionInvite
 }
 
+/**
+ * @description How inviteDetails renders the invitation.
+ * Url and QrCode hold a connection invitation, which carries the VCX only "~legacy" decorator
+ * that other VCX instances need to accept it through their agency. Other agents ignore it.
+ */
+export enum InviteFormat {
+  Json = 0,
+  Abbreviated = 1,
+  Url = 2,
+  QrCode = 3
+}
+
 export interface IConnectOptions {
   phone?: string
 }
@@ -188,13 +200,16 @@ export class Connection extends VCXBaseWithState<IConnectionData> {
    * connection = await Connection.create('foobar123')
    * inviteDetails = await connection.connect({phone: phoneNumber})
    * inivteDetailsAgain = await connection.inviteDetails()
+   * invitationUrl = await connection.inviteDetails(InviteFormat.Url)
    * ```
+   * A boolean is still accepted for the former abbreviated flag.
    */
-  public async inviteDetails (abbr: boolean = false): Promise<IConnectionInvite> {
+  public async inviteDetails (format: InviteFormat | boolean = InviteFormat.Json): Promise<IConnectionInvite> {
+    const inviteFormat = typeof format === 'boolean' ? (format ? InviteFormat.Abbreviated : InviteFormat.Json) : format
     try {
       const data = await createFFICallbackPromise<string>(
         (resolve, reject, cb) => {
-          const rc = this._inviteDetailFn(0, this.handle, abbr, cb)
+          const rc = this._inviteDetailFn(0, this.handle, inviteFormat, cb)
           if (rc) {
             reject(rc)
           }
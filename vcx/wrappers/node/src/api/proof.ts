@@ -10,6 +10,17 @@ export interface IProofCreateData {
   sourceId: string,
   attrs: IProofAttr[],
   name: string,
+  revocationInterval?: IRevocationInterval,
+}
+
+/**
+ * @description Interval in which the prover must show the credentials were not revoked.
+ * Times are unix timestamps in seconds.
+ * @interface
+ */
+export interface IRevocationInterval {
+  from?: number,
+  to?: number,
 }
 
 export interface IProofConstructorData {
@@ -59,6 +70,7 @@ export enum PredicateTypes {
 export interface IProofAttr {
   restrictions?: IFilter[],
   name: string,
+  non_revoked?: IRevocationInterval,
 }
 
 export interface IFilter {
@@ -73,7 +85,8 @@ export interface IFilter {
 export enum ProofState {
   Undefined = 0,
   Verified = 1,
-  Invalid = 2
+  Invalid = 2,
+  Tampered = 3
 }
 
 export interface IProofPredicate {
@@ -111,6 +124,7 @@ export class Proof extends VCXBaseWithState<IProofData> {
         proof.sourceId,
         JSON.stringify(createDataRest.attrs),
         JSON.stringify([]),
+        JSON.stringify(createDataRest.revocationInterval || {}),
         createDataRest.name,
         cb
       ))
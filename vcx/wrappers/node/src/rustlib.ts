@@ -84,7 +84,7 @@ export interface IFFIEntryPoint {
   vcx_connection_serialize: (commandId: number, handle: string, cb: any) => number,
   vcx_connection_update_state: (commandId: number, handle: string, cb: any) => number,
   vcx_connection_get_state: (commandId: number, handle: string, cb: any) => number,
-  vcx_connection_invite_details: (commandId: number, handle: string, abbreviated: boolean, cb: any) => number,
+  vcx_connection_invite_details: (commandId: number, handle: string, format: number, cb: any) => number,
 
   // issuer
   vcx_issuer_credential_release: (handle: string) => number,
@@ -102,7 +102,7 @@ export interface IFFIEntryPoint {
 
   // proof
   vcx_proof_create: (commandId: number, sourceId: string, attrs: string, predicates: string,
-                     name: string, cb: any) => number,
+                     revocationInterval: string, name: string, cb: any) => number,
   vcx_proof_deserialize: (commandId: number, data: string, cb: any) => number,
   vcx_get_proof: (commandId: number, proofHandle: string, connectionHandle: string, cb: any) => number,
   vcx_proof_release: (handle: string) => number,
@@ -219,7 +219,7 @@ export const FFIConfiguration: { [ Key in keyof IFFIEntryPoint ]: any } = {
   vcx_connection_serialize: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_CONNECTION_HANDLE, FFI_CALLBACK_PTR]],
   vcx_connection_update_state: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_CONNECTION_HANDLE, FFI_CALLBACK_PTR]],
   vcx_connection_get_state: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_CONNECTION_HANDLE, FFI_CALLBACK_PTR]],
-  vcx_connection_invite_details: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_CONNECTION_HANDLE, FFI_BOOL,
+  vcx_connection_invite_details: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_CONNECTION_HANDLE, FFI_UNSIGNED_INT,
     FFI_CALLBACK_PTR]],
 
   // issuer
@@ -238,7 +238,7 @@ export const FFIConfiguration: { [ Key in keyof IFFIEntryPoint ]: any } = {
 
   // proof
   vcx_proof_create: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_SOURCE_ID, FFI_STRING_DATA, FFI_STRING_DATA,
-    FFI_STRING_DATA, FFI_CALLBACK_PTR]],
+    FFI_STRING_DATA, FFI_STRING_DATA, FFI_CALLBACK_PTR]],
   vcx_proof_deserialize: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_STRING_DATA, FFI_CALLBACK_PTR]],
   vcx_get_proof: [FFI_ERROR_CODE, [FFI_COMMAND_HANDLE, FFI_PROOF_HANDLE, FFI_CONNECTION_HANDLE,
     FFI_CALLBACK_PTR]],// tslint:disable-line
@@ -5,7 +5,7 @@ import { connectionCreate, connectionCreateConnect, dataConnectionCreate } from
 import { gcTest } from 'helpers/gc'
 import { TIMEOUT_GC } from 'helpers/test-constants'
 import { initVcxTestMode, shouldThrow, sleep } from 'helpers/utils'
-import { Connection, rustAPI, StateType, VCXCode, VCXMock, VCXMockMessage } from 'src'
+import { Connection, InviteFormat, rustAPI, StateType, VCXCode, VCXMock, VCXMockMessage } from 'src'
 
 describe('Connection:', () => {
   before(() => initVcxTestMode())
@@ -171,6 +171,21 @@ describe('Connection:', () => {
       const details = await connection.inviteDetails()
       assert.include(details, '"senderAgencyDetail":')
     })
+
+    it('success: with format', async () => {
+      const connection = await connectionCreateConnect()
+      assert.include(await connection.inviteDetails(InviteFormat.Json), '"senderAgencyDetail":')
+      assert.include(await connection.inviteDetails(InviteFormat.Abbreviated), '"dp":')
+      assert.include(await connection.inviteDetails(InviteFormat.Url), '?c_i=')
+      const invitation = JSON.parse(await connection.inviteDetails(InviteFormat.QrCode))
+      assert.ok(invitation.recipientKeys.length)
+    })
+
+    it('throws: unknown format', async () => {
+      const connection = await connectionCreateConnect()
+      const error = await shouldThrow(() => connection.inviteDetails(4))
+      assert.equal(error.vcxCode, VCXCode.INVALID_OPTION)
+    })
   })
 
   describe('GC:', function () {
@@ -2,7 +2,7 @@ import pytest
 import random
 from vcx.error import ErrorCode, VcxError
 from vcx.state import State
-from vcx.api.connection import Connection
+from vcx.api.connection import Connection, InviteFormat
 
 
 source_id = '123'
@@ -27,6 +27,21 @@ async def test_connection_connect():
     with pytest.raises(VcxError) as e:
         await connection.serialize()
 
+@pytest.mark.asyncio
+@pytest.mark.usefixtures('vcx_init_test_mode')
+async def test_invite_details_formats():
+    connection = await Connection.create(source_id)
+    await connection.connect(phone_number)
+    assert 'senderAgencyDetail' in await connection.invite_details()
+    assert 'dp' in (await connection.invite_details(True))['s']
+    assert 'dp' in (await connection.invite_details(InviteFormat.Abbreviated))['s']
+    assert '?c_i=' in await connection.invite_details(InviteFormat.Url)
+    assert (await connection.invite_details(InviteFormat.QrCode))['recipientKeys']
+    with pytest.raises(VcxError) as e:
+        await connection.invite_details(4)
+    assert ErrorCode.InvalidOption == e.value.error_code
+
+
 @pytest.mark.asyncio
 @pytest.mark.usefixtures('vcx_init_test_mode')
 async def test_connection_with_invite_connect():
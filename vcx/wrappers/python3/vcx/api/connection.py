@@ -5,7 +5,8 @@ proofs are exchanged using this object.
 
 TODO: document attributes
 """
-from typing import Optional
+from typing import Optional, Union
+from enum import IntEnum
 from ctypes import *
 from vcx.common import do_call, create_cb
 from vcx.api.vcx_stateful import VcxStateful
@@ -13,6 +14,18 @@ from vcx.api.vcx_stateful import VcxStateful
 import json
 
 
+class InviteFormat(IntEnum):
+    """
+    How invite_details renders the invitation.
+    Url and QrCode hold a connection invitation, which carries the VCX only "~legacy" decorator
+    that other VCX instances need to accept it through their agency. Other agents ignore it.
+    """
+    Json = 0,
+    Abbreviated = 1,
+    Url = 2,
+    QrCode = 3,
+
+
 class Connection(VcxStateful):
     """
     The basic object of the VCX API.  Represents a pairwise relationship with another identity owner.  Once the
@@ -171,28 +184,33 @@ class Connection(VcxStateful):
         """
         await self._delete()
 
-    async def invite_details(self, abbreviated: bool) -> dict:
+    async def invite_details(self, format: Union[InviteFormat, bool] = InviteFormat.Json) -> Union[dict, str]:
         """
         Get the invite details that were sent or can be sent to the endpoint.
 
-        :param abbreviated: abbreviate invite details or not
+        :param format: InviteFormat to render the details in, a bool is taken as the former abbreviated flag
         Example:
         phone_number = '8019119191'
         connection = await Connection.create('foobar123')
         invite_details = await connection.connect(phone_number)
         inivte_details_again = await connection.invite_details()
-        :return: JSON of invite_details sent to connection
+        invitation_url = await connection.invite_details(InviteFormat.Url)
+        :return: JSON of invite_details sent to connection, the invitation url for InviteFormat.Url
         """
         if not hasattr(Connection.invite_details, "cb"):
             self.logger.debug("vcx_connection_invite_details: Creating callback")
             Connection.invite_details.cb = create_cb(CFUNCTYPE(None, c_uint32, c_uint32, c_char_p))
 
         c_connection_handle = c_uint32(self.handle)
-        c_abbreviated = c_bool(abbreviated)
+        if isinstance(format, bool):
+            format = InviteFormat.Abbreviated if format else InviteFormat.Json
+        c_format = c_uint32(format)
 
         details = await do_call('vcx_connection_invite_details',
                                 c_connection_handle,
-                                c_abbreviated,
+                                c_format,
                                 Connection.invite_details.cb)
 
+        if format == InviteFormat.Url:
+            return details.decode()
         return json.loads(details.decode())
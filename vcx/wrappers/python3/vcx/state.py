@@ -15,4 +15,5 @@ class State(IntEnum):
 class ProofState(IntEnum):
     Undefined = 0,
     Verified = 1,
-    Invalid = 2
+    Invalid = 2,
+    Tampered = 3